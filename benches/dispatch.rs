@@ -0,0 +1,47 @@
+//! Instruction dispatch benchmark.
+//!
+//! Runs a tight guest loop (ALU + branch) and reports the interpreter throughput.
+//! Run with `cargo bench --bench dispatch [--features ...]`.
+use std::hint::black_box;
+use std::time::Instant;
+
+use embive::{
+    engine::{Config, Engine},
+    memory::SliceMemory,
+};
+
+/// Loop iterations (each iteration executes 4 instructions).
+const ITERATIONS: u32 = 0x0100_0000;
+
+/// Number of samples (best one is reported).
+const SAMPLES: usize = 5;
+
+fn main() {
+    let code = &[
+        0xb7, 0x02, 0x00, 0x01, // lui  t0, 0x1000   (ITERATIONS)
+        0x93, 0x82, 0xf2, 0xff, // addi t0, t0, -1
+        0x33, 0x05, 0x55, 0x00, // add  a0, a0, t0
+        0xb3, 0xc5, 0xa5, 0x00, // xor  a1, a1, a0
+        0xe3, 0x9a, 0x02, 0xfe, // bnez t0, -12
+        0x73, 0x00, 0x10, 0x00, // ebreak            (Halt)
+    ];
+
+    let mut best = f64::MAX;
+    for _ in 0..SAMPLES {
+        let mut memory = SliceMemory::new(code, &mut []);
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+
+        let start = Instant::now();
+        black_box(engine.run().unwrap());
+        best = best.min(start.elapsed().as_secs_f64());
+
+        black_box(&engine.registers);
+    }
+
+    let instructions = ITERATIONS as f64 * 4.0;
+    println!(
+        "dispatch: {:.3} s, {:.1} M instructions/s",
+        best,
+        instructions / best / 1_000_000.0
+    );
+}
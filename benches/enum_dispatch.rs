@@ -0,0 +1,740 @@
+//! Instruction dispatch design benchmark.
+//!
+//! Prototype of a RV32I subset interpreter (the instructions used by the `suite` mixes), implemented twice:
+//! - `trait`: the Embive design, an [`Instruction`] per opcode with its operands (`type Operands: From<u32>`)
+//!   and a provided `decode_execute`, dispatched with a `match` over the opcode.
+//! - `enum`: a decoded-instruction enum ([`Decoded`]) and a `match`-based executor, run three ways:
+//!     - `fused`: decode and execute inlined into the loop (the compiler can fuse both `match`es).
+//!     - `materialized`: decode out of line, so the enum is built before executing it (as tracing would).
+//!     - `cached`: the whole code decoded once and executed from the cache (as a decode cache would).
+//!
+//! Both share the registers, memory and errors ([`EmbiveError`]), and every run is checked against
+//! [`Engine`] (same registers). [`Engine`] throughput is reported as a reference.
+//! Run with `cargo bench --bench enum_dispatch`, check `benches/README.md` for the published numbers.
+use std::hint::black_box;
+use std::time::Instant;
+
+use embive::{
+    engine::{Config, Engine},
+    error::EmbiveError,
+    memory::SliceMemory,
+};
+
+/// Loop iterations (`lui t0, 0x400`).
+const ITERATIONS: u32 = 0x0040_0000;
+
+/// Number of samples (best one is reported).
+const SAMPLES: usize = 5;
+
+/// RAM address (same as Embive).
+const RAM_OFFSET: u32 = 0x8000_0000;
+
+/// Guest loop with a known instruction mix (same as `suite.rs`).
+struct Mix {
+    /// Mix name.
+    name: &'static str,
+    /// Guest code (halts with `ebreak`).
+    code: &'static [u8],
+    /// Instructions executed per loop iteration (on average).
+    per_iteration: f64,
+}
+
+const MIXES: &[Mix] = &[
+    Mix {
+        name: "alu",
+        code: &[
+            0xb7, 0x02, 0x40, 0x00, // lui  t0, 0x400     (ITERATIONS)
+            0x93, 0x82, 0xf2, 0xff, // addi t0, t0, -1
+            0x33, 0x05, 0x55, 0x00, // add  a0, a0, t0
+            0xb3, 0xc5, 0xa5, 0x00, // xor  a1, a1, a0
+            0xe3, 0x9a, 0x02, 0xfe, // bnez t0, -12
+            0x73, 0x00, 0x10, 0x00, // ebreak             (Halt)
+        ],
+        per_iteration: 4.0,
+    },
+    Mix {
+        name: "memory",
+        code: &[
+            0xb7, 0x02, 0x40, 0x00, // lui  t0, 0x400     (ITERATIONS)
+            0x37, 0x05, 0x00, 0x80, // lui  a0, 0x80000   (RAM)
+            0x83, 0x25, 0x05, 0x00, // lw   a1, 0(a0)
+            0x93, 0x85, 0x15, 0x00, // addi a1, a1, 1
+            0x23, 0x22, 0xb5, 0x00, // sw   a1, 4(a0)
+            0x93, 0x82, 0xf2, 0xff, // addi t0, t0, -1
+            0xe3, 0x98, 0x02, 0xfe, // bnez t0, -16
+            0x73, 0x00, 0x10, 0x00, // ebreak             (Halt)
+        ],
+        per_iteration: 5.0,
+    },
+    Mix {
+        name: "branch",
+        code: &[
+            0xb7, 0x02, 0x40, 0x00, // lui  t0, 0x400     (ITERATIONS)
+            0x93, 0xf5, 0x12, 0x00, // andi a1, t0, 1
+            0x63, 0x84, 0x05, 0x00, // beqz a1, 8
+            0x13, 0x06, 0x16, 0x00, // addi a2, a2, 1     (Odd iterations)
+            0x93, 0x82, 0xf2, 0xff, // addi t0, t0, -1
+            0xe3, 0x98, 0x02, 0xfe, // bnez t0, -16
+            0x73, 0x00, 0x10, 0x00, // ebreak             (Halt)
+        ],
+        per_iteration: 4.5,
+    },
+    Mix {
+        name: "call",
+        code: &[
+            0xb7, 0x02, 0x40, 0x00, // lui  t0, 0x400     (ITERATIONS)
+            0xef, 0x00, 0x00, 0x01, // jal  ra, 16
+            0x93, 0x82, 0xf2, 0xff, // addi t0, t0, -1
+            0xe3, 0x9c, 0x02, 0xfe, // bnez t0, -8
+            0x73, 0x00, 0x10, 0x00, // ebreak             (Halt)
+            0x13, 0x05, 0x15, 0x00, // addi a0, a0, 1     (Function)
+            0x67, 0x80, 0x00, 0x00, // ret
+        ],
+        per_iteration: 5.0,
+    },
+];
+
+/// Prototype interpreter state (shared by both designs).
+struct Cpu<'a> {
+    /// Registers (`x0` is written and cleared after every instruction).
+    registers: [i32; 32],
+    /// Program counter.
+    pc: u32,
+    /// Guest code.
+    code: &'a [u8],
+    /// Guest RAM.
+    ram: [u8; 16],
+}
+
+impl<'a> Cpu<'a> {
+    fn new(code: &'a [u8]) -> Self {
+        Self {
+            registers: [0; 32],
+            pc: 0,
+            code,
+            ram: [0; 16],
+        }
+    }
+
+    #[inline(always)]
+    fn fetch(&self) -> Result<u32, EmbiveError> {
+        let pc = self.pc as usize;
+        match self.code.get(pc..pc.wrapping_add(4)) {
+            Some(bytes) => Ok(u32::from_le_bytes(bytes.try_into().unwrap())),
+            None => Err(EmbiveError::InvalidMemoryAddress),
+        }
+    }
+
+    #[inline(always)]
+    fn ram(&mut self, address: u32) -> Result<&mut [u8; 4], EmbiveError> {
+        let offset = address.wrapping_sub(RAM_OFFSET) as usize;
+        match self.ram.get_mut(offset..offset.wrapping_add(4)) {
+            Some(bytes) => Ok(bytes.try_into().unwrap()),
+            None => Err(EmbiveError::InvalidMemoryAddress),
+        }
+    }
+
+    #[inline(always)]
+    fn set(&mut self, rd: usize, value: i32) {
+        self.registers[rd] = value;
+        self.registers[0] = 0;
+    }
+}
+
+/// Trait design (as in Embive).
+mod by_trait {
+    use super::{Cpu, EmbiveError};
+
+    const LUI_OPCODE: u32 = 0b011_0111;
+    const OP_IMM_OPCODE: u32 = 0b001_0011;
+    const OP_OPCODE: u32 = 0b011_0011;
+    const BRANCH_OPCODE: u32 = 0b110_0011;
+    const LOAD_OPCODE: u32 = 0b000_0011;
+    const STORE_OPCODE: u32 = 0b010_0011;
+    const JAL_OPCODE: u32 = 0b110_1111;
+    const JALR_OPCODE: u32 = 0b110_0111;
+    const SYSTEM_OPCODE: u32 = 0b111_0011;
+
+    /// Instruction trait (same shape as Embive's).
+    trait Instruction {
+        /// Decoded operands.
+        type Operands: From<u32>;
+
+        /// Execute the instruction.
+        fn execute(inst: Self::Operands, cpu: &mut Cpu) -> Result<bool, EmbiveError>;
+
+        /// Decode and execute the instruction.
+        #[inline(always)]
+        fn decode_execute(data: u32, cpu: &mut Cpu) -> Result<bool, EmbiveError> {
+            Self::execute(Self::Operands::from(data), cpu)
+        }
+    }
+
+    struct TypeU {
+        rd: usize,
+        imm: i32,
+    }
+
+    impl From<u32> for TypeU {
+        #[inline(always)]
+        fn from(inst: u32) -> Self {
+            TypeU {
+                rd: ((inst >> 7) & 0b1_1111) as usize,
+                imm: (inst & (0b1111_1111_1111_1111_1111 << 12)) as i32,
+            }
+        }
+    }
+
+    struct TypeI {
+        rd: usize,
+        rs1: usize,
+        imm: i32,
+        funct3: u8,
+    }
+
+    impl From<u32> for TypeI {
+        #[inline(always)]
+        fn from(inst: u32) -> Self {
+            TypeI {
+                rd: ((inst >> 7) & 0b1_1111) as usize,
+                funct3: ((inst >> 12) & 0b111) as u8,
+                rs1: ((inst >> 15) & 0b1_1111) as usize,
+                imm: ((inst & (0b1111_1111_1111 << 20)) as i32 >> 20),
+            }
+        }
+    }
+
+    struct TypeR {
+        rd: usize,
+        rs1: usize,
+        rs2: usize,
+        funct10: u16,
+    }
+
+    impl From<u32> for TypeR {
+        #[inline(always)]
+        fn from(inst: u32) -> Self {
+            TypeR {
+                rd: ((inst >> 7) & 0b1_1111) as usize,
+                rs1: ((inst >> 15) & 0b1_1111) as usize,
+                rs2: ((inst >> 20) & 0b1_1111) as usize,
+                funct10: (((inst >> 22) & (0b111_1111 << 3)) | ((inst >> 12) & 0b111)) as u16,
+            }
+        }
+    }
+
+    struct TypeS {
+        rs1: usize,
+        rs2: usize,
+        imm: i32,
+        funct3: u8,
+    }
+
+    impl From<u32> for TypeS {
+        #[inline(always)]
+        fn from(inst: u32) -> Self {
+            TypeS {
+                imm: ((inst & (0b111_1111 << 25)) | ((inst & (0b1_1111 << 7)) << 13)) as i32 >> 20,
+                funct3: ((inst >> 12) & 0b111) as u8,
+                rs1: ((inst >> 15) & 0b1_1111) as usize,
+                rs2: ((inst >> 20) & 0b1_1111) as usize,
+            }
+        }
+    }
+
+    struct TypeB {
+        rs1: usize,
+        rs2: usize,
+        imm: i32,
+        funct3: u8,
+    }
+
+    impl From<u32> for TypeB {
+        #[inline(always)]
+        fn from(inst: u32) -> Self {
+            TypeB {
+                imm: ((inst & (0b1 << 31))
+                    | ((inst & (0b1 << 7)) << 23)
+                    | ((inst & (0b11_1111 << 25)) >> 1)
+                    | ((inst & (0b1111 << 8)) << 12)) as i32
+                    >> 19,
+                funct3: ((inst >> 12) & 0b111) as u8,
+                rs1: ((inst >> 15) & 0b1_1111) as usize,
+                rs2: ((inst >> 20) & 0b1_1111) as usize,
+            }
+        }
+    }
+
+    struct TypeJ {
+        rd: usize,
+        imm: i32,
+    }
+
+    impl From<u32> for TypeJ {
+        #[inline(always)]
+        fn from(inst: u32) -> Self {
+            TypeJ {
+                rd: ((inst >> 7) & 0b1_1111) as usize,
+                imm: ((inst & (0b1 << 31))
+                    | ((inst & (0b1111_1111 << 12)) << 11)
+                    | ((inst & (0b1 << 20)) << 2)
+                    | ((inst & (0b11_1111_1111 << 21)) >> 9)) as i32
+                    >> 11,
+            }
+        }
+    }
+
+    struct Lui;
+
+    impl Instruction for Lui {
+        type Operands = TypeU;
+
+        #[inline(always)]
+        fn execute(inst: TypeU, cpu: &mut Cpu) -> Result<bool, EmbiveError> {
+            cpu.set(inst.rd, inst.imm);
+            cpu.pc = cpu.pc.wrapping_add(4);
+            Ok(true)
+        }
+    }
+
+    struct OpImm;
+
+    impl Instruction for OpImm {
+        type Operands = TypeI;
+
+        #[inline(always)]
+        fn execute(inst: TypeI, cpu: &mut Cpu) -> Result<bool, EmbiveError> {
+            let rs1 = cpu.registers[inst.rs1];
+            let value = match inst.funct3 {
+                0b000 => rs1.wrapping_add(inst.imm), // Addi
+                0b100 => rs1 ^ inst.imm,             // Xori
+                0b111 => rs1 & inst.imm,             // Andi
+                _ => return Err(EmbiveError::InvalidInstruction),
+            };
+
+            cpu.set(inst.rd, value);
+            cpu.pc = cpu.pc.wrapping_add(4);
+            Ok(true)
+        }
+    }
+
+    struct Op;
+
+    impl Instruction for Op {
+        type Operands = TypeR;
+
+        #[inline(always)]
+        fn execute(inst: TypeR, cpu: &mut Cpu) -> Result<bool, EmbiveError> {
+            let rs1 = cpu.registers[inst.rs1];
+            let rs2 = cpu.registers[inst.rs2];
+            let value = match inst.funct10 {
+                0b00_0000_0000 => rs1.wrapping_add(rs2), // Add
+                0b01_0000_0000 => rs1.wrapping_sub(rs2), // Sub
+                0b00_0000_0100 => rs1 ^ rs2,             // Xor
+                _ => return Err(EmbiveError::InvalidInstruction),
+            };
+
+            cpu.set(inst.rd, value);
+            cpu.pc = cpu.pc.wrapping_add(4);
+            Ok(true)
+        }
+    }
+
+    struct Branch;
+
+    impl Instruction for Branch {
+        type Operands = TypeB;
+
+        #[inline(always)]
+        fn execute(inst: TypeB, cpu: &mut Cpu) -> Result<bool, EmbiveError> {
+            let rs1 = cpu.registers[inst.rs1];
+            let rs2 = cpu.registers[inst.rs2];
+            let taken = match inst.funct3 {
+                0b000 => rs1 == rs2, // Beq
+                0b001 => rs1 != rs2, // Bne
+                _ => return Err(EmbiveError::InvalidInstruction),
+            };
+
+            cpu.pc = cpu.pc.wrapping_add(if taken { inst.imm as u32 } else { 4 });
+            Ok(true)
+        }
+    }
+
+    struct Load;
+
+    impl Instruction for Load {
+        type Operands = TypeI;
+
+        #[inline(always)]
+        fn execute(inst: TypeI, cpu: &mut Cpu) -> Result<bool, EmbiveError> {
+            if inst.funct3 != 0b010 {
+                return Err(EmbiveError::InvalidInstruction);
+            }
+
+            let address = (cpu.registers[inst.rs1] as u32).wrapping_add(inst.imm as u32);
+            let value = i32::from_le_bytes(*cpu.ram(address)?); // Lw
+            cpu.set(inst.rd, value);
+            cpu.pc = cpu.pc.wrapping_add(4);
+            Ok(true)
+        }
+    }
+
+    struct Store;
+
+    impl Instruction for Store {
+        type Operands = TypeS;
+
+        #[inline(always)]
+        fn execute(inst: TypeS, cpu: &mut Cpu) -> Result<bool, EmbiveError> {
+            if inst.funct3 != 0b010 {
+                return Err(EmbiveError::InvalidInstruction);
+            }
+
+            let address = (cpu.registers[inst.rs1] as u32).wrapping_add(inst.imm as u32);
+            let value = cpu.registers[inst.rs2];
+            *cpu.ram(address)? = value.to_le_bytes(); // Sw
+            cpu.pc = cpu.pc.wrapping_add(4);
+            Ok(true)
+        }
+    }
+
+    struct Jal;
+
+    impl Instruction for Jal {
+        type Operands = TypeJ;
+
+        #[inline(always)]
+        fn execute(inst: TypeJ, cpu: &mut Cpu) -> Result<bool, EmbiveError> {
+            cpu.set(inst.rd, cpu.pc.wrapping_add(4) as i32);
+            cpu.pc = cpu.pc.wrapping_add(inst.imm as u32);
+            Ok(true)
+        }
+    }
+
+    struct Jalr;
+
+    impl Instruction for Jalr {
+        type Operands = TypeI;
+
+        #[inline(always)]
+        fn execute(inst: TypeI, cpu: &mut Cpu) -> Result<bool, EmbiveError> {
+            let target = (cpu.registers[inst.rs1] as u32).wrapping_add(inst.imm as u32) & !1;
+            cpu.set(inst.rd, cpu.pc.wrapping_add(4) as i32);
+            cpu.pc = target;
+            Ok(true)
+        }
+    }
+
+    struct System;
+
+    impl Instruction for System {
+        type Operands = u32;
+
+        #[inline(always)]
+        fn execute(inst: u32, cpu: &mut Cpu) -> Result<bool, EmbiveError> {
+            match inst {
+                0x0010_0073 => {
+                    // Ebreak
+                    cpu.pc = cpu.pc.wrapping_add(4);
+                    Ok(false)
+                }
+                _ => Err(EmbiveError::InvalidInstruction),
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn decode_execute(data: u32, cpu: &mut Cpu) -> Result<bool, EmbiveError> {
+        match data & 0b111_1111 {
+            LUI_OPCODE => Lui::decode_execute(data, cpu),
+            OP_IMM_OPCODE => OpImm::decode_execute(data, cpu),
+            OP_OPCODE => Op::decode_execute(data, cpu),
+            BRANCH_OPCODE => Branch::decode_execute(data, cpu),
+            LOAD_OPCODE => Load::decode_execute(data, cpu),
+            STORE_OPCODE => Store::decode_execute(data, cpu),
+            JAL_OPCODE => Jal::decode_execute(data, cpu),
+            JALR_OPCODE => Jalr::decode_execute(data, cpu),
+            SYSTEM_OPCODE => System::decode_execute(data, cpu),
+            _ => Err(EmbiveError::InvalidInstruction),
+        }
+    }
+
+    /// Run until the guest halts.
+    pub fn run(cpu: &mut Cpu) -> Result<(), EmbiveError> {
+        loop {
+            let data = cpu.fetch()?;
+            if !decode_execute(data, cpu)? {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Decoded-instruction enum design.
+mod by_enum {
+    use super::{Cpu, EmbiveError};
+
+    /// Decoded instruction (one variant per operation).
+    #[derive(Clone, Copy)]
+    pub enum Decoded {
+        Lui { rd: u8, imm: i32 },
+        Addi { rd: u8, rs1: u8, imm: i32 },
+        Xori { rd: u8, rs1: u8, imm: i32 },
+        Andi { rd: u8, rs1: u8, imm: i32 },
+        Add { rd: u8, rs1: u8, rs2: u8 },
+        Sub { rd: u8, rs1: u8, rs2: u8 },
+        Xor { rd: u8, rs1: u8, rs2: u8 },
+        Beq { rs1: u8, rs2: u8, imm: i32 },
+        Bne { rs1: u8, rs2: u8, imm: i32 },
+        Lw { rd: u8, rs1: u8, imm: i32 },
+        Sw { rs1: u8, rs2: u8, imm: i32 },
+        Jal { rd: u8, imm: i32 },
+        Jalr { rd: u8, rs1: u8, imm: i32 },
+        Ebreak,
+        Invalid,
+    }
+
+    /// Decode an instruction.
+    #[inline(always)]
+    pub fn decode(inst: u32) -> Decoded {
+        let rd = ((inst >> 7) & 0b1_1111) as u8;
+        let rs1 = ((inst >> 15) & 0b1_1111) as u8;
+        let rs2 = ((inst >> 20) & 0b1_1111) as u8;
+        let funct3 = (inst >> 12) & 0b111;
+        let imm_i = (inst & (0b1111_1111_1111 << 20)) as i32 >> 20;
+
+        match inst & 0b111_1111 {
+            0b011_0111 => Decoded::Lui {
+                rd,
+                imm: (inst & (0b1111_1111_1111_1111_1111 << 12)) as i32,
+            },
+            0b001_0011 => match funct3 {
+                0b000 => Decoded::Addi {
+                    rd,
+                    rs1,
+                    imm: imm_i,
+                },
+                0b100 => Decoded::Xori {
+                    rd,
+                    rs1,
+                    imm: imm_i,
+                },
+                0b111 => Decoded::Andi {
+                    rd,
+                    rs1,
+                    imm: imm_i,
+                },
+                _ => Decoded::Invalid,
+            },
+            0b011_0011 => match (inst >> 25, funct3) {
+                (0b000_0000, 0b000) => Decoded::Add { rd, rs1, rs2 },
+                (0b010_0000, 0b000) => Decoded::Sub { rd, rs1, rs2 },
+                (0b000_0000, 0b100) => Decoded::Xor { rd, rs1, rs2 },
+                _ => Decoded::Invalid,
+            },
+            0b110_0011 => {
+                let imm = ((inst & (0b1 << 31))
+                    | ((inst & (0b1 << 7)) << 23)
+                    | ((inst & (0b11_1111 << 25)) >> 1)
+                    | ((inst & (0b1111 << 8)) << 12)) as i32
+                    >> 19;
+                match funct3 {
+                    0b000 => Decoded::Beq { rs1, rs2, imm },
+                    0b001 => Decoded::Bne { rs1, rs2, imm },
+                    _ => Decoded::Invalid,
+                }
+            }
+            0b000_0011 if funct3 == 0b010 => Decoded::Lw {
+                rd,
+                rs1,
+                imm: imm_i,
+            },
+            0b010_0011 if funct3 == 0b010 => Decoded::Sw {
+                rs1,
+                rs2,
+                imm: ((inst & (0b111_1111 << 25)) | ((inst & (0b1_1111 << 7)) << 13)) as i32 >> 20,
+            },
+            0b110_1111 => Decoded::Jal {
+                rd,
+                imm: ((inst & (0b1 << 31))
+                    | ((inst & (0b1111_1111 << 12)) << 11)
+                    | ((inst & (0b1 << 20)) << 2)
+                    | ((inst & (0b11_1111_1111 << 21)) >> 9)) as i32
+                    >> 11,
+            },
+            0b110_0111 => Decoded::Jalr {
+                rd,
+                rs1,
+                imm: imm_i,
+            },
+            0b111_0011 if inst == 0x0010_0073 => Decoded::Ebreak,
+            _ => Decoded::Invalid,
+        }
+    }
+
+    /// Decode an instruction, out of line (the enum is materialized).
+    #[inline(never)]
+    fn decode_outlined(inst: u32) -> Decoded {
+        decode(inst)
+    }
+
+    /// Execute a decoded instruction.
+    #[inline(always)]
+    fn execute(inst: Decoded, cpu: &mut Cpu) -> Result<bool, EmbiveError> {
+        let reg = |cpu: &Cpu, r: u8| cpu.registers[r as usize];
+        let next = cpu.pc.wrapping_add(4);
+
+        match inst {
+            Decoded::Lui { rd, imm } => cpu.set(rd as usize, imm),
+            Decoded::Addi { rd, rs1, imm } => cpu.set(rd as usize, reg(cpu, rs1).wrapping_add(imm)),
+            Decoded::Xori { rd, rs1, imm } => cpu.set(rd as usize, reg(cpu, rs1) ^ imm),
+            Decoded::Andi { rd, rs1, imm } => cpu.set(rd as usize, reg(cpu, rs1) & imm),
+            Decoded::Add { rd, rs1, rs2 } => {
+                cpu.set(rd as usize, reg(cpu, rs1).wrapping_add(reg(cpu, rs2)))
+            }
+            Decoded::Sub { rd, rs1, rs2 } => {
+                cpu.set(rd as usize, reg(cpu, rs1).wrapping_sub(reg(cpu, rs2)))
+            }
+            Decoded::Xor { rd, rs1, rs2 } => cpu.set(rd as usize, reg(cpu, rs1) ^ reg(cpu, rs2)),
+            Decoded::Beq { rs1, rs2, imm } => {
+                let taken = reg(cpu, rs1) == reg(cpu, rs2);
+                cpu.pc = cpu.pc.wrapping_add(if taken { imm as u32 } else { 4 });
+                return Ok(true);
+            }
+            Decoded::Bne { rs1, rs2, imm } => {
+                let taken = reg(cpu, rs1) != reg(cpu, rs2);
+                cpu.pc = cpu.pc.wrapping_add(if taken { imm as u32 } else { 4 });
+                return Ok(true);
+            }
+            Decoded::Lw { rd, rs1, imm } => {
+                let address = (reg(cpu, rs1) as u32).wrapping_add(imm as u32);
+                let value = i32::from_le_bytes(*cpu.ram(address)?);
+                cpu.set(rd as usize, value);
+            }
+            Decoded::Sw { rs1, rs2, imm } => {
+                let address = (reg(cpu, rs1) as u32).wrapping_add(imm as u32);
+                let value = reg(cpu, rs2);
+                *cpu.ram(address)? = value.to_le_bytes();
+            }
+            Decoded::Jal { rd, imm } => {
+                cpu.set(rd as usize, next as i32);
+                cpu.pc = cpu.pc.wrapping_add(imm as u32);
+                return Ok(true);
+            }
+            Decoded::Jalr { rd, rs1, imm } => {
+                let target = (reg(cpu, rs1) as u32).wrapping_add(imm as u32) & !1;
+                cpu.set(rd as usize, next as i32);
+                cpu.pc = target;
+                return Ok(true);
+            }
+            Decoded::Ebreak => {
+                cpu.pc = next;
+                return Ok(false);
+            }
+            Decoded::Invalid => return Err(EmbiveError::InvalidInstruction),
+        }
+
+        cpu.pc = next;
+        Ok(true)
+    }
+
+    /// Run until the guest halts, decoding inline.
+    pub fn run_fused(cpu: &mut Cpu) -> Result<(), EmbiveError> {
+        loop {
+            let inst = decode(cpu.fetch()?);
+            if !execute(inst, cpu)? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Run until the guest halts, decoding out of line.
+    pub fn run_materialized(cpu: &mut Cpu) -> Result<(), EmbiveError> {
+        loop {
+            let inst = decode_outlined(cpu.fetch()?);
+            if !execute(inst, cpu)? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Run until the guest halts, from a decoded code cache.
+    pub fn run_cached(cpu: &mut Cpu, cache: &[Decoded]) -> Result<(), EmbiveError> {
+        loop {
+            let inst = match cache.get((cpu.pc >> 2) as usize) {
+                Some(inst) if cpu.pc & 0b11 == 0 => *inst,
+                _ => return Err(EmbiveError::InvalidMemoryAddress),
+            };
+
+            if !execute(inst, cpu)? {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Time a run, checking the final registers against the Embive engine.
+///
+/// Arguments:
+/// - `mix`: Guest loop.
+/// - `expected`: Registers after an [`Engine`] run.
+/// - `run`: Runs the prototype until the guest halts.
+///
+/// Returns:
+/// - `f64`: Best elapsed time, in seconds.
+fn time(mix: &Mix, expected: &[i32; 32], run: impl Fn(&mut Cpu) -> Result<(), EmbiveError>) -> f64 {
+    let mut best = f64::MAX;
+    for _ in 0..SAMPLES {
+        let mut cpu = Cpu::new(mix.code);
+
+        let start = Instant::now();
+        black_box(run(&mut cpu)).unwrap();
+        best = best.min(start.elapsed().as_secs_f64());
+
+        assert_eq!(&cpu.registers, expected, "{}: registers differ", mix.name);
+    }
+
+    best
+}
+
+fn main() {
+    println!("M instructions/s: engine, trait, enum fused, enum materialized, enum cached");
+    for mix in MIXES {
+        let mut expected = [0; 32];
+        let mut engine_best = f64::MAX;
+        for _ in 0..SAMPLES {
+            let mut ram = [0; 16];
+            let mut memory = SliceMemory::new(mix.code, &mut ram);
+            let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+
+            let start = Instant::now();
+            assert!(!black_box(engine.run().unwrap()));
+            engine_best = engine_best.min(start.elapsed().as_secs_f64());
+
+            for (i, value) in expected.iter_mut().enumerate() {
+                *value = engine.registers.get(i).unwrap();
+            }
+        }
+
+        let cache: Vec<by_enum::Decoded> = mix
+            .code
+            .chunks_exact(4)
+            .map(|bytes| by_enum::decode(u32::from_le_bytes(bytes.try_into().unwrap())))
+            .collect();
+
+        let results = [
+            engine_best,
+            time(mix, &expected, by_trait::run),
+            time(mix, &expected, by_enum::run_fused),
+            time(mix, &expected, by_enum::run_materialized),
+            time(mix, &expected, |cpu| by_enum::run_cached(cpu, &cache)),
+        ];
+
+        let instructions = ITERATIONS as f64 * mix.per_iteration;
+        print!("{:>8}:", mix.name);
+        for elapsed in results {
+            print!(" {:>6.1}", instructions / elapsed / 1_000_000.0);
+        }
+        println!();
+    }
+}
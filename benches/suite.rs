@@ -0,0 +1,242 @@
+//! Instruction mix benchmark suite.
+//!
+//! Runs guest loops with known instruction mixes (ALU, memory, branches, calls and M extension)
+//! and reports the interpreter throughput of each one, to measure hot path optimizations.
+//! Mixes run on a [`SliceMemory`], a [`PagedMemory`] and an instrumented memory (non-trivial memories,
+//! check the `fetch_batch` feature).
+//! Run with `cargo bench --bench suite [--features ...]`, check `benches/README.md` for the published numbers.
+use std::hint::black_box;
+use std::time::Instant;
+
+use embive::{
+    engine::{Config, Engine},
+    error::EmbiveError,
+    memory::{Memory, PagedMemory, SliceMemory},
+};
+
+/// Loop iterations (`lui t0, 0x400`).
+const ITERATIONS: u32 = 0x0040_0000;
+
+/// Number of samples (best one is reported).
+const SAMPLES: usize = 5;
+
+/// Guest loop with a known instruction mix.
+struct Mix {
+    /// Mix name.
+    name: &'static str,
+    /// Guest code (halts with `ebreak`).
+    code: &'static [u8],
+    /// Instructions executed per loop iteration (on average).
+    per_iteration: f64,
+}
+
+/// Integer arithmetic and a loop branch.
+const ALU: Mix = Mix {
+    name: "alu",
+    code: &[
+        0xb7, 0x02, 0x40, 0x00, // lui  t0, 0x400     (ITERATIONS)
+        0x93, 0x82, 0xf2, 0xff, // addi t0, t0, -1
+        0x33, 0x05, 0x55, 0x00, // add  a0, a0, t0
+        0xb3, 0xc5, 0xa5, 0x00, // xor  a1, a1, a0
+        0xe3, 0x9a, 0x02, 0xfe, // bnez t0, -12
+        0x73, 0x00, 0x10, 0x00, // ebreak             (Halt)
+    ],
+    per_iteration: 4.0,
+};
+
+/// Long straight-line integer arithmetic (check the `fetch_batch` feature).
+const STRAIGHT: Mix = Mix {
+    name: "straight",
+    code: &[
+        0xb7, 0x02, 0x40, 0x00, // lui  t0, 0x400     (ITERATIONS)
+        0x33, 0x05, 0x55, 0x00, // add  a0, a0, t0
+        0xb3, 0xc5, 0xa5, 0x00, // xor  a1, a1, a0
+        0x33, 0x06, 0xb6, 0x00, // add  a2, a2, a1
+        0xb3, 0xc6, 0xc6, 0x00, // xor  a3, a3, a2
+        0x33, 0x07, 0xd7, 0x00, // add  a4, a4, a3
+        0xb3, 0xc7, 0xe7, 0x00, // xor  a5, a5, a4
+        0x33, 0x08, 0xf8, 0x00, // add  a6, a6, a5
+        0xb3, 0xc8, 0x08, 0x01, // xor  a7, a7, a6
+        0x33, 0x05, 0x15, 0x01, // add  a0, a0, a7
+        0xb3, 0xc5, 0xa5, 0x00, // xor  a1, a1, a0
+        0x33, 0x06, 0xb6, 0x00, // add  a2, a2, a1
+        0xb3, 0xc6, 0xc6, 0x00, // xor  a3, a3, a2
+        0x33, 0x07, 0xd7, 0x00, // add  a4, a4, a3
+        0xb3, 0xc7, 0xe7, 0x00, // xor  a5, a5, a4
+        0x93, 0x82, 0xf2, 0xff, // addi t0, t0, -1
+        0xe3, 0x92, 0x02, 0xfc, // bnez t0, -60
+        0x73, 0x00, 0x10, 0x00, // ebreak             (Halt)
+    ],
+    per_iteration: 16.0,
+};
+
+/// RAM loads and stores.
+const MEMORY: Mix = Mix {
+    name: "memory",
+    code: &[
+        0xb7, 0x02, 0x40, 0x00, // lui  t0, 0x400     (ITERATIONS)
+        0x37, 0x05, 0x00, 0x80, // lui  a0, 0x80000   (RAM)
+        0x83, 0x25, 0x05, 0x00, // lw   a1, 0(a0)
+        0x93, 0x85, 0x15, 0x00, // addi a1, a1, 1
+        0x23, 0x22, 0xb5, 0x00, // sw   a1, 4(a0)
+        0x93, 0x82, 0xf2, 0xff, // addi t0, t0, -1
+        0xe3, 0x98, 0x02, 0xfe, // bnez t0, -16
+        0x73, 0x00, 0x10, 0x00, // ebreak             (Halt)
+    ],
+    per_iteration: 5.0,
+};
+
+/// Branches taken every other iteration.
+const BRANCH: Mix = Mix {
+    name: "branch",
+    code: &[
+        0xb7, 0x02, 0x40, 0x00, // lui  t0, 0x400     (ITERATIONS)
+        0x93, 0xf5, 0x12, 0x00, // andi a1, t0, 1
+        0x63, 0x84, 0x05, 0x00, // beqz a1, 8
+        0x13, 0x06, 0x16, 0x00, // addi a2, a2, 1     (Odd iterations)
+        0x93, 0x82, 0xf2, 0xff, // addi t0, t0, -1
+        0xe3, 0x98, 0x02, 0xfe, // bnez t0, -16
+        0x73, 0x00, 0x10, 0x00, // ebreak             (Halt)
+    ],
+    per_iteration: 4.5,
+};
+
+/// Function calls and returns.
+const CALL: Mix = Mix {
+    name: "call",
+    code: &[
+        0xb7, 0x02, 0x40, 0x00, // lui  t0, 0x400     (ITERATIONS)
+        0xef, 0x00, 0x00, 0x01, // jal  ra, 16
+        0x93, 0x82, 0xf2, 0xff, // addi t0, t0, -1
+        0xe3, 0x9c, 0x02, 0xfe, // bnez t0, -8
+        0x73, 0x00, 0x10, 0x00, // ebreak             (Halt)
+        0x13, 0x05, 0x15, 0x00, // addi a0, a0, 1     (Function)
+        0x67, 0x80, 0x00, 0x00, // ret
+    ],
+    per_iteration: 5.0,
+};
+
+/// Multiplications and divisions (M extension).
+#[cfg(feature = "m_extension")]
+const MULDIV: Mix = Mix {
+    name: "muldiv",
+    code: &[
+        0xb7, 0x02, 0x40, 0x00, // lui  t0, 0x400     (ITERATIONS)
+        0xb7, 0x35, 0x00, 0x00, // lui  a1, 3
+        0x93, 0x85, 0x95, 0x03, // addi a1, a1, 57
+        0x33, 0x86, 0xb2, 0x02, // mul   a2, t0, a1
+        0xb3, 0x96, 0xb2, 0x02, // mulh  a3, t0, a1
+        0x33, 0xd7, 0x55, 0x02, // divu  a4, a1, t0
+        0xb3, 0xf7, 0x55, 0x02, // remu  a5, a1, t0
+        0x93, 0x82, 0xf2, 0xff, // addi t0, t0, -1
+        0xe3, 0x96, 0x02, 0xfe, // bnez t0, -20
+        0x73, 0x00, 0x10, 0x00, // ebreak             (Halt)
+    ],
+    per_iteration: 6.0,
+};
+
+/// Instrumented memory, counts loads through out-of-line calls (ex.: tracing or a memory driver).
+struct CountingMemory<'a> {
+    /// Inner memory.
+    inner: SliceMemory<'a>,
+    /// Load calls.
+    loads: core::cell::Cell<u64>,
+}
+
+impl Memory for CountingMemory<'_> {
+    #[inline(never)]
+    fn load<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+        self.loads.set(self.loads.get() + 1);
+        self.inner.load(address)
+    }
+
+    #[inline(never)]
+    fn store<const N: usize>(&mut self, address: u32, data: [u8; N]) -> Result<(), EmbiveError> {
+        self.inner.store(address, data)
+    }
+}
+
+/// Memory implementation and configuration of a run.
+#[derive(Clone, Copy)]
+enum Setup {
+    /// [`SliceMemory`].
+    Slice,
+    /// [`PagedMemory`].
+    Paged,
+    /// [`CountingMemory`].
+    Counting,
+}
+
+/// Run a guest until it halts.
+///
+/// Arguments:
+/// - `memory`: Guest memory.
+/// - `config`: Engine configuration.
+///
+/// Returns:
+/// - `f64`: Elapsed time, in seconds.
+fn time<M: Memory>(memory: &mut M, config: Config<M>) -> f64 {
+    let mut engine = Engine::new(memory, config).unwrap();
+
+    let start = Instant::now();
+    assert!(!black_box(engine.run().unwrap()));
+    let elapsed = start.elapsed().as_secs_f64();
+
+    black_box(&engine.registers);
+    elapsed
+}
+
+/// Run a mix and report its throughput.
+///
+/// Arguments:
+/// - `mix`: Guest loop.
+/// - `setup`: Memory implementation and configuration.
+fn bench(mix: &Mix, setup: Setup) {
+    let mut best = f64::MAX;
+    for _ in 0..SAMPLES {
+        let mut ram = [0; 16];
+        let elapsed = match setup {
+            Setup::Slice => time(&mut SliceMemory::new(mix.code, &mut ram), Config::default()),
+            Setup::Paged => {
+                let mut memory = PagedMemory::<1, 16>::new(mix.code);
+                memory.map(0, &mut ram).unwrap();
+                time(&mut memory, Config::default())
+            }
+            Setup::Counting => {
+                let mut memory = CountingMemory {
+                    inner: SliceMemory::new(mix.code, &mut ram),
+                    loads: Default::default(),
+                };
+                let elapsed = time(&mut memory, Config::default());
+                black_box(memory.loads.get());
+                elapsed
+            }
+        };
+        best = best.min(elapsed);
+    }
+
+    let instructions = ITERATIONS as f64 * mix.per_iteration;
+    println!(
+        "{:<8} {:.3} s, {:.1} M instructions/s",
+        mix.name,
+        best,
+        instructions / best / 1_000_000.0
+    );
+}
+
+fn main() {
+    for (name, setup) in [
+        ("SliceMemory", Setup::Slice),
+        ("PagedMemory", Setup::Paged),
+        ("CountingMemory", Setup::Counting),
+    ] {
+        println!("{name}:");
+        bench(&ALU, setup);
+        bench(&STRAIGHT, setup);
+        bench(&MEMORY, setup);
+        bench(&BRANCH, setup);
+        bench(&CALL, setup);
+        #[cfg(feature = "m_extension")]
+        bench(&MULDIV, setup);
+    }
+}
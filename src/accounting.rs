@@ -0,0 +1,433 @@
+//! Accounting Module
+//!
+//! Tracks guest execution separately from host work done on behalf of the guest,
+//! so multi-tenant hosts can tell "guest burned CPU" from "host I/O for the guest":
+//! - Guest instructions executed by the engine.
+//! - Ticks spent inside the syscall function, measured with the host-supplied
+//!   [`crate::engine::Config::tick_fn`] (ex.: a cycle counter or a timer).
+//! - Per-syscall-number calls, errors and latency ([`SyscallStats`]), to find the host services
+//!   guests use the most.
+//!
+//! Syscall time can be limited with [`crate::engine::Config::syscall_tick_quota`]:
+//! when a syscall exceeds it, execution stops with [`crate::error::EmbiveError::QuotaExceeded`]
+//! (after the syscall completed, so running again resumes the guest).
+//!
+//! Counters are 64-bit and saturate (they don't wrap on always-on guests). The guest can read them with the
+//! counter syscall ([`crate::engine::Config::counter_nr`]):
+//! - `a0`: Counter ([`COUNTER_GUEST_INSTRUCTIONS`], [`COUNTER_SYSCALLS`] or [`COUNTER_SYSCALL_TICKS`]).
+//!
+//! Returns `0` in `a0` and the counter in `a1` (low word) and `a2` (high word),
+//! or [`crate::syscall::Errno::InvalidArgument`] for unknown counters.
+//!
+//! Exact counters are a side channel for untrusted guests, so the guest reads them through a [`CounterView`]
+//! ([`crate::engine::Engine::counter_view`]), controlled by the host. Host-side accounting is always precise:
+//! - Inhibit (freeze) counters, like `mcountinhibit`: they resume from the frozen value, hiding the inhibited interval.
+//! - Scale counters down, reducing their resolution.
+//! - Fuzz the low bits of counters.
+//!
+//! Accounting isn't cleared by [`crate::engine::Engine::reset`], take it at the end of each accounting period:
+//!
+//! ```
+//! use embive::{engine::{Config, Engine}, memory::SliceMemory};
+//!
+//! let code = &[0x73, 0x00, 0x10, 0x00]; // ebreak
+//! let mut memory = SliceMemory::new(code, &mut []);
+//! let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+//! engine.run().unwrap();
+//!
+//! let usage = core::mem::take(&mut engine.accounting);
+//! assert_eq!(usage.guest_instructions, 1);
+//! assert_eq!(engine.accounting.guest_instructions, 0);
+//! ```
+
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+pub use crate::engine::TickFn;
+
+/// Counter: Guest instructions executed ([`Accounting::guest_instructions`]).
+pub const COUNTER_GUEST_INSTRUCTIONS: i32 = 0;
+/// Counter: Syscalls handled by the syscall function ([`Accounting::syscalls`]).
+pub const COUNTER_SYSCALLS: i32 = 1;
+/// Counter: Ticks spent inside the syscall function ([`Accounting::syscall_ticks`]).
+pub const COUNTER_SYSCALL_TICKS: i32 = 2;
+
+/// Number of counters (check [`Accounting::counter`]).
+pub const COUNTERS: usize = 3;
+
+/// Syscall numbers tracked individually by [`SyscallStats`] (others are aggregated).
+pub const SYSCALL_STATS_ENTRIES: usize = 16;
+
+/// Execution accounting (check the [module documentation](self)).
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct Accounting {
+    /// Guest instructions executed.
+    pub guest_instructions: u64,
+    /// Syscalls handled by the syscall function.
+    pub syscalls: u64,
+    /// Ticks spent inside the syscall function (0 without a tick function).
+    pub syscall_ticks: u64,
+    /// Per-syscall-number statistics.
+    pub syscall_stats: SyscallStats,
+    /// Deepest run of the engine (1 = Never nested, check [`crate::engine::run_depth`]).
+    pub peak_run_depth: u32,
+}
+
+impl Accounting {
+    /// Read a counter (check the [module documentation](self)).
+    ///
+    /// Arguments:
+    /// - `counter`: Counter (ex.: [`COUNTER_GUEST_INSTRUCTIONS`]).
+    ///
+    /// Returns:
+    /// - `Some(u64)`: Counter value.
+    /// - `None`: Unknown counter.
+    pub fn counter(&self, counter: i32) -> Option<u64> {
+        match counter {
+            COUNTER_GUEST_INSTRUCTIONS => Some(self.guest_instructions),
+            COUNTER_SYSCALLS => Some(self.syscalls),
+            COUNTER_SYSCALL_TICKS => Some(self.syscall_ticks),
+            _ => None,
+        }
+    }
+}
+
+/// Guest view of the counters (check the [module documentation](self)).
+///
+/// ```
+/// use embive::accounting::{Accounting, CounterView, COUNTER_GUEST_INSTRUCTIONS};
+///
+/// let mut accounting = Accounting::default();
+/// let mut view = CounterView::default();
+///
+/// accounting.guest_instructions = 100;
+/// view.inhibit(1 << COUNTER_GUEST_INSTRUCTIONS, &accounting);
+/// accounting.guest_instructions = 150; // Host-side accounting stays precise
+/// assert_eq!(view.read(COUNTER_GUEST_INSTRUCTIONS, &accounting), Some(100));
+///
+/// view.inhibit(0, &accounting);
+/// accounting.guest_instructions = 160;
+/// assert_eq!(view.read(COUNTER_GUEST_INSTRUCTIONS, &accounting), Some(110));
+/// ```
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct CounterView {
+    /// Inhibited counters (bit `n` = counter `n`).
+    inhibited: u32,
+    /// Frozen values of the inhibited counters.
+    frozen: [u64; COUNTERS],
+    /// Hidden (inhibited) part of each counter.
+    offsets: [u64; COUNTERS],
+    /// Counters are divided by `2^shift`.
+    shift: u8,
+    /// Low bits randomized.
+    fuzz_bits: u8,
+    /// Fuzzing generator state (SplitMix64).
+    fuzz_state: u64,
+}
+
+impl CounterView {
+    /// Inhibit (freeze) counters, replacing the inhibited set (like writing `mcountinhibit`).
+    ///
+    /// Arguments:
+    /// - `mask`: Counters to inhibit (bit `n` = counter `n`, 0 = None).
+    /// - `accounting`: Current (precise) accounting.
+    pub fn inhibit(&mut self, mask: u32, accounting: &Accounting) {
+        for counter in 0..COUNTERS {
+            let bit = 1 << counter;
+            // Unwrap is safe because every counter below COUNTERS exists.
+            let precise = accounting.counter(counter as i32).unwrap();
+            match (self.inhibited & bit != 0, mask & bit != 0) {
+                (false, true) => {
+                    self.frozen[counter] = precise.saturating_sub(self.offsets[counter])
+                }
+                (true, false) => {
+                    self.offsets[counter] = precise.saturating_sub(self.frozen[counter])
+                }
+                _ => {}
+            }
+        }
+
+        self.inhibited = mask;
+    }
+
+    /// Inhibited counters (bit `n` = counter `n`).
+    pub fn inhibited(&self) -> u32 {
+        self.inhibited
+    }
+
+    /// Scale counters down, dividing them by `2^shift` (0 = Exact).
+    ///
+    /// Arguments:
+    /// - `shift`: Scale (clamped to 63).
+    pub fn set_scale(&mut self, shift: u8) {
+        self.shift = shift.min(63);
+    }
+
+    /// Randomize the low bits of counters (after scaling).
+    ///
+    /// Arguments:
+    /// - `bits`: Randomized low bits (0 = No fuzzing, clamped to 64).
+    /// - `seed`: Generator seed.
+    pub fn set_fuzz(&mut self, bits: u8, seed: u64) {
+        self.fuzz_bits = bits.min(64);
+        self.fuzz_state = seed;
+    }
+
+    /// Read a counter as seen by the guest.
+    ///
+    /// Arguments:
+    /// - `counter`: Counter (ex.: [`COUNTER_GUEST_INSTRUCTIONS`]).
+    /// - `accounting`: Current (precise) accounting.
+    ///
+    /// Returns:
+    /// - `Some(u64)`: Guest-visible counter value.
+    /// - `None`: Unknown counter.
+    pub fn read(&mut self, counter: i32, accounting: &Accounting) -> Option<u64> {
+        let precise = accounting.counter(counter)?;
+        let index = counter as usize;
+        let value = if self.inhibited & (1 << index) != 0 {
+            self.frozen[index]
+        } else {
+            precise.saturating_sub(self.offsets[index])
+        } >> self.shift;
+
+        if self.fuzz_bits == 0 {
+            return Some(value);
+        }
+
+        let mask = u64::MAX >> (64 - self.fuzz_bits as u32);
+        Some((value & !mask) | (self.next_fuzz() & mask))
+    }
+
+    /// Next fuzzing value (SplitMix64).
+    fn next_fuzz(&mut self) -> u64 {
+        self.fuzz_state = self.fuzz_state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.fuzz_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// Statistics of a syscall number.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct SyscallStat {
+    /// Syscall number (ignored for the aggregate of other numbers).
+    pub nr: i32,
+    /// Calls.
+    pub calls: u64,
+    /// Calls that returned an error.
+    pub errors: u64,
+    /// Total ticks spent inside the syscall function (0 without a tick function).
+    pub total_ticks: u64,
+    /// Maximum ticks spent in a single call.
+    pub max_ticks: u64,
+}
+
+impl SyscallStat {
+    /// Mean ticks per call.
+    ///
+    /// Returns:
+    /// - `u64`: Mean ticks (0 if never called).
+    pub fn mean_ticks(&self) -> u64 {
+        self.total_ticks.checked_div(self.calls).unwrap_or(0)
+    }
+
+    /// Record a call.
+    ///
+    /// Arguments:
+    /// - `error`: The call returned an error.
+    /// - `ticks`: Ticks spent inside the syscall function.
+    fn record(&mut self, error: bool, ticks: u64) {
+        self.calls = self.calls.saturating_add(1);
+        self.errors = self.errors.saturating_add(error as u64);
+        self.total_ticks = self.total_ticks.saturating_add(ticks);
+        self.max_ticks = self.max_ticks.max(ticks);
+    }
+}
+
+/// Per-syscall-number statistics table.
+///
+/// The first [`SYSCALL_STATS_ENTRIES`] distinct syscall numbers are tracked individually,
+/// calls to any other number are aggregated in [`SyscallStats::other`].
+/// Formatting it ([`Display`]) prints a compact table, one syscall number per line.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct SyscallStats {
+    /// Tracked syscall numbers.
+    entries: [SyscallStat; SYSCALL_STATS_ENTRIES],
+    /// Tracked syscall numbers count.
+    len: usize,
+    /// Aggregate of untracked syscall numbers.
+    other: SyscallStat,
+}
+
+impl SyscallStats {
+    /// Statistics of a syscall number.
+    ///
+    /// Arguments:
+    /// - `nr`: Syscall number.
+    ///
+    /// Returns:
+    /// - `Some(&SyscallStat)`: Statistics of the syscall number.
+    /// - `None`: Never called, or not tracked (check [`SyscallStats::other`]).
+    pub fn get(&self, nr: i32) -> Option<&SyscallStat> {
+        self.entries().iter().find(|stat| stat.nr == nr)
+    }
+
+    /// Statistics of the tracked syscall numbers, in order of first call.
+    pub fn entries(&self) -> &[SyscallStat] {
+        &self.entries[..self.len]
+    }
+
+    /// Aggregate statistics of the untracked syscall numbers.
+    pub fn other(&self) -> &SyscallStat {
+        &self.other
+    }
+
+    /// Record a syscall.
+    ///
+    /// Arguments:
+    /// - `nr`: Syscall number.
+    /// - `error`: The call returned an error.
+    /// - `ticks`: Ticks spent inside the syscall function.
+    pub(crate) fn record(&mut self, nr: i32, error: bool, ticks: u64) {
+        let stat = match self.entries[..self.len]
+            .iter()
+            .position(|stat| stat.nr == nr)
+        {
+            Some(index) => &mut self.entries[index],
+            None if self.len < SYSCALL_STATS_ENTRIES => {
+                self.len += 1;
+                let stat = &mut self.entries[self.len - 1];
+                stat.nr = nr;
+                stat
+            }
+            None => &mut self.other,
+        };
+
+        stat.record(error, ticks);
+    }
+}
+
+impl Display for SyscallStats {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        writeln!(f, "nr calls errors mean_ticks max_ticks")?;
+        for stat in self.entries() {
+            writeln!(
+                f,
+                "{} {} {} {} {}",
+                stat.nr,
+                stat.calls,
+                stat.errors,
+                stat.mean_ticks(),
+                stat.max_ticks
+            )?;
+        }
+
+        if self.other.calls > 0 {
+            writeln!(
+                f,
+                "other {} {} {} {}",
+                self.other.calls,
+                self.other.errors,
+                self.other.mean_ticks(),
+                self.other.max_ticks
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn test_counter_view() {
+        let mut accounting = Accounting {
+            guest_instructions: 1000,
+            syscalls: 5,
+            ..Default::default()
+        };
+        let mut view = CounterView::default();
+        assert_eq!(
+            view.read(COUNTER_GUEST_INSTRUCTIONS, &accounting),
+            Some(1000)
+        );
+        assert_eq!(view.read(COUNTERS as i32, &accounting), None);
+
+        // Only the inhibited counter is frozen
+        view.inhibit(1 << COUNTER_SYSCALLS, &accounting);
+        accounting.guest_instructions = 1100;
+        accounting.syscalls = 9;
+        assert_eq!(view.inhibited(), 1 << COUNTER_SYSCALLS);
+        assert_eq!(
+            view.read(COUNTER_GUEST_INSTRUCTIONS, &accounting),
+            Some(1100)
+        );
+        assert_eq!(view.read(COUNTER_SYSCALLS, &accounting), Some(5));
+        view.inhibit(0, &accounting);
+        accounting.syscalls = 10;
+        assert_eq!(view.read(COUNTER_SYSCALLS, &accounting), Some(6));
+
+        view.set_scale(4);
+        assert_eq!(
+            view.read(COUNTER_GUEST_INSTRUCTIONS, &accounting),
+            Some(1100 >> 4)
+        );
+
+        // Fuzzing only changes the low bits, deterministically for a seed
+        view.set_fuzz(3, 42);
+        let fuzzed: [u64; 8] =
+            core::array::from_fn(|_| view.read(COUNTER_GUEST_INSTRUCTIONS, &accounting).unwrap());
+        assert!(fuzzed.iter().all(|value| value >> 3 == (1100 >> 4) >> 3));
+        assert!(fuzzed.iter().any(|value| *value != fuzzed[0]));
+        view.set_fuzz(3, 42);
+        assert_eq!(
+            view.read(COUNTER_GUEST_INSTRUCTIONS, &accounting),
+            Some(fuzzed[0])
+        );
+        assert_eq!(accounting.guest_instructions, 1100);
+    }
+
+    #[test]
+    fn test_syscall_stats() {
+        let mut stats = SyscallStats::default();
+        stats.record(3, false, 10);
+        stats.record(-1, true, 4);
+        stats.record(3, true, 30);
+
+        assert_eq!(stats.entries().len(), 2);
+        assert_eq!(
+            stats.get(3),
+            Some(&SyscallStat {
+                nr: 3,
+                calls: 2,
+                errors: 1,
+                total_ticks: 40,
+                max_ticks: 30,
+            })
+        );
+        assert_eq!(stats.get(3).unwrap().mean_ticks(), 20);
+        assert_eq!(stats.get(7), None);
+        assert_eq!(
+            format!("{stats}"),
+            "nr calls errors mean_ticks max_ticks\n3 2 1 20 30\n-1 1 1 4 4\n"
+        );
+    }
+
+    #[test]
+    fn test_syscall_stats_other() {
+        let mut stats = SyscallStats::default();
+        for nr in 0..SYSCALL_STATS_ENTRIES as i32 + 2 {
+            stats.record(nr, false, 1);
+        }
+
+        assert_eq!(stats.entries().len(), SYSCALL_STATS_ENTRIES);
+        assert_eq!(stats.get(SYSCALL_STATS_ENTRIES as i32), None);
+        assert_eq!(stats.other().calls, 2);
+        assert_eq!(stats.other().mean_ticks(), 1);
+        assert!(format!("{stats}").ends_with("other 2 0 1 1\n"));
+    }
+}
@@ -0,0 +1,423 @@
+//! Adapter Module
+//!
+//! Framework-agnostic adapters to drive an engine from embedded run-loops (no additional dependencies):
+//! - [`Sandbox`]: Owns an engine and runs it in time slices, ending a slice early when a
+//!   [`YieldSignal`] is raised (ex.: from an interrupt handler).
+//! - [`Sandbox::run_async`]: A future running the guest until it halts or is suspended, cooperatively
+//!   yielding to the executor between slices (ex.: an Embassy task).
+//!
+//! Slices honor the configured yield point (if the `safepoint` feature is enabled, with `YieldPoint::Safepoint`
+//! slices only end at guest safepoints), but not the instruction limit (the slice length is used instead).
+//!
+//! ## Embassy
+//! Run the sandbox from a task (`#[embassy_executor::task]`), handling deferred syscalls
+//! ([`crate::engine::Config::defer_syscalls`]) without polling the suspended guest:
+//! ```no_run
+//! # use embive::{adapter::{Sandbox, SliceResult}, engine::{SyscallRequest, SyscallResponse}, memory::SliceMemory};
+//! # async fn handle_syscall(_request: SyscallRequest) -> SyscallResponse { Ok(0) }
+//! async fn guest(mut sandbox: Sandbox<'static, SliceMemory<'static>>) {
+//!     while sandbox.run_async().await.unwrap() == SliceResult::Syscall {
+//!         // Suspended, await the host I/O (ex.: a channel or a peripheral driver)
+//!         let (request, _memory) = sandbox.engine.syscall_request().unwrap();
+//!         let response = handle_syscall(request).await;
+//!         sandbox.engine.respond(response);
+//!     }
+//! }
+//! ```
+//!
+//! ## RTIC
+//! Keep the sandbox as a local (or shared) resource of a low priority task, and raise the signal from
+//! higher priority tasks to make the guest yield as soon as possible:
+//! ```no_run
+//! # use embive::{adapter::{Sandbox, YieldSignal}, memory::SliceMemory};
+//! # async fn delay_ms(_ms: u32) {}
+//! static SIGNAL: YieldSignal = YieldSignal::new();
+//!
+//! // `#[task(priority = 1, local = [sandbox])]`, the sandbox is created `with_signal(Some(&SIGNAL))`
+//! async fn guest(sandbox: &mut Sandbox<'static, SliceMemory<'static>>) {
+//!     while sandbox.run_slice().unwrap().should_continue() {
+//!         // Signaled, slice ended or suspended, let higher priority work run (ex.: `Systick::delay`)
+//!         delay_ms(1).await;
+//!     }
+//! }
+//!
+//! // `#[task(binds = EXTI0, priority = 2)]`
+//! fn button() {
+//!     SIGNAL.raise();
+//! }
+//! ```
+
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::{Context, Poll};
+
+use crate::engine::Engine;
+use crate::error::EmbiveError;
+use crate::memory::Memory;
+
+/// Default slice length (in guest instructions) of a [`Sandbox`].
+pub const DEFAULT_SLICE: u32 = 1_000;
+
+/// Why [`Sandbox::run_slice`] returned.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SliceResult {
+    /// The slice ended (slice length reached or yield requested).
+    Yielded,
+    /// The guest is suspended on a blocking syscall (check [`Engine::waiting_for`]).
+    Syscall,
+    /// The guest halted (call [`Engine::reset`] prior to running again).
+    Halted,
+    /// The debugger stopped the engine (check [`Engine::stop_reason`]).
+    #[cfg(feature = "debugger")]
+    Debugger,
+}
+
+impl SliceResult {
+    /// Check if the guest should keep running in the next slice (yielded or suspended).
+    pub fn should_continue(&self) -> bool {
+        matches!(self, SliceResult::Yielded | SliceResult::Syscall)
+    }
+}
+
+/// Interrupt-safe yield request, raised by the host and consumed by a [`Sandbox`].
+#[derive(Debug, Default)]
+pub struct YieldSignal {
+    /// Yield was requested.
+    raised: AtomicBool,
+}
+
+impl YieldSignal {
+    /// Create a new (lowered) signal.
+    pub const fn new() -> Self {
+        YieldSignal {
+            raised: AtomicBool::new(false),
+        }
+    }
+
+    /// Request the sandbox to yield as soon as possible (safe to call from interrupt handlers).
+    pub fn raise(&self) {
+        self.raised.store(true, Ordering::Release);
+    }
+
+    /// Check if a yield was requested.
+    pub fn is_raised(&self) -> bool {
+        self.raised.load(Ordering::Acquire)
+    }
+
+    /// Consume a yield request.
+    /// Load and store only (no compare-and-swap), so it works on every target (ex.: Cortex-M0).
+    /// A request raised meanwhile is merged with the one being consumed.
+    ///
+    /// Returns:
+    /// - `bool`: A yield was requested.
+    pub fn take(&self) -> bool {
+        let raised = self.is_raised();
+        if raised {
+            self.raised.store(false, Ordering::Release);
+        }
+
+        raised
+    }
+}
+
+/// Sandbox, an engine driven in time slices (check the [module documentation](self)).
+pub struct Sandbox<'a, M: Memory> {
+    /// Embive engine.
+    pub engine: Engine<'a, M>,
+    /// Slice length in guest instructions.
+    slice: u32,
+    /// Yield signal (if any).
+    signal: Option<&'a YieldSignal>,
+}
+
+impl<'a, M: Memory> Sandbox<'a, M> {
+    /// Create a new sandbox, with a [`DEFAULT_SLICE`] long slice and no yield signal.
+    ///
+    /// Arguments:
+    /// - `engine`: Embive engine.
+    pub fn new(engine: Engine<'a, M>) -> Self {
+        Sandbox {
+            engine,
+            slice: DEFAULT_SLICE,
+            signal: None,
+        }
+    }
+
+    /// Set the slice length and return the sandbox.
+    ///
+    /// Arguments:
+    /// - `slice`: Slice length in guest instructions (at least 1).
+    pub fn with_slice(mut self, slice: u32) -> Self {
+        self.slice = slice.max(1);
+        self
+    }
+
+    /// Set the yield signal and return the sandbox.
+    ///
+    /// Arguments:
+    /// - `signal`: Optional yield signal, ends the current slice early when raised.
+    pub fn with_signal(mut self, signal: Option<&'a YieldSignal>) -> Self {
+        self.signal = signal;
+        self
+    }
+
+    /// Run a single slice: until the slice length is reached or the yield signal is raised
+    /// (and, with `YieldPoint::Safepoint`, the engine is at a safepoint).
+    /// The slice is entered like [`Engine::run`] (maximum run depth, scratch memory and interrupt delivery).
+    ///
+    /// Returns:
+    /// - `Ok(SliceResult)`: Why the slice ended.
+    /// - `Err(EmbiveError)`: Failed to run (check [`Engine::run`]).
+    pub fn run_slice(&mut self) -> Result<SliceResult, EmbiveError> {
+        let signal = self.signal;
+        let running = self.engine.run_slice(self.slice, || {
+            signal.is_some_and(|signal| signal.is_raised())
+        })?;
+
+        #[cfg(feature = "debugger")]
+        if self.engine.stop_reason().is_some() {
+            return Ok(SliceResult::Debugger);
+        }
+
+        if self.engine.waiting_for().is_some() {
+            return Ok(SliceResult::Syscall);
+        }
+
+        if !running {
+            return Ok(SliceResult::Halted);
+        }
+
+        if let Some(signal) = signal {
+            signal.take();
+        }
+        Ok(SliceResult::Yielded)
+    }
+
+    /// Run the guest until it halts, is suspended on a blocking syscall or the debugger stops it,
+    /// yielding to the executor after every slice.
+    ///
+    /// A suspended guest can't make progress until the host wakes it ([`Engine::wake`], [`Engine::resume`]
+    /// or [`Engine::respond`]), so the future completes instead of polling it: await the host event,
+    /// wake the engine and call `run_async` again.
+    ///
+    /// Returns:
+    /// - `Ok(SliceResult)`: Guest halted ([`SliceResult::Halted`]), suspended ([`SliceResult::Syscall`])
+    ///   or the debugger stopped the engine.
+    /// - `Err(EmbiveError)`: Failed to run.
+    pub async fn run_async(&mut self) -> Result<SliceResult, EmbiveError> {
+        loop {
+            let result = self.run_slice()?;
+            if result != SliceResult::Yielded {
+                return Ok(result);
+            }
+
+            YieldNow(false).await;
+        }
+    }
+}
+
+/// Future that yields to the executor once (wakes itself, so it's polled again).
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Config;
+    #[cfg(feature = "safepoint")]
+    use crate::engine::YieldPoint;
+    use crate::memory::SliceMemory;
+    use core::pin::pin;
+    use core::task::Waker;
+    use std::sync::Arc;
+    use std::task::Wake;
+
+    const CODE: &[u8] = &[
+        0x13, 0x00, 0x00, 0x00, // nop
+        0x13, 0x10, 0xf0, 0x01, // slli x0, x0, 31 (Safepoint)
+        0x13, 0x00, 0x00, 0x00, // nop
+        0x13, 0x00, 0x00, 0x00, // nop
+        0x73, 0x00, 0x10, 0x00, // ebreak
+    ];
+
+    /// Waker doing nothing (the test polls the future in a loop).
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    fn noop_waker() -> Waker {
+        Waker::from(Arc::new(NoopWaker))
+    }
+
+    #[test]
+    fn test_run_slice() {
+        let mut memory = SliceMemory::new(CODE, &mut []);
+        let engine = Engine::new(&mut memory, Config::default()).unwrap();
+        let mut sandbox = Sandbox::new(engine).with_slice(2);
+
+        assert_eq!(sandbox.run_slice(), Ok(SliceResult::Yielded));
+        assert_eq!(sandbox.engine.program_counter, 8);
+        assert_eq!(sandbox.run_slice(), Ok(SliceResult::Yielded));
+        assert_eq!(sandbox.run_slice(), Ok(SliceResult::Halted));
+    }
+
+    #[test]
+    fn test_signal() {
+        let signal = YieldSignal::new();
+        let mut memory = SliceMemory::new(CODE, &mut []);
+        let engine = Engine::new(&mut memory, Config::default()).unwrap();
+        let mut sandbox = Sandbox::new(engine).with_signal(Some(&signal));
+
+        // Yield after the next instruction
+        signal.raise();
+        assert_eq!(sandbox.run_slice(), Ok(SliceResult::Yielded));
+        assert_eq!(sandbox.engine.program_counter, 4);
+        assert!(!signal.is_raised());
+
+        assert_eq!(sandbox.run_slice(), Ok(SliceResult::Halted));
+    }
+
+    #[cfg(feature = "safepoint")]
+    #[test]
+    fn test_signal_safepoint() {
+        let signal = YieldSignal::new();
+        let mut memory = SliceMemory::new(CODE, &mut []);
+        let config = Config::default().with_yield_point(YieldPoint::Safepoint);
+        let engine = Engine::new(&mut memory, config).unwrap();
+        let mut sandbox = Sandbox::new(engine).with_signal(Some(&signal));
+
+        // Yield at the first safepoint after the signal
+        signal.raise();
+        assert_eq!(sandbox.run_slice(), Ok(SliceResult::Yielded));
+        assert_eq!(sandbox.engine.program_counter, 8);
+        assert!(!signal.is_raised());
+
+        assert_eq!(sandbox.run_slice(), Ok(SliceResult::Halted));
+    }
+
+    #[test]
+    fn test_run_async() {
+        let mut memory = SliceMemory::new(CODE, &mut []);
+        let engine = Engine::new(&mut memory, Config::default()).unwrap();
+        let mut sandbox = Sandbox::new(engine).with_slice(1);
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut future = pin!(sandbox.run_async());
+
+        let mut polls = 1;
+        let result = loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(result) => break result,
+                Poll::Pending => polls += 1,
+            }
+        };
+        assert_eq!(result, Ok(SliceResult::Halted));
+        assert_eq!(polls, 5);
+    }
+
+    #[test]
+    fn test_run_async_syscall() {
+        let code = &[
+            0x93, 0x08, 0x10, 0x00, // li   a7, 1 (Syscall nr)
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x73, 0x00, 0x10, 0x00, // ebreak
+        ];
+
+        let mut memory = SliceMemory::new(code, &mut []);
+        let config = Config::default().with_defer_syscalls(true);
+        let engine = Engine::new(&mut memory, config).unwrap();
+        let mut sandbox = Sandbox::new(engine);
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        // Suspended, the future completes instead of polling the guest
+        let result = pin!(sandbox.run_async()).poll(&mut cx);
+        assert_eq!(result, Poll::Ready(Ok(SliceResult::Syscall)));
+
+        assert!(sandbox.engine.respond(Ok(7)));
+        let result = pin!(sandbox.run_async()).poll(&mut cx);
+        assert_eq!(result, Poll::Ready(Ok(SliceResult::Halted)));
+        assert_eq!(sandbox.engine.registers.get(11), Ok(7));
+    }
+
+    #[cfg(feature = "debugger")]
+    #[test]
+    fn test_breakpoint() {
+        use crate::debug::StopReason;
+
+        let mut memory = SliceMemory::new(CODE, &mut []);
+        let engine = Engine::new(&mut memory, Config::default()).unwrap();
+        let mut sandbox = Sandbox::new(engine).with_slice(2);
+        sandbox.engine.debugger.add_breakpoint(12).unwrap();
+
+        // Stopped before the breakpoint instruction, not halted
+        assert_eq!(sandbox.run_slice(), Ok(SliceResult::Yielded));
+        assert_eq!(sandbox.run_slice(), Ok(SliceResult::Debugger));
+        assert_eq!(
+            sandbox.engine.stop_reason(),
+            Some(StopReason::Breakpoint(12))
+        );
+        assert_eq!(sandbox.engine.program_counter, 12);
+
+        // Async runner returns on the stop
+        sandbox.engine.reset();
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let result = {
+            let mut future = pin!(sandbox.run_async());
+            loop {
+                if let Poll::Ready(result) = future.as_mut().poll(&mut cx) {
+                    break result;
+                }
+            }
+        };
+        assert_eq!(result, Ok(SliceResult::Debugger));
+
+        // Resumed from the breakpoint
+        sandbox.engine.debugger.clear();
+        assert_eq!(sandbox.run_slice(), Ok(SliceResult::Halted));
+    }
+
+    #[test]
+    fn test_run_depth() {
+        use crate::engine::{run_depth, SYSCALL_ARGS};
+
+        const SYSCALL_CODE: &[u8] = &[
+            0x93, 0x08, 0x10, 0x00, // li     a7, 1 (Syscall nr)
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x73, 0x00, 0x10, 0x00, // ebreak
+        ];
+
+        fn syscall(_: i32, _: &[i32; SYSCALL_ARGS], _: &mut SliceMemory) -> Result<i32, i32> {
+            Ok(run_depth() as i32)
+        }
+
+        // Slices are engine runs
+        let mut memory = SliceMemory::new(SYSCALL_CODE, &mut []);
+        let config = Config::default().with_syscall_fn(Some(syscall));
+        let engine = Engine::new(&mut memory, config).unwrap();
+        let mut sandbox = Sandbox::new(engine);
+
+        assert_eq!(sandbox.run_slice(), Ok(SliceResult::Halted));
+        assert_eq!(sandbox.engine.registers.get(11), Ok(1));
+        assert_eq!(run_depth(), 0);
+    }
+}
@@ -0,0 +1,139 @@
+//! Performance Counters Module
+//!
+//! Counts guest events per engine, for profiling guest code and for billing/quota systems in multi-tenant hosts:
+//! - Instructions retired (executed successfully, ex.: not faulting).
+//! - Loads and stores (atomics count as both, `lr` as a load and `sc` as a store, vector accesses aren't counted).
+//! - Conditional branches taken.
+//! - Syscalls completed (handled by the engine or the syscall function, including rejected ones).
+//!
+//! Counters are 64-bit and saturate. They aren't cleared by [`crate::engine::Engine::reset`],
+//! take them at the end of each profiling or billing period ([`crate::engine::Engine::reset_counters`]):
+//!
+//! ```
+//! use embive::{engine::{Config, Engine}, memory::SliceMemory};
+//!
+//! let code = &[
+//!     0x93, 0x02, 0x20, 0x00, // li   t0, 2
+//!     0x37, 0x03, 0x00, 0x80, // lui  t1, 0x80000 (RAM)
+//!     0x23, 0x20, 0x53, 0x00, // sw   t0, 0(t1)
+//!     0x93, 0x82, 0xf2, 0xff, // addi t0, t0, -1
+//!     0xe3, 0x9e, 0x02, 0xfe, // bnez t0, -4
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//! ];
+//! let mut ram = [0; 4];
+//! let mut memory = SliceMemory::new(code, &mut ram);
+//! let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+//! engine.run().unwrap();
+//!
+//! let counters = engine.reset_counters();
+//! assert_eq!(counters.instructions_retired, 8);
+//! assert_eq!(counters.stores, 1);
+//! assert_eq!(counters.branches_taken, 1);
+//! assert_eq!(engine.counters().instructions_retired, 0);
+//! ```
+
+use crate::instruction::{instruction_size, is_branch, memory_access, Access};
+
+/// Guest performance counters (check the [module documentation](self)).
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct PerformanceCounters {
+    /// Instructions retired.
+    pub instructions_retired: u64,
+    /// Loads executed (including atomics).
+    pub loads: u64,
+    /// Stores executed (including atomics).
+    pub stores: u64,
+    /// Conditional branches taken.
+    pub branches_taken: u64,
+    /// Syscalls completed.
+    pub syscalls: u64,
+}
+
+impl PerformanceCounters {
+    /// Count a retired instruction.
+    ///
+    /// Arguments:
+    /// - `address`: Instruction address.
+    /// - `data`: Instruction (raw).
+    /// - `next`: Program counter after the instruction.
+    #[inline(always)]
+    pub(crate) fn retired(&mut self, address: u32, data: u32, next: u32) {
+        self.instructions_retired = self.instructions_retired.saturating_add(1);
+
+        if let Some(access) = memory_access(data) {
+            if access.access != Access::Write {
+                self.loads = self.loads.saturating_add(1);
+            }
+            if access.access != Access::Read {
+                self.stores = self.stores.saturating_add(1);
+            }
+        } else if is_branch(data) && next != address.wrapping_add(instruction_size(data)) {
+            self.branches_taken = self.branches_taken.saturating_add(1);
+        }
+    }
+
+    /// Count a completed syscall.
+    #[inline(always)]
+    pub(crate) fn syscall(&mut self) {
+        self.syscalls = self.syscalls.saturating_add(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retired() {
+        let mut counters = PerformanceCounters::default();
+
+        counters.retired(0x0, 0x0000_2283, 0x4); // lw   t0, 0(zero)
+        counters.retired(0x4, 0x0050_2023, 0x8); // sw   t0, 0(zero)
+        counters.retired(0x8, 0x0002_8463, 0xc); // beqz t0, 8 (not taken)
+        counters.retired(0xc, 0x0002_8463, 0x14); // beqz t0, 8 (taken)
+        counters.retired(0x14, 0x0000_0073, 0x18); // ecall
+        counters.syscall();
+
+        assert_eq!(
+            counters,
+            PerformanceCounters {
+                instructions_retired: 5,
+                loads: 1,
+                stores: 1,
+                branches_taken: 1,
+                syscalls: 1,
+            }
+        );
+
+        // Saturates
+        counters.instructions_retired = u64::MAX;
+        counters.retired(0x18, 0x0000_0013, 0x1c); // nop
+        assert_eq!(counters.instructions_retired, u64::MAX);
+    }
+
+    #[cfg(feature = "a_extension")]
+    #[test]
+    fn test_atomics() {
+        let mut counters = PerformanceCounters::default();
+
+        counters.retired(0x0, 0x1000_22af, 0x4); // lr.w      t0, (zero)
+        counters.retired(0x4, 0x1850_232f, 0x8); // sc.w      t1, t0, (zero)
+        counters.retired(0x8, 0x0050_232f, 0xc); // amoadd.w  t1, t0, (zero)
+
+        assert_eq!(counters.loads, 2);
+        assert_eq!(counters.stores, 2);
+    }
+
+    #[cfg(feature = "c_extension")]
+    #[test]
+    fn test_compressed() {
+        let mut counters = PerformanceCounters::default();
+
+        counters.retired(0x0, 0xc011, 0x4); // c.beqz s0, 4 (taken)
+        counters.retired(0x4, 0xc011, 0x6); // c.beqz s0, 4 (not taken)
+        counters.retired(0x6, 0x4000, 0x8); // c.lw   s0, 0(s0)
+
+        assert_eq!(counters.branches_taken, 1);
+        assert_eq!(counters.loads, 1);
+    }
+}
@@ -0,0 +1,441 @@
+//! CSR Module
+//!
+//! Control and status registers (Zicsr): `csrrw`, `csrrs`, `csrrc` and their immediate variants are executed
+//! by the engine, the registers themselves are provided by a host [`CsrHandler`] implementation
+//! (registered with [`crate::engine::Config::with_csr`]), ex.: to expose the `cycle`, `time` and `instret` counters.
+//! Without a handler, or when the handler doesn't implement a register, CSR instructions are illegal.
+//! The debug CSRs (trigger module, [`TSELECT`] to [`TINFO`], and debug mode, [`DCSR`] to [`DSCRATCH1`] and
+//! [`DMONITOR`]) are handled by the engine when the `debugger` feature is enabled (check the `debug` module).
+//! The instruction budget registers (`BUDGET` and `BUDGETH`, read-only) are handled by the engine when
+//! the `instruction_limit` feature is enabled and [`crate::engine::Config::budget_csr`] is set.
+//! The trap registers ([`MTVEC`], [`MSCRATCH`], [`MEPC`], [`MCAUSE`] and [`MTVAL`]) are handled by the engine when guest traps are delegated
+//! or interrupts are delivered to the guest (check [`crate::trap`]).
+//!
+//! As in the specification, `csrrw` with `rd` = `x0` doesn't read the register, and `csrrs`/`csrrc` with a zero
+//! source (`rs1` = `x0` or `uimm` = 0) don't write it. Writing a read-only register
+//! (address bits `[11:10]` = `0b11`, ex.: [`CYCLE`]) is illegal.
+//!
+//! ```
+//! use embive::{
+//!     csr::{CsrHandler, TIME},
+//!     engine::{Config, Engine},
+//!     memory::SliceMemory,
+//! };
+//!
+//! struct Counters;
+//!
+//! impl CsrHandler for Counters {
+//!     fn read(csr: u16) -> Option<u32> {
+//!         match csr {
+//!             TIME => Some(1234), // ex.: host clock
+//!             _ => None,
+//!         }
+//!     }
+//! }
+//!
+//! let code = &[
+//!     0x73, 0x25, 0x10, 0xc0, // rdtime a0
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//! ];
+//! let mut memory = SliceMemory::new(code, &mut []);
+//! let config = Config::default().with_csr::<Counters>();
+//! let mut engine = Engine::new(&mut memory, config).unwrap();
+//!
+//! assert_eq!(engine.run(), Ok(false));
+//! assert_eq!(engine.registers.get(10), Ok(1234));
+//! ```
+
+#[cfg(feature = "debugger")]
+use crate::debug::Debugger;
+use crate::engine::Engine;
+use crate::error::EmbiveError;
+use crate::instruction::format::TypeI;
+use crate::memory::Memory;
+use crate::trap::TrapState;
+
+/// Cycle counter (lower 32 bits).
+pub const CYCLE: u16 = 0xC00;
+/// Timer (lower 32 bits).
+pub const TIME: u16 = 0xC01;
+/// Instructions-retired counter (lower 32 bits).
+pub const INSTRET: u16 = 0xC02;
+/// Cycle counter (upper 32 bits).
+pub const CYCLEH: u16 = 0xC80;
+/// Timer (upper 32 bits).
+pub const TIMEH: u16 = 0xC81;
+/// Instructions-retired counter (upper 32 bits).
+pub const INSTRETH: u16 = 0xC82;
+/// Machine trap handler address (check [`crate::trap`]).
+pub const MTVEC: u16 = 0x305;
+/// Machine trap scratch register (check [`crate::trap`]).
+pub const MSCRATCH: u16 = 0x340;
+/// Machine exception program counter (check [`crate::trap`]).
+pub const MEPC: u16 = 0x341;
+/// Machine trap cause (check [`crate::trap`]).
+pub const MCAUSE: u16 = 0x342;
+/// Machine trap value (check [`crate::trap`]).
+pub const MTVAL: u16 = 0x343;
+/// Debug trigger select.
+pub const TSELECT: u16 = 0x7A0;
+/// Debug trigger data 1 (configuration).
+pub const TDATA1: u16 = 0x7A1;
+/// Debug trigger data 2 (compare value).
+pub const TDATA2: u16 = 0x7A2;
+/// Debug trigger data 3.
+pub const TDATA3: u16 = 0x7A3;
+/// Debug trigger info (supported types).
+pub const TINFO: u16 = 0x7A4;
+/// Debug control and status.
+pub const DCSR: u16 = 0x7B0;
+/// Debug program counter.
+pub const DPC: u16 = 0x7B1;
+/// Debug scratch register 0.
+pub const DSCRATCH0: u16 = 0x7B2;
+/// Debug scratch register 1.
+pub const DSCRATCH1: u16 = 0x7B3;
+/// Debug monitor entry point (Embive custom, check the `debug::monitor` module).
+pub const DMONITOR: u16 = 0x7C0;
+/// Remaining instruction budget, lower 32 bits (Embive custom, check [`crate::engine::Engine::budget`]).
+#[cfg(feature = "instruction_limit")]
+pub const BUDGET: u16 = 0xCC0;
+/// Remaining instruction budget, upper 32 bits (Embive custom, check [`crate::engine::Engine::budget`]).
+#[cfg(feature = "instruction_limit")]
+pub const BUDGETH: u16 = 0xCC1;
+
+/// CSR instruction: atomic read/write.
+const CSRRW_FUNCT3: u8 = 0b001;
+/// CSR instruction: atomic read and set bits.
+const CSRRS_FUNCT3: u8 = 0b010;
+/// CSR instruction: atomic read and clear bits.
+const CSRRC_FUNCT3: u8 = 0b011;
+/// CSR instruction: immediate variant flag (`uimm` in the `rs1` field).
+const IMMEDIATE_FUNCT3: u8 = 0b100;
+
+/// Host side of the control and status registers (check the [module documentation](self)).
+pub trait CsrHandler {
+    /// Read a register.
+    ///
+    /// Arguments:
+    /// - `csr`: Register address (12 bits).
+    ///
+    /// Returns:
+    /// - `Some(u32)`: Register value.
+    /// - `None`: Not implemented (illegal instruction).
+    fn read(csr: u16) -> Option<u32>;
+
+    /// Write a register (not implemented by default).
+    ///
+    /// Arguments:
+    /// - `csr`: Register address (12 bits, never a read-only register).
+    /// - `value`: New value.
+    ///
+    /// Returns:
+    /// - `true`: Written.
+    /// - `false`: Not implemented (illegal instruction).
+    fn write(_csr: u16, _value: u32) -> bool {
+        false
+    }
+}
+
+/// Host functions of a [`CsrHandler`] implementation (check [`crate::engine::Config::with_csr`]).
+#[derive(Debug, Clone, Copy)]
+pub struct CsrHooks {
+    read: fn(u16) -> Option<u32>,
+    write: fn(u16, u32) -> bool,
+}
+
+impl CsrHooks {
+    /// Get the host functions of a [`CsrHandler`] implementation.
+    ///
+    /// Generic Arguments:
+    /// - `H`: Host implementation.
+    pub fn of<H: CsrHandler>() -> Self {
+        CsrHooks {
+            read: H::read,
+            write: H::write,
+        }
+    }
+}
+
+/// Check if a register is read-only (address bits `[11:10]` = `0b11`).
+///
+/// Arguments:
+/// - `csr`: Register address (12 bits).
+pub const fn is_read_only(csr: u16) -> bool {
+    (csr >> 10) & 0b11 == 0b11
+}
+
+/// Execute a CSR instruction (the program counter isn't changed).
+///
+/// Arguments:
+/// - `engine`: Embive engine.
+/// - `inst`: Decoded instruction (I-Type, register address in the immediate).
+///
+/// Returns:
+/// - `Ok(())`: The instruction was executed.
+/// - `Err(EmbiveError)`: Illegal instruction (no handler, register not implemented or read-only).
+pub(crate) fn execute<M: Memory>(engine: &mut Engine<M>, inst: TypeI) -> Result<(), EmbiveError> {
+    let csr = (inst.imm & 0xFFF) as u16;
+    let operation = inst.funct3 & !IMMEDIATE_FUNCT3;
+
+    let source = if inst.funct3 & IMMEDIATE_FUNCT3 != 0 {
+        inst.rs1 as u32
+    } else {
+        engine.registers.get_decoded(inst.rs1)? as u32
+    };
+
+    // `csrrw` to `x0` doesn't read, `csrrs`/`csrrc` from a zero source (register or immediate) don't write
+    let read = operation != CSRRW_FUNCT3 || inst.rd != 0;
+    let write = operation == CSRRW_FUNCT3 || inst.rs1 != 0;
+
+    let old = match read {
+        true => read_csr(engine, csr).ok_or(EmbiveError::InvalidInstruction)?,
+        false => 0,
+    };
+
+    if write {
+        let value = match operation {
+            CSRRW_FUNCT3 => source,
+            CSRRS_FUNCT3 => old | source,
+            CSRRC_FUNCT3 => old & !source,
+            _ => return Err(EmbiveError::InvalidInstruction),
+        };
+
+        if is_read_only(csr) || !write_csr(engine, csr, value) {
+            return Err(EmbiveError::InvalidInstruction);
+        }
+    }
+
+    if inst.rd != 0 {
+        *engine.registers.get_decoded_mut(inst.rd)? = old as i32;
+    }
+
+    Ok(())
+}
+
+/// Read a register (debugger, guest traps or instruction budget, if enabled, or host handler).
+///
+/// Arguments:
+/// - `engine`: Embive engine.
+/// - `csr`: Register address.
+///
+/// Returns:
+/// - `Some(u32)`: Register value.
+/// - `None`: Not implemented.
+fn read_csr<M: Memory>(engine: &Engine<M>, csr: u16) -> Option<u32> {
+    #[cfg(feature = "debugger")]
+    if Debugger::is_debug_csr(csr) {
+        return engine.debugger.read_csr(csr);
+    }
+
+    if engine.config.guest_trap_handler() && TrapState::is_trap_csr(csr) {
+        return engine.trap.read_csr(csr);
+    }
+
+    #[cfg(feature = "instruction_limit")]
+    if engine.config.budget_csr && (csr == BUDGET || csr == BUDGETH) {
+        let budget = engine.budget();
+        return Some(match csr {
+            BUDGET => budget as u32,
+            _ => (budget >> 32) as u32,
+        });
+    }
+
+    (engine.config.csr?.read)(csr)
+}
+
+/// Write a register (debugger or guest traps, if enabled, or host handler).
+///
+/// Arguments:
+/// - `engine`: Embive engine.
+/// - `csr`: Register address.
+/// - `value`: New value.
+///
+/// Returns:
+/// - `true`: Written.
+/// - `false`: Not implemented.
+fn write_csr<M: Memory>(engine: &mut Engine<M>, csr: u16, value: u32) -> bool {
+    #[cfg(feature = "debugger")]
+    if Debugger::is_debug_csr(csr) {
+        return engine.debugger.write_csr(csr, value);
+    }
+
+    if engine.config.guest_trap_handler() && TrapState::is_trap_csr(csr) {
+        return engine.trap.write_csr(csr, value);
+    }
+
+    engine
+        .config
+        .csr
+        .is_some_and(|hooks| (hooks.write)(csr, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Config;
+    use crate::memory::SliceMemory;
+
+    const SCRATCH: u16 = 0x340;
+
+    std::thread_local! {
+        static REGISTER: core::cell::Cell<u32> = const { core::cell::Cell::new(0) };
+        static READS: core::cell::Cell<u32> = const { core::cell::Cell::new(0) };
+    }
+
+    struct Handler;
+
+    impl CsrHandler for Handler {
+        fn read(csr: u16) -> Option<u32> {
+            READS.with(|reads| reads.set(reads.get() + 1));
+            match csr {
+                CYCLE => Some(100),
+                SCRATCH => Some(REGISTER.with(|register| register.get())),
+                _ => None,
+            }
+        }
+
+        fn write(csr: u16, value: u32) -> bool {
+            match csr {
+                SCRATCH => {
+                    REGISTER.with(|register| register.set(value));
+                    true
+                }
+                _ => false,
+            }
+        }
+    }
+
+    fn inst(funct3: u8, rd: usize, rs1: usize, csr: u16) -> TypeI {
+        TypeI {
+            rd,
+            rs1,
+            imm: csr as i32,
+            funct3,
+        }
+    }
+
+    #[test]
+    fn test_no_handler() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+
+        let result = execute(&mut engine, inst(CSRRS_FUNCT3, 10, 0, CYCLE));
+        assert_eq!(result, Err(EmbiveError::InvalidInstruction));
+    }
+
+    #[test]
+    fn test_read_write() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let config = Config::default().with_csr::<Handler>();
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+        engine.registers.inner[11] = 0b1100;
+
+        // csrrw a0, scratch, a1
+        REGISTER.with(|register| register.set(0b1010));
+        assert_eq!(
+            execute(&mut engine, inst(CSRRW_FUNCT3, 10, 11, SCRATCH)),
+            Ok(())
+        );
+        assert_eq!(engine.registers.inner[10], 0b1010);
+        assert_eq!(REGISTER.with(|register| register.get()), 0b1100);
+
+        // csrrs a0, scratch, a1 (set)
+        REGISTER.with(|register| register.set(0b0011));
+        assert_eq!(
+            execute(&mut engine, inst(CSRRS_FUNCT3, 10, 11, SCRATCH)),
+            Ok(())
+        );
+        assert_eq!(engine.registers.inner[10], 0b0011);
+        assert_eq!(REGISTER.with(|register| register.get()), 0b1111);
+
+        // csrrci a0, scratch, 0b101 (clear)
+        let funct3 = CSRRC_FUNCT3 | IMMEDIATE_FUNCT3;
+        assert_eq!(
+            execute(&mut engine, inst(funct3, 10, 0b101, SCRATCH)),
+            Ok(())
+        );
+        assert_eq!(engine.registers.inner[10], 0b1111);
+        assert_eq!(REGISTER.with(|register| register.get()), 0b1010);
+
+        // csrrwi zero, scratch, 7 (no read)
+        let reads = READS.with(|reads| reads.get());
+        let funct3 = CSRRW_FUNCT3 | IMMEDIATE_FUNCT3;
+        assert_eq!(execute(&mut engine, inst(funct3, 0, 7, SCRATCH)), Ok(()));
+        assert_eq!(READS.with(|reads| reads.get()), reads);
+        assert_eq!(REGISTER.with(|register| register.get()), 7);
+    }
+
+    #[test]
+    fn test_read_only() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let config = Config::default().with_csr::<Handler>();
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+        engine.registers.inner[11] = 1;
+
+        // rdcycle a0 (csrrs a0, cycle, zero doesn't write)
+        assert_eq!(
+            execute(&mut engine, inst(CSRRS_FUNCT3, 10, 0, CYCLE)),
+            Ok(())
+        );
+        assert_eq!(engine.registers.inner[10], 100);
+
+        // csrrs a0, cycle, a1
+        let result = execute(&mut engine, inst(CSRRS_FUNCT3, 10, 11, CYCLE));
+        assert_eq!(result, Err(EmbiveError::InvalidInstruction));
+
+        // Not implemented
+        let result = execute(&mut engine, inst(CSRRS_FUNCT3, 10, 0, INSTRETH));
+        assert_eq!(result, Err(EmbiveError::InvalidInstruction));
+        assert!(is_read_only(INSTRETH));
+        assert!(!is_read_only(SCRATCH));
+    }
+
+    #[test]
+    #[cfg(feature = "instruction_limit")]
+    fn test_budget() {
+        use crate::engine::RunResult;
+
+        let code = &[
+            0x73, 0x25, 0x00, 0xcc, // csrr a0, budget
+            0xf3, 0x25, 0x10, 0xcc, // csrr a1, budgeth
+            0x73, 0x00, 0x10, 0x00, // ebreak
+        ];
+        let mut memory = SliceMemory::new(code, &mut []);
+
+        // Not exposed
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+        assert_eq!(
+            engine.run(),
+            Err(EmbiveError::IllegalInstruction {
+                pc: 0,
+                raw: 0xcc002573
+            })
+        );
+
+        // Fuel (after the csrr)
+        engine.config = Config::default().with_budget_csr(true);
+        engine.reset();
+        assert_eq!(engine.run_with_fuel(10), Ok(RunResult::Halted));
+        assert_eq!(engine.registers.inner[10], 9);
+        assert_eq!(engine.registers.inner[11], 0);
+        assert_eq!(engine.budget(), 7);
+
+        // Instruction limit
+        engine.config = engine.config.with_instruction_limit(5);
+        engine.reset();
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.inner[10], 4);
+
+        // No limit
+        engine.config = engine.config.with_instruction_limit(0);
+        engine.reset();
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.inner[10], -1);
+        assert_eq!(engine.registers.inner[11], -1);
+
+        // Read-only
+        let result = execute(&mut engine, inst(CSRRW_FUNCT3, 10, 11, BUDGET));
+        assert_eq!(result, Err(EmbiveError::InvalidInstruction));
+    }
+}
@@ -0,0 +1,454 @@
+//! Debug Module
+//!
+//! Breakpoints, watchpoints and single-stepping for debuggers built on top of Embive, without re-decoding
+//! instructions on the host. Configured through [`crate::engine::Engine::debugger`].
+//!
+//! When a breakpoint or watchpoint is hit, the engine stops **before** executing the instruction.
+//! In single-step mode, it stops **after** every executed instruction.
+//! [`crate::engine::Engine::run`] returns `Ok(true)` and the reason is available in
+//! [`crate::engine::Engine::stop_reason`], until the engine runs again. Running again executes the stopped
+//! instruction (breakpoints and watchpoints are not hit twice in a row).
+//!
+//! Watchpoints match the memory accesses of loads, stores and atomics (including compressed ones),
+//! vector accesses and host accesses (ex.: from syscalls) are not matched.
+//!
+//! Guests (or debugger front-ends, through [`Debugger::read_csr`] and [`Debugger::write_csr`]) can also set
+//! triggers with the standard trigger module CSRs (check [`trigger`]), stopping the engine the same way.
+//! In-guest debuggers can also single-step the guest with a debug monitor, without stopping the engine
+//! (check [`monitor`]).
+//!
+//! ```
+//! use embive::{
+//!     debug::{Access, StopReason, Watchpoint},
+//!     engine::{Config, Engine},
+//!     memory::SliceMemory,
+//! };
+//!
+//! let code = &[
+//!     0x37, 0x05, 0x00, 0x80, // lui  a0, 0x80000 (RAM)
+//!     0x93, 0x05, 0x20, 0x00, // li   a1, 2
+//!     0x23, 0x00, 0xb5, 0x00, // sb   a1, 0(a0)
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//! ];
+//! let mut ram = [0; 4];
+//! let mut memory = SliceMemory::new(code, &mut ram);
+//! let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+//!
+//! engine.debugger.add_breakpoint(4).unwrap();
+//! engine.debugger.add_watchpoint(Watchpoint {
+//!     address: 0x8000_0000,
+//!     len: 4,
+//!     access: Access::Write,
+//! }).unwrap();
+//!
+//! assert_eq!(engine.run(), Ok(true));
+//! assert_eq!(engine.stop_reason(), Some(StopReason::Breakpoint(4)));
+//!
+//! assert_eq!(engine.run(), Ok(true));
+//! assert_eq!(
+//!     engine.stop_reason(),
+//!     Some(StopReason::Watchpoint { pc: 8, address: 0x8000_0000, access: Access::Write })
+//! );
+//!
+//! assert_eq!(engine.run(), Ok(false));
+//! ```
+
+pub mod monitor;
+pub mod trigger;
+
+pub use crate::instruction::Access;
+
+use crate::error::EmbiveError;
+use crate::instruction::memory_access;
+use crate::register::Registers;
+use monitor::Monitor;
+use trigger::Triggers;
+
+/// Maximum number of breakpoints.
+pub const BREAKPOINTS: usize = 8;
+
+/// Maximum number of watchpoints.
+pub const WATCHPOINTS: usize = 4;
+
+impl Access {
+    /// Check if an access matches a watched access kind.
+    ///
+    /// Arguments:
+    /// - `watched`: Watched access kind.
+    const fn matches(self, watched: Access) -> bool {
+        !matches!(
+            (self, watched),
+            (Access::Read, Access::Write) | (Access::Write, Access::Read)
+        )
+    }
+}
+
+/// Memory watchpoint.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Watchpoint {
+    /// Start address.
+    pub address: u32,
+    /// Length in bytes.
+    pub len: u32,
+    /// Watched access kind.
+    pub access: Access,
+}
+
+/// Why the engine stopped (check [`crate::engine::Engine::stop_reason`]).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StopReason {
+    /// Breakpoint hit (instruction address, not executed).
+    Breakpoint(u32),
+    /// Watchpoint hit (the instruction at `pc` wasn't executed).
+    Watchpoint {
+        /// Instruction address.
+        pc: u32,
+        /// Accessed address.
+        address: u32,
+        /// Access kind.
+        access: Access,
+    },
+    /// Single-step (next instruction address).
+    Step(u32),
+    /// Trigger hit (check [`trigger`], the instruction at `pc` wasn't executed).
+    Trigger {
+        /// Trigger index (`tselect`).
+        index: usize,
+        /// Instruction address.
+        pc: u32,
+    },
+}
+
+/// Engine debugger: breakpoints, watchpoints and single-step mode (check the [module documentation](self)).
+#[derive(Debug, Default)]
+pub struct Debugger {
+    /// Breakpoint addresses (None = Free slot).
+    breakpoints: [Option<u32>; BREAKPOINTS],
+    /// Watchpoints (None = Free slot).
+    watchpoints: [Option<Watchpoint>; WATCHPOINTS],
+    /// Stop after every instruction.
+    single_step: bool,
+    /// Trigger module (guest-visible, check [`trigger`]).
+    pub(crate) triggers: Triggers,
+    /// Guest debug monitor (check [`monitor`]).
+    pub(crate) monitor: Monitor,
+    /// Why the engine stopped (None = Not stopped).
+    pub(crate) stop: Option<StopReason>,
+}
+
+impl Debugger {
+    /// Add a breakpoint.
+    ///
+    /// Arguments:
+    /// - `address`: Instruction address.
+    ///
+    /// Returns:
+    /// - `Ok(())`: The breakpoint was added (or already existed).
+    /// - `Err(EmbiveError)`: All [`BREAKPOINTS`] are in use ([`EmbiveError::TooManyTriggers`]).
+    pub fn add_breakpoint(&mut self, address: u32) -> Result<(), EmbiveError> {
+        if self.breakpoints.contains(&Some(address)) {
+            return Ok(());
+        }
+
+        let slot = self
+            .breakpoints
+            .iter_mut()
+            .find(|breakpoint| breakpoint.is_none())
+            .ok_or(EmbiveError::TooManyTriggers)?;
+        *slot = Some(address);
+
+        Ok(())
+    }
+
+    /// Remove a breakpoint.
+    ///
+    /// Arguments:
+    /// - `address`: Instruction address.
+    ///
+    /// Returns:
+    /// - `bool`: The breakpoint existed (and was removed).
+    pub fn remove_breakpoint(&mut self, address: u32) -> bool {
+        self.breakpoints
+            .iter_mut()
+            .find(|breakpoint| **breakpoint == Some(address))
+            .map(|breakpoint| *breakpoint = None)
+            .is_some()
+    }
+
+    /// Add a watchpoint.
+    ///
+    /// Arguments:
+    /// - `watchpoint`: Watched memory range and access kind.
+    ///
+    /// Returns:
+    /// - `Ok(())`: The watchpoint was added.
+    /// - `Err(EmbiveError)`: All [`WATCHPOINTS`] are in use ([`EmbiveError::TooManyTriggers`]).
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) -> Result<(), EmbiveError> {
+        let slot = self
+            .watchpoints
+            .iter_mut()
+            .find(|watchpoint| watchpoint.is_none())
+            .ok_or(EmbiveError::TooManyTriggers)?;
+        *slot = Some(watchpoint);
+
+        Ok(())
+    }
+
+    /// Remove the watchpoints starting at an address.
+    ///
+    /// Arguments:
+    /// - `address`: Watchpoint start address.
+    ///
+    /// Returns:
+    /// - `bool`: A watchpoint existed (and was removed).
+    pub fn remove_watchpoint(&mut self, address: u32) -> bool {
+        let mut removed = false;
+        for slot in self.watchpoints.iter_mut() {
+            if slot.is_some_and(|watchpoint| watchpoint.address == address) {
+                *slot = None;
+                removed = true;
+            }
+        }
+
+        removed
+    }
+
+    /// Enable or disable the single-step mode.
+    ///
+    /// Arguments:
+    /// - `enabled`: Stop after every executed instruction.
+    pub fn set_single_step(&mut self, enabled: bool) {
+        self.single_step = enabled;
+    }
+
+    /// Check if the single-step mode is enabled.
+    pub fn single_step(&self) -> bool {
+        self.single_step
+    }
+
+    /// Remove every breakpoint and watchpoint, and disable the single-step mode (guest triggers and monitor are kept).
+    pub fn clear(&mut self) {
+        *self = Debugger {
+            triggers: self.triggers,
+            monitor: self.monitor,
+            stop: self.stop,
+            ..Default::default()
+        };
+    }
+
+    /// Check if a CSR is handled by the debugger (trigger module or debug monitor).
+    ///
+    /// Arguments:
+    /// - `csr`: Register address.
+    pub(crate) const fn is_debug_csr(csr: u16) -> bool {
+        Triggers::is_trigger_csr(csr) || Monitor::is_monitor_csr(csr)
+    }
+
+    /// Read a debug CSR (ex.: from a debugger front-end, check [`trigger`] and [`monitor`]).
+    ///
+    /// Arguments:
+    /// - `csr`: Register address.
+    ///
+    /// Returns:
+    /// - `Some(u32)`: Register value.
+    /// - `None`: Not a debug CSR.
+    pub fn read_csr(&self, csr: u16) -> Option<u32> {
+        self.triggers.read(csr).or_else(|| self.monitor.read(csr))
+    }
+
+    /// Write a debug CSR (ex.: from a debugger front-end, check [`trigger`] and [`monitor`]).
+    ///
+    /// Arguments:
+    /// - `csr`: Register address.
+    /// - `value`: New value.
+    ///
+    /// Returns:
+    /// - `true`: Written.
+    /// - `false`: Not a writable debug CSR.
+    pub fn write_csr(&mut self, csr: u16, value: u32) -> bool {
+        self.triggers.write(csr, value) || self.monitor.write(csr, value)
+    }
+
+    /// Check the breakpoints and watchpoints before executing an instruction.
+    /// Clears the previous stop reason.
+    ///
+    /// Arguments:
+    /// - `pc`: Instruction address.
+    /// - `data`: Instruction (raw).
+    /// - `registers`: CPU registers (to compute the accessed address).
+    ///
+    /// Returns:
+    /// - `Some(StopReason)`: The engine should stop before executing the instruction.
+    /// - `None`: The instruction can be executed.
+    #[inline]
+    pub(crate) fn check(
+        &mut self,
+        pc: u32,
+        data: u32,
+        registers: &Registers,
+    ) -> Option<StopReason> {
+        // Resuming from this instruction
+        match self.stop.take() {
+            Some(
+                StopReason::Breakpoint(stopped)
+                | StopReason::Watchpoint { pc: stopped, .. }
+                | StopReason::Trigger { pc: stopped, .. },
+            ) if stopped == pc => return None,
+            _ => {}
+        }
+
+        if self.breakpoints.contains(&Some(pc)) {
+            return Some(StopReason::Breakpoint(pc));
+        }
+
+        let triggers = self.triggers.armed();
+        if triggers {
+            if let Some(index) = self.triggers.execute(pc) {
+                return Some(StopReason::Trigger { index, pc });
+            }
+        }
+
+        if !triggers && self.watchpoints.iter().all(Option::is_none) {
+            return None;
+        }
+
+        let inst = memory_access(data)?;
+        let address = (registers.get(inst.base).ok()? as u32).wrapping_add_signed(inst.offset);
+        let end = address.wrapping_add(inst.len - 1);
+        if self.watchpoints.iter().flatten().any(|watchpoint| {
+            let last = watchpoint.address.wrapping_add(watchpoint.len.max(1) - 1);
+            inst.access.matches(watchpoint.access) && address <= last && watchpoint.address <= end
+        }) {
+            return Some(StopReason::Watchpoint {
+                pc,
+                address,
+                access: inst.access,
+            });
+        }
+
+        if triggers {
+            let value = registers.get(inst.data).ok()? as u32 & (u32::MAX >> (32 - inst.len * 8));
+            if let Some(index) = self.triggers.access(address, inst.len, inst.access, value) {
+                return Some(StopReason::Trigger { index, pc });
+            }
+        }
+
+        None
+    }
+
+    /// Check the single-step mode after executing an instruction.
+    ///
+    /// Arguments:
+    /// - `pc`: Next instruction address.
+    ///
+    /// Returns:
+    /// - `Some(StopReason)`: The engine should stop.
+    /// - `None`: The engine can continue.
+    #[inline]
+    pub(crate) fn stepped(&self, pc: u32) -> Option<StopReason> {
+        self.single_step.then_some(StopReason::Step(pc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registers(base: i32) -> Registers {
+        let mut registers = Registers::default();
+        registers.inner[10] = base;
+        registers
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let mut debugger = Debugger::default();
+        for address in 0..BREAKPOINTS as u32 {
+            assert_eq!(debugger.add_breakpoint(address * 4), Ok(()));
+        }
+        assert_eq!(debugger.add_breakpoint(0), Ok(()));
+        assert_eq!(
+            debugger.add_breakpoint(0x100),
+            Err(EmbiveError::TooManyTriggers)
+        );
+
+        let registers = registers(0);
+        let nop = 0x0000_0013;
+        assert_eq!(
+            debugger.check(4, nop, &registers),
+            Some(StopReason::Breakpoint(4))
+        );
+
+        // Resuming
+        debugger.stop = Some(StopReason::Breakpoint(4));
+        assert_eq!(debugger.check(4, nop, &registers), None);
+        assert_eq!(debugger.stop, None);
+
+        assert!(debugger.remove_breakpoint(8));
+        assert!(!debugger.remove_breakpoint(8));
+        assert_eq!(debugger.check(8, nop, &registers), None);
+    }
+
+    #[test]
+    fn test_watchpoints() {
+        let mut debugger = Debugger::default();
+        debugger
+            .add_watchpoint(Watchpoint {
+                address: 0x8000_0004,
+                len: 4,
+                access: Access::Write,
+            })
+            .unwrap();
+
+        let registers = registers(0x8000_0000u32 as i32);
+        let lw = 0x0045_2583; // lw a1, 4(a0)
+        let sh = 0x00b5_1123; // sh a1, 2(a0)
+        let sb = 0x00b5_03a3; // sb a1, 7(a0)
+        let sb_outside = 0x00b5_0423; // sb a1, 8(a0)
+
+        assert_eq!(debugger.check(0, lw, &registers), None);
+        assert_eq!(debugger.check(0, sh, &registers), None);
+        assert_eq!(debugger.check(0, sb_outside, &registers), None);
+        assert_eq!(
+            debugger.check(0, sb, &registers),
+            Some(StopReason::Watchpoint {
+                pc: 0,
+                address: 0x8000_0007,
+                access: Access::Write
+            })
+        );
+
+        debugger
+            .add_watchpoint(Watchpoint {
+                address: 0x8000_0000,
+                len: 4,
+                access: Access::ReadWrite,
+            })
+            .unwrap();
+        assert_eq!(
+            debugger.check(0, sh, &registers),
+            Some(StopReason::Watchpoint {
+                pc: 0,
+                address: 0x8000_0002,
+                access: Access::Write
+            })
+        );
+
+        assert!(debugger.remove_watchpoint(0x8000_0000));
+        assert!(!debugger.remove_watchpoint(0x8000_0000));
+        assert_eq!(debugger.check(0, sh, &registers), None);
+    }
+
+    #[test]
+    fn test_single_step() {
+        let mut debugger = Debugger::default();
+        assert_eq!(debugger.stepped(4), None);
+
+        debugger.set_single_step(true);
+        assert!(debugger.single_step());
+        assert_eq!(debugger.stepped(4), Some(StopReason::Step(4)));
+
+        debugger.clear();
+        assert!(!debugger.single_step());
+    }
+}
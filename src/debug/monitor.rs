@@ -0,0 +1,188 @@
+//! Monitor Module
+//!
+//! Guest debug monitor: in-guest debuggers can single-step the rest of the guest without host cooperation,
+//! following the RISC-V Debug Specification `dcsr.step` semantics.
+//!
+//! Guest side:
+//! - Install the monitor entry point in [`DMONITOR`] (Embive custom CSR).
+//! - Set `dcsr.step` (bit 2 of [`DCSR`]) and return to the debugged code (ex.: `dret` from the monitor, or a jump).
+//! - After every retired instruction outside of the monitor, the engine enters debug mode: the next instruction
+//!   address is saved in [`DPC`], `dcsr.cause` is set to 4 (step) and execution continues at the monitor entry point.
+//! - The monitor inspects the guest (ex.: [`DSCRATCH0`] and [`DSCRATCH1`] for scratch registers) and returns
+//!   with `dret`, which leaves debug mode and jumps to [`DPC`] (can be changed by the monitor).
+//!
+//! `dcsr` reads as: `debugver` = 4, `cause`, `prv` = 3 (machine mode) and `step` (the only writable field).
+//! The debug CSRs are also accessible outside of debug mode (to arm stepping), `dret` is illegal outside of it.
+//! Without a monitor entry point, `dcsr.step` has no effect.
+//!
+//! [`DCSR`]: crate::csr::DCSR
+//! [`DPC`]: crate::csr::DPC
+//! [`DSCRATCH0`]: crate::csr::DSCRATCH0
+//! [`DSCRATCH1`]: crate::csr::DSCRATCH1
+//! [`DMONITOR`]: crate::csr::DMONITOR
+
+use crate::csr::{DCSR, DMONITOR, DPC, DSCRATCH0, DSCRATCH1};
+
+/// `dcsr` debug version (external debug support).
+const DEBUGVER: u32 = 4 << 28;
+/// `dcsr` cause field position.
+const CAUSE_SHIFT: u32 = 6;
+/// `dcsr` cause: single-step.
+pub const CAUSE_STEP: u32 = 4;
+/// `dcsr` step flag.
+const STEP: u32 = 1 << 2;
+/// `dcsr` privilege mode before entering debug mode (machine mode).
+const PRV_MACHINE: u32 = 0b11;
+
+/// Guest debug monitor state.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct Monitor {
+    /// Single-step (`dcsr.step`).
+    step: bool,
+    /// Debug mode entry cause (`dcsr.cause`).
+    cause: u32,
+    /// Debug program counter (`dpc`).
+    dpc: u32,
+    /// Debug scratch registers (`dscratch0` and `dscratch1`).
+    dscratch: [u32; 2],
+    /// Monitor entry point (None = No monitor).
+    entry: Option<u32>,
+    /// In debug mode (running the monitor).
+    active: bool,
+    /// Left debug mode with the last retired instruction (`dret`).
+    resumed: bool,
+}
+
+impl Monitor {
+    /// Check if a CSR belongs to the monitor.
+    ///
+    /// Arguments:
+    /// - `csr`: Register address.
+    pub(crate) const fn is_monitor_csr(csr: u16) -> bool {
+        matches!(csr, DCSR..=DSCRATCH1 | DMONITOR)
+    }
+
+    /// Read a monitor CSR.
+    ///
+    /// Arguments:
+    /// - `csr`: Register address.
+    ///
+    /// Returns:
+    /// - `Some(u32)`: Register value.
+    /// - `None`: Not a monitor CSR.
+    pub(crate) fn read(&self, csr: u16) -> Option<u32> {
+        match csr {
+            DCSR => Some(
+                DEBUGVER
+                    | (self.cause << CAUSE_SHIFT)
+                    | if self.step { STEP } else { 0 }
+                    | PRV_MACHINE,
+            ),
+            DPC => Some(self.dpc),
+            DSCRATCH0 => Some(self.dscratch[0]),
+            DSCRATCH1 => Some(self.dscratch[1]),
+            DMONITOR => Some(self.entry.unwrap_or(0)),
+            _ => None,
+        }
+    }
+
+    /// Write a monitor CSR (read-only `dcsr` fields are ignored).
+    ///
+    /// Arguments:
+    /// - `csr`: Register address.
+    /// - `value`: New value.
+    ///
+    /// Returns:
+    /// - `true`: Written.
+    /// - `false`: Not a monitor CSR.
+    pub(crate) fn write(&mut self, csr: u16, value: u32) -> bool {
+        match csr {
+            DCSR => self.step = value & STEP != 0,
+            DPC => self.dpc = value,
+            DSCRATCH0 => self.dscratch[0] = value,
+            DSCRATCH1 => self.dscratch[1] = value,
+            DMONITOR => self.entry = Some(value),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Enter debug mode after a retired instruction, if stepping outside of the monitor.
+    ///
+    /// Arguments:
+    /// - `pc`: Next instruction address.
+    ///
+    /// Returns:
+    /// - `Some(u32)`: Entered debug mode, continue at the monitor entry point.
+    /// - `None`: Continue at `pc`.
+    #[inline]
+    pub(crate) fn retired(&mut self, pc: u32) -> Option<u32> {
+        if core::mem::take(&mut self.resumed) || !self.step || self.active {
+            return None;
+        }
+
+        let entry = self.entry?;
+        self.dpc = pc;
+        self.cause = CAUSE_STEP;
+        self.active = true;
+        Some(entry)
+    }
+
+    /// Leave debug mode (`dret`).
+    ///
+    /// Returns:
+    /// - `Some(u32)`: Return address ([`DPC`]).
+    /// - `None`: Not in debug mode (illegal instruction).
+    pub(crate) fn dret(&mut self) -> Option<u32> {
+        if !self.active {
+            return None;
+        }
+
+        self.active = false;
+        self.resumed = true;
+        Some(self.dpc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csr() {
+        let mut monitor = Monitor::default();
+        assert_eq!(monitor.read(DCSR), Some(0x4000_0003));
+        assert_eq!(monitor.read(DMONITOR), Some(0));
+
+        assert!(monitor.write(DCSR, u32::MAX));
+        assert_eq!(monitor.read(DCSR), Some(0x4000_0007));
+        assert!(monitor.write(DSCRATCH1, 5));
+        assert_eq!(monitor.read(DSCRATCH1), Some(5));
+        assert!(!monitor.write(0x7B4, 0));
+        assert_eq!(monitor.read(0x7B4), None);
+    }
+
+    #[test]
+    fn test_step() {
+        let mut monitor = Monitor::default();
+        monitor.write(DCSR, STEP);
+
+        // No monitor
+        assert_eq!(monitor.retired(4), None);
+        assert_eq!(monitor.dret(), None);
+
+        monitor.write(DMONITOR, 0x100);
+        assert_eq!(monitor.retired(4), Some(0x100));
+        assert_eq!(monitor.read(DPC), Some(4));
+        assert_eq!(monitor.read(DCSR), Some(0x4000_0107));
+
+        // Not stepping inside the monitor (or the dret)
+        assert_eq!(monitor.retired(0x104), None);
+        assert_eq!(monitor.dret(), Some(4));
+        assert_eq!(monitor.retired(4), None);
+        assert_eq!(monitor.dret(), None);
+
+        assert_eq!(monitor.retired(8), Some(0x100));
+    }
+}
@@ -0,0 +1,292 @@
+//! Trigger Module
+//!
+//! Subset of the RISC-V Debug Specification trigger module: [`TRIGGERS`] address/data match triggers
+//! (`mcontrol`, type 2), programmed through the `tselect`, `tdata1`, `tdata2`, `tdata3` and `tinfo` CSRs.
+//!
+//! Supported `mcontrol` configurations (anything else disables the trigger, `tdata1` reads back as type 15):
+//! - `execute`, `load` and/or `store` with `select` = 0: instruction or accessed address equal to `tdata2`
+//!   (any byte of the access).
+//! - `store` with `select` = 1: stored value equal to `tdata2`.
+//! - `match` = 0 (equal), `timing` = 0 (before the instruction), `sizelo` = 0, `chain` = 0.
+//! - `action` 0 (breakpoint exception) or 1 (debug mode), both stop the engine (check [`super::StopReason::Trigger`]).
+//!
+//! The privilege mode bits (`m`, `s`, `u`) are kept but ignored, `dmode` is read-only zero.
+
+use super::Access;
+use crate::csr::{TDATA1, TDATA2, TDATA3, TINFO, TSELECT};
+
+/// Number of triggers.
+pub const TRIGGERS: usize = 4;
+
+/// `tdata1` type field position.
+const TYPE_SHIFT: u32 = 28;
+/// Trigger type: address/data match (`mcontrol`).
+const TYPE_MCONTROL: u32 = 2;
+/// Trigger type: disabled.
+const TYPE_DISABLED: u32 = 15;
+
+/// `mcontrol` hit flag.
+const HIT: u32 = 1 << 20;
+/// `mcontrol` select flag (0 = address, 1 = data).
+const SELECT: u32 = 1 << 19;
+/// `mcontrol` timing flag (0 = before, 1 = after).
+const TIMING: u32 = 1 << 18;
+/// `mcontrol` access size (low bits).
+const SIZELO: u32 = 0b11 << 16;
+/// `mcontrol` action field.
+const ACTION: u32 = 0b1111 << 12;
+/// `mcontrol` action: enter debug mode.
+const ACTION_DEBUG_MODE: u32 = 1 << 12;
+/// `mcontrol` chain flag.
+const CHAIN: u32 = 1 << 11;
+/// `mcontrol` match field.
+const MATCH: u32 = 0b1111 << 7;
+/// `mcontrol` privilege mode flags (M, S, U).
+const MODES: u32 = (1 << 6) | (1 << 4) | (1 << 3);
+/// `mcontrol` execute flag.
+const EXECUTE: u32 = 1 << 2;
+/// `mcontrol` store flag.
+const STORE: u32 = 1 << 1;
+/// `mcontrol` load flag.
+const LOAD: u32 = 1;
+
+/// `tdata1` value of a disabled trigger.
+const DISABLED: u32 = TYPE_DISABLED << TYPE_SHIFT;
+/// `tdata1` value of an unarmed address/data match trigger (reset value).
+const UNARMED: u32 = TYPE_MCONTROL << TYPE_SHIFT;
+
+/// Trigger module state.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Triggers {
+    /// Selected trigger (`tselect`).
+    select: usize,
+    /// Trigger configuration (`tdata1`).
+    tdata1: [u32; TRIGGERS],
+    /// Trigger compare value (`tdata2`).
+    tdata2: [u32; TRIGGERS],
+}
+
+impl Default for Triggers {
+    fn default() -> Self {
+        Triggers {
+            select: 0,
+            tdata1: [UNARMED; TRIGGERS],
+            tdata2: [0; TRIGGERS],
+        }
+    }
+}
+
+impl Triggers {
+    /// Check if a CSR belongs to the trigger module.
+    ///
+    /// Arguments:
+    /// - `csr`: Register address.
+    pub(crate) const fn is_trigger_csr(csr: u16) -> bool {
+        matches!(csr, TSELECT..=TINFO)
+    }
+
+    /// Read a trigger CSR.
+    ///
+    /// Arguments:
+    /// - `csr`: Register address.
+    ///
+    /// Returns:
+    /// - `Some(u32)`: Register value.
+    /// - `None`: Not a trigger CSR.
+    pub(crate) fn read(&self, csr: u16) -> Option<u32> {
+        match csr {
+            TSELECT => Some(self.select as u32),
+            TDATA1 => Some(self.tdata1[self.select]),
+            TDATA2 => Some(self.tdata2[self.select]),
+            TDATA3 => Some(0),
+            TINFO => Some(1 << TYPE_MCONTROL),
+            _ => None,
+        }
+    }
+
+    /// Write a trigger CSR. Invalid `tselect` values and `tdata3` writes are ignored.
+    ///
+    /// Arguments:
+    /// - `csr`: Register address.
+    /// - `value`: New value.
+    ///
+    /// Returns:
+    /// - `true`: Written.
+    /// - `false`: Not a writable trigger CSR.
+    pub(crate) fn write(&mut self, csr: u16, value: u32) -> bool {
+        match csr {
+            TSELECT => {
+                if (value as usize) < TRIGGERS {
+                    self.select = value as usize;
+                }
+            }
+            TDATA1 => self.tdata1[self.select] = Self::legalize(value),
+            TDATA2 => self.tdata2[self.select] = value,
+            TDATA3 => {}
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Legalize a `tdata1` value (unsupported configurations disable the trigger).
+    ///
+    /// Arguments:
+    /// - `value`: Written value.
+    const fn legalize(value: u32) -> u32 {
+        let unsupported = value >> TYPE_SHIFT != TYPE_MCONTROL
+            || value & (TIMING | SIZELO | CHAIN | MATCH) != 0
+            || value & ACTION > ACTION_DEBUG_MODE
+            || (value & SELECT != 0 && value & (EXECUTE | LOAD) != 0);
+
+        match unsupported {
+            true => DISABLED,
+            false => {
+                (TYPE_MCONTROL << TYPE_SHIFT)
+                    | (value & (HIT | SELECT | ACTION | MODES | EXECUTE | STORE | LOAD))
+            }
+        }
+    }
+
+    /// Find the first trigger matching a condition, and set its hit flag.
+    ///
+    /// Arguments:
+    /// - `flags`: Required `mcontrol` flags (any of them).
+    /// - `select`: Required `mcontrol` select flag.
+    /// - `matches`: Compare value condition.
+    ///
+    /// Returns:
+    /// - `Some(usize)`: Index of the hit trigger.
+    /// - `None`: No trigger matched.
+    fn hit(&mut self, flags: u32, select: u32, matches: impl Fn(u32) -> bool) -> Option<usize> {
+        let index = (0..TRIGGERS).find(|&index| {
+            let tdata1 = self.tdata1[index];
+            tdata1 >> TYPE_SHIFT == TYPE_MCONTROL
+                && tdata1 & flags != 0
+                && tdata1 & SELECT == select
+                && matches(self.tdata2[index])
+        })?;
+
+        self.tdata1[index] |= HIT;
+        Some(index)
+    }
+
+    /// Check the execute triggers.
+    ///
+    /// Arguments:
+    /// - `pc`: Instruction address.
+    ///
+    /// Returns:
+    /// - `Some(usize)`: Index of the hit trigger.
+    /// - `None`: No trigger matched.
+    #[inline]
+    pub(crate) fn execute(&mut self, pc: u32) -> Option<usize> {
+        self.hit(EXECUTE, 0, |tdata2| tdata2 == pc)
+    }
+
+    /// Check the load and store triggers.
+    ///
+    /// Arguments:
+    /// - `address`: Accessed address.
+    /// - `len`: Access width in bytes.
+    /// - `access`: Access kind.
+    /// - `value`: Stored value (truncated to the access width).
+    ///
+    /// Returns:
+    /// - `Some(usize)`: Index of the hit trigger.
+    /// - `None`: No trigger matched.
+    #[inline]
+    pub(crate) fn access(
+        &mut self,
+        address: u32,
+        len: u32,
+        access: Access,
+        value: u32,
+    ) -> Option<usize> {
+        let flags = match access {
+            Access::Read => LOAD,
+            Access::Write => STORE,
+            Access::ReadWrite => LOAD | STORE,
+        };
+
+        self.hit(flags, 0, |tdata2| tdata2.wrapping_sub(address) < len)
+            .or_else(|| match access {
+                Access::Write => self.hit(STORE, SELECT, |tdata2| tdata2 == value),
+                _ => None,
+            })
+    }
+
+    /// Check if any trigger is armed.
+    #[inline]
+    pub(crate) fn armed(&self) -> bool {
+        self.tdata1.iter().any(|tdata1| {
+            tdata1 >> TYPE_SHIFT == TYPE_MCONTROL && tdata1 & (EXECUTE | LOAD | STORE) != 0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csr() {
+        let mut triggers = Triggers::default();
+        assert_eq!(triggers.read(TINFO), Some(1 << 2));
+        assert_eq!(triggers.read(TDATA1), Some(UNARMED));
+
+        // Select
+        assert!(triggers.write(TSELECT, 1));
+        assert!(triggers.write(TSELECT, TRIGGERS as u32));
+        assert_eq!(triggers.read(TSELECT), Some(1));
+
+        // Unsupported (match = 2, chain, unknown type)
+        assert!(triggers.write(TDATA1, UNARMED | (2 << 7) | EXECUTE));
+        assert_eq!(triggers.read(TDATA1), Some(DISABLED));
+        assert!(triggers.write(TDATA1, UNARMED | CHAIN | EXECUTE));
+        assert_eq!(triggers.read(TDATA1), Some(DISABLED));
+        assert!(triggers.write(TDATA1, (6 << TYPE_SHIFT) | EXECUTE));
+        assert_eq!(triggers.read(TDATA1), Some(DISABLED));
+
+        // dmode is read-only zero
+        assert!(triggers.write(TDATA1, UNARMED | (1 << 27) | MODES | STORE));
+        assert_eq!(triggers.read(TDATA1), Some(UNARMED | MODES | STORE));
+        assert!(triggers.write(TDATA2, 0x1234));
+        assert_eq!(triggers.read(TDATA2), Some(0x1234));
+        assert!(triggers.armed());
+
+        assert!(!triggers.write(TINFO, 0));
+        assert_eq!(triggers.read(0x7A5), None);
+    }
+
+    #[test]
+    fn test_match() {
+        let mut triggers = Triggers::default();
+        assert!(!triggers.armed());
+
+        triggers.write(TDATA1, UNARMED | EXECUTE);
+        triggers.write(TDATA2, 0x100);
+        triggers.write(TSELECT, 1);
+        triggers.write(TDATA1, UNARMED | LOAD);
+        triggers.write(TDATA2, 0x8000_0002);
+        triggers.write(TSELECT, 2);
+        triggers.write(TDATA1, UNARMED | SELECT | STORE);
+        triggers.write(TDATA2, 0xAB);
+
+        assert_eq!(triggers.execute(0x104), None);
+        assert_eq!(triggers.execute(0x100), Some(0));
+        assert_ne!(triggers.tdata1[0] & HIT, 0);
+
+        // Address match (any accessed byte)
+        assert_eq!(triggers.access(0x8000_0000, 4, Access::Read, 0), Some(1));
+        assert_eq!(triggers.access(0x8000_0000, 2, Access::Read, 0), None);
+        assert_eq!(triggers.access(0x8000_0000, 4, Access::Write, 0), None);
+
+        // Data match
+        assert_eq!(
+            triggers.access(0x8000_0010, 1, Access::Write, 0xAB),
+            Some(2)
+        );
+        assert_eq!(triggers.access(0x8000_0010, 1, Access::Write, 0xAC), None);
+    }
+}
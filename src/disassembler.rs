@@ -0,0 +1,989 @@
+//! Disassembler Module
+//!
+//! Decodes raw instructions into a structured [`DecodedInstruction`], for hosts building debuggers, trace viewers
+//! or profilers on top of the engine, with the same decoding rules as the interpreter (check [`decode`]):
+//! - Instructions are decoded for the enabled extensions (ex.: `mul` needs the `m_extension` feature),
+//!   anything the engine wouldn't execute is `None`.
+//! - Compressed instructions are decoded as their 32-bit equivalent (ex.: `c.li a0, 1` as `addi a0, zero, 1`).
+//! - Vector instructions aren't decoded.
+//!
+//! Instructions are displayed as text ([`core::fmt::Display`], canonical assembly with ABI register names),
+//! without allocating (ex.: `write!` into a fixed buffer, or `to_string()` with the `alloc` feature).
+//!
+//! ```
+//! use embive::disassembler::{self, DecodedInstruction, Mnemonic};
+//!
+//! let code = &[
+//!     0x13, 0x05, 0x15, 0x00, // addi a0, a0, 1
+//!     0x23, 0xa2, 0xa5, 0x00, // sw   a0, 4(a1)
+//!     0xff, 0xff, 0xff, 0xff, // (illegal)
+//! ];
+//!
+//! let mut listing = disassembler::disassemble(code, 0x0);
+//! let (address, decoded) = listing.next().unwrap();
+//! assert_eq!(address, 0x0);
+//! assert_eq!(
+//!     decoded,
+//!     Some(DecodedInstruction::Immediate { mnemonic: Mnemonic::Addi, rd: 10, rs1: 10, imm: 1 })
+//! );
+//!
+//! let (address, decoded) = listing.next().unwrap();
+//! assert_eq!(address, 0x4);
+//! assert_eq!(decoded.unwrap().mnemonic().name(), "sw");
+//!
+//! assert_eq!(listing.next(), Some((0x8, None)));
+//! assert_eq!(listing.next(), None);
+//! ```
+
+use core::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "a_extension")]
+use crate::instruction::amo;
+#[cfg(feature = "c_extension")]
+use crate::instruction::compressed;
+use crate::instruction::format::{TypeB, TypeI, TypeJ, TypeR, TypeS, TypeU};
+use crate::instruction::{branch, load, misc_mem, op, op_imm, store, system};
+use crate::instruction::{
+    instruction_size, AUI_PC_OPCODE, BRANCH_OPCODE, JALR_OPCODE, JAL_OPCODE, LOAD_OPCODE,
+    LUI_OPCODE, MISC_MEM_OPCODE, OP_IMM_OPCODE, OP_OPCODE, STORE_OPCODE, SYSTEM_OPCODE,
+};
+
+#[cfg(feature = "a_extension")]
+use crate::instruction::AMO_OPCODE;
+
+/// ABI register names, by register index.
+const REGISTER_NAMES: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+    "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+    "t5", "t6",
+];
+
+/// Instruction Mnemonic Enum
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Mnemonic {
+    /// Load upper immediate.
+    Lui,
+    /// Add upper immediate to program counter.
+    Auipc,
+    /// Jump and link.
+    Jal,
+    /// Jump and link register.
+    Jalr,
+    /// Branch if equal.
+    Beq,
+    /// Branch if not equal.
+    Bne,
+    /// Branch if less than.
+    Blt,
+    /// Branch if greater or equal.
+    Bge,
+    /// Branch if less than (unsigned).
+    Bltu,
+    /// Branch if greater or equal (unsigned).
+    Bgeu,
+    /// Load byte.
+    Lb,
+    /// Load half-word.
+    Lh,
+    /// Load word.
+    Lw,
+    /// Load byte (unsigned).
+    Lbu,
+    /// Load half-word (unsigned).
+    Lhu,
+    /// Store byte.
+    Sb,
+    /// Store half-word.
+    Sh,
+    /// Store word.
+    Sw,
+    /// Add immediate.
+    Addi,
+    /// Set less than immediate.
+    Slti,
+    /// Set less than immediate (unsigned).
+    Sltiu,
+    /// Xor immediate.
+    Xori,
+    /// Or immediate.
+    Ori,
+    /// And immediate.
+    Andi,
+    /// Shift left logical immediate.
+    Slli,
+    /// Shift right logical immediate.
+    Srli,
+    /// Shift right arithmetic immediate.
+    Srai,
+    /// Add.
+    Add,
+    /// Subtract.
+    Sub,
+    /// Shift left logical.
+    Sll,
+    /// Set less than.
+    Slt,
+    /// Set less than (unsigned).
+    Sltu,
+    /// Xor.
+    Xor,
+    /// Shift right logical.
+    Srl,
+    /// Shift right arithmetic.
+    Sra,
+    /// Or.
+    Or,
+    /// And.
+    And,
+    /// Multiply.
+    Mul,
+    /// Multiply high.
+    Mulh,
+    /// Multiply high (signed, unsigned).
+    Mulhsu,
+    /// Multiply high (unsigned).
+    Mulhu,
+    /// Divide.
+    Div,
+    /// Divide (unsigned).
+    Divu,
+    /// Remainder.
+    Rem,
+    /// Remainder (unsigned).
+    Remu,
+    /// Memory fence.
+    Fence,
+    /// Instruction fetch fence.
+    FenceI,
+    /// Pause hint (Zihintpause).
+    Pause,
+    /// Cache block invalidate.
+    CboInval,
+    /// Cache block clean.
+    CboClean,
+    /// Cache block flush.
+    CboFlush,
+    /// Cache block zero.
+    CboZero,
+    /// Environment call (syscall).
+    Ecall,
+    /// Environment break (halt).
+    Ebreak,
+    /// Return from the guest trap handler.
+    Mret,
+    /// Return from the debug monitor.
+    Dret,
+    /// CSR read and write.
+    Csrrw,
+    /// CSR read and set bits.
+    Csrrs,
+    /// CSR read and clear bits.
+    Csrrc,
+    /// CSR read and write immediate.
+    Csrrwi,
+    /// CSR read and set bits immediate.
+    Csrrsi,
+    /// CSR read and clear bits immediate.
+    Csrrci,
+    /// Load reserved.
+    LrW,
+    /// Store conditional.
+    ScW,
+    /// Atomic swap.
+    AmoswapW,
+    /// Atomic add.
+    AmoaddW,
+    /// Atomic xor.
+    AmoxorW,
+    /// Atomic and.
+    AmoandW,
+    /// Atomic or.
+    AmoorW,
+    /// Atomic minimum.
+    AmominW,
+    /// Atomic maximum.
+    AmomaxW,
+    /// Atomic minimum (unsigned).
+    AmominuW,
+    /// Atomic maximum (unsigned).
+    AmomaxuW,
+    /// Atomic compare-and-swap (word).
+    AmocasW,
+    /// Atomic compare-and-swap (doubleword, register pairs).
+    AmocasD,
+}
+
+impl Mnemonic {
+    /// Get the assembly name of the instruction (ex.: `"addi"`).
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Mnemonic::Lui => "lui",
+            Mnemonic::Auipc => "auipc",
+            Mnemonic::Jal => "jal",
+            Mnemonic::Jalr => "jalr",
+            Mnemonic::Beq => "beq",
+            Mnemonic::Bne => "bne",
+            Mnemonic::Blt => "blt",
+            Mnemonic::Bge => "bge",
+            Mnemonic::Bltu => "bltu",
+            Mnemonic::Bgeu => "bgeu",
+            Mnemonic::Lb => "lb",
+            Mnemonic::Lh => "lh",
+            Mnemonic::Lw => "lw",
+            Mnemonic::Lbu => "lbu",
+            Mnemonic::Lhu => "lhu",
+            Mnemonic::Sb => "sb",
+            Mnemonic::Sh => "sh",
+            Mnemonic::Sw => "sw",
+            Mnemonic::Addi => "addi",
+            Mnemonic::Slti => "slti",
+            Mnemonic::Sltiu => "sltiu",
+            Mnemonic::Xori => "xori",
+            Mnemonic::Ori => "ori",
+            Mnemonic::Andi => "andi",
+            Mnemonic::Slli => "slli",
+            Mnemonic::Srli => "srli",
+            Mnemonic::Srai => "srai",
+            Mnemonic::Add => "add",
+            Mnemonic::Sub => "sub",
+            Mnemonic::Sll => "sll",
+            Mnemonic::Slt => "slt",
+            Mnemonic::Sltu => "sltu",
+            Mnemonic::Xor => "xor",
+            Mnemonic::Srl => "srl",
+            Mnemonic::Sra => "sra",
+            Mnemonic::Or => "or",
+            Mnemonic::And => "and",
+            Mnemonic::Mul => "mul",
+            Mnemonic::Mulh => "mulh",
+            Mnemonic::Mulhsu => "mulhsu",
+            Mnemonic::Mulhu => "mulhu",
+            Mnemonic::Div => "div",
+            Mnemonic::Divu => "divu",
+            Mnemonic::Rem => "rem",
+            Mnemonic::Remu => "remu",
+            Mnemonic::Fence => "fence",
+            Mnemonic::FenceI => "fence.i",
+            Mnemonic::Pause => "pause",
+            Mnemonic::CboInval => "cbo.inval",
+            Mnemonic::CboClean => "cbo.clean",
+            Mnemonic::CboFlush => "cbo.flush",
+            Mnemonic::CboZero => "cbo.zero",
+            Mnemonic::Ecall => "ecall",
+            Mnemonic::Ebreak => "ebreak",
+            Mnemonic::Mret => "mret",
+            Mnemonic::Dret => "dret",
+            Mnemonic::Csrrw => "csrrw",
+            Mnemonic::Csrrs => "csrrs",
+            Mnemonic::Csrrc => "csrrc",
+            Mnemonic::Csrrwi => "csrrwi",
+            Mnemonic::Csrrsi => "csrrsi",
+            Mnemonic::Csrrci => "csrrci",
+            Mnemonic::LrW => "lr.w",
+            Mnemonic::ScW => "sc.w",
+            Mnemonic::AmoswapW => "amoswap.w",
+            Mnemonic::AmoaddW => "amoadd.w",
+            Mnemonic::AmoxorW => "amoxor.w",
+            Mnemonic::AmoandW => "amoand.w",
+            Mnemonic::AmoorW => "amoor.w",
+            Mnemonic::AmominW => "amomin.w",
+            Mnemonic::AmomaxW => "amomax.w",
+            Mnemonic::AmominuW => "amominu.w",
+            Mnemonic::AmomaxuW => "amomaxu.w",
+            Mnemonic::AmocasW => "amocas.w",
+            Mnemonic::AmocasD => "amocas.d",
+        }
+    }
+}
+
+impl Display for Mnemonic {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Decoded Instruction Enum (check [`decode`])
+///
+/// Register fields are register indexes (0-31), offsets are relative to the instruction address.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DecodedInstruction {
+    /// Upper immediate (`lui`, `auipc`).
+    Upper {
+        /// Instruction.
+        mnemonic: Mnemonic,
+        /// Destination register.
+        rd: usize,
+        /// Immediate (already shifted, lower 12 bits are zero).
+        imm: i32,
+    },
+    /// Jump and link (`jal`).
+    Jal {
+        /// Link register.
+        rd: usize,
+        /// Jump offset.
+        offset: i32,
+    },
+    /// Jump and link register (`jalr`).
+    Jalr {
+        /// Link register.
+        rd: usize,
+        /// Base address register.
+        rs1: usize,
+        /// Offset from the base address.
+        offset: i32,
+    },
+    /// Conditional branch (ex.: `beq`).
+    Branch {
+        /// Instruction.
+        mnemonic: Mnemonic,
+        /// First compared register.
+        rs1: usize,
+        /// Second compared register.
+        rs2: usize,
+        /// Branch offset.
+        offset: i32,
+    },
+    /// Load (ex.: `lw`).
+    Load {
+        /// Instruction.
+        mnemonic: Mnemonic,
+        /// Destination register.
+        rd: usize,
+        /// Base address register.
+        rs1: usize,
+        /// Offset from the base address.
+        offset: i32,
+    },
+    /// Store (ex.: `sw`).
+    Store {
+        /// Instruction.
+        mnemonic: Mnemonic,
+        /// Base address register.
+        rs1: usize,
+        /// Stored value register.
+        rs2: usize,
+        /// Offset from the base address.
+        offset: i32,
+    },
+    /// Operation with an immediate (ex.: `addi`).
+    Immediate {
+        /// Instruction.
+        mnemonic: Mnemonic,
+        /// Destination register.
+        rd: usize,
+        /// Source register.
+        rs1: usize,
+        /// Immediate (shift amount for shifts).
+        imm: i32,
+    },
+    /// Operation between registers (ex.: `add`, `mul`).
+    Register {
+        /// Instruction.
+        mnemonic: Mnemonic,
+        /// Destination register.
+        rd: usize,
+        /// First source register.
+        rs1: usize,
+        /// Second source register.
+        rs2: usize,
+    },
+    /// Memory fence (`fence`).
+    Fence {
+        /// Predecessor set (bits: input, output, read, write).
+        pred: u8,
+        /// Successor set (bits: input, output, read, write).
+        succ: u8,
+    },
+    /// Cache block operation (ex.: `cbo.zero`).
+    Cache {
+        /// Instruction.
+        mnemonic: Mnemonic,
+        /// Block address register.
+        rs1: usize,
+    },
+    /// Instruction without operands (ex.: `ecall`, `fence.i`).
+    System {
+        /// Instruction.
+        mnemonic: Mnemonic,
+    },
+    /// CSR access (ex.: `csrrw`).
+    Csr {
+        /// Instruction.
+        mnemonic: Mnemonic,
+        /// Destination register.
+        rd: usize,
+        /// Source register (unsigned immediate for the immediate variants, ex.: `csrrwi`).
+        rs1: usize,
+        /// CSR address.
+        csr: u16,
+    },
+    /// Atomic memory operation (ex.: `amoadd.w`).
+    Atomic {
+        /// Instruction.
+        mnemonic: Mnemonic,
+        /// Destination register.
+        rd: usize,
+        /// Address register.
+        rs1: usize,
+        /// Source register (zero register for `lr.w`).
+        rs2: usize,
+        /// Acquire ordering bit.
+        aq: bool,
+        /// Release ordering bit.
+        rl: bool,
+    },
+}
+
+impl DecodedInstruction {
+    /// Get the instruction mnemonic.
+    pub fn mnemonic(&self) -> Mnemonic {
+        match *self {
+            DecodedInstruction::Jal { .. } => Mnemonic::Jal,
+            DecodedInstruction::Jalr { .. } => Mnemonic::Jalr,
+            DecodedInstruction::Fence { .. } => Mnemonic::Fence,
+            DecodedInstruction::Upper { mnemonic, .. }
+            | DecodedInstruction::Branch { mnemonic, .. }
+            | DecodedInstruction::Load { mnemonic, .. }
+            | DecodedInstruction::Store { mnemonic, .. }
+            | DecodedInstruction::Immediate { mnemonic, .. }
+            | DecodedInstruction::Register { mnemonic, .. }
+            | DecodedInstruction::Cache { mnemonic, .. }
+            | DecodedInstruction::System { mnemonic }
+            | DecodedInstruction::Csr { mnemonic, .. }
+            | DecodedInstruction::Atomic { mnemonic, .. } => mnemonic,
+        }
+    }
+}
+
+/// Register name, for display.
+fn name(register: usize) -> &'static str {
+    REGISTER_NAMES[register & 0b1_1111]
+}
+
+/// Write a fence set (ex.: `rw`, `0` if empty).
+fn fence_set(f: &mut Formatter, set: u8) -> fmt::Result {
+    if set & 0b1111 == 0 {
+        return f.write_str("0");
+    }
+
+    for (bit, access) in ["i", "o", "r", "w"].iter().enumerate() {
+        if set & (0b1000 >> bit) != 0 {
+            f.write_str(access)?;
+        }
+    }
+
+    Ok(())
+}
+
+impl Display for DecodedInstruction {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mnemonic = self.mnemonic();
+        match *self {
+            DecodedInstruction::Upper { rd, imm, .. } => {
+                write!(f, "{mnemonic} {}, {:#x}", name(rd), (imm as u32) >> 12)
+            }
+            DecodedInstruction::Jal { rd, offset } => {
+                write!(f, "{mnemonic} {}, {offset}", name(rd))
+            }
+            DecodedInstruction::Jalr { rd, rs1, offset }
+            | DecodedInstruction::Load {
+                rd, rs1, offset, ..
+            } => write!(f, "{mnemonic} {}, {offset}({})", name(rd), name(rs1)),
+            DecodedInstruction::Branch {
+                rs1, rs2, offset, ..
+            } => write!(f, "{mnemonic} {}, {}, {offset}", name(rs1), name(rs2)),
+            DecodedInstruction::Store {
+                rs1, rs2, offset, ..
+            } => write!(f, "{mnemonic} {}, {offset}({})", name(rs2), name(rs1)),
+            DecodedInstruction::Immediate { rd, rs1, imm, .. } => {
+                write!(f, "{mnemonic} {}, {}, {imm}", name(rd), name(rs1))
+            }
+            DecodedInstruction::Register { rd, rs1, rs2, .. } => {
+                write!(f, "{mnemonic} {}, {}, {}", name(rd), name(rs1), name(rs2))
+            }
+            DecodedInstruction::Fence { pred, succ } => {
+                write!(f, "{mnemonic} ")?;
+                fence_set(f, pred)?;
+                f.write_str(", ")?;
+                fence_set(f, succ)
+            }
+            DecodedInstruction::Cache { rs1, .. } => write!(f, "{mnemonic} 0({})", name(rs1)),
+            DecodedInstruction::System { .. } => write!(f, "{mnemonic}"),
+            DecodedInstruction::Csr { rd, rs1, csr, .. } => match mnemonic {
+                Mnemonic::Csrrwi | Mnemonic::Csrrsi | Mnemonic::Csrrci => {
+                    write!(f, "{mnemonic} {}, {csr:#x}, {rs1}", name(rd))
+                }
+                _ => write!(f, "{mnemonic} {}, {csr:#x}, {}", name(rd), name(rs1)),
+            },
+            DecodedInstruction::Atomic {
+                rd,
+                rs1,
+                rs2,
+                aq,
+                rl,
+                ..
+            } => {
+                write!(f, "{mnemonic}")?;
+                if aq {
+                    f.write_str(".aq")?;
+                }
+                if rl {
+                    f.write_str(".rl")?;
+                }
+
+                if mnemonic == Mnemonic::LrW {
+                    write!(f, " {}, ({})", name(rd), name(rs1))
+                } else {
+                    write!(f, " {}, {}, ({})", name(rd), name(rs2), name(rs1))
+                }
+            }
+        }
+    }
+}
+
+/// Decode an instruction.
+///
+/// Arguments:
+/// - `data`: `u32` value representing the instruction (only the lower 16 bits are used for compressed instructions).
+///
+/// Returns:
+/// - `Some(DecodedInstruction)`: The decoded instruction.
+/// - `None`: Illegal, reserved or not supported instruction (ex.: disabled extension, or vector instruction).
+pub fn decode(data: u32) -> Option<DecodedInstruction> {
+    #[cfg(feature = "c_extension")]
+    if data & 0b11 != 0b11 {
+        return decode_compressed(data & 0xFFFF);
+    }
+
+    let decoded = match (data & 0x7F) as u8 {
+        LUI_OPCODE | AUI_PC_OPCODE => {
+            let inst = TypeU::from(data);
+            DecodedInstruction::Upper {
+                mnemonic: if (data & 0x7F) as u8 == LUI_OPCODE {
+                    Mnemonic::Lui
+                } else {
+                    Mnemonic::Auipc
+                },
+                rd: inst.rd,
+                imm: inst.imm,
+            }
+        }
+        JAL_OPCODE => {
+            let inst = TypeJ::from(data);
+            DecodedInstruction::Jal {
+                rd: inst.rd,
+                offset: inst.imm,
+            }
+        }
+        JALR_OPCODE => {
+            let inst = TypeI::from(data);
+            DecodedInstruction::Jalr {
+                rd: inst.rd,
+                rs1: inst.rs1,
+                offset: inst.imm,
+            }
+        }
+        BRANCH_OPCODE => {
+            let inst = TypeB::from(data);
+            let mnemonic = match inst.funct3 {
+                branch::BEQ_FUNCT3 => Mnemonic::Beq,
+                branch::BNE_FUNCT3 => Mnemonic::Bne,
+                branch::BLT_FUNCT3 => Mnemonic::Blt,
+                branch::BGE_FUNCT3 => Mnemonic::Bge,
+                branch::BLTU_FUNCT3 => Mnemonic::Bltu,
+                branch::BGEU_FUNCT3 => Mnemonic::Bgeu,
+                _ => return None,
+            };
+            DecodedInstruction::Branch {
+                mnemonic,
+                rs1: inst.rs1,
+                rs2: inst.rs2,
+                offset: inst.imm,
+            }
+        }
+        LOAD_OPCODE => {
+            let inst = TypeI::from(data);
+            let mnemonic = match inst.funct3 {
+                load::LB_FUNCT3 => Mnemonic::Lb,
+                load::LH_FUNCT3 => Mnemonic::Lh,
+                load::LW_FUNCT3 => Mnemonic::Lw,
+                load::LBU_FUNCT3 => Mnemonic::Lbu,
+                load::LHU_FUNCT3 => Mnemonic::Lhu,
+                _ => return None,
+            };
+            DecodedInstruction::Load {
+                mnemonic,
+                rd: inst.rd,
+                rs1: inst.rs1,
+                offset: inst.imm,
+            }
+        }
+        STORE_OPCODE => {
+            let inst = TypeS::from(data);
+            let mnemonic = match inst.funct3 {
+                store::SB_FUNCT3 => Mnemonic::Sb,
+                store::SH_FUNCT3 => Mnemonic::Sh,
+                store::SW_FUNCT3 => Mnemonic::Sw,
+                _ => return None,
+            };
+            DecodedInstruction::Store {
+                mnemonic,
+                rs1: inst.rs1,
+                rs2: inst.rs2,
+                offset: inst.imm,
+            }
+        }
+        OP_IMM_OPCODE => {
+            let inst = TypeI::from(data);
+            let (mnemonic, imm) = match inst.funct3 {
+                op_imm::ADDI_FUNC3 => (Mnemonic::Addi, inst.imm),
+                op_imm::SLTI_FUNC3 => (Mnemonic::Slti, inst.imm),
+                op_imm::SLTIU_FUNC3 => (Mnemonic::Sltiu, inst.imm),
+                op_imm::XORI_FUNC3 => (Mnemonic::Xori, inst.imm),
+                op_imm::ORI_FUNC3 => (Mnemonic::Ori, inst.imm),
+                op_imm::ANDI_FUNC3 => (Mnemonic::Andi, inst.imm),
+                op_imm::SLLI_FUNC3 => (Mnemonic::Slli, inst.imm & 0b1_1111),
+                op_imm::SRLI_SRAI_FUNC3 if inst.imm & (0b1 << 10) != 0 => {
+                    (Mnemonic::Srai, inst.imm & 0b1_1111)
+                }
+                op_imm::SRLI_SRAI_FUNC3 => (Mnemonic::Srli, inst.imm & 0b1_1111),
+                _ => return None,
+            };
+            DecodedInstruction::Immediate {
+                mnemonic,
+                rd: inst.rd,
+                rs1: inst.rs1,
+                imm,
+            }
+        }
+        OP_OPCODE => {
+            let inst = TypeR::from(data);
+            let mnemonic = match inst.funct10 {
+                op::ADD_FUNCT10 => Mnemonic::Add,
+                op::SUB_FUNCT10 => Mnemonic::Sub,
+                op::SLL_FUNCT10 => Mnemonic::Sll,
+                op::SLT_FUNCT10 => Mnemonic::Slt,
+                op::SLTU_FUNCT10 => Mnemonic::Sltu,
+                op::XOR_FUNCT10 => Mnemonic::Xor,
+                op::SRL_FUNCT10 => Mnemonic::Srl,
+                op::SRA_FUNCT10 => Mnemonic::Sra,
+                op::OR_FUNCT10 => Mnemonic::Or,
+                op::AND_FUNCT10 => Mnemonic::And,
+                #[cfg(feature = "m_extension")]
+                op::MUL_FUNCT10 => Mnemonic::Mul,
+                #[cfg(feature = "m_extension")]
+                op::MULH_FUNCT10 => Mnemonic::Mulh,
+                #[cfg(feature = "m_extension")]
+                op::MULHSU_FUNCT10 => Mnemonic::Mulhsu,
+                #[cfg(feature = "m_extension")]
+                op::MULHU_FUNCT10 => Mnemonic::Mulhu,
+                #[cfg(feature = "m_extension")]
+                op::DIV_FUNCT10 => Mnemonic::Div,
+                #[cfg(feature = "m_extension")]
+                op::DIVU_FUNCT10 => Mnemonic::Divu,
+                #[cfg(feature = "m_extension")]
+                op::REM_FUNCT10 => Mnemonic::Rem,
+                #[cfg(feature = "m_extension")]
+                op::REMU_FUNCT10 => Mnemonic::Remu,
+                _ => return None,
+            };
+            DecodedInstruction::Register {
+                mnemonic,
+                rd: inst.rd,
+                rs1: inst.rs1,
+                rs2: inst.rs2,
+            }
+        }
+        MISC_MEM_OPCODE => decode_misc_mem(TypeI::from(data))?,
+        SYSTEM_OPCODE => decode_system(TypeI::from(data))?,
+        #[cfg(feature = "a_extension")]
+        AMO_OPCODE => decode_amo(TypeR::from(data))?,
+        _ => return None,
+    };
+
+    Some(decoded)
+}
+
+/// Decode a miscellaneous memory instruction (fences and cache block operations).
+fn decode_misc_mem(inst: TypeI) -> Option<DecodedInstruction> {
+    let decoded = match inst.funct3 {
+        misc_mem::FENCE_FUNCT3 => {
+            let pred = ((inst.imm >> 4) & 0b1111) as u8;
+            let succ = (inst.imm & 0b1111) as u8;
+            if inst.imm >> 8 == 0
+                && inst.rd == 0
+                && inst.rs1 == 0
+                && pred == misc_mem::FENCE_W as u8
+                && succ == 0
+            {
+                DecodedInstruction::System {
+                    mnemonic: Mnemonic::Pause,
+                }
+            } else {
+                DecodedInstruction::Fence { pred, succ }
+            }
+        }
+        misc_mem::FENCE_I_FUNCT3 => DecodedInstruction::System {
+            mnemonic: Mnemonic::FenceI,
+        },
+        misc_mem::CBO_FUNCT3 if inst.rd == 0 => DecodedInstruction::Cache {
+            mnemonic: match inst.imm {
+                misc_mem::CBO_INVAL_IMM => Mnemonic::CboInval,
+                misc_mem::CBO_CLEAN_IMM => Mnemonic::CboClean,
+                misc_mem::CBO_FLUSH_IMM => Mnemonic::CboFlush,
+                misc_mem::CBO_ZERO_IMM => Mnemonic::CboZero,
+                _ => return None,
+            },
+            rs1: inst.rs1,
+        },
+        _ => return None,
+    };
+
+    Some(decoded)
+}
+
+/// Decode a system instruction (environment calls, trap returns and CSR accesses).
+fn decode_system(inst: TypeI) -> Option<DecodedInstruction> {
+    let mnemonic = match inst.funct3 {
+        system::EBREAK_ECALL_FUNCT3 => {
+            let mnemonic = match inst.imm {
+                system::ECALL_IMM => Mnemonic::Ecall,
+                system::EBREAK_IMM => Mnemonic::Ebreak,
+                system::MRET_IMM if inst.rd == 0 && inst.rs1 == 0 => Mnemonic::Mret,
+                #[cfg(feature = "debugger")]
+                system::DRET_IMM if inst.rd == 0 && inst.rs1 == 0 => Mnemonic::Dret,
+                _ => return None,
+            };
+
+            return Some(DecodedInstruction::System { mnemonic });
+        }
+        system::CSRRW_FUNCT3 => Mnemonic::Csrrw,
+        system::CSRRS_FUNCT3 => Mnemonic::Csrrs,
+        system::CSRRC_FUNCT3 => Mnemonic::Csrrc,
+        system::CSRRWI_FUNCT3 => Mnemonic::Csrrwi,
+        system::CSRRSI_FUNCT3 => Mnemonic::Csrrsi,
+        system::CSRRCI_FUNCT3 => Mnemonic::Csrrci,
+        _ => return None,
+    };
+
+    Some(DecodedInstruction::Csr {
+        mnemonic,
+        rd: inst.rd,
+        rs1: inst.rs1,
+        csr: (inst.imm & 0xFFF) as u16,
+    })
+}
+
+/// Decode an atomic memory operation.
+#[cfg(feature = "a_extension")]
+fn decode_amo(inst: TypeR) -> Option<DecodedInstruction> {
+    let funct5 = (inst.funct10 >> 5) as u8;
+    let mnemonic = match ((inst.funct10 & 0b111) as u8, funct5) {
+        (amo::WORD_WIDTH, amo::LR_FUNCT5) => Mnemonic::LrW,
+        (amo::WORD_WIDTH, amo::SC_FUNCT5) => Mnemonic::ScW,
+        (amo::WORD_WIDTH, amo::AMOSWAP_FUNCT5) => Mnemonic::AmoswapW,
+        (amo::WORD_WIDTH, amo::AMOADD_FUNCT5) => Mnemonic::AmoaddW,
+        (amo::WORD_WIDTH, amo::AMOXOR_FUNCT5) => Mnemonic::AmoxorW,
+        (amo::WORD_WIDTH, amo::AMOAND_FUNCT5) => Mnemonic::AmoandW,
+        (amo::WORD_WIDTH, amo::AMOOR_FUNCT5) => Mnemonic::AmoorW,
+        (amo::WORD_WIDTH, amo::AMOMIN_FUNCT5) => Mnemonic::AmominW,
+        (amo::WORD_WIDTH, amo::AMOMAX_FUNCT5) => Mnemonic::AmomaxW,
+        (amo::WORD_WIDTH, amo::AMOMINU_FUNCT5) => Mnemonic::AmominuW,
+        (amo::WORD_WIDTH, amo::AMOMAXU_FUNCT5) => Mnemonic::AmomaxuW,
+        #[cfg(feature = "zacas")]
+        (amo::WORD_WIDTH, amo::AMOCAS_FUNCT5) => Mnemonic::AmocasW,
+        #[cfg(feature = "zacas")]
+        (amo::DOUBLE_WIDTH, amo::AMOCAS_FUNCT5) => Mnemonic::AmocasD,
+        _ => return None,
+    };
+
+    Some(DecodedInstruction::Atomic {
+        mnemonic,
+        rd: inst.rd,
+        rs1: inst.rs1,
+        rs2: inst.rs2,
+        aq: inst.funct10 & (0b1 << 4) != 0,
+        rl: inst.funct10 & (0b1 << 3) != 0,
+    })
+}
+
+/// Decode a compressed instruction.
+/// Control transfers are decoded directly (they aren't expanded), everything else as its expanded equivalent.
+#[cfg(feature = "c_extension")]
+fn decode_compressed(data: u32) -> Option<DecodedInstruction> {
+    let funct3 = data >> 13;
+    let decoded = match (data & 0b11, funct3) {
+        // c.jal, c.j
+        (0b01, 0b001 | 0b101) => DecodedInstruction::Jal {
+            rd: (funct3 == 0b001) as usize,
+            offset: compressed::cj_imm(data),
+        },
+        // c.beqz, c.bnez
+        (0b01, 0b110 | 0b111) => DecodedInstruction::Branch {
+            mnemonic: if funct3 == 0b110 {
+                Mnemonic::Beq
+            } else {
+                Mnemonic::Bne
+            },
+            rs1: compressed::compact(data >> 7),
+            rs2: 0,
+            offset: compressed::cb_imm(data),
+        },
+        // c.jr, c.jalr
+        (0b10, 0b100) if (data >> 2) & 0b1_1111 == 0 && (data >> 7) & 0b1_1111 != 0 => {
+            DecodedInstruction::Jalr {
+                rd: ((data >> 12) & 0b1) as usize,
+                rs1: compressed::reg(data >> 7),
+                offset: 0,
+            }
+        }
+        _ => return decode(compressed::expand(data)?),
+    };
+
+    Some(decoded)
+}
+
+/// Instruction listing of a code region (check [`disassemble`]).
+#[derive(Debug, Clone)]
+pub struct Disassembly<'a> {
+    /// Remaining code.
+    code: &'a [u8],
+    /// Address of the next instruction.
+    address: u32,
+}
+
+impl Iterator for Disassembly<'_> {
+    type Item = (u32, Option<DecodedInstruction>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let half = u16::from_le_bytes(*self.code.first_chunk::<2>()?) as u32;
+        let size = instruction_size(half);
+        let data = match size {
+            2 => half,
+            _ => u32::from_le_bytes(*self.code.first_chunk::<4>()?),
+        };
+
+        let address = self.address;
+        self.code = &self.code[size as usize..];
+        self.address = self.address.wrapping_add(size);
+        Some((address, decode(data)))
+    }
+}
+
+/// Disassemble a code region, instruction by instruction (ex.: the guest code, for a listing).
+///
+/// Arguments:
+/// - `code`: Code bytes (a trailing partial instruction is ignored).
+/// - `address`: Address of the first instruction.
+///
+/// Returns:
+/// - `Disassembly`: Iterator over the address and decoded instruction (`None` if illegal) of each instruction.
+pub fn disassemble(code: &[u8], address: u32) -> Disassembly<'_> {
+    Disassembly { code, address }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::string::ToString;
+
+    fn text(data: u32) -> std::string::String {
+        decode(data).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(
+            decode(0x0025_1503), // lh   a0, 2(a0)
+            Some(DecodedInstruction::Load {
+                mnemonic: Mnemonic::Lh,
+                rd: 10,
+                rs1: 10,
+                offset: 2
+            })
+        );
+        assert_eq!(
+            decode(0xfe02_9ee3), // bnez t0, -4
+            Some(DecodedInstruction::Branch {
+                mnemonic: Mnemonic::Bne,
+                rs1: 5,
+                rs2: 0,
+                offset: -4
+            })
+        );
+        assert_eq!(
+            decode(0x4030_d093), // srai ra, ra, 3
+            Some(DecodedInstruction::Immediate {
+                mnemonic: Mnemonic::Srai,
+                rd: 1,
+                rs1: 1,
+                imm: 3
+            })
+        );
+
+        // Illegal, reserved
+        assert_eq!(decode(0xffff_ffff), None);
+        assert_eq!(decode(0x0000_3003), None); // (ld)
+        assert_eq!(decode(0x0020_0073), None); // (uret)
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(text(0x8000_0537), "lui a0, 0x80000");
+        assert_eq!(text(0x0080_00ef), "jal ra, 8");
+        assert_eq!(text(0x0000_8067), "jalr zero, 0(ra)");
+        assert_eq!(text(0xfe02_9ee3), "bne t0, zero, -4");
+        assert_eq!(text(0x00a5_a223), "sw a0, 4(a1)");
+        assert_eq!(text(0xc180_8193), "addi gp, ra, -1000");
+        assert_eq!(text(0x4030_d093), "srai ra, ra, 3");
+        assert_eq!(text(0x4032_50b3), "sra ra, tp, gp");
+        assert_eq!(text(0x0ff0_000f), "fence iorw, iorw");
+        assert_eq!(text(0x0100_000f), "pause");
+        assert_eq!(text(0x0000_100f), "fence.i");
+        assert_eq!(text(0x0045_200f), "cbo.zero 0(a0)");
+        assert_eq!(text(0x0000_0073), "ecall");
+        assert_eq!(text(0x0010_0073), "ebreak");
+        assert_eq!(text(0x3020_0073), "mret");
+        assert_eq!(text(0xc000_2573), "csrrs a0, 0xc00, zero");
+        assert_eq!(text(0x3002_e073), "csrrsi zero, 0x300, 5");
+    }
+
+    #[cfg(feature = "m_extension")]
+    #[test]
+    fn test_m_extension() {
+        assert_eq!(text(0x02c5_8533), "mul a0, a1, a2");
+        assert_eq!(text(0x02c5_f533), "remu a0, a1, a2");
+    }
+
+    #[cfg(not(feature = "m_extension"))]
+    #[test]
+    fn test_disabled_extension() {
+        assert_eq!(decode(0x02c5_8533), None); // (mul a0, a1, a2)
+    }
+
+    #[cfg(feature = "a_extension")]
+    #[test]
+    fn test_atomics() {
+        assert_eq!(text(0x1000_22af), "lr.w t0, (zero)");
+        assert_eq!(text(0x1850_232f), "sc.w t1, t0, (zero)");
+        assert_eq!(text(0x0650_232f), "amoadd.w.aq.rl t1, t0, (zero)");
+        assert_eq!(decode(0x0050_332f), None); // (amoadd.d)
+    }
+
+    #[cfg(feature = "c_extension")]
+    #[test]
+    fn test_compressed() {
+        assert_eq!(text(0x4505), "addi a0, zero, 1"); // c.li   a0, 1
+        assert_eq!(text(0xc011), "beq s0, zero, 4"); // c.beqz s0, 4
+        assert_eq!(text(0x2011), "jal ra, 4"); // c.jal  4
+        assert_eq!(text(0x9582), "jalr ra, 0(a1)"); // c.jalr a1
+        assert_eq!(text(0x8082), "jalr zero, 0(ra)"); // c.jr   ra (ret)
+        assert_eq!(decode(0x0000), None);
+
+        let code = [0x05, 0x45, 0x73, 0x00, 0x10, 0x00, 0x82];
+        let listing: std::vec::Vec<_> = disassemble(&code, 0x10)
+            .map(|(address, decoded)| (address, decoded.unwrap().mnemonic()))
+            .collect();
+        assert_eq!(listing, [(0x10, Mnemonic::Addi), (0x12, Mnemonic::Ebreak)]);
+    }
+}
@@ -1,11 +1,101 @@
 //! Engine Module
 
-use std::boxed::Box;
-
-use crate::error::EmbiveError;
-use crate::instruction::decode_execute;
-use crate::memory::Memory;
+#[cfg(feature = "accounting")]
+use crate::accounting::{Accounting, CounterView};
+#[cfg(feature = "counters")]
+use crate::counters::PerformanceCounters;
+use crate::csr::{CsrHandler, CsrHooks};
+#[cfg(feature = "debugger")]
+use crate::debug::{Debugger, StopReason};
+use crate::error::{ConfigError, EmbiveError};
+use crate::extension::{Extension, ExtensionFn};
+pub use crate::instruction::Access;
+use crate::instruction::{decode_execute, memory_access};
+#[cfg(feature = "interrupt")]
+use crate::interrupt::{
+    Granularity, Interrupt, InterruptFn, SoftwareInterrupt, SOFTWARE_INTERRUPT_NOT_PERMITTED,
+    SOFTWARE_INTERRUPT_QUEUE_FULL,
+};
+#[cfg(feature = "libc_support")]
+use crate::libc::{self, LibcFn, LibcHost};
+use crate::lint::{IllegalStats, IsaExtension};
+#[cfg(any(feature = "libc_support", feature = "runtime"))]
+use crate::memory::Heap;
+#[cfg(feature = "tinygo")]
+use crate::memory::RamLayout;
+use crate::memory::{Memory, RAM_OFFSET};
+#[cfg(feature = "v_extension")]
+use crate::register::VectorRegisters;
 use crate::register::{Register, Registers};
+#[cfg(feature = "replay")]
+use crate::replay::{self, Replay};
+#[cfg(feature = "runtime")]
+use crate::runtime::{
+    self, Outcome, PanicReport, Runtime, RuntimeHooks, RuntimeHost, RUNTIME_VERSION,
+};
+use crate::syscall::capability::Capabilities;
+use crate::syscall::format;
+#[cfg(feature = "log")]
+use crate::syscall::log::{self, LogBudget, LogFn, LogSink};
+use crate::syscall::trace::TraceContext;
+use crate::syscall::SyscallDisplay;
+use crate::syscall::{self, Errno, SyscallContract, Syscalls};
+#[cfg(feature = "crypto")]
+use crate::syscall::{
+    capability::Kind,
+    crypto::{self, Crypto, CryptoFn},
+};
+#[cfg(feature = "timer")]
+use crate::timer::{TimerDelivery, Timers};
+use crate::trap::{self, Exception, TrapState};
+
+mod call;
+mod coroutine;
+mod depth;
+mod export;
+#[cfg(feature = "fetch_batch")]
+mod fetch;
+mod fill;
+mod history;
+#[cfg(feature = "instance_blob")]
+mod instance;
+#[cfg(feature = "persistent_regions")]
+mod persistent;
+mod retire;
+mod scratch;
+mod snapshot;
+mod static_engine;
+#[cfg(feature = "telemetry")]
+mod telemetry;
+#[cfg(feature = "trace")]
+mod tracing;
+pub use call::{CALL_ARGS, CALL_RETURN_ADDRESS};
+pub use coroutine::{CoroutineState, GuestCoroutine};
+pub use depth::run_depth;
+use depth::RunGuard;
+pub use export::{EXPORT_ENTRY_SIZE, EXPORT_MAGIC, EXPORT_NAME_SIZE, EXPORT_SECTION};
+#[cfg(feature = "fetch_batch")]
+use fetch::FetchBuffer;
+#[cfg(feature = "fetch_batch")]
+pub use fetch::FETCH_BATCH;
+pub use fill::RamFill;
+use history::SyscallHistory;
+pub use history::{SyscallRecord, SYSCALL_RECORD_SIZE};
+#[cfg(feature = "persistent_regions")]
+use persistent::PersistentRegions;
+#[cfg(feature = "persistent_regions")]
+pub use persistent::{PERSISTENT_REGIONS, PERSISTENT_REGION_FULL, PERSISTENT_REGION_INVALID};
+pub use retire::{RetireFn, RetireHook};
+pub use snapshot::{EngineState, SNAPSHOT_MAGIC, SNAPSHOT_VERSION};
+pub use static_engine::{StaticEngine, StaticRam};
+#[cfg(feature = "telemetry")]
+use telemetry::Telemetry;
+#[cfg(feature = "telemetry")]
+pub use telemetry::{TelemetryRecord, TELEMETRY_RECORD_SIZE};
+#[cfg(feature = "replay")]
+pub(crate) use tracing::changed;
+#[cfg(feature = "trace")]
+pub use tracing::{TraceHook, TraceHooks};
 
 /// Number of syscall arguments
 pub const SYSCALL_ARGS: usize = 7;
@@ -26,19 +116,417 @@ pub const SYSCALL_ARGS: usize = 7;
 ///
 /// Returns:
 /// - `Result<i32, i32>`: value (`a1`), error (`a0`).
-pub type SyscallFn<M> = Box<dyn FnMut(i32, &[i32; SYSCALL_ARGS], &mut M) -> Result<i32, i32>>;
+pub type SyscallFn<M> = fn(nr: i32, args: &[i32; SYSCALL_ARGS], memory: &mut M) -> Result<i32, i32>;
+
+/// Required entry point alignment in bytes (halfword aligned when compressed instructions are enabled).
+#[cfg(not(feature = "c_extension"))]
+const ENTRY_POINT_ALIGNMENT: u32 = 4;
+#[cfg(feature = "c_extension")]
+const ENTRY_POINT_ALIGNMENT: u32 = 2;
+
+/// Cache block size in bytes (Zicbom/Zicboz), `cbo.*` addresses are aligned down to it.
+pub const CACHE_BLOCK_SIZE: u32 = 64;
+
+/// Cache block management operation (Zicbom).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CacheOp {
+    /// `cbo.inval`: Invalidate the block.
+    Invalidate,
+    /// `cbo.clean`: Write back the block.
+    Clean,
+    /// `cbo.flush`: Write back and invalidate the block.
+    Flush,
+}
+
+/// Cache management function signature
+///
+/// This function is called by the `cbo.inval`, `cbo.clean` and `cbo.flush` instructions.
+/// Without it, these instructions are no-ops (`cbo.zero` is always executed by the engine).
+///
+/// Arguments:
+/// - `op`: Cache block operation.
+/// - `address`: Block address (aligned to [`CACHE_BLOCK_SIZE`]).
+/// - `memory`: System Memory (code + RAM).
+///
+/// Returns:
+/// - `Ok(())`: Operation completed.
+/// - `Err(EmbiveError)`: Operation failed, execution stops with this error.
+pub type CacheFn<M> = fn(op: CacheOp, address: u32, memory: &mut M) -> Result<(), EmbiveError>;
+
+/// Non-temporal locality domain (Zihintntl).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum NtlDomain {
+    /// `ntl.p1`: Not expected to be reused by the innermost private cache.
+    P1,
+    /// `ntl.pall`: Not expected to be reused by any private cache.
+    PAll,
+    /// `ntl.s1`: Not expected to be reused by the innermost shared cache.
+    S1,
+    /// `ntl.all`: Not expected to be reused by any cache.
+    All,
+}
+
+/// HINT instruction, executed as a no-op (check [`HintFn`]).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Hint {
+    /// Non-temporal locality hint (Zihintntl, `add x0, x0, x2..x5`).
+    NonTemporal(NtlDomain),
+    /// Pause hint (Zihintpause, `fence w, 0`).
+    Pause,
+    /// Instruction prefetch hint (Zicbop, `prefetch.i`).
+    PrefetchInstruction,
+    /// Read prefetch hint (Zicbop, `prefetch.r`).
+    PrefetchRead,
+    /// Write prefetch hint (Zicbop, `prefetch.w`).
+    PrefetchWrite,
+    /// Guest safepoint (check [`SAFEPOINT_INSTRUCTION`]).
+    #[cfg(feature = "safepoint")]
+    Safepoint,
+    /// Other HINT encoding (reserved for future standard or custom use, ex.: `lui x0, imm`).
+    Reserved,
+}
+
+/// Tick function signature (host clock)
+///
+/// Returns a monotonic host tick count (ex.: cycle counter, microseconds), in any unit.
+/// Used to timestamp engine events (log records, syscall faults and illegal instructions) and,
+/// with the `accounting` feature, to measure syscall time.
+/// Wrapping is supported, as long as a single syscall takes less than `u64::MAX` ticks.
+pub type TickFn = fn() -> u64;
+
+/// Hint function signature
+///
+/// This function is called when a HINT instruction is executed, allowing the host to report them.
+/// HINTs are always executed as no-ops, `addi x0, x0, 0` (`nop`) isn't reported.
+///
+/// Arguments:
+/// - `hint`: Executed hint.
+/// - `program_counter`: Address of the HINT instruction.
+pub type HintFn = fn(hint: Hint, program_counter: u32);
+
+/// Safepoint instruction, `slli x0, x0, 31` (custom HINT space).
+///
+/// The guest executes it where its state is consistent (ex.: outside critical sections),
+/// so the host can snapshot or hot-reload it (check [`YieldPoint::Safepoint`] and [`Engine::at_safepoint`]).
+#[cfg(feature = "safepoint")]
+pub const SAFEPOINT_INSTRUCTION: u32 = 0x01F0_1013;
+
+/// Where [`Engine::run`] is allowed to yield.
+#[cfg(feature = "safepoint")]
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum YieldPoint {
+    /// Any instruction (when the instruction limit is reached).
+    #[default]
+    Any,
+    /// Only after a guest safepoint (check [`SAFEPOINT_INSTRUCTION`]):
+    /// - When the instruction limit is reached, keep running until the next safepoint.
+    /// - Without an instruction limit, yield at every safepoint.
+    Safepoint,
+}
+
+/// Syscall function panic, caught by the engine (check [`Config::syscall_panic_error`]).
+#[cfg(feature = "std")]
+#[derive(Debug, PartialEq, Clone)]
+pub struct SyscallFault {
+    /// Syscall number.
+    pub nr: i32,
+    /// Address of the `ecall` instruction.
+    pub program_counter: u32,
+    /// Panic message (None = Not a string).
+    pub message: Option<std::string::String>,
+    /// Host clock when the panic was caught (None = No tick function, check [`Config::tick_fn`]).
+    pub timestamp: Option<u64>,
+}
+
+/// Number of I/O handles that can be polled (handles `0` to `POLL_HANDLES - 1`, check [`Config::poll_nr`]).
+pub const POLL_HANDLES: u32 = 32;
+
+/// What a suspended engine is waiting for (check [`Engine::waiting_for`]).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum WaitingFor {
+    /// A blocking syscall on an I/O handle (check [`Config::suspend_on_would_block`]).
+    Handle(u32),
+    /// Any of the I/O handles in the mask to be ready (check [`Config::poll_nr`]).
+    AnyOf(u32),
+    /// The host to resume the guest ([`Engine::resume`]), with the value yielded by the guest
+    /// (check [`Config::yield_nr`]).
+    Resume(i32),
+    /// The host to handle a syscall ([`Engine::respond`], check [`Config::defer_syscalls`]).
+    Syscall(SyscallRequest),
+}
+
+/// Syscall captured by the engine, handled later by the host (check [`Config::defer_syscalls`]).
+/// The guest memory is accessed through [`Engine::syscall_request`] (or [`Engine::memory`]).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SyscallRequest {
+    /// Syscall number (`a7`).
+    pub nr: i32,
+    /// Syscall arguments (`a0` to `a6`, after the syscall contract, if any).
+    pub args: [i32; SYSCALL_ARGS],
+}
+
+/// Host result of a deferred syscall (check [`Engine::respond`]), as returned by a syscall function
+/// (`Ok` = value, `Err` = error code).
+pub type SyscallResponse = Result<i32, i32>;
+
+/// Instruction budget of a run (check [`Engine::budget`]).
+#[cfg(feature = "instruction_limit")]
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Budget {
+    /// No limit.
+    Unlimited,
+    /// Instruction limit ([`Config::instruction_limit`]).
+    Slice,
+    /// Fuel ([`Engine::run_with_fuel`]).
+    Fuel,
+}
+
+/// Why [`Engine::run_with_fuel`] stopped.
+#[cfg(feature = "instruction_limit")]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RunResult {
+    /// The guest halted (call [`Engine::reset`] prior to running again).
+    Halted,
+    /// The fuel ran out (refill it and run again, ex.: after running other guests).
+    OutOfFuel,
+    /// The guest is suspended on a syscall (check [`Engine::waiting_for`]), remaining fuel is kept.
+    Syscall,
+    /// The debugger stopped the engine (check [`Engine::stop_reason`]), remaining fuel is kept.
+    #[cfg(feature = "debugger")]
+    Debugger,
+}
+
+/// Instruction limit used by the [`Config::strict_sandbox`] preset.
+pub const STRICT_INSTRUCTION_LIMIT: u32 = 100_000;
+
+/// Maximum run depth used by the [`Config::strict_sandbox`] preset.
+pub const STRICT_MAX_RUN_DEPTH: u32 = 8;
 
 /// Embive Engine Configuration Struct
+///
+/// Start from [`Config::default`] (permissive) or one of the presets:
+/// - [`Config::strict_sandbox`]: Hardened baseline for untrusted code.
+/// - [`Config::debug`]: Fine-grained control for debugging.
+/// - [`Config::max_performance`]: Fastest execution for trusted code.
+/// - `Config::tinygo`: TinyGo guests (if the `tinygo` feature is enabled, check [`crate::tinygo`]).
 #[non_exhaustive]
 pub struct Config<M: Memory> {
-    /// System call function (Called by `ecall` instruction).
+    /// System call function (Called by `ecall` instruction, unless the engine has a syscall handler,
+    /// check [`Engine::set_syscalls`]). Closures capturing host state need a syscall handler.
     pub syscall_fn: Option<SyscallFn<M>>,
+    /// Cache management function (Called by `cbo.inval`, `cbo.clean` and `cbo.flush` instructions).
+    pub cache_fn: Option<CacheFn<M>>,
+    /// Hint function (Called by HINT instructions, executed as no-ops).
+    pub hint_fn: Option<HintFn>,
+    /// Retire hooks (Called after instructions in their program counter ranges, check [`RetireHook`]).
+    pub retire_hooks: &'static [RetireHook],
+    /// Execution tracing, host implementation (None = Not traced, check [`TraceHook`]).
+    #[cfg(feature = "trace")]
+    pub trace: Option<TraceHooks>,
+    /// Extension function (Called for instructions not implemented by Embive, check [`crate::extension`]).
+    pub extension_fn: Option<ExtensionFn<M>>,
+    /// Entry point, initial program counter (None = `0x00000000`, not validated).
+    pub entry_point: Option<u32>,
+    /// Export table address, guest functions looked up by name (None = No exports, check [`Engine::resolve_export`]).
+    pub export_table: Option<u32>,
+    /// Stack size, minimum RAM size required by the guest (0 = Not validated).
+    pub stack_size: u32,
+    /// Stack guard region, `(address, size)`, ex.: the bytes below the stack (None = No guard).
+    /// Guest stores into it fail with [`EmbiveError::StackOverflow`], host stores (ex.: syscalls) are not checked.
+    pub stack_guard: Option<(u32, u32)>,
+    /// Where the engine is allowed to yield.
+    #[cfg(feature = "safepoint")]
+    pub yield_point: YieldPoint,
+    /// Syscall contracts, checked before calling the syscall function (check [`crate::syscall`]).
+    pub syscall_contracts: &'static [SyscallContract],
+    /// Error code returned to the guest when a syscall violates its contract (default: [`Errno::InvalidPointer`]).
+    pub syscall_contract_error: i32,
+    /// Catch panics from the syscall function, returning this error code to the guest (None = Don't catch).
+    /// Caught panics are reported by [`Engine::take_syscall_fault`], the memory may be left partially modified.
+    #[cfg(feature = "std")]
+    pub syscall_panic_error: Option<i32>,
+    /// Suspend the engine when the syscall function returns [`Errno::WouldBlock`], instead of returning the error
+    /// to the guest (blocking syscalls, check [`Engine::waiting_for`]).
+    pub suspend_on_would_block: bool,
+    /// Suspend the engine on syscalls for the syscall function, surfacing them to the host
+    /// ([`WaitingFor::Syscall`]) instead of calling it (two-phase syscalls, ex.: async hosts, queues or FFI).
+    /// The host responds with [`Engine::respond`], the syscall completes when the engine is run again.
+    pub defer_syscalls: bool,
+    /// Syscall number used by the guest to wait for any of a set of I/O handles to be ready (None = Not permitted).
+    /// The argument is a mask of handles (`a0`, bit `n` = handle `n`, up to [`POLL_HANDLES`]). If any is ready,
+    /// returns the ready handles (`a1`) and consumes their readiness, otherwise suspends the engine until the host
+    /// wakes one of them ([`Engine::wake`]). Returns [`Errno::InvalidArgument`] for an empty mask.
+    pub poll_nr: Option<i32>,
+    /// Syscall number used by the guest to yield a value to the host (None = Not permitted).
+    /// The argument is the yielded value (`a0`). Suspends the engine until the host resumes it
+    /// ([`Engine::resume`]), returning the value passed in by the host (`a1`).
+    pub yield_nr: Option<i32>,
+    /// Syscall number used by the guest to manage timers (None = Not permitted, check [`crate::timer`]).
+    #[cfg(feature = "timer")]
+    pub timer_nr: Option<i32>,
+    /// Crypto service, syscall number and host implementation (None = Not permitted, check [`crate::syscall::crypto`]).
+    #[cfg(feature = "crypto")]
+    pub crypto: Option<(i32, CryptoFn<M>)>,
+    /// Syscall number used by the guest to declare persistent RAM regions (None = Not permitted).
+    /// Arguments are the region address (`a0`) and size (`a1`), returns `0` in `a0` on success,
+    /// or one of the `PERSISTENT_REGION_*` error codes (check [`Engine::persist`]).
+    #[cfg(feature = "persistent_regions")]
+    pub persistent_region_nr: Option<i32>,
+    /// Fill pattern of scrubbed RAM ([`Engine::fill_ram`], and warm restarts if the `persistent_regions` feature
+    /// is enabled).
+    pub ram_fill: RamFill,
+    /// Classify and count illegal instructions hit by the guest ([`Engine::illegal_instructions`]).
+    pub illegal_instruction_stats: bool,
+    /// Extensions disabled at runtime, their instructions are illegal (bitmask of [`IsaExtension::bit`],
+    /// 0 = None), ex.: to validate guests for builds without them (check [`crate::testrunner::matrix`]).
+    pub disabled_extensions: u32,
+    /// Extensions probed, their illegal instructions are skipped (as no-ops) instead of faulting, and reported
+    /// in [`Engine::probed_extensions`] (bitmask of [`IsaExtension::bit`], 0 = None), ex.: to learn which
+    /// extensions a guest needs before choosing the engine build (check [`crate::lint`]).
+    pub probe_extensions: u32,
+    /// Exceptions vectored to the guest trap handler, instead of being reported to the host
+    /// (bitmask of [`Exception::bit`], 0 = None, check [`crate::trap`]).
+    pub guest_traps: u32,
+    /// Syscall number used by the guest to read the instance blob (None = Not permitted).
+    /// Arguments are the destination buffer (`a0`, `a1`) and the blob offset (`a2`), returns the blob size
+    /// (check [`Engine::set_instance_blob`]).
+    #[cfg(feature = "instance_blob")]
+    pub instance_blob_nr: Option<i32>,
+    /// Logging service, syscall number and host sink (None = Not permitted, check [`crate::syscall::log`]).
+    #[cfg(feature = "log")]
+    pub log: Option<(i32, LogFn)>,
+    /// Log records delivered between budget refills (0 = Unlimited, check [`Engine::refill_log_budget`]).
+    #[cfg(feature = "log")]
+    pub log_burst: u32,
+    /// Control and status registers, host implementation (None = CSR instructions are illegal, check [`crate::csr`]).
+    pub csr: Option<CsrHooks>,
+    /// Libc services, host implementation and guest heap (None = Not permitted, check [`crate::libc`]).
+    /// Takes precedence over the syscall function for the libc syscall numbers.
+    #[cfg(feature = "libc_support")]
+    pub libc: Option<(LibcFn<M>, Heap)>,
+    /// Guest runtime services, host implementation and guest heap (None = Not permitted, check [`crate::runtime`]).
+    /// Takes precedence over the syscall function for the runtime syscall numbers.
+    #[cfg(feature = "runtime")]
+    pub runtime: Option<(RuntimeHooks, Heap)>,
+    /// Syscall number used by the guest to format strings (None = Not permitted, check [`crate::syscall::format`]).
+    pub format_nr: Option<i32>,
+    /// Syscalls kept in the syscall history, allocated from scratch memory (0 = Disabled, check
+    /// [`Engine::syscall_history`]).
+    pub syscall_history: u32,
+    /// Telemetry records kept in the telemetry ring, allocated from scratch memory (0 = Disabled, check
+    /// [`Engine::telemetry`]).
+    #[cfg(feature = "telemetry")]
+    pub telemetry: u32,
+    /// Retired instructions between telemetry records (0 = Every instruction).
+    #[cfg(feature = "telemetry")]
+    pub telemetry_interval: u32,
+    /// Maximum run depth, nested engine runs started from host callbacks (0 = Unlimited, check [`run_depth`]).
+    pub max_run_depth: u32,
+    /// Tick function (host clock), timestamps engine events ([`Engine::timestamp`]) and measures
+    /// the time spent inside the syscall function (check [`crate::accounting`]).
+    pub tick_fn: Option<TickFn>,
+    /// Syscall tick quota. Stop with an error when the accounted syscall ticks exceed it (0 = No quota).
+    #[cfg(feature = "accounting")]
+    pub syscall_tick_quota: u64,
+    /// Syscall number used by the guest to read its accounting counters, through [`Engine::counter_view`]
+    /// (None = Not permitted, check [`crate::accounting`]).
+    #[cfg(feature = "accounting")]
+    pub counter_nr: Option<i32>,
     /// Instruction limit. Yield when the limit is reached (0 = No limit).
     #[cfg(feature = "instruction_limit")]
     pub instruction_limit: u32,
+    /// Expose the remaining instruction budget to the guest, through the [`crate::csr::BUDGET`] and
+    /// [`crate::csr::BUDGETH`] registers (check [`Engine::budget`]).
+    #[cfg(feature = "instruction_limit")]
+    pub budget_csr: bool,
+    /// Interrupt function (Called when a pending interrupt is delivered).
+    #[cfg(feature = "interrupt")]
+    pub interrupt_fn: Option<InterruptFn<M>>,
+    /// Deliver interrupts to the guest trap handler (`mtvec`, check [`crate::trap`]), instead of the interrupt function.
+    #[cfg(feature = "interrupt")]
+    pub guest_interrupts: bool,
+    /// Interrupt delivery granularity.
+    #[cfg(feature = "interrupt")]
+    pub interrupt_granularity: Granularity,
+    /// Syscall number used by the guest to send software interrupts to other sandboxes (None = Not permitted).
+    #[cfg(feature = "interrupt")]
+    pub software_interrupt_nr: Option<i32>,
+    /// Sandboxes the guest is permitted to send software interrupts to (bitmask of sandbox ids).
+    #[cfg(feature = "interrupt")]
+    pub software_interrupt_targets: u32,
 }
 
 impl<M: Memory> Config<M> {
+    /// Hardened configuration for running untrusted code.
+    /// Start from this preset and enable only what the guest needs.
+    /// - Instruction limit of [`STRICT_INSTRUCTION_LIMIT`] (the guest can't starve the host).
+    /// - Interrupts delivered before every instruction (lowest latency).
+    /// - Software interrupts to other sandboxes are not permitted.
+    /// - Maximum run depth of [`STRICT_MAX_RUN_DEPTH`] (the guest can't exhaust the host stack through callbacks).
+    pub fn strict_sandbox() -> Self {
+        let config = Self::default().with_max_run_depth(STRICT_MAX_RUN_DEPTH);
+
+        #[cfg(feature = "instruction_limit")]
+        let config = config.with_instruction_limit(STRICT_INSTRUCTION_LIMIT);
+
+        #[cfg(feature = "interrupt")]
+        let config = config
+            .with_interrupt_granularity(Granularity::Instruction)
+            .with_software_interrupts(None, 0);
+
+        config
+    }
+
+    /// Configuration for debugging guests.
+    /// - Instruction limit of 1 (the engine yields after every instruction, so the host can inspect it).
+    /// - Interrupts delivered before every instruction (deterministic delivery point).
+    pub fn debug() -> Self {
+        let config = Self::default();
+
+        #[cfg(feature = "instruction_limit")]
+        let config = config.with_instruction_limit(1);
+
+        #[cfg(feature = "interrupt")]
+        let config = config.with_interrupt_granularity(Granularity::Instruction);
+
+        config
+    }
+
+    /// Configuration for maximum interpreter speed (trusted guests only).
+    /// - No instruction limit (the engine only stops when the guest halts).
+    /// - Interrupts delivered at basic block boundaries.
+    pub fn max_performance() -> Self {
+        let config = Self::default();
+
+        #[cfg(feature = "instruction_limit")]
+        let config = config.with_instruction_limit(0);
+
+        #[cfg(feature = "interrupt")]
+        let config = config.with_interrupt_granularity(Granularity::BasicBlock);
+
+        config
+    }
+
+    /// Configuration for TinyGo guests (check [`crate::tinygo`]).
+    /// - Guest runtime services, without the runtime allocator (the heap is owned by the garbage collector).
+    /// - Sleeping ([`crate::tinygo::SYS_SLEEP`]) yields to the host.
+    /// - RAM is validated to fit the whole layout (static data, heap and stack).
+    ///
+    /// Generic Arguments:
+    /// - `H`: Host implementation of the guest runtime services.
+    ///
+    /// Arguments:
+    /// - `layout`: Guest RAM layout (check [`crate::tinygo::layout`]).
+    /// - `tick_fn`: Host clock, in microseconds.
+    #[cfg(feature = "tinygo")]
+    pub fn tinygo<H: RuntimeHost>(layout: RamLayout, tick_fn: TickFn) -> Self {
+        Self::default()
+            .with_runtime::<H>(Heap::new(layout.heap_start, 0))
+            .with_yield_nr(Some(crate::tinygo::SYS_SLEEP))
+            .with_tick_fn(Some(tick_fn))
+            .with_stack_size(layout.stack_top.wrapping_sub(RAM_OFFSET))
+    }
+
     /// Set the system call function and return the configuration.
     ///
     /// Arguments:
@@ -48,254 +536,3660 @@ impl<M: Memory> Config<M> {
         self
     }
 
-    /// Set the instruction limit and return the configuration.
+    /// Set the cache management function and return the configuration.
     ///
     /// Arguments:
-    /// - `instruction_limit`: Instruction limit (0 = No limit).
-    #[cfg(feature = "instruction_limit")]
-    pub fn with_instruction_limit(mut self, instruction_limit: u32) -> Self {
-        self.instruction_limit = instruction_limit;
+    /// - `cache_fn`: Optional cache management function.
+    pub fn with_cache_fn(mut self, cache_fn: Option<CacheFn<M>>) -> Self {
+        self.cache_fn = cache_fn;
         self
     }
-}
 
-impl<M: Memory> Default for Config<M> {
-    fn default() -> Self {
-        Config {
-            syscall_fn: None,
-            #[cfg(feature = "instruction_limit")]
-            instruction_limit: 0,
-        }
+    /// Set the hint function and return the configuration.
+    ///
+    /// Arguments:
+    /// - `hint_fn`: Optional hint function.
+    pub fn with_hint_fn(mut self, hint_fn: Option<HintFn>) -> Self {
+        self.hint_fn = hint_fn;
+        self
     }
-}
-
-/// Embive Engine Struct
-#[non_exhaustive]
-pub struct Engine<M: Memory> {
-    /// Program Counter.
-    pub program_counter: u32,
-    /// CPU Registers.
-    pub registers: Registers,
-    /// System Memory (code + RAM).
-    pub memory: M,
-    /// Engine Configuration.
-    pub config: Config<M>,
-    /// Memory reservation for atomic operations (addr, value).
-    #[cfg(feature = "a_extension")]
-    pub(crate) memory_reservation: Option<(u32, i32)>,
-}
 
-impl<M: Memory> Engine<M> {
-    /// Create a new engine.
+    /// Set the retire hooks and return the configuration.
     ///
     /// Arguments:
-    /// - `code`: Code buffer, `u8` slice.
-    /// - `ram`: RAM buffer, mutable `u8` slice.
-    /// - `config`: Engine configuration.
-    pub fn new(memory: M, config: Config<M>) -> Result<Self, EmbiveError> {
-        // Create the engine
-        Ok(Engine {
-            program_counter: 0,
-            registers: Registers::new(),
-            memory,
-            config,
-            #[cfg(feature = "a_extension")]
-            memory_reservation: None,
-        })
+    /// - `retire_hooks`: Retire hooks (empty = None).
+    pub fn with_retire_hooks(mut self, retire_hooks: &'static [RetireHook]) -> Self {
+        self.retire_hooks = retire_hooks;
+        self
     }
 
-    /// Reset the engine:
-    /// - Program counter is reset to 0.
-    /// - Registers are reset to 0.
-    /// - Memory reservation is cleared.
-    pub fn reset(&mut self) {
-        self.program_counter = 0;
-        self.registers.reset();
-        #[cfg(feature = "a_extension")]
-        {
-            self.memory_reservation = None;
-        }
+    /// Set the execution tracing callbacks (check [`TraceHook`]) and return the configuration.
+    ///
+    /// Generic Arguments:
+    /// - `T`: Host implementation.
+    #[cfg(feature = "trace")]
+    pub fn with_trace<T: TraceHook>(mut self) -> Self {
+        self.trace = Some(TraceHooks::of::<T>());
+        self
     }
 
-    /// Run the engine
-    /// If the `instruction_limit` feature is enabled, the engine will yield when the limit is reached.
+    /// Register an instruction set extension and return the configuration.
+    /// Replaces any previously registered extension, use a tuple (`(A, B)`) to register multiple ones.
     ///
-    /// Returns:
-    /// - `Ok(bool)`: Success, returns if should continue:
-    ///     - `True`: Continue running (yielded, call `run` again).
-    ///     - `False`: Stop running (halted, call `reset` prior to running again).
-    /// - `Err(EmbiveError)`: Failed to run.
-    pub fn run(&mut self) -> Result<bool, EmbiveError> {
-        #[cfg(feature = "instruction_limit")]
-        {
-            // Check if there is an instruction limit
-            if self.config.instruction_limit > 0 {
-                // Run the engine with an instruction limit
-                for _ in 0..self.config.instruction_limit {
-                    // Step through the program
-                    if !self.step()? {
-                        // Stop running
-                        return Ok(false);
-                    }
-                }
+    /// Generic Arguments:
+    /// - `E`: Extension (check [`crate::extension::Extension`]).
+    pub fn with_extension<E: Extension<M>>(mut self) -> Self {
+        self.extension_fn = Some(E::decode_execute);
+        self
+    }
 
-                // Yield
-                return Ok(true);
-            }
-        }
+    /// Set the entry point and return the configuration.
+    ///
+    /// Arguments:
+    /// - `entry_point`: Optional entry point (initial program counter).
+    pub fn with_entry_point(mut self, entry_point: Option<u32>) -> Self {
+        self.entry_point = entry_point;
+        self
+    }
 
-        // No instruction limit
-        loop {
-            // Step through the program
-            if !self.step()? {
-                // Stop running
-                return Ok(false);
-            }
-        }
+    /// Set the export table address and return the configuration.
+    ///
+    /// Arguments:
+    /// - `export_table`: Optional export table address (check [`Engine::resolve_export`]).
+    pub fn with_export_table(mut self, export_table: Option<u32>) -> Self {
+        self.export_table = export_table;
+        self
     }
 
-    /// Step through a single instruction from the current program counter.
+    /// Set the stack size and return the configuration.
     ///
-    /// Returns:
-    /// - `Ok(bool)`: Success, returns if should continue:
-    ///     - `True`: Should continue.
-    ///     - `False`: Should stop (halted).
-    /// - `Err(EmbiveError)`: Failed to execute.
-    #[inline]
-    pub fn step(&mut self) -> Result<bool, EmbiveError> {
-        // Fetch next instruction
-        let data = self.fetch()?;
+    /// Arguments:
+    /// - `stack_size`: Stack size (0 = Not validated).
+    pub fn with_stack_size(mut self, stack_size: u32) -> Self {
+        self.stack_size = stack_size;
+        self
+    }
 
-        // Decode and execute the instruction
-        let ret = decode_execute(self, data)?;
+    /// Set the stack guard region and return the configuration.
+    ///
+    /// Arguments:
+    /// - `stack_guard`: Optional stack guard region, `(address, size)`.
+    pub fn with_stack_guard(mut self, stack_guard: Option<(u32, u32)>) -> Self {
+        self.stack_guard = stack_guard;
+        self
+    }
 
-        Ok(ret)
+    /// Set where the engine is allowed to yield and return the configuration.
+    ///
+    /// Arguments:
+    /// - `yield_point`: Yield point.
+    #[cfg(feature = "safepoint")]
+    pub fn with_yield_point(mut self, yield_point: YieldPoint) -> Self {
+        self.yield_point = yield_point;
+        self
     }
 
-    /// Fetch the next instruction (raw) from the program counter.
+    /// Set the syscall contracts and return the configuration.
     ///
-    /// Returns:
-    /// - `Ok(u32)`: The instruction (raw) that was fetched.
-    /// - `Err(EmbiveError)`: The program counter is out of bounds.
-    #[inline]
-    pub fn fetch(&mut self) -> Result<u32, EmbiveError> {
-        let data = self.memory.load::<4>(self.program_counter)?;
-        Ok(u32::from_le_bytes(data))
+    /// Arguments:
+    /// - `contracts`: Syscall contracts (empty = No checks).
+    /// - `error`: Error code returned to the guest when a syscall violates its contract.
+    pub fn with_syscall_contracts(
+        mut self,
+        contracts: &'static [SyscallContract],
+        error: i32,
+    ) -> Self {
+        self.syscall_contracts = contracts;
+        self.syscall_contract_error = error;
+        self
     }
 
-    /// Handle a system call.
-    /// The system call function is called with the system call number and arguments.
+    /// Set the syscall panic error code and return the configuration.
     ///
-    /// Returns:
-    /// - `Ok(())`: Syscall executed.
-    /// - `Err(EmbiveError)`: Failed to execute the system call function.
-    ///     - System call function is not set.
-    #[inline(always)]
-    pub(crate) fn syscall(&mut self) -> Result<(), EmbiveError> {
-        if let Some(syscall_fn) = self.config.syscall_fn.as_mut() {
-            // Syscall Number
-            let nr = self.registers.inner[Register::A7 as usize];
+    /// Arguments:
+    /// - `syscall_panic_error`: Error code returned to the guest when the syscall function panics (None = Don't catch).
+    #[cfg(feature = "std")]
+    pub fn with_syscall_panic_error(mut self, syscall_panic_error: Option<i32>) -> Self {
+        self.syscall_panic_error = syscall_panic_error;
+        self
+    }
 
-            // Syscall Arguments
-            let args = self.registers.inner[Register::A0 as usize..]
-                .first_chunk()
-                // Unwrap is safe because the slice is guaranteed to have more than SYSCALL_ARGS elements.
-                .unwrap();
+    /// Set if the engine suspends on blocking syscalls and return the configuration.
+    ///
+    /// Arguments:
+    /// - `suspend_on_would_block`: Suspend when the syscall function returns [`Errno::WouldBlock`].
+    pub fn with_suspend_on_would_block(mut self, suspend_on_would_block: bool) -> Self {
+        self.suspend_on_would_block = suspend_on_would_block;
+        self
+    }
 
-            // Call the syscall function
-            match syscall_fn(nr, args, &mut self.memory) {
-                Ok(value) => {
-                    // Clear error code
-                    self.registers.inner[Register::A0 as usize] = 0;
+    /// Set if the engine defers syscalls to the host and return the configuration.
+    ///
+    /// Arguments:
+    /// - `defer_syscalls`: Suspend on syscalls, instead of calling the syscall function (check [`Engine::respond`]).
+    pub fn with_defer_syscalls(mut self, defer_syscalls: bool) -> Self {
+        self.defer_syscalls = defer_syscalls;
+        self
+    }
 
-                    // Set return value
-                    self.registers.inner[Register::A1 as usize] = value;
-                }
-                Err(error) => {
-                    // Set error code
-                    self.registers.inner[Register::A0 as usize] = error;
+    /// Permit the guest to poll I/O handles and return the configuration.
+    ///
+    /// Arguments:
+    /// - `nr`: Syscall number used to poll I/O handles (None = Not permitted).
+    pub fn with_poll_nr(mut self, nr: Option<i32>) -> Self {
+        self.poll_nr = nr;
+        self
+    }
 
-                    // Clear return value
-                    self.registers.inner[Register::A1 as usize] = 0;
-                }
-            }
+    /// Permit the guest to yield values to the host and return the configuration.
+    ///
+    /// Arguments:
+    /// - `nr`: Syscall number used to yield (None = Not permitted).
+    pub fn with_yield_nr(mut self, nr: Option<i32>) -> Self {
+        self.yield_nr = nr;
+        self
+    }
 
-            return Ok(());
-        }
+    /// Permit the guest to use the timer service and return the configuration.
+    ///
+    /// Arguments:
+    /// - `nr`: Syscall number used to manage timers (None = Not permitted).
+    #[cfg(feature = "timer")]
+    pub fn with_timer_nr(mut self, nr: Option<i32>) -> Self {
+        self.timer_nr = nr;
+        self
+    }
 
-        // No syscall function set
-        Err(EmbiveError::NoSyscallFunction)
+    /// Permit the guest to use the crypto service and return the configuration.
+    ///
+    /// Generic Arguments:
+    /// - `C`: Host crypto implementation (check [`crate::syscall::crypto::Crypto`]).
+    ///
+    /// Arguments:
+    /// - `nr`: Syscall number used for crypto operations (None = Not permitted).
+    #[cfg(feature = "crypto")]
+    pub fn with_crypto<C: Crypto<M>>(mut self, nr: Option<i32>) -> Self {
+        self.crypto = nr.map(|nr| (nr, C::syscall as CryptoFn<M>));
+        self
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::memory::SliceMemory;
+    /// Permit the guest to declare persistent RAM regions (check [`Engine::warm_restart`]) and return the configuration.
+    ///
+    /// Arguments:
+    /// - `nr`: Syscall number used to declare persistent regions (None = Not permitted).
+    #[cfg(feature = "persistent_regions")]
+    pub fn with_persistent_region_nr(mut self, nr: Option<i32>) -> Self {
+        self.persistent_region_nr = nr;
+        self
+    }
 
-    use super::*;
+    /// Set the fill pattern of scrubbed RAM and return the configuration.
+    ///
+    /// Arguments:
+    /// - `ram_fill`: RAM fill pattern (check [`Engine::fill_ram`]).
+    pub fn with_ram_fill(mut self, ram_fill: RamFill) -> Self {
+        self.ram_fill = ram_fill;
+        self
+    }
 
-    #[test]
-    fn test_reset() {
-        let mut engine = Engine::new(SliceMemory::new(&[], &mut []), Default::default()).unwrap();
-        engine.reset();
+    /// Enable illegal instruction statistics and return the configuration.
+    ///
+    /// Arguments:
+    /// - `enabled`: Classify and count illegal instructions (check [`crate::lint::IllegalStats`]).
+    pub fn with_illegal_instruction_stats(mut self, enabled: bool) -> Self {
+        self.illegal_instruction_stats = enabled;
+        self
+    }
 
-        assert_eq!(engine.program_counter, 0);
+    /// Set the extensions disabled at runtime and return the configuration.
+    ///
+    /// Arguments:
+    /// - `disabled_extensions`: Disabled extensions (bitmask of [`IsaExtension::bit`], 0 = None).
+    pub fn with_disabled_extensions(mut self, disabled_extensions: u32) -> Self {
+        self.disabled_extensions = disabled_extensions;
+        self
     }
 
-    #[cfg(feature = "instruction_limit")]
-    #[test]
-    fn test_instruction_limit() {
-        let code = &[
-            0x93, 0x08, 0x20, 0x00, // li   a7, 2      (Syscall nr)
-            0x13, 0x05, 0x10, 0x00, // li   a0, 1      (arg0, set first bit)
+    /// Set the probed extensions and return the configuration.
+    ///
+    /// Arguments:
+    /// - `probe_extensions`: Probed extensions (bitmask of [`IsaExtension::bit`], 0 = None).
+    pub fn with_probe_extensions(mut self, probe_extensions: u32) -> Self {
+        self.probe_extensions = probe_extensions;
+        self
+    }
+
+    /// Set the exceptions vectored to the guest trap handler (check [`crate::trap`]) and return the configuration.
+    ///
+    /// Arguments:
+    /// - `guest_traps`: Delegated exceptions (bitmask of [`Exception::bit`], 0 = None).
+    pub fn with_guest_traps(mut self, guest_traps: u32) -> Self {
+        self.guest_traps = guest_traps;
+        self
+    }
+
+    /// Permit the guest to read the instance blob (check [`Engine::set_instance_blob`]) and return the configuration.
+    ///
+    /// Arguments:
+    /// - `nr`: Syscall number used to read the instance blob (None = Not permitted).
+    #[cfg(feature = "instance_blob")]
+    pub fn with_instance_blob_nr(mut self, nr: Option<i32>) -> Self {
+        self.instance_blob_nr = nr;
+        self
+    }
+
+    /// Permit the guest to log and return the configuration.
+    ///
+    /// Generic Arguments:
+    /// - `S`: Host log sink (check [`crate::syscall::log::LogSink`]).
+    ///
+    /// Arguments:
+    /// - `nr`: Syscall number used to log (None = Not permitted).
+    #[cfg(feature = "log")]
+    pub fn with_log<S: LogSink>(mut self, nr: Option<i32>) -> Self {
+        self.log = nr.map(|nr| (nr, S::log as LogFn));
+        self
+    }
+
+    /// Set the log record budget and return the configuration.
+    ///
+    /// Arguments:
+    /// - `burst`: Log records delivered between budget refills (0 = Unlimited).
+    #[cfg(feature = "log")]
+    pub fn with_log_burst(mut self, burst: u32) -> Self {
+        self.log_burst = burst;
+        self
+    }
+
+    /// Set the control and status registers (check [`crate::csr`]) and return the configuration.
+    ///
+    /// Generic Arguments:
+    /// - `H`: Host implementation.
+    pub fn with_csr<H: CsrHandler>(mut self) -> Self {
+        self.csr = Some(CsrHooks::of::<H>());
+        self
+    }
+
+    /// Set the libc services (check [`crate::libc`]) and return the configuration.
+    ///
+    /// Generic Arguments:
+    /// - `H`: Host implementation.
+    ///
+    /// Arguments:
+    /// - `heap`: Guest heap, managed with the `brk` syscall.
+    #[cfg(feature = "libc_support")]
+    pub fn with_libc<H: LibcHost>(mut self, heap: Heap) -> Self {
+        self.libc = Some((libc::syscall::<H, M>, heap));
+        self
+    }
+
+    /// Set the guest runtime services (check [`crate::runtime`]) and return the configuration.
+    ///
+    /// Generic Arguments:
+    /// - `H`: Host implementation.
+    ///
+    /// Arguments:
+    /// - `heap`: Guest heap, managed by the runtime allocator.
+    #[cfg(feature = "runtime")]
+    pub fn with_runtime<H: RuntimeHost>(mut self, heap: Heap) -> Self {
+        self.runtime = Some((RuntimeHooks::of::<H>(), heap));
+        self
+    }
+
+    /// Permit the guest to use the formatting service and return the configuration.
+    ///
+    /// Arguments:
+    /// - `nr`: Syscall number used to format strings (None = Not permitted).
+    pub fn with_format_nr(mut self, nr: Option<i32>) -> Self {
+        self.format_nr = nr;
+        self
+    }
+
+    /// Set the syscall history size and return the configuration.
+    ///
+    /// Arguments:
+    /// - `syscall_history`: Syscalls kept in the history (0 = Disabled, check [`Engine::set_scratch`]).
+    pub fn with_syscall_history(mut self, syscall_history: u32) -> Self {
+        self.syscall_history = syscall_history;
+        self
+    }
+
+    /// Set the telemetry ring size and interval and return the configuration.
+    ///
+    /// Arguments:
+    /// - `telemetry`: Telemetry records kept in the ring (0 = Disabled, check [`Engine::set_scratch`]).
+    /// - `interval`: Retired instructions between telemetry records (0 = Every instruction).
+    #[cfg(feature = "telemetry")]
+    pub fn with_telemetry(mut self, telemetry: u32, interval: u32) -> Self {
+        self.telemetry = telemetry;
+        self.telemetry_interval = interval;
+        self
+    }
+
+    /// Set the maximum run depth and return the configuration.
+    ///
+    /// Arguments:
+    /// - `max_run_depth`: Maximum nested engine runs (0 = Unlimited, check [`run_depth`]).
+    pub fn with_max_run_depth(mut self, max_run_depth: u32) -> Self {
+        self.max_run_depth = max_run_depth;
+        self
+    }
+
+    /// Set the tick function and return the configuration.
+    ///
+    /// Arguments:
+    /// - `tick_fn`: Optional tick function (None = Events aren't timestamped and syscall time isn't measured).
+    pub fn with_tick_fn(mut self, tick_fn: Option<TickFn>) -> Self {
+        self.tick_fn = tick_fn;
+        self
+    }
+
+    /// Set the syscall tick quota and return the configuration.
+    ///
+    /// Arguments:
+    /// - `syscall_tick_quota`: Syscall tick quota (0 = No quota).
+    #[cfg(feature = "accounting")]
+    pub fn with_syscall_tick_quota(mut self, syscall_tick_quota: u64) -> Self {
+        self.syscall_tick_quota = syscall_tick_quota;
+        self
+    }
+
+    /// Permit the guest to read its accounting counters and return the configuration.
+    ///
+    /// Arguments:
+    /// - `nr`: Syscall number used to read counters (None = Not permitted).
+    #[cfg(feature = "accounting")]
+    pub fn with_counter_nr(mut self, nr: Option<i32>) -> Self {
+        self.counter_nr = nr;
+        self
+    }
+
+    /// Set the instruction limit and return the configuration.
+    ///
+    /// Arguments:
+    /// - `instruction_limit`: Instruction limit (0 = No limit).
+    #[cfg(feature = "instruction_limit")]
+    pub fn with_instruction_limit(mut self, instruction_limit: u32) -> Self {
+        self.instruction_limit = instruction_limit;
+        self
+    }
+
+    /// Expose the remaining instruction budget to the guest and return the configuration.
+    ///
+    /// Arguments:
+    /// - `enabled`: Guest can read [`crate::csr::BUDGET`] and [`crate::csr::BUDGETH`] (check [`Engine::budget`]).
+    #[cfg(feature = "instruction_limit")]
+    pub fn with_budget_csr(mut self, enabled: bool) -> Self {
+        self.budget_csr = enabled;
+        self
+    }
+
+    /// Set the interrupt function and return the configuration.
+    ///
+    /// Arguments:
+    /// - `interrupt_fn`: Optional interrupt function.
+    #[cfg(feature = "interrupt")]
+    pub fn with_interrupt_fn(mut self, interrupt_fn: Option<InterruptFn<M>>) -> Self {
+        self.interrupt_fn = interrupt_fn;
+        self
+    }
+
+    /// Set if interrupts are delivered to the guest trap handler and return the configuration.
+    ///
+    /// Arguments:
+    /// - `guest_interrupts`: Deliver interrupts to the guest trap handler (`mtvec`), instead of the interrupt function.
+    #[cfg(feature = "interrupt")]
+    pub fn with_guest_interrupts(mut self, guest_interrupts: bool) -> Self {
+        self.guest_interrupts = guest_interrupts;
+        self
+    }
+
+    /// Set the interrupt delivery granularity and return the configuration.
+    ///
+    /// Arguments:
+    /// - `interrupt_granularity`: Interrupt delivery granularity.
+    #[cfg(feature = "interrupt")]
+    pub fn with_interrupt_granularity(mut self, interrupt_granularity: Granularity) -> Self {
+        self.interrupt_granularity = interrupt_granularity;
+        self
+    }
+
+    /// Permit the guest to send software interrupts to other sandboxes and return the configuration.
+    ///
+    /// Arguments:
+    /// - `nr`: Syscall number used to send software interrupts (None = Not permitted).
+    /// - `targets`: Permitted target sandboxes (bitmask of sandbox ids).
+    #[cfg(feature = "interrupt")]
+    pub fn with_software_interrupts(mut self, nr: Option<i32>, targets: u32) -> Self {
+        self.software_interrupt_nr = nr;
+        self.software_interrupt_targets = targets;
+        self
+    }
+
+    /// Check if the guest trap registers and `mret` are available to the guest (check [`crate::trap`]):
+    /// exceptions or interrupts are delivered to the guest trap handler.
+    pub(crate) fn guest_trap_handler(&self) -> bool {
+        #[cfg(feature = "interrupt")]
+        if self.guest_interrupts {
+            return true;
+        }
+
+        self.guest_traps != 0
+    }
+
+    /// Get the maximum interrupt delivery latency for this configuration.
+    ///
+    /// Returns:
+    /// - `Some(u32)`: Maximum number of guest instructions executed before delivery.
+    /// - `None`: Latency is unbounded.
+    #[cfg(feature = "interrupt")]
+    pub fn interrupt_latency(&self) -> Option<u32> {
+        #[cfg(feature = "instruction_limit")]
+        let instruction_limit = self.instruction_limit;
+        #[cfg(not(feature = "instruction_limit"))]
+        let instruction_limit = 0;
+
+        self.interrupt_granularity.latency(instruction_limit)
+    }
+
+    /// Validate the configuration against the system memory.
+    /// Called by [`Engine::new`], so misconfigurations are reported before running.
+    ///
+    /// Arguments:
+    /// - `memory`: System memory (code + RAM).
+    ///
+    /// Returns:
+    /// - `Ok(())`: Configuration is valid.
+    /// - `Err(ConfigError)`: The first problem found in the configuration.
+    pub fn validate(&self, memory: &M) -> Result<(), ConfigError> {
+        if let Some(entry_point) = self.entry_point {
+            if entry_point % ENTRY_POINT_ALIGNMENT != 0 {
+                return Err(ConfigError::MisalignedEntryPoint);
+            }
+
+            if memory
+                .load::<{ ENTRY_POINT_ALIGNMENT as usize }>(entry_point)
+                .is_err()
+            {
+                return Err(ConfigError::EntryPointOutOfBounds);
+            }
+        }
+
+        if self.stack_size > 0 {
+            // Last byte of the stack must be inside RAM
+            let address = RAM_OFFSET.checked_add(self.stack_size - 1);
+            if address.map_or(true, |address| memory.load::<1>(address).is_err()) {
+                return Err(ConfigError::StackTooLarge);
+            }
+        }
+
+        #[cfg(feature = "interrupt")]
+        {
+            if self.interrupt_granularity == Granularity::Yield
+                && self.interrupt_latency().is_none()
+            {
+                // Interrupts would only be delivered when `run` is called
+                return Err(ConfigError::UnboundedInterruptLatency);
+            }
+
+            if self.software_interrupt_nr.is_some() && self.software_interrupt_targets == 0 {
+                return Err(ConfigError::NoSoftwareInterruptTargets);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<M: Memory> Default for Config<M> {
+    fn default() -> Self {
+        Config {
+            syscall_fn: None,
+            cache_fn: None,
+            hint_fn: None,
+            retire_hooks: &[],
+            #[cfg(feature = "trace")]
+            trace: None,
+            extension_fn: None,
+            entry_point: None,
+            export_table: None,
+            stack_size: 0,
+            stack_guard: None,
+            #[cfg(feature = "safepoint")]
+            yield_point: YieldPoint::Any,
+            syscall_contracts: &[],
+            syscall_contract_error: Errno::InvalidPointer.code(),
+            #[cfg(feature = "std")]
+            syscall_panic_error: None,
+            suspend_on_would_block: false,
+            defer_syscalls: false,
+            poll_nr: None,
+            yield_nr: None,
+            #[cfg(feature = "timer")]
+            timer_nr: None,
+            #[cfg(feature = "crypto")]
+            crypto: None,
+            #[cfg(feature = "persistent_regions")]
+            persistent_region_nr: None,
+            ram_fill: RamFill::Zero,
+            illegal_instruction_stats: false,
+            disabled_extensions: 0,
+            probe_extensions: 0,
+            guest_traps: 0,
+            #[cfg(feature = "instance_blob")]
+            instance_blob_nr: None,
+            #[cfg(feature = "log")]
+            log: None,
+            #[cfg(feature = "log")]
+            log_burst: 0,
+            csr: None,
+            #[cfg(feature = "libc_support")]
+            libc: None,
+            #[cfg(feature = "runtime")]
+            runtime: None,
+            format_nr: None,
+            syscall_history: 0,
+            #[cfg(feature = "telemetry")]
+            telemetry: 0,
+            #[cfg(feature = "telemetry")]
+            telemetry_interval: 0,
+            max_run_depth: 0,
+            tick_fn: None,
+            #[cfg(feature = "accounting")]
+            syscall_tick_quota: 0,
+            #[cfg(feature = "accounting")]
+            counter_nr: None,
+            #[cfg(feature = "instruction_limit")]
+            instruction_limit: 0,
+            #[cfg(feature = "instruction_limit")]
+            budget_csr: false,
+            #[cfg(feature = "interrupt")]
+            interrupt_fn: None,
+            #[cfg(feature = "interrupt")]
+            guest_interrupts: false,
+            #[cfg(feature = "interrupt")]
+            interrupt_granularity: Granularity::default(),
+            #[cfg(feature = "interrupt")]
+            software_interrupt_nr: None,
+            #[cfg(feature = "interrupt")]
+            software_interrupt_targets: 0,
+        }
+    }
+}
+
+/// Embive Engine Struct
+#[non_exhaustive]
+pub struct Engine<'a, M: Memory> {
+    /// Program Counter.
+    pub program_counter: u32,
+    /// CPU Registers.
+    pub registers: Registers,
+    /// Vector Registers (`V` extension subset).
+    #[cfg(feature = "v_extension")]
+    pub vector: VectorRegisters,
+    /// System Memory (code + RAM).
+    pub memory: &'a mut M,
+    /// Engine Configuration.
+    pub config: Config<M>,
+    /// Memory reservation for atomic operations (addr, value).
+    #[cfg(feature = "a_extension")]
+    pub(crate) memory_reservation: Option<(u32, i32)>,
+    /// The last executed instruction was a safepoint (or the engine was reset).
+    #[cfg(feature = "safepoint")]
+    pub(crate) safepoint: bool,
+    /// Persistent RAM regions (preserved by [`Engine::warm_restart`]).
+    #[cfg(feature = "persistent_regions")]
+    pub(crate) persistent: PersistentRegions,
+    /// Code fetched ahead of the program counter (check [`Engine::invalidate_fetch`]).
+    #[cfg(feature = "fetch_batch")]
+    fetch_buffer: FetchBuffer,
+    /// Instance configuration/identity blob (check [`Engine::set_instance_blob`]).
+    #[cfg(feature = "instance_blob")]
+    instance_blob: &'a [u8],
+    /// Syscall handler, takes precedence over [`Config::syscall_fn`] (check [`Engine::set_syscalls`]).
+    syscalls: Option<&'a mut (dyn Syscalls<M> + Send)>,
+    /// Last syscalls handled by the engine, in scratch memory (check [`Engine::syscall_history`]).
+    syscall_history: SyscallHistory<'a>,
+    /// Periodic execution snapshots, in scratch memory (check [`Engine::telemetry`]).
+    #[cfg(feature = "telemetry")]
+    telemetry: Telemetry<'a>,
+    /// Log record budget (check [`Config::log_burst`]).
+    #[cfg(feature = "log")]
+    log_budget: LogBudget,
+    /// Trace context of the run (check [`crate::syscall::trace`]).
+    trace_context: Option<TraceContext>,
+    /// What the engine is suspended on (None = Not suspended).
+    waiting: Option<WaitingFor>,
+    /// Value passed in by the host, returned by the pending yield syscall (check [`Engine::resume`]).
+    resume_value: Option<i32>,
+    /// Result supplied by the host, returned by the pending deferred syscall (check [`Engine::respond`]).
+    syscall_response: Option<SyscallResponse>,
+    /// Guest program break (check [`crate::libc`]).
+    #[cfg(feature = "libc_support")]
+    program_break: u32,
+    /// Guest exit code (check [`crate::libc`]).
+    #[cfg(feature = "libc_support")]
+    exit_code: Option<i32>,
+    /// Guest runtime state (check [`crate::runtime`]).
+    #[cfg(feature = "runtime")]
+    runtime: Runtime,
+    /// Ready I/O handles, not yet polled by the guest (bit `n` = handle `n`).
+    ready: u32,
+    /// Guest timers.
+    #[cfg(feature = "timer")]
+    timers: Timers,
+    /// Interrupt controller state (lines, priorities and nesting).
+    #[cfg(feature = "interrupt")]
+    pub interrupt: Interrupt,
+    /// Execution accounting (guest instructions and syscall time, not cleared by [`Engine::reset`]).
+    #[cfg(feature = "accounting")]
+    pub accounting: Accounting,
+    /// Guest view of the accounting counters (not cleared by [`Engine::reset`], check [`crate::accounting`]).
+    #[cfg(feature = "accounting")]
+    pub counter_view: CounterView,
+    /// Performance counters (not cleared by [`Engine::reset`], check [`Engine::counters`]).
+    #[cfg(feature = "counters")]
+    counters: PerformanceCounters,
+    /// Replay log, recording or replaying the guest inputs (check [`crate::replay`]).
+    #[cfg(feature = "replay")]
+    pub(crate) replay: Replay<'a>,
+    /// Guest trap registers (check [`crate::trap`]).
+    pub trap: TrapState,
+    /// Illegal instructions hit by the guest, by extension (not cleared by [`Engine::reset`],
+    /// check [`Config::illegal_instruction_stats`]).
+    pub illegal_instructions: IllegalStats,
+    /// Probed extensions the guest attempted to use (bitmask of [`IsaExtension::bit`], not cleared by
+    /// [`Engine::reset`], check [`Config::probe_extensions`]).
+    pub probed_extensions: u32,
+    /// Remaining fuel, in instructions (not cleared by [`Engine::reset`], check [`Engine::run_with_fuel`]).
+    #[cfg(feature = "instruction_limit")]
+    fuel: u64,
+    /// Instructions left in the current run slice (check [`Config::instruction_limit`]).
+    #[cfg(feature = "instruction_limit")]
+    slice: u32,
+    /// Instruction budget of the current run (check [`Engine::budget`]).
+    #[cfg(feature = "instruction_limit")]
+    budget: Budget,
+    /// Capabilities granted to the guest (revoked by [`Engine::reset`], check [`crate::syscall::capability`]).
+    pub capabilities: Capabilities,
+    /// Breakpoints, watchpoints and single-step mode (kept by [`Engine::reset`], check [`crate::debug`]).
+    #[cfg(feature = "debugger")]
+    pub debugger: Debugger,
+    /// Last caught syscall function panic.
+    #[cfg(feature = "std")]
+    syscall_fault: Option<SyscallFault>,
+}
+
+impl<'a, M: Memory> Engine<'a, M> {
+    /// Create a new engine.
+    ///
+    /// Arguments:
+    /// - `memory`: System memory (code + RAM).
+    /// - `config`: Engine configuration.
+    ///
+    /// Returns:
+    /// - `Ok(Engine)`: The engine was created.
+    /// - `Err(EmbiveError)`: The configuration is invalid ([`EmbiveError::InvalidConfig`]).
+    pub fn new(memory: &'a mut M, config: Config<M>) -> Result<Self, EmbiveError> {
+        // Validate the configuration
+        config
+            .validate(memory)
+            .map_err(EmbiveError::InvalidConfig)?;
+
+        #[cfg(feature = "libc_support")]
+        let program_break = config.libc.map(|(_, heap)| heap.start).unwrap_or(0);
+
+        // Create the engine
+        let mut engine = Engine {
+            program_counter: config.entry_point.unwrap_or(0),
+            registers: Registers::new(),
+            #[cfg(feature = "v_extension")]
+            vector: VectorRegisters::new(),
+            memory,
+            config,
+            #[cfg(feature = "a_extension")]
+            memory_reservation: None,
+            #[cfg(feature = "safepoint")]
+            safepoint: true,
+            #[cfg(feature = "persistent_regions")]
+            persistent: PersistentRegions::default(),
+            #[cfg(feature = "fetch_batch")]
+            fetch_buffer: FetchBuffer::default(),
+            #[cfg(feature = "instance_blob")]
+            instance_blob: &[],
+            syscalls: None,
+            syscall_history: SyscallHistory::default(),
+            #[cfg(feature = "telemetry")]
+            telemetry: Telemetry::default(),
+            #[cfg(feature = "log")]
+            log_budget: LogBudget::default(),
+            trace_context: None,
+            waiting: None,
+            resume_value: None,
+            syscall_response: None,
+            #[cfg(feature = "libc_support")]
+            program_break,
+            #[cfg(feature = "libc_support")]
+            exit_code: None,
+            #[cfg(feature = "runtime")]
+            runtime: Runtime::default(),
+            ready: 0,
+            #[cfg(feature = "timer")]
+            timers: Timers::default(),
+            #[cfg(feature = "interrupt")]
+            interrupt: Interrupt::default(),
+            #[cfg(feature = "accounting")]
+            accounting: Accounting::default(),
+            #[cfg(feature = "accounting")]
+            counter_view: CounterView::default(),
+            #[cfg(feature = "counters")]
+            counters: PerformanceCounters::default(),
+            #[cfg(feature = "replay")]
+            replay: Replay::default(),
+            trap: TrapState::default(),
+            illegal_instructions: IllegalStats::default(),
+            probed_extensions: 0,
+            #[cfg(feature = "debugger")]
+            debugger: Debugger::default(),
+            #[cfg(feature = "instruction_limit")]
+            fuel: 0,
+            #[cfg(feature = "instruction_limit")]
+            slice: 0,
+            #[cfg(feature = "instruction_limit")]
+            budget: Budget::Unlimited,
+            capabilities: Capabilities::default(),
+            #[cfg(feature = "std")]
+            syscall_fault: None,
+        };
+        engine.enter();
+
+        Ok(engine)
+    }
+
+    /// Apply the entry convention (ex.: guest runtime version in `a0`).
+    fn enter(&mut self) {
+        #[cfg(feature = "runtime")]
+        if self.config.runtime.is_some() {
+            self.registers.inner[Register::A0 as usize] = RUNTIME_VERSION as i32;
+        }
+    }
+
+    /// Reset the engine:
+    /// - Program counter is reset to the entry point (0 if not set).
+    /// - Registers are reset to 0 (vector registers also have an illegal vector type).
+    /// - Memory reservation is cleared.
+    /// - Interrupt controller state is reset.
+    /// - Guest trap registers are cleared.
+    /// - Not suspended and no I/O handle is ready.
+    /// - The engine is at a safepoint (if the `safepoint` feature is enabled).
+    /// - Guest timers are deleted (if the `timer` feature is enabled).
+    /// - Capabilities are revoked.
+    /// - Fetched code is dropped (if the `fetch_batch` feature is enabled).
+    /// - Log record budget is refilled (if the `log` feature is enabled).
+    /// - Debugger stop reason, triggers and debug monitor are cleared (if the `debugger` feature is enabled).
+    /// - Program break is reset to the heap start and the exit code is cleared (if the `libc_support` feature is enabled).
+    /// - Guest runtime state is cleared and the entry convention is applied (if the `runtime` feature is enabled).
+    pub fn reset(&mut self) {
+        self.program_counter = self.config.entry_point.unwrap_or(0);
+        self.capabilities.clear();
+        self.invalidate_fetch();
+        #[cfg(feature = "log")]
+        self.log_budget.refill();
+        #[cfg(feature = "safepoint")]
+        {
+            self.safepoint = true;
+        }
+        self.waiting = None;
+        self.resume_value = None;
+        self.syscall_response = None;
+        self.ready = 0;
+        self.trap = TrapState::default();
+        #[cfg(feature = "libc_support")]
+        {
+            self.program_break = self.config.libc.map(|(_, heap)| heap.start).unwrap_or(0);
+            self.exit_code = None;
+        }
+        #[cfg(feature = "timer")]
+        self.timers.clear();
+        self.registers.reset();
+        #[cfg(feature = "v_extension")]
+        self.vector.reset();
+        #[cfg(feature = "a_extension")]
+        {
+            self.memory_reservation = None;
+        }
+        #[cfg(feature = "interrupt")]
+        {
+            self.interrupt = Interrupt::default();
+        }
+        #[cfg(feature = "runtime")]
+        self.runtime.reset();
+        #[cfg(feature = "debugger")]
+        {
+            self.debugger.stop = None;
+            self.debugger.triggers = Default::default();
+            self.debugger.monitor = Default::default();
+        }
+        self.enter();
+    }
+
+    /// Get the performance counters (check [`crate::counters`]).
+    #[cfg(feature = "counters")]
+    pub fn counters(&self) -> &PerformanceCounters {
+        &self.counters
+    }
+
+    /// Take the performance counters, clearing them (ex.: at the end of a billing period).
+    ///
+    /// Returns:
+    /// - `PerformanceCounters`: Counters since the last time they were taken.
+    #[cfg(feature = "counters")]
+    pub fn reset_counters(&mut self) -> PerformanceCounters {
+        core::mem::take(&mut self.counters)
+    }
+
+    /// Run the engine
+    /// If the `instruction_limit` feature is enabled, the engine will yield when the limit is reached.
+    /// If the `safepoint` feature is enabled, the configured `YieldPoint` restricts where the engine yields.
+    ///
+    /// While suspended on a blocking syscall ([`Engine::waiting_for`]), returns immediately without running.
+    ///
+    /// Returns:
+    /// - `Ok(bool)`: Success, returns if should continue:
+    ///     - `True`: Continue running (yielded or suspended, call `run` again).
+    ///     - `False`: Stop running (halted, call `reset` prior to running again).
+    /// - `Err(EmbiveError)`: Failed to run.
+    ///     - Maximum run depth exceeded, nothing was executed ([`EmbiveError::RecursionLimit`]).
+    ///     - Not enough scratch memory for the configuration, nothing was executed ([`EmbiveError::ScratchTooSmall`]).
+    #[cfg_attr(
+        all(feature = "cortex_m_optimized", target_arch = "arm"),
+        link_section = ".itcm.embive"
+    )]
+    pub fn run(&mut self) -> Result<bool, EmbiveError> {
+        if self.waiting.is_some() {
+            // Suspended, wait for the host to wake the engine
+            return Ok(true);
+        }
+
+        // Nested runs are limited (check `Config::max_run_depth`)
+        let _run = self.enter_run()?;
+
+        #[cfg(feature = "interrupt")]
+        if self.config.interrupt_granularity == Granularity::Yield {
+            // Deliver interrupts raised while yielded
+            self.deliver_interrupt()?;
+        }
+
+        #[cfg(feature = "safepoint")]
+        if self.config.yield_point == YieldPoint::Safepoint {
+            return match self.instrumented() {
+                true => self.run_to_safepoint::<true>(),
+                false => self.run_to_safepoint::<false>(),
+            };
+        }
+
+        match self.instrumented() {
+            true => self.run_loop::<true>(),
+            false => self.run_loop::<false>(),
+        }
+    }
+
+    /// Instruction loop of [`Engine::run`], monomorphized for instrumented and uninstrumented runs
+    /// (check [`Engine::instrumented`]).
+    ///
+    /// Returns:
+    /// - `Ok(bool)`: Success, returns if should continue (check [`Engine::run`]).
+    /// - `Err(EmbiveError)`: Failed to run.
+    #[cfg_attr(
+        all(feature = "cortex_m_optimized", target_arch = "arm"),
+        link_section = ".itcm.embive"
+    )]
+    fn run_loop<const INSTRUMENTED: bool>(&mut self) -> Result<bool, EmbiveError> {
+        #[cfg(feature = "instruction_limit")]
+        {
+            // Check if there is an instruction limit
+            self.budget = Budget::Unlimited;
+            if self.config.instruction_limit > 0 {
+                // Run the engine with an instruction limit
+                self.budget = Budget::Slice;
+                self.slice = self.config.instruction_limit;
+                while self.slice > 0 {
+                    self.slice -= 1;
+
+                    // Step through the program
+                    if !self.run_step::<INSTRUMENTED>()? {
+                        // Stop running (halted, suspended or stopped)
+                        return Ok(self.stopped());
+                    }
+                }
+
+                // Yield
+                return Ok(true);
+            }
+        }
+
+        // No instruction limit
+        loop {
+            // Step through the program
+            if !self.run_step::<INSTRUMENTED>()? {
+                // Stop running (halted, suspended or stopped)
+                return Ok(self.stopped());
+            }
+        }
+    }
+
+    /// Enter a run, checking the scratch memory ([`Engine::set_scratch`]) and the maximum run depth
+    /// ([`Config::max_run_depth`]).
+    ///
+    /// Returns:
+    /// - `Ok(RunGuard)`: Run entered, left when dropped.
+    /// - `Err(EmbiveError)`: Failed to enter the run.
+    ///     - Not enough scratch memory for the configuration ([`EmbiveError::ScratchTooSmall`]).
+    ///     - Maximum run depth exceeded ([`EmbiveError::RecursionLimit`]).
+    fn enter_run(&mut self) -> Result<RunGuard, EmbiveError> {
+        self.check_scratch()?;
+        let run = RunGuard::enter(self.config.max_run_depth)?;
+
+        // The host may have changed the code while not running
+        self.invalidate_fetch();
+
+        #[cfg(feature = "accounting")]
+        {
+            self.accounting.peak_run_depth = self.accounting.peak_run_depth.max(run.depth());
+        }
+
+        Ok(run)
+    }
+
+    /// Check if the execution is instrumented by the host: misaligned access traps, disabled extensions,
+    /// retire hooks or telemetry (if the `telemetry` feature is enabled). Checked once per run (the configuration can't change while running),
+    /// to select the instruction loop: uninstrumented runs don't test for the host hooks at all.
+    ///
+    /// Returns:
+    /// - `bool`: Instructions go through the host hooks ([`Engine::before_instruction`] and
+    ///   [`Engine::after_instruction`]).
+    fn instrumented(&self) -> bool {
+        #[cfg(feature = "telemetry")]
+        if self.telemetry.enabled() {
+            return true;
+        }
+
+        self.config.guest_traps & trap::MISALIGNED != 0
+            || self.config.disabled_extensions != 0
+            || !self.config.retire_hooks.is_empty()
+    }
+
+    /// Check if the engine stopped without halting: suspended on a blocking syscall, or stopped by the debugger.
+    #[inline]
+    fn stopped(&self) -> bool {
+        #[cfg(feature = "debugger")]
+        if self.debugger.stop.is_some() {
+            return true;
+        }
+
+        self.waiting.is_some()
+    }
+
+    /// Run the engine with fuel: every executed instruction consumes one unit of fuel, and the engine stops when
+    /// it runs out. Fuel left when the guest halts or is suspended is kept for the next run, so hosts can
+    /// schedule multiple guests cooperatively (ex.: round-robin with a fixed fuel per turn).
+    ///
+    /// The instruction limit and the yield point of the configuration are not used (check [`Engine::run`]).
+    ///
+    /// Arguments:
+    /// - `fuel`: Fuel added to the remaining fuel before running (check [`Engine::refill_fuel`]).
+    ///
+    /// Returns:
+    /// - `Ok(RunResult)`: Why the engine stopped.
+    /// - `Err(EmbiveError)`: Failed to run (the fuel of the failed instruction is consumed).
+    ///     - Maximum run depth exceeded, nothing was executed ([`EmbiveError::RecursionLimit`]).
+    ///     - Not enough scratch memory for the configuration, nothing was executed ([`EmbiveError::ScratchTooSmall`]).
+    #[cfg(feature = "instruction_limit")]
+    pub fn run_with_fuel(&mut self, fuel: u64) -> Result<RunResult, EmbiveError> {
+        self.refill_fuel(fuel);
+
+        if self.waiting.is_some() {
+            // Suspended, wait for the host to wake the engine
+            return Ok(RunResult::Syscall);
+        }
+
+        // Nested runs are limited (check `Config::max_run_depth`)
+        let _run = self.enter_run()?;
+
+        #[cfg(feature = "interrupt")]
+        if self.config.interrupt_granularity == Granularity::Yield {
+            // Deliver interrupts raised while yielded
+            self.deliver_interrupt()?;
+        }
+
+        self.budget = Budget::Fuel;
+        match self.instrumented() {
+            true => self.fuel_loop::<true>(),
+            false => self.fuel_loop::<false>(),
+        }
+    }
+
+    /// Instruction loop of [`Engine::run_with_fuel`] (check [`Engine::run_loop`]).
+    ///
+    /// Returns:
+    /// - `Ok(RunResult)`: Why the engine stopped.
+    /// - `Err(EmbiveError)`: Failed to run.
+    #[cfg(feature = "instruction_limit")]
+    fn fuel_loop<const INSTRUMENTED: bool>(&mut self) -> Result<RunResult, EmbiveError> {
+        while self.fuel > 0 {
+            self.fuel -= 1;
+
+            // Step through the program
+            if !self.run_step::<INSTRUMENTED>()? {
+                #[cfg(feature = "debugger")]
+                match self.debugger.stop {
+                    Some(StopReason::Step(_)) => return Ok(RunResult::Debugger),
+                    Some(_) => {
+                        // Stopped before executing the instruction
+                        self.fuel += 1;
+                        return Ok(RunResult::Debugger);
+                    }
+                    None => {}
+                }
+
+                return Ok(match self.waiting {
+                    Some(_) => RunResult::Syscall,
+                    None => RunResult::Halted,
+                });
+            }
+        }
+
+        Ok(RunResult::OutOfFuel)
+    }
+
+    /// Get the remaining fuel (check [`Engine::run_with_fuel`]).
+    ///
+    /// Returns:
+    /// - `u64`: Remaining fuel, in instructions.
+    #[cfg(feature = "instruction_limit")]
+    pub fn fuel(&self) -> u64 {
+        self.fuel
+    }
+
+    /// Add fuel to the remaining fuel (saturating, check [`Engine::run_with_fuel`]).
+    ///
+    /// Arguments:
+    /// - `fuel`: Fuel to add, in instructions.
+    #[cfg(feature = "instruction_limit")]
+    pub fn refill_fuel(&mut self, fuel: u64) {
+        self.fuel = self.fuel.saturating_add(fuel);
+    }
+
+    /// Get the remaining instruction budget of the current (or last) run: instructions the engine executes
+    /// before yielding (after the current one, if called while running). The guest can read it
+    /// with [`Config::budget_csr`], to wrap up work before being preempted (ex.: not starting another audio block).
+    ///
+    /// Returns:
+    /// - `u64`: Remaining fuel ([`Engine::run_with_fuel`]), instructions left before the instruction limit
+    ///   ([`Config::instruction_limit`], at least before the next safepoint with `YieldPoint::Safepoint`),
+    ///   or `u64::MAX` (no limit).
+    #[cfg(feature = "instruction_limit")]
+    pub fn budget(&self) -> u64 {
+        match self.budget {
+            Budget::Unlimited => u64::MAX,
+            Budget::Slice => self.slice as u64,
+            Budget::Fuel => self.fuel,
+        }
+    }
+
+    /// Drain the remaining fuel (ex.: to preempt a guest from a syscall).
+    ///
+    /// Returns:
+    /// - `u64`: Fuel that was remaining, in instructions.
+    #[cfg(feature = "instruction_limit")]
+    pub fn drain_fuel(&mut self) -> u64 {
+        core::mem::take(&mut self.fuel)
+    }
+
+    /// Run the engine until a safepoint is reached after the instruction limit (if any).
+    ///
+    /// Returns:
+    /// - `Ok(bool)`: Success, returns if should continue (check [`Engine::run`]).
+    /// - `Err(EmbiveError)`: Failed to run.
+    #[cfg(feature = "safepoint")]
+    fn run_to_safepoint<const INSTRUMENTED: bool>(&mut self) -> Result<bool, EmbiveError> {
+        #[cfg(feature = "instruction_limit")]
+        let instruction_limit = self.config.instruction_limit;
+        #[cfg(not(feature = "instruction_limit"))]
+        let instruction_limit = 0;
+
+        #[cfg(feature = "instruction_limit")]
+        {
+            self.budget = match instruction_limit {
+                0 => Budget::Unlimited,
+                _ => Budget::Slice,
+            };
+            self.slice = instruction_limit;
+        }
+
+        let mut executed = 0u32;
+        loop {
+            #[cfg(feature = "instruction_limit")]
+            {
+                self.slice = self.slice.saturating_sub(1);
+            }
+
+            // Step through the program
+            if !self.run_step::<INSTRUMENTED>()? {
+                // Stop running (halted, suspended or stopped)
+                return Ok(self.stopped());
+            }
+
+            executed = executed.saturating_add(1);
+            if self.safepoint && executed >= instruction_limit {
+                // Yield
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Run the engine for a time slice (check [`crate::adapter::Sandbox`]), entering the run like [`Engine::run`].
+    /// The slice ends after `slice` instructions, or when `yield_now` returns true, at the configured yield point
+    /// (if the `safepoint` feature is enabled).
+    /// The instruction limit is not used.
+    ///
+    /// Arguments:
+    /// - `slice`: Slice length in guest instructions.
+    /// - `yield_now`: Called after every instruction, returns if a yield was requested.
+    ///
+    /// Returns:
+    /// - `Ok(bool)`: Success, returns if should continue (check [`Engine::run`]).
+    /// - `Err(EmbiveError)`: Failed to run (check [`Engine::run`]).
+    #[cfg(feature = "adapter")]
+    pub(crate) fn run_slice(
+        &mut self,
+        slice: u32,
+        yield_now: impl FnMut() -> bool,
+    ) -> Result<bool, EmbiveError> {
+        if self.waiting.is_some() {
+            // Suspended, wait for the host to wake the engine
+            return Ok(true);
+        }
+
+        // Nested runs are limited (check `Config::max_run_depth`)
+        let _run = self.enter_run()?;
+
+        #[cfg(feature = "interrupt")]
+        if self.config.interrupt_granularity == Granularity::Yield {
+            // Deliver interrupts raised while yielded
+            self.deliver_interrupt()?;
+        }
+
+        #[cfg(feature = "instruction_limit")]
+        {
+            self.budget = Budget::Slice;
+            self.slice = slice;
+        }
+
+        match self.instrumented() {
+            true => self.slice_loop::<true>(slice, yield_now),
+            false => self.slice_loop::<false>(slice, yield_now),
+        }
+    }
+
+    /// Instruction loop of [`Engine::run_slice`] (check [`Engine::run_loop`]).
+    ///
+    /// Arguments:
+    /// - `slice`: Slice length in guest instructions.
+    /// - `yield_now`: Called after every instruction, returns if a yield was requested.
+    ///
+    /// Returns:
+    /// - `Ok(bool)`: Success, returns if should continue (check [`Engine::run`]).
+    /// - `Err(EmbiveError)`: Failed to run.
+    #[cfg(feature = "adapter")]
+    fn slice_loop<const INSTRUMENTED: bool>(
+        &mut self,
+        slice: u32,
+        mut yield_now: impl FnMut() -> bool,
+    ) -> Result<bool, EmbiveError> {
+        let mut executed = 0u32;
+        loop {
+            #[cfg(feature = "instruction_limit")]
+            {
+                self.slice = self.slice.saturating_sub(1);
+            }
+
+            // Step through the program
+            if !self.run_step::<INSTRUMENTED>()? {
+                // Stop running (halted, suspended or stopped)
+                return Ok(self.stopped());
+            }
+
+            executed = executed.saturating_add(1);
+            if executed >= slice || yield_now() {
+                #[cfg(feature = "safepoint")]
+                if self.config.yield_point == YieldPoint::Safepoint && !self.safepoint {
+                    // Keep running until the next safepoint
+                    continue;
+                }
+
+                // Yield
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Check if the engine is at a safepoint: the last executed instruction was a safepoint,
+    /// or no instruction was executed since the engine was created or reset.
+    /// The guest state is consistent here (ex.: snapshots and hot-reloads are safe).
+    ///
+    /// Returns:
+    /// - `bool`: The engine is at a safepoint.
+    #[cfg(feature = "safepoint")]
+    pub fn at_safepoint(&self) -> bool {
+        self.safepoint
+    }
+
+    /// Set the syscall handler of this engine, a stateful alternative to [`Config::syscall_fn`]
+    /// (ex.: a closure capturing host state, check [`Syscalls`]).
+    /// Takes precedence over the syscall function, not cleared by [`Engine::reset`].
+    ///
+    /// Arguments:
+    /// - `syscalls`: Syscall handler (None = Use the syscall function).
+    pub fn set_syscalls(&mut self, syscalls: Option<&'a mut (dyn Syscalls<M> + Send)>) {
+        self.syscalls = syscalls;
+    }
+
+    /// Attach a trace context to the guest run, included in log records and syscall traces
+    /// (check [`crate::syscall::trace`]). Not cleared by [`Engine::reset`].
+    ///
+    /// Arguments:
+    /// - `trace`: Trace context (None = Detach).
+    pub fn set_trace_context(&mut self, trace: Option<TraceContext>) {
+        self.trace_context = trace;
+    }
+
+    /// Get the trace context of the guest run.
+    pub fn trace_context(&self) -> Option<TraceContext> {
+        self.trace_context
+    }
+
+    /// Format the pending syscall (`a7` and arguments) for traces, with the trace context.
+    ///
+    /// Returns:
+    /// - `Some(SyscallDisplay)`: Syscall formatting (check [`SyscallContract::display`]).
+    /// - `None`: No contract declared for the syscall number ([`Config::syscall_contracts`]).
+    pub fn display_syscall(&self) -> Option<SyscallDisplay<'_>> {
+        let nr = self.registers.inner[Register::A7 as usize];
+        let args = self.registers.inner[Register::A0 as usize..]
+            .first_chunk()
+            // Unwrap is safe because the slice is guaranteed to have more than SYSCALL_ARGS elements.
+            .unwrap();
+
+        syscall::find(self.config.syscall_contracts, nr).map(|contract| {
+            contract
+                .display(args)
+                .with_trace(self.trace_context)
+                .with_timestamp(self.timestamp())
+        })
+    }
+
+    /// Read the host clock, used to timestamp engine events (check [`Config::tick_fn`]).
+    ///
+    /// Returns:
+    /// - `Some(u64)`: Current host tick count.
+    /// - `None`: No tick function configured.
+    #[inline]
+    pub fn timestamp(&self) -> Option<u64> {
+        self.config.tick_fn.map(|tick_fn| tick_fn())
+    }
+
+    /// Refill the log record budget (check [`Config::log_burst`]), ex.: periodically from a host timer.
+    #[cfg(feature = "log")]
+    pub fn refill_log_budget(&mut self) {
+        self.log_budget.refill();
+    }
+
+    /// Get why the debugger stopped the engine (check [`crate::debug`]), until it runs again.
+    ///
+    /// Returns:
+    /// - `Some(StopReason)`: Breakpoint, watchpoint or single-step.
+    /// - `None`: Not stopped by the debugger.
+    #[cfg(feature = "debugger")]
+    pub fn stop_reason(&self) -> Option<StopReason> {
+        self.debugger.stop
+    }
+
+    /// Get what the engine is suspended on (check [`Config::suspend_on_would_block`], [`Config::poll_nr`]
+    /// and [`Config::yield_nr`]). The syscall is retried when the engine is woken ([`Engine::wake`])
+    /// or resumed ([`Engine::resume`]) and run again.
+    ///
+    /// Returns:
+    /// - `Some(WaitingFor)`: I/O handle(s) the engine is waiting for.
+    /// - `None`: Not suspended.
+    pub fn waiting_for(&self) -> Option<WaitingFor> {
+        self.waiting
+    }
+
+    /// Mark an I/O handle as ready (ex.: data arrived), waking the engine if suspended on it.
+    /// Readiness of pollable handles (below [`POLL_HANDLES`]) is kept until polled by the guest,
+    /// so guests should expect spurious readiness (ex.: a handle also used by a blocking syscall).
+    ///
+    /// Arguments:
+    /// - `handle`: I/O handle that is ready.
+    ///
+    /// Returns:
+    /// - `bool`: The engine was suspended on the handle, and is now woken.
+    pub fn wake(&mut self, handle: u32) -> bool {
+        let mask = 1u32.checked_shl(handle).unwrap_or(0);
+        self.ready |= mask;
+
+        let woken = match self.waiting {
+            Some(WaitingFor::Handle(waiting)) => waiting == handle,
+            Some(WaitingFor::AnyOf(waiting)) => waiting & mask != 0,
+            Some(WaitingFor::Resume(_) | WaitingFor::Syscall(_)) | None => false,
+        };
+        if woken {
+            self.waiting = None;
+        }
+
+        woken
+    }
+
+    /// Get the guest exit code (check [`crate::libc`]).
+    ///
+    /// Returns:
+    /// - `Some(i32)`: The guest exited with this code (the engine is halted).
+    /// - `None`: The guest didn't exit.
+    #[cfg(feature = "libc_support")]
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    /// Get the guest runtime exit code (check [`crate::runtime`]).
+    ///
+    /// Returns:
+    /// - `Some(i32)`: The guest exited with this code (the engine is halted).
+    /// - `None`: The guest didn't exit.
+    #[cfg(feature = "runtime")]
+    pub fn runtime_exit_code(&self) -> Option<i32> {
+        self.runtime.exit_code
+    }
+
+    /// Get the guest panic report (check [`crate::runtime`]).
+    ///
+    /// Returns:
+    /// - `Some(&PanicReport)`: The guest panicked (the engine is halted).
+    /// - `None`: The guest didn't panic.
+    #[cfg(feature = "runtime")]
+    pub fn panic_report(&self) -> Option<&PanicReport> {
+        self.runtime.panic.as_ref()
+    }
+
+    /// Get the guest program break (check [`crate::libc`]).
+    #[cfg(feature = "libc_support")]
+    pub fn program_break(&self) -> u32 {
+        self.program_break
+    }
+
+    /// Resume a guest that yielded to the host (check [`Config::yield_nr`]).
+    /// The yield syscall completes when the engine is run again, returning the value to the guest.
+    ///
+    /// Arguments:
+    /// - `value`: Value passed in to the guest (`a1`).
+    ///
+    /// Returns:
+    /// - `bool`: The guest had yielded, and is now resumed.
+    pub fn resume(&mut self, value: i32) -> bool {
+        if !matches!(self.waiting, Some(WaitingFor::Resume(_))) {
+            return false;
+        }
+
+        self.waiting = None;
+        self.resume_value = Some(value);
+        true
+    }
+
+    /// Get the syscall the engine is suspended on, with the guest memory (check [`Config::defer_syscalls`]).
+    ///
+    /// Returns:
+    /// - `Some((SyscallRequest, &mut M))`: Pending syscall and guest memory (ex.: to read or fill syscall buffers).
+    /// - `None`: Not suspended on a syscall.
+    pub fn syscall_request(&mut self) -> Option<(SyscallRequest, &mut M)> {
+        match self.waiting {
+            Some(WaitingFor::Syscall(request)) => Some((request, &mut *self.memory)),
+            _ => None,
+        }
+    }
+
+    /// Respond to the syscall the engine is suspended on (check [`Config::defer_syscalls`]).
+    /// The syscall completes when the engine is run again, returning the result to the guest
+    /// (a [`Errno::WouldBlock`] error suspends it again, if [`Config::suspend_on_would_block`] is set).
+    ///
+    /// Arguments:
+    /// - `response`: Syscall result.
+    ///
+    /// Returns:
+    /// - `bool`: The engine was suspended on a syscall, and is now resumed.
+    pub fn respond(&mut self, response: SyscallResponse) -> bool {
+        if !matches!(self.waiting, Some(WaitingFor::Syscall(_))) {
+            return false;
+        }
+
+        self.waiting = None;
+        self.syscall_response = Some(response);
+        true
+    }
+
+    /// Advance the guest timers with the host clock, delivering the expired ones (check [`crate::timer`]).
+    ///
+    /// Arguments:
+    /// - `now`: Host clock (ticks), monotonic.
+    ///
+    /// Returns:
+    /// - `Ok(())`: Timers advanced.
+    /// - `Err(EmbiveError)`: Failed to raise an interrupt (ex.: no interrupt function).
+    #[cfg(feature = "timer")]
+    pub fn advance_timers(&mut self, now: u64) -> Result<(), EmbiveError> {
+        for delivery in self.timers.advance(now).into_iter().flatten() {
+            match delivery {
+                TimerDelivery::Event(handle) => {
+                    self.wake(handle);
+                }
+                #[cfg(feature = "interrupt")]
+                TimerDelivery::Interrupt(line) => self.raise_interrupt(line)?,
+                #[cfg(not(feature = "interrupt"))]
+                TimerDelivery::Interrupt(_) => unreachable!("Timer interrupts are not created"),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the next guest timer deadline, so the host can sleep until then.
+    ///
+    /// Returns:
+    /// - `Some(u64)`: Earliest deadline of the armed timers (host clock ticks).
+    /// - `None`: No timer is armed.
+    #[cfg(feature = "timer")]
+    pub fn next_timer_deadline(&self) -> Option<u64> {
+        self.timers.next_deadline()
+    }
+
+    /// Poll I/O handles requested by the guest (`a0`: handle mask).
+    ///
+    /// Returns:
+    /// - `Some(Result<i32, i32>)`: Ready handles (mask) or error code.
+    /// - `None`: No handle is ready, suspend the engine.
+    fn poll_syscall(&mut self) -> Option<Result<i32, i32>> {
+        let mask = self.registers.inner[Register::A0 as usize] as u32;
+        if mask == 0 {
+            return Some(Err(Errno::InvalidArgument.code()));
+        }
+
+        let ready = self.ready & mask;
+        if ready == 0 {
+            self.waiting = Some(WaitingFor::AnyOf(mask));
+            return None;
+        }
+
+        // Consume the readiness of the returned handles
+        self.ready &= !ready;
+        Some(Ok(ready as i32))
+    }
+
+    /// Step through a single instruction from the current program counter.
+    ///
+    /// Returns:
+    /// - `Ok(bool)`: Success, returns if should continue:
+    ///     - `True`: Should continue.
+    ///     - `False`: Should stop (halted, suspended on a blocking syscall or stopped by the debugger).
+    /// - `Err(EmbiveError)`: Failed to execute.
+    #[inline]
+    pub fn step(&mut self) -> Result<bool, EmbiveError> {
+        // The host hooks check their own configuration, a single step doesn't need the uninstrumented path
+        self.run_step::<true>()
+    }
+
+    /// Step through a single instruction, inside a run (check [`Engine::step`]).
+    ///
+    /// `INSTRUMENTED`: Go through the host hooks (check [`Engine::instrumented`]).
+    #[inline(always)]
+    pub(crate) fn run_step<const INSTRUMENTED: bool>(&mut self) -> Result<bool, EmbiveError> {
+        #[cfg(all(feature = "replay", feature = "interrupt"))]
+        if self.replay.replaying() {
+            // Deliver the recorded interrupts
+            self.replay_interrupts()?;
+        }
+
+        #[cfg(feature = "interrupt")]
+        if self.config.interrupt_granularity == Granularity::Instruction {
+            // Deliver interrupts before the instruction
+            self.deliver_interrupt()?;
+        }
+
+        // Fetch next instruction
+        let data = self.fetch().map_err(|error| match error {
+            EmbiveError::InvalidMemoryAddress => EmbiveError::FetchFault {
+                pc: self.program_counter,
+            },
+            error => error,
+        })?;
+
+        #[cfg(feature = "debugger")]
+        if let Some(reason) = self
+            .debugger
+            .check(self.program_counter, data, &self.registers)
+        {
+            // Breakpoint or watchpoint hit, stop before the instruction
+            self.debugger.stop = Some(reason);
+            return Ok(false);
+        }
+
+        #[cfg(feature = "interrupt")]
+        let next_instruction = self
+            .program_counter
+            .wrapping_add(crate::instruction::instruction_size(data));
+
+        // Decode and execute the instruction
+        let ret = self.execute::<INSTRUMENTED>(data)?;
+
+        #[cfg(feature = "interrupt")]
+        if ret && self.config.interrupt_granularity == Granularity::BasicBlock {
+            self.interrupt.sequential += 1;
+
+            // Deliver interrupts at the end of the basic block (or after too many sequential instructions)
+            if self.program_counter != next_instruction
+                || self.interrupt.sequential >= crate::interrupt::BASIC_BLOCK_LATENCY
+            {
+                self.interrupt.sequential = 0;
+                self.deliver_interrupt()?;
+            }
+        }
+
+        #[cfg(feature = "debugger")]
+        if ret {
+            if let Some(entry) = self.debugger.monitor.retired(self.program_counter) {
+                // Guest single-step, enter the debug monitor
+                self.program_counter = entry;
+            }
+
+            if let Some(reason) = self.debugger.stepped(self.program_counter) {
+                self.debugger.stop = Some(reason);
+                return Ok(false);
+            }
+        }
+
+        Ok(ret)
+    }
+
+    /// Execute a single instruction (raw), as if it was fetched from the program counter, without reading the
+    /// code memory (ex.: REPL-style tools, testing host hooks or instrumentation shims).
+    /// The program counter is updated by the instruction (next instruction, jump or branch target).
+    ///
+    /// Interrupts are not delivered (check [`Engine::step`]). The instruction is accounted and reported in the
+    /// illegal instruction stats as any other executed instruction.
+    ///
+    /// Arguments:
+    /// - `data`: Instruction (raw), only the lower 16 bits are used for compressed instructions.
+    ///
+    /// Returns:
+    /// - `Ok(bool)`: Success, returns if should continue (check [`Engine::step`]).
+    /// - `Err(EmbiveError)`: Failed to execute, with the instruction context (ex.: [`EmbiveError::IllegalInstruction`]
+    ///   or [`EmbiveError::LoadFault`]).
+    #[inline]
+    pub fn execute_raw(&mut self, data: u32) -> Result<bool, EmbiveError> {
+        self.execute::<true>(data)
+    }
+
+    /// Execute a single instruction (raw), fetched from the program counter (check [`Engine::execute_raw`]).
+    ///
+    /// Arguments:
+    /// - `data`: Instruction (raw).
+    ///
+    /// `INSTRUMENTED`: Go through the host hooks (check [`Engine::instrumented`]).
+    #[inline(always)]
+    fn execute<const INSTRUMENTED: bool>(&mut self, data: u32) -> Result<bool, EmbiveError> {
+        #[cfg(feature = "accounting")]
+        {
+            self.accounting.guest_instructions =
+                self.accounting.guest_instructions.saturating_add(1);
+        }
+
+        #[cfg(feature = "safepoint")]
+        {
+            self.safepoint = false;
+        }
+
+        let address = self.program_counter;
+        #[cfg(feature = "trace")]
+        let traced = self.config.trace.map(|hooks| {
+            (
+                hooks,
+                tracing::fetched(&hooks, address, data, &self.registers),
+            )
+        });
+
+        if INSTRUMENTED {
+            if let Some(result) = self.before_instruction(address, data) {
+                return result;
+            }
+        }
+
+        #[cfg(feature = "replay")]
+        let result = match self.replay.active() && replay::is_input(data) {
+            true => self.execute_input(address, data),
+            false => decode_execute(self, data),
+        };
+        #[cfg(not(feature = "replay"))]
+        let result = decode_execute(self, data);
+
+        let ret = match result {
+            Ok(ret) => ret,
+            Err(error) => return self.exception(error, address, data),
+        };
+
+        // Suspended syscalls aren't retired (retried later)
+        #[cfg(feature = "replay")]
+        if ret || self.program_counter != address {
+            self.replay.retired();
+        }
+
+        #[cfg(feature = "counters")]
+        self.counters.retired(address, data, self.program_counter);
+
+        if INSTRUMENTED {
+            self.after_instruction(address, data);
+        }
+
+        #[cfg(feature = "trace")]
+        if let Some((hooks, traced)) = traced {
+            tracing::retired(&hooks, address, traced, &self.registers);
+        }
+
+        Ok(ret)
+    }
+
+    /// Run the host hooks before an instruction (check [`Engine::instrumented`]).
+    ///
+    /// Arguments:
+    /// - `address`: Instruction address.
+    /// - `data`: Instruction (raw).
+    ///
+    /// Returns:
+    /// - `Some(Result)`: The instruction was trapped, don't execute it (check [`Engine::execute_raw`]).
+    /// - `None`: Execute the instruction.
+    #[cold]
+    #[inline(never)]
+    fn before_instruction(&mut self, address: u32, data: u32) -> Option<Result<bool, EmbiveError>> {
+        if self.config.guest_traps & trap::MISALIGNED != 0 {
+            if let Some((exception, tval)) = self.misaligned(data) {
+                if self.trap(exception, address, tval) {
+                    return Some(Ok(true));
+                }
+            }
+        }
+
+        if self.config.disabled_extensions != 0
+            && self.config.disabled_extensions & IsaExtension::of(data).bit() != 0
+        {
+            return Some(self.exception(EmbiveError::InvalidInstruction, address, data));
+        }
+
+        None
+    }
+
+    /// Run the host hooks after an instruction retired (check [`Engine::instrumented`]).
+    ///
+    /// Arguments:
+    /// - `address`: Instruction address.
+    /// - `data`: Instruction (raw).
+    #[cold]
+    #[inline(never)]
+    fn after_instruction(&mut self, address: u32, data: u32) {
+        if !self.config.retire_hooks.is_empty() {
+            retire::retired(self.config.retire_hooks, address, data, &self.registers);
+        }
+
+        #[cfg(feature = "telemetry")]
+        if self.telemetry.tick(self.config.telemetry_interval) {
+            self.record_telemetry();
+        }
+    }
+
+    /// Handle an instruction error: record illegal instructions, skip the ones of probed extensions,
+    /// add the guest instruction context, and vector it to the guest trap handler (if delegated).
+    ///
+    /// Arguments:
+    /// - `error`: Error returned by the instruction.
+    /// - `pc`: Instruction address.
+    /// - `data`: Instruction (raw).
+    ///
+    /// Returns:
+    /// - `Ok(true)`: Trap taken (the program counter is at the trap handler), or probed instruction skipped.
+    /// - `Err(EmbiveError)`: The error, with its context (check [`Engine::execute_raw`]).
+    #[cold]
+    fn exception(&mut self, error: EmbiveError, pc: u32, data: u32) -> Result<bool, EmbiveError> {
+        if error == EmbiveError::InvalidInstruction && self.config.illegal_instruction_stats {
+            let timestamp = self.timestamp();
+            self.illegal_instructions.record(data, pc, timestamp);
+        }
+
+        if error == EmbiveError::InvalidInstruction && self.config.probe_extensions != 0 {
+            let extension = IsaExtension::of(data).bit();
+            if self.config.probe_extensions & extension != 0 {
+                // Attempted, skip it
+                self.probed_extensions |= extension;
+                self.program_counter = pc.wrapping_add(crate::instruction::instruction_size(data));
+                return Ok(true);
+            }
+        }
+
+        match self.fault(error, pc, data) {
+            EmbiveError::IllegalInstruction { raw, .. }
+                if self.trap(Exception::IllegalInstruction, pc, raw) =>
+            {
+                Ok(true)
+            }
+            error => Err(error),
+        }
+    }
+
+    /// Vector an exception to the guest trap handler, if delegated (check [`crate::trap`]).
+    ///
+    /// Arguments:
+    /// - `exception`: Exception.
+    /// - `pc`: Address of the instruction that trapped.
+    /// - `tval`: Exception value (`mtval`).
+    ///
+    /// Returns:
+    /// - `true`: Trap taken, the program counter is at the trap handler.
+    /// - `false`: Not delegated, no handler installed or inside the handler (report the exception to the host).
+    #[cold]
+    pub(crate) fn trap(&mut self, exception: Exception, pc: u32, tval: u32) -> bool {
+        if self.config.guest_traps & exception.bit() == 0 {
+            return false;
+        }
+
+        match self.trap.take(exception.code(), pc, tval) {
+            Some(handler) => {
+                self.program_counter = handler;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Check if an instruction accesses memory at an address not aligned to the access width.
+    ///
+    /// Arguments:
+    /// - `data`: Instruction (raw).
+    ///
+    /// Returns:
+    /// - `Some((Exception, u32))`: Misaligned load or store, and its address.
+    /// - `None`: Aligned access, or the instruction doesn't access memory.
+    #[inline]
+    fn misaligned(&self, data: u32) -> Option<(Exception, u32)> {
+        let access = memory_access(data)?;
+        let base = self.registers.get(access.base).ok()?;
+        let address = (base as u32).wrapping_add_signed(access.offset);
+        if address % access.len == 0 {
+            return None;
+        }
+
+        let exception = match access.access {
+            Access::Read => Exception::LoadMisaligned,
+            Access::Write | Access::ReadWrite => Exception::StoreMisaligned,
+        };
+        Some((exception, address))
+    }
+
+    /// Add the guest instruction context to an error.
+    ///
+    /// Arguments:
+    /// - `error`: Error returned by the instruction.
+    /// - `pc`: Instruction address.
+    /// - `data`: Instruction (raw).
+    ///
+    /// Returns:
+    /// - `EmbiveError`: Bare instruction and memory errors with their context ([`EmbiveError::IllegalInstruction`],
+    ///   [`EmbiveError::LoadFault`] and [`EmbiveError::StoreFault`]), other errors unchanged
+    ///   (ex.: vector accesses, not decoded).
+    #[cold]
+    fn fault(&self, error: EmbiveError, pc: u32, data: u32) -> EmbiveError {
+        match error {
+            EmbiveError::InvalidInstruction => {
+                #[cfg(feature = "c_extension")]
+                let data = match data & 0b11 {
+                    0b11 => data,
+                    _ => data & 0xFFFF,
+                };
+
+                EmbiveError::IllegalInstruction { pc, raw: data }
+            }
+            EmbiveError::InvalidMemoryAddress => {
+                let Some(access) = memory_access(data) else {
+                    return error;
+                };
+                let Ok(base) = self.registers.get(access.base) else {
+                    return error;
+                };
+
+                let address = (base as u32).wrapping_add_signed(access.offset);
+                match access.access {
+                    Access::Read => EmbiveError::LoadFault { pc, address },
+                    Access::Write | Access::ReadWrite => EmbiveError::StoreFault { pc, address },
+                }
+            }
+            error => error,
+        }
+    }
+
+    /// Fetch the next instruction (raw) from the program counter.
+    ///
+    /// Returns:
+    /// - `Ok(u32)`: The instruction (raw) that was fetched.
+    /// - `Err(EmbiveError)`: The program counter is out of bounds.
+    #[inline]
+    pub fn fetch(&mut self) -> Result<u32, EmbiveError> {
+        #[cfg(feature = "fetch_batch")]
+        if let Some(data) = self.fetch_buffer.fetch(self.memory, self.program_counter) {
+            return Ok(data);
+        }
+
+        let data = match self.memory.fetch::<4>(self.program_counter) {
+            Ok(data) => data,
+            #[cfg(feature = "c_extension")]
+            Err(error) => {
+                // A compressed instruction may be the last halfword of the code
+                let data = self
+                    .memory
+                    .fetch::<2>(self.program_counter)
+                    .or(Err(error))?;
+                if data[0] & 0b11 == 0b11 {
+                    return Err(error);
+                }
+
+                return Ok(u16::from_le_bytes(data) as u32);
+            }
+            #[cfg(not(feature = "c_extension"))]
+            Err(error) => return Err(error),
+        };
+        Ok(u32::from_le_bytes(data))
+    }
+
+    /// Check a guest store against the stack guard region (check [`Config::stack_guard`]).
+    ///
+    /// Arguments:
+    /// - `address`: Store address.
+    /// - `len`: Store width in bytes.
+    ///
+    /// Returns:
+    /// - `Ok(())`: The store doesn't touch the guard region.
+    /// - `Err(EmbiveError)`: Stack overflow, at the current instruction ([`EmbiveError::StackOverflow`]).
+    #[inline(always)]
+    pub(crate) fn check_stack_guard(&self, address: u32, len: u32) -> Result<(), EmbiveError> {
+        if let Some((start, size)) = self.config.stack_guard {
+            // Store starts inside the guard, or the guard starts inside the store
+            if address.wrapping_sub(start) < size || start.wrapping_sub(address) < len {
+                return Err(EmbiveError::StackOverflow {
+                    pc: self.program_counter,
+                    address,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Drop the code fetched ahead of the program counter (if the `fetch_batch` feature is enabled).
+    /// Needed if the host changes the code between [`Engine::step`] calls, or from host callbacks
+    /// other than the syscall, interrupt and cache functions.
+    /// The engine drops it when running, on reset, syscalls, interrupt delivery and `fence.i`.
+    #[inline]
+    pub fn invalidate_fetch(&mut self) {
+        #[cfg(feature = "fetch_batch")]
+        self.fetch_buffer.invalidate();
+    }
+
+    /// Raise an interrupt.
+    /// The interrupt line is kept pending until delivered, according to the configured [`Granularity`],
+    /// the line enable and priority, and the interrupts in service (check [`crate::interrupt`]).
+    ///
+    /// Arguments:
+    /// - `line`: Interrupt line (from 0 to [`crate::interrupt::INTERRUPT_LINES`] - 1).
+    ///
+    /// Returns:
+    /// - `Ok(())`: Interrupt is pending.
+    /// - `Err(EmbiveError)`: Failed to raise the interrupt.
+    ///     - Interrupt function is not set (and interrupts aren't delivered to the guest, check
+    ///       [`Config::guest_interrupts`]).
+    ///     - Interrupt line is out of bounds.
+    #[cfg(feature = "interrupt")]
+    pub fn raise_interrupt(&mut self, line: u32) -> Result<(), EmbiveError> {
+        if self.config.interrupt_fn.is_none() && !self.config.guest_interrupts {
+            return Err(EmbiveError::NoInterruptFunction);
+        }
+
+        self.interrupt.raise(line)
+    }
+
+    /// Complete the interrupt in service (`mret` semantics).
+    /// Restores the global interrupt enable (`mstatus.MIE`) from `mstatus.MPIE`
+    /// and leaves the highest priority level in service.
+    #[cfg(feature = "interrupt")]
+    pub fn complete_interrupt(&mut self) {
+        self.interrupt.complete();
+    }
+
+    /// Deliver the highest priority pending interrupt (if any) to the interrupt function,
+    /// or to the guest trap handler (check [`Config::guest_interrupts`]).
+    ///
+    /// Returns:
+    /// - `Ok(())`: No interrupt deliverable or interrupt delivered.
+    /// - `Err(EmbiveError)`: Failed to deliver the interrupt.
+    #[cfg(feature = "interrupt")]
+    #[inline(always)]
+    pub(crate) fn deliver_interrupt(&mut self) -> Result<(), EmbiveError> {
+        if self.interrupt.pending == 0 {
+            return Ok(());
+        }
+
+        // Host interrupts are ignored while replaying (check `Engine::replay_interrupts`)
+        #[cfg(feature = "replay")]
+        if self.replay.replaying() {
+            return Ok(());
+        }
+
+        if self.config.guest_interrupts {
+            // Kept pending while no handler is installed, or inside the handler
+            if !self.trap.accepts() {
+                return Ok(());
+            }
+
+            if let Some(line) = self.interrupt.claim() {
+                let cause = trap::INTERRUPT_CAUSE | (trap::LOCAL_INTERRUPT_BASE + line);
+                if let Some(handler) = self.trap.take(cause, self.program_counter, 0) {
+                    self.invalidate_fetch();
+                    self.program_counter = handler;
+                    #[cfg(feature = "replay")]
+                    self.record_interrupt(line)?;
+                }
+            }
+
+            return Ok(());
+        }
+
+        if let Some(line) = self.interrupt.claim() {
+            if let Some(interrupt_fn) = self.config.interrupt_fn {
+                self.invalidate_fetch();
+                return interrupt_fn(line, self);
+            }
+
+            // No interrupt function set
+            return Err(EmbiveError::NoInterruptFunction);
+        }
+
+        Ok(())
+    }
+
+    /// Handle a system call.
+    /// The system call function is called with the system call number and arguments.
+    ///
+    /// Returns:
+    /// - `Ok(bool)`: Syscall executed:
+    ///     - `True`: Should continue execution.
+    ///     - `False`: Engine suspended (check [`Config::suspend_on_would_block`]), retry the syscall when woken.
+    /// - `Err(EmbiveError)`: Failed to execute the system call function.
+    ///     - System call function is not set.
+    #[inline(always)]
+    pub(crate) fn syscall(&mut self) -> Result<bool, EmbiveError> {
+        // The syscall may change the code
+        self.invalidate_fetch();
+
+        // Syscall Number
+        let nr = self.registers.inner[Register::A7 as usize];
+
+        if self.config.poll_nr == Some(nr) {
+            // Poll I/O handles (handled by the engine)
+            let Some(result) = self.poll_syscall() else {
+                // Suspended, retry the syscall when woken
+                return Ok(false);
+            };
+            self.syscall_result(result);
+            return Ok(true);
+        }
+
+        if self.config.yield_nr == Some(nr) {
+            // Yield to the host (handled by the engine)
+            let Some(value) = self.resume_value.take() else {
+                // Suspended, retry the syscall when resumed
+                self.waiting = Some(WaitingFor::Resume(
+                    self.registers.inner[Register::A0 as usize],
+                ));
+                return Ok(false);
+            };
+            self.syscall_result(Ok(value));
+            return Ok(true);
+        }
+
+        #[cfg(feature = "timer")]
+        if self.config.timer_nr == Some(nr) {
+            // Timer service (handled by the engine)
+            let result = self
+                .timers
+                .syscall(&self.registers.inner[Register::A0 as usize..]);
+            self.syscall_result(result);
+            return Ok(true);
+        }
+
+        #[cfg(feature = "accounting")]
+        if self.config.counter_nr == Some(nr) {
+            // Counter read (handled by the engine), 64-bit value in `a1` (low) and `a2` (high)
+            let counter = self.registers.inner[Register::A0 as usize];
+            let value = self.counter_view.read(counter, &self.accounting);
+            let result = value
+                .map(|value| value as i32)
+                .ok_or(Errno::InvalidArgument.code());
+            self.syscall_result(result);
+            self.registers.inner[Register::A2 as usize] =
+                value.map(|value| (value >> 32) as i32).unwrap_or(0);
+            return Ok(true);
+        }
+
+        #[cfg(feature = "crypto")]
+        if let Some((_, crypto_fn)) = self.config.crypto.filter(|(crypto_nr, _)| *crypto_nr == nr) {
+            // Crypto service (host implementation)
+            let mut args = *self.registers.inner[Register::A0 as usize..]
+                .first_chunk()
+                // Unwrap is safe because the slice is guaranteed to have more than SYSCALL_ARGS elements.
+                .unwrap();
+
+            // Resolve the key handle, the implementation only sees the host key id
+            let key = match crypto::key_rights(args[0]) {
+                Some(rights) => self.capabilities.resolve(args[1] as u32, Kind::Key, rights),
+                None => Ok(0),
+            };
+            let result = match key {
+                Ok(key) => {
+                    args[1] = key as i32;
+                    crypto_fn(&args, self.memory)
+                }
+                Err(error) => Err(error.code()),
+            };
+            self.syscall_result(result);
+            return Ok(true);
+        }
+
+        #[cfg(feature = "persistent_regions")]
+        if self.config.persistent_region_nr == Some(nr) {
+            // Persistent region declaration (handled by the engine)
+            let address = self.registers.inner[Register::A0 as usize] as u32;
+            let size = self.registers.inner[Register::A1 as usize] as u32;
+            let result = self.persist_syscall(address, size);
+            self.syscall_result(result);
+            return Ok(true);
+        }
+
+        #[cfg(feature = "log")]
+        if let Some((_, log_fn)) = self.config.log.filter(|(log_nr, _)| *log_nr == nr) {
+            // Logging service (host sink)
+            let timestamp = self.timestamp();
+            let result = log::syscall(
+                &self.registers.inner[Register::A0 as usize..],
+                self.memory,
+                &mut self.log_budget,
+                self.config.log_burst,
+                log_fn,
+                self.trace_context,
+                timestamp,
+            );
+            self.syscall_result(result);
+            return Ok(true);
+        }
+
+        if self.config.format_nr == Some(nr) {
+            // Formatting service (handled by the engine)
+            let result =
+                format::syscall(&self.registers.inner[Register::A0 as usize..], self.memory);
+            self.syscall_result(result);
+            return Ok(true);
+        }
+
+        #[cfg(feature = "instance_blob")]
+        if self.config.instance_blob_nr == Some(nr) {
+            // Instance blob read (handled by the engine)
+            let args = self.registers.inner[Register::A0 as usize..]
+                .first_chunk::<3>()
+                .copied()
+                // Unwrap is safe because the slice is guaranteed to have more than 3 elements.
+                .unwrap();
+            let result = self.instance_blob_syscall(&args);
+            self.syscall_result(result);
+            return Ok(true);
+        }
+
+        #[cfg(feature = "interrupt")]
+        if self.config.software_interrupt_nr == Some(nr) {
+            // Software interrupt to another sandbox (handled by the engine)
+            let result = self.send_software_interrupt();
+            self.syscall_result(result);
+            return Ok(true);
+        }
+
+        #[cfg(feature = "libc_support")]
+        if let Some((libc_fn, heap)) = self.config.libc {
+            if nr == libc::SYS_BRK {
+                // Program break (handled by the engine)
+                let requested = self.registers.inner[Register::A0 as usize] as u32;
+                if heap.contains(requested) {
+                    self.program_break = requested;
+                }
+                self.syscall_result(Ok(self.program_break as i32));
+                return Ok(true);
+            }
+
+            let args = *self.registers.inner[Register::A0 as usize..]
+                .first_chunk()
+                // Unwrap is safe because the slice is guaranteed to have more than SYSCALL_ARGS elements.
+                .unwrap();
+            if let Some(result) = libc_fn(nr, &args, self.memory) {
+                if nr == libc::SYS_EXIT {
+                    // Halt (the exit syscall doesn't return)
+                    self.exit_code = Some(args[0]);
+                    return Ok(false);
+                }
+
+                self.syscall_result(result);
+                return Ok(true);
+            }
+        }
+
+        #[cfg(feature = "runtime")]
+        if let Some((hooks, heap)) = self.config.runtime {
+            if nr == runtime::SYS_TICKS {
+                // Host clock (handled by the engine), 64-bit value in `a1` (low) and `a2` (high)
+                let ticks = self.timestamp();
+                let result = ticks
+                    .map(|ticks| ticks as i32)
+                    .ok_or(Errno::NotSupported.code());
+                self.syscall_result(result);
+                self.registers.inner[Register::A2 as usize] =
+                    ticks.map(|ticks| (ticks >> 32) as i32).unwrap_or(0);
+                return Ok(true);
+            }
+
+            let args = *self.registers.inner[Register::A0 as usize..]
+                .first_chunk()
+                // Unwrap is safe because the slice is guaranteed to have more than SYSCALL_ARGS elements.
+                .unwrap();
+            match runtime::syscall(nr, &args, self.memory, &hooks, heap, &mut self.runtime) {
+                Some(Outcome::Return(result)) => {
+                    self.syscall_result(result);
+                    return Ok(true);
+                }
+                // Halt (the exit and panic syscalls don't return)
+                Some(Outcome::Halt) => return Ok(false),
+                None => {}
+            }
+        }
+
+        if self.syscalls.is_some() || self.config.syscall_fn.is_some() || self.config.defer_syscalls
+        {
+            // Syscall Arguments
+            let mut args = *self.registers.inner[Register::A0 as usize..]
+                .first_chunk()
+                // Unwrap is safe because the slice is guaranteed to have more than SYSCALL_ARGS elements.
+                .unwrap();
+
+            if let Some(contract) = syscall::find(self.config.syscall_contracts, nr) {
+                if !contract.check(&args, self.memory) {
+                    // Contract violation, don't call the syscall function
+                    self.syscall_result(Err(self.config.syscall_contract_error));
+                    return Ok(true);
+                }
+
+                if let Err(error) = contract.resolve(&mut args, &self.capabilities) {
+                    // Capability not granted, don't call the syscall function
+                    self.syscall_result(Err(error.code()));
+                    return Ok(true);
+                }
+            }
+
+            #[cfg(feature = "accounting")]
+            let start = self.config.tick_fn.map(|tick_fn| tick_fn());
+
+            let result = if self.config.defer_syscalls {
+                // Deferred to the host (handled between runs)
+                let Some(result) = self.syscall_response.take() else {
+                    // Suspended, retry the syscall when responded
+                    self.waiting = Some(WaitingFor::Syscall(SyscallRequest { nr, args }));
+                    return Ok(false);
+                };
+                result
+            } else {
+                // Call the syscall handler
+                self.call_syscall(nr, args)
+            };
+
+            if self.config.suspend_on_would_block && result == Err(Errno::WouldBlock.code()) {
+                // Suspend until the I/O handle (`a0`) is woken, the syscall is retried (and accounted) later
+                self.waiting = Some(WaitingFor::Handle(args[0] as u32));
+                return Ok(false);
+            }
+            self.syscall_result(result);
+
+            #[cfg(feature = "accounting")]
+            self.account_syscall(nr, start, result.is_err())?;
+
+            return Ok(true);
+        }
+
+        // No syscall function set
+        Err(EmbiveError::NoSyscallFunction)
+    }
+
+    /// Call the syscall handler ([`Engine::set_syscalls`] or [`Config::syscall_fn`]), catching panics if configured
+    /// ([`Config::syscall_panic_error`]).
+    ///
+    /// Arguments:
+    /// - `nr`: Syscall number.
+    /// - `args`: Syscall arguments.
+    ///
+    /// Returns:
+    /// - `Result<i32, i32>`: Syscall result (the configured error code if it panicked).
+    #[inline(always)]
+    fn call_syscall(&mut self, nr: i32, args: [i32; SYSCALL_ARGS]) -> Result<i32, i32> {
+        let mut syscall_fn = self.config.syscall_fn;
+        let syscalls: &mut dyn Syscalls<M> = match self.syscalls.as_deref_mut() {
+            Some(syscalls) => syscalls,
+            // Unwrap is safe because a syscall handler is set (checked by the caller).
+            None => syscall_fn.as_mut().unwrap(),
+        };
+
+        #[cfg(feature = "std")]
+        if let Some(error) = self.config.syscall_panic_error {
+            let memory = &mut *self.memory;
+            return std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                syscalls.syscall(nr, &args, memory)
+            }))
+            .unwrap_or_else(|payload| {
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(|message| std::string::String::from(*message))
+                    .or_else(|| payload.downcast_ref::<std::string::String>().cloned());
+
+                self.syscall_fault = Some(SyscallFault {
+                    nr,
+                    program_counter: self.program_counter,
+                    message,
+                    timestamp: self.timestamp(),
+                });
+                Err(error)
+            });
+        }
+
+        syscalls.syscall(nr, &args, self.memory)
+    }
+
+    /// Take the last syscall function panic caught by the engine (check [`Config::syscall_panic_error`]).
+    ///
+    /// Returns:
+    /// - `Some(SyscallFault)`: Last caught panic.
+    /// - `None`: No panic was caught.
+    #[cfg(feature = "std")]
+    pub fn take_syscall_fault(&mut self) -> Option<SyscallFault> {
+        self.syscall_fault.take()
+    }
+
+    /// Account a syscall handled by the syscall function and check the syscall tick quota.
+    ///
+    /// Arguments:
+    /// - `nr`: Syscall number.
+    /// - `start`: Tick count before calling the syscall function (None = Not measured).
+    /// - `error`: The syscall function returned an error.
+    ///
+    /// Returns:
+    /// - `Ok(())`: Syscall accounted.
+    /// - `Err(EmbiveError)`: Syscall tick quota exceeded ([`EmbiveError::QuotaExceeded`]).
+    #[cfg(feature = "accounting")]
+    fn account_syscall(
+        &mut self,
+        nr: i32,
+        start: Option<u64>,
+        error: bool,
+    ) -> Result<(), EmbiveError> {
+        let ticks = match (start, self.config.tick_fn) {
+            (Some(start), Some(tick_fn)) => tick_fn().wrapping_sub(start),
+            _ => 0,
+        };
+
+        self.accounting.syscalls = self.accounting.syscalls.saturating_add(1);
+        self.accounting.syscall_ticks = self.accounting.syscall_ticks.saturating_add(ticks);
+        self.accounting.syscall_stats.record(nr, error, ticks);
+
+        if self.config.syscall_tick_quota > 0
+            && self.accounting.syscall_ticks > self.config.syscall_tick_quota
+        {
+            return Err(EmbiveError::QuotaExceeded);
+        }
+
+        Ok(())
+    }
+
+    /// Set the result of a system call.
+    ///
+    /// Arguments:
+    /// - `result`: value (`a1`), error (`a0`).
+    #[inline(always)]
+    fn syscall_result(&mut self, result: Result<i32, i32>) {
+        self.record_syscall(result);
+        #[cfg(feature = "counters")]
+        self.counters.syscall();
+        #[cfg(feature = "replay")]
+        {
+            self.replay.syscall = true;
+        }
+        #[cfg(feature = "telemetry")]
+        {
+            self.telemetry.last_syscall = Some(self.registers.inner[Register::A7 as usize]);
+        }
+
+        match result {
+            Ok(value) => {
+                // Clear error code
+                self.registers.inner[Register::A0 as usize] = 0;
+
+                // Set return value
+                self.registers.inner[Register::A1 as usize] = value;
+            }
+            Err(error) => {
+                // Set error code
+                self.registers.inner[Register::A0 as usize] = error;
+
+                // Clear return value
+                self.registers.inner[Register::A1 as usize] = 0;
+            }
+        }
+    }
+
+    /// Queue a software interrupt to another sandbox (`a0`: target, `a1`: line).
+    ///
+    /// Returns:
+    /// - `Ok(i32)`: Software interrupt was queued (0).
+    /// - `Err(i32)`: Target not permitted or queue full (check [`crate::interrupt`] error codes).
+    #[cfg(feature = "interrupt")]
+    fn send_software_interrupt(&mut self) -> Result<i32, i32> {
+        let target = self.registers.inner[Register::A0 as usize] as u32;
+        let line = self.registers.inner[Register::A1 as usize] as u32;
+
+        if target >= u32::BITS || self.config.software_interrupt_targets & (1 << target) == 0 {
+            return Err(SOFTWARE_INTERRUPT_NOT_PERMITTED);
+        }
+
+        if !self
+            .interrupt
+            .outbox
+            .push(SoftwareInterrupt { target, line })
+        {
+            return Err(SOFTWARE_INTERRUPT_QUEUE_FULL);
+        }
+
+        Ok(0)
+    }
+
+    /// Take the oldest software interrupt sent by the guest, to be routed by the host.
+    ///
+    /// Returns:
+    /// - `Some(SoftwareInterrupt)`: Software interrupt (target sandbox and line).
+    /// - `None`: No software interrupt was sent.
+    #[cfg(feature = "interrupt")]
+    pub fn take_software_interrupt(&mut self) -> Option<SoftwareInterrupt> {
+        self.interrupt.outbox.pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::SliceMemory;
+
+    use super::*;
+
+    #[test]
+    fn test_reset() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        engine.reset();
+
+        assert_eq!(engine.program_counter, 0);
+    }
+
+    #[cfg(feature = "instruction_limit")]
+    #[test]
+    fn test_instruction_limit() {
+        let code = &[
+            0x93, 0x08, 0x20, 0x00, // li   a7, 2      (Syscall nr)
+            0x13, 0x05, 0x10, 0x00, // li   a0, 1      (arg0, set first bit)
+            0x13, 0x15, 0xf5, 0x01, // slli a0, a0, 31 (arg0, shift-left 31 bits)
+            0x73, 0x00, 0x10, 0x00, // ebreak          (Halt)
+        ];
+
+        let mut memory = SliceMemory::new(code, &mut []);
+        let mut engine = Engine::new(
+            &mut memory,
+            Config {
+                instruction_limit: 2,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // Run the engine
+        let result = engine.run();
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.program_counter, 4 * 2);
+
+        // Run the engine again
+        let result = engine.run();
+        assert_eq!(result, Ok(false));
+        assert_eq!(engine.program_counter, 4 * 4);
+    }
+
+    #[cfg(feature = "debugger")]
+    #[test]
+    fn test_debugger() {
+        let code = &[
+            0x13, 0x05, 0x10, 0x00, // li   a0, 1
+            0x13, 0x05, 0x25, 0x00, // addi a0, a0, 2
+            0x13, 0x05, 0x35, 0x00, // addi a0, a0, 3
+            0x73, 0x00, 0x10, 0x00, // ebreak
+        ];
+
+        let mut memory = SliceMemory::new(code, &mut []);
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+        engine.debugger.add_breakpoint(4).unwrap();
+
+        // Stopped before the breakpoint instruction
+        assert_eq!(engine.run(), Ok(true));
+        assert_eq!(engine.stop_reason(), Some(StopReason::Breakpoint(4)));
+        assert_eq!(engine.registers.get(Register::A0 as usize), Ok(1));
+
+        // Single-step from the breakpoint
+        engine.debugger.set_single_step(true);
+        assert_eq!(engine.run(), Ok(true));
+        assert_eq!(engine.stop_reason(), Some(StopReason::Step(8)));
+        assert_eq!(engine.registers.get(Register::A0 as usize), Ok(3));
+
+        engine.debugger.clear();
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.stop_reason(), None);
+        assert_eq!(engine.registers.get(Register::A0 as usize), Ok(6));
+
+        // Kept by reset
+        engine.debugger.add_breakpoint(4).unwrap();
+        engine.reset();
+        assert_eq!(engine.run(), Ok(true));
+        assert_eq!(engine.stop_reason(), Some(StopReason::Breakpoint(4)));
+        engine.reset();
+        assert_eq!(engine.stop_reason(), None);
+    }
+
+    #[cfg(feature = "debugger")]
+    #[test]
+    fn test_debugger_trigger() {
+        let code = &[
+            0xb7, 0x02, 0x00, 0x20, // lui  t0, 0x20000
+            0x93, 0x82, 0x42, 0x00, // addi t0, t0, 4   (mcontrol, execute)
+            0x73, 0x90, 0x12, 0x7a, // csrw tdata1, t0
+            0x93, 0x02, 0x40, 0x01, // li   t0, 20
+            0x73, 0x90, 0x22, 0x7a, // csrw tdata2, t0
+            0x13, 0x05, 0x10, 0x00, // li   a0, 1
+            0x13, 0x05, 0x25, 0x00, // addi a0, a0, 2
+            0x73, 0x00, 0x10, 0x00, // ebreak
+        ];
+
+        let mut memory = SliceMemory::new(code, &mut []);
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+
+        // Trigger set by the guest
+        assert_eq!(engine.run(), Ok(true));
+        assert_eq!(
+            engine.stop_reason(),
+            Some(StopReason::Trigger { index: 0, pc: 20 })
+        );
+        assert_eq!(engine.registers.get(Register::A0 as usize), Ok(0));
+        assert_eq!(
+            engine.debugger.read_csr(crate::csr::TDATA1),
+            Some(0x2010_0004) // Hit
+        );
+
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(Register::A0 as usize), Ok(3));
+
+        // Cleared by reset
+        engine.reset();
+        assert_eq!(engine.debugger.read_csr(crate::csr::TDATA2), Some(0));
+    }
+
+    #[cfg(feature = "debugger")]
+    #[test]
+    fn test_debug_monitor() {
+        let code = &[
+            0x93, 0x02, 0x00, 0x02, // li    t0, 32          (monitor)
+            0x73, 0x90, 0x02, 0x7c, // csrw  dmonitor, t0
+            0x73, 0x50, 0x02, 0x7b, // csrwi dcsr, 4         (step)
+            0x13, 0x05, 0x15, 0x00, // addi  a0, a0, 1
+            0x13, 0x05, 0x15, 0x00, // addi  a0, a0, 1
+            0x73, 0x00, 0x10, 0x00, // ebreak
+            0x13, 0x00, 0x00, 0x00, // nop
+            0x13, 0x00, 0x00, 0x00, // nop
+            0x93, 0x85, 0x15, 0x00, // addi  a1, a1, 1       (monitor entry)
+            0x73, 0x00, 0x20, 0x7b, // dret
+        ];
+
+        let mut memory = SliceMemory::new(code, &mut []);
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+
+        // Monitor entered after every instruction (csrwi and both addi)
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(Register::A0 as usize), Ok(2));
+        assert_eq!(engine.registers.get(Register::A1 as usize), Ok(3));
+        assert_eq!(engine.debugger.read_csr(crate::csr::DPC), Some(20));
+        assert_eq!(engine.stop_reason(), None);
+
+        // dret outside of debug mode
+        engine.program_counter = 36;
+        assert_eq!(
+            engine.run(),
+            Err(EmbiveError::IllegalInstruction {
+                pc: 36,
+                raw: 0x7b200073
+            })
+        );
+    }
+
+    #[cfg(all(feature = "debugger", feature = "instruction_limit"))]
+    #[test]
+    fn test_debugger_fuel() {
+        let code = &[
+            0x13, 0x05, 0x10, 0x00, // li   a0, 1
+            0x13, 0x05, 0x25, 0x00, // addi a0, a0, 2
+            0x73, 0x00, 0x10, 0x00, // ebreak
+        ];
+
+        let mut memory = SliceMemory::new(code, &mut []);
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+        engine.debugger.add_breakpoint(4).unwrap();
+
+        // The stopped instruction doesn't consume fuel
+        assert_eq!(engine.run_with_fuel(10), Ok(RunResult::Debugger));
+        assert_eq!(engine.fuel(), 9);
+
+        engine.debugger.set_single_step(true);
+        assert_eq!(engine.run_with_fuel(0), Ok(RunResult::Debugger));
+        assert_eq!(engine.fuel(), 8);
+        assert_eq!(engine.stop_reason(), Some(StopReason::Step(8)));
+    }
+
+    #[test]
+    fn test_execute_raw() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+        engine.program_counter = 0x100;
+
+        // addi a0, zero, 5 (no code memory)
+        assert_eq!(engine.execute_raw(0x0050_0513), Ok(true));
+        assert_eq!(engine.registers.get(Register::A0 as usize), Ok(5));
+        assert_eq!(engine.program_counter, 0x104);
+
+        // jal zero, -4
+        assert_eq!(engine.execute_raw(0xffdf_f06f), Ok(true));
+        assert_eq!(engine.program_counter, 0x100);
+
+        // Illegal
+        assert_eq!(
+            engine.execute_raw(0xFFFF_FFFF),
+            Err(EmbiveError::IllegalInstruction {
+                pc: 0x100,
+                raw: 0xFFFF_FFFF
+            })
+        );
+
+        // ebreak
+        assert_eq!(engine.execute_raw(0x0010_0073), Ok(false));
+    }
+
+    #[cfg(feature = "instruction_limit")]
+    #[test]
+    fn test_run_with_fuel() {
+        let code = &[
+            0x93, 0x08, 0x90, 0x00, // li   a7, 9  (Syscall nr)
+            0x13, 0x05, 0x50, 0x00, // li   a0, 5  (Yielded value)
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x13, 0x05, 0x10, 0x00, // li   a0, 1
+            0x73, 0x00, 0x10, 0x00, // ebreak      (Halt)
+        ];
+
+        let mut memory = SliceMemory::new(code, &mut []);
+        let config = Config::default().with_yield_nr(Some(9));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        // Out of fuel
+        assert_eq!(engine.run_with_fuel(0), Ok(RunResult::OutOfFuel));
+        assert_eq!(engine.run_with_fuel(2), Ok(RunResult::OutOfFuel));
+        assert_eq!(engine.program_counter, 4 * 2);
+        assert_eq!(engine.fuel(), 0);
+
+        // Suspended on the yield syscall, the remaining fuel is kept
+        assert_eq!(engine.run_with_fuel(10), Ok(RunResult::Syscall));
+        assert_eq!(engine.fuel(), 9);
+        assert_eq!(engine.run_with_fuel(0), Ok(RunResult::Syscall));
+        assert_eq!(engine.fuel(), 9);
+
+        // Refilled and resumed
+        engine.refill_fuel(u64::MAX);
+        assert_eq!(engine.fuel(), u64::MAX);
+        assert_eq!(engine.drain_fuel(), u64::MAX);
+        assert!(engine.resume(0));
+        assert_eq!(engine.run_with_fuel(1), Ok(RunResult::OutOfFuel));
+        assert_eq!(engine.run_with_fuel(5), Ok(RunResult::Halted));
+        assert_eq!(engine.fuel(), 3); // Yield syscall completed after resuming, li, ebreak
+        assert_eq!(engine.registers.get(Register::A0 as usize), Ok(1));
+    }
+
+    #[cfg(feature = "instruction_limit")]
+    #[test]
+    fn test_instruction_limit_zero() {
+        let code = &[
+            0x93, 0x08, 0x20, 0x00, // li   a7, 2      (Syscall nr)
+            0x13, 0x05, 0x10, 0x00, // li   a0, 1      (arg0, set first bit)
             0x13, 0x15, 0xf5, 0x01, // slli a0, a0, 31 (arg0, shift-left 31 bits)
             0x73, 0x00, 0x10, 0x00, // ebreak          (Halt)
         ];
 
         let mut memory = SliceMemory::new(code, &mut []);
-        let mut engine = Engine::new(
-            &mut memory,
-            Config {
-                instruction_limit: 2,
-                ..Default::default()
-            },
-        )
-        .unwrap();
+        let mut engine = Engine::new(
+            &mut memory,
+            Config {
+                instruction_limit: 0,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // Run the engine
+        let result = engine.run();
+        assert_eq!(result, Ok(false));
+        assert_eq!(engine.program_counter, 4 * 4);
+    }
+
+    #[cfg(feature = "safepoint")]
+    #[test]
+    fn test_safepoint_yield() {
+        let code = &[
+            0x13, 0x05, 0x10, 0x00, // li   a0, 1      (Not a safepoint)
+            0x13, 0x10, 0xf0, 0x01, // slli x0, x0, 31 (Safepoint)
+            0x13, 0x05, 0x20, 0x00, // li   a0, 2      (Not a safepoint)
+            0x73, 0x00, 0x10, 0x00, // ebreak          (Halt)
+        ];
+
+        let mut memory = SliceMemory::new(code, &mut []);
+        let config = Config::default().with_yield_point(YieldPoint::Safepoint);
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+        assert!(engine.at_safepoint());
+
+        // Yield after the safepoint
+        let result = engine.run();
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.program_counter, 4 * 2);
+        assert!(engine.at_safepoint());
+
+        engine.step().unwrap();
+        assert!(!engine.at_safepoint());
+
+        let result = engine.run();
+        assert_eq!(result, Ok(false));
+
+        engine.reset();
+        assert!(engine.at_safepoint());
+    }
+
+    #[cfg(all(feature = "instruction_limit", feature = "safepoint"))]
+    #[test]
+    fn test_safepoint_instruction_limit() {
+        let code = &[
+            0x13, 0x10, 0xf0, 0x01, // slli x0, x0, 31 (Safepoint)
+            0x13, 0x05, 0x10, 0x00, // li   a0, 1      (Critical section)
+            0x13, 0x05, 0x20, 0x00, // li   a0, 2      (Critical section)
+            0x13, 0x10, 0xf0, 0x01, // slli x0, x0, 31 (Safepoint)
+            0x73, 0x00, 0x10, 0x00, // ebreak          (Halt)
+        ];
+
+        let mut memory = SliceMemory::new(code, &mut []);
+        let config = Config::default()
+            .with_yield_point(YieldPoint::Safepoint)
+            .with_instruction_limit(2);
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        // Limit reached inside the critical section, keep running until the next safepoint
+        let result = engine.run();
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.program_counter, 4 * 4);
+        assert!(engine.at_safepoint());
+
+        let result = engine.run();
+        assert_eq!(result, Ok(false));
+    }
+
+    #[cfg(feature = "accounting")]
+    #[test]
+    fn test_accounting() {
+        use std::thread_local;
+
+        thread_local! {
+            static TICKS: core::cell::Cell<u64> = const { core::cell::Cell::new(0) };
+        }
+
+        // Every call advances the clock by 10 ticks
+        fn tick_fn() -> u64 {
+            TICKS.with(|ticks| {
+                ticks.set(ticks.get() + 10);
+                ticks.get()
+            })
+        }
+
+        let code = &[
+            0x93, 0x08, 0x10, 0x00, // li   a7, 1 (Syscall nr)
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x73, 0x00, 0x10, 0x00, // ebreak     (Halt)
+        ];
+
+        let mut memory = SliceMemory::new(code, &mut []);
+        let config = Config::default()
+            .with_syscall_fn(Some(|_, _, _| Ok(0)))
+            .with_tick_fn(Some(tick_fn))
+            .with_syscall_tick_quota(15);
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        // Second syscall exceeds the quota
+        let result = engine.run();
+        assert_eq!(result, Err(EmbiveError::QuotaExceeded));
+        assert_eq!(engine.program_counter, 4 * 3);
+        let usage = core::mem::take(&mut engine.accounting);
+        assert_eq!(usage.guest_instructions, 3);
+        assert_eq!(usage.syscalls, 2);
+        assert_eq!(usage.syscall_ticks, 20);
+        assert_eq!(usage.syscall_stats.get(1).map(|stat| stat.calls), Some(2));
+        assert_eq!(engine.accounting, Accounting::default());
+
+        // New accounting period, resume
+        let result = engine.run();
+        assert_eq!(result, Ok(false));
+        assert_eq!(engine.accounting.guest_instructions, 1);
+
+        engine.reset();
+        assert_eq!(engine.accounting.guest_instructions, 1);
+    }
+
+    #[cfg(feature = "accounting")]
+    #[test]
+    fn test_counter_syscall() {
+        let code = &[
+            0x93, 0x08, 0x50, 0x00, // li   a7, 5 (Syscall nr)
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x73, 0x00, 0x10, 0x00, // ebreak     (Halt)
+        ];
+
+        let mut memory = SliceMemory::new(code, &mut []);
+        let config = Config::default().with_counter_nr(Some(5));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        // Counters don't wrap at 32 bits
+        engine.accounting.guest_instructions = 0x1_FFFF_FFFE;
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(Register::A0 as usize), Ok(0));
+        assert_eq!(engine.registers.get(Register::A1 as usize), Ok(0));
+        assert_eq!(engine.registers.get(Register::A2 as usize), Ok(2));
+
+        engine.reset();
+        engine.registers.inner[Register::A0 as usize] = 7;
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(
+            engine.registers.get(Register::A0 as usize),
+            Ok(Errno::InvalidArgument.code())
+        );
+        assert_eq!(engine.registers.get(Register::A2 as usize), Ok(0));
+
+        engine.accounting.guest_instructions = u64::MAX;
+        engine.reset();
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.accounting.guest_instructions, u64::MAX);
+
+        // Guest sees the frozen counter, the host the precise one
+        engine.counter_view.inhibit(1, &engine.accounting);
+        engine.accounting.guest_instructions = 10;
+        engine.reset();
+        engine.registers.inner[Register::A0 as usize] = 0;
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(Register::A1 as usize), Ok(-1));
+        assert_eq!(engine.registers.get(Register::A2 as usize), Ok(-1));
+        assert_eq!(engine.accounting.guest_instructions, 13);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_syscall_panic() {
+        let code = &[
+            0x93, 0x08, 0x30, 0x00, // li   a7, 3 (Syscall nr)
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x73, 0x00, 0x10, 0x00, // ebreak     (Halt)
+        ];
+
+        let mut memory = SliceMemory::new(code, &mut []);
+        let config = Config::default()
+            .with_syscall_fn(Some(|_, _, _| panic!("buggy handler")))
+            .with_syscall_panic_error(Some(-1))
+            .with_tick_fn(Some(|| 42));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
 
-        // Run the engine
+        // Guest sees an error, host gets a fault
         let result = engine.run();
-        assert_eq!(result, Ok(true));
-        assert_eq!(engine.program_counter, 4 * 2);
+        assert_eq!(result, Ok(false));
+        assert_eq!(engine.registers.get(Register::A0 as usize), Ok(-1));
+        assert_eq!(
+            engine.take_syscall_fault(),
+            Some(SyscallFault {
+                nr: 3,
+                program_counter: 4,
+                message: Some("buggy handler".into()),
+                timestamp: Some(42),
+            })
+        );
+        assert_eq!(engine.take_syscall_fault(), None);
+    }
+
+    #[test]
+    fn test_syscalls() {
+        // Stateful handler, one table per engine
+        struct Table {
+            base: i32,
+            calls: u32,
+        }
+
+        impl Syscalls<SliceMemory<'_>> for Table {
+            fn syscall(
+                &mut self,
+                nr: i32,
+                args: &[i32; SYSCALL_ARGS],
+                _memory: &mut SliceMemory,
+            ) -> Result<i32, i32> {
+                self.calls += 1;
+                match nr {
+                    1 => Ok(self.base + args[0]),
+                    _ => Err(Errno::NotSupported.code()),
+                }
+            }
+        }
+
+        let code = &[
+            0x93, 0x08, 0x10, 0x00, // li   a7, 1 (Syscall nr)
+            0x13, 0x05, 0x20, 0x00, // li   a0, 2
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x73, 0x00, 0x10, 0x00, // ebreak     (Halt)
+        ];
+
+        let (mut table_a, mut table_b) =
+            (Table { base: 10, calls: 0 }, Table { base: 20, calls: 0 });
+        let mut memory_a = SliceMemory::new(code, &mut []);
+        let mut memory_b = SliceMemory::new(code, &mut []);
+        let config = || Config::default().with_syscall_fn(Some(|_, _, _| unreachable!()));
+        let mut engine_a = Engine::new(&mut memory_a, config()).unwrap();
+        let mut engine_b = Engine::new(&mut memory_b, config()).unwrap();
+        engine_a.set_syscalls(Some(&mut table_a));
+        engine_b.set_syscalls(Some(&mut table_b));
+
+        // Handler takes precedence over the syscall function
+        assert_eq!(engine_a.run(), Ok(false));
+        assert_eq!(engine_b.run(), Ok(false));
+        assert_eq!(engine_a.registers.get(Register::A1 as usize), Ok(12));
+        assert_eq!(engine_b.registers.get(Register::A1 as usize), Ok(22));
+
+        // Kept by reset
+        engine_a.reset();
+        assert_eq!(engine_a.run(), Ok(false));
+        assert_eq!((table_a.calls, table_b.calls), (2, 1));
+
+        // Syscall function
+        let mut memory = SliceMemory::new(code, &mut []);
+        let config = Config::default().with_syscall_fn(Some(|_, args, _| Ok(args[0] * 3)));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(Register::A1 as usize), Ok(6));
+    }
+
+    #[test]
+    fn test_max_run_depth() {
+        static CODE: &[u8] = &[
+            0x93, 0x08, 0x10, 0x00, // li   a7, 1 (Syscall nr)
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x73, 0x00, 0x10, 0x00, // ebreak     (Halt)
+        ];
+
+        // Run the guest again, returns the number of nested runs below
+        fn syscall(_: i32, _: &[i32; SYSCALL_ARGS], _: &mut SliceMemory) -> Result<i32, i32> {
+            let mut memory = SliceMemory::new(CODE, &mut []);
+            let config = Config::default()
+                .with_syscall_fn(Some(syscall))
+                .with_max_run_depth(3);
+            let mut engine = Engine::new(&mut memory, config).unwrap();
+            match engine.run() {
+                Ok(false) => Ok(engine.registers.inner[Register::A1 as usize] + 1),
+                Err(EmbiveError::RecursionLimit) => Ok(0),
+                _ => Err(-1),
+            }
+        }
+
+        let mut memory = SliceMemory::new(CODE, &mut []);
+        let config = Config::default()
+            .with_syscall_fn(Some(syscall))
+            .with_max_run_depth(3);
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(Register::A1 as usize), Ok(2));
+        assert_eq!(run_depth(), 0);
+        #[cfg(feature = "accounting")]
+        assert_eq!(engine.accounting.peak_run_depth, 1);
+
+        // Limit reached before running
+        engine.reset();
+        engine.config.max_run_depth = 1;
+        let _outer = RunGuard::enter(0).unwrap();
+        assert_eq!(engine.run(), Err(EmbiveError::RecursionLimit));
+        assert_eq!(engine.program_counter, 0);
+    }
+
+    #[test]
+    fn test_syscall_contract() {
+        use crate::syscall::Arg;
+
+        static CONTRACTS: [SyscallContract; 1] = [SyscallContract::new(
+            64,
+            "write",
+            &[Arg::Value("fd"), Arg::Buffer("buf")],
+        )];
+
+        let code = &[
+            0x93, 0x08, 0x00, 0x04, // li   a7, 64   (Syscall nr)
+            0x37, 0x06, 0x01, 0x00, // lui  a2, 0x10 (buf length, out of RAM)
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x73, 0x00, 0x10, 0x00, // ebreak        (Halt)
+        ];
+
+        let mut memory = SliceMemory::new(code, &mut []);
+        let config = Config::default()
+            .with_syscall_fn(Some(|_, _, _| unreachable!()))
+            .with_syscall_contracts(&CONTRACTS, 14);
+        let mut engine = Engine::new(&mut memory, config).unwrap();
 
-        // Run the engine again
         let result = engine.run();
         assert_eq!(result, Ok(false));
-        assert_eq!(engine.program_counter, 4 * 4);
+        assert_eq!(engine.registers.get(Register::A0 as usize), Ok(14));
     }
 
-    #[cfg(feature = "instruction_limit")]
     #[test]
-    fn test_instruction_limit_zero() {
+    fn test_syscall_capability() {
+        use crate::syscall::{
+            capability::{Kind, Rights},
+            Arg,
+        };
+
+        static CONTRACTS: [SyscallContract; 1] = [SyscallContract::new(
+            1,
+            "close",
+            &[Arg::Capability("stream", Kind::Stream, Rights::CONTROL)],
+        )];
+
         let code = &[
-            0x93, 0x08, 0x20, 0x00, // li   a7, 2      (Syscall nr)
-            0x13, 0x05, 0x10, 0x00, // li   a0, 1      (arg0, set first bit)
-            0x13, 0x15, 0xf5, 0x01, // slli a0, a0, 31 (arg0, shift-left 31 bits)
-            0x73, 0x00, 0x10, 0x00, // ebreak          (Halt)
+            0x93, 0x08, 0x10, 0x00, // li   a7, 1 (Syscall nr)
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x73, 0x00, 0x10, 0x00, // ebreak     (Halt)
+        ];
+
+        let mut memory = SliceMemory::new(code, &mut []);
+        let config = Config::default()
+            .with_syscall_fn(Some(|_, args, _| Ok(args[0])))
+            .with_syscall_contracts(&CONTRACTS, Errno::InvalidPointer.code());
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        // Syscall function receives the host resource id
+        let handle = engine
+            .capabilities
+            .grant(Kind::Stream, 0x55, Rights::ALL)
+            .unwrap();
+        engine.registers.inner[Register::A0 as usize] = handle as i32;
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(Register::A0 as usize), Ok(0));
+        assert_eq!(engine.registers.get(Register::A1 as usize), Ok(0x55));
+
+        // Revoked on reset
+        engine.reset();
+        engine.registers.inner[Register::A0 as usize] = handle as i32;
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(
+            engine.registers.get(Register::A0 as usize),
+            Ok(Errno::AccessDenied.code())
+        );
+    }
+
+    #[test]
+    fn test_display_syscall() {
+        use crate::syscall::{trace::TraceContext, Arg};
+        use std::format;
+
+        static CONTRACTS: [SyscallContract; 1] =
+            [SyscallContract::new(3, "sleep", &[Arg::Value("ms")])];
+
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let config = Config::default().with_syscall_contracts(&CONTRACTS, 1);
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+        assert!(engine.display_syscall().is_none());
+
+        engine.registers.inner[Register::A7 as usize] = 3;
+        engine.registers.inner[Register::A0 as usize] = 10;
+        assert_eq!(
+            format!("{}", engine.display_syscall().unwrap()),
+            "sleep(ms=10)"
+        );
+
+        engine.set_trace_context(Some(TraceContext::new(1, 0)));
+        assert_eq!(
+            format!("{}", engine.display_syscall().unwrap()),
+            "[00000000000000000000000000000001-0000000000000000] sleep(ms=10)"
+        );
+    }
+
+    #[test]
+    fn test_fault_context() {
+        let code = &[
+            0x37, 0x05, 0x00, 0x80, // lui  a0, 0x80000
+            0x83, 0x25, 0x45, 0x00, // lw   a1, 4(a0)  (out of bounds)
+            0x23, 0x20, 0xb0, 0x00, // sw   a1, 0(zero) (code)
+        ];
+        let mut ram = [0; 4];
+        let mut memory = SliceMemory::new(code, &mut ram);
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+
+        let fault = EmbiveError::LoadFault {
+            pc: 4,
+            address: RAM_OFFSET + 4,
+        };
+        assert_eq!(engine.run(), Err(fault));
+        assert_eq!(fault.pc(), Some(4));
+
+        engine.program_counter = 8;
+        assert_eq!(
+            engine.run(),
+            Err(EmbiveError::StoreFault { pc: 8, address: 0 })
+        );
+
+        engine.program_counter = 12;
+        assert_eq!(engine.run(), Err(EmbiveError::FetchFault { pc: 12 }));
+        assert_eq!(EmbiveError::InvalidMemoryAddress.pc(), None);
+    }
+
+    #[test]
+    fn test_illegal_instruction_stats() {
+        use crate::lint::IsaExtension;
+
+        let code = &[
+            0x07, 0x25, 0x05, 0x00, // flw fa0, 0(a0)
+        ];
+
+        let mut memory = SliceMemory::new(code, &mut []);
+        let illegal = EmbiveError::IllegalInstruction {
+            pc: 0,
+            raw: 0x0005_2507,
+        };
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+        assert_eq!(engine.run(), Err(illegal));
+        assert_eq!(engine.illegal_instructions.total(), 0);
+
+        engine.config = Config::default().with_illegal_instruction_stats(true);
+        for _ in 0..2 {
+            engine.reset();
+            assert_eq!(engine.run(), Err(illegal));
+        }
+        assert_eq!(engine.illegal_instructions.count(IsaExtension::F), 2);
+        assert_eq!(
+            engine.illegal_instructions.last(),
+            Some((IsaExtension::F, 0, 0x0005_2507))
+        );
+    }
+
+    #[test]
+    fn test_disabled_extensions() {
+        let code = &[
+            0x0f, 0x10, 0x00, 0x00, // fence.i
+            0x73, 0x00, 0x10, 0x00, // ebreak
+        ];
+
+        let mut memory = SliceMemory::new(code, &mut []);
+        let config = Config::default().with_disabled_extensions(IsaExtension::M.bit());
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+        assert_eq!(engine.run(), Ok(false));
+
+        engine.config = Config::default()
+            .with_disabled_extensions(IsaExtension::Zifencei.bit())
+            .with_illegal_instruction_stats(true);
+        engine.reset();
+        assert_eq!(
+            engine.run(),
+            Err(EmbiveError::IllegalInstruction {
+                pc: 0,
+                raw: 0x0000_100f
+            })
+        );
+        assert_eq!(engine.illegal_instructions.count(IsaExtension::Zifencei), 1);
+    }
+
+    #[test]
+    fn test_probe_extensions() {
+        let code = &[
+            0x53, 0x75, 0xb5, 0x00, // fadd.s  fa0, fa0, fa1
+            0x13, 0x05, 0x10, 0x00, // li      a0, 1
+            0x0b, 0x00, 0x00, 0x00, // (custom-0)
+            0x73, 0x00, 0x10, 0x00, // ebreak
+        ];
+
+        let mut memory = SliceMemory::new(code, &mut []);
+        let config = Config::default()
+            .with_probe_extensions(IsaExtension::F.bit() | IsaExtension::V.bit())
+            .with_illegal_instruction_stats(true);
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        // Custom instructions aren't probed
+        assert_eq!(
+            engine.run(),
+            Err(EmbiveError::IllegalInstruction {
+                pc: 0x8,
+                raw: 0x0000_000b
+            })
+        );
+        assert_eq!(engine.probed_extensions, IsaExtension::F.bit());
+        assert_eq!(engine.registers.get(10), Ok(1));
+        assert_eq!(engine.illegal_instructions.count(IsaExtension::F), 1);
+
+        engine.config.probe_extensions |= IsaExtension::Custom.bit();
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(
+            engine.probed_extensions,
+            IsaExtension::F.bit() | IsaExtension::Custom.bit()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "fetch_batch")]
+    fn test_fetch_batch() {
+        use crate::memory::UnifiedSliceMemory;
+
+        for fence in [false, true] {
+            let mut code = [
+                0x23, 0x24, 0xa0, 0x00, // sw      a0, 8(zero)
+                0x13, 0x00, 0x00, 0x00, // nop     (fence.i)
+                0x13, 0x00, 0x00, 0x00, // nop     (overwritten)
+                0x73, 0x00, 0x10, 0x00, // ebreak
+            ];
+            if fence {
+                code[4..8].copy_from_slice(&[0x0f, 0x10, 0x00, 0x00]);
+            }
+
+            let mut memory = UnifiedSliceMemory::new(&mut code, &mut []);
+            let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+            engine.registers.inner[Register::A0 as usize] = 0x02a0_0593; // li a1, 42
+
+            // Without fence.i, the overwritten instruction was already fetched
+            assert_eq!(engine.run(), Ok(false));
+            let expected = if fence { 42 } else { 0 };
+            assert_eq!(engine.registers.inner[Register::A1 as usize], expected);
+
+            // Fetched code is dropped on reset
+            engine.reset();
+            engine.registers.inner[Register::A0 as usize] = 0x02a0_0593;
+            assert_eq!(engine.run(), Ok(false));
+            assert_eq!(engine.registers.inner[Register::A1 as usize], 42);
+        }
+    }
+
+    #[test]
+    fn test_blocking_syscall() {
+        use std::thread_local;
+
+        thread_local! {
+            static AVAILABLE: core::cell::Cell<Option<i32>> = const { core::cell::Cell::new(None) };
+        }
+
+        // Read a value from a stream, blocking until data is available
+        fn read(_: i32, _: &[i32; SYSCALL_ARGS], _: &mut SliceMemory) -> Result<i32, i32> {
+            AVAILABLE
+                .with(|available| available.take())
+                .ok_or(Errno::WouldBlock.code())
+        }
+
+        let code = &[
+            0x13, 0x05, 0x30, 0x00, // li   a0, 3 (I/O handle)
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x73, 0x00, 0x10, 0x00, // ebreak     (Halt)
+        ];
+
+        let mut memory = SliceMemory::new(code, &mut []);
+        let config = Config::default()
+            .with_syscall_fn(Some(read))
+            .with_suspend_on_would_block(true);
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        // Suspended at the syscall
+        assert_eq!(engine.run(), Ok(true));
+        assert_eq!(engine.waiting_for(), Some(WaitingFor::Handle(3)));
+        assert_eq!(engine.program_counter, 4);
+        assert_eq!(engine.run(), Ok(true));
+        assert!(!engine.wake(2));
+
+        // Data arrived
+        AVAILABLE.with(|available| available.set(Some(42)));
+        assert!(engine.wake(3));
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.waiting_for(), None);
+        assert_eq!(engine.registers.get(Register::A0 as usize), Ok(0));
+        assert_eq!(engine.registers.get(Register::A1 as usize), Ok(42));
+    }
+
+    #[test]
+    fn test_poll_syscall() {
+        let code = &[
+            0x93, 0x08, 0x70, 0x00, // li   a7, 7    (Syscall nr)
+            0x13, 0x05, 0x60, 0x00, // li   a0, 0x6  (Handles 1 and 2)
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x73, 0x00, 0x10, 0x00, // ebreak        (Halt)
+        ];
+
+        let mut memory = SliceMemory::new(code, &mut []);
+        let config = Config::default().with_poll_nr(Some(7));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        // Nothing ready, suspended
+        assert_eq!(engine.run(), Ok(true));
+        assert_eq!(engine.waiting_for(), Some(WaitingFor::AnyOf(0x6)));
+        assert!(!engine.wake(3));
+        assert!(engine.wake(2));
+
+        // Handle 2 is ready (handle 3 is kept)
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(Register::A0 as usize), Ok(0));
+        assert_eq!(engine.registers.get(Register::A1 as usize), Ok(0x4));
+        assert_eq!(engine.ready, 1 << 3);
+
+        // Empty mask
+        engine.reset();
+        engine.program_counter = 8;
+        *engine.registers.get_mut(Register::A7 as usize).unwrap() = 7;
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(
+            engine.registers.get(Register::A0 as usize),
+            Ok(Errno::InvalidArgument.code())
+        );
+    }
+
+    #[test]
+    fn test_yield_syscall() {
+        let code = &[
+            0x93, 0x08, 0x90, 0x00, // li   a7, 9  (Syscall nr)
+            0x13, 0x05, 0x50, 0x00, // li   a0, 5  (Yielded value)
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x13, 0x85, 0x15, 0x00, // addi a0, a1, 1
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x73, 0x00, 0x10, 0x00, // ebreak      (Halt)
+        ];
+
+        let mut memory = SliceMemory::new(code, &mut []);
+        let config = Config::default().with_yield_nr(Some(9));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+        assert!(!engine.resume(0));
+
+        // Yielded, stays suspended until resumed
+        assert_eq!(engine.run(), Ok(true));
+        assert_eq!(engine.waiting_for(), Some(WaitingFor::Resume(5)));
+        assert_eq!(engine.run(), Ok(true));
+        assert!(!engine.wake(0));
+
+        // Value passed in, returned to the guest (and yielded back incremented)
+        assert!(engine.resume(41));
+        assert_eq!(engine.run(), Ok(true));
+        assert_eq!(engine.waiting_for(), Some(WaitingFor::Resume(42)));
+
+        assert!(engine.resume(0));
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(Register::A0 as usize), Ok(0));
+        assert_eq!(engine.registers.get(Register::A1 as usize), Ok(0));
+    }
+
+    #[test]
+    fn test_deferred_syscall() {
+        let code = &[
+            0x93, 0x08, 0x10, 0x00, // li   a7, 1       (Syscall nr)
+            0x37, 0x05, 0x00, 0x80, // lui  a0, 0x80000 (Buffer)
+            0x93, 0x05, 0x40, 0x00, // li   a1, 4       (Length)
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0xb7, 0x02, 0x00, 0x80, // lui  t0, 0x80000
+            0x03, 0xa6, 0x02, 0x00, // lw   a2, 0(t0)
+            0x73, 0x00, 0x10, 0x00, // ebreak           (Halt)
+        ];
+
+        let mut ram = [0; 4];
+        let mut memory = SliceMemory::new(code, &mut ram);
+        let config = Config::default().with_defer_syscalls(true);
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+        assert!(!engine.respond(Ok(0)));
+
+        // Captured, stays suspended until responded
+        assert_eq!(engine.run(), Ok(true));
+        let request = SyscallRequest {
+            nr: 1,
+            args: [RAM_OFFSET as i32, 4, 0, 0, 0, 0, 0],
+        };
+        assert_eq!(engine.waiting_for(), Some(WaitingFor::Syscall(request)));
+        assert_eq!(engine.program_counter, 12);
+        assert_eq!(engine.run(), Ok(true));
+        assert!(!engine.wake(0));
+
+        // Effect applied by the host, later
+        let (request, memory) = engine.syscall_request().unwrap();
+        memory
+            .store_bytes(request.args[0] as u32, &[0x1, 0x2, 0x3, 0x4])
+            .unwrap();
+        assert!(engine.respond(Ok(4)));
+        assert_eq!(engine.syscall_request().map(|(request, _)| request), None);
+
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(Register::A0 as usize), Ok(0));
+        assert_eq!(engine.registers.get(Register::A1 as usize), Ok(4));
+        assert_eq!(engine.registers.get(Register::A2 as usize), Ok(0x04030201));
+    }
+
+    #[cfg(feature = "timer")]
+    #[test]
+    fn test_timer_syscall() {
+        let code = &[
+            0x93, 0x08, 0x80, 0x00, // li   a7, 8  (Syscall nr)
+            0x13, 0x05, 0x00, 0x00, // li   a0, 0  (Create)
+            0x93, 0x05, 0x00, 0x00, // li   a1, 0  (Event)
+            0x13, 0x06, 0x40, 0x00, // li   a2, 4  (I/O handle)
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x93, 0x05, 0x10, 0x00, // li   a1, 1  (Timer id)
+            0x13, 0x05, 0x10, 0x00, // li   a0, 1  (Arm)
+            0x13, 0x06, 0xa0, 0x00, // li   a2, 10 (Delay)
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x93, 0x08, 0x70, 0x00, // li   a7, 7  (Poll syscall nr)
+            0x13, 0x05, 0x00, 0x01, // li   a0, 16 (Handle 4)
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x73, 0x00, 0x10, 0x00, // ebreak      (Halt)
         ];
 
         let mut memory = SliceMemory::new(code, &mut []);
+        let config = Config::default()
+            .with_timer_nr(Some(8))
+            .with_poll_nr(Some(7));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+        engine.advance_timers(100).unwrap();
+        engine.timers.syscall(&[0, 0, 0, 0]).unwrap(); // Timer 0 is taken
+
+        // Waiting for the timer
+        assert_eq!(engine.run(), Ok(true));
+        assert_eq!(engine.waiting_for(), Some(WaitingFor::AnyOf(1 << 4)));
+        assert_eq!(engine.next_timer_deadline(), Some(110));
+
+        engine.advance_timers(105).unwrap();
+        assert_eq!(engine.run(), Ok(true));
+        engine.advance_timers(110).unwrap();
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(Register::A1 as usize), Ok(1 << 4));
+
+        engine.reset();
+        assert_eq!(engine.next_timer_deadline(), None);
+    }
+
+    #[test]
+    fn test_presets() {
+        let memory = SliceMemory::new(&[], &mut []);
+
+        let strict: Config<SliceMemory> = Config::strict_sandbox();
+        assert_eq!(strict.validate(&memory), Ok(()));
+        #[cfg(feature = "instruction_limit")]
+        assert_eq!(strict.instruction_limit, STRICT_INSTRUCTION_LIMIT);
+        #[cfg(feature = "interrupt")]
+        assert_eq!(strict.software_interrupt_nr, None);
+
+        let debug: Config<SliceMemory> = Config::debug();
+        assert_eq!(debug.validate(&memory), Ok(()));
+        #[cfg(feature = "instruction_limit")]
+        assert_eq!(debug.instruction_limit, 1);
+
+        let performance: Config<SliceMemory> = Config::max_performance();
+        assert_eq!(performance.validate(&memory), Ok(()));
+        #[cfg(feature = "interrupt")]
+        assert_eq!(performance.interrupt_granularity, Granularity::BasicBlock);
+    }
+
+    #[test]
+    fn test_entry_point() {
+        let code = &[0x73, 0x00, 0x10, 0x00, 0x73, 0x00, 0x10, 0x00];
+        let mut memory = SliceMemory::new(code, &mut []);
+        let mut engine =
+            Engine::new(&mut memory, Config::default().with_entry_point(Some(4))).unwrap();
+        assert_eq!(engine.program_counter, 4);
+
+        engine.program_counter = 0;
+        engine.reset();
+        assert_eq!(engine.program_counter, 4);
+    }
+
+    #[test]
+    fn test_invalid_entry_point() {
+        let code = &[0x73, 0x00, 0x10, 0x00];
+        let mut memory = SliceMemory::new(code, &mut []);
+
+        let config = Config::default().with_entry_point(Some(1));
+        assert_eq!(
+            config.validate(&memory),
+            Err(ConfigError::MisalignedEntryPoint)
+        );
+
+        let config = Config::default().with_entry_point(Some(4));
+        assert_eq!(
+            Engine::new(&mut memory, config).err(),
+            Some(EmbiveError::InvalidConfig(
+                ConfigError::EntryPointOutOfBounds
+            ))
+        );
+    }
+
+    #[test]
+    fn test_stack_size() {
+        let mut ram = [0; 64];
+        let memory = SliceMemory::new(&[], &mut ram);
+
+        let config = Config::default().with_stack_size(64);
+        assert_eq!(config.validate(&memory), Ok(()));
+
+        let config = Config::default().with_stack_size(65);
+        assert_eq!(config.validate(&memory), Err(ConfigError::StackTooLarge));
+
+        let config = Config::default().with_stack_size(u32::MAX);
+        assert_eq!(config.validate(&memory), Err(ConfigError::StackTooLarge));
+    }
+
+    #[test]
+    fn test_stack_guard() {
+        let code = &[
+            0x37, 0x01, 0x00, 0x80, // lui  sp, 0x80000
+            0x13, 0x01, 0x01, 0x04, // addi sp, sp, 64
+            0x13, 0x01, 0x01, 0xff, // addi sp, sp, -16 (unbounded recursion)
+            0x23, 0x26, 0x11, 0x00, // sw   ra, 12(sp)
+            0x6f, 0xf0, 0x9f, 0xff, // j    -8
+        ];
+        let mut ram = [0; 64];
+        let mut memory = SliceMemory::new(code, &mut ram);
+        let config = Config::default().with_stack_guard(Some((RAM_OFFSET, 16)));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        assert_eq!(
+            engine.run(),
+            Err(EmbiveError::StackOverflow {
+                pc: 12,
+                address: RAM_OFFSET + 12
+            })
+        );
+        assert_eq!(engine.registers.get(2), Ok(RAM_OFFSET as i32));
+
+        // Store crossing into the guard
+        assert_eq!(engine.check_stack_guard(RAM_OFFSET + 16, 4), Ok(()));
+        assert!(engine.check_stack_guard(RAM_OFFSET - 2, 4).is_err());
+    }
+
+    #[cfg(feature = "interrupt")]
+    #[test]
+    fn test_invalid_interrupt_config() {
+        let memory = SliceMemory::new(&[], &mut []);
+
+        let config = Config::default().with_interrupt_granularity(Granularity::Yield);
+        assert_eq!(
+            config.validate(&memory),
+            Err(ConfigError::UnboundedInterruptLatency)
+        );
+
+        let config = Config::default().with_software_interrupts(Some(1), 0);
+        assert_eq!(
+            config.validate(&memory),
+            Err(ConfigError::NoSoftwareInterruptTargets)
+        );
+    }
+
+    #[cfg(feature = "interrupt")]
+    fn interrupt(cause: u32, engine: &mut Engine<SliceMemory>) -> Result<(), EmbiveError> {
+        // Store the cause and program counter at delivery time
+        engine.registers.inner[Register::A1 as usize] = cause as i32;
+        engine.registers.inner[Register::A2 as usize] = engine.program_counter as i32;
+        engine.complete_interrupt();
+        Ok(())
+    }
+
+    #[cfg(feature = "interrupt")]
+    const INTERRUPT_CODE: &[u8] = &[
+        0x13, 0x05, 0x15, 0x00, // addi a0, a0, 1
+        0x13, 0x05, 0x15, 0x00, // addi a0, a0, 1
+        0x6f, 0x00, 0x80, 0x00, // jal  zero, 8
+        0x13, 0x05, 0x15, 0x00, // addi a0, a0, 1
+        0x73, 0x00, 0x10, 0x00, // ebreak          (Halt)
+    ];
+
+    #[cfg(feature = "interrupt")]
+    #[test]
+    fn test_interrupt_instruction() {
+        let mut memory = SliceMemory::new(INTERRUPT_CODE, &mut []);
         let mut engine = Engine::new(
             &mut memory,
-            Config {
-                instruction_limit: 0,
-                ..Default::default()
-            },
+            Config::default().with_interrupt_fn(Some(interrupt)),
         )
         .unwrap();
 
-        // Run the engine
-        let result = engine.run();
-        assert_eq!(result, Ok(false));
-        assert_eq!(engine.program_counter, 4 * 4);
+        engine.raise_interrupt(10).unwrap();
+        engine.raise_interrupt(11).unwrap();
+        assert_eq!(engine.run(), Ok(false));
+
+        // Both delivered, one before each of the first instructions
+        assert_eq!(engine.registers.get(Register::A1 as usize), Ok(11));
+        assert_eq!(engine.registers.get(Register::A2 as usize), Ok(4));
+        assert_eq!(engine.interrupt.pending(), 0);
+    }
+
+    #[cfg(feature = "interrupt")]
+    #[test]
+    fn test_interrupt_basic_block() {
+        let mut memory = SliceMemory::new(INTERRUPT_CODE, &mut []);
+        let mut engine = Engine::new(
+            &mut memory,
+            Config::default()
+                .with_interrupt_fn(Some(interrupt))
+                .with_interrupt_granularity(Granularity::BasicBlock),
+        )
+        .unwrap();
+
+        engine.raise_interrupt(20).unwrap();
+        assert_eq!(engine.run(), Ok(false));
+
+        // Delivered after the jump
+        assert_eq!(engine.registers.get(Register::A1 as usize), Ok(20));
+        assert_eq!(engine.registers.get(Register::A2 as usize), Ok(4 * 4));
+    }
+
+    #[cfg(all(feature = "interrupt", feature = "instruction_limit"))]
+    #[test]
+    fn test_interrupt_yield() {
+        let mut memory = SliceMemory::new(INTERRUPT_CODE, &mut []);
+        let mut engine = Engine::new(
+            &mut memory,
+            Config::default()
+                .with_interrupt_fn(Some(interrupt))
+                .with_interrupt_granularity(Granularity::Yield)
+                .with_instruction_limit(100),
+        )
+        .unwrap();
+
+        // Not delivered while stepping
+        engine.step().unwrap();
+        engine.raise_interrupt(30).unwrap();
+        engine.step().unwrap();
+        assert_eq!(engine.registers.get(Register::A1 as usize), Ok(0));
+
+        // Delivered when running
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(Register::A1 as usize), Ok(30));
+        assert_eq!(engine.registers.get(Register::A2 as usize), Ok(4 * 2));
+    }
+
+    #[cfg(feature = "interrupt")]
+    #[test]
+    fn test_interrupt_no_function() {
+        let mut memory = SliceMemory::new(INTERRUPT_CODE, &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+
+        assert_eq!(
+            engine.raise_interrupt(1),
+            Err(EmbiveError::NoInterruptFunction)
+        );
+    }
+
+    #[cfg(feature = "interrupt")]
+    #[test]
+    fn test_interrupt_in_service() {
+        fn interrupt_no_complete(
+            cause: u32,
+            engine: &mut Engine<SliceMemory>,
+        ) -> Result<(), EmbiveError> {
+            engine.registers.inner[Register::A1 as usize] += cause as i32;
+            Ok(())
+        }
+
+        let mut memory = SliceMemory::new(INTERRUPT_CODE, &mut []);
+        let mut engine = Engine::new(
+            &mut memory,
+            Config::default().with_interrupt_fn(Some(interrupt_no_complete)),
+        )
+        .unwrap();
+
+        engine.raise_interrupt(1).unwrap();
+        engine.raise_interrupt(2).unwrap();
+        assert_eq!(engine.run(), Ok(false));
+
+        // Line 2 is still pending, waiting for line 1 to complete
+        assert_eq!(engine.registers.get(Register::A1 as usize), Ok(1));
+        assert_eq!(engine.interrupt.pending(), 1 << 2);
+        assert_eq!(engine.interrupt.level(), Some(0));
     }
 }
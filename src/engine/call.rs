@@ -0,0 +1,159 @@
+//! Guest calls, host-called guest functions (reverse calls).
+//!
+//! [`Engine::call`] calls a guest function (ex.: a plugin export) with the RISC-V calling convention:
+//! arguments in `a0`-`a7` (unused ones cleared), and the return address (`ra`) set to [`CALL_RETURN_ADDRESS`], a sentinel
+//! that is never executed. The function runs until it returns to the sentinel, and its result (`a0`)
+//! is returned to the host. The program counter and registers are restored afterwards, so the guest
+//! can be called repeatedly, and keeps running from where it was.
+//!
+//! The function uses the current stack pointer: run the guest initialization (ex.: until it halts) first.
+//!
+//! ```
+//! use embive::{engine::{Config, Engine}, memory::SliceMemory};
+//!
+//! let code = &[
+//!     0x73, 0x00, 0x10, 0x00, // ebreak           (Initialization done)
+//!     // add(a, b)
+//!     0x33, 0x05, 0xb5, 0x00, // add  a0, a0, a1
+//!     0x67, 0x80, 0x00, 0x00, // ret
+//! ];
+//! let mut memory = SliceMemory::new(code, &mut []);
+//! let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+//!
+//! assert_eq!(engine.run(), Ok(false));
+//! assert_eq!(engine.call(0x4, &[40, 2]), Ok(42));
+//! assert_eq!(engine.call(0x4, &[-1, 1]), Ok(0));
+//! ```
+
+#[cfg(feature = "instruction_limit")]
+use super::Budget;
+use super::Engine;
+use crate::error::EmbiveError;
+use crate::memory::Memory;
+use crate::register::Register;
+
+/// Maximum number of guest call arguments (`a0`-`a7`).
+pub const CALL_ARGS: usize = 8;
+
+/// Return address of guest calls, the call completes when the guest jumps to it (never executed).
+pub const CALL_RETURN_ADDRESS: u32 = 0xffff_fffc;
+
+impl<M: Memory> Engine<'_, M> {
+    /// Call a guest function (check the [module documentation](self)).
+    ///
+    /// If the `instruction_limit` feature is enabled, the function can execute up to
+    /// [`super::Config::instruction_limit`] instructions (0 = Unlimited).
+    ///
+    /// Arguments:
+    /// - `address`: Function address.
+    /// - `args`: Function arguments (`a0`-`a7`, up to [`CALL_ARGS`]).
+    ///
+    /// Returns:
+    /// - `Ok(i32)`: Function result (`a0`), the program counter and registers are restored.
+    /// - `Err(EmbiveError)`: Failed to call, the guest state is left as is (ex.: to inspect the fault).
+    ///     - Too many arguments, nothing was executed ([`EmbiveError::TooManyArguments`]).
+    ///     - Halted, suspended, stopped or out of instructions before returning ([`EmbiveError::CallNotReturned`]).
+    ///     - Maximum run depth exceeded, nothing was executed ([`EmbiveError::RecursionLimit`]).
+    ///     - Not enough scratch memory for the configuration, nothing was executed ([`EmbiveError::ScratchTooSmall`]).
+    pub fn call(&mut self, address: u32, args: &[i32]) -> Result<i32, EmbiveError> {
+        if args.len() > CALL_ARGS {
+            return Err(EmbiveError::TooManyArguments);
+        }
+
+        // Nested runs are limited (check `Config::max_run_depth`)
+        let _run = self.enter_run()?;
+
+        let program_counter = self.program_counter;
+        let registers = self.registers;
+
+        // Unused argument registers are cleared
+        let a0 = Register::A0 as usize;
+        self.registers.inner[a0..a0 + CALL_ARGS].fill(0);
+        self.registers.inner[a0..a0 + args.len()].copy_from_slice(args);
+        self.registers.inner[Register::RA as usize] = CALL_RETURN_ADDRESS as i32;
+        self.program_counter = address;
+
+        #[cfg(feature = "instruction_limit")]
+        {
+            self.budget = match self.config.instruction_limit {
+                0 => Budget::Unlimited,
+                _ => Budget::Slice,
+            };
+            self.slice = self.config.instruction_limit;
+        }
+
+        match self.instrumented() {
+            true => self.call_loop::<true>()?,
+            false => self.call_loop::<false>()?,
+        }
+
+        let result = self.registers.inner[a0];
+        self.program_counter = program_counter;
+        self.registers = registers;
+
+        Ok(result)
+    }
+
+    /// Instruction loop of [`Engine::call`], until the function returns (check [`Engine::instrumented`]).
+    ///
+    /// Returns:
+    /// - `Ok(())`: The function returned.
+    /// - `Err(EmbiveError)`: Failed to run, or the function didn't return (check [`Engine::call`]).
+    fn call_loop<const INSTRUMENTED: bool>(&mut self) -> Result<(), EmbiveError> {
+        while self.program_counter != CALL_RETURN_ADDRESS {
+            #[cfg(feature = "instruction_limit")]
+            if self.budget == Budget::Slice {
+                if self.slice == 0 {
+                    return Err(EmbiveError::CallNotReturned);
+                }
+                self.slice -= 1;
+            }
+
+            // Step through the function
+            if !self.run_step::<INSTRUMENTED>()? {
+                // Halted, suspended or stopped
+                return Err(EmbiveError::CallNotReturned);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Config;
+    use crate::memory::SliceMemory;
+
+    #[test]
+    fn test_call() {
+        let code = &[
+            0x13, 0x04, 0x70, 0x00, // li   s0, 7
+            0x73, 0x00, 0x10, 0x00, // ebreak
+            // sum(a0, .., a7)
+            0x33, 0x05, 0xb5, 0x00, // add  a0, a0, a1
+            0x33, 0x05, 0x15, 0x01, // add  a0, a0, a7
+            0x67, 0x80, 0x00, 0x00, // ret
+        ];
+        let mut memory = SliceMemory::new(code, &mut []);
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+
+        // Call before the guest initialization, state restored
+        assert_eq!(engine.call(0x8, &[1, 2, 0, 0, 0, 0, 0, 3]), Ok(6));
+        assert_eq!(engine.program_counter, 0x0);
+        assert_eq!(engine.registers.get(10), Ok(0));
+
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.call(0x8, &[5]), Ok(5));
+        assert_eq!(engine.registers.get(8), Ok(7));
+
+        assert_eq!(
+            engine.call(0x8, &[0; CALL_ARGS + 1]),
+            Err(EmbiveError::TooManyArguments)
+        );
+
+        // Halts before returning
+        assert_eq!(engine.call(0x0, &[]), Err(EmbiveError::CallNotReturned));
+    }
+}
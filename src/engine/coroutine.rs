@@ -0,0 +1,170 @@
+//! Coroutine Module
+//!
+//! Generator-style host API over the yield syscall ([`super::Config::yield_nr`]): the host resumes the guest
+//! with an input, and gets back the value the guest yields (or its result, once it halts).
+//!
+//! ```
+//! use embive::{
+//!     engine::{Config, CoroutineState, Engine, GuestCoroutine},
+//!     memory::SliceMemory,
+//! };
+//!
+//! let code = &[
+//!     0x93, 0x08, 0x90, 0x00, // li   a7, 9   (Yield syscall)
+//!     0x13, 0x05, 0x00, 0x00, // li   a0, 0
+//!     0x73, 0x00, 0x00, 0x00, // ecall        (Yield a0, a1 = input)
+//!     0x13, 0x85, 0x15, 0x00, // addi a0, a1, 1
+//!     0xe3, 0xdc, 0x05, 0xfe, // bgez a1, -8  (Until a negative input)
+//!     0x73, 0x00, 0x10, 0x00, // ebreak       (Halt, result in a0)
+//! ];
+//! let mut memory = SliceMemory::new(code, &mut []);
+//! let config = Config::default().with_yield_nr(Some(9));
+//! let mut engine = Engine::new(&mut memory, config).unwrap();
+//! let mut coroutine = GuestCoroutine::new(&mut engine);
+//!
+//! assert_eq!(coroutine.resume(0), Ok(CoroutineState::Yielded(0)));
+//! assert_eq!(coroutine.resume(41), Ok(CoroutineState::Yielded(42)));
+//! assert_eq!(coroutine.resume(-1), Ok(CoroutineState::Complete(0)));
+//! ```
+
+use super::{Engine, WaitingFor};
+use crate::error::EmbiveError;
+use crate::memory::Memory;
+use crate::register::Register;
+
+/// State of a guest coroutine after resuming it.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CoroutineState {
+    /// The guest yielded a value, resume it with the next input.
+    Yielded(i32),
+    /// The guest halted, with its result (`a0`).
+    Complete(i32),
+    /// The guest didn't yield yet, resume it again (the input is ignored):
+    /// - `None`: Preempted (ex.: instruction limit reached).
+    /// - `Some(WaitingFor)`: Suspended on I/O, wake it first ([`Engine::wake`]).
+    Pending(Option<WaitingFor>),
+}
+
+/// Guest coroutine, driving an engine with the yield syscall (check the [module documentation](self)).
+pub struct GuestCoroutine<'e, 'a, M: Memory> {
+    /// Embive engine.
+    engine: &'e mut Engine<'a, M>,
+    /// Guest result, once complete.
+    result: Option<i32>,
+}
+
+impl<'e, 'a, M: Memory> GuestCoroutine<'e, 'a, M> {
+    /// Create a new coroutine, starting from the current engine state.
+    ///
+    /// Arguments:
+    /// - `engine`: Embive engine, with the yield syscall permitted ([`super::Config::with_yield_nr`]).
+    pub fn new(engine: &'e mut Engine<'a, M>) -> Self {
+        GuestCoroutine {
+            engine,
+            result: None,
+        }
+    }
+
+    /// Resume the guest until it yields, halts or stops running.
+    ///
+    /// Arguments:
+    /// - `input`: Value passed in to the guest, returned by its pending yield syscall (`a1`).
+    ///   Ignored if the guest isn't yielded (ex.: on the first resume).
+    ///
+    /// Returns:
+    /// - `Ok(CoroutineState)`: State of the guest (once complete, the result is returned again).
+    /// - `Err(EmbiveError)`: Failed to run.
+    pub fn resume(&mut self, input: i32) -> Result<CoroutineState, EmbiveError> {
+        if let Some(result) = self.result {
+            return Ok(CoroutineState::Complete(result));
+        }
+
+        self.engine.resume(input);
+        if !self.engine.run()? {
+            let result = self.engine.registers.inner[Register::A0 as usize];
+            self.result = Some(result);
+            return Ok(CoroutineState::Complete(result));
+        }
+
+        Ok(match self.engine.waiting_for() {
+            Some(WaitingFor::Resume(value)) => CoroutineState::Yielded(value),
+            waiting => CoroutineState::Pending(waiting),
+        })
+    }
+
+    /// Check if the guest halted.
+    pub fn is_complete(&self) -> bool {
+        self.result.is_some()
+    }
+
+    /// Restart the guest from the entry point ([`Engine::reset`]).
+    pub fn restart(&mut self) {
+        self.engine.reset();
+        self.result = None;
+    }
+
+    /// Get a reference to the engine.
+    pub fn engine(&self) -> &Engine<'a, M> {
+        self.engine
+    }
+
+    /// Get a mutable reference to the engine.
+    pub fn engine_mut(&mut self) -> &mut Engine<'a, M> {
+        self.engine
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Config;
+    use crate::memory::SliceMemory;
+
+    const CODE: &[u8] = &[
+        0x93, 0x08, 0x90, 0x00, // li   a7, 9   (Yield syscall)
+        0x13, 0x05, 0x30, 0x00, // li   a0, 3
+        0x73, 0x00, 0x00, 0x00, // ecall
+        0x33, 0x85, 0xa5, 0x00, // add  a0, a1, a0 (a0 = 0 on success)
+        0x73, 0x00, 0x10, 0x00, // ebreak
+    ];
+
+    #[test]
+    fn test_coroutine() {
+        let mut memory = SliceMemory::new(CODE, &mut []);
+        let config = Config::default().with_yield_nr(Some(9));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+        let mut coroutine = GuestCoroutine::new(&mut engine);
+
+        assert_eq!(coroutine.resume(100), Ok(CoroutineState::Yielded(3)));
+        assert_eq!(coroutine.resume(4), Ok(CoroutineState::Complete(4)));
+        assert!(coroutine.is_complete());
+        assert_eq!(coroutine.resume(5), Ok(CoroutineState::Complete(4)));
+
+        coroutine.restart();
+        assert!(!coroutine.is_complete());
+        assert_eq!(coroutine.resume(0), Ok(CoroutineState::Yielded(3)));
+        assert_eq!(coroutine.resume(-3), Ok(CoroutineState::Complete(-3)));
+    }
+
+    #[cfg(feature = "instruction_limit")]
+    #[test]
+    fn test_coroutine_pending() {
+        let mut memory = SliceMemory::new(CODE, &mut []);
+        let config = Config::default()
+            .with_yield_nr(Some(9))
+            .with_instruction_limit(1);
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+        let mut coroutine = GuestCoroutine::new(&mut engine);
+
+        assert_eq!(coroutine.resume(0), Ok(CoroutineState::Pending(None)));
+        assert_eq!(coroutine.resume(0), Ok(CoroutineState::Pending(None)));
+        assert_eq!(coroutine.resume(0), Ok(CoroutineState::Yielded(3)));
+        assert_eq!(coroutine.resume(4), Ok(CoroutineState::Pending(None)));
+        assert_eq!(coroutine.resume(0), Ok(CoroutineState::Pending(None)));
+        assert_eq!(
+            coroutine.engine().registers.get(Register::A0 as usize),
+            Ok(4)
+        );
+        assert_eq!(coroutine.resume(0), Ok(CoroutineState::Complete(4)));
+    }
+}
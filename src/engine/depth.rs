@@ -0,0 +1,234 @@
+//! Run depth, nested host/guest transitions.
+//!
+//! Host callbacks (ex.: the syscall function) can run other engines, so a guest calling the host can end up
+//! running a guest that calls the host again (ex.: a plugin calling another plugin). Every level adds host
+//! stack frames, and adversarial guests can ping-pong until the host stack is exhausted.
+//!
+//! The run depth is the number of engine runs ([`Engine::run`], [`Engine::run_with_fuel`]) in progress:
+//! per thread with the `std` feature, process-wide otherwise (`no_std` hosts, runs from interrupt handlers
+//! count as nested).
+//! A run fails with [`EmbiveError::RecursionLimit`] if it would exceed [`super::Config::max_run_depth`],
+//! without executing any guest instruction, the outer levels keep running.
+//!
+//! ```
+//! use embive::{
+//!     engine::{run_depth, Config, Engine, SYSCALL_ARGS},
+//!     error::EmbiveError,
+//!     memory::SliceMemory,
+//! };
+//!
+//! static CODE: &[u8] = &[
+//!     0x93, 0x08, 0x10, 0x00, // li     a7, 1 (Syscall nr)
+//!     0x73, 0x00, 0x00, 0x00, // ecall
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//! ];
+//!
+//! // Every syscall runs the guest again
+//! fn syscall(_nr: i32, _args: &[i32; SYSCALL_ARGS], _memory: &mut SliceMemory) -> Result<i32, i32> {
+//!     let mut memory = SliceMemory::new(CODE, &mut []);
+//!     let mut engine = Engine::new(&mut memory, config()).unwrap();
+//!     match engine.run() {
+//!         Ok(_) => Ok(0),
+//!         Err(EmbiveError::RecursionLimit) => Err(1),
+//!         Err(_) => Err(2),
+//!     }
+//! }
+//!
+//! fn config() -> Config<SliceMemory<'static>> {
+//!     Config::default()
+//!         .with_syscall_fn(Some(syscall))
+//!         .with_max_run_depth(4)
+//! }
+//!
+//! let mut memory = SliceMemory::new(CODE, &mut []);
+//! let mut engine = Engine::new(&mut memory, config()).unwrap();
+//! assert_eq!(engine.run(), Ok(false));
+//! assert_eq!(engine.registers.get(10), Ok(0));
+//! assert_eq!(run_depth(), 0);
+//! ```
+//!
+//! [`Engine::run`]: super::Engine::run
+//! [`Engine::run_with_fuel`]: super::Engine::run_with_fuel
+
+use crate::error::EmbiveError;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+// Per thread in tests too, they run in parallel
+#[cfg(any(test, feature = "std"))]
+std::thread_local! {
+    /// Engine runs in progress (current thread).
+    static DEPTH: AtomicDepth = const { AtomicDepth::new() };
+}
+
+/// Engine runs in progress (process-wide).
+#[cfg(not(any(test, feature = "std")))]
+static DEPTH: AtomicDepth = AtomicDepth::new();
+
+/// Run depth counter.
+///
+/// Entering and leaving are atomic read-modify-writes, so runs interleaved by preemption
+/// (ex.: an engine running from an interrupt handler) don't corrupt each other's count.
+/// Targets without atomic read-modify-writes (ex.: ARMv6-M) fall back to a load and a store,
+/// don't run engines from preempting contexts there.
+pub(crate) struct AtomicDepth(AtomicU32);
+
+impl AtomicDepth {
+    /// Create a counter (not running).
+    pub(crate) const fn new() -> Self {
+        AtomicDepth(AtomicU32::new(0))
+    }
+
+    /// Get the run depth.
+    fn get(&self) -> u32 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Enter a run.
+    ///
+    /// Arguments:
+    /// - `max_depth`: Maximum run depth (0 = Unlimited).
+    ///
+    /// Returns:
+    /// - `Ok(u32)`: Depth of the entered run (1 = Not nested).
+    /// - `Err(EmbiveError)`: Maximum run depth exceeded ([`EmbiveError::RecursionLimit`]), the depth is unchanged.
+    fn enter(&self, max_depth: u32) -> Result<u32, EmbiveError> {
+        #[cfg(target_has_atomic = "32")]
+        let depth = self.0.fetch_add(1, Ordering::Relaxed).wrapping_add(1);
+        #[cfg(not(target_has_atomic = "32"))]
+        let depth = {
+            let depth = self.get().wrapping_add(1);
+            self.0.store(depth, Ordering::Relaxed);
+            depth
+        };
+
+        if max_depth > 0 && depth > max_depth {
+            self.leave();
+            return Err(EmbiveError::RecursionLimit);
+        }
+
+        Ok(depth)
+    }
+
+    /// Leave a run.
+    fn leave(&self) {
+        #[cfg(target_has_atomic = "32")]
+        self.0.fetch_sub(1, Ordering::Relaxed);
+        #[cfg(not(target_has_atomic = "32"))]
+        self.0.store(self.get().wrapping_sub(1), Ordering::Relaxed);
+    }
+}
+
+/// Run a function with the current run depth counter.
+///
+/// Arguments:
+/// - `f`: Function to run.
+///
+/// Returns:
+/// - `T`: Result of the function.
+#[cfg(any(test, feature = "std"))]
+fn with<T>(f: impl FnOnce(&AtomicDepth) -> T) -> T {
+    DEPTH.with(f)
+}
+
+/// Run a function with the current run depth counter.
+///
+/// Arguments:
+/// - `f`: Function to run.
+///
+/// Returns:
+/// - `T`: Result of the function.
+#[cfg(not(any(test, feature = "std")))]
+fn with<T>(f: impl FnOnce(&AtomicDepth) -> T) -> T {
+    f(&DEPTH)
+}
+
+/// Get the number of engine runs in progress (check the [module documentation](self)).
+///
+/// Returns:
+/// - `u32`: Run depth (0 = Not running, 1 = Running, not nested).
+pub fn run_depth() -> u32 {
+    with(|depth| depth.get())
+}
+
+/// Engine run in progress, leaves the run when dropped (also when unwinding from a host callback panic).
+pub(crate) struct RunGuard {
+    /// Depth of this run when entered (1 = Not nested).
+    #[cfg_attr(not(feature = "accounting"), allow(dead_code))]
+    depth: u32,
+}
+
+impl RunGuard {
+    /// Enter a run.
+    ///
+    /// Arguments:
+    /// - `max_depth`: Maximum run depth (0 = Unlimited).
+    ///
+    /// Returns:
+    /// - `Ok(RunGuard)`: Run entered.
+    /// - `Err(EmbiveError)`: Maximum run depth exceeded ([`EmbiveError::RecursionLimit`]).
+    pub(crate) fn enter(max_depth: u32) -> Result<Self, EmbiveError> {
+        let depth = with(|depth| depth.enter(max_depth))?;
+        Ok(RunGuard { depth })
+    }
+
+    /// Get the depth of this run when entered (1 = Not nested).
+    #[cfg(feature = "accounting")]
+    pub(crate) fn depth(&self) -> u32 {
+        self.depth
+    }
+}
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        with(|depth| depth.leave());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_guard() {
+        assert_eq!(run_depth(), 0);
+
+        let outer = RunGuard::enter(2).unwrap();
+        assert_eq!(run_depth(), 1);
+        {
+            let _inner = RunGuard::enter(2).unwrap();
+            assert_eq!(run_depth(), 2);
+            assert!(matches!(
+                RunGuard::enter(2),
+                Err(EmbiveError::RecursionLimit)
+            ));
+            assert_eq!(run_depth(), 2);
+
+            // Unlimited
+            let _unlimited = RunGuard::enter(0).unwrap();
+            assert_eq!(run_depth(), 3);
+        }
+        assert_eq!(run_depth(), 1);
+
+        drop(outer);
+        assert_eq!(run_depth(), 0);
+    }
+
+    #[test]
+    fn test_atomic_depth() {
+        // Process-wide counter of `no_std` builds
+        let depth = AtomicDepth::new();
+
+        assert_eq!(depth.enter(2), Ok(1));
+        assert_eq!(depth.enter(2), Ok(2));
+        assert_eq!(depth.enter(2), Err(EmbiveError::RecursionLimit));
+        assert_eq!(depth.get(), 2);
+
+        // Runs left out of order (ex.: interleaved by an interrupt handler)
+        depth.leave();
+        assert_eq!(depth.get(), 1);
+        assert_eq!(depth.enter(0), Ok(2));
+        depth.leave();
+        depth.leave();
+        assert_eq!(depth.get(), 0);
+    }
+}
@@ -0,0 +1,158 @@
+//! Export table, guest functions looked up by name (check [`Engine::resolve_export`]).
+//!
+//! Guests embed a table of their exported functions, located by the host with [`super::Config::export_table`]
+//! (ex.: the [`EXPORT_SECTION`] section address, check [`crate::loader::Elf::section`]).
+//! Format (little endian):
+//! - Magic ([`EXPORT_MAGIC`], `"EXPT"`).
+//! - Number of entries (`u32`).
+//! - Entries ([`EXPORT_ENTRY_SIZE`] bytes each): function address (`u32`), then the name
+//!   (up to [`EXPORT_NAME_SIZE`] bytes, zero-padded).
+//!
+//! ```
+//! use embive::{
+//!     engine::{Config, Engine, EXPORT_MAGIC},
+//!     memory::SliceMemory,
+//! };
+//!
+//! let mut code = [0; 52];
+//! code[..12].copy_from_slice(&[
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//!     // add(a, b)
+//!     0x33, 0x05, 0xb5, 0x00, // add  a0, a0, a1
+//!     0x67, 0x80, 0x00, 0x00, // ret
+//! ]);
+//! // Export table: 1 entry, `add` at 0x4
+//! code[12..16].copy_from_slice(&EXPORT_MAGIC.to_le_bytes());
+//! code[16..20].copy_from_slice(&1u32.to_le_bytes());
+//! code[20..24].copy_from_slice(&4u32.to_le_bytes());
+//! code[24..27].copy_from_slice(b"add");
+//!
+//! let mut memory = SliceMemory::new(&code, &mut []);
+//! let config = Config::default().with_export_table(Some(12));
+//! let mut engine = Engine::new(&mut memory, config).unwrap();
+//!
+//! let add = engine.resolve_export("add").unwrap();
+//! assert_eq!(engine.call(add, &[40, 2]), Ok(42));
+//! assert_eq!(engine.resolve_export("sub"), None);
+//! ```
+
+use super::Engine;
+use crate::memory::Memory;
+
+/// Export table magic (`"EXPT"`).
+pub const EXPORT_MAGIC: u32 = u32::from_le_bytes(*b"EXPT");
+/// Maximum export name size in bytes.
+pub const EXPORT_NAME_SIZE: usize = 28;
+/// Export table entry size in bytes (address and name).
+pub const EXPORT_ENTRY_SIZE: usize = 4 + EXPORT_NAME_SIZE;
+/// Conventional section of the export table (ELF guests).
+pub const EXPORT_SECTION: &str = ".embive_exports";
+
+impl<M: Memory> Engine<'_, M> {
+    /// Look up an exported guest function by name (check the [module documentation](self)).
+    ///
+    /// Arguments:
+    /// - `name`: Export name.
+    ///
+    /// Returns:
+    /// - `Some(u32)`: Function address (ex.: for [`Engine::call`]).
+    /// - `None`: Not exported, or no valid export table ([`super::Config::export_table`]).
+    pub fn resolve_export(&self, name: &str) -> Option<u32> {
+        let table = self.config.export_table?;
+        let name = name.as_bytes();
+        if name.is_empty() || name.len() > EXPORT_NAME_SIZE {
+            return None;
+        }
+
+        let word = |address: u32| self.memory.load::<4>(address).ok().map(u32::from_le_bytes);
+        if word(table)? != EXPORT_MAGIC {
+            return None;
+        }
+
+        let mut entry = table.checked_add(8)?;
+        for _ in 0..word(table.wrapping_add(4))? {
+            let mut export = [0; EXPORT_NAME_SIZE];
+            self.memory
+                .load_bytes(entry.checked_add(4)?, &mut export)
+                .ok()?;
+
+            // Exact match, zero-padded
+            if export.starts_with(name) && export[name.len()..].iter().all(|&byte| byte == 0) {
+                return word(entry);
+            }
+
+            entry = entry.checked_add(EXPORT_ENTRY_SIZE as u32)?;
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Config;
+    use crate::memory::SliceMemory;
+
+    /// Build an export table (at address 0).
+    fn table(entries: &[(u32, &[u8])]) -> [u8; 8 + 3 * EXPORT_ENTRY_SIZE] {
+        let mut table = [0; 8 + 3 * EXPORT_ENTRY_SIZE];
+        table[..4].copy_from_slice(&EXPORT_MAGIC.to_le_bytes());
+        table[4..8].copy_from_slice(&(entries.len() as u32).to_le_bytes());
+        for (index, (address, name)) in entries.iter().enumerate() {
+            let offset = 8 + index * EXPORT_ENTRY_SIZE;
+            table[offset..offset + 4].copy_from_slice(&address.to_le_bytes());
+            table[offset + 4..offset + 4 + name.len()].copy_from_slice(name);
+        }
+        table
+    }
+
+    #[test]
+    fn test_resolve_export() {
+        let long = [b'f'; EXPORT_NAME_SIZE];
+        let code = table(&[(0x100, b"init"), (0x200, b"in"), (0x300, &long)]);
+        let mut memory = SliceMemory::new(&code, &mut []);
+        let config = Config::default().with_export_table(Some(0));
+        let engine = Engine::new(&mut memory, config).unwrap();
+
+        assert_eq!(engine.resolve_export("init"), Some(0x100));
+        assert_eq!(engine.resolve_export("in"), Some(0x200));
+        assert_eq!(
+            engine.resolve_export(core::str::from_utf8(&long).unwrap()),
+            Some(0x300)
+        );
+        assert_eq!(engine.resolve_export("i"), None);
+        assert_eq!(engine.resolve_export("init2"), None);
+        assert_eq!(engine.resolve_export(""), None);
+        assert_eq!(engine.resolve_export("fffffffffffffffffffffffffffff"), None);
+    }
+
+    #[test]
+    fn test_invalid_table() {
+        // No table
+        let mut code = table(&[(0x100, b"init")]);
+        let mut memory = SliceMemory::new(&code, &mut []);
+        let engine = Engine::new(&mut memory, Config::default()).unwrap();
+        assert_eq!(engine.resolve_export("init"), None);
+
+        // Out of bounds
+        let config = Config::default().with_export_table(Some(0x1000));
+        let engine = Engine::new(&mut memory, config).unwrap();
+        assert_eq!(engine.resolve_export("init"), None);
+
+        // Entries past the end of the memory
+        code[4..8].copy_from_slice(&100u32.to_le_bytes());
+        let mut memory = SliceMemory::new(&code, &mut []);
+        let config = Config::default().with_export_table(Some(0));
+        let engine = Engine::new(&mut memory, config).unwrap();
+        assert_eq!(engine.resolve_export("init"), Some(0x100));
+        assert_eq!(engine.resolve_export("exit"), None);
+
+        // Bad magic
+        code[0] = 0;
+        let mut memory = SliceMemory::new(&code, &mut []);
+        let config = Config::default().with_export_table(Some(0));
+        let engine = Engine::new(&mut memory, config).unwrap();
+        assert_eq!(engine.resolve_export("init"), None);
+    }
+}
@@ -0,0 +1,97 @@
+//! Fetch batching, instruction words fetched ahead of the program counter.
+//!
+//! With the `fetch_batch` feature, the engine loads [`FETCH_BATCH`] bytes of code with a single
+//! [`Memory::fetch`] call and executes straight-line code out of them, going back to memory when the
+//! program counter leaves the window (ex.: a taken branch out of it). This cuts the memory call overhead
+//! when the memory implementation isn't trivial (ex.: paged, banked or instrumented).
+//!
+//! The window is a copy of the code: it is dropped when the engine runs, is reset, handles a syscall,
+//! delivers an interrupt or executes `fence.i`. Hosts changing the code otherwise (ex.: between
+//! [`super::Engine::step`] calls) must call [`super::Engine::invalidate_fetch`].
+
+use crate::memory::Memory;
+
+/// Bytes fetched per memory call (4 instruction words).
+pub const FETCH_BATCH: usize = 16;
+
+/// Instruction words fetched ahead of the program counter.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct FetchBuffer {
+    /// Address of the first byte.
+    address: u32,
+    /// Window holds fetched code.
+    valid: bool,
+    /// Code bytes.
+    data: [u8; FETCH_BATCH],
+}
+
+impl FetchBuffer {
+    /// Get an instruction (raw) from the window, fetching a new one if the address is outside it.
+    ///
+    /// Arguments:
+    /// - `memory`: System memory.
+    /// - `address`: Instruction address.
+    ///
+    /// Returns:
+    /// - `Some(u32)`: The instruction (raw), 4 bytes.
+    /// - `None`: Not enough code for a full window (ex.: end of the code), fetch without batching.
+    #[inline(always)]
+    pub fn fetch<M: Memory>(&mut self, memory: &M, address: u32) -> Option<u32> {
+        let offset = address.wrapping_sub(self.address) as usize;
+        if self.valid && offset <= FETCH_BATCH - 4 {
+            let word = &self.data[offset..offset + 4];
+            return Some(u32::from_le_bytes([word[0], word[1], word[2], word[3]]));
+        }
+
+        match memory.fetch::<FETCH_BATCH>(address) {
+            Ok(data) => {
+                self.address = address;
+                self.valid = true;
+                self.data = data;
+
+                // Unwrap is safe because FETCH_BATCH is larger than 4 bytes.
+                Some(u32::from_le_bytes(*data.first_chunk().unwrap()))
+            }
+            Err(_) => {
+                self.invalidate();
+                None
+            }
+        }
+    }
+
+    /// Drop the fetched bytes.
+    #[inline(always)]
+    pub fn invalidate(&mut self) {
+        self.valid = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::SliceMemory;
+
+    #[test]
+    fn test_fetch_batch() {
+        let code: [u8; 20] = core::array::from_fn(|i| i as u8);
+        let mut memory = SliceMemory::new(&code, &mut []);
+        let mut buffer = FetchBuffer::default();
+
+        assert_eq!(buffer.fetch(&memory, 0), Some(0x03020100));
+        assert_eq!(buffer.fetch(&memory, 12), Some(0x0f0e0d0c));
+
+        // Window still in use (memory isn't read)
+        memory = SliceMemory::new(&[], &mut []);
+        assert_eq!(buffer.fetch(&memory, 2), Some(0x05040302));
+
+        // Outside the window, not enough code
+        assert_eq!(buffer.fetch(&memory, 14), None);
+        assert_eq!(buffer.fetch(&memory, 0), None);
+
+        memory = SliceMemory::new(&code, &mut []);
+        assert_eq!(buffer.fetch(&memory, 4), Some(0x07060504));
+        assert_eq!(buffer.fetch(&memory, 8), Some(0x0b0a0908));
+        buffer.invalidate();
+        assert_eq!(buffer.fetch(&memory, 2), Some(0x05040302));
+    }
+}
@@ -0,0 +1,118 @@
+//! RAM fill pattern, to scrub the guest RAM.
+//!
+//! The RAM is filled with the configured pattern ([`super::Config::ram_fill`]): zero (as a fresh guest expects),
+//! or a poison pattern to flush out reads of uninitialized memory. Hosts whose RAM buffers aren't zeroed
+//! (ex.: reused or uninitialized) apply it to the whole RAM with [`Engine::fill_ram`], so runs are reproducible.
+//! Warm restarts scrub the RAM with the same pattern (if the `persistent_regions` feature is enabled).
+
+use super::Engine;
+use crate::error::EmbiveError;
+use crate::memory::{Memory, RAM_OFFSET};
+
+/// Fill pattern of scrubbed RAM (check [`super::Config::ram_fill`]).
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum RamFill {
+    /// Zero bytes.
+    #[default]
+    Zero,
+    /// A repeated byte (ex.: `0xAA`).
+    Byte(u8),
+    /// Pseudo-random bytes from a seed, the same on every host (depend only on the seed and the address).
+    Random(u64),
+}
+
+impl RamFill {
+    /// Get the fill bytes of an aligned word.
+    ///
+    /// Arguments:
+    /// - `address`: Word address (4 bytes aligned).
+    pub const fn word(self, address: u32) -> [u8; 4] {
+        match self {
+            RamFill::Zero => [0; 4],
+            RamFill::Byte(byte) => [byte; 4],
+            RamFill::Random(seed) => {
+                // SplitMix64 of the seed and word index
+                let mut value = seed ^ (address / 4) as u64;
+                value = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+                value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+                value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+                ((value ^ (value >> 31)) as u32).to_le_bytes()
+            }
+        }
+    }
+
+    /// Get the fill byte of an address.
+    ///
+    /// Arguments:
+    /// - `address`: Byte address.
+    pub const fn byte(self, address: u32) -> u8 {
+        self.word(address & !0b11)[(address & 0b11) as usize]
+    }
+}
+
+impl<M: Memory> Engine<'_, M> {
+    /// Fill the whole RAM with the configured pattern ([`super::Config::ram_fill`]), including the persistent regions (if any).
+    /// Ex.: before loading a guest into a RAM buffer that isn't zeroed.
+    ///
+    /// Arguments:
+    /// - `ram_size`: RAM size in bytes (filled from [`RAM_OFFSET`]).
+    ///
+    /// Returns:
+    /// - `Ok(())`: RAM filled.
+    /// - `Err(EmbiveError)`: Failed to fill the RAM (ex.: RAM is smaller than `ram_size`).
+    pub fn fill_ram(&mut self, ram_size: u32) -> Result<(), EmbiveError> {
+        self.scrub(RAM_OFFSET, RAM_OFFSET.saturating_add(ram_size))
+    }
+
+    /// Fill a RAM range with the configured pattern, using word stores where aligned.
+    ///
+    /// Arguments:
+    /// - `address`: Range start.
+    /// - `end`: Range end (exclusive).
+    pub(super) fn scrub(&mut self, mut address: u32, end: u32) -> Result<(), EmbiveError> {
+        let fill = self.config.ram_fill;
+        while address < end {
+            if address % 4 == 0 && end - address >= 4 {
+                self.memory.store(address, fill.word(address))?;
+                address += 4;
+            } else {
+                self.memory.store(address, [fill.byte(address)])?;
+                address += 1;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Config;
+    use crate::memory::SliceMemory;
+
+    #[test]
+    fn test_ram_fill() {
+        let mut ram = [0; 16];
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let config = Config::default().with_ram_fill(RamFill::Byte(0xAA));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        engine.fill_ram(16).unwrap();
+        assert_eq!(ram, [0xAA; 16]);
+
+        // Random pattern, unaligned scrubs match the aligned fill
+        let fill = RamFill::Random(42);
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut engine = Engine::new(&mut memory, Config::default().with_ram_fill(fill)).unwrap();
+        engine.scrub(RAM_OFFSET + 1, RAM_OFFSET + 7).unwrap();
+
+        let mut expected = [0xAA; 16];
+        expected[1..7]
+            .copy_from_slice(&[fill.word(RAM_OFFSET), fill.word(RAM_OFFSET + 4)].concat()[1..7]);
+        assert_eq!(ram, expected);
+        assert_ne!(fill.word(RAM_OFFSET), fill.word(RAM_OFFSET + 4));
+        assert_ne!(fill.word(RAM_OFFSET), RamFill::Random(43).word(RAM_OFFSET));
+        assert_eq!(fill.byte(RAM_OFFSET + 6), fill.word(RAM_OFFSET + 4)[2]);
+    }
+}
@@ -0,0 +1,209 @@
+//! Syscall history, the last syscalls handled by the engine (flight recorder for diagnostics).
+//!
+//! Enabled with [`super::Config::syscall_history`], the records are kept in scratch memory
+//! ([`Engine::set_scratch`], [`SYSCALL_RECORD_SIZE`] bytes each). The history survives resets,
+//! so the host can dump what the guest was doing before a failure.
+//!
+//! ```
+//! use embive::{engine::{Config, Engine, SyscallRecord}, memory::{ScratchMemory, SliceMemory}};
+//!
+//! let code = &[
+//!     0x93, 0x08, 0x10, 0x00, // li   a7, 1 (Syscall nr)
+//!     0x73, 0x00, 0x00, 0x00, // ecall
+//!     0x73, 0x00, 0x00, 0x00, // ecall
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//! ];
+//! let mut pool = [0; 64];
+//! let mut scratch = ScratchMemory::new(&mut pool);
+//! let mut memory = SliceMemory::new(code, &mut []);
+//! let config = Config::default()
+//!     .with_syscall_fn(Some(|_, args, _| Err(args[0] + 1)))
+//!     .with_syscall_history(4);
+//! let mut engine = Engine::new(&mut memory, config).unwrap();
+//! engine.set_scratch(&mut scratch).unwrap();
+//!
+//! assert_eq!(engine.run(), Ok(false));
+//! let mut history = engine.syscall_history();
+//! assert_eq!(history.next(), Some(SyscallRecord { program_counter: 4, nr: 1, result: Err(1) }));
+//! assert_eq!(history.next(), Some(SyscallRecord { program_counter: 8, nr: 1, result: Err(2) }));
+//! assert_eq!(history.next(), None);
+//! ```
+
+use super::Engine;
+use crate::memory::Memory;
+use crate::register::Register;
+
+/// Syscall record size in bytes (in scratch memory).
+pub const SYSCALL_RECORD_SIZE: usize = 16;
+
+/// Syscall handled by the engine (check the [module documentation](self)).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SyscallRecord {
+    /// Address of the `ecall` instruction.
+    pub program_counter: u32,
+    /// Syscall number.
+    pub nr: i32,
+    /// Syscall result: value (`a1`) or error (`a0`).
+    pub result: Result<i32, i32>,
+}
+
+impl SyscallRecord {
+    /// Encode the record (little-endian words: address, number, error flag and value).
+    fn encode(&self) -> [u8; SYSCALL_RECORD_SIZE] {
+        let (error, value) = match self.result {
+            Ok(value) => (0, value),
+            Err(error) => (1, error),
+        };
+
+        let mut bytes = [0; SYSCALL_RECORD_SIZE];
+        for (chunk, word) in bytes.chunks_exact_mut(4).zip([
+            self.program_counter,
+            self.nr as u32,
+            error,
+            value as u32,
+        ]) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decode a record.
+    ///
+    /// Arguments:
+    /// - `bytes`: Encoded record.
+    fn decode(bytes: &[u8]) -> Self {
+        let word = |index: usize| {
+            // Unwrap is safe because records are SYSCALL_RECORD_SIZE bytes long.
+            u32::from_le_bytes(*bytes[index * 4..].first_chunk().unwrap())
+        };
+        let value = word(3) as i32;
+
+        SyscallRecord {
+            program_counter: word(0),
+            nr: word(1) as i32,
+            result: match word(2) {
+                0 => Ok(value),
+                _ => Err(value),
+            },
+        }
+    }
+}
+
+/// Syscall history ring (empty = Disabled).
+#[derive(Debug, Default)]
+pub(crate) struct SyscallHistory<'a> {
+    /// Records (multiple of [`SYSCALL_RECORD_SIZE`]).
+    buffer: &'a mut [u8],
+    /// Next record to write.
+    next: usize,
+    /// Number of records written (up to the capacity).
+    len: usize,
+}
+
+impl<'a> SyscallHistory<'a> {
+    /// Create a new (empty) history.
+    ///
+    /// Arguments:
+    /// - `buffer`: Records buffer (from scratch memory).
+    pub(crate) fn new(buffer: &'a mut [u8]) -> Self {
+        SyscallHistory {
+            buffer,
+            next: 0,
+            len: 0,
+        }
+    }
+
+    /// Number of records the ring can hold.
+    pub(crate) fn capacity(&self) -> usize {
+        self.buffer.len() / SYSCALL_RECORD_SIZE
+    }
+
+    /// Record a syscall, overwriting the oldest record when full.
+    ///
+    /// Arguments:
+    /// - `record`: Handled syscall.
+    #[inline]
+    pub(crate) fn record(&mut self, record: SyscallRecord) {
+        let capacity = self.capacity();
+        if capacity == 0 {
+            return;
+        }
+
+        let offset = self.next * SYSCALL_RECORD_SIZE;
+        self.buffer[offset..offset + SYSCALL_RECORD_SIZE].copy_from_slice(&record.encode());
+        self.next = (self.next + 1) % capacity;
+        self.len = (self.len + 1).min(capacity);
+    }
+
+    /// Iterate over the records, oldest first.
+    fn iter(&self) -> impl Iterator<Item = SyscallRecord> + '_ {
+        let capacity = self.capacity();
+        let first = (self.next + capacity - self.len) % capacity.max(1);
+        (0..self.len).map(move |i| {
+            let offset = (first + i) % capacity * SYSCALL_RECORD_SIZE;
+            SyscallRecord::decode(&self.buffer[offset..offset + SYSCALL_RECORD_SIZE])
+        })
+    }
+}
+
+impl<M: Memory> Engine<'_, M> {
+    /// Record a syscall handled by the engine in the history.
+    ///
+    /// Arguments:
+    /// - `result`: Syscall result.
+    #[inline(always)]
+    pub(crate) fn record_syscall(&mut self, result: Result<i32, i32>) {
+        self.syscall_history.record(SyscallRecord {
+            program_counter: self.program_counter,
+            nr: self.registers.inner[Register::A7 as usize],
+            result,
+        });
+    }
+
+    /// Get the syscall history, oldest first (check the [module documentation](self)).
+    ///
+    /// Returns:
+    /// - `impl Iterator<Item = SyscallRecord>`: Recorded syscalls (empty without scratch memory).
+    pub fn syscall_history(&self) -> impl Iterator<Item = SyscallRecord> + '_ {
+        self.syscall_history.iter()
+    }
+
+    /// Clear the syscall history.
+    pub fn clear_syscall_history(&mut self) {
+        self.syscall_history.next = 0;
+        self.syscall_history.len = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring() {
+        let mut buffer = [0; 3 * SYSCALL_RECORD_SIZE];
+        let mut history = SyscallHistory::new(&mut buffer);
+        let record = |nr: i32| SyscallRecord {
+            program_counter: nr as u32 * 4,
+            nr,
+            result: if nr % 2 == 0 { Ok(-nr) } else { Err(nr) },
+        };
+
+        assert_eq!(history.iter().count(), 0);
+        for nr in 0..5 {
+            history.record(record(nr));
+        }
+
+        // Oldest records overwritten
+        let mut iter = history.iter();
+        assert_eq!(iter.next(), Some(record(2)));
+        assert_eq!(iter.next(), Some(record(3)));
+        assert_eq!(iter.next(), Some(record(4)));
+        assert_eq!(iter.next(), None);
+
+        // Disabled
+        let mut disabled = SyscallHistory::default();
+        disabled.record(record(1));
+        assert_eq!(disabled.iter().count(), 0);
+    }
+}
@@ -0,0 +1,129 @@
+//! Per-instance configuration blob, read by the guest through a syscall.
+//!
+//! The host provides a read-only blob ([`Engine::set_instance_blob`], ex.: device ID, feature flags or tenant
+//! configuration), and the guest copies it into its own memory with the syscall configured in
+//! [`super::Config::instance_blob_nr`]:
+//! - `a0`: Destination buffer address (in RAM).
+//! - `a1`: Destination buffer length in bytes.
+//! - `a2`: Offset inside the blob.
+//!
+//! Copies up to the buffer length (less if the blob ends first), and returns the total blob size in `a1`
+//! (call with an empty buffer to query it). Errors are returned in `a0`: [`Errno::InvalidPointer`] if the buffer
+//! isn't in RAM, [`Errno::InvalidArgument`] if the offset is past the end of the blob.
+//! The blob survives resets, so the guest can read it on every (re)start.
+//!
+//! ```
+//! use embive::{engine::{Config, Engine}, memory::{Memory, SliceMemory, RAM_OFFSET}};
+//!
+//! static BLOB: &[u8] = b"device-42";
+//!
+//! let code = &[
+//!     0x37, 0x05, 0x00, 0x80, // lui  a0, 0x80000 (RAM)
+//!     0x93, 0x05, 0x00, 0x01, // li   a1, 16
+//!     0x93, 0x08, 0x00, 0x03, // li   a7, 48 (Syscall nr)
+//!     0x73, 0x00, 0x00, 0x00, // ecall
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//! ];
+//! let mut ram = [0; 16];
+//! let mut memory = SliceMemory::new(code, &mut ram);
+//! let config = Config::default().with_instance_blob_nr(Some(48));
+//! let mut engine = Engine::new(&mut memory, config).unwrap();
+//! engine.set_instance_blob(BLOB);
+//!
+//! assert_eq!(engine.run(), Ok(false));
+//! assert_eq!(engine.registers.get(11), Ok(9)); // Blob size
+//! assert_eq!(engine.memory.load(RAM_OFFSET), Ok(*b"device-42"));
+//! ```
+
+use super::Engine;
+use crate::memory::Memory;
+use crate::syscall::{Arg, Errno, SyscallContract};
+
+/// Destination buffer of the instance blob syscall (`a0`, `a1`).
+const DESTINATION: SyscallContract =
+    SyscallContract::new(0, "instance_blob", &[Arg::BufferMut("buf")]);
+
+impl<'a, M: Memory> Engine<'a, M> {
+    /// Set the instance blob, read by the guest with the syscall configured in
+    /// [`super::Config::instance_blob_nr`]. Replaces any previous blob.
+    ///
+    /// Arguments:
+    /// - `blob`: Read-only instance configuration/identity data.
+    pub fn set_instance_blob(&mut self, blob: &'a [u8]) {
+        self.instance_blob = blob;
+    }
+
+    /// Get the instance blob (empty if not set).
+    pub fn instance_blob(&self) -> &'a [u8] {
+        self.instance_blob
+    }
+
+    /// Copy the instance blob into the guest memory (`a0`: address, `a1`: length, `a2`: offset).
+    ///
+    /// Returns:
+    /// - `Ok(i32)`: Total blob size.
+    /// - `Err(i32)`: Invalid buffer or offset ([`Errno`] code).
+    pub(crate) fn instance_blob_syscall(&mut self, args: &[i32]) -> Result<i32, i32> {
+        let [address, len, offset] = [args[0], args[1], args[2]].map(|arg| arg as u32);
+        if !DESTINATION.check(&[address as i32, len as i32, 0, 0, 0, 0, 0], self.memory) {
+            return Err(Errno::InvalidPointer.code());
+        }
+
+        let blob = self
+            .instance_blob
+            .get(offset as usize..)
+            .ok_or(Errno::InvalidArgument.code())?;
+        for (i, byte) in blob.iter().take(len as usize).enumerate() {
+            self.memory
+                .store(address.wrapping_add(i as u32), [*byte])
+                .map_err(|_| Errno::InvalidPointer.code())?;
+        }
+
+        Ok(self.instance_blob.len() as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{Config, Engine};
+    use crate::memory::{Memory, SliceMemory, RAM_OFFSET};
+    use crate::syscall::Errno;
+
+    const BLOB: &[u8] = &[1, 2, 3, 4, 5, 6];
+
+    #[test]
+    fn test_instance_blob_syscall() {
+        let mut ram = [0; 8];
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+        let ram = RAM_OFFSET as i32;
+
+        // No blob set
+        assert_eq!(engine.instance_blob_syscall(&[ram, 8, 0]), Ok(0));
+
+        engine.set_instance_blob(BLOB);
+        engine.reset();
+        assert_eq!(engine.instance_blob(), BLOB);
+
+        // Query size, partial reads
+        assert_eq!(engine.instance_blob_syscall(&[0, 0, 0]), Ok(6));
+        assert_eq!(engine.instance_blob_syscall(&[ram, 2, 0]), Ok(6));
+        assert_eq!(engine.instance_blob_syscall(&[ram + 2, 6, 4]), Ok(6));
+        assert_eq!(engine.instance_blob_syscall(&[ram + 4, 4, 6]), Ok(6));
+        assert_eq!(engine.memory.load(RAM_OFFSET), Ok([1, 2, 5, 6, 0, 0, 0, 0]));
+
+        // Invalid offset, buffer outside of RAM
+        assert_eq!(
+            engine.instance_blob_syscall(&[ram, 1, 7]),
+            Err(Errno::InvalidArgument.code())
+        );
+        assert_eq!(
+            engine.instance_blob_syscall(&[0, 4, 0]),
+            Err(Errno::InvalidPointer.code())
+        );
+        assert_eq!(
+            engine.instance_blob_syscall(&[ram + 4, 5, 0]),
+            Err(Errno::InvalidPointer.code())
+        );
+    }
+}
@@ -0,0 +1,246 @@
+//! Persistent RAM regions, preserved by [`Engine::warm_restart`].
+//!
+//! The rest of the RAM is scrubbed with the configured pattern ([`super::Config::ram_fill`], check [`super::RamFill`]).
+//!
+//! Regions are declared by the host ([`Engine::persist`], ex.: from container metadata) or by the guest,
+//! with the syscall configured in [`super::Config::persistent_region_nr`]:
+//! - `a0`: Region address (in RAM).
+//! - `a1`: Region size in bytes.
+//!
+//! Returns `0` in `a0` on success, or one of the `PERSISTENT_REGION_*` error codes.
+//! Declarations survive resets and warm restarts (declaring the same region again is a no-op),
+//! clear them with [`Engine::clear_persistent`] when loading a new guest.
+//!
+//! ```
+//! use embive::{engine::{Config, Engine}, memory::{Memory, SliceMemory, RAM_OFFSET}};
+//!
+//! let mut ram = [0xFF; 16];
+//! let mut memory = SliceMemory::new(&[], &mut ram);
+//! let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+//!
+//! // Keep a calibration table (first 4 bytes of RAM), scrub everything else
+//! engine.persist(RAM_OFFSET, 4).unwrap();
+//! engine.warm_restart(16).unwrap();
+//!
+//! assert_eq!(engine.memory.load(RAM_OFFSET + 2), Ok([0xFF, 0xFF, 0x00, 0x00]));
+//! ```
+
+use super::Engine;
+use crate::error::EmbiveError;
+use crate::memory::{Memory, RAM_OFFSET};
+use crate::syscall::Errno;
+
+/// Maximum number of persistent regions.
+pub const PERSISTENT_REGIONS: usize = 4;
+
+/// Persistent region syscall error: region is empty or outside of RAM.
+pub const PERSISTENT_REGION_INVALID: i32 = Errno::InvalidPointer.code();
+
+/// Persistent region syscall error: too many persistent regions.
+pub const PERSISTENT_REGION_FULL: i32 = Errno::QuotaExceeded.code();
+
+/// Declared persistent regions.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct PersistentRegions {
+    /// Regions (start, end), end exclusive.
+    regions: [(u32, u32); PERSISTENT_REGIONS],
+    /// Declared regions count.
+    len: usize,
+}
+
+impl PersistentRegions {
+    /// Declared regions (start, end), end exclusive.
+    fn regions(&self) -> &[(u32, u32)] {
+        &self.regions[..self.len]
+    }
+
+    /// End of the persistent region containing an address (if any).
+    fn containing(&self, address: u32) -> Option<u32> {
+        self.regions()
+            .iter()
+            .filter(|(start, end)| (*start..*end).contains(&address))
+            .map(|(_, end)| *end)
+            .max()
+    }
+
+    /// Start of the first persistent region after an address (if any).
+    fn next(&self, address: u32) -> Option<u32> {
+        self.regions()
+            .iter()
+            .map(|(start, _)| *start)
+            .filter(|start| *start > address)
+            .min()
+    }
+}
+
+impl<M: Memory> Engine<'_, M> {
+    /// Declare a persistent RAM region, preserved by [`Engine::warm_restart`].
+    ///
+    /// Arguments:
+    /// - `address`: Region address (in RAM).
+    /// - `size`: Region size in bytes.
+    ///
+    /// Returns:
+    /// - `Ok(())`: Region declared (or already declared).
+    /// - `Err(EmbiveError)`: Failed to declare the region.
+    ///     - Region is empty or outside of RAM ([`EmbiveError::InvalidMemoryAddress`]).
+    ///     - Too many persistent regions ([`EmbiveError::TooManyPersistentRegions`]).
+    pub fn persist(&mut self, address: u32, size: u32) -> Result<(), EmbiveError> {
+        let end = address
+            .checked_add(size)
+            .filter(|_| address >= RAM_OFFSET && size > 0)
+            .ok_or(EmbiveError::InvalidMemoryAddress)?;
+
+        // Last byte of the region must be inside RAM
+        self.memory.load::<1>(end - 1)?;
+
+        let persistent = &mut self.persistent;
+        if persistent.regions().contains(&(address, end)) {
+            return Ok(());
+        }
+
+        if persistent.len == PERSISTENT_REGIONS {
+            return Err(EmbiveError::TooManyPersistentRegions);
+        }
+
+        persistent.regions[persistent.len] = (address, end);
+        persistent.len += 1;
+        Ok(())
+    }
+
+    /// Clear all persistent region declarations.
+    pub fn clear_persistent(&mut self) {
+        self.persistent = PersistentRegions::default();
+    }
+
+    /// Warm restart the engine: reset it ([`Engine::reset`]) and scrub the RAM with the configured pattern
+    /// ([`super::Config::ram_fill`]), except for the persistent regions ([`Engine::persist`]).
+    ///
+    /// Arguments:
+    /// - `ram_size`: RAM size in bytes (scrubbed from [`RAM_OFFSET`]).
+    ///
+    /// Returns:
+    /// - `Ok(())`: Engine restarted.
+    /// - `Err(EmbiveError)`: Failed to scrub the RAM (ex.: RAM is smaller than `ram_size`).
+    pub fn warm_restart(&mut self, ram_size: u32) -> Result<(), EmbiveError> {
+        self.reset();
+
+        let end = RAM_OFFSET.saturating_add(ram_size);
+        let mut address = RAM_OFFSET;
+        while address < end {
+            if let Some(region_end) = self.persistent.containing(address) {
+                // Skip the persistent region
+                address = region_end;
+                continue;
+            }
+
+            let gap_end = self
+                .persistent
+                .next(address)
+                .map_or(end, |next| next.min(end));
+            self.scrub(address, gap_end)?;
+            address = gap_end;
+        }
+
+        Ok(())
+    }
+
+    /// Declare a persistent region requested by the guest (`a0`: address, `a1`: size).
+    ///
+    /// Returns:
+    /// - `Ok(i32)`: Region declared (0).
+    /// - `Err(i32)`: Invalid region or too many regions (`PERSISTENT_REGION_*` error codes).
+    pub(crate) fn persist_syscall(&mut self, address: u32, size: u32) -> Result<i32, i32> {
+        match self.persist(address, size) {
+            Ok(()) => Ok(0),
+            Err(EmbiveError::TooManyPersistentRegions) => Err(PERSISTENT_REGION_FULL),
+            Err(_) => Err(PERSISTENT_REGION_INVALID),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Config, RamFill};
+    use crate::memory::SliceMemory;
+
+    #[test]
+    fn test_warm_restart() {
+        let mut ram = [0xAA; 32];
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+        engine.program_counter = 0x10;
+
+        // Overlapping and unaligned regions
+        engine.persist(RAM_OFFSET + 3, 6).unwrap();
+        engine.persist(RAM_OFFSET + 5, 2).unwrap();
+        engine.persist(RAM_OFFSET + 30, 2).unwrap();
+        engine.persist(RAM_OFFSET + 30, 2).unwrap();
+        assert_eq!(engine.persistent.len, 3);
+
+        engine.warm_restart(32).unwrap();
+        assert_eq!(engine.program_counter, 0);
+
+        let mut expected = [0; 32];
+        expected[3..9].fill(0xAA);
+        expected[30..].fill(0xAA);
+        assert_eq!(ram, expected);
+    }
+
+    #[test]
+    fn test_warm_restart_fill() {
+        let mut ram = [0; 16];
+        let fill = RamFill::Random(42);
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut engine = Engine::new(&mut memory, Config::default().with_ram_fill(fill)).unwrap();
+        engine.persist(RAM_OFFSET + 1, 2).unwrap();
+        engine.fill_ram(16).unwrap();
+        engine.memory.store(RAM_OFFSET + 1, [0xAA; 2]).unwrap();
+        engine.warm_restart(16).unwrap();
+
+        // Unaligned scrubs match the aligned fill
+        let mut expected = [0; 16];
+        for (i, chunk) in expected.chunks_exact_mut(4).enumerate() {
+            chunk.copy_from_slice(&fill.word(RAM_OFFSET + i as u32 * 4));
+        }
+        expected[1..3].fill(0xAA);
+        assert_eq!(ram, expected);
+    }
+
+    #[test]
+    fn test_persist_invalid() {
+        let mut ram = [0; 8];
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+
+        assert_eq!(engine.persist(0, 4), Err(EmbiveError::InvalidMemoryAddress));
+        assert_eq!(
+            engine.persist(RAM_OFFSET, 0),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            engine.persist(RAM_OFFSET + 4, 5),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            engine.persist_syscall(u32::MAX, 2),
+            Err(PERSISTENT_REGION_INVALID)
+        );
+
+        for i in 0..PERSISTENT_REGIONS as u32 {
+            assert_eq!(engine.persist_syscall(RAM_OFFSET + i, 1), Ok(0));
+        }
+        assert_eq!(
+            engine.persist_syscall(RAM_OFFSET + 7, 1),
+            Err(PERSISTENT_REGION_FULL)
+        );
+
+        engine.clear_persistent();
+        assert_eq!(engine.persist(RAM_OFFSET + 7, 1), Ok(()));
+        assert_eq!(
+            engine.warm_restart(16),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! Retire hooks, host functions called after guest instructions in a program counter range are retired.
+//!
+//! Registered with [`super::Config::retire_hooks`], each hook covers an address range (ex.: one guest function),
+//! so a single routine can be traced while the rest of the guest runs at full speed: without hooks, the engine
+//! only checks that the list is empty. Instructions that fail or trap (check [`crate::trap`]) aren't retired.
+//!
+//! ```
+//! use core::sync::atomic::{AtomicU32, Ordering};
+//! use embive::{
+//!     engine::{Config, Engine, RetireHook},
+//!     memory::SliceMemory,
+//!     register::Registers,
+//! };
+//!
+//! static RETIRED: AtomicU32 = AtomicU32::new(0);
+//!
+//! fn trace(program_counter: u32, _data: u32, registers: &Registers) {
+//!     assert!((0x0c..0x14).contains(&program_counter));
+//!     assert_eq!(registers.get(10), Ok(2));
+//!     RETIRED.fetch_add(1, Ordering::Relaxed);
+//! }
+//!
+//! static HOOKS: [RetireHook; 1] = [RetireHook::new(0x0c, 0x14, trace)];
+//!
+//! let code = &[
+//!     0x13, 0x05, 0x10, 0x00, // li   a0, 1
+//!     0xef, 0x00, 0x80, 0x00, // jal  ra, 0x0c (call)
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//!     // Traced function
+//!     0x13, 0x05, 0x15, 0x00, // addi a0, a0, 1
+//!     0x67, 0x80, 0x00, 0x00, // ret
+//! ];
+//! let mut memory = SliceMemory::new(code, &mut []);
+//! let config = Config::default().with_retire_hooks(&HOOKS);
+//! let mut engine = Engine::new(&mut memory, config).unwrap();
+//!
+//! assert_eq!(engine.run(), Ok(false));
+//! assert_eq!(RETIRED.load(Ordering::Relaxed), 2);
+//! ```
+
+use crate::register::Registers;
+
+/// Retire hook function signature
+///
+/// Called after an instruction inside the hook range is retired.
+///
+/// Arguments:
+/// - `program_counter`: Address of the retired instruction.
+/// - `data`: Retired instruction (raw, compressed instructions in the lower 16 bits).
+/// - `registers`: CPU registers, after the instruction.
+pub type RetireFn = fn(program_counter: u32, data: u32, registers: &Registers);
+
+/// Retire hook, called for the instructions in a program counter range (check the [module documentation](self)).
+#[derive(Debug, Clone, Copy)]
+pub struct RetireHook {
+    /// Range start address (inclusive).
+    pub start: u32,
+    /// Range end address (exclusive).
+    pub end: u32,
+    /// Hook function.
+    pub hook: RetireFn,
+}
+
+impl RetireHook {
+    /// Create a new retire hook.
+    ///
+    /// Arguments:
+    /// - `start`: Range start address (inclusive).
+    /// - `end`: Range end address (exclusive).
+    /// - `hook`: Hook function.
+    pub const fn new(start: u32, end: u32, hook: RetireFn) -> Self {
+        RetireHook { start, end, hook }
+    }
+
+    /// Check if an instruction address is inside the hook range.
+    ///
+    /// Arguments:
+    /// - `program_counter`: Instruction address.
+    #[inline(always)]
+    pub const fn contains(&self, program_counter: u32) -> bool {
+        program_counter >= self.start && program_counter < self.end
+    }
+}
+
+/// Call the hooks covering a retired instruction.
+///
+/// Arguments:
+/// - `hooks`: Retire hooks.
+/// - `program_counter`: Address of the retired instruction.
+/// - `data`: Retired instruction (raw).
+/// - `registers`: CPU registers, after the instruction.
+#[cold]
+pub(crate) fn retired(
+    hooks: &[RetireHook],
+    program_counter: u32,
+    data: u32,
+    registers: &Registers,
+) {
+    for hook in hooks.iter().filter(|hook| hook.contains(program_counter)) {
+        (hook.hook)(program_counter, data, registers);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Config, Engine};
+    use crate::error::EmbiveError;
+    use crate::memory::SliceMemory;
+    use core::cell::RefCell;
+    use std::vec::Vec;
+
+    std::thread_local! {
+        static RETIRED: RefCell<Vec<(u32, u32)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn record(program_counter: u32, data: u32, _registers: &Registers) {
+        RETIRED.with(|retired| retired.borrow_mut().push((program_counter, data)));
+    }
+
+    static HOOKS: [RetireHook; 2] = [
+        RetireHook::new(0x4, 0x8, record),
+        RetireHook::new(0x4, 0x10, record),
+    ];
+
+    #[test]
+    fn test_ranges() {
+        let code = &[
+            0x13, 0x05, 0x10, 0x00, // li   a0, 1
+            0x13, 0x05, 0x15, 0x00, // addi a0, a0, 1
+            0xff, 0xff, 0xff, 0xff, // (illegal)
+        ];
+        let mut memory = SliceMemory::new(code, &mut []);
+        let config = Config::default().with_retire_hooks(&HOOKS);
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        assert_eq!(
+            engine.run(),
+            Err(EmbiveError::IllegalInstruction {
+                pc: 0x8,
+                raw: 0xffffffff
+            })
+        );
+        assert_eq!(
+            RETIRED.with(|retired| retired.take()),
+            [(0x4, 0x0015_0513), (0x4, 0x0015_0513)]
+        );
+    }
+}
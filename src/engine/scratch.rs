@@ -0,0 +1,158 @@
+//! Scratch memory budget, buffers of the engine subsystems allocated from host-supplied scratch memory
+//! (check [`ScratchMemory`]).
+//!
+//! Subsystems using scratch memory:
+//! - Syscall history ([`super::Config::syscall_history`], check [`Engine::syscall_history`]).
+//! - Telemetry ring (`Config::telemetry`, if the `telemetry` feature is enabled, check `Engine::telemetry`).
+//!
+//! Size the scratch memory with [`Engine::required_scratch`] and give it to the engine with
+//! [`Engine::set_scratch`]. Running an engine without enough scratch memory for its configuration fails
+//! with [`EmbiveError::ScratchTooSmall`], instead of silently disabling the subsystems.
+//!
+//! ```
+//! use embive::{engine::{Config, Engine}, error::EmbiveError, memory::{ScratchMemory, SliceMemory}};
+//!
+//! let config = Config::default().with_syscall_history(8);
+//! let mut pool = [0; 128];
+//! assert!(Engine::<SliceMemory>::required_scratch(&config) <= pool.len());
+//!
+//! let code = &[0x73, 0x00, 0x10, 0x00]; // ebreak
+//! let mut memory = SliceMemory::new(code, &mut []);
+//! let mut engine = Engine::new(&mut memory, config).unwrap();
+//! assert_eq!(engine.run(), Err(EmbiveError::ScratchTooSmall));
+//!
+//! let mut scratch = ScratchMemory::new(&mut pool);
+//! engine.set_scratch(&mut scratch).unwrap();
+//! assert_eq!(engine.run(), Ok(false));
+//! ```
+
+use super::history::{SyscallHistory, SYSCALL_RECORD_SIZE};
+#[cfg(feature = "telemetry")]
+use super::telemetry::{Telemetry, TELEMETRY_RECORD_SIZE};
+use super::{Config, Engine};
+use crate::error::EmbiveError;
+use crate::memory::{Memory, ScratchMemory};
+
+impl<'a, M: Memory> Engine<'a, M> {
+    /// Get the scratch memory required by a configuration (check the [module documentation](self)).
+    ///
+    /// Arguments:
+    /// - `config`: Engine configuration.
+    ///
+    /// Returns:
+    /// - `usize`: Scratch memory size in bytes (0 = Not needed).
+    pub fn required_scratch(config: &Config<M>) -> usize {
+        let size = Self::syscall_history_size(config);
+        #[cfg(feature = "telemetry")]
+        let size = size.saturating_add(Self::telemetry_size(config));
+        size
+    }
+
+    /// Get the syscall history size of a configuration.
+    ///
+    /// Arguments:
+    /// - `config`: Engine configuration.
+    fn syscall_history_size(config: &Config<M>) -> usize {
+        (config.syscall_history as usize).saturating_mul(SYSCALL_RECORD_SIZE)
+    }
+
+    /// Get the telemetry ring size of a configuration.
+    ///
+    /// Arguments:
+    /// - `config`: Engine configuration.
+    #[cfg(feature = "telemetry")]
+    fn telemetry_size(config: &Config<M>) -> usize {
+        (config.telemetry as usize).saturating_mul(TELEMETRY_RECORD_SIZE)
+    }
+
+    /// Give scratch memory to the engine, allocating the buffers of the configured subsystems
+    /// (check the [module documentation](self)). Replaces (and clears) the previous buffers.
+    ///
+    /// Arguments:
+    /// - `scratch`: Scratch memory, at least [`Engine::required_scratch`] bytes left.
+    ///
+    /// Returns:
+    /// - `Ok(())`: Buffers allocated.
+    /// - `Err(EmbiveError)`: Not enough scratch memory ([`EmbiveError::ScratchTooSmall`]), nothing was allocated.
+    pub fn set_scratch(&mut self, scratch: &mut ScratchMemory<'a>) -> Result<(), EmbiveError> {
+        if scratch.remaining() < Self::required_scratch(&self.config) {
+            return Err(EmbiveError::ScratchTooSmall);
+        }
+
+        self.syscall_history =
+            SyscallHistory::new(scratch.alloc(Self::syscall_history_size(&self.config))?);
+        #[cfg(feature = "telemetry")]
+        {
+            self.telemetry = Telemetry::new(scratch.alloc(Self::telemetry_size(&self.config))?);
+        }
+
+        Ok(())
+    }
+
+    /// Check that the subsystem buffers fit the configuration.
+    ///
+    /// Returns:
+    /// - `Ok(())`: Enough scratch memory.
+    /// - `Err(EmbiveError)`: Not enough scratch memory was given ([`EmbiveError::ScratchTooSmall`]).
+    #[inline]
+    pub(crate) fn check_scratch(&self) -> Result<(), EmbiveError> {
+        if self.syscall_history.capacity() < self.config.syscall_history as usize {
+            return Err(EmbiveError::ScratchTooSmall);
+        }
+
+        #[cfg(feature = "telemetry")]
+        if self.telemetry.capacity() < self.config.telemetry as usize {
+            return Err(EmbiveError::ScratchTooSmall);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::SliceMemory;
+
+    #[test]
+    fn test_required_scratch() {
+        let config = Config::default();
+        assert_eq!(Engine::<SliceMemory>::required_scratch(&config), 0);
+
+        let config = Config::default().with_syscall_history(3);
+        assert_eq!(
+            Engine::<SliceMemory>::required_scratch(&config),
+            3 * SYSCALL_RECORD_SIZE
+        );
+        #[cfg(feature = "telemetry")]
+        assert_eq!(
+            Engine::<SliceMemory>::required_scratch(
+                &Config::default()
+                    .with_syscall_history(3)
+                    .with_telemetry(2, 100)
+            ),
+            3 * SYSCALL_RECORD_SIZE + 2 * TELEMETRY_RECORD_SIZE
+        );
+
+        // Fails fast, nothing allocated
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+        let mut small = [0; 3 * SYSCALL_RECORD_SIZE - 1];
+        let mut scratch = ScratchMemory::new(&mut small);
+        assert_eq!(
+            engine.set_scratch(&mut scratch),
+            Err(EmbiveError::ScratchTooSmall)
+        );
+        assert_eq!(scratch.remaining(), 3 * SYSCALL_RECORD_SIZE - 1);
+        assert_eq!(engine.check_scratch(), Err(EmbiveError::ScratchTooSmall));
+
+        let mut pool = [0; 3 * SYSCALL_RECORD_SIZE];
+        let mut scratch = ScratchMemory::new(&mut pool);
+        assert_eq!(engine.set_scratch(&mut scratch), Ok(()));
+        assert_eq!(engine.check_scratch(), Ok(()));
+
+        // Configuration changed after allocating
+        engine.config.syscall_history = 4;
+        assert_eq!(engine.check_scratch(), Err(EmbiveError::ScratchTooSmall));
+    }
+}
@@ -0,0 +1,421 @@
+//! Guest snapshots, saved to and restored from a host-provided buffer.
+//!
+//! A snapshot captures the guest state ([`EngineState`]): program counter, registers (and vector registers, if the
+//! `v_extension` feature is enabled), the program break (if the `libc_support` feature is enabled) and the RAM
+//! contents, exported by the memory ([`Memory::export_ram`]). Hosts can store it (ex.: in flash) and resume the
+//! guest deterministically after a reboot, on the same code.
+//!
+//! Host-side state is not captured: timers, interrupts, capabilities, persistent regions, accounting and the
+//! debugger are reset by [`Engine::restore`], re-establish them after restoring.
+//! Take snapshots at a safepoint (`Engine::at_safepoint`, with the `safepoint` feature) to resume from a consistent guest state,
+//! a suspended guest retries its blocking syscall when restored.
+//!
+//! Layout (little-endian):
+//! - Header: [`SNAPSHOT_MAGIC`], [`SNAPSHOT_VERSION`] (`u8`), enabled features (`u8`) and 2 reserved bytes.
+//! - Program counter and registers `x0` to `x31` (`u32` each).
+//! - Vector registers (`vl`, `vtype` and the register file), if the `v_extension` feature is enabled.
+//! - Program break (`u32`), if the `libc_support` feature is enabled.
+//! - RAM size (`u32`), followed by the RAM contents.
+//!
+//! ```
+//! use embive::{engine::{Config, Engine}, memory::SliceMemory};
+//!
+//! let code = &[
+//!     0x37, 0x05, 0x00, 0x80, // lui  a0, 0x80000 (RAM)
+//!     0x93, 0x05, 0x20, 0x00, // li   a1, 2
+//!     0x23, 0x00, 0xb5, 0x00, // sb   a1, 0(a0)
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//! ];
+//! let mut ram = [0; 4];
+//! let mut memory = SliceMemory::new(code, &mut ram);
+//! let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+//! engine.step().unwrap();
+//!
+//! let mut buffer = [0; 1024];
+//! let len = engine.snapshot(&mut buffer).unwrap();
+//! assert_eq!(engine.snapshot_size(), Some(len));
+//!
+//! // After a reboot
+//! let mut ram = [0; 4];
+//! let mut memory = SliceMemory::new(code, &mut ram);
+//! let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+//! engine.restore(&buffer[..len]).unwrap();
+//! assert_eq!(engine.run(), Ok(false));
+//! assert_eq!(ram, [2, 0, 0, 0]);
+//! ```
+
+use super::Engine;
+use crate::error::EmbiveError;
+use crate::memory::{Memory, RAM_OFFSET};
+use crate::register::{Registers, REGISTER_COUNT};
+#[cfg(feature = "v_extension")]
+use crate::register::{VectorRegisters, VLENB};
+
+/// Snapshot magic number.
+pub const SNAPSHOT_MAGIC: [u8; 4] = *b"EMBS";
+
+/// Snapshot layout version.
+pub const SNAPSHOT_VERSION: u8 = 1;
+
+/// Snapshot feature flag: vector registers.
+const FLAG_VECTOR: u8 = 1 << 0;
+/// Snapshot feature flag: program break.
+const FLAG_LIBC: u8 = 1 << 1;
+
+/// Features captured by this build.
+const FLAGS: u8 = if cfg!(feature = "v_extension") {
+    FLAG_VECTOR
+} else {
+    0
+} | if cfg!(feature = "libc_support") {
+    FLAG_LIBC
+} else {
+    0
+};
+
+/// Header size in bytes (magic, version, flags and reserved bytes).
+const HEADER_SIZE: usize = 8;
+
+/// Guest state size in bytes, without the RAM contents (after the header).
+const STATE_SIZE: usize = 4
+    + REGISTER_COUNT * 4
+    + if cfg!(feature = "v_extension") {
+        8 + VECTOR_SIZE
+    } else {
+        0
+    }
+    + if cfg!(feature = "libc_support") { 4 } else { 0 }
+    + 4;
+
+/// Vector register file size in bytes.
+#[cfg(feature = "v_extension")]
+const VECTOR_SIZE: usize = VLENB * REGISTER_COUNT;
+/// Vector register file size in bytes.
+#[cfg(not(feature = "v_extension"))]
+const VECTOR_SIZE: usize = 0;
+
+/// Guest state, independent of the configuration and the memory: what a snapshot captures besides the RAM
+/// (check the [module documentation](self)). Serializable with any serde format with the `serde` feature.
+///
+/// ```
+/// use embive::{engine::{Config, Engine}, memory::SliceMemory};
+///
+/// let mut memory = SliceMemory::new(&[], &mut []);
+/// let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+/// engine.program_counter = 8;
+/// *engine.registers.get_mut(10).unwrap() = 42;
+/// let state = engine.state();
+///
+/// engine.reset();
+/// engine.set_state(&state);
+/// assert_eq!(engine.program_counter, 8);
+/// assert_eq!(engine.registers.get(10), Ok(42));
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct EngineState {
+    /// Program counter.
+    pub program_counter: u32,
+    /// CPU registers.
+    pub registers: Registers,
+    /// Vector registers.
+    #[cfg(feature = "v_extension")]
+    pub vector: VectorRegisters,
+    /// Guest program break (check [`crate::libc`]).
+    #[cfg(feature = "libc_support")]
+    pub program_break: u32,
+}
+
+/// Sequential snapshot writer.
+struct Writer<'b> {
+    /// Remaining buffer.
+    buffer: &'b mut [u8],
+}
+
+impl Writer<'_> {
+    /// Write bytes (the buffer size is checked beforehand).
+    fn bytes(&mut self, data: &[u8]) {
+        let (head, tail) = core::mem::take(&mut self.buffer).split_at_mut(data.len());
+        head.copy_from_slice(data);
+        self.buffer = tail;
+    }
+
+    /// Write a word.
+    fn word(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+}
+
+/// Sequential snapshot reader.
+struct Reader<'b> {
+    /// Remaining snapshot.
+    snapshot: &'b [u8],
+}
+
+impl<'b> Reader<'b> {
+    /// Read bytes.
+    ///
+    /// Returns:
+    /// - `Ok(&[u8])`: Bytes read.
+    /// - `Err(EmbiveError)`: Snapshot is truncated ([`EmbiveError::InvalidSnapshot`]).
+    fn bytes(&mut self, len: usize) -> Result<&'b [u8], EmbiveError> {
+        let (head, tail) = self
+            .snapshot
+            .split_at_checked(len)
+            .ok_or(EmbiveError::InvalidSnapshot)?;
+        self.snapshot = tail;
+        Ok(head)
+    }
+
+    /// Read a word.
+    fn word(&mut self) -> Result<u32, EmbiveError> {
+        let bytes = self.bytes(4)?;
+        // Unwrap is safe because 4 bytes were read.
+        Ok(u32::from_le_bytes(*bytes.first_chunk().unwrap()))
+    }
+}
+
+impl<M: Memory> Engine<'_, M> {
+    /// Get the guest state (check [`EngineState`]).
+    pub fn state(&self) -> EngineState {
+        EngineState {
+            program_counter: self.program_counter,
+            registers: self.registers,
+            #[cfg(feature = "v_extension")]
+            vector: self.vector,
+            #[cfg(feature = "libc_support")]
+            program_break: self.program_break,
+        }
+    }
+
+    /// Set the guest state (check [`EngineState`]). The rest of the engine is not modified.
+    ///
+    /// Arguments:
+    /// - `state`: Guest state, from [`Engine::state`].
+    pub fn set_state(&mut self, state: &EngineState) {
+        self.program_counter = state.program_counter;
+        self.registers = state.registers;
+        #[cfg(feature = "v_extension")]
+        {
+            self.vector = state.vector;
+        }
+        #[cfg(feature = "libc_support")]
+        {
+            self.program_break = state.program_break;
+        }
+    }
+
+    /// Get the size of a snapshot of the guest (check the [module documentation](self)).
+    ///
+    /// Returns:
+    /// - `Some(usize)`: Snapshot size in bytes.
+    /// - `None`: The memory doesn't export its RAM ([`Memory::export_ram`]).
+    pub fn snapshot_size(&self) -> Option<usize> {
+        let ram = self.memory.export_ram()?;
+        Some(HEADER_SIZE + STATE_SIZE + ram.len())
+    }
+
+    /// Save a snapshot of the guest (check the [module documentation](self)).
+    ///
+    /// Arguments:
+    /// - `buffer`: Host buffer, at least [`Engine::snapshot_size`] bytes.
+    ///
+    /// Returns:
+    /// - `Ok(usize)`: Snapshot size in bytes, written to the start of the buffer.
+    /// - `Err(EmbiveError)`: The buffer is too small, or the memory doesn't export its RAM
+    ///   ([`EmbiveError::InvalidSnapshot`]).
+    pub fn snapshot(&self, buffer: &mut [u8]) -> Result<usize, EmbiveError> {
+        let ram = self
+            .memory
+            .export_ram()
+            .ok_or(EmbiveError::InvalidSnapshot)?;
+        let size = HEADER_SIZE + STATE_SIZE + ram.len();
+        let ram_size = u32::try_from(ram.len()).map_err(|_| EmbiveError::InvalidSnapshot)?;
+        let buffer = buffer.get_mut(..size).ok_or(EmbiveError::InvalidSnapshot)?;
+
+        let state = self.state();
+        let mut writer = Writer { buffer };
+        writer.bytes(&SNAPSHOT_MAGIC);
+        writer.bytes(&[SNAPSHOT_VERSION, FLAGS, 0, 0]);
+        writer.word(state.program_counter);
+        for register in state.registers.inner {
+            writer.word(register as u32);
+        }
+        #[cfg(feature = "v_extension")]
+        {
+            writer.word(state.vector.vl);
+            writer.word(state.vector.vtype);
+            writer.bytes(&state.vector.inner);
+        }
+        #[cfg(feature = "libc_support")]
+        writer.word(state.program_break);
+        writer.word(ram_size);
+        writer.bytes(ram);
+
+        Ok(size)
+    }
+
+    /// Restore a snapshot of the guest (check the [module documentation](self)).
+    /// The engine is reset ([`Engine::reset`]), then the guest state is restored and the RAM is written
+    /// through [`Memory::store_bytes`] (from [`RAM_OFFSET`]).
+    ///
+    /// Arguments:
+    /// - `snapshot`: Snapshot saved by [`Engine::snapshot`], on a build with the same features.
+    ///
+    /// Returns:
+    /// - `Ok(())`: Snapshot restored.
+    /// - `Err(EmbiveError)`: Failed to restore the snapshot, the engine is not modified.
+    ///     - The snapshot is malformed, or was saved with different features ([`EmbiveError::InvalidSnapshot`]).
+    ///     - The RAM is too small (the RAM may be partially written, [`EmbiveError::InvalidMemoryAddress`]).
+    pub fn restore(&mut self, snapshot: &[u8]) -> Result<(), EmbiveError> {
+        let mut reader = Reader { snapshot };
+        if reader.bytes(4)? != SNAPSHOT_MAGIC || reader.bytes(4)? != [SNAPSHOT_VERSION, FLAGS, 0, 0]
+        {
+            return Err(EmbiveError::InvalidSnapshot);
+        }
+
+        let mut state = self.state();
+        state.program_counter = reader.word()?;
+        for register in &mut state.registers.inner {
+            *register = reader.word()? as i32;
+        }
+        #[cfg(feature = "v_extension")]
+        {
+            state.vector.vl = reader.word()?;
+            state.vector.vtype = reader.word()?;
+            state
+                .vector
+                .inner
+                .copy_from_slice(reader.bytes(VECTOR_SIZE)?);
+        }
+        #[cfg(feature = "libc_support")]
+        {
+            state.program_break = reader.word()?;
+        }
+        let ram_size = reader.word()?;
+        let ram = reader.bytes(ram_size as usize)?;
+        if !reader.snapshot.is_empty() || ram_size > u32::MAX - RAM_OFFSET + 1 {
+            return Err(EmbiveError::InvalidSnapshot);
+        }
+
+        self.memory.store_bytes(RAM_OFFSET, ram)?;
+
+        self.reset();
+        self.set_state(&state);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Config;
+    use crate::memory::{HashedMemory, SliceMemory};
+
+    const CODE: &[u8] = &[
+        0x37, 0x05, 0x00, 0x80, // lui  a0, 0x80000 (RAM)
+        0x93, 0x05, 0x20, 0x00, // li   a1, 2
+        0xa3, 0x02, 0xb5, 0x00, // sb   a1, 5(a0)
+        0x93, 0x85, 0x15, 0x00, // addi a1, a1, 1
+        0x73, 0x00, 0x10, 0x00, // ebreak
+    ];
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut ram = [0; 6];
+        let mut memory = SliceMemory::new(CODE, &mut ram);
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+        for _ in 0..3 {
+            engine.step().unwrap();
+        }
+
+        let size = engine.snapshot_size().unwrap();
+        assert_eq!(size, HEADER_SIZE + STATE_SIZE + 6);
+        let mut buffer = [0; 1024];
+        assert_eq!(
+            engine.snapshot(&mut buffer[..size - 1]),
+            Err(EmbiveError::InvalidSnapshot)
+        );
+        assert_eq!(engine.snapshot(&mut buffer), Ok(size));
+        let snapshot = &buffer[..size];
+
+        // Restore (hash is updated by the RAM stores)
+        let mut ram = [0xFF; 6];
+        let mut memory = HashedMemory::new(SliceMemory::new(CODE, &mut ram));
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+        assert_eq!(engine.restore(snapshot), Ok(()));
+        assert_eq!(engine.program_counter, 12);
+        assert_ne!(engine.memory.memory_hash(), 0);
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(11), Ok(3));
+        assert_eq!(engine.memory.load(RAM_OFFSET + 4), Ok([0, 2]));
+    }
+
+    #[test]
+    fn test_restore_invalid() {
+        let mut ram = [0; 6];
+        let mut memory = SliceMemory::new(CODE, &mut ram);
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+        engine.step().unwrap();
+        let mut buffer = [0; 1024];
+        let size = engine.snapshot(&mut buffer).unwrap();
+
+        // Truncated, trailing data and bad version
+        assert_eq!(
+            engine.restore(&buffer[..size - 1]),
+            Err(EmbiveError::InvalidSnapshot)
+        );
+        assert_eq!(
+            engine.restore(&buffer[..size + 1]),
+            Err(EmbiveError::InvalidSnapshot)
+        );
+        buffer[4] = SNAPSHOT_VERSION + 1;
+        assert_eq!(
+            engine.restore(&buffer[..size]),
+            Err(EmbiveError::InvalidSnapshot)
+        );
+        assert_eq!(engine.program_counter, 4);
+
+        // RAM is too small
+        buffer[4] = SNAPSHOT_VERSION;
+        let mut ram = [0; 4];
+        let mut memory = SliceMemory::new(CODE, &mut ram);
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+        assert_eq!(
+            engine.restore(&buffer[..size]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(engine.program_counter, 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_state_serde() {
+        let mut ram = [0; 6];
+        let mut restored_ram = [0; 6];
+        {
+            let mut memory = SliceMemory::new(CODE, &mut ram);
+            let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+            for _ in 0..2 {
+                engine.step().unwrap();
+            }
+
+            let state = engine.state();
+            let serialized = serde_json::to_string(&state).unwrap();
+            let deserialized: EngineState = serde_json::from_str(&serialized).unwrap();
+            assert_eq!(deserialized, state);
+
+            // Same execution from the deserialized state
+            let mut memory = SliceMemory::new(CODE, &mut restored_ram);
+            let mut restored = Engine::new(&mut memory, Config::default()).unwrap();
+            restored.set_state(&deserialized);
+            assert_eq!(restored.run(), Ok(false));
+            assert_eq!(engine.run(), Ok(false));
+            assert_eq!(restored.state(), engine.state());
+            assert_eq!(restored.registers.get(11), Ok(3));
+        }
+        assert_eq!(restored_ram, ram);
+    }
+}
@@ -0,0 +1,162 @@
+//! Static Engine Module
+//!
+//! Helpers for placing the engine state and guest RAM in static memory (`.bss`),
+//! avoiding stack usage spikes during construction and keeping the memory layout
+//! visible in the linker map file.
+//!
+//! The storage is handed over as a `&'static mut` reference, obtained once from a safe
+//! platform primitive (ex.: `cortex_m::singleton!`, `static_cell::StaticCell`, a RTIC local resource,
+//! or `Box::leak` on hosted targets).
+//!
+//! ```
+//! use embive::{
+//!     engine::{Config, StaticEngine, StaticRam},
+//!     memory::ArrayMemory,
+//! };
+//!
+//! static CODE: [u8; 4] = [0x73, 0x00, 0x10, 0x00]; // ebreak (Halt)
+//!
+//! // Static storage, `Box::leak` stands in for the platform primitive
+//! let ram: &'static mut StaticRam<1024> = Box::leak(Box::new(StaticRam::new()));
+//! let storage: &'static mut StaticEngine<ArrayMemory<'static, 4, 1024>> =
+//!     Box::leak(Box::new(StaticEngine::new()));
+//!
+//! let engine = storage
+//!     .init(ArrayMemory::new(&CODE, ram.buffer()), Config::default())
+//!     .unwrap();
+//!
+//! assert_eq!(engine.run(), Ok(false));
+//! ```
+
+use core::mem::MaybeUninit;
+
+use super::{Config, Engine};
+use crate::error::EmbiveError;
+use crate::memory::Memory;
+
+/// Statically allocated guest RAM.
+///
+/// Zero-initialized, so it is placed in `.bss` (no flash usage, no copy at startup).
+pub struct StaticRam<const N: usize> {
+    /// RAM buffer.
+    ram: [u8; N],
+}
+
+impl<const N: usize> StaticRam<N> {
+    /// Create a new (zeroed) RAM buffer.
+    pub const fn new() -> Self {
+        StaticRam { ram: [0; N] }
+    }
+
+    /// Get the RAM buffer, for the rest of the program.
+    ///
+    /// Arguments:
+    /// - `self`: Static storage, from a safe platform primitive (check the [module documentation](self)).
+    pub fn buffer(&'static mut self) -> &'static mut [u8; N] {
+        &mut self.ram
+    }
+}
+
+impl<const N: usize> Default for StaticRam<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Statically allocated engine.
+///
+/// Holds the system memory and the engine state, both initialized in place by [`StaticEngine::init`].
+pub struct StaticEngine<M: Memory + 'static> {
+    /// System memory storage.
+    memory: MaybeUninit<M>,
+    /// Engine storage.
+    engine: MaybeUninit<Engine<'static, M>>,
+}
+
+impl<M: Memory + 'static> StaticEngine<M> {
+    /// Create a new (uninitialized) static engine.
+    pub const fn new() -> Self {
+        StaticEngine {
+            memory: MaybeUninit::uninit(),
+            engine: MaybeUninit::uninit(),
+        }
+    }
+
+    /// Initialize the engine.
+    /// The storage is borrowed for the rest of the program, so the engine can be initialized only once.
+    ///
+    /// Arguments:
+    /// - `self`: Static storage, from a safe platform primitive (check the [module documentation](self)).
+    /// - `memory`: System memory (code + RAM), moved into the static storage.
+    /// - `config`: Engine configuration.
+    ///
+    /// Returns:
+    /// - `Ok(&mut Engine)`: The engine was initialized.
+    /// - `Err(EmbiveError)`: The configuration is invalid ([`EmbiveError::InvalidConfig`]).
+    pub fn init(
+        &'static mut self,
+        memory: M,
+        config: Config<M>,
+    ) -> Result<&'static mut Engine<'static, M>, EmbiveError> {
+        // The configuration is validated by `Engine::new`
+        let memory = self.memory.write(memory);
+        let engine = Engine::new(memory, config)?;
+        Ok(self.engine.write(engine))
+    }
+}
+
+impl<M: Memory + 'static> Default for StaticEngine<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::boxed::Box;
+
+    use super::*;
+    use crate::{error::ConfigError, memory::ArrayMemory};
+
+    static CODE: [u8; 8] = [
+        0x13, 0x05, 0x10, 0x00, // li   a0, 1
+        0x73, 0x00, 0x10, 0x00, // ebreak
+    ];
+
+    /// Get static storage (stands in for a platform primitive).
+    fn leak<T>(value: T) -> &'static mut T {
+        Box::leak(Box::new(value))
+    }
+
+    #[test]
+    fn static_ram() {
+        let ram = leak(StaticRam::<16>::new()).buffer();
+        assert_eq!(ram, &[0; 16]);
+    }
+
+    #[test]
+    fn static_engine() {
+        let ram = leak(StaticRam::<16>::new()).buffer();
+        let storage = leak(StaticEngine::<ArrayMemory<'static, 8, 16>>::new());
+
+        let engine = storage
+            .init(ArrayMemory::new(&CODE, ram), Config::default())
+            .unwrap();
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(10), Ok(1));
+    }
+
+    #[test]
+    fn static_engine_invalid_config() {
+        let ram = leak(StaticRam::<0>::new()).buffer();
+        let storage = leak(StaticEngine::<ArrayMemory<'static, 8, 0>>::new());
+
+        let config = Config::default().with_entry_point(Some(1));
+        assert!(matches!(
+            storage.init(ArrayMemory::new(&CODE, ram), config),
+            Err(EmbiveError::InvalidConfig(
+                ConfigError::MisalignedEntryPoint
+            ))
+        ));
+    }
+}
@@ -0,0 +1,271 @@
+//! Telemetry ring, periodic snapshots of the guest execution (flight recorder for diagnostics).
+//!
+//! Enabled with [`super::Config::telemetry`], a record ([`TelemetryRecord`]) is taken every
+//! [`super::Config::telemetry_interval`] retired instructions, and kept in scratch memory
+//! ([`Engine::set_scratch`], [`TELEMETRY_RECORD_SIZE`] bytes each), overwriting the oldest one.
+//! The ring survives failed runs and resets, so the host can dump what the guest was doing before an incident.
+//! Hosts can also take a record on demand ([`Engine::record_telemetry`], ex.: when a run fails).
+//!
+//! ```
+//! use embive::{engine::{Config, Engine}, memory::{ScratchMemory, SliceMemory}};
+//!
+//! let code = &[
+//!     0x13, 0x01, 0x01, 0xff, // addi sp, sp, -16
+//!     0x93, 0x08, 0x10, 0x00, // li   a7, 1 (Syscall nr)
+//!     0x73, 0x00, 0x00, 0x00, // ecall
+//!     0xff, 0xff, 0xff, 0xff, // (illegal)
+//! ];
+//! let mut pool = [0; 128];
+//! let mut scratch = ScratchMemory::new(&mut pool);
+//! let mut memory = SliceMemory::new(code, &mut []);
+//! let config = Config::default()
+//!     .with_syscall_fn(Some(|_, _, _| Ok(0)))
+//!     .with_telemetry(4, 2);
+//! let mut engine = Engine::new(&mut memory, config).unwrap();
+//! engine.set_scratch(&mut scratch).unwrap();
+//!
+//! assert!(engine.run().is_err());
+//! let record = engine.telemetry().last().unwrap();
+//! assert_eq!(record.program_counter, 0x8);
+//! assert_eq!(record.stack_pointer, -16i32 as u32);
+//! assert_eq!(record.instret, 2);
+//! assert_eq!(record.last_syscall, None);
+//!
+//! engine.record_telemetry();
+//! let record = engine.telemetry().last().unwrap();
+//! assert_eq!(record.program_counter, 0xc);
+//! assert_eq!(record.instret, 3);
+//! assert_eq!(record.last_syscall, Some(1));
+//! ```
+
+use super::Engine;
+use crate::memory::Memory;
+use crate::register::Register;
+
+/// Telemetry record size in bytes (in scratch memory).
+pub const TELEMETRY_RECORD_SIZE: usize = 32;
+
+/// Snapshot of the guest execution (check the [module documentation](self)).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TelemetryRecord {
+    /// Program counter (next instruction to execute).
+    pub program_counter: u32,
+    /// Stack pointer (`sp`).
+    pub stack_pointer: u32,
+    /// Instructions retired while the telemetry was enabled.
+    pub instret: u64,
+    /// Remaining instruction budget (check [`Engine::budget`], `u64::MAX` without the `instruction_limit` feature).
+    pub budget: u64,
+    /// Number of the last syscall handled by the engine (None = No syscall yet).
+    pub last_syscall: Option<i32>,
+}
+
+impl TelemetryRecord {
+    /// Encode the record (little-endian words: addresses, counters, syscall flag and number).
+    fn encode(&self) -> [u8; TELEMETRY_RECORD_SIZE] {
+        let (syscall, nr) = match self.last_syscall {
+            Some(nr) => (1, nr),
+            None => (0, 0),
+        };
+
+        let mut bytes = [0; TELEMETRY_RECORD_SIZE];
+        for (chunk, word) in bytes.chunks_exact_mut(4).zip([
+            self.program_counter,
+            self.stack_pointer,
+            self.instret as u32,
+            (self.instret >> 32) as u32,
+            self.budget as u32,
+            (self.budget >> 32) as u32,
+            syscall,
+            nr as u32,
+        ]) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decode a record.
+    ///
+    /// Arguments:
+    /// - `bytes`: Encoded record.
+    fn decode(bytes: &[u8]) -> Self {
+        let word = |index: usize| {
+            // Unwrap is safe because records are TELEMETRY_RECORD_SIZE bytes long.
+            u32::from_le_bytes(*bytes[index * 4..].first_chunk().unwrap())
+        };
+        let double = |index: usize| word(index) as u64 | (word(index + 1) as u64) << 32;
+
+        TelemetryRecord {
+            program_counter: word(0),
+            stack_pointer: word(1),
+            instret: double(2),
+            budget: double(4),
+            last_syscall: match word(6) {
+                0 => None,
+                _ => Some(word(7) as i32),
+            },
+        }
+    }
+}
+
+/// Telemetry ring (empty = Disabled).
+#[derive(Debug, Default)]
+pub(crate) struct Telemetry<'a> {
+    /// Records (multiple of [`TELEMETRY_RECORD_SIZE`]).
+    buffer: &'a mut [u8],
+    /// Next record to write.
+    next: usize,
+    /// Number of records written (up to the capacity).
+    len: usize,
+    /// Instructions retired while enabled.
+    instret: u64,
+    /// Instructions retired since the last record.
+    elapsed: u32,
+    /// Number of the last syscall handled by the engine.
+    pub(crate) last_syscall: Option<i32>,
+}
+
+impl<'a> Telemetry<'a> {
+    /// Create a new (empty) telemetry ring.
+    ///
+    /// Arguments:
+    /// - `buffer`: Records buffer (from scratch memory).
+    pub(crate) fn new(buffer: &'a mut [u8]) -> Self {
+        Telemetry {
+            buffer,
+            ..Default::default()
+        }
+    }
+
+    /// Number of records the ring can hold.
+    pub(crate) fn capacity(&self) -> usize {
+        self.buffer.len() / TELEMETRY_RECORD_SIZE
+    }
+
+    /// Check if the ring is enabled (has a buffer in scratch memory).
+    pub(crate) fn enabled(&self) -> bool {
+        !self.buffer.is_empty()
+    }
+
+    /// Count a retired instruction.
+    ///
+    /// Arguments:
+    /// - `interval`: Instructions between records.
+    ///
+    /// Returns:
+    /// - `bool`: A record is due.
+    #[inline(always)]
+    pub(crate) fn tick(&mut self, interval: u32) -> bool {
+        if self.buffer.is_empty() {
+            return false;
+        }
+
+        self.instret = self.instret.wrapping_add(1);
+        self.elapsed += 1;
+        if self.elapsed < interval {
+            return false;
+        }
+
+        self.elapsed = 0;
+        true
+    }
+
+    /// Write a record, overwriting the oldest one when full.
+    ///
+    /// Arguments:
+    /// - `record`: Telemetry record.
+    fn record(&mut self, record: TelemetryRecord) {
+        let capacity = self.capacity();
+        if capacity == 0 {
+            return;
+        }
+
+        let offset = self.next * TELEMETRY_RECORD_SIZE;
+        self.buffer[offset..offset + TELEMETRY_RECORD_SIZE].copy_from_slice(&record.encode());
+        self.next = (self.next + 1) % capacity;
+        self.len = (self.len + 1).min(capacity);
+    }
+
+    /// Iterate over the records, oldest first.
+    fn iter(&self) -> impl Iterator<Item = TelemetryRecord> + '_ {
+        let capacity = self.capacity();
+        let first = (self.next + capacity - self.len) % capacity.max(1);
+        (0..self.len).map(move |i| {
+            let offset = (first + i) % capacity * TELEMETRY_RECORD_SIZE;
+            TelemetryRecord::decode(&self.buffer[offset..offset + TELEMETRY_RECORD_SIZE])
+        })
+    }
+}
+
+impl<M: Memory> Engine<'_, M> {
+    /// Take a telemetry record of the current guest state (ex.: when a run fails).
+    /// Does nothing without scratch memory for the telemetry (check [`super::Config::telemetry`]).
+    pub fn record_telemetry(&mut self) {
+        #[cfg(feature = "instruction_limit")]
+        let budget = self.budget();
+        #[cfg(not(feature = "instruction_limit"))]
+        let budget = u64::MAX;
+
+        self.telemetry.record(TelemetryRecord {
+            program_counter: self.program_counter,
+            stack_pointer: self.registers.inner[Register::SP as usize] as u32,
+            instret: self.telemetry.instret,
+            budget,
+            last_syscall: self.telemetry.last_syscall,
+        });
+    }
+
+    /// Get the telemetry records, oldest first (check the [module documentation](self)).
+    ///
+    /// Returns:
+    /// - `impl Iterator<Item = TelemetryRecord>`: Telemetry records (empty without scratch memory).
+    pub fn telemetry(&self) -> impl Iterator<Item = TelemetryRecord> + '_ {
+        self.telemetry.iter()
+    }
+
+    /// Clear the telemetry records (the instruction counter and last syscall are kept).
+    pub fn clear_telemetry(&mut self) {
+        self.telemetry.next = 0;
+        self.telemetry.len = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring() {
+        let mut buffer = [0; 2 * TELEMETRY_RECORD_SIZE + 1];
+        let mut telemetry = Telemetry::new(&mut buffer);
+        let record = |instret: u64| TelemetryRecord {
+            program_counter: instret as u32 * 4,
+            stack_pointer: 0x8000_1000,
+            instret,
+            budget: u64::MAX - instret,
+            last_syscall: (instret % 2 == 0).then_some(-(instret as i32)),
+        };
+
+        assert_eq!(telemetry.capacity(), 2);
+        assert!(!telemetry.tick(2));
+        assert!(telemetry.tick(2));
+        assert!(!telemetry.tick(2));
+        assert_eq!(telemetry.instret, 3);
+
+        for instret in 0..3 {
+            telemetry.record(record(instret));
+        }
+
+        // Oldest record overwritten
+        let mut iter = telemetry.iter();
+        assert_eq!(iter.next(), Some(record(1)));
+        assert_eq!(iter.next(), Some(record(2)));
+        assert_eq!(iter.next(), None);
+
+        // Disabled
+        let mut disabled = Telemetry::default();
+        assert!(!disabled.tick(1));
+        disabled.record(record(1));
+        assert_eq!(disabled.iter().count(), 0);
+    }
+}
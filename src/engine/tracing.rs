@@ -0,0 +1,280 @@
+//! Execution tracing, host callbacks for every guest instruction (check [`TraceHook`]).
+//!
+//! Registered with [`super::Config::with_trace`], to build tracers, coverage tools and replay debuggers on top
+//! of the engine. Tracing is compiled out entirely without the `trace` feature, and without a hook
+//! the engine only checks that none is set. Callbacks, for each instruction:
+//! - Fetch ([`TraceHook::fetch`]): before executing it (even if it fails).
+//! - Memory access ([`TraceHook::memory_access`]): after it's retired, for loads, stores and atomics
+//!   (vector accesses aren't reported).
+//! - Register writes ([`TraceHook::register_write`]): after it's retired, for each register it changed
+//!   (including syscall results).
+//!
+//! ```
+//! use core::sync::atomic::{AtomicU32, Ordering};
+//! use embive::{
+//!     engine::{Access, Config, Engine, TraceHook},
+//!     memory::SliceMemory,
+//! };
+//!
+//! static FETCHED: AtomicU32 = AtomicU32::new(0);
+//! static STORED: AtomicU32 = AtomicU32::new(0);
+//!
+//! struct Tracer;
+//!
+//! impl TraceHook for Tracer {
+//!     fn fetch(_program_counter: u32, _data: u32) {
+//!         FETCHED.fetch_add(1, Ordering::Relaxed);
+//!     }
+//!
+//!     fn memory_access(_program_counter: u32, address: u32, _len: u32, access: Access) {
+//!         if access == Access::Write {
+//!             STORED.store(address, Ordering::Relaxed);
+//!         }
+//!     }
+//! }
+//!
+//! let code = &[
+//!     0x37, 0x05, 0x00, 0x80, // lui    a0, 0x80000 (RAM)
+//!     0x23, 0x22, 0xa5, 0x00, // sw     a0, 4(a0)
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//! ];
+//! let mut ram = [0; 8];
+//! let mut memory = SliceMemory::new(code, &mut ram);
+//! let config = Config::default().with_trace::<Tracer>();
+//! let mut engine = Engine::new(&mut memory, config).unwrap();
+//!
+//! assert_eq!(engine.run(), Ok(false));
+//! assert_eq!(FETCHED.load(Ordering::Relaxed), 3);
+//! assert_eq!(STORED.load(Ordering::Relaxed), 0x8000_0004);
+//! ```
+
+use crate::instruction::{memory_access, Access};
+use crate::register::Registers;
+
+/// Execution tracing callbacks (check the [module documentation](self)).
+///
+/// All callbacks are optional, by default they do nothing.
+pub trait TraceHook {
+    /// Called when an instruction is fetched, before executing it.
+    ///
+    /// Arguments:
+    /// - `program_counter`: Instruction address.
+    /// - `data`: Instruction (raw, compressed instructions in the lower 16 bits).
+    fn fetch(program_counter: u32, data: u32) {
+        let _ = (program_counter, data);
+    }
+
+    /// Called when a retired instruction changed a register.
+    ///
+    /// Arguments:
+    /// - `program_counter`: Instruction address.
+    /// - `register`: Register index (1-31).
+    /// - `value`: New register value.
+    fn register_write(program_counter: u32, register: usize, value: i32) {
+        let _ = (program_counter, register, value);
+    }
+
+    /// Called when a retired instruction accessed memory.
+    ///
+    /// Arguments:
+    /// - `program_counter`: Instruction address.
+    /// - `address`: Accessed address.
+    /// - `len`: Access width in bytes.
+    /// - `access`: Access kind.
+    fn memory_access(program_counter: u32, address: u32, len: u32, access: Access) {
+        let _ = (program_counter, address, len, access);
+    }
+}
+
+/// Host functions of a [`TraceHook`] implementation (check [`super::Config::with_trace`]).
+#[derive(Debug, Clone, Copy)]
+pub struct TraceHooks {
+    fetch: fn(u32, u32),
+    register_write: fn(u32, usize, i32),
+    memory_access: fn(u32, u32, u32, Access),
+}
+
+impl TraceHooks {
+    /// Get the host functions of a [`TraceHook`] implementation.
+    ///
+    /// Generic Arguments:
+    /// - `T`: Host implementation.
+    pub fn of<T: TraceHook>() -> Self {
+        TraceHooks {
+            fetch: T::fetch,
+            register_write: T::register_write,
+            memory_access: T::memory_access,
+        }
+    }
+}
+
+/// Instruction being traced, state before its execution.
+pub(crate) struct Traced {
+    /// CPU registers, before the instruction.
+    registers: Registers,
+    /// Memory access (address, width and kind).
+    access: Option<(u32, u32, Access)>,
+}
+
+/// Trace a fetched instruction.
+///
+/// Arguments:
+/// - `hooks`: Trace hooks.
+/// - `program_counter`: Instruction address.
+/// - `data`: Instruction (raw).
+/// - `registers`: CPU registers, before the instruction.
+///
+/// Returns:
+/// - `Traced`: State to report once the instruction is retired (check [`retired`]).
+#[cold]
+pub(crate) fn fetched(
+    hooks: &TraceHooks,
+    program_counter: u32,
+    data: u32,
+    registers: &Registers,
+) -> Traced {
+    (hooks.fetch)(program_counter, data);
+
+    // Address computed before execution, the base register can be overwritten (ex.: `lw a0, 0(a0)`)
+    let access = memory_access(data).and_then(|access| {
+        let base = registers.get(access.base).ok()?;
+        let address = (base as u32).wrapping_add_signed(access.offset);
+        Some((address, access.len, access.access))
+    });
+
+    Traced {
+        registers: *registers,
+        access,
+    }
+}
+
+/// Trace a retired instruction.
+///
+/// Arguments:
+/// - `hooks`: Trace hooks.
+/// - `program_counter`: Address of the retired instruction.
+/// - `traced`: State before the instruction (check [`fetched`]).
+/// - `registers`: CPU registers, after the instruction.
+#[cold]
+pub(crate) fn retired(
+    hooks: &TraceHooks,
+    program_counter: u32,
+    traced: Traced,
+    registers: &Registers,
+) {
+    if let Some((address, len, access)) = traced.access {
+        (hooks.memory_access)(program_counter, address, len, access);
+    }
+
+    for (register, value) in changed(&traced.registers, registers) {
+        (hooks.register_write)(program_counter, register, value);
+    }
+}
+
+/// Registers changed by an instruction (ex.: for [`TraceHook::register_write`]).
+///
+/// Arguments:
+/// - `before`: CPU registers, before the instruction.
+/// - `after`: CPU registers, after the instruction.
+///
+/// Returns:
+/// - `impl Iterator<Item = (usize, i32)>`: Index and new value of each changed register.
+pub(crate) fn changed<'r>(
+    before: &'r Registers,
+    after: &'r Registers,
+) -> impl Iterator<Item = (usize, i32)> + Clone + 'r {
+    before
+        .inner
+        .iter()
+        .zip(after.inner.iter())
+        .enumerate()
+        .filter(|(_, (before, after))| before != after)
+        .map(|(register, (_, after))| (register, *after))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Config, Engine};
+    use crate::error::EmbiveError;
+    use crate::memory::SliceMemory;
+    use core::cell::RefCell;
+    use std::vec::Vec;
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Fetch(u32, u32),
+        Write(u32, usize, i32),
+        Memory(u32, u32, u32, Access),
+    }
+
+    std::thread_local! {
+        static EVENTS: RefCell<Vec<Event>> = const { RefCell::new(Vec::new()) };
+    }
+
+    struct Recorder;
+
+    impl TraceHook for Recorder {
+        fn fetch(program_counter: u32, data: u32) {
+            EVENTS.with(|events| {
+                events
+                    .borrow_mut()
+                    .push(Event::Fetch(program_counter, data))
+            });
+        }
+
+        fn register_write(program_counter: u32, register: usize, value: i32) {
+            EVENTS.with(|events| {
+                events
+                    .borrow_mut()
+                    .push(Event::Write(program_counter, register, value))
+            });
+        }
+
+        fn memory_access(program_counter: u32, address: u32, len: u32, access: Access) {
+            EVENTS.with(|events| {
+                events
+                    .borrow_mut()
+                    .push(Event::Memory(program_counter, address, len, access))
+            });
+        }
+    }
+
+    #[test]
+    fn test_trace() {
+        let code = &[
+            0x37, 0x05, 0x00, 0x80, // lui  a0, 0x80000
+            0x03, 0x15, 0x25, 0x00, // lh   a0, 2(a0)
+            0x13, 0x00, 0x00, 0x00, // nop
+            0xff, 0xff, 0xff, 0xff, // (illegal)
+        ];
+        let mut ram = [0x00, 0x00, 0xff, 0xff];
+        let mut memory = SliceMemory::new(code, &mut ram);
+        let config = Config::default().with_trace::<Recorder>();
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        assert_eq!(
+            engine.run(),
+            Err(EmbiveError::IllegalInstruction {
+                pc: 0xc,
+                raw: 0xffff_ffff
+            })
+        );
+
+        // Failed instructions are fetched, not retired
+        EVENTS.with(|events| {
+            assert_eq!(
+                *events.borrow(),
+                [
+                    Event::Fetch(0x0, 0x8000_0537),
+                    Event::Write(0x0, 10, 0x8000_0000u32 as i32),
+                    Event::Fetch(0x4, 0x0025_1503),
+                    Event::Memory(0x4, 0x8000_0002, 2, Access::Read),
+                    Event::Write(0x4, 10, -1),
+                    Event::Fetch(0x8, 0x0000_0013),
+                    Event::Fetch(0xc, 0xffff_ffff),
+                ]
+            );
+        });
+    }
+}
@@ -3,22 +3,186 @@
 use core::{error::Error, fmt::Display};
 
 /// Embive Error Enum
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum EmbiveError {
-    /// Memory address is out of bounds.
+    /// Memory address is out of bounds (without context, ex.: from a memory implementation,
+    /// guest accesses are reported by the engine as [`EmbiveError::LoadFault`] or [`EmbiveError::StoreFault`]).
     InvalidMemoryAddress,
     /// Program counter is out of bounds.
     InvalidProgramCounter,
-    /// Instruction is not implemented.
+    /// Instruction is not implemented (without context, reported by the engine as [`EmbiveError::IllegalInstruction`]).
     InvalidInstruction,
+    /// Guest instruction is illegal or not implemented.
+    IllegalInstruction {
+        /// Instruction address.
+        pc: u32,
+        /// Instruction (raw, compressed instructions in the lower 16 bits).
+        raw: u32,
+    },
+    /// Guest instruction fetch out of bounds.
+    FetchFault {
+        /// Instruction address.
+        pc: u32,
+    },
+    /// Guest load out of bounds.
+    LoadFault {
+        /// Load instruction address.
+        pc: u32,
+        /// Load address.
+        address: u32,
+    },
+    /// Guest store (or atomic read-modify-write) out of bounds.
+    StoreFault {
+        /// Store instruction address.
+        pc: u32,
+        /// Store address.
+        address: u32,
+    },
     /// Register is out of bounds.
     InvalidRegister,
     /// No syscall function is set.
     NoSyscallFunction,
+    /// No interrupt function is set.
+    NoInterruptFunction,
+    /// Interrupt line or priority is out of bounds.
+    InvalidInterrupt,
+    /// Engine configuration is invalid.
+    InvalidConfig(ConfigError),
+    /// Syscall time quota exceeded.
+    QuotaExceeded,
+    /// Too many persistent RAM regions.
+    TooManyPersistentRegions,
+    /// Too many capabilities granted to the guest.
+    TooManyCapabilities,
+    /// Too many memory-mapped I/O regions.
+    TooManyMmioRegions,
+    /// Framebuffer doesn't fit in its pixel buffer or in the address space (check [`crate::memory::framebuffer`]).
+    InvalidFramebuffer,
+    /// Too many debugger breakpoints or watchpoints.
+    TooManyTriggers,
+    /// Maximum run depth exceeded by nested engine runs (check [`crate::engine::Config::max_run_depth`]).
+    RecursionLimit,
+    /// Snapshot is malformed or doesn't fit in the buffer, or the memory doesn't support snapshots.
+    InvalidSnapshot,
+    /// Not enough scratch memory for the configured subsystems.
+    ScratchTooSmall,
+    /// Guest store into the stack guard region (check [`crate::engine::Config::stack_guard`]).
+    StackOverflow {
+        /// Store instruction address.
+        pc: u32,
+        /// Store address.
+        address: u32,
+    },
+    /// Memory access denied by a protection region or a red zone (check [`crate::memory::ProtectedMemory`]
+    /// and [`crate::memory::SanitizedMemory`]).
+    AccessViolation {
+        /// Access address.
+        address: u32,
+        /// Access kind.
+        kind: AccessKind,
+    },
+    /// Too many memory protection regions.
+    TooManyProtectionRegions,
+    /// Too many host buffers shared with the guest.
+    TooManySharedBuffers,
+    /// Too many guest call arguments (check [`crate::engine::CALL_ARGS`]).
+    TooManyArguments,
+    /// Guest function called by the host halted, was suspended or stopped before returning
+    /// (check [`crate::engine::Engine::call`]).
+    CallNotReturned,
+    /// Replay log full, the input wasn't recorded (check [`crate::replay`]).
+    ReplayLogFull,
+    /// Execution diverged from the replay log, or the log is invalid (check [`crate::replay`]).
+    ReplayDiverged,
     /// Custom error.
     Custom(&'static str),
 }
 
+/// Memory Access Kind Enum (check [`EmbiveError::AccessViolation`])
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AccessKind {
+    /// Data load.
+    Read,
+    /// Data store.
+    Write,
+    /// Instruction fetch.
+    Execute,
+}
+
+/// Embive Configuration Error Enum
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ConfigError {
+    /// Entry point is not aligned to an instruction boundary (4 bytes, 2 if the `c_extension` feature is enabled).
+    MisalignedEntryPoint,
+    /// Entry point is outside of the system memory.
+    EntryPointOutOfBounds,
+    /// RAM is smaller than the configured stack size.
+    StackTooLarge,
+    /// Yield interrupt granularity without an instruction limit (interrupts could be delayed forever).
+    UnboundedInterruptLatency,
+    /// Software interrupt syscall is set, but no target sandboxes are permitted.
+    NoSoftwareInterruptTargets,
+}
+
+/// Embive Loader Error Enum
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LoaderError {
+    /// File is not a valid ELF file (bad magic or truncated headers).
+    InvalidHeader,
+    /// ELF file is not a 32-bit, little endian, RISC-V executable.
+    Unsupported,
+    /// Segment is invalid (data outside of the file, crosses memory regions, etc.).
+    InvalidSegment,
+    /// Section headers are invalid (table or names outside of the file).
+    InvalidSection,
+    /// Code doesn't fit in the code buffer.
+    CodeTooLarge,
+    /// Static RAM and requested stack don't fit in the RAM buffer.
+    RamTooLarge,
+    /// Uploaded chunk is out of order (offset differs from the bytes written so far).
+    OutOfOrder,
+    /// Uploaded size differs from the expected size.
+    SizeMismatch,
+    /// Uploaded data digest differs from the expected digest.
+    DigestMismatch,
+    /// Image slot is under trial (standby slot holds the rollback image).
+    SlotInTrial,
+}
+
+impl Error for LoaderError {}
+
+impl Display for LoaderError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Error for ConfigError {}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl EmbiveError {
+    /// Get the address of the guest instruction that faulted (if known).
+    ///
+    /// Returns:
+    /// - `Some(u32)`: Faulting program counter.
+    /// - `None`: Not a guest fault (or no context).
+    pub const fn pc(&self) -> Option<u32> {
+        match self {
+            EmbiveError::IllegalInstruction { pc, .. }
+            | EmbiveError::FetchFault { pc }
+            | EmbiveError::LoadFault { pc, .. }
+            | EmbiveError::StoreFault { pc, .. }
+            | EmbiveError::StackOverflow { pc, .. } => Some(*pc),
+            _ => None,
+        }
+    }
+}
+
 impl Error for EmbiveError {}
 
 impl Display for EmbiveError {
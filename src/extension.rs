@@ -0,0 +1,182 @@
+//! Extension Module
+//!
+//! Instruction set extensions provided outside of Embive (ex.: vendor DSP instructions).
+//!
+//! An extension implements the [`Extension`] trait and is registered in the engine configuration
+//! ([`crate::engine::Config::with_extension`]). Instructions not implemented by Embive (custom opcodes,
+//! disabled extensions, etc.) are forwarded to it before an [`EmbiveError::InvalidInstruction`] is returned,
+//! so the built-in instructions keep their dispatch speed.
+//!
+//! Multiple extensions can be registered as a tuple (`(A, B)`), they are tried in order.
+//!
+//! ```
+//! use embive::{
+//!     engine::{Config, Engine},
+//!     error::EmbiveError,
+//!     extension::{Extension, TypeR, CUSTOM_0_OPCODE, INSTRUCTION_SIZE},
+//!     memory::{Memory, SliceMemory},
+//! };
+//!
+//! /// Multiply-accumulate (`rd = rd + rs1 * rs2`) on the custom-0 opcode.
+//! struct Mac;
+//!
+//! impl<M: Memory> Extension<M> for Mac {
+//!     fn decode_execute(data: u32, engine: &mut Engine<M>) -> Result<Option<bool>, EmbiveError> {
+//!         if data & 0x7F != CUSTOM_0_OPCODE as u32 {
+//!             return Ok(None); // Not handled
+//!         }
+//!
+//!         let inst = TypeR::from(data);
+//!         let value = engine.registers.get(inst.rs1)? * engine.registers.get(inst.rs2)?;
+//!         let rd = engine.registers.get_mut(inst.rd)?;
+//!         *rd = rd.wrapping_add(value);
+//!
+//!         engine.program_counter = engine.program_counter.wrapping_add(INSTRUCTION_SIZE);
+//!         Ok(Some(true))
+//!     }
+//! }
+//!
+//! let code = &[
+//!     0x93, 0x05, 0x30, 0x00, // li  a1, 3
+//!     0x13, 0x06, 0x40, 0x00, // li  a2, 4
+//!     0x0b, 0x85, 0xc5, 0x00, // mac a0, a1, a2 (custom-0)
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//! ];
+//! let mut memory = SliceMemory::new(code, &mut []);
+//! let mut engine = Engine::new(&mut memory, Config::default().with_extension::<Mac>()).unwrap();
+//!
+//! assert_eq!(engine.run(), Ok(false));
+//! assert_eq!(engine.registers.get(10), Ok(12));
+//! ```
+
+use crate::engine::Engine;
+use crate::error::EmbiveError;
+use crate::memory::Memory;
+
+pub use crate::instruction::format::{TypeB, TypeI, TypeJ, TypeR, TypeS, TypeU};
+pub use crate::instruction::INSTRUCTION_SIZE;
+
+/// Custom-0 opcode (reserved for custom extensions).
+pub const CUSTOM_0_OPCODE: u8 = 0b000_1011;
+/// Custom-1 opcode (reserved for custom extensions).
+pub const CUSTOM_1_OPCODE: u8 = 0b010_1011;
+/// Custom-2 opcode (reserved for custom extensions, RV128 `rv128` opcode).
+pub const CUSTOM_2_OPCODE: u8 = 0b101_1011;
+/// Custom-3 opcode (reserved for custom extensions, RV128 `rv128` opcode).
+pub const CUSTOM_3_OPCODE: u8 = 0b111_1011;
+
+/// Extension function signature (check [`Extension::decode_execute`]).
+pub type ExtensionFn<M> =
+    fn(data: u32, engine: &mut Engine<M>) -> Result<Option<bool>, EmbiveError>;
+
+/// Extension trait. Instruction set extensions must implement this trait.
+pub trait Extension<M: Memory> {
+    /// Decode and execute an instruction not implemented by Embive.
+    ///
+    /// The extension is responsible for updating the program counter
+    /// (usually by [`INSTRUCTION_SIZE`]).
+    ///
+    /// Arguments:
+    /// - `data`: `u32` value representing the instruction.
+    /// - `engine`: Mutable pointer to embive engine.
+    ///
+    /// Returns:
+    /// - `Ok(Some(bool))`: Instruction executed successfully:
+    ///     - `True`: Should continue execution.
+    ///     - `False`: Should halt.
+    /// - `Ok(None)`: Instruction is not handled by this extension.
+    /// - `Err(EmbiveError)`: Failed to execute instruction.
+    fn decode_execute(data: u32, engine: &mut Engine<M>) -> Result<Option<bool>, EmbiveError>;
+}
+
+impl<M: Memory, A: Extension<M>, B: Extension<M>> Extension<M> for (A, B) {
+    #[inline]
+    fn decode_execute(data: u32, engine: &mut Engine<M>) -> Result<Option<bool>, EmbiveError> {
+        match A::decode_execute(data, engine)? {
+            Some(ret) => Ok(Some(ret)),
+            None => B::decode_execute(data, engine),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{engine::Config, memory::SliceMemory};
+
+    /// `rd = rs1 + 1` (custom-0).
+    struct Inc;
+
+    impl<M: Memory> Extension<M> for Inc {
+        fn decode_execute(data: u32, engine: &mut Engine<M>) -> Result<Option<bool>, EmbiveError> {
+            if data & 0x7F != CUSTOM_0_OPCODE as u32 {
+                return Ok(None);
+            }
+
+            let inst = TypeI::from(data);
+            *engine.registers.get_mut(inst.rd)? = engine.registers.get(inst.rs1)? + 1;
+            engine.program_counter = engine.program_counter.wrapping_add(INSTRUCTION_SIZE);
+            Ok(Some(true))
+        }
+    }
+
+    /// Halt (custom-1).
+    struct Halt;
+
+    impl<M: Memory> Extension<M> for Halt {
+        fn decode_execute(data: u32, _engine: &mut Engine<M>) -> Result<Option<bool>, EmbiveError> {
+            if data & 0x7F != CUSTOM_1_OPCODE as u32 {
+                return Ok(None);
+            }
+
+            Ok(Some(false))
+        }
+    }
+
+    fn custom_inst(opcode: u8) -> u32 {
+        u32::from(TypeI {
+            rd: 10,
+            rs1: 10,
+            imm: 0,
+            funct3: 0,
+        }) | opcode as u32
+    }
+
+    #[test]
+    fn test_no_extension() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+
+        let result = crate::instruction::decode_execute(&mut engine, custom_inst(CUSTOM_0_OPCODE));
+        assert_eq!(result, Err(EmbiveError::InvalidInstruction));
+    }
+
+    #[test]
+    fn test_extension() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let config = Config::default().with_extension::<Inc>();
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        let result = crate::instruction::decode_execute(&mut engine, custom_inst(CUSTOM_0_OPCODE));
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.registers.get(10), Ok(1));
+        assert_eq!(engine.program_counter, INSTRUCTION_SIZE);
+
+        let result = crate::instruction::decode_execute(&mut engine, custom_inst(CUSTOM_1_OPCODE));
+        assert_eq!(result, Err(EmbiveError::InvalidInstruction));
+    }
+
+    #[test]
+    fn test_extension_tuple() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let config = Config::default().with_extension::<(Inc, Halt)>();
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        let result = crate::instruction::decode_execute(&mut engine, custom_inst(CUSTOM_0_OPCODE));
+        assert_eq!(result, Ok(true));
+        let result = crate::instruction::decode_execute(&mut engine, custom_inst(CUSTOM_1_OPCODE));
+        assert_eq!(result, Ok(false));
+        let result = crate::instruction::decode_execute(&mut engine, custom_inst(CUSTOM_2_OPCODE));
+        assert_eq!(result, Err(EmbiveError::InvalidInstruction));
+    }
+}
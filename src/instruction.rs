@@ -1,20 +1,28 @@
 //! RISC-V instruction set implementation.
 #[cfg(feature = "a_extension")]
-mod amo;
+pub(crate) mod amo;
 mod auipc;
-mod branch;
-mod format;
+pub(crate) mod branch;
+#[cfg(feature = "c_extension")]
+pub(crate) mod compressed;
+pub(crate) mod format;
 mod jal;
 mod jalr;
-mod load;
+pub(crate) mod load;
+#[cfg(feature = "v_extension")]
+mod load_fp;
 mod lui;
-mod misc_mem;
-mod op;
-mod op_imm;
-mod store;
-mod system;
+pub(crate) mod misc_mem;
+pub(crate) mod op;
+pub(crate) mod op_imm;
+#[cfg(feature = "v_extension")]
+mod op_v;
+pub(crate) mod store;
+#[cfg(feature = "v_extension")]
+mod store_fp;
+pub(crate) mod system;
 
-use crate::engine::Engine;
+use crate::engine::{Engine, Hint};
 use crate::error::EmbiveError;
 use crate::memory::Memory;
 
@@ -22,36 +30,96 @@ use crate::memory::Memory;
 use amo::Amo;
 use auipc::Auipc;
 use branch::Branch;
+#[cfg(feature = "c_extension")]
+use compressed::Compressed;
 use jal::Jal;
 use jalr::Jalr;
 use load::Load;
+#[cfg(feature = "v_extension")]
+use load_fp::LoadFp;
 use lui::Lui;
 use misc_mem::MiscMem;
 use op::Op;
 use op_imm::OpImm;
+#[cfg(feature = "v_extension")]
+use op_v::OpV;
 use store::Store;
+#[cfg(feature = "v_extension")]
+use store_fp::StoreFp;
 use system::System;
 
 /// The size of an instruction in bytes.
-const INSTRUCTION_SIZE: u32 = 4;
+pub const INSTRUCTION_SIZE: u32 = 4;
+
+/// The size of a compressed (RV32C) instruction in bytes.
+#[cfg(feature = "c_extension")]
+pub const COMPRESSED_INSTRUCTION_SIZE: u32 = 2;
+
+/// Get the size of an instruction in bytes.
+///
+/// Arguments:
+/// - `data`: `u32` value representing the instruction (only the lower 16 bits are used for compressed instructions).
+///
+/// Returns:
+/// - `u32`: [`COMPRESSED_INSTRUCTION_SIZE`] for compressed instructions (if enabled), [`INSTRUCTION_SIZE`] otherwise.
+#[inline(always)]
+pub(crate) const fn instruction_size(data: u32) -> u32 {
+    #[cfg(feature = "c_extension")]
+    if data & 0b11 != 0b11 {
+        return COMPRESSED_INSTRUCTION_SIZE;
+    }
+
+    let _ = data;
+    INSTRUCTION_SIZE
+}
 
 // RISC-V opcodes.
 #[cfg(feature = "a_extension")]
-const AMO_OPCODE: u8 = 0b010_1111;
-const LUI_OPCODE: u8 = 0b011_0111;
-const AUI_PC_OPCODE: u8 = 0b001_0111;
-const JAL_OPCODE: u8 = 0b110_1111;
-const JALR_OPCODE: u8 = 0b110_0111;
-const BRANCH_OPCODE: u8 = 0b110_0011;
-const LOAD_OPCODE: u8 = 0b000_0011;
-const STORE_OPCODE: u8 = 0b010_0011;
-const OP_IMM_OPCODE: u8 = 0b001_0011;
-const OP_OPCODE: u8 = 0b011_0011;
-const MISC_MEM_OPCODE: u8 = 0b000_1111;
-const SYSTEM_OPCODE: u8 = 0b111_0011;
+pub(crate) const AMO_OPCODE: u8 = 0b010_1111;
+pub(crate) const LUI_OPCODE: u8 = 0b011_0111;
+pub(crate) const AUI_PC_OPCODE: u8 = 0b001_0111;
+pub(crate) const JAL_OPCODE: u8 = 0b110_1111;
+pub(crate) const JALR_OPCODE: u8 = 0b110_0111;
+pub(crate) const BRANCH_OPCODE: u8 = 0b110_0011;
+pub(crate) const LOAD_OPCODE: u8 = 0b000_0011;
+pub(crate) const STORE_OPCODE: u8 = 0b010_0011;
+pub(crate) const OP_IMM_OPCODE: u8 = 0b001_0011;
+pub(crate) const OP_OPCODE: u8 = 0b011_0011;
+pub(crate) const MISC_MEM_OPCODE: u8 = 0b000_1111;
+pub(crate) const SYSTEM_OPCODE: u8 = 0b111_0011;
+#[cfg(feature = "v_extension")]
+pub(crate) const LOAD_FP_OPCODE: u8 = 0b000_0111;
+#[cfg(feature = "v_extension")]
+pub(crate) const STORE_FP_OPCODE: u8 = 0b010_0111;
+#[cfg(feature = "v_extension")]
+pub(crate) const OP_V_OPCODE: u8 = 0b101_0111;
 
 /// Instruction trait. All instructions must implement this trait.
+///
+/// Decoding and execution are split: [`Instruction::execute`] takes the decoded operands by value, so they can be
+/// produced (and inspected, ex.: for tracing) independently of the execution. The dispatch itself stays a `match`
+/// over the opcode in [`decode_execute`], with every instruction inlined into it. A decoded-instruction enum
+/// (decode to an enum, then `match` on it to execute) was prototyped in `benches/enum_dispatch.rs`: it performs the
+/// same while the compiler fuses both `match`es, and is 35% to 40% slower once the enum is materialized (as needed to
+/// trace decoded instructions). Only a decoded code cache makes it faster, which needs invalidation on code writes,
+/// so it isn't used (check `benches/README.md`).
 trait Instruction<M: Memory> {
+    /// Decoded operands (instruction format, or the raw instruction if decoded during execution).
+    type Operands: From<u32>;
+
+    /// Execute the instruction.
+    ///
+    /// Arguments:
+    /// - `inst`: Decoded operands.
+    /// - `engine`: Mutable pointer to embive engine.
+    ///
+    /// Returns:
+    /// - `Ok(bool)`: Instruction executed successfully:
+    ///     - `True`: Should continue execution.
+    ///     - `False`: Should halt.
+    /// - `Err(EmbiveError)`: Failed to execute instruction.
+    fn execute(inst: Self::Operands, engine: &mut Engine<M>) -> Result<bool, EmbiveError>;
+
     /// Decode and Execute the instruction.
     ///
     /// Arguments:
@@ -63,7 +131,10 @@ trait Instruction<M: Memory> {
     ///     - `True`: Should continue execution.
     ///     - `False`: Should halt.
     /// - `Err(EmbiveError)`: Failed to execute instruction.
-    fn decode_execute(data: u32, engine: &mut Engine<M>) -> Result<bool, EmbiveError>;
+    #[inline(always)]
+    fn decode_execute(data: u32, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
+        Self::execute(Self::Operands::from(data), engine)
+    }
 }
 
 /// Decode and execute an instruction.
@@ -77,11 +148,16 @@ trait Instruction<M: Memory> {
 ///     - `True`: Should continue execution.
 ///     - `False`: Should halt.
 /// - `Err(EmbiveError)`: Failed to decode or execute instruction.
-#[inline]
+#[inline(always)]
 pub(crate) fn decode_execute<M: Memory>(
     engine: &mut Engine<M>,
     data: u32,
 ) -> Result<bool, EmbiveError> {
+    #[cfg(feature = "c_extension")]
+    if data & 0b11 != 0b11 {
+        return Compressed::decode_execute(data, engine);
+    }
+
     match (data & 0x7F) as u8 {
         LOAD_OPCODE => Load::decode_execute(data, engine),
         MISC_MEM_OPCODE => MiscMem::decode_execute(data, engine),
@@ -96,14 +172,178 @@ pub(crate) fn decode_execute<M: Memory>(
         JALR_OPCODE => Jalr::decode_execute(data, engine),
         JAL_OPCODE => Jal::decode_execute(data, engine),
         SYSTEM_OPCODE => System::decode_execute(data, engine),
-        _ => Err(EmbiveError::InvalidInstruction),
+        #[cfg(feature = "v_extension")]
+        OP_V_OPCODE => OpV::decode_execute(data, engine),
+        #[cfg(feature = "v_extension")]
+        LOAD_FP_OPCODE => LoadFp::decode_execute(data, engine),
+        #[cfg(feature = "v_extension")]
+        STORE_FP_OPCODE => StoreFp::decode_execute(data, engine),
+        _ => extension(engine, data),
+    }
+}
+
+/// Memory access kind (of a guest instruction).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Access {
+    /// Load.
+    Read,
+    /// Store.
+    Write,
+    /// Load and store (ex.: atomic read-modify-write). As a watchpoint, matches any access.
+    #[cfg_attr(not(feature = "a_extension"), allow(dead_code))]
+    ReadWrite,
+}
+
+/// Memory access of an instruction, decoded without executing it (check [`memory_access`]).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) struct MemoryAccess {
+    /// Base address register.
+    pub base: usize,
+    /// Offset from the base address.
+    pub offset: i32,
+    /// Stored value register (zero register for loads).
+    #[cfg_attr(not(feature = "debugger"), allow(dead_code))]
+    pub data: usize,
+    /// Access width in bytes.
+    pub len: u32,
+    /// Access kind.
+    pub access: Access,
+}
+
+/// Decode the memory access of a load, store or atomic instruction (vector accesses are not decoded).
+///
+/// Arguments:
+/// - `data`: `u32` value representing the instruction.
+///
+/// Returns:
+/// - `Some(MemoryAccess)`: The instruction accesses memory.
+/// - `None`: The instruction doesn't access memory (or is illegal).
+pub(crate) fn memory_access(data: u32) -> Option<MemoryAccess> {
+    #[cfg(feature = "c_extension")]
+    if data & 0b11 != 0b11 {
+        return memory_access(compressed::expand(data & 0xFFFF)?);
+    }
+
+    let (base, offset, data, funct3, access) = match (data & 0x7F) as u8 {
+        LOAD_OPCODE => {
+            let inst = format::TypeI::from(data);
+            (inst.rs1, inst.imm, 0, inst.funct3, Access::Read)
+        }
+        STORE_OPCODE => {
+            let inst = format::TypeS::from(data);
+            (inst.rs1, inst.imm, inst.rs2, inst.funct3, Access::Write)
+        }
+        #[cfg(feature = "a_extension")]
+        AMO_OPCODE => {
+            let inst = format::TypeR::from(data);
+            let access = match (inst.funct10 >> 5) as u8 {
+                amo::LR_FUNCT5 => Access::Read,
+                amo::SC_FUNCT5 => Access::Write,
+                _ => Access::ReadWrite,
+            };
+
+            // Doubleword compare-and-swap, on register pairs
+            #[cfg(feature = "zacas")]
+            if (inst.funct10 & 0b111) as u8 == amo::DOUBLE_WIDTH
+                && (inst.funct10 >> 5) as u8 == amo::AMOCAS_FUNCT5
+            {
+                return Some(MemoryAccess {
+                    base: inst.rs1,
+                    offset: 0,
+                    data: inst.rs2,
+                    len: 8,
+                    access,
+                });
+            }
+
+            (inst.rs1, 0, inst.rs2, (inst.funct10 & 0b111) as u8, access)
+        }
+        _ => return None,
+    };
+
+    // Byte, half-word or word (sign-extension bit ignored)
+    let len = match funct3 & 0b11 {
+        0b11 => return None,
+        width => 1 << width,
+    };
+
+    Some(MemoryAccess {
+        base,
+        offset,
+        data,
+        len,
+        access,
+    })
+}
+
+/// Check if an instruction is a conditional branch.
+///
+/// Arguments:
+/// - `data`: `u32` value representing the instruction.
+///
+/// Returns:
+/// - `bool`: The instruction is a conditional branch (ex.: `beq`, or `c.beqz`).
+#[cfg(feature = "counters")]
+pub(crate) fn is_branch(data: u32) -> bool {
+    // c.beqz, c.bnez (control transfers are not expanded)
+    #[cfg(feature = "c_extension")]
+    if data & 0b11 != 0b11 {
+        return data & 0b11 == 0b01 && (data >> 13) & 0b111 >= 0b110;
+    }
+
+    (data & 0x7F) as u8 == BRANCH_OPCODE
+}
+
+/// Report a HINT instruction (executed as a no-op) to the hint function, if set.
+///
+/// Arguments:
+/// - `engine`: Embive engine (program counter at the HINT instruction).
+/// - `hint`: Executed hint.
+#[inline]
+fn hint<M: Memory>(engine: &Engine<M>, hint: Hint) {
+    if let Some(hint_fn) = engine.config.hint_fn {
+        hint_fn(hint, engine.program_counter);
+    }
+}
+
+/// Instruction not implemented by Embive, forward it to the configured extension (if any).
+/// Kept out of the dispatch hot path when optimizing for Cortex-M.
+///
+/// Arguments:
+/// - `engine`: Mutable pointer to embive engine.
+/// - `data`: `u32` value representing the instruction.
+///
+/// Returns:
+/// - `Ok(bool)`: The extension executed the instruction successfully.
+/// - `Err(EmbiveError)`: No extension handled the instruction or it failed to execute.
+#[cfg_attr(feature = "cortex_m_optimized", cold, inline(never))]
+fn extension<M: Memory>(engine: &mut Engine<M>, data: u32) -> Result<bool, EmbiveError> {
+    match engine.config.extension_fn {
+        Some(extension_fn) => extension_fn(data, engine)?.ok_or(EmbiveError::InvalidInstruction),
+        None => Err(EmbiveError::InvalidInstruction),
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use crate::{engine::Engine, memory::SliceMemory};
+    use core::cell::Cell;
+    use std::thread_local;
+
+    thread_local! {
+        static LAST_HINT: Cell<Option<(Hint, u32)>> = const { Cell::new(None) };
+    }
+
+    /// Hint function that records the last hint (check [`last_hint`]).
+    pub(crate) fn record_hint(hint: Hint, program_counter: u32) {
+        LAST_HINT.with(|last| last.set(Some((hint, program_counter))));
+    }
+
+    /// Take the last hint recorded by [`record_hint`].
+    pub(crate) fn last_hint() -> Option<(Hint, u32)> {
+        LAST_HINT.with(|last| last.take())
+    }
 
     #[test]
     fn test_invalid_instruction() {
@@ -112,4 +352,92 @@ mod tests {
         let result = super::decode_execute(&mut engine, 0);
         assert_eq!(result, Err(EmbiveError::InvalidInstruction));
     }
+
+    #[test]
+    fn test_hint_fn() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let config = crate::engine::Config::default().with_hint_fn(Some(record_hint));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+        engine.program_counter = 0x8;
+
+        // lui x0, 0x1
+        let result = super::decode_execute(&mut engine, 0x0000_1037);
+        assert_eq!(result, Ok(true));
+        assert_eq!(last_hint(), Some((Hint::Reserved, 0x8)));
+
+        // nop (not reported)
+        let result = super::decode_execute(&mut engine, 0x0000_0013);
+        assert_eq!(result, Ok(true));
+        assert_eq!(last_hint(), None);
+    }
+
+    #[cfg(feature = "debugger")]
+    #[test]
+    fn test_memory_access() {
+        // lw a1, 4(a0)
+        assert_eq!(
+            memory_access(0x0045_2583),
+            Some(MemoryAccess {
+                base: 10,
+                offset: 4,
+                data: 0,
+                len: 4,
+                access: Access::Read
+            })
+        );
+
+        // sb a1, -1(a0)
+        assert_eq!(
+            memory_access(0xfeb5_0fa3),
+            Some(MemoryAccess {
+                base: 10,
+                offset: -1,
+                data: 11,
+                len: 1,
+                access: Access::Write
+            })
+        );
+
+        // addi a0, a0, 1
+        assert_eq!(memory_access(0x0015_0513), None);
+
+        // c.swsp a1, 8(sp)
+        #[cfg(feature = "c_extension")]
+        assert_eq!(
+            memory_access(0xc42e),
+            Some(MemoryAccess {
+                base: 2,
+                offset: 8,
+                data: 11,
+                len: 4,
+                access: Access::Write
+            })
+        );
+
+        // amoadd.w a0, a1, (a2)
+        #[cfg(feature = "a_extension")]
+        assert_eq!(
+            memory_access(0x00b6_252f),
+            Some(MemoryAccess {
+                base: 12,
+                offset: 0,
+                data: 11,
+                len: 4,
+                access: Access::ReadWrite
+            })
+        );
+
+        // amocas.d a0, a2, (a4)
+        #[cfg(feature = "zacas")]
+        assert_eq!(
+            memory_access(0x28c7_352f),
+            Some(MemoryAccess {
+                base: 14,
+                offset: 0,
+                data: 12,
+                len: 8,
+                access: Access::ReadWrite
+            })
+        );
+    }
 }
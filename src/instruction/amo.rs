@@ -4,34 +4,44 @@ use crate::instruction::format::TypeR;
 use crate::instruction::{Instruction, INSTRUCTION_SIZE};
 use crate::memory::Memory;
 
-const WORD_WIDTH: u8 = 0b010;
-
-const LR_FUNCT5: u8 = 0b00010;
-const SC_FUNCT5: u8 = 0b00011;
-const AMOSWAP_FUNCT5: u8 = 0b00001;
-const AMOADD_FUNCT5: u8 = 0b00000;
-const AMOXOR_FUNCT5: u8 = 0b00100;
-const AMOAND_FUNCT5: u8 = 0b01100;
-const AMOOR_FUNCT5: u8 = 0b01000;
-const AMOMIN_FUNCT5: u8 = 0b10000;
-const AMOMAX_FUNCT5: u8 = 0b10100;
-const AMOMINU_FUNCT5: u8 = 0b11000;
-const AMOMAXU_FUNCT5: u8 = 0b11100;
+pub(crate) const WORD_WIDTH: u8 = 0b010;
+#[cfg(feature = "zacas")]
+pub(crate) const DOUBLE_WIDTH: u8 = 0b011;
+
+pub(crate) const LR_FUNCT5: u8 = 0b00010;
+pub(crate) const SC_FUNCT5: u8 = 0b00011;
+pub(crate) const AMOSWAP_FUNCT5: u8 = 0b00001;
+pub(crate) const AMOADD_FUNCT5: u8 = 0b00000;
+pub(crate) const AMOXOR_FUNCT5: u8 = 0b00100;
+pub(crate) const AMOAND_FUNCT5: u8 = 0b01100;
+pub(crate) const AMOOR_FUNCT5: u8 = 0b01000;
+pub(crate) const AMOMIN_FUNCT5: u8 = 0b10000;
+pub(crate) const AMOMAX_FUNCT5: u8 = 0b10100;
+pub(crate) const AMOMINU_FUNCT5: u8 = 0b11000;
+pub(crate) const AMOMAXU_FUNCT5: u8 = 0b11100;
+#[cfg(feature = "zacas")]
+pub(crate) const AMOCAS_FUNCT5: u8 = 0b00101;
 
 /// Atomic Memory Operations
-/// Instructions: LR, SC, AMOSWAP, AMOADD, AMOXOR, AMOAND, AMOOR, AMOMIN, AMOMAX, AMOMINU, AMOMAXU
+/// Instructions: LR, SC, AMOSWAP, AMOADD, AMOXOR, AMOAND, AMOOR, AMOMIN, AMOMAX, AMOMINU, AMOMAXU,
+/// AMOCAS.W and AMOCAS.D (`zacas` feature)
 /// Format: R-Type.
 pub struct Amo {}
 
 impl<M: Memory> Instruction<M> for Amo {
-    #[inline(always)]
-    fn decode_execute(data: u32, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
-        let inst = TypeR::from(data);
+    type Operands = TypeR;
 
-        let rs1 = engine.registers.get(inst.rs1)? as u32;
-        let rs2 = engine.registers.get(inst.rs2)?;
+    #[inline(always)]
+    fn execute(inst: TypeR, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
+        let rs1 = engine.registers.get_decoded(inst.rs1)? as u32;
+        let rs2 = engine.registers.get_decoded(inst.rs2)?;
         let result;
 
+        // Every atomic but a load-reserved may store (a failed store-conditional included)
+        if (inst.funct10 >> 5) as u8 != LR_FUNCT5 {
+            engine.check_stack_guard(rs1, 1 << (inst.funct10 & 0b11))?;
+        }
+
         // Check if width is supported
         match (inst.funct10 & 0b111) as u8 {
             WORD_WIDTH => {
@@ -57,19 +67,20 @@ impl<M: Memory> Instruction<M> for Amo {
                     }
                     SC_FUNCT5 => {
                         // Store Conditional (mem[rs1] = rs2; rd = 0 if successful, 1 otherwise)
+                        // The reservation is consumed, even if it is for another address
                         match engine.memory_reservation.take() {
-                            Some((addr, old_value)) => {
-                                let value = i32::from_le_bytes(engine.memory.load(addr)?);
+                            Some((addr, old_value)) if addr == rs1 => {
+                                let value = i32::from_le_bytes(engine.memory.load(rs1)?);
                                 if value == old_value {
-                                    engine.memory.store(addr, rs2.to_le_bytes())?;
+                                    engine.memory.store(rs1, rs2.to_le_bytes())?;
                                     result = 0;
                                 } else {
                                     // Value has changed
                                     result = 1;
                                 }
                             }
-                            None => {
-                                // No reservation
+                            _ => {
+                                // No reservation (or reserved another address)
                                 result = 1;
                             }
                         }
@@ -113,15 +124,30 @@ impl<M: Memory> Instruction<M> for Amo {
                             .memory
                             .store(rs1, (result as u32).max(rs2 as u32).to_le_bytes())?;
                     }
+                    #[cfg(feature = "zacas")]
+                    AMOCAS_FUNCT5 => {
+                        // Atomic Compare-and-Swap (rd = mem[rs1]; mem[rs1] = rs2 if mem[rs1] == rd)
+                        result = i32::from_le_bytes(engine.memory.load(rs1)?);
+                        if result == engine.registers.get_decoded(inst.rd)? {
+                            engine.memory.store(rs1, rs2.to_le_bytes())?;
+                        }
+                    }
                     _ => return Err(EmbiveError::InvalidInstruction),
                 }
             }
+            #[cfg(feature = "zacas")]
+            DOUBLE_WIDTH if (inst.funct10 >> 5) as u8 == AMOCAS_FUNCT5 => {
+                // 64 bits, register pairs
+                amocas_d(inst, rs1, engine)?;
+                engine.program_counter = engine.program_counter.wrapping_add(INSTRUCTION_SIZE);
+                return Ok(true);
+            }
             _ => return Err(EmbiveError::InvalidInstruction),
         }
 
         // Store the result in the destination register
         if inst.rd != 0 {
-            let rd = engine.registers.get_mut(inst.rd)?;
+            let rd = engine.registers.get_decoded_mut(inst.rd)?;
             *rd = result;
         }
 
@@ -133,6 +159,62 @@ impl<M: Memory> Instruction<M> for Amo {
     }
 }
 
+/// Get a register pair (`x[index + 1]:x[index]`), the `x0` pair reads as zero.
+///
+/// Arguments:
+/// - `index`: Even register index.
+/// - `engine`: Embive engine.
+#[cfg(feature = "zacas")]
+#[inline]
+fn get_pair<M: Memory>(index: usize, engine: &Engine<M>) -> Result<u64, EmbiveError> {
+    if index == 0 {
+        return Ok(0);
+    }
+
+    let low = engine.registers.get_decoded(index)? as u32;
+    let high = engine.registers.get_decoded(index + 1)? as u32;
+    Ok(((high as u64) << 32) | low as u64)
+}
+
+/// Atomic Compare-and-Swap Doubleword, on register pairs
+/// (rd:rd+1 = mem[rs1]; mem[rs1] = rs2:rs2+1 if mem[rs1] == rd:rd+1).
+///
+/// Arguments:
+/// - `inst`: Decoded instruction.
+/// - `address`: Memory address (`rs1`).
+/// - `engine`: Embive engine.
+///
+/// Returns:
+/// - `Ok(())`: Executed.
+/// - `Err(EmbiveError)`: Odd register pair (reserved encoding) or memory error.
+#[cfg(feature = "zacas")]
+#[inline]
+fn amocas_d<M: Memory>(
+    inst: TypeR,
+    address: u32,
+    engine: &mut Engine<M>,
+) -> Result<(), EmbiveError> {
+    if inst.rd % 2 != 0 || inst.rs2 % 2 != 0 {
+        return Err(EmbiveError::InvalidInstruction);
+    }
+
+    let expected = get_pair(inst.rd, engine)?;
+    let desired = get_pair(inst.rs2, engine)?;
+
+    let value = u64::from_le_bytes(engine.memory.load(address)?);
+    if value == expected {
+        engine.memory.store(address, desired.to_le_bytes())?;
+    }
+
+    // The x0 pair is not written
+    if inst.rd != 0 {
+        *engine.registers.get_decoded_mut(inst.rd)? = value as i32;
+        *engine.registers.get_decoded_mut(inst.rd + 1)? = (value >> 32) as i32;
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -207,7 +289,7 @@ mod tests {
         assert_eq!(result, Ok(true));
 
         assert_eq!(*engine.registers.get_mut(1).unwrap(), 14);
-        assert_eq!(engine.memory_reservation, Some((RAM_OFFSET as u32, 14)));
+        assert_eq!(engine.memory_reservation, Some((RAM_OFFSET, 14)));
     }
 
     #[test]
@@ -227,7 +309,7 @@ mod tests {
         *engine.registers.get_mut(2).unwrap() = 2;
         *engine.registers.get_mut(3).unwrap() = RAM_OFFSET as i32;
 
-        engine.memory_reservation = Some((RAM_OFFSET as u32, 14));
+        engine.memory_reservation = Some((RAM_OFFSET, 14));
 
         let result = Amo::decode_execute(amo.into(), &mut engine);
         assert_eq!(result, Ok(true));
@@ -236,6 +318,34 @@ mod tests {
         assert_eq!(i32::from_le_bytes(ram), 2);
     }
 
+    #[test]
+    fn test_sc_other_address() {
+        let mut ram = [0; 8];
+
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+
+        let amo = TypeR {
+            rd: 1,
+            rs1: 3,
+            rs2: 2,
+            funct10: WORD_WIDTH as u16 | ((SC_FUNCT5 as u16) << 5),
+        };
+
+        *engine.registers.get_mut(2).unwrap() = 2;
+        *engine.registers.get_mut(3).unwrap() = (RAM_OFFSET + 4) as i32;
+
+        engine.memory_reservation = Some((RAM_OFFSET, 0));
+
+        let result = Amo::decode_execute(amo.into(), &mut engine);
+        assert_eq!(result, Ok(true));
+
+        // Failed, and the reservation was consumed
+        assert_eq!(*engine.registers.get_mut(1).unwrap(), 1);
+        assert_eq!(engine.memory_reservation, None);
+        assert_eq!(ram, [0; 8]);
+    }
+
     #[test]
     fn test_amoxor() {
         let mut ram = 14i32.to_le_bytes();
@@ -310,7 +420,7 @@ mod tests {
 
     #[test]
     fn test_amomin() {
-        let mut ram = (-14 as i32).to_le_bytes();
+        let mut ram = (-14i32).to_le_bytes();
 
         let mut memory = SliceMemory::new(&[], &mut ram);
         let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
@@ -334,7 +444,7 @@ mod tests {
 
     #[test]
     fn test_amomax() {
-        let mut ram = (-14 as i32).to_le_bytes();
+        let mut ram = (-14i32).to_le_bytes();
 
         let mut memory = SliceMemory::new(&[], &mut ram);
         let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
@@ -358,7 +468,7 @@ mod tests {
 
     #[test]
     fn test_amominu() {
-        let mut ram = (-14 as i32).to_le_bytes();
+        let mut ram = (-14i32).to_le_bytes();
 
         let mut memory = SliceMemory::new(&[], &mut ram);
         let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
@@ -382,7 +492,7 @@ mod tests {
 
     #[test]
     fn test_amomaxu() {
-        let mut ram = (-14 as i32).to_le_bytes();
+        let mut ram = (-14i32).to_le_bytes();
 
         let mut memory = SliceMemory::new(&[], &mut ram);
         let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
@@ -403,4 +513,89 @@ mod tests {
         assert_eq!(*engine.registers.get_mut(1).unwrap(), -14);
         assert_eq!(i32::from_le_bytes(ram), -14);
     }
+
+    #[cfg(feature = "zacas")]
+    #[test]
+    fn test_amocas_w() {
+        let mut ram = 14i32.to_le_bytes();
+
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+
+        let amo = TypeR {
+            rd: 1,
+            rs1: 3,
+            rs2: 2,
+            funct10: WORD_WIDTH as u16 | ((AMOCAS_FUNCT5 as u16) << 5),
+        };
+
+        *engine.registers.get_mut(2).unwrap() = 2;
+        *engine.registers.get_mut(3).unwrap() = RAM_OFFSET as i32;
+
+        // Mismatch, memory unchanged
+        *engine.registers.get_mut(1).unwrap() = 13;
+        assert_eq!(Amo::decode_execute(amo.into(), &mut engine), Ok(true));
+        assert_eq!(*engine.registers.get_mut(1).unwrap(), 14);
+
+        // Match (rd now holds the loaded value)
+        assert_eq!(Amo::decode_execute(amo.into(), &mut engine), Ok(true));
+        assert_eq!(*engine.registers.get_mut(1).unwrap(), 14);
+        assert_eq!(i32::from_le_bytes(ram), 2);
+    }
+
+    #[cfg(feature = "zacas")]
+    #[test]
+    fn test_amocas_d() {
+        let mut ram = 0x1122_3344_5566_7788u64.to_le_bytes();
+
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+
+        let amo = |rd, rs2| TypeR {
+            rd,
+            rs1: 3,
+            rs2,
+            funct10: DOUBLE_WIDTH as u16 | ((AMOCAS_FUNCT5 as u16) << 5),
+        };
+
+        *engine.registers.get_mut(3).unwrap() = RAM_OFFSET as i32;
+        *engine.registers.get_mut(10).unwrap() = 0x5566_7788;
+        *engine.registers.get_mut(11).unwrap() = 0x1122_3344;
+        *engine.registers.get_mut(12).unwrap() = 1;
+        *engine.registers.get_mut(13).unwrap() = 2;
+
+        // Match
+        assert_eq!(
+            Amo::decode_execute(amo(10, 12).into(), &mut engine),
+            Ok(true)
+        );
+        assert_eq!(engine.registers.get(10), Ok(0x5566_7788));
+        assert_eq!(engine.registers.get(11), Ok(0x1122_3344));
+
+        // Mismatch, memory unchanged
+        assert_eq!(
+            Amo::decode_execute(amo(10, 12).into(), &mut engine),
+            Ok(true)
+        );
+        assert_eq!(engine.registers.get(10), Ok(1));
+        assert_eq!(engine.registers.get(11), Ok(2));
+
+        // x0 pair compares with zero, not written
+        assert_eq!(Amo::decode_execute(amo(0, 0).into(), &mut engine), Ok(true));
+        assert_eq!(engine.registers.get(1), Ok(0));
+
+        // Odd register pairs are reserved
+        assert_eq!(
+            Amo::decode_execute(amo(11, 12).into(), &mut engine),
+            Err(EmbiveError::InvalidInstruction)
+        );
+        assert_eq!(
+            Amo::decode_execute(amo(10, 13).into(), &mut engine),
+            Err(EmbiveError::InvalidInstruction)
+        );
+        assert_eq!(
+            engine.memory.load(RAM_OFFSET).map(u64::from_le_bytes),
+            Ok(0x0000_0002_0000_0001)
+        );
+    }
 }
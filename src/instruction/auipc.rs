@@ -1,7 +1,7 @@
-use crate::engine::Engine;
+use crate::engine::{Engine, Hint};
 use crate::error::EmbiveError;
 use crate::instruction::format::TypeU;
-use crate::instruction::{Instruction, INSTRUCTION_SIZE};
+use crate::instruction::{hint, Instruction, INSTRUCTION_SIZE};
 use crate::memory::Memory;
 
 /// Add Upper Immediate to Program Counter
@@ -11,15 +11,17 @@ use crate::memory::Memory;
 pub struct Auipc {}
 
 impl<M: Memory> Instruction<M> for Auipc {
-    #[inline(always)]
-    fn decode_execute(data: u32, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
-        let inst = TypeU::from(data);
+    type Operands = TypeU;
 
+    #[inline(always)]
+    fn execute(inst: TypeU, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
         if inst.rd != 0 {
-            // rd = 0 means its a HINT instruction, just ignore it.
             // Load the immediate value + pc into the register.
-            let reg = engine.registers.get_mut(inst.rd)?;
+            let reg = engine.registers.get_decoded_mut(inst.rd)?;
             *reg = engine.program_counter.wrapping_add_signed(inst.imm) as i32;
+        } else {
+            // rd = 0 means its a HINT instruction, just report it.
+            hint(engine, Hint::Reserved);
         }
 
         // Go to next instruction
@@ -4,12 +4,12 @@ use crate::instruction::format::TypeB;
 use crate::instruction::{Instruction, INSTRUCTION_SIZE};
 use crate::memory::Memory;
 
-const BEQ_FUNCT3: u8 = 0b000;
-const BNE_FUNCT3: u8 = 0b001;
-const BLT_FUNCT3: u8 = 0b100;
-const BGE_FUNCT3: u8 = 0b101;
-const BLTU_FUNCT3: u8 = 0b110;
-const BGEU_FUNCT3: u8 = 0b111;
+pub(crate) const BEQ_FUNCT3: u8 = 0b000;
+pub(crate) const BNE_FUNCT3: u8 = 0b001;
+pub(crate) const BLT_FUNCT3: u8 = 0b100;
+pub(crate) const BGE_FUNCT3: u8 = 0b101;
+pub(crate) const BLTU_FUNCT3: u8 = 0b110;
+pub(crate) const BGEU_FUNCT3: u8 = 0b111;
 
 /// Branch OpCode
 /// Instructions: Beq, Bne, Blt, Bqe, Bltu, Bgeu
@@ -17,12 +17,12 @@ const BGEU_FUNCT3: u8 = 0b111;
 pub struct Branch {}
 
 impl<M: Memory> Instruction<M> for Branch {
-    #[inline(always)]
-    fn decode_execute(data: u32, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
-        let inst = TypeB::from(data);
+    type Operands = TypeB;
 
-        let rs1 = engine.registers.get(inst.rs1)?;
-        let rs2 = engine.registers.get(inst.rs2)?;
+    #[inline(always)]
+    fn execute(inst: TypeB, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
+        let rs1 = engine.registers.get_decoded(inst.rs1)?;
+        let rs2 = engine.registers.get_decoded(inst.rs2)?;
 
         let branch = match inst.funct3 {
             BEQ_FUNCT3 => rs1 == rs2,
@@ -34,13 +34,13 @@ impl<M: Memory> Instruction<M> for Branch {
             _ => return Err(EmbiveError::InvalidInstruction),
         };
 
-        engine.program_counter = if branch {
-            // Branch to new address
-            engine.program_counter.wrapping_add_signed(inst.imm)
+        // Branch to new address or go to next instruction (select, no host branch)
+        let offset = if branch {
+            inst.imm
         } else {
-            // Go to next instruction
-            engine.program_counter.wrapping_add(INSTRUCTION_SIZE)
+            INSTRUCTION_SIZE as i32
         };
+        engine.program_counter = engine.program_counter.wrapping_add_signed(offset);
 
         Ok(true)
     }
@@ -0,0 +1,401 @@
+use crate::engine::Engine;
+use crate::error::EmbiveError;
+use crate::instruction::format::{TypeI, TypeR, TypeS, TypeU};
+use crate::instruction::{
+    decode_execute, Instruction, COMPRESSED_INSTRUCTION_SIZE, LOAD_OPCODE, LUI_OPCODE,
+    OP_IMM_OPCODE, OP_OPCODE, STORE_OPCODE, SYSTEM_OPCODE,
+};
+use crate::memory::Memory;
+use crate::register::Register;
+
+/// Stack pointer register (implicit in `c.*sp` instructions).
+const SP: usize = Register::SP as usize;
+/// Return address register (link register of `c.jal` and `c.jalr`).
+const RA: usize = Register::RA as usize;
+
+/// Compressed Instructions (RV32C)
+/// Format: CR, CI, CSS, CIW, CL, CS, CA, CB and CJ (16 bits).
+/// Action: Control transfers (`c.j`, `c.jal`, `c.jr`, `c.jalr`, `c.beqz`, `c.bnez`) are executed directly,
+/// every other instruction is expanded to its 32-bit equivalent.
+pub struct Compressed {}
+
+impl<M: Memory> Instruction<M> for Compressed {
+    type Operands = u32;
+
+    #[inline]
+    fn execute(data: u32, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
+        let data = data & 0xFFFF;
+        let funct3 = data >> 13;
+        let next = engine
+            .program_counter
+            .wrapping_add(COMPRESSED_INSTRUCTION_SIZE);
+
+        match (data & 0b11, funct3) {
+            // c.jal, c.j
+            (0b01, 0b001 | 0b101) => {
+                if funct3 == 0b001 {
+                    *engine.registers.get_decoded_mut(RA)? = next as i32;
+                }
+                engine.program_counter = engine.program_counter.wrapping_add_signed(cj_imm(data));
+                Ok(true)
+            }
+            // c.beqz, c.bnez
+            (0b01, 0b110 | 0b111) => {
+                let rs1 = engine.registers.get_decoded(compact(data >> 7))?;
+                engine.program_counter = if (rs1 == 0) == (funct3 == 0b110) {
+                    engine.program_counter.wrapping_add_signed(cb_imm(data))
+                } else {
+                    next
+                };
+                Ok(true)
+            }
+            // c.jr, c.jalr (c.mv, c.add and c.ebreak are expanded)
+            (0b10, 0b100) if (data >> 2) & 0b1_1111 == 0 && (data >> 7) & 0b1_1111 != 0 => {
+                let target = engine.registers.get_decoded(reg(data >> 7))? as u32 & !1;
+                if data & (1 << 12) != 0 {
+                    *engine.registers.get_decoded_mut(RA)? = next as i32;
+                }
+                engine.program_counter = target;
+                Ok(true)
+            }
+            _ => {
+                let expanded = expand(data).ok_or(EmbiveError::InvalidInstruction)?;
+                let ret = decode_execute(engine, expanded)?;
+
+                // Expanded instructions advance the program counter by 4 bytes
+                engine.program_counter = next;
+                Ok(ret)
+            }
+        }
+    }
+}
+
+/// Full register index (`rd`, `rs1` or `rs2`, 5 bits).
+#[inline(always)]
+pub(crate) fn reg(bits: u32) -> usize {
+    (bits & 0b1_1111) as usize
+}
+
+/// Compact register index (`rd'`, `rs1'` or `rs2'`, 3 bits, `x8` to `x15`).
+#[inline(always)]
+pub(crate) fn compact(bits: u32) -> usize {
+    (bits & 0b111) as usize + 8
+}
+
+/// Extract a bit field.
+#[inline(always)]
+fn bits(data: u32, high: u32, low: u32) -> u32 {
+    (data >> low) & ((1 << (high - low + 1)) - 1)
+}
+
+/// Sign-extend a value from a bit width.
+#[inline(always)]
+fn sign_extend(value: u32, width: u32) -> i32 {
+    ((value << (32 - width)) as i32) >> (32 - width)
+}
+
+/// CI-format 6-bit immediate (`imm[5]` = bit 12, `imm[4:0]` = bits 6:2), sign-extended.
+#[inline(always)]
+fn ci_imm(data: u32) -> i32 {
+    sign_extend((bits(data, 12, 12) << 5) | bits(data, 6, 2), 6)
+}
+
+/// CJ-format jump offset, sign-extended.
+#[inline(always)]
+pub(crate) fn cj_imm(data: u32) -> i32 {
+    let offset = (bits(data, 12, 12) << 11)
+        | (bits(data, 11, 11) << 4)
+        | (bits(data, 10, 9) << 8)
+        | (bits(data, 8, 8) << 10)
+        | (bits(data, 7, 7) << 6)
+        | (bits(data, 6, 6) << 7)
+        | (bits(data, 5, 3) << 1)
+        | (bits(data, 2, 2) << 5);
+    sign_extend(offset, 12)
+}
+
+/// CB-format branch offset, sign-extended.
+#[inline(always)]
+pub(crate) fn cb_imm(data: u32) -> i32 {
+    let offset = (bits(data, 12, 12) << 8)
+        | (bits(data, 11, 10) << 3)
+        | (bits(data, 6, 5) << 6)
+        | (bits(data, 4, 3) << 1)
+        | (bits(data, 2, 2) << 5);
+    sign_extend(offset, 9)
+}
+
+/// CL/CS-format word offset (`c.lw`, `c.sw`).
+#[inline(always)]
+fn word_offset(data: u32) -> i32 {
+    ((bits(data, 12, 10) << 3) | (bits(data, 6, 6) << 2) | (bits(data, 5, 5) << 6)) as i32
+}
+
+/// I-Type instruction.
+#[inline(always)]
+fn i_type(opcode: u8, funct3: u8, rd: usize, rs1: usize, imm: i32) -> u32 {
+    u32::from(TypeI {
+        rd,
+        rs1,
+        imm,
+        funct3,
+    }) | opcode as u32
+}
+
+/// R-Type instruction.
+#[inline(always)]
+fn r_type(funct10: u16, rd: usize, rs1: usize, rs2: usize) -> u32 {
+    u32::from(TypeR {
+        rd,
+        rs1,
+        rs2,
+        funct10,
+    }) | OP_OPCODE as u32
+}
+
+/// Expand a compressed instruction (other than control transfers) to its 32-bit equivalent.
+///
+/// Arguments:
+/// - `data`: Compressed instruction (lower 16 bits).
+///
+/// Returns:
+/// - `Some(u32)`: The equivalent 32-bit instruction.
+/// - `None`: Illegal or reserved instruction (or not supported, ex.: floating point loads and stores).
+pub(crate) fn expand(data: u32) -> Option<u32> {
+    let funct3 = data >> 13;
+    let rd = reg(data >> 7);
+    let rs2 = reg(data >> 2);
+    let rd_compact = compact(data >> 2);
+    let rs1_compact = compact(data >> 7);
+
+    let expanded = match (data & 0b11, funct3) {
+        // c.addi4spn: addi rd', x2, nzuimm
+        (0b00, 0b000) => {
+            let imm = (bits(data, 12, 11) << 4)
+                | (bits(data, 10, 7) << 6)
+                | (bits(data, 6, 6) << 2)
+                | (bits(data, 5, 5) << 3);
+            if imm == 0 {
+                // Includes the all-zero illegal instruction
+                return None;
+            }
+            i_type(OP_IMM_OPCODE, 0b000, rd_compact, SP, imm as i32)
+        }
+        // c.lw: lw rd', offset(rs1')
+        (0b00, 0b010) => i_type(
+            LOAD_OPCODE,
+            0b010,
+            rd_compact,
+            rs1_compact,
+            word_offset(data),
+        ),
+        // c.sw: sw rs2', offset(rs1')
+        (0b00, 0b110) => {
+            u32::from(TypeS {
+                rs1: rs1_compact,
+                rs2: rd_compact,
+                imm: word_offset(data),
+                funct3: 0b010,
+            }) | STORE_OPCODE as u32
+        }
+        // c.addi (c.nop): addi rd, rd, imm
+        (0b01, 0b000) => i_type(OP_IMM_OPCODE, 0b000, rd, rd, ci_imm(data)),
+        // c.li: addi rd, x0, imm
+        (0b01, 0b010) => i_type(OP_IMM_OPCODE, 0b000, rd, 0, ci_imm(data)),
+        // c.addi16sp: addi x2, x2, nzimm
+        (0b01, 0b011) if rd == SP => {
+            let imm = (bits(data, 12, 12) << 9)
+                | (bits(data, 6, 6) << 4)
+                | (bits(data, 5, 5) << 6)
+                | (bits(data, 4, 3) << 7)
+                | (bits(data, 2, 2) << 5);
+            if imm == 0 {
+                return None;
+            }
+            i_type(OP_IMM_OPCODE, 0b000, SP, SP, sign_extend(imm, 10))
+        }
+        // c.lui: lui rd, nzimm
+        (0b01, 0b011) => {
+            let imm = ci_imm(data);
+            if imm == 0 {
+                return None;
+            }
+            u32::from(TypeU { rd, imm: imm << 12 }) | LUI_OPCODE as u32
+        }
+        // c.srli, c.srai, c.andi, c.sub, c.xor, c.or, c.and
+        (0b01, 0b100) => {
+            let shamt = bits(data, 6, 2) as i32;
+            match bits(data, 11, 10) {
+                // shamt[5] must be zero on RV32
+                0b00 if data & (1 << 12) == 0 => {
+                    i_type(OP_IMM_OPCODE, 0b101, rs1_compact, rs1_compact, shamt)
+                }
+                0b01 if data & (1 << 12) == 0 => i_type(
+                    OP_IMM_OPCODE,
+                    0b101,
+                    rs1_compact,
+                    rs1_compact,
+                    shamt | 0b0100_0000_0000,
+                ),
+                0b10 => i_type(OP_IMM_OPCODE, 0b111, rs1_compact, rs1_compact, ci_imm(data)),
+                0b11 if data & (1 << 12) == 0 => {
+                    let funct10 = match bits(data, 6, 5) {
+                        0b00 => 0b010_0000 << 3, // sub
+                        0b01 => 0b100,           // xor
+                        0b10 => 0b110,           // or
+                        _ => 0b111,              // and
+                    };
+                    r_type(funct10, rs1_compact, rs1_compact, rd_compact)
+                }
+                _ => return None,
+            }
+        }
+        // c.slli: slli rd, rd, shamt (shamt[5] must be zero on RV32)
+        (0b10, 0b000) if data & (1 << 12) == 0 => {
+            i_type(OP_IMM_OPCODE, 0b001, rd, rd, bits(data, 6, 2) as i32)
+        }
+        // c.lwsp: lw rd, offset(x2)
+        (0b10, 0b010) if rd != 0 => {
+            let imm = (bits(data, 12, 12) << 5) | (bits(data, 6, 4) << 2) | (bits(data, 3, 2) << 6);
+            i_type(LOAD_OPCODE, 0b010, rd, SP, imm as i32)
+        }
+        // c.mv, c.ebreak, c.add (c.jr and c.jalr are executed directly)
+        (0b10, 0b100) => match (data & (1 << 12) != 0, rd, rs2) {
+            (false, _, 1..) => r_type(0, rd, 0, rs2),
+            (true, 0, 0) => i_type(SYSTEM_OPCODE, 0b000, 0, 0, 1),
+            (true, _, 1..) => r_type(0, rd, rd, rs2),
+            _ => return None,
+        },
+        // c.swsp: sw rs2, offset(x2)
+        (0b10, 0b110) => {
+            let imm = (bits(data, 12, 9) << 2) | (bits(data, 8, 7) << 6);
+            u32::from(TypeS {
+                rs1: SP,
+                rs2,
+                imm: imm as i32,
+                funct3: 0b010,
+            }) | STORE_OPCODE as u32
+        }
+        _ => return None,
+    };
+
+    Some(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{SliceMemory, RAM_OFFSET};
+
+    fn execute(engine: &mut Engine<SliceMemory>, data: u16) -> Result<bool, EmbiveError> {
+        Compressed::decode_execute(data as u32, engine)
+    }
+
+    #[test]
+    fn test_expand() {
+        // Checked against the LLVM assembler
+        assert_eq!(expand(0x0808), Some(0x0101_0513)); // c.addi4spn a0, sp, 16
+        assert_eq!(expand(0x41c8), Some(0x0045_a503)); // c.lw a0, 4(a1)
+        assert_eq!(expand(0xc1c8), Some(0x00a5_a223)); // c.sw a0, 4(a1)
+        assert_eq!(expand(0x157d), Some(0xfff5_0513)); // c.addi a0, -1
+        assert_eq!(expand(0x4515), Some(0x0050_0513)); // c.li a0, 5
+        assert_eq!(expand(0x7179), Some(0xfd01_0113)); // c.addi16sp sp, -48
+        assert_eq!(expand(0x6505), Some(0x0000_1537)); // c.lui a0, 1
+        assert_eq!(expand(0x7501), Some(0xfffe_0537)); // c.lui a0, 0xfffe0
+        assert_eq!(expand(0x8105), Some(0x0015_5513)); // c.srli a0, 1
+        assert_eq!(expand(0x857d), Some(0x41f5_5513)); // c.srai a0, 31
+        assert_eq!(expand(0x893d), Some(0x00f5_7513)); // c.andi a0, 15
+        assert_eq!(expand(0x8d0d), Some(0x40b5_0533)); // c.sub a0, a1
+        assert_eq!(expand(0x8d2d), Some(0x00b5_4533)); // c.xor a0, a1
+        assert_eq!(expand(0x8d4d), Some(0x00b5_6533)); // c.or a0, a1
+        assert_eq!(expand(0x8d6d), Some(0x00b5_7533)); // c.and a0, a1
+        assert_eq!(expand(0x050e), Some(0x0035_1513)); // c.slli a0, 3
+        assert_eq!(expand(0x4532), Some(0x00c1_2503)); // c.lwsp a0, 12(sp)
+        assert_eq!(expand(0x852e), Some(0x00b0_0533)); // c.mv a0, a1
+        assert_eq!(expand(0x952e), Some(0x00b5_0533)); // c.add a0, a1
+        assert_eq!(expand(0x9002), Some(0x0010_0073)); // c.ebreak
+        assert_eq!(expand(0xc62a), Some(0x00a1_2623)); // c.swsp a0, 12(sp)
+
+        // Illegal, reserved and unsupported
+        assert_eq!(expand(0x0000), None); // Illegal instruction
+        assert_eq!(expand(0x6101), None); // c.addi16sp sp, 0
+        assert_eq!(expand(0x6501), None); // c.lui a0, 0
+        assert_eq!(expand(0x9105), None); // c.srli a0, 33 (RV64)
+        assert_eq!(expand(0x9d0d), None); // c.subw a0, a1 (RV64)
+        assert_eq!(expand(0x4002), None); // c.lwsp zero, 0(sp)
+        assert_eq!(expand(0x2108), None); // c.fld fa0, 0(a0)
+    }
+
+    #[test]
+    fn test_execute() {
+        let mut ram = [0; 16];
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        engine.registers.inner[Register::SP as usize] = RAM_OFFSET as i32;
+
+        assert_eq!(execute(&mut engine, 0x4515), Ok(true)); // c.li a0, 5
+        assert_eq!(execute(&mut engine, 0xc62a), Ok(true)); // c.swsp a0, 12(sp)
+        assert_eq!(execute(&mut engine, 0x157d), Ok(true)); // c.addi a0, -1
+        assert_eq!(execute(&mut engine, 0x45b2), Ok(true)); // c.lwsp a1, 12(sp)
+        assert_eq!(engine.registers.get(Register::A0 as usize), Ok(4));
+        assert_eq!(engine.registers.get(Register::A1 as usize), Ok(5));
+        assert_eq!(engine.program_counter, 8);
+
+        assert_eq!(execute(&mut engine, 0x9002), Ok(false)); // c.ebreak
+        assert_eq!(engine.program_counter, 10);
+        assert_eq!(
+            execute(&mut engine, 0x0000),
+            Err(EmbiveError::InvalidInstruction)
+        );
+    }
+
+    #[test]
+    fn test_control_transfer() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        engine.program_counter = 0x100;
+
+        assert_eq!(execute(&mut engine, 0x2011), Ok(true)); // c.jal 4
+        assert_eq!(engine.program_counter, 0x104);
+        assert_eq!(engine.registers.get(Register::RA as usize), Ok(0x102));
+
+        assert_eq!(execute(&mut engine, 0xbff5), Ok(true)); // c.j -4
+        assert_eq!(engine.program_counter, 0x100);
+
+        // a0 = 0
+        assert_eq!(execute(&mut engine, 0xe111), Ok(true)); // c.bnez a0, 4
+        assert_eq!(engine.program_counter, 0x102);
+        assert_eq!(execute(&mut engine, 0xc111), Ok(true)); // c.beqz a0, 4
+        assert_eq!(engine.program_counter, 0x106);
+        assert_eq!(execute(&mut engine, 0xdd6d), Ok(true)); // c.beqz a0, -6
+        assert_eq!(engine.program_counter, 0x100);
+
+        engine.registers.inner[Register::A1 as usize] = 0x200;
+        assert_eq!(execute(&mut engine, 0x9582), Ok(true)); // c.jalr a1
+        assert_eq!(engine.program_counter, 0x200);
+        assert_eq!(engine.registers.get(Register::RA as usize), Ok(0x102));
+        assert_eq!(execute(&mut engine, 0x8082), Ok(true)); // c.jr ra (ret)
+        assert_eq!(engine.program_counter, 0x102);
+        assert_eq!(
+            execute(&mut engine, 0x8002),
+            Err(EmbiveError::InvalidInstruction)
+        ); // c.jr zero
+    }
+
+    #[test]
+    fn test_mixed_fetch() {
+        let code = &[
+            0x15, 0x45, // c.li a0, 5
+            0x13, 0x05, 0x15, 0x00, // addi a0, a0, 1 (halfword aligned)
+            0x85, 0x05, // c.addi a1, 1
+            0x02, 0x90, // c.ebreak (last halfword of the code)
+        ];
+        let mut memory = SliceMemory::new(code, &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(Register::A0 as usize), Ok(6));
+        assert_eq!(engine.registers.get(Register::A1 as usize), Ok(1));
+        assert_eq!(engine.program_counter, 10);
+    }
+}
@@ -224,7 +224,7 @@ mod tests {
         assert_eq!(parsed.rd, 3);
         assert_eq!(parsed.funct3, 0);
         assert_eq!(parsed.rs1, 2);
-        assert_eq!(parsed.imm as i32, -1000);
+        assert_eq!(parsed.imm, -1000);
     }
 
     #[test]
@@ -236,7 +236,7 @@ mod tests {
         assert_eq!(parsed.rd, 1);
         assert_eq!(parsed.funct3, 4);
         assert_eq!(parsed.rs1, 0);
-        assert_eq!(parsed.imm as i32, 2042);
+        assert_eq!(parsed.imm, 2042);
     }
 
     #[test]
@@ -11,13 +11,13 @@ use crate::memory::Memory;
 pub struct Jal {}
 
 impl<M: Memory> Instruction<M> for Jal {
-    #[inline(always)]
-    fn decode_execute(data: u32, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
-        let inst = TypeJ::from(data);
+    type Operands = TypeJ;
 
+    #[inline(always)]
+    fn execute(inst: TypeJ, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
         // Load pc + instruction size into the destination register.
         if inst.rd != 0 {
-            let reg = engine.registers.get_mut(inst.rd)?;
+            let reg = engine.registers.get_decoded_mut(inst.rd)?;
             *reg = engine.program_counter.wrapping_add(INSTRUCTION_SIZE) as i32;
         }
 
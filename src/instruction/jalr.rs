@@ -7,25 +7,25 @@ use crate::memory::Memory;
 /// Jump And Link Reg
 /// Both an Opcode and an Instruction
 /// Format: I-Type.
-/// Action: rd = PC+4; PC = rs1 + imm
+/// Action: rd = PC+4; PC = (rs1 + imm) & !1
 pub struct Jalr {}
 
 impl<M: Memory> Instruction<M> for Jalr {
-    #[inline(always)]
-    fn decode_execute(data: u32, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
-        let inst = TypeI::from(data);
+    type Operands = TypeI;
 
+    #[inline(always)]
+    fn execute(inst: TypeI, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
         // Get the value of the source register.
-        let rs1 = engine.registers.get(inst.rs1)?;
+        let rs1 = engine.registers.get_decoded(inst.rs1)?;
 
         // Load pc + instruction size into the destination register (if not unconditional).
         if inst.rd != 0 {
-            let rd = engine.registers.get_mut(inst.rd)?;
+            let rd = engine.registers.get_decoded_mut(inst.rd)?;
             *rd = engine.program_counter.wrapping_add(INSTRUCTION_SIZE) as i32;
         }
 
-        // Set the program counter to the new address.
-        engine.program_counter = (rs1 as u32).wrapping_add_signed(inst.imm);
+        // Set the program counter to the new address (least-significant bit cleared).
+        engine.program_counter = (rs1 as u32).wrapping_add_signed(inst.imm) & !1;
 
         // Continue execution
         Ok(true)
@@ -97,4 +97,22 @@ mod tests {
         assert_eq!(*engine.registers.get_mut(1).unwrap(), 0x5);
         assert_eq!(engine.program_counter, 0x300);
     }
+
+    #[test]
+    fn test_jalr_odd_target() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        let jalr = TypeI {
+            funct3: 0x0,
+            rd: 1,
+            rs1: 2,
+            imm: 0x101,
+        };
+
+        *engine.registers.get_mut(2).unwrap() = 0x200;
+
+        let result = Jalr::decode_execute(jalr.into(), &mut engine);
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.program_counter, 0x300);
+    }
 }
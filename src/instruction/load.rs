@@ -4,11 +4,11 @@ use crate::instruction::format::TypeI;
 use crate::instruction::{Instruction, INSTRUCTION_SIZE};
 use crate::memory::Memory;
 
-const LB_FUNCT3: u8 = 0b000;
-const LH_FUNCT3: u8 = 0b001;
-const LW_FUNCT3: u8 = 0b010;
-const LBU_FUNCT3: u8 = 0b100;
-const LHU_FUNCT3: u8 = 0b101;
+pub(crate) const LB_FUNCT3: u8 = 0b000;
+pub(crate) const LH_FUNCT3: u8 = 0b001;
+pub(crate) const LW_FUNCT3: u8 = 0b010;
+pub(crate) const LBU_FUNCT3: u8 = 0b100;
+pub(crate) const LHU_FUNCT3: u8 = 0b101;
 
 /// Load OpCode
 /// Instructions: Lb, Lh, Lw, Lbu, Lhu
@@ -16,11 +16,11 @@ const LHU_FUNCT3: u8 = 0b101;
 pub struct Load {}
 
 impl<M: Memory> Instruction<M> for Load {
-    #[inline(always)]
-    fn decode_execute(data: u32, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
-        let inst = TypeI::from(data);
+    type Operands = TypeI;
 
-        let rs1 = engine.registers.get(inst.rs1)?;
+    #[inline(always)]
+    fn execute(inst: TypeI, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
+        let rs1 = engine.registers.get_decoded(inst.rs1)?;
 
         let address = (rs1 as u32).wrapping_add_signed(inst.imm);
         let result = match inst.funct3 {
@@ -32,9 +32,11 @@ impl<M: Memory> Instruction<M> for Load {
             _ => return Err(EmbiveError::InvalidInstruction),
         };
 
-        // Store the result in the destination register
-        let rd = engine.registers.get_mut(inst.rd)?;
-        *rd = result;
+        // Store the result in the destination register (x0 is hardwired to zero)
+        if inst.rd != 0 {
+            let rd = engine.registers.get_decoded_mut(inst.rd)?;
+            *rd = result;
+        }
 
         // Go to next instruction
         engine.program_counter = engine.program_counter.wrapping_add(INSTRUCTION_SIZE);
@@ -269,4 +271,24 @@ mod tests {
         );
         assert_eq!(engine.program_counter, INSTRUCTION_SIZE);
     }
+
+    #[test]
+    fn test_load_x0() {
+        let mut ram = [0x12, 0x34];
+
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        let lhu = TypeI {
+            rd: 0,
+            rs1: 2,
+            imm: 0x0,
+            funct3: LHU_FUNCT3,
+        };
+        *engine.registers.get_mut(2).unwrap() = get_ram_addr();
+
+        let result = Load::decode_execute(lhu.into(), &mut engine);
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.registers.get(0), Ok(0));
+        assert_eq!(engine.program_counter, INSTRUCTION_SIZE);
+    }
 }
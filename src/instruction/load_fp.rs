@@ -0,0 +1,175 @@
+use crate::engine::Engine;
+use crate::error::EmbiveError;
+use crate::instruction::op_v::{check_group, current_vtype};
+use crate::instruction::{Instruction, INSTRUCTION_SIZE};
+use crate::memory::Memory;
+
+/// Unit-stride vector memory access (shared by [`LoadFp`] and [`super::store_fp::StoreFp`]).
+pub(crate) struct VectorAccess {
+    /// Destination (load) or source (store) register group.
+    pub vd: usize,
+    /// Base address (`rs1`).
+    pub address: u32,
+    /// Effective element width in bytes.
+    pub eew: usize,
+    /// Instruction is unmasked (`vm` = 1).
+    pub unmasked: bool,
+    /// Vector length (elements).
+    pub vl: usize,
+}
+
+impl VectorAccess {
+    /// Decode a unit-stride vector load/store (`vle{8,16,32}.v` / `vse{8,16,32}.v`).
+    ///
+    /// Arguments:
+    /// - `data`: `u32` value representing the instruction.
+    /// - `engine`: Embive engine.
+    ///
+    /// Returns:
+    /// - `Ok(VectorAccess)`: The decoded access.
+    /// - `Err(EmbiveError)`: Unsupported access (scalar floating point, strided, indexed, segment, etc.).
+    pub fn decode<M: Memory>(data: u32, engine: &Engine<M>) -> Result<Self, EmbiveError> {
+        let vd = ((data >> 7) & 0b1_1111) as usize;
+        let rs1 = ((data >> 15) & 0b1_1111) as usize;
+        let unmasked = (data >> 25) & 1 != 0;
+
+        let eew = match (data >> 12) & 0b111 {
+            0b000 => 1,
+            0b101 => 2,
+            0b110 => 4,
+            _ => return Err(EmbiveError::InvalidInstruction),
+        };
+
+        // nf = 0, mew = 0, mop = 0 (unit-stride), lumop/sumop = 0
+        if (data >> 26) != 0 || (data >> 20) & 0b1_1111 != 0 {
+            return Err(EmbiveError::InvalidInstruction);
+        }
+
+        let vtype = current_vtype(engine)?;
+        let group = vtype.group(eew).ok_or(EmbiveError::InvalidInstruction)?;
+        check_group(vd, group)?;
+
+        Ok(VectorAccess {
+            vd,
+            address: engine.registers.get_decoded(rs1)? as u32,
+            eew,
+            unmasked,
+            vl: engine.vector.vl as usize,
+        })
+    }
+}
+
+/// Vector Load OpCode (LOAD-FP)
+/// Instructions: vle8.v, vle16.v, vle32.v (unit-stride)
+/// Format: `nf | mew | mop | vm | lumop | rs1 | width | vd`.
+pub struct LoadFp {}
+
+impl<M: Memory> Instruction<M> for LoadFp {
+    type Operands = u32;
+
+    #[inline(always)]
+    fn execute(data: u32, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
+        let access = VectorAccess::decode(data, engine)?;
+
+        // Masked destination can't overlap the mask register
+        if !access.unmasked && access.vd == 0 {
+            return Err(EmbiveError::InvalidInstruction);
+        }
+
+        for i in 0..access.vl {
+            if !engine.vector.active(access.unmasked, i) {
+                continue;
+            }
+
+            let address = access.address.wrapping_add((i * access.eew) as u32);
+            let value = match access.eew {
+                1 => u8::from_le_bytes(engine.memory.load(address)?) as u32,
+                2 => u16::from_le_bytes(engine.memory.load(address)?) as u32,
+                _ => u32::from_le_bytes(engine.memory.load(address)?),
+            };
+            engine.vector.set_element(access.vd, i, access.eew, value)?;
+        }
+
+        // Go to next instruction
+        engine.program_counter = engine.program_counter.wrapping_add(INSTRUCTION_SIZE);
+
+        // Continue execution
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{SliceMemory, RAM_OFFSET};
+
+    /// Encode a unit-stride load.
+    fn vle(width: u32, vm: bool, rs1: u32, vd: u32) -> u32 {
+        ((vm as u32) << 25) | (rs1 << 15) | (width << 12) | (vd << 7) | 0b000_0111
+    }
+
+    #[test]
+    fn test_vle16() {
+        let mut ram = [1, 0, 2, 0, 3, 0, 4, 0];
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        engine.vector.vtype = 0b001_000; // e16, m1
+        engine.vector.vl = 3;
+        *engine.registers.get_mut(5).unwrap() = RAM_OFFSET as i32;
+
+        let result = LoadFp::decode_execute(vle(0b101, true, 5, 1), &mut engine);
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.program_counter, INSTRUCTION_SIZE);
+        assert_eq!(engine.vector.element(1, 0, 2), Ok(1));
+        assert_eq!(engine.vector.element(1, 2, 2), Ok(3));
+        assert_eq!(engine.vector.element(1, 3, 2), Ok(0)); // Tail undisturbed
+    }
+
+    #[test]
+    fn test_vle8_masked() {
+        let mut ram = [1, 2, 3, 4];
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        engine.vector.vtype = 0b000_000; // e8, m1
+        engine.vector.vl = 4;
+        engine.vector.get_mut(0).unwrap()[0] = 0b1010;
+        *engine.registers.get_mut(5).unwrap() = RAM_OFFSET as i32;
+
+        let result = LoadFp::decode_execute(vle(0b000, false, 5, 1), &mut engine);
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.vector.get(1).unwrap()[..4], [0, 2, 0, 4]);
+    }
+
+    #[test]
+    fn test_vle_out_of_bounds() {
+        let mut ram = [0; 4];
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        engine.vector.vtype = 0b010_000; // e32, m1
+        engine.vector.vl = 2;
+        *engine.registers.get_mut(5).unwrap() = RAM_OFFSET as i32;
+
+        let result = LoadFp::decode_execute(vle(0b110, true, 5, 1), &mut engine);
+        assert_eq!(result, Err(EmbiveError::InvalidMemoryAddress));
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        engine.vector.vtype = 0b010_000; // e32, m1
+
+        // flw (scalar floating point)
+        let result = LoadFp::decode_execute(vle(0b010, true, 5, 1), &mut engine);
+        assert_eq!(result, Err(EmbiveError::InvalidInstruction));
+
+        // Strided load
+        let result = LoadFp::decode_execute(vle(0b110, true, 5, 1) | (0b10 << 26), &mut engine);
+        assert_eq!(result, Err(EmbiveError::InvalidInstruction));
+
+        // vtype not configured
+        engine.vector.reset();
+        let result = LoadFp::decode_execute(vle(0b110, true, 5, 1), &mut engine);
+        assert_eq!(result, Err(EmbiveError::InvalidInstruction));
+    }
+}
@@ -1,36 +1,114 @@
-use crate::engine::Engine;
+use crate::engine::{CacheOp, Engine, Hint, CACHE_BLOCK_SIZE};
 use crate::error::EmbiveError;
 use crate::instruction::format::TypeI;
-use crate::instruction::{Instruction, INSTRUCTION_SIZE};
+use crate::instruction::{hint, Instruction, INSTRUCTION_SIZE};
 use crate::memory::Memory;
 
+pub(crate) const FENCE_FUNCT3: u8 = 0b000;
+pub(crate) const FENCE_I_FUNCT3: u8 = 0b001;
+pub(crate) const CBO_FUNCT3: u8 = 0b010;
+
+pub(crate) const FENCE_W: i32 = 0b0001;
+
+pub(crate) const CBO_INVAL_IMM: i32 = 0b0000;
+pub(crate) const CBO_CLEAN_IMM: i32 = 0b0001;
+pub(crate) const CBO_FLUSH_IMM: i32 = 0b0010;
+pub(crate) const CBO_ZERO_IMM: i32 = 0b0100;
+
 /// Miscellaneous Memory OpCode
+/// Instructions: FENCE, FENCE.I, CBO.INVAL, CBO.CLEAN, CBO.FLUSH, CBO.ZERO
+/// Hints: PAUSE (Zihintpause), reserved fences (no predecessor or successor)
 /// Format: I-Type.
-/// Action: Nothing (Not implemented / Not applicable)
+/// Action:
+/// - Fences: Nothing (Not applicable), `fence.i` drops the fetched code (with the `fetch_batch` feature)
+/// - Cache block management: Forwarded to the cache function (Nothing if not set)
+/// - Cache block zero: Zero the cache block (rs1 aligned down to [`CACHE_BLOCK_SIZE`])
 pub struct MiscMem {}
 
 impl<M: Memory> Instruction<M> for MiscMem {
+    type Operands = TypeI;
+
     #[inline(always)]
-    fn decode_execute(data: u32, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
-        let _inst = TypeI::from(data);
+    fn execute(inst: TypeI, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
+        if inst.funct3 == CBO_FUNCT3 {
+            if inst.rd != 0 {
+                return Err(EmbiveError::InvalidInstruction);
+            }
+
+            let address =
+                (engine.registers.get_decoded(inst.rs1)? as u32) & !(CACHE_BLOCK_SIZE - 1);
+            let op = match inst.imm {
+                CBO_INVAL_IMM => CacheOp::Invalidate,
+                CBO_CLEAN_IMM => CacheOp::Clean,
+                CBO_FLUSH_IMM => CacheOp::Flush,
+                CBO_ZERO_IMM => {
+                    // Zero the block
+                    engine.check_stack_guard(address, CACHE_BLOCK_SIZE)?;
+                    for offset in (0..CACHE_BLOCK_SIZE).step_by(4) {
+                        engine
+                            .memory
+                            .store(address.wrapping_add(offset), [0u8; 4])?;
+                    }
+                    return next(engine);
+                }
+                _ => return Err(EmbiveError::InvalidInstruction),
+            };
+
+            if let Some(cache_fn) = engine.config.cache_fn {
+                engine.invalidate_fetch();
+                cache_fn(op, address, engine.memory)?;
+            }
+        }
+
+        if inst.funct3 == FENCE_I_FUNCT3 {
+            // Instruction fetch fence, code may have been changed
+            engine.invalidate_fetch();
+        }
 
         // Fencing isn't applicable to this implementation.
         // This is a nop.
+        if inst.funct3 == FENCE_FUNCT3 && inst.rd == 0 && inst.rs1 == 0 {
+            let fm = (inst.imm >> 8) & 0b1111;
+            let pred = (inst.imm >> 4) & 0b1111;
+            let succ = inst.imm & 0b1111;
+            if fm == 0 && (pred == 0 || succ == 0) {
+                // Fence without predecessor or successor set is a HINT
+                let kind = if pred == FENCE_W && succ == 0 {
+                    Hint::Pause
+                } else {
+                    Hint::Reserved
+                };
+                hint(engine, kind);
+            }
+        }
 
-        // Go to next instruction
-        engine.program_counter = engine.program_counter.wrapping_add(INSTRUCTION_SIZE);
-
-        // Continue execution
-        Ok(true)
+        next(engine)
     }
 }
 
+/// Go to next instruction and continue execution.
+#[inline(always)]
+fn next<M: Memory>(engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
+    engine.program_counter = engine.program_counter.wrapping_add(INSTRUCTION_SIZE);
+    Ok(true)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::memory::SliceMemory;
+    use crate::engine::Config;
+    use crate::memory::{SliceMemory, RAM_OFFSET};
 
     use super::*;
 
+    fn cbo(imm: i32, rs1: usize) -> TypeI {
+        TypeI {
+            rd: 0,
+            rs1,
+            imm,
+            funct3: CBO_FUNCT3,
+        }
+    }
+
     #[test]
     fn test_misc_mem() {
         let mut memory = SliceMemory::new(&[], &mut []);
@@ -47,4 +125,110 @@ mod tests {
         assert_eq!(result, Ok(true));
         assert_eq!(engine.program_counter, 0x1 + INSTRUCTION_SIZE);
     }
+
+    #[test]
+    fn test_cbo_zero() {
+        let mut ram = [0xFF; 2 * CACHE_BLOCK_SIZE as usize];
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        *engine.registers.get_mut(1).unwrap() = (RAM_OFFSET + CACHE_BLOCK_SIZE + 5) as i32;
+
+        let result = MiscMem::decode_execute(cbo(CBO_ZERO_IMM, 1).into(), &mut engine);
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.program_counter, INSTRUCTION_SIZE);
+        assert!(ram[..CACHE_BLOCK_SIZE as usize].iter().all(|&b| b == 0xFF));
+        assert!(ram[CACHE_BLOCK_SIZE as usize..].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_cbo_zero_out_of_bounds() {
+        let mut ram = [0; 4];
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        *engine.registers.get_mut(1).unwrap() = RAM_OFFSET as i32;
+
+        let result = MiscMem::decode_execute(cbo(CBO_ZERO_IMM, 1).into(), &mut engine);
+        assert_eq!(result, Err(EmbiveError::InvalidMemoryAddress));
+    }
+
+    #[test]
+    fn test_cbo_no_cache_fn() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+
+        for imm in [CBO_INVAL_IMM, CBO_CLEAN_IMM, CBO_FLUSH_IMM] {
+            let result = MiscMem::decode_execute(cbo(imm, 1).into(), &mut engine);
+            assert_eq!(result, Ok(true));
+        }
+        assert_eq!(engine.program_counter, 3 * INSTRUCTION_SIZE);
+    }
+
+    #[test]
+    fn test_cbo_cache_fn() {
+        fn cache_fn(
+            op: CacheOp,
+            address: u32,
+            memory: &mut SliceMemory,
+        ) -> Result<(), EmbiveError> {
+            match op {
+                CacheOp::Flush => memory.store(address, [0x1u8]),
+                CacheOp::Clean => Err(EmbiveError::Custom("clean")),
+                CacheOp::Invalidate => Ok(()),
+            }
+        }
+
+        let mut ram = [0; 2 * CACHE_BLOCK_SIZE as usize];
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let config = Config::default().with_cache_fn(Some(cache_fn));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+        *engine.registers.get_mut(1).unwrap() = (RAM_OFFSET + CACHE_BLOCK_SIZE + 1) as i32;
+
+        let result = MiscMem::decode_execute(cbo(CBO_FLUSH_IMM, 1).into(), &mut engine);
+        assert_eq!(result, Ok(true));
+        let result = MiscMem::decode_execute(cbo(CBO_CLEAN_IMM, 1).into(), &mut engine);
+        assert_eq!(result, Err(EmbiveError::Custom("clean")));
+        assert_eq!(ram[CACHE_BLOCK_SIZE as usize], 0x1);
+    }
+
+    #[test]
+    fn test_cbo_invalid() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+
+        let result = MiscMem::decode_execute(cbo(0b11, 1).into(), &mut engine);
+        assert_eq!(result, Err(EmbiveError::InvalidInstruction));
+
+        let mut inst = cbo(CBO_CLEAN_IMM, 1);
+        inst.rd = 1;
+        let result = MiscMem::decode_execute(inst.into(), &mut engine);
+        assert_eq!(result, Err(EmbiveError::InvalidInstruction));
+    }
+
+    #[test]
+    fn test_fence_hints() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let config = Config::default().with_hint_fn(Some(crate::instruction::tests::record_hint));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        // pause (fence w, 0)
+        let result = MiscMem::decode_execute(0x0100_000F, &mut engine);
+        assert_eq!(result, Ok(true));
+        assert_eq!(
+            crate::instruction::tests::last_hint(),
+            Some((Hint::Pause, 0))
+        );
+
+        // fence 0, rw
+        let result = MiscMem::decode_execute(0x0030_000F, &mut engine);
+        assert_eq!(result, Ok(true));
+        assert_eq!(
+            crate::instruction::tests::last_hint(),
+            Some((Hint::Reserved, INSTRUCTION_SIZE))
+        );
+
+        // fence rw, rw (not a hint)
+        let result = MiscMem::decode_execute(0x0330_000F, &mut engine);
+        assert_eq!(result, Ok(true));
+        assert_eq!(crate::instruction::tests::last_hint(), None);
+    }
 }
@@ -1,7 +1,7 @@
-use crate::engine::Engine;
+use crate::engine::{Engine, Hint, NtlDomain};
 use crate::error::EmbiveError;
 use crate::instruction::format::TypeR;
-use crate::instruction::{Instruction, INSTRUCTION_SIZE};
+use crate::instruction::{hint, Instruction, INSTRUCTION_SIZE};
 use crate::memory::Memory;
 
 const MUL_ADD_SUB_FUNCT3: u8 = 0b000;
@@ -13,110 +13,119 @@ const DIVU_SRL_SRA_FUNCT3: u8 = 0b101;
 const MULHSU_SLT_FUNCT3: u8 = 0b010;
 const MULHU_SLTU_FUNCT3: u8 = 0b011;
 
-#[cfg(feature = "m_extension")]
 const M_EXT_FUNCT7: u8 = 0b0000001;
 const SUB_SRA_FUNCT7: u8 = 0b0100000;
 
-const ADD_FUNCT10: u16 = MUL_ADD_SUB_FUNCT3 as u16;
-const SUB_FUNCT10: u16 = ((SUB_SRA_FUNCT7 as u16) << 3) | MUL_ADD_SUB_FUNCT3 as u16;
-const XOR_FUNCT10: u16 = DIV_XOR_FUNCT3 as u16;
-const OR_FUNCT10: u16 = REM_OR_FUNCT3 as u16;
-const AND_FUNCT10: u16 = REMU_AND_FUNCT3 as u16;
-const SLL_FUNCT10: u16 = MULH_SLL_FUNCT3 as u16;
-const SRL_FUNCT10: u16 = DIVU_SRL_SRA_FUNCT3 as u16;
-const SRA_FUNCT10: u16 = ((SUB_SRA_FUNCT7 as u16) << 3) | DIVU_SRL_SRA_FUNCT3 as u16;
-const SLT_FUNCT10: u16 = MULHSU_SLT_FUNCT3 as u16;
-const SLTU_FUNCT10: u16 = MULHU_SLTU_FUNCT3 as u16;
+pub(crate) const ADD_FUNCT10: u16 = MUL_ADD_SUB_FUNCT3 as u16;
+pub(crate) const SUB_FUNCT10: u16 = ((SUB_SRA_FUNCT7 as u16) << 3) | MUL_ADD_SUB_FUNCT3 as u16;
+pub(crate) const XOR_FUNCT10: u16 = DIV_XOR_FUNCT3 as u16;
+pub(crate) const OR_FUNCT10: u16 = REM_OR_FUNCT3 as u16;
+pub(crate) const AND_FUNCT10: u16 = REMU_AND_FUNCT3 as u16;
+pub(crate) const SLL_FUNCT10: u16 = MULH_SLL_FUNCT3 as u16;
+pub(crate) const SRL_FUNCT10: u16 = DIVU_SRL_SRA_FUNCT3 as u16;
+pub(crate) const SRA_FUNCT10: u16 = ((SUB_SRA_FUNCT7 as u16) << 3) | DIVU_SRL_SRA_FUNCT3 as u16;
+pub(crate) const SLT_FUNCT10: u16 = MULHSU_SLT_FUNCT3 as u16;
+pub(crate) const SLTU_FUNCT10: u16 = MULHU_SLTU_FUNCT3 as u16;
 
 #[cfg(feature = "m_extension")]
-const MUL_FUNCT10: u16 = ((M_EXT_FUNCT7 as u16) << 3) | MUL_ADD_SUB_FUNCT3 as u16;
+pub(crate) const MUL_FUNCT10: u16 = ((M_EXT_FUNCT7 as u16) << 3) | MUL_ADD_SUB_FUNCT3 as u16;
 #[cfg(feature = "m_extension")]
-const DIV_FUNCT10: u16 = ((M_EXT_FUNCT7 as u16) << 3) | DIV_XOR_FUNCT3 as u16;
+pub(crate) const DIV_FUNCT10: u16 = ((M_EXT_FUNCT7 as u16) << 3) | DIV_XOR_FUNCT3 as u16;
 #[cfg(feature = "m_extension")]
-const REM_FUNCT10: u16 = ((M_EXT_FUNCT7 as u16) << 3) | REM_OR_FUNCT3 as u16;
+pub(crate) const REM_FUNCT10: u16 = ((M_EXT_FUNCT7 as u16) << 3) | REM_OR_FUNCT3 as u16;
 #[cfg(feature = "m_extension")]
-const REMU_FUNCT10: u16 = ((M_EXT_FUNCT7 as u16) << 3) | REMU_AND_FUNCT3 as u16;
+pub(crate) const REMU_FUNCT10: u16 = ((M_EXT_FUNCT7 as u16) << 3) | REMU_AND_FUNCT3 as u16;
 #[cfg(feature = "m_extension")]
-const MULH_FUNCT10: u16 = ((M_EXT_FUNCT7 as u16) << 3) | MULH_SLL_FUNCT3 as u16;
+pub(crate) const MULH_FUNCT10: u16 = ((M_EXT_FUNCT7 as u16) << 3) | MULH_SLL_FUNCT3 as u16;
 #[cfg(feature = "m_extension")]
-const DIVU_FUNCT10: u16 = ((M_EXT_FUNCT7 as u16) << 3) | DIVU_SRL_SRA_FUNCT3 as u16;
+pub(crate) const DIVU_FUNCT10: u16 = ((M_EXT_FUNCT7 as u16) << 3) | DIVU_SRL_SRA_FUNCT3 as u16;
 #[cfg(feature = "m_extension")]
-const MULHSU_FUNCT10: u16 = ((M_EXT_FUNCT7 as u16) << 3) | MULHSU_SLT_FUNCT3 as u16;
+pub(crate) const MULHSU_FUNCT10: u16 = ((M_EXT_FUNCT7 as u16) << 3) | MULHSU_SLT_FUNCT3 as u16;
 #[cfg(feature = "m_extension")]
-const MULHU_FUNCT10: u16 = ((M_EXT_FUNCT7 as u16) << 3) | MULHU_SLTU_FUNCT3 as u16;
+pub(crate) const MULHU_FUNCT10: u16 = ((M_EXT_FUNCT7 as u16) << 3) | MULHU_SLTU_FUNCT3 as u16;
 
 /// Operation OpCode
 /// Instructions: Add, Sub, Xor, Or, And, Sll, Srl, Sra, Slt, Sltu
 /// Instructions (M Extension): Mul, Mulh, Mulhsu, Mulhu, Div, Divu, Rem, Remu
+/// Hints (rd = 0): Ntl.p1, Ntl.pall, Ntl.s1, Ntl.all (Zihintntl), reserved
 /// Format: R-Type.
 pub struct Op {}
 
 impl<M: Memory> Instruction<M> for Op {
-    #[inline(always)]
-    fn decode_execute(data: u32, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
-        let inst = TypeR::from(data);
+    type Operands = TypeR;
 
-        let rs1 = engine.registers.get(inst.rs1)?;
-        let rs2 = engine.registers.get(inst.rs2)?;
+    #[inline(always)]
+    fn execute(inst: TypeR, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
+        let rs1 = engine.registers.get_decoded(inst.rs1)?;
+        let rs2 = engine.registers.get_decoded(inst.rs2)?;
+
+        let value = match inst.funct10 {
+            ADD_FUNCT10 => rs1.wrapping_add(rs2),        // Add
+            SLL_FUNCT10 => rs1.wrapping_shl(rs2 as u32), // Sll (Logical shift left, fill with zero)
+            SLT_FUNCT10 => (rs1 < rs2) as u8 as i32,     // Slt (Set less than)
+            SLTU_FUNCT10 => ((rs1 as u32) < (rs2 as u32)) as u8 as i32, // Sltu (Set less than, unsigned)
+            XOR_FUNCT10 => rs1 ^ rs2,                                   // Xor
+            SRL_FUNCT10 => ((rs1 as u32).wrapping_shr(rs2 as u32)) as i32, // Srl (Logical shift right, fill with zero)
+            OR_FUNCT10 => rs1 | rs2,                                       // Or
+            AND_FUNCT10 => rs1 & rs2,                                      // And
+            #[cfg(feature = "m_extension")]
+            MUL_FUNCT10 => rs1.wrapping_mul(rs2), // Mul (Multiply)
+            #[cfg(feature = "m_extension")]
+            MULH_FUNCT10 => mulh(rs1, rs2), // Mulh (Multiply High)
+            #[cfg(feature = "m_extension")]
+            MULHSU_FUNCT10 => mulhsu(rs1, rs2), // Mulhsu (Multiply High, signed, unsigned)
+            #[cfg(feature = "m_extension")]
+            MULHU_FUNCT10 => mulhu(rs1, rs2), // Mulhu (Multiply High, unsigned)
+            #[cfg(feature = "m_extension")]
+            DIV_FUNCT10 => {
+                if rs2 == 0 {
+                    -1
+                } else {
+                    div(rs1, rs2)
+                }
+            } // Div (Divide)
+            #[cfg(feature = "m_extension")]
+            DIVU_FUNCT10 => {
+                if rs2 == 0 {
+                    -1
+                } else {
+                    divu(rs1 as u32, rs2 as u32).0 as i32
+                }
+            } // Divu (Divide, unsigned)
+            #[cfg(feature = "m_extension")]
+            REM_FUNCT10 => {
+                if rs2 == 0 {
+                    rs1
+                } else {
+                    rem(rs1, rs2)
+                }
+            } // Rem (Remainder)
+            #[cfg(feature = "m_extension")]
+            REMU_FUNCT10 => {
+                if rs2 == 0 {
+                    rs1
+                } else {
+                    divu(rs1 as u32, rs2 as u32).1 as i32
+                }
+            } // Remu (Remainder, unsigned)
+            SUB_FUNCT10 => rs1.wrapping_sub(rs2),                          // Sub
+            SRA_FUNCT10 => rs1.wrapping_shr(rs2 as u32), // Sra (Arithmetic shift right, fill with sign bit)
+            _ => return Err(EmbiveError::InvalidInstruction),
+        };
 
         if inst.rd != 0 {
-            // rd = 0 means its a HINT instruction, just ignore it.
-            let rd = engine.registers.get_mut(inst.rd)?;
-            *rd = match inst.funct10 {
-                ADD_FUNCT10 => rs1.wrapping_add(rs2),        // Add
-                SLL_FUNCT10 => rs1.wrapping_shl(rs2 as u32), // Sll (Logical shift left, fill with zero)
-                SLT_FUNCT10 => (rs1 < rs2) as u8 as i32,     // Slt (Set less than)
-                SLTU_FUNCT10 => ((rs1 as u32) < (rs2 as u32)) as u8 as i32, // Sltu (Set less than, unsigned)
-                XOR_FUNCT10 => rs1 ^ rs2,                                   // Xor
-                SRL_FUNCT10 => ((rs1 as u32).wrapping_shr(rs2 as u32)) as i32, // Srl (Logical shift right, fill with zero)
-                OR_FUNCT10 => rs1 | rs2,                                       // Or
-                AND_FUNCT10 => rs1 & rs2,                                      // And
-                #[cfg(feature = "m_extension")]
-                MUL_FUNCT10 => rs1.wrapping_mul(rs2), // Mul (Multiply)
-                #[cfg(feature = "m_extension")]
-                MULH_FUNCT10 => ((rs1 as i64).wrapping_mul(rs2 as i64) >> 32) as u32 as i32, // Mulh (Multiply High)
-                #[cfg(feature = "m_extension")]
-                MULHSU_FUNCT10 => {
-                    ((rs1 as i64).wrapping_mul((rs2 as u32) as i64) >> 32) as u32 as i32
-                } // Mulhsu (Multiply High, signed, unsigned)
-                #[cfg(feature = "m_extension")]
-                MULHU_FUNCT10 => ((rs1 as u32 as u64).wrapping_mul(rs2 as u32 as u64) >> 32) as i32, // Mulhu (Multiply High, unsigned)
-                #[cfg(feature = "m_extension")]
-                DIV_FUNCT10 => {
-                    if rs2 == 0 {
-                        -1
-                    } else {
-                        rs1.wrapping_div(rs2)
-                    }
-                } // Div (Divide)
-                #[cfg(feature = "m_extension")]
-                DIVU_FUNCT10 => {
-                    if rs2 == 0 {
-                        -1
-                    } else {
-                        (rs1 as u32).wrapping_div(rs2 as u32) as i32
-                    }
-                } // Divu (Divide, unsigned)
-                #[cfg(feature = "m_extension")]
-                REM_FUNCT10 => {
-                    if rs2 == 0 {
-                        rs1
-                    } else {
-                        rs1.wrapping_rem(rs2)
-                    }
-                } // Rem (Remainder)
-                #[cfg(feature = "m_extension")]
-                REMU_FUNCT10 => {
-                    if rs2 == 0 {
-                        rs1
-                    } else {
-                        (rs1 as u32).wrapping_rem(rs2 as u32) as i32
-                    }
-                } // Remu (Remainder, unsigned)
-                SUB_FUNCT10 => rs1.wrapping_sub(rs2), // Sub
-                SRA_FUNCT10 => rs1.wrapping_shr(rs2 as u32), // Sra (Arithmetic shift right, fill with sign bit)
-                _ => return Err(EmbiveError::InvalidInstruction),
+            let rd = engine.registers.get_decoded_mut(inst.rd)?;
+            *rd = value;
+        } else if inst.funct10 >> 3 != M_EXT_FUNCT7 as u16 {
+            // rd = 0 means its a HINT instruction (base instructions only), just report it.
+            let kind = match (inst.funct10, inst.rs1, inst.rs2) {
+                (ADD_FUNCT10, 0, 2) => Hint::NonTemporal(NtlDomain::P1),
+                (ADD_FUNCT10, 0, 3) => Hint::NonTemporal(NtlDomain::PAll),
+                (ADD_FUNCT10, 0, 4) => Hint::NonTemporal(NtlDomain::S1),
+                (ADD_FUNCT10, 0, 5) => Hint::NonTemporal(NtlDomain::All),
+                _ => Hint::Reserved,
             };
+            hint(engine, kind);
         }
 
         // Go to next instruction
@@ -126,6 +135,156 @@ impl<M: Memory> Instruction<M> for Op {
     }
 }
 
+/// Multiply High (upper 32 bits of the signed 64-bit product).
+///
+/// 64-bit hosts multiply natively, 32-bit hosts (ex.: Cortex-M) use the unsigned widening multiply
+/// (`umull`) with a sign correction, instead of a full 64x64-bit multiplication.
+#[cfg(feature = "m_extension")]
+#[inline(always)]
+fn mulh(rs1: i32, rs2: i32) -> i32 {
+    #[cfg(target_pointer_width = "64")]
+    return ((rs1 as i64 * rs2 as i64) >> 32) as i32;
+
+    #[cfg(not(target_pointer_width = "64"))]
+    return mulh_corrected(rs1, rs2);
+}
+
+/// Multiply High, signed * unsigned (check [`mulh`]).
+#[cfg(feature = "m_extension")]
+#[inline(always)]
+fn mulhsu(rs1: i32, rs2: i32) -> i32 {
+    #[cfg(target_pointer_width = "64")]
+    return ((rs1 as i64 * rs2 as u32 as i64) >> 32) as i32;
+
+    #[cfg(not(target_pointer_width = "64"))]
+    return mulhsu_corrected(rs1, rs2);
+}
+
+/// Multiply High, unsigned (widening multiply on every host).
+#[cfg(feature = "m_extension")]
+#[inline(always)]
+fn mulhu(rs1: i32, rs2: i32) -> i32 {
+    ((rs1 as u32 as u64 * rs2 as u32 as u64) >> 32) as i32
+}
+
+/// Multiply High from the unsigned product: a negative operand `x` is read as `x + 2^32`,
+/// adding the other operand to the upper half, which is subtracted back.
+#[cfg(all(feature = "m_extension", any(test, not(target_pointer_width = "64"))))]
+#[inline(always)]
+fn mulh_corrected(rs1: i32, rs2: i32) -> i32 {
+    mulhu(rs1, rs2)
+        .wrapping_sub(if rs1 < 0 { rs2 } else { 0 })
+        .wrapping_sub(if rs2 < 0 { rs1 } else { 0 })
+}
+
+/// Multiply High, signed * unsigned, from the unsigned product (check [`mulh_corrected`]).
+#[cfg(all(feature = "m_extension", any(test, not(target_pointer_width = "64"))))]
+#[inline(always)]
+fn mulhsu_corrected(rs1: i32, rs2: i32) -> i32 {
+    mulhu(rs1, rs2).wrapping_sub(if rs1 < 0 { rs2 } else { 0 })
+}
+
+/// Divide, signed (`rs2` != 0, `i32::MIN / -1` overflows to `i32::MIN`).
+#[cfg(feature = "m_extension")]
+#[inline(always)]
+fn div(rs1: i32, rs2: i32) -> i32 {
+    #[cfg(not(feature = "soft_divide"))]
+    return rs1.wrapping_div(rs2);
+
+    #[cfg(feature = "soft_divide")]
+    return soft_div(rs1, rs2);
+}
+
+/// Remainder, signed (`rs2` != 0, `i32::MIN % -1` is 0).
+#[cfg(feature = "m_extension")]
+#[inline(always)]
+fn rem(rs1: i32, rs2: i32) -> i32 {
+    #[cfg(not(feature = "soft_divide"))]
+    return rs1.wrapping_rem(rs2);
+
+    #[cfg(feature = "soft_divide")]
+    return soft_rem(rs1, rs2);
+}
+
+/// Divide, unsigned (`rs2` != 0).
+///
+/// Returns:
+/// - `(u32, u32)`: Quotient and remainder.
+#[cfg(feature = "m_extension")]
+#[inline(always)]
+fn divu(rs1: u32, rs2: u32) -> (u32, u32) {
+    #[cfg(not(feature = "soft_divide"))]
+    return (rs1 / rs2, rs1 % rs2);
+
+    #[cfg(feature = "soft_divide")]
+    return soft_divu(rs1, rs2);
+}
+
+/// Shift-subtract division, for hosts without a hardware divider (ex.: Cortex-M0).
+/// Iterates once per quotient bit, so small quotients (the common case) are cheap.
+/// No `clz` is used, it isn't available on those hosts either.
+///
+/// Arguments:
+/// - `dividend`: Dividend.
+/// - `divisor`: Divisor (not 0).
+///
+/// Returns:
+/// - `(u32, u32)`: Quotient and remainder.
+#[cfg(all(feature = "m_extension", any(test, feature = "soft_divide")))]
+#[inline]
+fn soft_divu(dividend: u32, divisor: u32) -> (u32, u32) {
+    debug_assert!(divisor != 0);
+    if divisor > dividend {
+        return (0, dividend);
+    }
+
+    // Align the divisor with the dividend (never overflows, shifted <= dividend)
+    let mut shifted = divisor;
+    let mut bit = 1;
+    while shifted <= dividend >> 1 {
+        shifted <<= 1;
+        bit <<= 1;
+    }
+
+    let mut quotient = 0;
+    let mut remainder = dividend;
+    loop {
+        if remainder >= shifted {
+            remainder -= shifted;
+            quotient |= bit;
+        }
+        if bit == 1 {
+            return (quotient, remainder);
+        }
+        shifted >>= 1;
+        bit >>= 1;
+    }
+}
+
+/// Signed division on [`soft_divu`] (quotient rounded towards zero).
+#[cfg(all(feature = "m_extension", any(test, feature = "soft_divide")))]
+#[inline]
+fn soft_div(rs1: i32, rs2: i32) -> i32 {
+    let (quotient, _) = soft_divu(rs1.unsigned_abs(), rs2.unsigned_abs());
+    if (rs1 < 0) != (rs2 < 0) {
+        quotient.wrapping_neg() as i32
+    } else {
+        quotient as i32
+    }
+}
+
+/// Signed remainder on [`soft_divu`] (sign of the dividend).
+#[cfg(all(feature = "m_extension", any(test, feature = "soft_divide")))]
+#[inline]
+fn soft_rem(rs1: i32, rs2: i32) -> i32 {
+    let (_, remainder) = soft_divu(rs1.unsigned_abs(), rs2.unsigned_abs());
+    if rs1 < 0 {
+        remainder.wrapping_neg() as i32
+    } else {
+        remainder as i32
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::memory::SliceMemory;
@@ -799,7 +958,170 @@ mod tests {
 
         let result = Op::decode_execute(op.into(), &mut engine);
         assert_eq!(result, Ok(true));
-        assert_eq!(*engine.registers.get_mut(1).unwrap(), (u32::MAX % 1) as i32);
+        assert_eq!(*engine.registers.get_mut(1).unwrap(), 0);
         assert_eq!(engine.program_counter, INSTRUCTION_SIZE);
     }
+
+    #[test]
+    fn test_ntl_hints() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let config = crate::engine::Config::default()
+            .with_hint_fn(Some(crate::instruction::tests::record_hint));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        for (rs2, domain) in [
+            (2, NtlDomain::P1),
+            (3, NtlDomain::PAll),
+            (4, NtlDomain::S1),
+            (5, NtlDomain::All),
+        ] {
+            let op = TypeR {
+                rd: 0,
+                rs1: 0,
+                rs2,
+                funct10: ADD_FUNCT10,
+            };
+            let result = Op::decode_execute(op.into(), &mut engine);
+            assert_eq!(result, Ok(true));
+            assert_eq!(
+                crate::instruction::tests::last_hint().map(|(hint, _)| hint),
+                Some(Hint::NonTemporal(domain))
+            );
+        }
+
+        let op = TypeR {
+            rd: 0,
+            rs1: 1,
+            rs2: 2,
+            funct10: SUB_FUNCT10,
+        };
+        let result = Op::decode_execute(op.into(), &mut engine);
+        assert_eq!(result, Ok(true));
+        assert_eq!(
+            crate::instruction::tests::last_hint().map(|(hint, _)| hint),
+            Some(Hint::Reserved)
+        );
+    }
+
+    #[test]
+    fn test_rd_0_invalid() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        let op = TypeR {
+            rd: 0,
+            rs1: 0,
+            rs2: 0,
+            funct10: 0b11_1111_1000,
+        };
+
+        // Not a HINT (invalid encoding)
+        let result = Op::decode_execute(op.into(), &mut engine);
+        assert_eq!(result, Err(EmbiveError::InvalidInstruction));
+    }
+
+    /// Edge-case operands: zero, one, extremes, mixed signs and half-word boundaries.
+    #[cfg(feature = "m_extension")]
+    const EDGE_VECTORS: [i32; 16] = [
+        0,
+        1,
+        -1,
+        2,
+        -2,
+        i32::MIN,
+        i32::MIN + 1,
+        i32::MAX,
+        i32::MAX - 1,
+        0xFFFF,
+        0x1_0000,
+        -0x1_0000,
+        0x5555_5555,
+        0xAAAA_AAAAu32 as i32,
+        0x8000_0001u32 as i32,
+        0x7FFF_0000,
+    ];
+
+    #[cfg(feature = "m_extension")]
+    #[test]
+    fn test_mulh_vectors() {
+        for a in EDGE_VECTORS {
+            for b in EDGE_VECTORS {
+                let expected_h = ((a as i128 * b as i128) >> 32) as i32;
+                let expected_hsu = ((a as i128 * b as u32 as i128) >> 32) as i32;
+                let expected_hu = ((a as u32 as u128 * b as u32 as u128) >> 32) as i32;
+
+                assert_eq!(mulh(a, b), expected_h, "mulh({:#x}, {:#x})", a, b);
+                assert_eq!(mulh_corrected(a, b), expected_h, "mulh({:#x}, {:#x})", a, b);
+                assert_eq!(mulhsu(a, b), expected_hsu, "mulhsu({:#x}, {:#x})", a, b);
+                assert_eq!(
+                    mulhsu_corrected(a, b),
+                    expected_hsu,
+                    "mulhsu({:#x}, {:#x})",
+                    a,
+                    b
+                );
+                assert_eq!(mulhu(a, b), expected_hu, "mulhu({:#x}, {:#x})", a, b);
+            }
+        }
+
+        // Known results
+        assert_eq!(mulh(i32::MIN, i32::MIN), 0x4000_0000);
+        assert_eq!(mulh(i32::MIN, -1), 0);
+        assert_eq!(mulhsu(-1, -1), -1);
+        assert_eq!(
+            mulhsu(i32::MIN, -1),
+            (0x8000_0000_8000_0000u64 >> 32) as i32
+        );
+        assert_eq!(mulhu(-1, -1), -2);
+    }
+
+    #[cfg(feature = "m_extension")]
+    #[test]
+    fn test_mulh_engine() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+
+        for (funct10, expected) in [
+            (MULH_FUNCT10, 0x4000_0000),
+            (MULHSU_FUNCT10, -0x4000_0000),
+            (MULHU_FUNCT10, 0x4000_0000),
+        ] {
+            let op = TypeR {
+                rd: 1,
+                rs1: 2,
+                rs2: 3,
+                funct10,
+            };
+            *engine.registers.get_mut(2).unwrap() = i32::MIN;
+            *engine.registers.get_mut(3).unwrap() = i32::MIN;
+
+            assert_eq!(Op::decode_execute(op.into(), &mut engine), Ok(true));
+            assert_eq!(engine.registers.get(1), Ok(expected));
+        }
+    }
+
+    #[cfg(feature = "m_extension")]
+    #[test]
+    fn test_soft_divide() {
+        let mut operands = std::vec::Vec::from(EDGE_VECTORS);
+        operands.extend([3, -3, 7, 10, 100, -100, 0x1234_5678, 0x7FFF_FFF0, -0x8000]);
+
+        for a in operands.iter().copied() {
+            for b in operands.iter().copied().filter(|b| *b != 0) {
+                assert_eq!(soft_div(a, b), a.wrapping_div(b), "div({:#x}, {:#x})", a, b);
+                assert_eq!(soft_rem(a, b), a.wrapping_rem(b), "rem({:#x}, {:#x})", a, b);
+                assert_eq!(
+                    soft_divu(a as u32, b as u32),
+                    (a as u32 / b as u32, a as u32 % b as u32),
+                    "divu({:#x}, {:#x})",
+                    a,
+                    b
+                );
+            }
+        }
+
+        // Overflow
+        assert_eq!(soft_div(i32::MIN, -1), i32::MIN);
+        assert_eq!(soft_rem(i32::MIN, -1), 0);
+        assert_eq!(soft_divu(u32::MAX, 1), (u32::MAX, 0));
+    }
 }
@@ -1,53 +1,75 @@
-use crate::engine::Engine;
+use crate::engine::{Engine, Hint};
 use crate::error::EmbiveError;
 use crate::instruction::format::TypeI;
-use crate::instruction::{Instruction, INSTRUCTION_SIZE};
+use crate::instruction::{hint, Instruction, INSTRUCTION_SIZE};
 use crate::memory::Memory;
-
-const ADDI_FUNC3: u8 = 0b000;
-const XORI_FUNC3: u8 = 0b100;
-const ORI_FUNC3: u8 = 0b110;
-const ANDI_FUNC3: u8 = 0b111;
-const SLLI_FUNC3: u8 = 0b001;
-const SRLI_SRAI_FUNC3: u8 = 0b101;
-const SLTI_FUNC3: u8 = 0b010;
-const SLTIU_FUNC3: u8 = 0b011;
+#[cfg(feature = "safepoint")]
+use crate::{engine::SAFEPOINT_INSTRUCTION, instruction::OP_IMM_OPCODE};
+
+pub(crate) const ADDI_FUNC3: u8 = 0b000;
+pub(crate) const XORI_FUNC3: u8 = 0b100;
+pub(crate) const ORI_FUNC3: u8 = 0b110;
+pub(crate) const ANDI_FUNC3: u8 = 0b111;
+pub(crate) const SLLI_FUNC3: u8 = 0b001;
+pub(crate) const SRLI_SRAI_FUNC3: u8 = 0b101;
+pub(crate) const SLTI_FUNC3: u8 = 0b010;
+pub(crate) const SLTIU_FUNC3: u8 = 0b011;
+
+const PREFETCH_I_IMM: i32 = 0b00000;
+const PREFETCH_R_IMM: i32 = 0b00001;
+const PREFETCH_W_IMM: i32 = 0b00011;
 
 /// Operation Immediate OpCode
 /// Instructions: Addi, Xori, Ori, Andi, Slli, Srli, Srai, Slti, Sltiu
+/// Hints (rd = 0): Prefetch.i, Prefetch.r, Prefetch.w (Zicbop), safepoint, reserved
 /// Format: I-Type.
 pub struct OpImm {}
 
 impl<M: Memory> Instruction<M> for OpImm {
-    #[inline(always)]
-    fn decode_execute(data: u32, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
-        let inst = TypeI::from(data);
+    type Operands = TypeI;
 
-        let rs1 = engine.registers.get(inst.rs1)?;
+    #[inline(always)]
+    fn execute(inst: TypeI, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
+        let rs1 = engine.registers.get_decoded(inst.rs1)?;
         let imm = inst.imm;
 
+        let value = match inst.funct3 {
+            ADDI_FUNC3 => rs1.wrapping_add(imm),
+            SLLI_FUNC3 => rs1 << (imm & 0b11111),
+            SLTI_FUNC3 => (rs1 < imm) as u8 as i32,
+            SLTIU_FUNC3 => ((rs1 as u32) < (imm as u32)) as u8 as i32,
+            XORI_FUNC3 => rs1 ^ imm,
+            SRLI_SRAI_FUNC3 => {
+                if (imm & (0b1 << 10)) != 0 {
+                    // Sra (Arithmetic shift right, fill with sign bit)
+                    rs1 >> (imm & 0b11111)
+                } else {
+                    // Srl (Logical shift right, fill with zero)
+                    ((rs1 as u32) >> ((imm & 0b11111) as u32)) as i32
+                }
+            }
+            ORI_FUNC3 => rs1 | imm,
+            ANDI_FUNC3 => rs1 & imm,
+            _ => return Err(EmbiveError::InvalidInstruction),
+        };
+
         if inst.rd != 0 {
-            // rd = 0 means its a HINT instruction, just ignore it.
-            let rd = engine.registers.get_mut(inst.rd)?;
-            *rd = match inst.funct3 {
-                ADDI_FUNC3 => rs1.wrapping_add(imm),
-                SLLI_FUNC3 => rs1 << (imm & 0b11111),
-                SLTI_FUNC3 => (rs1 < imm) as u8 as i32,
-                SLTIU_FUNC3 => ((rs1 as u32) < (imm as u32)) as u8 as i32,
-                XORI_FUNC3 => rs1 ^ imm,
-                SRLI_SRAI_FUNC3 => {
-                    if (imm & (0b1 << 10)) != 0 {
-                        // Sra (Arithmetic shift right, fill with sign bit)
-                        rs1 >> (imm & 0b11111)
-                    } else {
-                        // Srl (Logical shift right, fill with zero)
-                        ((rs1 as u32) >> ((imm & 0b11111) as u32)) as i32
-                    }
+            let rd = engine.registers.get_decoded_mut(inst.rd)?;
+            *rd = value;
+        } else if inst.rs1 != 0 || imm != 0 || inst.funct3 != ADDI_FUNC3 {
+            // rd = 0 means its a HINT instruction (except for `nop`), just report it.
+            let kind = match (inst.funct3, imm & 0b1_1111) {
+                #[cfg(feature = "safepoint")]
+                _ if u32::from(inst) | OP_IMM_OPCODE as u32 == SAFEPOINT_INSTRUCTION => {
+                    engine.safepoint = true;
+                    Hint::Safepoint
                 }
-                ORI_FUNC3 => rs1 | imm,
-                ANDI_FUNC3 => rs1 & imm,
-                _ => return Err(EmbiveError::InvalidInstruction),
+                (ORI_FUNC3, PREFETCH_I_IMM) => Hint::PrefetchInstruction,
+                (ORI_FUNC3, PREFETCH_R_IMM) => Hint::PrefetchRead,
+                (ORI_FUNC3, PREFETCH_W_IMM) => Hint::PrefetchWrite,
+                _ => Hint::Reserved,
             };
+            hint(engine, kind);
         }
 
         // Go to next instruction
@@ -390,4 +412,33 @@ mod tests {
         assert_eq!(*engine.registers.get_mut(1).unwrap(), 1);
         assert_eq!(engine.program_counter, INSTRUCTION_SIZE);
     }
+
+    #[test]
+    fn test_prefetch_hints() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let config = crate::engine::Config::default()
+            .with_hint_fn(Some(crate::instruction::tests::record_hint));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        for (imm, hint) in [
+            (0x40 | PREFETCH_I_IMM, Hint::PrefetchInstruction),
+            (PREFETCH_R_IMM, Hint::PrefetchRead),
+            (0x20 | PREFETCH_W_IMM, Hint::PrefetchWrite),
+            (0b00010, Hint::Reserved),
+        ] {
+            let ori = TypeI {
+                rd: 0,
+                rs1: 1,
+                imm,
+                funct3: ORI_FUNC3,
+            };
+            let result = OpImm::decode_execute(ori.into(), &mut engine);
+            assert_eq!(result, Ok(true));
+            assert_eq!(
+                crate::instruction::tests::last_hint().map(|(hint, _)| hint),
+                Some(hint)
+            );
+        }
+        assert_eq!(engine.registers.get(0), Ok(0));
+    }
 }
@@ -0,0 +1,578 @@
+use crate::engine::Engine;
+use crate::error::EmbiveError;
+use crate::instruction::{Instruction, INSTRUCTION_SIZE};
+use crate::memory::Memory;
+use crate::register::{VType, VILL};
+
+const OPIVV_FUNCT3: u32 = 0b000;
+const OPMVV_FUNCT3: u32 = 0b010;
+const OPIVI_FUNCT3: u32 = 0b011;
+const OPIVX_FUNCT3: u32 = 0b100;
+const OPMVX_FUNCT3: u32 = 0b110;
+const OPCFG_FUNCT3: u32 = 0b111;
+
+const VADD_FUNCT6: u32 = 0b000000;
+const VSUB_FUNCT6: u32 = 0b000010;
+const VRSUB_FUNCT6: u32 = 0b000011;
+const VMINU_FUNCT6: u32 = 0b000100;
+const VMIN_FUNCT6: u32 = 0b000101;
+const VMAXU_FUNCT6: u32 = 0b000110;
+const VMAX_FUNCT6: u32 = 0b000111;
+const VAND_FUNCT6: u32 = 0b001001;
+const VOR_FUNCT6: u32 = 0b001010;
+const VXOR_FUNCT6: u32 = 0b001011;
+const VMERGE_FUNCT6: u32 = 0b010111;
+const VSLL_FUNCT6: u32 = 0b100101;
+const VSRL_FUNCT6: u32 = 0b101000;
+const VSRA_FUNCT6: u32 = 0b101001;
+
+const VREDSUM_FUNCT6: u32 = 0b000000;
+const VMV_SCALAR_FUNCT6: u32 = 0b010000;
+const VMULHU_FUNCT6: u32 = 0b100100;
+const VMUL_FUNCT6: u32 = 0b100101;
+const VMULH_FUNCT6: u32 = 0b100111;
+const VMACC_FUNCT6: u32 = 0b101101;
+
+/// Second operand of an arithmetic instruction.
+#[derive(Clone, Copy)]
+enum Operand {
+    /// Vector register group (`vs1`).
+    Vector(usize),
+    /// Scalar value (`rs1` or immediate), truncated to SEW.
+    Scalar(u32),
+}
+
+/// Vector arithmetic and configuration OpCode (OP-V)
+/// Instructions:
+/// - Configuration: vsetvli, vsetivli, vsetvl
+/// - Integer (vv, vx, vi): vadd, vsub, vrsub, vminu, vmin, vmaxu, vmax, vand, vor, vxor, vsll, vsrl, vsra, vmerge, vmv.v
+/// - Integer multiply (vv, vx): vmul, vmulh, vmulhu, vmacc
+/// - Reduction / scalar move: vredsum.vs, vmv.x.s, vmv.s.x
+///
+/// Format: OP-V (`funct6 | vm | vs2 | vs1/rs1/imm | funct3 | vd/rd`).
+pub struct OpV {}
+
+impl<M: Memory> Instruction<M> for OpV {
+    type Operands = u32;
+
+    #[inline(always)]
+    fn execute(data: u32, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
+        let funct3 = (data >> 12) & 0b111;
+
+        if funct3 == OPCFG_FUNCT3 {
+            configure(data, engine)?;
+        } else {
+            arithmetic(data, funct3, engine)?;
+        }
+
+        // Go to next instruction
+        engine.program_counter = engine.program_counter.wrapping_add(INSTRUCTION_SIZE);
+
+        // Continue execution
+        Ok(true)
+    }
+}
+
+/// Sign-extend an element.
+#[inline(always)]
+fn sext(value: u32, sew: usize) -> i32 {
+    let shift = 32 - sew as u32 * 8;
+    ((value << shift) as i32) >> shift
+}
+
+/// Get the current vector type, fails if illegal (`vill`).
+#[inline]
+pub(crate) fn current_vtype<M: Memory>(engine: &Engine<M>) -> Result<VType, EmbiveError> {
+    VType::decode(engine.vector.vtype).ok_or(EmbiveError::InvalidInstruction)
+}
+
+/// Check that a register group is aligned to its size.
+#[inline]
+pub(crate) fn check_group(register: usize, group: usize) -> Result<(), EmbiveError> {
+    if register % group != 0 {
+        return Err(EmbiveError::InvalidInstruction);
+    }
+
+    Ok(())
+}
+
+/// vsetvli / vsetivli / vsetvl (set `vl` and `vtype`, rd = vl)
+fn configure<M: Memory>(data: u32, engine: &mut Engine<M>) -> Result<(), EmbiveError> {
+    let rd = ((data >> 7) & 0b1_1111) as usize;
+    let rs1 = ((data >> 15) & 0b1_1111) as usize;
+
+    let (vtype, avl) = if data >> 31 == 0 {
+        // vsetvli
+        (
+            (data >> 20) & 0x7FF,
+            (rs1 != 0).then(|| engine.registers.get_decoded(rs1).map(|v| v as u32)),
+        )
+    } else if data >> 30 == 0b11 {
+        // vsetivli (AVL is an immediate)
+        ((data >> 20) & 0x3FF, Some(Ok(rs1 as u32)))
+    } else if data >> 25 == 0b100_0000 {
+        // vsetvl
+        let vtype = engine
+            .registers
+            .get_decoded(((data >> 20) & 0b1_1111) as usize)? as u32;
+        (
+            vtype,
+            (rs1 != 0).then(|| engine.registers.get_decoded(rs1).map(|v| v as u32)),
+        )
+    } else {
+        return Err(EmbiveError::InvalidInstruction);
+    };
+
+    match VType::decode(vtype) {
+        Some(decoded) => {
+            engine.vector.vl = match avl {
+                Some(avl) => avl?.min(decoded.vlmax),
+                // rs1 = x0, rd != x0: Set vl to VLMAX
+                None if rd != 0 => decoded.vlmax,
+                // rs1 = x0, rd = x0: Keep vl
+                None => engine.vector.vl.min(decoded.vlmax),
+            };
+            engine.vector.vtype = vtype;
+        }
+        None => {
+            // Unsupported vector type
+            engine.vector.vl = 0;
+            engine.vector.vtype = VILL;
+        }
+    }
+
+    if rd != 0 {
+        let rd = engine.registers.get_decoded_mut(rd)?;
+        *rd = engine.vector.vl as i32;
+    }
+
+    Ok(())
+}
+
+/// Integer arithmetic (OPIVV, OPIVX, OPIVI, OPMVV, OPMVX)
+fn arithmetic<M: Memory>(
+    data: u32,
+    funct3: u32,
+    engine: &mut Engine<M>,
+) -> Result<(), EmbiveError> {
+    let vd = ((data >> 7) & 0b1_1111) as usize;
+    let rs1 = ((data >> 15) & 0b1_1111) as usize;
+    let vs2 = ((data >> 20) & 0b1_1111) as usize;
+    let unmasked = (data >> 25) & 1 != 0;
+    let funct6 = data >> 26;
+
+    let vtype = current_vtype(engine)?;
+    let sew = vtype.sew;
+    let mask = u32::MAX >> (32 - sew * 8);
+    let vl = engine.vector.vl as usize;
+    // vtype is legal, so the group multiplier for SEW is always valid
+    let group = vtype.group(sew).ok_or(EmbiveError::InvalidInstruction)?;
+
+    let operand = match funct3 {
+        OPIVV_FUNCT3 | OPMVV_FUNCT3 => Operand::Vector(rs1),
+        OPIVX_FUNCT3 | OPMVX_FUNCT3 => {
+            Operand::Scalar(engine.registers.get_decoded(rs1)? as u32 & mask)
+        }
+        OPIVI_FUNCT3 => Operand::Scalar(((((rs1 as u32) << 27) as i32) >> 27) as u32 & mask),
+        _ => return Err(EmbiveError::InvalidInstruction),
+    };
+
+    // Scalar moves and reductions (not element-wise)
+    if matches!(funct3, OPMVV_FUNCT3 | OPMVX_FUNCT3) {
+        match (funct6, operand) {
+            (VMV_SCALAR_FUNCT6, Operand::Vector(0)) if unmasked => {
+                // vmv.x.s (rd = vs2[0])
+                let value = sext(engine.vector.element(vs2, 0, sew)?, sew);
+                if vd != 0 {
+                    *engine.registers.get_decoded_mut(vd)? = value;
+                }
+                return Ok(());
+            }
+            (VMV_SCALAR_FUNCT6, Operand::Scalar(value)) if unmasked && vs2 == 0 => {
+                // vmv.s.x (vd[0] = rs1)
+                if vl > 0 {
+                    engine.vector.set_element(vd, 0, sew, value)?;
+                }
+                return Ok(());
+            }
+            (VREDSUM_FUNCT6, Operand::Vector(vs1)) => {
+                // vredsum.vs (vd[0] = vs1[0] + sum(vs2[*]))
+                check_group(vs2, group)?;
+                if vl > 0 {
+                    let mut sum = engine.vector.element(vs1, 0, sew)?;
+                    for i in 0..vl {
+                        if engine.vector.active(unmasked, i) {
+                            sum = sum.wrapping_add(engine.vector.element(vs2, i, sew)?);
+                        }
+                    }
+                    engine.vector.set_element(vd, 0, sew, sum)?;
+                }
+                return Ok(());
+            }
+            _ => {}
+        }
+    }
+
+    // Element-wise operations
+    check_group(vd, group)?;
+    check_group(vs2, group)?;
+    if let Operand::Vector(vs1) = operand {
+        check_group(vs1, group)?;
+    }
+    // Masked destination can't overlap the mask register
+    if !unmasked && vd == 0 {
+        return Err(EmbiveError::InvalidInstruction);
+    }
+
+    let integer = matches!(funct3, OPIVV_FUNCT3 | OPIVX_FUNCT3 | OPIVI_FUNCT3);
+    let shift_mask = sew as u32 * 8 - 1;
+
+    if integer && funct6 == VMERGE_FUNCT6 {
+        // vmerge (vd = v0 ? op : vs2) / vmv.v (vd = op)
+        if unmasked && vs2 != 0 {
+            return Err(EmbiveError::InvalidInstruction);
+        }
+
+        for i in 0..vl {
+            let value = if engine.vector.active(unmasked, i) {
+                match operand {
+                    Operand::Vector(vs1) => engine.vector.element(vs1, i, sew)?,
+                    Operand::Scalar(value) => value,
+                }
+            } else {
+                engine.vector.element(vs2, i, sew)?
+            };
+            engine.vector.set_element(vd, i, sew, value)?;
+        }
+
+        return Ok(());
+    }
+
+    // Check that the operation exists for this operand type
+    let valid = if integer {
+        match funct6 {
+            VADD_FUNCT6 | VAND_FUNCT6 | VOR_FUNCT6 | VXOR_FUNCT6 | VSLL_FUNCT6 | VSRL_FUNCT6
+            | VSRA_FUNCT6 => true,
+            VSUB_FUNCT6 | VMINU_FUNCT6 | VMIN_FUNCT6 | VMAXU_FUNCT6 | VMAX_FUNCT6 => {
+                funct3 != OPIVI_FUNCT3
+            }
+            VRSUB_FUNCT6 => funct3 != OPIVV_FUNCT3,
+            _ => false,
+        }
+    } else {
+        matches!(
+            funct6,
+            VMUL_FUNCT6 | VMULH_FUNCT6 | VMULHU_FUNCT6 | VMACC_FUNCT6
+        )
+    };
+    if !valid {
+        return Err(EmbiveError::InvalidInstruction);
+    }
+
+    for i in 0..vl {
+        if !engine.vector.active(unmasked, i) {
+            // Masked-off elements are left undisturbed
+            continue;
+        }
+
+        let a = engine.vector.element(vs2, i, sew)?;
+        let b = match operand {
+            Operand::Vector(vs1) => engine.vector.element(vs1, i, sew)?,
+            Operand::Scalar(value) => value,
+        };
+
+        let result = if integer {
+            match funct6 {
+                VADD_FUNCT6 => a.wrapping_add(b),
+                VSUB_FUNCT6 => a.wrapping_sub(b),
+                VRSUB_FUNCT6 => b.wrapping_sub(a),
+                VMINU_FUNCT6 => a.min(b),
+                VMIN_FUNCT6 => sext(a, sew).min(sext(b, sew)) as u32,
+                VMAXU_FUNCT6 => a.max(b),
+                VMAX_FUNCT6 => sext(a, sew).max(sext(b, sew)) as u32,
+                VAND_FUNCT6 => a & b,
+                VOR_FUNCT6 => a | b,
+                VXOR_FUNCT6 => a ^ b,
+                VSLL_FUNCT6 => a << (b & shift_mask),
+                VSRL_FUNCT6 => a >> (b & shift_mask),
+                _ => (sext(a, sew) >> (b & shift_mask)) as u32, // VSRA_FUNCT6
+            }
+        } else {
+            match funct6 {
+                VMUL_FUNCT6 => a.wrapping_mul(b),
+                VMULH_FUNCT6 => ((sext(a, sew) as i64 * sext(b, sew) as i64) >> (sew * 8)) as u32,
+                VMULHU_FUNCT6 => ((a as u64 * b as u64) >> (sew * 8)) as u32,
+                _ => {
+                    // VMACC_FUNCT6 (vd = vd + vs1 * vs2)
+                    let old = engine.vector.element(vd, i, sew)?;
+                    old.wrapping_add(a.wrapping_mul(b))
+                }
+            }
+        };
+
+        engine.vector.set_element(vd, i, sew, result)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::SliceMemory;
+
+    /// Encode an OP-V instruction.
+    fn op_v(funct6: u32, vm: bool, vs2: u32, rs1: u32, funct3: u32, vd: u32) -> u32 {
+        (funct6 << 26)
+            | ((vm as u32) << 25)
+            | (vs2 << 20)
+            | (rs1 << 15)
+            | (funct3 << 12)
+            | (vd << 7)
+            | 0b101_0111
+    }
+
+    /// vsetvli rd, rs1, vtype
+    fn vsetvli(rd: u32, rs1: u32, vtype: u32) -> u32 {
+        (vtype << 20) | (rs1 << 15) | (OPCFG_FUNCT3 << 12) | (rd << 7) | 0b101_0111
+    }
+
+    /// Set the engine to e32, m1 with vl = 4.
+    fn setup<M: Memory>(engine: &mut Engine<M>) {
+        let result = OpV::decode_execute(vsetvli(0, 0, 0b010_000), engine);
+        assert_eq!(result, Ok(true));
+        engine.vector.vl = 4;
+        for i in 0..4 {
+            engine.vector.set_element(1, i, 4, i as u32 + 1).unwrap();
+            engine.vector.set_element(2, i, 4, 10).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_vsetvli() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        *engine.registers.get_mut(11).unwrap() = 100;
+
+        // e8, m2 (VLMAX = 32)
+        let result = OpV::decode_execute(vsetvli(10, 11, 0b000_001), &mut engine);
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.registers.get(10), Ok(32));
+        assert_eq!(engine.vector.vl(), 32);
+        assert_eq!(engine.program_counter, INSTRUCTION_SIZE);
+
+        // e32, m1, rs1 = x0 (VLMAX = 4)
+        let result = OpV::decode_execute(vsetvli(10, 0, 0b010_000), &mut engine);
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.registers.get(10), Ok(4));
+
+        // e64 (unsupported)
+        let result = OpV::decode_execute(vsetvli(10, 11, 0b011_000), &mut engine);
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.registers.get(10), Ok(0));
+        assert_eq!(engine.vector.vtype(), VILL);
+    }
+
+    #[test]
+    fn test_vsetivli() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+
+        // vsetivli a0, 3, e16, m1
+        let inst = (0b11 << 30) | (0b001_000 << 20) | (3 << 15) | (OPCFG_FUNCT3 << 12) | (10 << 7);
+        let result = OpV::decode_execute(inst | 0b101_0111, &mut engine);
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.registers.get(10), Ok(3));
+    }
+
+    #[test]
+    fn test_vill() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+
+        let result =
+            OpV::decode_execute(op_v(VADD_FUNCT6, true, 1, 2, OPIVV_FUNCT3, 3), &mut engine);
+        assert_eq!(result, Err(EmbiveError::InvalidInstruction));
+    }
+
+    #[test]
+    fn test_vadd() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        setup(&mut engine);
+
+        let result =
+            OpV::decode_execute(op_v(VADD_FUNCT6, true, 1, 2, OPIVV_FUNCT3, 3), &mut engine);
+        assert_eq!(result, Ok(true));
+        for i in 0..4 {
+            assert_eq!(engine.vector.element(3, i, 4), Ok(i as u32 + 11));
+        }
+
+        // vadd.vi v3, v1, -1
+        let result = OpV::decode_execute(
+            op_v(VADD_FUNCT6, true, 1, 0b11111, OPIVI_FUNCT3, 3),
+            &mut engine,
+        );
+        assert_eq!(result, Ok(true));
+        for i in 0..4 {
+            assert_eq!(engine.vector.element(3, i, 4), Ok(i as u32));
+        }
+    }
+
+    #[test]
+    fn test_masked() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        setup(&mut engine);
+        engine.vector.get_mut(0).unwrap()[0] = 0b0101;
+        *engine.registers.get_mut(5).unwrap() = 7;
+
+        // vrsub.vx v1, v1, t0, v0.t (v1 = 7 - v1)
+        let result = OpV::decode_execute(
+            op_v(VRSUB_FUNCT6, false, 1, 5, OPIVX_FUNCT3, 1),
+            &mut engine,
+        );
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.vector.element(1, 0, 4), Ok(6));
+        assert_eq!(engine.vector.element(1, 1, 4), Ok(2));
+        assert_eq!(engine.vector.element(1, 2, 4), Ok(4));
+        assert_eq!(engine.vector.element(1, 3, 4), Ok(4));
+
+        // Masked destination overlapping the mask
+        let result =
+            OpV::decode_execute(op_v(VADD_FUNCT6, false, 1, 2, OPIVV_FUNCT3, 0), &mut engine);
+        assert_eq!(result, Err(EmbiveError::InvalidInstruction));
+    }
+
+    #[test]
+    fn test_vmerge() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        setup(&mut engine);
+        engine.vector.get_mut(0).unwrap()[0] = 0b0011;
+
+        // vmerge.vim v3, v1, 0, v0
+        let result = OpV::decode_execute(
+            op_v(VMERGE_FUNCT6, false, 1, 0, OPIVI_FUNCT3, 3),
+            &mut engine,
+        );
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.vector.element(3, 0, 4), Ok(0));
+        assert_eq!(engine.vector.element(3, 1, 4), Ok(0));
+        assert_eq!(engine.vector.element(3, 2, 4), Ok(3));
+        assert_eq!(engine.vector.element(3, 3, 4), Ok(4));
+
+        // vmv.v.v v3, v2
+        let result = OpV::decode_execute(
+            op_v(VMERGE_FUNCT6, true, 0, 2, OPIVV_FUNCT3, 3),
+            &mut engine,
+        );
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.vector.element(3, 3, 4), Ok(10));
+    }
+
+    #[test]
+    fn test_shifts_and_minmax() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        setup(&mut engine);
+        engine.vector.set_element(1, 0, 4, -8i32 as u32).unwrap();
+
+        // vsra.vi v3, v1, 1
+        let result =
+            OpV::decode_execute(op_v(VSRA_FUNCT6, true, 1, 1, OPIVI_FUNCT3, 3), &mut engine);
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.vector.element(3, 0, 4), Ok(-4i32 as u32));
+
+        // vmin.vv v3, v1, v2
+        let result =
+            OpV::decode_execute(op_v(VMIN_FUNCT6, true, 1, 2, OPIVV_FUNCT3, 3), &mut engine);
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.vector.element(3, 0, 4), Ok(-8i32 as u32));
+
+        // vmaxu.vv v3, v1, v2
+        let result =
+            OpV::decode_execute(op_v(VMAXU_FUNCT6, true, 1, 2, OPIVV_FUNCT3, 3), &mut engine);
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.vector.element(3, 0, 4), Ok(-8i32 as u32));
+        assert_eq!(engine.vector.element(3, 1, 4), Ok(10));
+
+        // vsub.vi doesn't exist
+        let result =
+            OpV::decode_execute(op_v(VSUB_FUNCT6, true, 1, 1, OPIVI_FUNCT3, 3), &mut engine);
+        assert_eq!(result, Err(EmbiveError::InvalidInstruction));
+    }
+
+    #[test]
+    fn test_vmacc_vredsum() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        setup(&mut engine);
+
+        // vmacc.vv v3, v1, v2 (v3 = 0 + v1 * v2)
+        let result =
+            OpV::decode_execute(op_v(VMACC_FUNCT6, true, 2, 1, OPMVV_FUNCT3, 3), &mut engine);
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.vector.element(3, 3, 4), Ok(40));
+
+        // vredsum.vs v4, v3, v2 (v4[0] = v2[0] + sum(v3))
+        let result = OpV::decode_execute(
+            op_v(VREDSUM_FUNCT6, true, 3, 2, OPMVV_FUNCT3, 4),
+            &mut engine,
+        );
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.vector.element(4, 0, 4), Ok(110));
+
+        // vmv.x.s a0, v4
+        let result = OpV::decode_execute(
+            op_v(VMV_SCALAR_FUNCT6, true, 4, 0, OPMVV_FUNCT3, 10),
+            &mut engine,
+        );
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.registers.get(10), Ok(110));
+
+        // vmv.s.x v5, a0
+        let result = OpV::decode_execute(
+            op_v(VMV_SCALAR_FUNCT6, true, 0, 10, OPMVX_FUNCT3, 5),
+            &mut engine,
+        );
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.vector.element(5, 0, 4), Ok(110));
+    }
+
+    #[test]
+    fn test_vmulh() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        setup(&mut engine);
+        *engine.registers.get_mut(5).unwrap() = -1;
+
+        // vmulh.vx v3, v2, t0 (10 * -1, high bits)
+        let result =
+            OpV::decode_execute(op_v(VMULH_FUNCT6, true, 2, 5, OPMVX_FUNCT3, 3), &mut engine);
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.vector.element(3, 0, 4), Ok(u32::MAX));
+
+        // vmulhu.vx v3, v2, t0 (10 * 0xFFFFFFFF, high bits)
+        let result = OpV::decode_execute(
+            op_v(VMULHU_FUNCT6, true, 2, 5, OPMVX_FUNCT3, 3),
+            &mut engine,
+        );
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.vector.element(3, 0, 4), Ok(9));
+    }
+
+    #[test]
+    fn test_group_alignment() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+
+        // e8, m2
+        let result = OpV::decode_execute(vsetvli(0, 0, 0b000_001), &mut engine);
+        assert_eq!(result, Ok(true));
+
+        let result =
+            OpV::decode_execute(op_v(VADD_FUNCT6, true, 2, 4, OPIVV_FUNCT3, 3), &mut engine);
+        assert_eq!(result, Err(EmbiveError::InvalidInstruction));
+    }
+}
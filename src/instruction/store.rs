@@ -4,9 +4,9 @@ use crate::instruction::format::TypeS;
 use crate::instruction::{Instruction, INSTRUCTION_SIZE};
 use crate::memory::Memory;
 
-const SB_FUNCT3: u8 = 0b000;
-const SH_FUNCT3: u8 = 0b001;
-const SW_FUNCT3: u8 = 0b010;
+pub(crate) const SB_FUNCT3: u8 = 0b000;
+pub(crate) const SH_FUNCT3: u8 = 0b001;
+pub(crate) const SW_FUNCT3: u8 = 0b010;
 
 /// Store OpCode
 /// Instructions: Sb, Sh, Sw
@@ -14,14 +14,15 @@ const SW_FUNCT3: u8 = 0b010;
 pub struct Store {}
 
 impl<M: Memory> Instruction<M> for Store {
-    #[inline(always)]
-    fn decode_execute(data: u32, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
-        let inst = TypeS::from(data);
+    type Operands = TypeS;
 
-        let rs1 = engine.registers.get(inst.rs1)?;
-        let rs2 = engine.registers.get(inst.rs2)?;
+    #[inline(always)]
+    fn execute(inst: TypeS, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
+        let rs1 = engine.registers.get_decoded(inst.rs1)?;
+        let rs2 = engine.registers.get_decoded(inst.rs2)?;
 
         let address = (rs1 as u32).wrapping_add_signed(inst.imm);
+        engine.check_stack_guard(address, 1 << (inst.funct3 & 0b11))?;
         match inst.funct3 {
             SB_FUNCT3 => engine.memory.store(address, (rs2 as u8).to_le_bytes())?,
             SH_FUNCT3 => engine.memory.store(address, (rs2 as u16).to_le_bytes())?,
@@ -0,0 +1,99 @@
+use crate::engine::Engine;
+use crate::error::EmbiveError;
+use crate::instruction::load_fp::VectorAccess;
+use crate::instruction::{Instruction, INSTRUCTION_SIZE};
+use crate::memory::Memory;
+
+/// Vector Store OpCode (STORE-FP)
+/// Instructions: vse8.v, vse16.v, vse32.v (unit-stride)
+/// Format: `nf | mew | mop | vm | sumop | rs1 | width | vs3`.
+pub struct StoreFp {}
+
+impl<M: Memory> Instruction<M> for StoreFp {
+    type Operands = u32;
+
+    #[inline(always)]
+    fn execute(data: u32, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
+        let access = VectorAccess::decode(data, engine)?;
+
+        for i in 0..access.vl {
+            if !engine.vector.active(access.unmasked, i) {
+                continue;
+            }
+
+            let address = access.address.wrapping_add((i * access.eew) as u32);
+            engine.check_stack_guard(address, access.eew as u32)?;
+            let value = engine
+                .vector
+                .element(access.vd, i, access.eew)?
+                .to_le_bytes();
+            match access.eew {
+                1 => engine.memory.store(address, [value[0]])?,
+                2 => engine.memory.store(address, [value[0], value[1]])?,
+                _ => engine.memory.store(address, value)?,
+            }
+        }
+
+        // Go to next instruction
+        engine.program_counter = engine.program_counter.wrapping_add(INSTRUCTION_SIZE);
+
+        // Continue execution
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{SliceMemory, RAM_OFFSET};
+
+    /// Encode a unit-stride store.
+    fn vse(width: u32, vm: bool, rs1: u32, vs3: u32) -> u32 {
+        ((vm as u32) << 25) | (rs1 << 15) | (width << 12) | (vs3 << 7) | 0b010_0111
+    }
+
+    #[test]
+    fn test_vse32() {
+        let mut ram = [0; 12];
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        engine.vector.vtype = 0b010_000; // e32, m1
+        engine.vector.vl = 2;
+        engine.vector.set_element(1, 0, 4, 0x04030201).unwrap();
+        engine.vector.set_element(1, 1, 4, 0x08070605).unwrap();
+        engine.vector.set_element(1, 2, 4, 0xFFFFFFFF).unwrap();
+        *engine.registers.get_mut(5).unwrap() = RAM_OFFSET as i32;
+
+        let result = StoreFp::decode_execute(vse(0b110, true, 5, 1), &mut engine);
+        assert_eq!(result, Ok(true));
+        assert_eq!(engine.program_counter, INSTRUCTION_SIZE);
+        assert_eq!(ram, [1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_vse8_masked() {
+        let mut ram = [0; 4];
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        engine.vector.vtype = 0b000_000; // e8, m1
+        engine.vector.vl = 4;
+        engine.vector.get_mut(0).unwrap()[0] = 0b0110;
+        engine.vector.get_mut(1).unwrap()[..4].copy_from_slice(&[1, 2, 3, 4]);
+        *engine.registers.get_mut(5).unwrap() = RAM_OFFSET as i32;
+
+        let result = StoreFp::decode_execute(vse(0b000, false, 5, 1), &mut engine);
+        assert_eq!(result, Ok(true));
+        assert_eq!(ram, [0, 2, 3, 0]);
+    }
+
+    #[test]
+    fn test_vse_unsupported() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut engine = Engine::new(&mut memory, Default::default()).unwrap();
+        engine.vector.vtype = 0b010_000; // e32, m1
+
+        // fsw (scalar floating point)
+        let result = StoreFp::decode_execute(vse(0b010, true, 5, 1), &mut engine);
+        assert_eq!(result, Err(EmbiveError::InvalidInstruction));
+    }
+}
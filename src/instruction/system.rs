@@ -1,34 +1,79 @@
+use crate::csr;
 use crate::engine::Engine;
 use crate::error::EmbiveError;
 use crate::instruction::format::TypeI;
 use crate::instruction::Instruction;
 use crate::memory::Memory;
+use crate::trap::Exception;
 
 use super::INSTRUCTION_SIZE;
 
-const ECALL_IMM: i32 = 0x0000;
-const EBREAK_IMM: i32 = 0x0001;
+pub(crate) const ECALL_IMM: i32 = 0x0000;
+pub(crate) const EBREAK_IMM: i32 = 0x0001;
+pub(crate) const MRET_IMM: i32 = 0x0302;
+#[cfg(feature = "debugger")]
+pub(crate) const DRET_IMM: i32 = 0x07B2;
 
-const EBREAK_ECALL_FUNCT3: u8 = 0b000;
+pub(crate) const EBREAK_ECALL_FUNCT3: u8 = 0b000;
+pub(crate) const CSRRW_FUNCT3: u8 = 0b001;
+pub(crate) const CSRRS_FUNCT3: u8 = 0b010;
+pub(crate) const CSRRC_FUNCT3: u8 = 0b011;
+pub(crate) const CSRRWI_FUNCT3: u8 = 0b101;
+pub(crate) const CSRRSI_FUNCT3: u8 = 0b110;
+pub(crate) const CSRRCI_FUNCT3: u8 = 0b111;
 
 /// System OpCode
 /// Format: I-Type.
-/// Action: Halt
+/// Action: Syscall (ecall), Halt (ebreak), return from a guest trap (mret, check [`crate::trap`]),
+/// leave the debug monitor (dret) or CSR access (check [`crate::csr`])
 pub struct System {}
 
 impl<M: Memory> Instruction<M> for System {
-    #[inline(always)]
-    fn decode_execute(data: u32, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
-        let inst = TypeI::from(data);
+    type Operands = TypeI;
 
+    #[inline(always)]
+    fn execute(inst: TypeI, engine: &mut Engine<M>) -> Result<bool, EmbiveError> {
+        let pc = engine.program_counter;
         let ret = match inst.funct3 {
             EBREAK_ECALL_FUNCT3 => {
                 match inst.imm {
-                    ECALL_IMM => engine.syscall().map(|_| true), // Execute the syscall function (ecall)
-                    EBREAK_IMM => Ok(false),                     // Halt the execution (ebreak)
+                    // Vector to the guest trap handler, if delegated (ecall)
+                    ECALL_IMM if engine.trap(Exception::EnvironmentCall, pc, 0) => return Ok(true),
+                    // Execute the syscall function (ecall)
+                    ECALL_IMM => match engine.syscall() {
+                        // Suspended, retry the syscall when woken
+                        Ok(false) => return Ok(false),
+                        ret => ret.map(|_| true),
+                    },
+                    EBREAK_IMM => Ok(false), // Halt the execution (ebreak)
+                    MRET_IMM
+                        if inst.rd == 0 && inst.rs1 == 0 && engine.config.guest_trap_handler() =>
+                    {
+                        // Return from the guest trap handler (mret), completing the interrupt in service
+                        #[cfg(feature = "interrupt")]
+                        if engine.trap.mcause & crate::trap::INTERRUPT_CAUSE != 0 {
+                            engine.interrupt.complete();
+                        }
+
+                        engine.program_counter = engine.trap.mret();
+                        return Ok(true);
+                    }
+                    #[cfg(feature = "debugger")]
+                    DRET_IMM if inst.rd == 0 && inst.rs1 == 0 => {
+                        // Leave the debug monitor (dret)
+                        let dpc = engine
+                            .debugger
+                            .monitor
+                            .dret()
+                            .ok_or(EmbiveError::InvalidInstruction)?;
+                        engine.program_counter = dpc;
+                        return Ok(true);
+                    }
                     _ => Err(EmbiveError::InvalidInstruction),
                 }
             }
+            CSRRW_FUNCT3 | CSRRS_FUNCT3 | CSRRC_FUNCT3 | CSRRWI_FUNCT3 | CSRRSI_FUNCT3
+            | CSRRCI_FUNCT3 => csr::execute(engine, inst).map(|_| true),
             _ => Err(EmbiveError::InvalidInstruction),
         };
 
@@ -128,7 +173,7 @@ mod tests {
             engine
                 .memory
                 .load::<4>(get_ram_addr())
-                .map(|v| i32::from_le_bytes(v)),
+                .map(i32::from_le_bytes),
             Ok(-1)
         );
         assert_eq!(engine.program_counter, INSTRUCTION_SIZE);
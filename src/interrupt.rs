@@ -0,0 +1,596 @@
+//! Interrupt Module
+//!
+//! Interrupts are raised by the host ([`crate::engine::Engine::raise_interrupt`]) and delivered
+//! by the engine at the points selected by the configured [`Granularity`].
+//! Checking for pending interrupts less often makes the interpreter faster, at the cost of a higher
+//! (but still bounded) delivery latency.
+//!
+//! Latency bounds (guest instructions executed between raising and delivering an interrupt):
+//! - Instruction granularity: [`INSTRUCTION_LATENCY`].
+//! - Basic block granularity: [`BASIC_BLOCK_LATENCY`].
+//! - Yield granularity: the configured instruction limit (unbounded without one).
+//!
+//! ## Lines, Priorities and Nesting
+//! There are [`INTERRUPT_LINES`] interrupt lines, each with its own enable bit and priority.
+//! When more than one line is pending, the one with the highest priority is delivered first
+//! (ties are broken by the lowest line number).
+//!
+//! Delivery follows the RISC-V machine-mode semantics: the global enable (`mstatus.MIE`) is saved
+//! into `mstatus.MPIE` and cleared, so the handler isn't interrupted. If the handler re-enables
+//! interrupts, only lines with a strictly higher priority than the ones in service can preempt it.
+//! Completing an interrupt ([`crate::engine::Engine::complete_interrupt`]) restores `mstatus.MIE`
+//! from `mstatus.MPIE`, just like the `mret` instruction.
+//!
+//! ## Guest Handler
+//! Interrupts are delivered to the host interrupt function ([`InterruptFn`]) by default. With
+//! [`crate::engine::Config::guest_interrupts`], they are delivered to the guest trap handler instead (`mtvec`,
+//! check [`crate::trap`]), so event-driven guests (ex.: timers, UART RX) don't need to poll through syscalls.
+//! The guest `mret` completes the interrupt in service.
+//!
+//! ## Software Interrupts
+//! Guests can notify other sandboxes (doorbell-style) by using the syscall configured in
+//! [`crate::engine::Config::software_interrupt_nr`]:
+//! - `a0`: Target sandbox id (must be permitted by [`crate::engine::Config::software_interrupt_targets`]).
+//! - `a1`: Interrupt line to raise in the target sandbox.
+//!
+//! Returns `0` in `a0` on success, or one of the `SOFTWARE_INTERRUPT_*` error codes.
+//! Sent interrupts are queued by the engine (up to [`SOFTWARE_INTERRUPT_QUEUE`]) and routed by the host scheduler,
+//! either manually ([`crate::engine::Engine::take_software_interrupt`]) or with [`route`].
+//!
+//! ## External Interrupts
+//! A platform-level interrupt controller model is available in [`plic`], for guests that handle
+//! external interrupts through the standard RISC-V claim/complete registers.
+
+pub mod plic;
+
+use crate::engine::Engine;
+use crate::error::EmbiveError;
+use crate::memory::Memory;
+use crate::syscall::Errno;
+
+/// Number of interrupt lines.
+pub const INTERRUPT_LINES: usize = 32;
+
+/// Number of interrupt priority levels (from 0 to `PRIORITY_LEVELS - 1`, higher is more urgent).
+pub const PRIORITY_LEVELS: u8 = 32;
+
+/// Maximum number of queued software interrupts (per sandbox).
+pub const SOFTWARE_INTERRUPT_QUEUE: usize = 8;
+
+/// Software interrupt error code: target sandbox is not permitted.
+pub const SOFTWARE_INTERRUPT_NOT_PERMITTED: i32 = Errno::AccessDenied.code();
+
+/// Software interrupt error code: queue is full (host hasn't routed previous interrupts yet).
+pub const SOFTWARE_INTERRUPT_QUEUE_FULL: i32 = Errno::WouldBlock.code();
+
+/// Maximum delivery latency (in guest instructions) with [`Granularity::Instruction`].
+pub const INSTRUCTION_LATENCY: u32 = 0;
+
+/// Maximum delivery latency (in guest instructions) with [`Granularity::BasicBlock`].
+/// Pending interrupts are checked at the end of every basic block, or after this many
+/// sequential instructions, whichever comes first.
+pub const BASIC_BLOCK_LATENCY: u32 = 32;
+
+/// Interrupt function signature
+///
+/// This function is called by the engine when a pending interrupt is delivered.
+/// It has full access to the engine, so it can inspect and modify the guest state
+/// (ex.: redirect the program counter to a guest handler).
+///
+/// Arguments:
+/// - `cause`: Interrupt line being delivered (from 0 to [`INTERRUPT_LINES`] - 1).
+/// - `engine`: Mutable reference to the engine.
+///
+/// The interrupt remains in service until [`crate::engine::Engine::complete_interrupt`] is called.
+///
+/// Returns:
+/// - `Ok(())`: Interrupt was handled.
+/// - `Err(EmbiveError)`: Failed to handle the interrupt, execution is stopped.
+pub type InterruptFn<M> = fn(cause: u32, engine: &mut Engine<M>) -> Result<(), EmbiveError>;
+
+/// Interrupt Delivery Granularity
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    /// Check for pending interrupts before every instruction (lowest latency, slowest).
+    #[default]
+    Instruction,
+    /// Check for pending interrupts after every control transfer (jumps and taken branches),
+    /// or after [`BASIC_BLOCK_LATENCY`] sequential instructions.
+    BasicBlock,
+    /// Check for pending interrupts only when the engine starts running (after a yield).
+    Yield,
+}
+
+impl Granularity {
+    /// Get the maximum delivery latency for this granularity.
+    ///
+    /// Arguments:
+    /// - `instruction_limit`: Instruction limit of the engine (0 = No limit).
+    ///
+    /// Returns:
+    /// - `Some(u32)`: Maximum number of guest instructions executed before delivery.
+    /// - `None`: Latency is unbounded (yield granularity without an instruction limit).
+    pub const fn latency(&self, instruction_limit: u32) -> Option<u32> {
+        match self {
+            Granularity::Instruction => Some(INSTRUCTION_LATENCY),
+            Granularity::BasicBlock => Some(BASIC_BLOCK_LATENCY),
+            Granularity::Yield => {
+                if instruction_limit > 0 {
+                    Some(instruction_limit)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Software Interrupt (sent by a guest to another sandbox)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SoftwareInterrupt {
+    /// Target sandbox id.
+    pub target: u32,
+    /// Interrupt line to raise in the target sandbox.
+    pub line: u32,
+}
+
+/// Outgoing software interrupt queue (FIFO).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Outbox {
+    /// Queued software interrupts.
+    queue: [SoftwareInterrupt; SOFTWARE_INTERRUPT_QUEUE],
+    /// Number of queued software interrupts.
+    len: usize,
+}
+
+impl Outbox {
+    /// Create a new empty queue.
+    const fn new() -> Self {
+        Self {
+            queue: [SoftwareInterrupt { target: 0, line: 0 }; SOFTWARE_INTERRUPT_QUEUE],
+            len: 0,
+        }
+    }
+
+    /// Push a software interrupt to the back of the queue.
+    ///
+    /// Returns:
+    /// - `true`: Software interrupt was queued.
+    /// - `false`: Queue is full.
+    pub(crate) fn push(&mut self, interrupt: SoftwareInterrupt) -> bool {
+        if self.len >= SOFTWARE_INTERRUPT_QUEUE {
+            return false;
+        }
+
+        self.queue[self.len] = interrupt;
+        self.len += 1;
+        true
+    }
+
+    /// Pop a software interrupt from the front of the queue.
+    pub(crate) fn pop(&mut self) -> Option<SoftwareInterrupt> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let interrupt = self.queue[0];
+        self.queue.copy_within(1..self.len, 0);
+        self.len -= 1;
+        Some(interrupt)
+    }
+}
+
+/// Route the software interrupts sent between sandboxes.
+/// The sandbox id of each engine is its index in the `engines` slice.
+///
+/// Arguments:
+/// - `engines`: Engines to route software interrupts between.
+///
+/// Returns:
+/// - `Ok(())`: All software interrupts were routed.
+/// - `Err(EmbiveError)`: Failed to raise a software interrupt in the target sandbox.
+///     - Target sandbox doesn't exist ([`EmbiveError::InvalidInterrupt`]).
+///     - Target sandbox failed to raise the interrupt.
+pub fn route<M: Memory>(engines: &mut [Engine<'_, M>]) -> Result<(), EmbiveError> {
+    for source in 0..engines.len() {
+        while let Some(interrupt) = engines[source].take_software_interrupt() {
+            engines
+                .get_mut(interrupt.target as usize)
+                .ok_or(EmbiveError::InvalidInterrupt)?
+                .raise_interrupt(interrupt.line)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Interrupt Controller State
+///
+/// By default, all lines are enabled with priority 0 and interrupts are globally enabled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interrupt {
+    /// Pending lines (bitmask).
+    pub(crate) pending: u32,
+    /// Enabled lines (bitmask).
+    pub(crate) enabled: u32,
+    /// Priority of each line.
+    pub(crate) priority: [u8; INTERRUPT_LINES],
+    /// Priority levels currently in service (bitmask).
+    pub(crate) active: u32,
+    /// Global interrupt enable (`mstatus.MIE`).
+    pub(crate) mie: bool,
+    /// Previous global interrupt enable (`mstatus.MPIE`).
+    pub(crate) mpie: bool,
+    /// Sequential instructions executed since the last check (basic block granularity).
+    pub(crate) sequential: u32,
+    /// Outgoing software interrupts.
+    pub(crate) outbox: Outbox,
+}
+
+impl Default for Interrupt {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Interrupt {
+    /// Create a new interrupt controller state.
+    /// All lines are enabled with priority 0, interrupts are globally enabled.
+    pub(crate) const fn new() -> Self {
+        Self {
+            pending: 0,
+            enabled: u32::MAX,
+            priority: [0; INTERRUPT_LINES],
+            active: 0,
+            mie: true,
+            mpie: true,
+            sequential: 0,
+            outbox: Outbox::new(),
+        }
+    }
+
+    /// Enable or disable an interrupt line.
+    ///
+    /// Arguments:
+    /// - `line`: Interrupt line (from 0 to [`INTERRUPT_LINES`] - 1).
+    /// - `enabled`: If the line should be enabled.
+    ///
+    /// Returns:
+    /// - `Ok(())`: Success.
+    /// - `Err(EmbiveError)`: The interrupt line is out of bounds.
+    pub fn set_enabled(&mut self, line: u32, enabled: bool) -> Result<(), EmbiveError> {
+        let mask = line_mask(line)?;
+        if enabled {
+            self.enabled |= mask;
+        } else {
+            self.enabled &= !mask;
+        }
+
+        Ok(())
+    }
+
+    /// Set the priority of an interrupt line.
+    ///
+    /// Arguments:
+    /// - `line`: Interrupt line (from 0 to [`INTERRUPT_LINES`] - 1).
+    /// - `priority`: Priority (from 0 to [`PRIORITY_LEVELS`] - 1, higher is more urgent).
+    ///
+    /// Returns:
+    /// - `Ok(())`: Success.
+    /// - `Err(EmbiveError)`: The interrupt line or priority is out of bounds.
+    pub fn set_priority(&mut self, line: u32, priority: u8) -> Result<(), EmbiveError> {
+        line_mask(line)?;
+        if priority >= PRIORITY_LEVELS {
+            return Err(EmbiveError::InvalidInterrupt);
+        }
+
+        self.priority[line as usize] = priority;
+        Ok(())
+    }
+
+    /// Globally enable or disable interrupts (`mstatus.MIE`).
+    ///
+    /// Arguments:
+    /// - `enabled`: If interrupts should be globally enabled.
+    pub fn set_global_enabled(&mut self, enabled: bool) {
+        self.mie = enabled;
+    }
+
+    /// Check if interrupts are globally enabled (`mstatus.MIE`).
+    pub fn global_enabled(&self) -> bool {
+        self.mie
+    }
+
+    /// Get the pending interrupt lines (bitmask).
+    pub fn pending(&self) -> u32 {
+        self.pending
+    }
+
+    /// Get the priority level currently in service, if any.
+    pub fn level(&self) -> Option<u8> {
+        if self.active == 0 {
+            return None;
+        }
+
+        Some((u32::BITS - 1 - self.active.leading_zeros()) as u8)
+    }
+
+    /// Mark an interrupt line as pending.
+    ///
+    /// Arguments:
+    /// - `line`: Interrupt line (from 0 to [`INTERRUPT_LINES`] - 1).
+    ///
+    /// Returns:
+    /// - `Ok(())`: Success.
+    /// - `Err(EmbiveError)`: The interrupt line is out of bounds.
+    pub(crate) fn raise(&mut self, line: u32) -> Result<(), EmbiveError> {
+        self.pending |= line_mask(line)?;
+        Ok(())
+    }
+
+    /// Claim the highest priority deliverable interrupt, entering it:
+    /// - `mstatus.MPIE` is set to `mstatus.MIE`, `mstatus.MIE` is cleared.
+    /// - The priority level of the line is marked as in service.
+    ///
+    /// Returns:
+    /// - `Some(u32)`: The interrupt line that was claimed.
+    /// - `None`: No interrupt can be delivered.
+    #[inline(always)]
+    pub(crate) fn claim(&mut self) -> Option<u32> {
+        let deliverable = self.pending & self.enabled;
+        if !self.mie || deliverable == 0 {
+            return None;
+        }
+
+        // Highest priority (lowest line number on ties) that preempts the current level
+        let mut claimed: Option<(u32, u8)> = None;
+        for line in 0..INTERRUPT_LINES as u32 {
+            if deliverable & (1 << line) == 0 {
+                continue;
+            }
+
+            let priority = self.priority[line as usize];
+            if claimed.map_or(true, |(_, p)| priority > p) {
+                claimed = Some((line, priority));
+            }
+        }
+
+        let (line, priority) = claimed?;
+        if self.level().is_some_and(|level| priority <= level) {
+            // Can't preempt the interrupt in service
+            return None;
+        }
+
+        self.pending &= !(1 << line);
+        self.active |= 1 << priority;
+        self.mpie = self.mie;
+        self.mie = false;
+
+        Some(line)
+    }
+
+    /// Complete the interrupt in service (`mret` semantics):
+    /// - `mstatus.MIE` is set to `mstatus.MPIE`, `mstatus.MPIE` is set.
+    /// - The highest priority level in service is cleared.
+    pub(crate) fn complete(&mut self) {
+        if let Some(level) = self.level() {
+            self.active &= !(1 << level);
+        }
+
+        self.mie = self.mpie;
+        self.mpie = true;
+    }
+}
+
+/// Get the bitmask of an interrupt line.
+#[inline(always)]
+fn line_mask(line: u32) -> Result<u32, EmbiveError> {
+    if line as usize >= INTERRUPT_LINES {
+        return Err(EmbiveError::InvalidInterrupt);
+    }
+
+    Ok(1 << line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency() {
+        assert_eq!(
+            Granularity::Instruction.latency(0),
+            Some(INSTRUCTION_LATENCY)
+        );
+        assert_eq!(
+            Granularity::BasicBlock.latency(10),
+            Some(BASIC_BLOCK_LATENCY)
+        );
+        assert_eq!(Granularity::Yield.latency(0), None);
+        assert_eq!(Granularity::Yield.latency(100), Some(100));
+    }
+
+    #[test]
+    fn test_claim_priority() {
+        let mut interrupt = Interrupt::new();
+        interrupt.set_priority(3, 1).unwrap();
+        interrupt.set_priority(5, 2).unwrap();
+        interrupt.raise(1).unwrap();
+        interrupt.raise(3).unwrap();
+        interrupt.raise(5).unwrap();
+
+        assert_eq!(interrupt.claim(), Some(5));
+        assert_eq!(interrupt.level(), Some(2));
+        assert!(!interrupt.global_enabled());
+
+        // Globally disabled while in service
+        assert_eq!(interrupt.claim(), None);
+
+        interrupt.complete();
+        assert_eq!(interrupt.claim(), Some(3));
+        interrupt.complete();
+        assert_eq!(interrupt.claim(), Some(1));
+        interrupt.complete();
+        assert_eq!(interrupt.claim(), None);
+        assert_eq!(interrupt.level(), None);
+        assert!(interrupt.global_enabled());
+    }
+
+    #[test]
+    fn test_claim_disabled_line() {
+        let mut interrupt = Interrupt::new();
+        interrupt.set_enabled(2, false).unwrap();
+        interrupt.raise(2).unwrap();
+
+        assert_eq!(interrupt.claim(), None);
+        assert_eq!(interrupt.pending(), 1 << 2);
+
+        interrupt.set_enabled(2, true).unwrap();
+        assert_eq!(interrupt.claim(), Some(2));
+    }
+
+    #[test]
+    fn test_nesting() {
+        let mut interrupt = Interrupt::new();
+        interrupt.set_priority(0, 1).unwrap();
+        interrupt.set_priority(1, 1).unwrap();
+        interrupt.set_priority(2, 3).unwrap();
+
+        interrupt.raise(0).unwrap();
+        assert_eq!(interrupt.claim(), Some(0));
+
+        // Handler re-enables interrupts, same priority can't preempt
+        interrupt.set_global_enabled(true);
+        interrupt.raise(1).unwrap();
+        assert_eq!(interrupt.claim(), None);
+
+        // Higher priority preempts
+        interrupt.raise(2).unwrap();
+        assert_eq!(interrupt.claim(), Some(2));
+        assert_eq!(interrupt.level(), Some(3));
+
+        // Complete nested interrupt, back to the first handler (interrupts enabled)
+        interrupt.complete();
+        assert_eq!(interrupt.level(), Some(1));
+        assert!(interrupt.global_enabled());
+
+        // Complete first handler, pending line 1 is now deliverable
+        interrupt.complete();
+        assert_eq!(interrupt.claim(), Some(1));
+    }
+
+    #[test]
+    fn test_outbox() {
+        let mut outbox = Outbox::new();
+        for line in 0..SOFTWARE_INTERRUPT_QUEUE as u32 {
+            assert!(outbox.push(SoftwareInterrupt { target: 1, line }));
+        }
+        assert!(!outbox.push(SoftwareInterrupt { target: 1, line: 0 }));
+
+        for line in 0..SOFTWARE_INTERRUPT_QUEUE as u32 {
+            assert_eq!(outbox.pop(), Some(SoftwareInterrupt { target: 1, line }));
+        }
+        assert_eq!(outbox.pop(), None);
+    }
+
+    #[test]
+    fn test_route() {
+        use crate::engine::Config;
+        use crate::memory::SliceMemory;
+        use crate::register::Register;
+
+        fn interrupt_fn(cause: u32, engine: &mut Engine<SliceMemory>) -> Result<(), EmbiveError> {
+            engine.registers.inner[Register::A2 as usize] = cause as i32;
+            engine.complete_interrupt();
+            Ok(())
+        }
+
+        let code = &[
+            0x93, 0x08, 0x00, 0x10, // li   a7, 256    (Software interrupt syscall)
+            0x13, 0x05, 0x10, 0x00, // li   a0, 1      (Target sandbox)
+            0x93, 0x05, 0x50, 0x00, // li   a1, 5      (Line)
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x73, 0x00, 0x10, 0x00, // ebreak          (Halt)
+        ];
+        let config = |targets| {
+            Config::default()
+                .with_interrupt_fn(Some(interrupt_fn))
+                .with_software_interrupts(Some(256), targets)
+        };
+
+        let mut memory0 = SliceMemory::new(code, &mut []);
+        let mut memory1 = SliceMemory::new(code, &mut []);
+        let mut engines = [
+            Engine::new(&mut memory0, config(0b10)).unwrap(),
+            Engine::new(&mut memory1, config(0b01)).unwrap(),
+        ];
+
+        // Sandbox 0 is permitted to notify sandbox 1
+        assert_eq!(engines[0].run(), Ok(false));
+        assert_eq!(engines[0].registers.get(Register::A0 as usize), Ok(0));
+
+        // Sandbox 1 is only permitted to notify sandbox 0
+        assert_eq!(engines[1].run(), Ok(false));
+        assert_eq!(
+            engines[1].registers.get(Register::A0 as usize),
+            Ok(SOFTWARE_INTERRUPT_NOT_PERMITTED)
+        );
+
+        route(&mut engines).unwrap();
+        assert_eq!(engines[1].interrupt.pending(), 1 << 5);
+        assert_eq!(engines[0].interrupt.pending(), 0);
+    }
+
+    #[test]
+    fn test_guest_handler() {
+        use crate::engine::Config;
+        use crate::memory::SliceMemory;
+        use crate::register::Register;
+        use crate::trap::{INTERRUPT_CAUSE, LOCAL_INTERRUPT_BASE};
+
+        let code = &[
+            0x93, 0x02, 0x00, 0x01, // li   t0, 0x10 (handler)
+            0x73, 0x90, 0x52, 0x30, // csrw mtvec, t0
+            0x13, 0x05, 0x10, 0x00, // li   a0, 1
+            0x73, 0x00, 0x10, 0x00, // ebreak
+            // Handler
+            0xf3, 0x25, 0x20, 0x34, // csrr a1, mcause
+            0x73, 0x00, 0x20, 0x30, // mret
+        ];
+        let mut memory = SliceMemory::new(code, &mut []);
+        let config = Config::default().with_guest_interrupts(true);
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        // Kept pending until the handler is installed
+        engine.raise_interrupt(3).unwrap();
+        assert_eq!(engine.step(), Ok(true));
+        assert_eq!(engine.step(), Ok(true));
+        assert_eq!(engine.interrupt.pending(), 1 << 3);
+
+        // Delivered before the next instruction, completed by `mret`
+        assert_eq!(engine.step(), Ok(true));
+        assert_eq!(engine.trap.mepc, 0x8);
+        assert_eq!(engine.interrupt.level(), Some(0));
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(
+            engine.registers.get(Register::A1 as usize),
+            Ok((INTERRUPT_CAUSE | (LOCAL_INTERRUPT_BASE + 3)) as i32)
+        );
+        assert_eq!(engine.registers.get(Register::A0 as usize), Ok(1));
+        assert_eq!(engine.interrupt.level(), None);
+        assert!(engine.interrupt.global_enabled());
+    }
+
+    #[test]
+    fn test_invalid_line() {
+        let mut interrupt = Interrupt::new();
+        assert_eq!(
+            interrupt.raise(INTERRUPT_LINES as u32),
+            Err(EmbiveError::InvalidInterrupt)
+        );
+        assert_eq!(
+            interrupt.set_priority(0, PRIORITY_LEVELS),
+            Err(EmbiveError::InvalidInterrupt)
+        );
+    }
+}
@@ -0,0 +1,364 @@
+//! Platform-Level Interrupt Controller (PLIC) Lite
+//!
+//! A small PLIC model following the standard RISC-V platform register layout (single context),
+//! so guests can handle external interrupts with the usual claim/complete flow.
+//!
+//! Register map (offsets from the PLIC base address, 32-bit accesses only):
+//! - `0x000000 + 4 * id`: Source priority (0 = never interrupt, up to [`PLIC_PRIORITY_MAX`]).
+//! - `0x001000`: Pending sources (bitmask, read-only).
+//! - `0x002000`: Enabled sources (bitmask).
+//! - `0x200000`: Priority threshold.
+//! - `0x200004`: Claim (read) / Complete (write).
+//!
+//! The PLIC drives the [`EXTERNAL_INTERRUPT_LINE`] of the engine, check [`update`].
+
+use core::cell::Cell;
+
+use crate::engine::Engine;
+use crate::error::EmbiveError;
+use crate::memory::Memory;
+
+/// Default PLIC base address (standard RISC-V platform address).
+pub const PLIC_BASE: u32 = 0x0C00_0000;
+
+/// Size of the PLIC address space.
+pub const PLIC_SIZE: u32 = 0x0040_0000;
+
+/// Number of interrupt sources (ids from 1 to `PLIC_SOURCES`, id 0 is reserved).
+pub const PLIC_SOURCES: u32 = 31;
+
+/// Maximum source priority.
+pub const PLIC_PRIORITY_MAX: u32 = 7;
+
+/// Engine interrupt line driven by the PLIC (machine external interrupt).
+pub const EXTERNAL_INTERRUPT_LINE: u32 = 11;
+
+const PRIORITY_OFFSET: u32 = 0x00_0000;
+const PENDING_OFFSET: u32 = 0x00_1000;
+const ENABLE_OFFSET: u32 = 0x00_2000;
+const THRESHOLD_OFFSET: u32 = 0x20_0000;
+const CLAIM_COMPLETE_OFFSET: u32 = 0x20_0004;
+
+/// PLIC State
+#[derive(Debug, Default)]
+pub struct Plic {
+    /// Priority of each source (index 0 is reserved).
+    priority: [u32; PLIC_SOURCES as usize + 1],
+    /// Pending sources (bitmask).
+    pending: Cell<u32>,
+    /// Enabled sources (bitmask).
+    enabled: u32,
+    /// Priority threshold.
+    threshold: u32,
+    /// Claimed sources, waiting for completion (bitmask).
+    claimed: Cell<u32>,
+}
+
+impl Plic {
+    /// Create a new PLIC.
+    /// All sources are disabled, with priority 0.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Raise an interrupt source (marks it as pending).
+    ///
+    /// Arguments:
+    /// - `source`: Source id (from 1 to [`PLIC_SOURCES`]).
+    ///
+    /// Returns:
+    /// - `Ok(())`: Source is pending.
+    /// - `Err(EmbiveError)`: The source id is out of bounds.
+    pub fn raise(&mut self, source: u32) -> Result<(), EmbiveError> {
+        if source == 0 || source > PLIC_SOURCES {
+            return Err(EmbiveError::InvalidInterrupt);
+        }
+
+        self.pending.set(self.pending.get() | (1 << source));
+        Ok(())
+    }
+
+    /// Check if the PLIC is asserting the external interrupt
+    /// (an enabled source is pending with a priority above the threshold).
+    pub fn asserted(&self) -> bool {
+        self.highest() != 0
+    }
+
+    /// Get the highest priority source that can be claimed (0 if none).
+    fn highest(&self) -> u32 {
+        let candidates = self.pending.get() & self.enabled & !self.claimed.get();
+
+        let mut highest = 0;
+        let mut highest_priority = self.threshold;
+        for source in 1..=PLIC_SOURCES {
+            let priority = self.priority[source as usize];
+            if candidates & (1 << source) != 0 && priority > highest_priority {
+                highest = source;
+                highest_priority = priority;
+            }
+        }
+
+        highest
+    }
+
+    /// Claim the highest priority source (clears its pending bit).
+    fn claim(&self) -> u32 {
+        let source = self.highest();
+        if source != 0 {
+            self.pending.set(self.pending.get() & !(1 << source));
+            self.claimed.set(self.claimed.get() | (1 << source));
+        }
+
+        source
+    }
+
+    /// Read a PLIC register.
+    fn read(&self, offset: u32) -> Result<u32, EmbiveError> {
+        match offset {
+            PRIORITY_OFFSET..PENDING_OFFSET => {
+                let source = (offset - PRIORITY_OFFSET) / 4;
+                self.priority
+                    .get(source as usize)
+                    .copied()
+                    .ok_or(EmbiveError::InvalidMemoryAddress)
+            }
+            PENDING_OFFSET => Ok(self.pending.get()),
+            ENABLE_OFFSET => Ok(self.enabled),
+            THRESHOLD_OFFSET => Ok(self.threshold),
+            CLAIM_COMPLETE_OFFSET => Ok(self.claim()),
+            _ => Err(EmbiveError::InvalidMemoryAddress),
+        }
+    }
+
+    /// Write a PLIC register.
+    fn write(&mut self, offset: u32, value: u32) -> Result<(), EmbiveError> {
+        match offset {
+            PRIORITY_OFFSET..PENDING_OFFSET => {
+                let source = (offset - PRIORITY_OFFSET) / 4;
+                if source == 0 {
+                    // Source 0 is reserved (hardwired to 0)
+                    return Ok(());
+                }
+
+                let priority = self
+                    .priority
+                    .get_mut(source as usize)
+                    .ok_or(EmbiveError::InvalidMemoryAddress)?;
+                *priority = value.min(PLIC_PRIORITY_MAX);
+            }
+            PENDING_OFFSET => {} // Read-only
+            ENABLE_OFFSET => self.enabled = value & !1,
+            THRESHOLD_OFFSET => self.threshold = value.min(PLIC_PRIORITY_MAX),
+            CLAIM_COMPLETE_OFFSET => {
+                if value != 0 && value <= PLIC_SOURCES {
+                    self.claimed.set(self.claimed.get() & !(1 << value));
+                }
+            }
+            _ => return Err(EmbiveError::InvalidMemoryAddress),
+        }
+
+        Ok(())
+    }
+}
+
+/// Memory wrapper mapping a [`Plic`] into the guest address space.
+/// Accesses outside of the PLIC address range are forwarded to the inner memory.
+#[derive(Debug)]
+pub struct PlicMemory<M: Memory> {
+    /// Inner memory.
+    pub memory: M,
+    /// PLIC state.
+    pub plic: Plic,
+    /// PLIC base address.
+    base: u32,
+}
+
+impl<M: Memory> PlicMemory<M> {
+    /// Create a new memory with a PLIC mapped at `base`.
+    ///
+    /// Arguments:
+    /// - `memory`: Inner memory.
+    /// - `base`: PLIC base address (ex.: [`PLIC_BASE`]).
+    pub fn new(memory: M, base: u32) -> Self {
+        Self {
+            memory,
+            plic: Plic::new(),
+            base,
+        }
+    }
+
+    /// Get the PLIC register offset of an address, if it is inside the PLIC range.
+    #[inline(always)]
+    fn offset(&self, address: u32) -> Option<u32> {
+        let offset = address.wrapping_sub(self.base);
+        (offset < PLIC_SIZE).then_some(offset)
+    }
+}
+
+impl<M: Memory> Memory for PlicMemory<M> {
+    fn load<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+        match self.offset(address) {
+            Some(offset) => {
+                if N != 4 {
+                    // Only 32-bit accesses (claim has side effects)
+                    return Err(EmbiveError::InvalidMemoryAddress);
+                }
+
+                let value = self.plic.read(offset)?.to_le_bytes();
+                value
+                    .first_chunk::<N>()
+                    .copied()
+                    .ok_or(EmbiveError::InvalidMemoryAddress)
+            }
+            None => self.memory.load(address),
+        }
+    }
+
+    fn store<const N: usize>(&mut self, address: u32, data: [u8; N]) -> Result<(), EmbiveError> {
+        match self.offset(address) {
+            Some(offset) => {
+                let value = data
+                    .first_chunk::<4>()
+                    .filter(|_| N == 4)
+                    .ok_or(EmbiveError::InvalidMemoryAddress)?;
+                self.plic.write(offset, u32::from_le_bytes(*value))
+            }
+            None => self.memory.store(address, data),
+        }
+    }
+
+    // PLIC state isn't part of the RAM
+    fn export_ram(&self) -> Option<&[u8]> {
+        self.memory.export_ram()
+    }
+}
+
+/// Update the engine external interrupt line from the PLIC state.
+/// Should be called by the host after raising PLIC sources (and after the guest completes an interrupt).
+///
+/// Arguments:
+/// - `engine`: Engine using a [`PlicMemory`].
+///
+/// Returns:
+/// - `Ok(())`: Success.
+/// - `Err(EmbiveError)`: Failed to raise the external interrupt.
+pub fn update<M: Memory>(engine: &mut Engine<'_, PlicMemory<M>>) -> Result<(), EmbiveError> {
+    if engine.memory.plic.asserted() {
+        engine.raise_interrupt(EXTERNAL_INTERRUPT_LINE)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Config;
+    use crate::memory::SliceMemory;
+    use crate::register::Register;
+
+    fn plic_memory<'a>(code: &'a [u8]) -> PlicMemory<SliceMemory<'a>> {
+        let mut memory = PlicMemory::new(SliceMemory::new(code, &mut []), PLIC_BASE);
+        memory
+            .store(PLIC_BASE + ENABLE_OFFSET, 0b1110u32.to_le_bytes())
+            .unwrap();
+        memory.store(PLIC_BASE + 4, 1u32.to_le_bytes()).unwrap();
+        memory.store(PLIC_BASE + 8, 3u32.to_le_bytes()).unwrap();
+        memory.store(PLIC_BASE + 12, 2u32.to_le_bytes()).unwrap();
+        memory
+    }
+
+    fn claim(memory: &PlicMemory<SliceMemory>) -> u32 {
+        u32::from_le_bytes(memory.load(PLIC_BASE + CLAIM_COMPLETE_OFFSET).unwrap())
+    }
+
+    #[test]
+    fn test_claim_complete() {
+        let mut memory = plic_memory(&[]);
+        memory.plic.raise(1).unwrap();
+        memory.plic.raise(2).unwrap();
+        memory.plic.raise(3).unwrap();
+        assert!(memory.plic.asserted());
+
+        // Highest priority first
+        assert_eq!(claim(&memory), 2);
+        assert_eq!(claim(&memory), 3);
+        assert_eq!(claim(&memory), 1);
+        assert_eq!(claim(&memory), 0);
+        assert!(!memory.plic.asserted());
+
+        // Can't be claimed again before completion
+        memory.plic.raise(2).unwrap();
+        assert_eq!(claim(&memory), 0);
+        memory
+            .store(PLIC_BASE + CLAIM_COMPLETE_OFFSET, 2u32.to_le_bytes())
+            .unwrap();
+        assert_eq!(claim(&memory), 2);
+    }
+
+    #[test]
+    fn test_threshold() {
+        let mut memory = plic_memory(&[]);
+        memory
+            .store(PLIC_BASE + THRESHOLD_OFFSET, 2u32.to_le_bytes())
+            .unwrap();
+        memory.plic.raise(1).unwrap();
+        memory.plic.raise(3).unwrap();
+
+        assert!(!memory.plic.asserted());
+        assert_eq!(
+            u32::from_le_bytes(memory.load(PLIC_BASE + PENDING_OFFSET).unwrap()),
+            0b1010
+        );
+    }
+
+    #[test]
+    fn test_invalid_access() {
+        let mut memory = plic_memory(&[]);
+        assert_eq!(
+            memory.load::<2>(PLIC_BASE),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            memory.store(PLIC_BASE + 0x10_0000, 0u32.to_le_bytes()),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(memory.plic.raise(0), Err(EmbiveError::InvalidInterrupt));
+    }
+
+    #[test]
+    fn test_guest_claim() {
+        fn interrupt_fn(
+            cause: u32,
+            engine: &mut Engine<PlicMemory<SliceMemory>>,
+        ) -> Result<(), EmbiveError> {
+            engine.registers.inner[Register::A1 as usize] = cause as i32;
+            engine.complete_interrupt();
+            Ok(())
+        }
+
+        let code = &[
+            0xb7, 0x02, 0x20, 0x0c, // lui  t0, 0x0c200 (PLIC context 0)
+            0x03, 0xa5, 0x42, 0x00, // lw   a0, 4(t0)   (Claim)
+            0x23, 0xa2, 0xa2, 0x00, // sw   a0, 4(t0)   (Complete)
+            0x73, 0x00, 0x10, 0x00, // ebreak           (Halt)
+        ];
+        let mut memory = plic_memory(code);
+        memory.plic.raise(3).unwrap();
+
+        let mut engine = Engine::new(
+            &mut memory,
+            Config::default().with_interrupt_fn(Some(interrupt_fn)),
+        )
+        .unwrap();
+        update(&mut engine).unwrap();
+
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(
+            engine.registers.get(Register::A1 as usize),
+            Ok(EXTERNAL_INTERRUPT_LINE as i32)
+        );
+        assert_eq!(engine.registers.get(Register::A0 as usize), Ok(3));
+        assert!(!engine.memory.plic.asserted());
+    }
+}
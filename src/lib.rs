@@ -7,7 +7,7 @@
 //! Embive is designed for any error during execution to be recoverable, allowing the host to handle it as needed.
 //! As so, no panics should occur on release builds, despite the bytecode being executed.
 //!
-//! Currently, it supports the `RV32I[MA]Zifencei` unprivileged instruction set (Check [Features](#features)).
+//! Currently, it supports the `RV32I[MA]Zifencei_Zicbom_Zicbop_Zicboz_Zihintntl_Zihintpause` unprivileged instruction set, plus a subset of `V` (Check [Features](#features)).
 //!
 //! ## Bytecode
 //! The bytecode can be generated by any compiler that supports the RISC-V 32-bit instruction set, as long as it can output a flat
@@ -89,24 +89,211 @@
 //! - `m_extension`:
 //!     - Enable the RV32M extension (multiply and divide instructions).
 //!         - Disabled by default, no additional dependencies.
+//! - `soft_divide`:
+//!     - Execute `div`, `divu`, `rem` and `remu` with a shift-subtract routine (one iteration per quotient bit),
+//!       for hosts without a hardware divider (ex.: Cortex-M0/M0+), instead of the compiler's division libcalls.
+//!     - Faster for small quotients (the common case), slower for large ones. Measure on your target before enabling.
+//!     - Enables the `m_extension` feature.
+//!         - Disabled by default, no additional dependencies.
 //! - `a_extension`:
 //!     - Enable the RV32A extension (atomic instructions).
+//!     - Memory model: the engine is a single hart executing one instruction at a time, every instruction
+//!       (including AMOs) is atomic and sequentially consistent for the guest, an `sc` succeeds if the reserved word
+//!       still holds the value loaded by the `lr`.
+//!       The ordering bits (`aq`/`rl`) and `fence` have nothing left to order and are ignored.
+//!       Memory shared with the host or other engines is only accessed between instructions (ex.: from syscalls),
+//!       so no host-side atomics are used.
+//!         - Disabled by default, no additional dependencies.
+//! - `zacas`:
+//!     - Enable the Zacas extension (atomic compare-and-swap, `amocas.w` and `amocas.d` on register pairs),
+//!       used by lock-free primitives of newer profiles (ex.: RVA23). Same memory model as `a_extension`.
+//!     - Enables the `a_extension` feature.
+//!         - Disabled by default, no additional dependencies.
+//! - `c_extension`:
+//!     - Enable the RV32C extension (16-bit compressed instructions, mixed with 32-bit ones).
+//!         - Disabled by default, no additional dependencies.
+//! - `v_extension`:
+//!     - Enable a subset of the vector extension for DSP-style guests (`VLEN` = 128, `ELEN` = 32):
+//!         - Configuration: `vsetvli`, `vsetivli`, `vsetvl` (SEW of 8, 16 or 32 bits, LMUL from 1/4 to 8).
+//!         - Unit-stride loads and stores: `vle{8,16,32}.v`, `vse{8,16,32}.v`.
+//!         - Integer arithmetic: add/sub, min/max, logical, shifts, merge/move, `vmul[h[u]]`, `vmacc`,
+//!           `vredsum.vs`, `vmv.x.s`, `vmv.s.x`.
+//!         - Anything else (floating point, widening, comparisons, strided/indexed accesses, etc.)
+//!           is an invalid instruction, the vector CSRs are not accessible.
+//!     - Adds 512 bytes of vector registers to the engine.
 //!         - Disabled by default, no additional dependencies.
 //! - `instruction_limit`:
 //!     - Limit the number of instructions executed by the engine, yielding when the limit is reached.
+//!     - Fuel metering for cooperative scheduling ([`engine::Engine::run_with_fuel`]).
+//!     - Remaining budget readable by the guest ([`engine::Config::budget_csr`]).
+//!         - Disabled by default, no additional dependencies.
+//! - `safepoint`:
+//!     - Guest safepoints, where the guest state is consistent (ex.: for snapshots and hot-reloads),
+//!       and safepoint-only yielding (Check [`engine::SAFEPOINT_INSTRUCTION`] and [`engine::YieldPoint`]).
+//!     - Tracked on every executed instruction, check `benches/README.md` for the cost.
+//!         - Disabled by default, no additional dependencies.
+//! - `debugger`:
+//!     - Breakpoints, watchpoints and single-stepping (Check [`debug`]).
+//!         - Disabled by default, no additional dependencies.
+//! - `interrupt`:
+//!     - Enable host-raised interrupts, delivered at a configurable granularity (Check [`interrupt`]).
+//!         - Disabled by default, no additional dependencies.
+//! - `performance_unchecked`:
+//!     - Remove runtime checks of invariants guaranteed by the instruction decoder (ex.: decoded register index < 32),
+//!       relying on debug assertions only.
+//!     - Speedup: within measurement noise on a x86-64 host (the compiler already removes most of these checks),
+//!       it may be higher on targets where the checks aren't optimized away. Measure on your target before enabling.
+//!     - Risk: an internal decoder bug would access a wrong (masked) register instead of returning an error.
+//!       No `unsafe` code is used, so memory safety is not affected.
+//!         - Disabled by default, no additional dependencies.
+//! - `cortex_m_optimized`:
+//!     - Cortex-M (ARM) layout optimizations for the interpreter hot loop:
+//!         - [`engine::Engine::run`] and its instruction loops are placed in the `.itcm.embive` section
+//!           (ARM targets only), map it to ITCM (or RAM) in your linker script. Check `benches/README.md`.
+//!         - Error paths of the instruction dispatch are marked as cold.
+//!         - Disabled by default, no additional dependencies.
+//! - `fetch_batch`:
+//!     - Fetch [`engine::FETCH_BATCH`] bytes of code per [`memory::Memory::fetch`] call, executing straight-line code
+//!       out of them, for memory implementations where a call isn't trivial (ex.: [`memory::PagedMemory`],
+//!       banked or instrumented memories). Slower with [`memory::SliceMemory`], measure with `benches/suite.rs`.
+//!     - Fetched code is dropped when running, on reset, syscalls, interrupt delivery and `fence.i`.
+//!       Guests changing their code must execute `fence.i` before running it, and hosts changing it
+//!       between steps must call [`engine::Engine::invalidate_fetch`].
+//!         - Disabled by default, no additional dependencies.
+//! - `accounting`:
+//!     - Account guest instructions and host syscall time separately, with an optional syscall time quota
+//!       and per-syscall-number statistics (Check [`accounting`]).
+//!         - Disabled by default, no additional dependencies.
+//! - `counters`:
+//!     - Per-engine performance counters: instructions retired, loads, stores, branches taken and syscalls
+//!       (Check [`counters`]).
+//!         - Disabled by default, no additional dependencies.
+//! - `telemetry`:
+//!     - Flight recorder: periodic execution snapshots kept in a ring, in scratch memory
+//!       (Check [`engine::Engine::telemetry`] and [`memory::ScratchMemory`]).
+//!         - Disabled by default, no additional dependencies.
+//! - `trace`:
+//!     - Execution tracing hooks for instruction fetches, register writes and memory accesses, to build tracers,
+//!       coverage tools and replay debuggers (Check [`engine::TraceHook`]).
+//!         - Disabled by default, no additional dependencies.
+//! - `replay`:
+//!     - Deterministic replay: record the guest inputs (syscall results, CSR reads and interrupts) into a compact log,
+//!       and re-execute the guest bit-exactly from it (Check [`replay`]). Enables the `trace` feature.
+//!         - Disabled by default, no additional dependencies.
+//! - `disassembler`:
+//!     - Decode raw instructions into a structured form, displayable as assembly text without allocating,
+//!       for debuggers and trace viewers (Check [`disassembler`]).
+//!         - Disabled by default, no additional dependencies.
+//! - `adapter`:
+//!     - Run-loop adapters for embedded frameworks (ex.: Embassy tasks, RTIC resources),
+//!       with time slices, interrupt-safe yield signaling and an async runner (Check [`adapter`]).
+//!         - Disabled by default, no additional dependencies.
+//! - `instance_blob`:
+//!     - Read-only host configuration/identity blob readable by the guest (ex.: device ID, feature flags),
+//!       with a syscall (Check [`engine::Engine::set_instance_blob`]).
+//!         - Disabled by default, no additional dependencies.
+//! - `persistent_regions`:
+//!     - Warm restarts preserving the RAM regions declared by the host or the guest (with a syscall),
+//!       scrubbing the rest (Check [`engine::Engine::warm_restart`]).
+//!         - Disabled by default, no additional dependencies.
+//! - `timer`:
+//!     - Guest timer service (one-shot and periodic timers) backed by the host clock (Check [`timer`]).
+//!         - Disabled by default, no additional dependencies.
+//! - `crypto`:
+//!     - Hashing, HMAC and AEAD syscalls backed by host implementations (ex.: hardware crypto),
+//!       keeping key material outside the sandbox behind opaque key handles
+//!       (Check [`syscall::crypto`] and [`syscall::capability`]).
+//!         - Disabled by default, no additional dependencies.
+//! - `log`:
+//!     - Structured guest logging to a host sink, with truncation and a record budget (Check [`syscall::log`]).
+//!         - Disabled by default, no additional dependencies.
+//! - `libc_support`:
+//!     - Minimal services needed by small C libraries (ex.: picolibc, newlib): `brk`, `write`, `read`, `close`,
+//!       `lseek`, `exit` and `gettimeofday`, backed by host traits (Check [`libc`]).
+//!         - Disabled by default, no additional dependencies.
+//! - `runtime`:
+//!     - Engine-side counterpart of a guest runtime support crate (print, panic reports, heap allocator,
+//!       exit and entry conventions), versioned together with it (Check [`runtime`]).
 //!         - Disabled by default, no additional dependencies.
-#![feature(type_alias_impl_trait)]
+//! - `tinygo`:
+//!     - Compatibility mode for TinyGo guests: engine preset, RAM sizing helpers and the sleep convention
+//!       (Check [`tinygo`]). Enables the `runtime` feature.
+//!         - Disabled by default, no additional dependencies.
+//! - `testkit`:
+//!     - Instruction-level RV32I/RV32M conformance test vectors and runner, to re-verify the engine semantics
+//!       in user test suites (ex.: with custom extensions), and a pseudo-random program generator
+//!       for differential stress testing (Check [`testkit`]).
+//!         - Disabled by default, no additional dependencies.
+//! - `testrunner`:
+//!     - Run the upstream riscv-tests ELFs (ex.: `rv32ui-p-add`) directly, handling their linker layout
+//!       and pass/fail conventions, against the enabled features (Check [`testrunner`]).
+//!         - Disabled by default, no additional dependencies.
+//! - `alloc`:
+//!     - Enable features that require dynamic memory allocation:
+//!         - Owned memory, with growable RAM (Check [`memory::VecMemory`]).
+//!         - Disabled by default, depends on the `alloc` crate.
+//! - `std`:
+//!     - Enable features that require the standard library:
+//!         - Syscall panic boundary, catching panics from the syscall function
+//!           (Check [`engine::Config::syscall_panic_error`]).
+//!         - Conformance matrix, running a test corpus against every extension combination of the build
+//!           (with the `testrunner` feature, Check [`testrunner::matrix`]).
+//!     - Enables the `alloc` feature.
+//!         - Disabled by default, depends on the standard library.
+//! - `serde`:
+//!     - Serialize and deserialize the guest state (Check [`engine::EngineState`]) with any serde format.
+//!         - Disabled by default, depends on `serde` (without default features).
+#![no_std]
+#![forbid(unsafe_code)]
+#[cfg(feature = "accounting")]
+pub mod accounting;
+#[cfg(feature = "adapter")]
+pub mod adapter;
+#[cfg(feature = "counters")]
+pub mod counters;
+pub mod csr;
+#[cfg(feature = "debugger")]
+pub mod debug;
+#[cfg(feature = "disassembler")]
+pub mod disassembler;
 pub mod engine;
 pub mod error;
+pub mod extension;
 mod instruction;
+#[cfg(feature = "interrupt")]
+pub mod interrupt;
+#[cfg(feature = "libc_support")]
+pub mod libc;
+pub mod lint;
+pub mod loader;
 pub mod memory;
 pub mod register;
+#[cfg(feature = "replay")]
+pub mod replay;
+#[cfg(feature = "runtime")]
+pub mod runtime;
+pub mod syscall;
+#[cfg(feature = "testkit")]
+pub mod testkit;
+#[cfg(feature = "testrunner")]
+pub mod testrunner;
+#[cfg(feature = "timer")]
+pub mod timer;
+#[cfg(feature = "tinygo")]
+pub mod tinygo;
+pub mod trap;
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(any(test, feature = "std"))]
+extern crate std;
 
 #[cfg(test)]
 mod tests {
     use std::{
         fs::{read_dir, DirEntry},
         path::PathBuf,
+        println,
     };
 
     use crate::{
@@ -121,11 +308,7 @@ mod tests {
     #[cfg(feature = "a_extension")]
     const RV32UA_TESTS: usize = 10;
 
-    thread_local! {
-        static SYSCALL_COUNTER: std::cell::RefCell<i32> = std::cell::RefCell::new(0);
-    }
-
-    fn syscall(nr: i32, args: &[i32; SYSCALL_ARGS], _memory: &mut SliceMemory) -> Result<i32, i32> {
+    fn syscall(nr: i32, args: &[i32; SYSCALL_ARGS]) -> Result<i32, i32> {
         if nr == 93 {
             if args[0] == 0 {
                 println!("Test was successful");
@@ -136,7 +319,6 @@ mod tests {
             panic!("Unknown syscall: {}", nr);
         }
 
-        SYSCALL_COUNTER.with(|c| *c.borrow_mut() += 1);
         Ok(0)
     }
 
@@ -152,30 +334,27 @@ mod tests {
 
         let mut memory = SliceMemory::new(code, &mut ram);
 
-        // Create engine
-        let mut engine = Engine::new(
-            &mut memory,
-            Config {
-                syscall_fn: Some(Box::new(syscall)),
-                ..Default::default()
-            },
-        )
-        .unwrap();
+        // Count the syscalls made by the test
+        let mut syscall_counter = 0;
+        let mut syscalls = |nr: i32, args: &[i32; SYSCALL_ARGS], _memory: &mut SliceMemory| {
+            syscall_counter += 1;
+            syscall(nr, args)
+        };
 
-        // Set program counter to RAM (code start)
-        engine.program_counter = RAM_OFFSET;
+        {
+            // Create engine
+            let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+            engine.set_syscalls(Some(&mut syscalls));
 
-        // Get syscall counter prior to running
-        let prev_syscall_counter = SYSCALL_COUNTER.with(|c| *c.borrow());
+            // Set program counter to RAM (code start)
+            engine.program_counter = RAM_OFFSET;
 
-        // Run it
-        engine.run().unwrap();
-
-        // Get syscall counter after running
-        let new_syscall_counter = SYSCALL_COUNTER.with(|c| *c.borrow());
+            // Run it
+            engine.run().unwrap();
+        }
 
-        // Check if syscall was incremented
-        if new_syscall_counter <= prev_syscall_counter {
+        // Check if syscall was made
+        if syscall_counter == 0 {
             panic!("No syscall was made");
         }
     }
@@ -238,4 +417,61 @@ mod tests {
         }
         assert_eq!(tested_files, RV32UA_TESTS);
     }
+
+    #[cfg(all(
+        feature = "tinygo",
+        feature = "m_extension",
+        feature = "a_extension",
+        feature = "c_extension"
+    ))]
+    #[test]
+    fn tinygo_glue_test() {
+        use crate::engine::WaitingFor;
+        use crate::runtime::RuntimeHost;
+        use crate::syscall::Errno;
+        use crate::tinygo;
+        use std::vec::Vec;
+
+        std::thread_local! {
+            static CLOCK: std::cell::Cell<u64> = const { std::cell::Cell::new(0) };
+            static CONSOLE: std::cell::RefCell<Vec<u8>> = const { std::cell::RefCell::new(Vec::new()) };
+        }
+
+        struct Console;
+
+        impl RuntimeHost for Console {
+            fn print(_stream: i32, data: &[u8]) -> Result<usize, Errno> {
+                CONSOLE.with(|console| console.borrow_mut().extend_from_slice(data));
+                Ok(data.len())
+            }
+        }
+
+        fn micros() -> u64 {
+            CLOCK.with(|clock| clock.get())
+        }
+
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("tests");
+        path.push("tinygo");
+        path.push("glue.bin");
+        let code = std::fs::read(path).expect("Failed to read file");
+
+        let mut ram = [0; 4096];
+        let layout = tinygo::layout(ram.len() as u32, 16, tinygo::DEFAULT_STACK_SIZE / 2);
+        let mut memory = SliceMemory::new(&code, &mut ram);
+        let config = Config::tinygo::<Console>(layout, micros);
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        // Sleeps, the host advances its clock
+        assert_eq!(engine.run(), Ok(true));
+        assert_eq!(engine.waiting_for(), Some(WaitingFor::Resume(1000)));
+        CLOCK.with(|clock| clock.set(clock.get() + 1000));
+        assert!(engine.resume(0));
+
+        // Exits with the elapsed time, in nanoseconds
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.panic_report(), None);
+        assert_eq!(engine.runtime_exit_code(), Some(1_000_000));
+        assert_eq!(CONSOLE.with(|console| console.borrow().clone()), b"hello\n");
+    }
 }
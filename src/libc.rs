@@ -0,0 +1,429 @@
+//! Libc Support Module
+//!
+//! The minimal set of services needed by the system call stubs of small C libraries (ex.: picolibc, newlib),
+//! backed by a host [`LibcHost`] implementation registered with [`crate::engine::Config::with_libc`].
+//! Syscall numbers follow the RISC-V Linux ABI (`a7`), arguments are passed in `a0` to `a2`,
+//! and results use the Embive convention (`a0`: [`Errno`] code, `a1`: value):
+//! - [`SYS_BRK`]: Set the program break to `a0` (0 = Query), inside the configured [`Heap`]. Returns the
+//!   current break (unchanged if the request is out of the heap), so `sbrk` is `brk(brk(0) + increment)`.
+//! - [`SYS_WRITE`]: Write `a2` bytes from `a1` to file descriptor `a0`. Returns the bytes written.
+//! - [`SYS_READ`]: Read up to `a2` bytes (at most [`LIBC_CHUNK`] per call) from file descriptor `a0`
+//!   into `a1`. Returns the bytes read (0 = End of file).
+//! - [`SYS_CLOSE`]: Close file descriptor `a0`.
+//! - [`SYS_LSEEK`]: Seek file descriptor `a0` to offset `a1` from `a2` (`SEEK_SET`, `SEEK_CUR` or `SEEK_END`).
+//!   Returns the new offset.
+//! - [`SYS_GETTIMEOFDAY`]: Write the host time to the `struct timeval` at `a0` (64-bit seconds, then 32-bit
+//!   microseconds, 16 bytes). The timezone argument is ignored.
+//! - [`SYS_EXIT`]: Halt the guest with exit code `a0` ([`crate::engine::Engine::exit_code`]).
+//!
+//! Guest stubs translate these results to the C library conventions (ex.: `errno`). Operations not provided
+//! by the host return [`Errno::NotSupported`] (except `close`, which succeeds).
+//!
+//! ```
+//! use embive::{
+//!     engine::{Config, Engine},
+//!     libc::{Heap, LibcHost},
+//!     memory::{SliceMemory, RAM_OFFSET},
+//!     syscall::Errno,
+//! };
+//!
+//! struct Console;
+//!
+//! impl LibcHost for Console {
+//!     fn write(fd: i32, data: &[u8]) -> Result<usize, Errno> {
+//!         match fd {
+//!             1 | 2 => Ok(data.len()), // ex.: forward to a UART
+//!             _ => Err(Errno::InvalidArgument),
+//!         }
+//!     }
+//! }
+//!
+//! let code = &[
+//!     0x13, 0x05, 0xa0, 0x02, // li   a0, 42
+//!     0x93, 0x08, 0xd0, 0x05, // li   a7, 93 (exit)
+//!     0x73, 0x00, 0x00, 0x00, // ecall
+//! ];
+//! let mut ram = [0; 1024];
+//! let mut memory = SliceMemory::new(code, &mut ram);
+//! let config = Config::default().with_libc::<Console>(Heap::new(RAM_OFFSET + 512, 256));
+//! let mut engine = Engine::new(&mut memory, config).unwrap();
+//!
+//! assert_eq!(engine.run(), Ok(false));
+//! assert_eq!(engine.exit_code(), Some(42));
+//! ```
+
+use crate::engine::SYSCALL_ARGS;
+pub use crate::memory::Heap;
+use crate::memory::Memory;
+use crate::syscall::Errno;
+
+/// Syscall number: close a file descriptor.
+pub const SYS_CLOSE: i32 = 57;
+/// Syscall number: seek a file descriptor.
+pub const SYS_LSEEK: i32 = 62;
+/// Syscall number: read from a file descriptor.
+pub const SYS_READ: i32 = 63;
+/// Syscall number: write to a file descriptor.
+pub const SYS_WRITE: i32 = 64;
+/// Syscall number: exit the guest.
+pub const SYS_EXIT: i32 = 93;
+/// Syscall number: get the time of day.
+pub const SYS_GETTIMEOFDAY: i32 = 169;
+/// Syscall number: set the program break.
+pub const SYS_BRK: i32 = 214;
+
+/// Bytes passed to the host per [`LibcHost::write`] or [`LibcHost::read`] call.
+pub const LIBC_CHUNK: usize = 64;
+
+/// Libc service function signature (check [`crate::engine::Config::with_libc`]).
+///
+/// Arguments:
+/// - `nr`: Syscall number.
+/// - `args`: Syscall arguments.
+/// - `memory`: System memory (code + RAM).
+///
+/// Returns:
+/// - `Some(Result<i32, i32>)`: Syscall result (value or [`Errno`] code).
+/// - `None`: Not a libc syscall (or handled by the engine, ex.: [`SYS_BRK`]).
+pub type LibcFn<M> =
+    fn(nr: i32, args: &[i32; SYSCALL_ARGS], memory: &mut M) -> Option<Result<i32, i32>>;
+
+/// Host side of the libc services (check the [module documentation](self)).
+pub trait LibcHost {
+    /// Write to a file descriptor.
+    ///
+    /// Arguments:
+    /// - `fd`: File descriptor.
+    /// - `data`: Data to write (at most [`LIBC_CHUNK`] bytes).
+    ///
+    /// Returns:
+    /// - `Ok(usize)`: Bytes written (fewer bytes end the guest write early).
+    /// - `Err(Errno)`: Failed to write.
+    fn write(fd: i32, data: &[u8]) -> Result<usize, Errno>;
+
+    /// Read from a file descriptor (not supported by default).
+    ///
+    /// Arguments:
+    /// - `fd`: File descriptor.
+    /// - `buffer`: Destination (at most [`LIBC_CHUNK`] bytes).
+    ///
+    /// Returns:
+    /// - `Ok(usize)`: Bytes read (0 = End of file).
+    /// - `Err(Errno)`: Failed to read.
+    fn read(_fd: i32, _buffer: &mut [u8]) -> Result<usize, Errno> {
+        Err(Errno::NotSupported)
+    }
+
+    /// Close a file descriptor (succeeds by default).
+    ///
+    /// Arguments:
+    /// - `fd`: File descriptor.
+    fn close(_fd: i32) -> Result<(), Errno> {
+        Ok(())
+    }
+
+    /// Seek a file descriptor (not supported by default).
+    ///
+    /// Arguments:
+    /// - `fd`: File descriptor.
+    /// - `offset`: Offset.
+    /// - `whence`: Offset origin (`SEEK_SET` = 0, `SEEK_CUR` = 1, `SEEK_END` = 2).
+    ///
+    /// Returns:
+    /// - `Ok(i32)`: New offset.
+    /// - `Err(Errno)`: Failed to seek.
+    fn lseek(_fd: i32, _offset: i32, _whence: i32) -> Result<i32, Errno> {
+        Err(Errno::NotSupported)
+    }
+
+    /// Current time, in microseconds since the Unix epoch (not supported by default).
+    fn time() -> Option<u64> {
+        None
+    }
+
+    /// The guest exited (the engine halts).
+    ///
+    /// Arguments:
+    /// - `code`: Exit code.
+    fn exit(_code: i32) {}
+}
+
+/// Handle a libc syscall with a host implementation (check the [module documentation](self)).
+///
+/// Generic Arguments:
+/// - `H`: Host implementation.
+/// - `M`: Memory type.
+///
+/// Arguments:
+/// - `nr`: Syscall number.
+/// - `args`: Syscall arguments.
+/// - `memory`: System memory (code + RAM).
+///
+/// Returns:
+/// - `Some(Result<i32, i32>)`: Syscall result (value or [`Errno`] code).
+/// - `None`: Not a libc syscall.
+pub(crate) fn syscall<H: LibcHost, M: Memory>(
+    nr: i32,
+    args: &[i32; SYSCALL_ARGS],
+    memory: &mut M,
+) -> Option<Result<i32, i32>> {
+    let [fd, address, len] = [args[0], args[1], args[2]];
+    let result = match nr {
+        SYS_WRITE => write::<H, M>(fd, address as u32, len as u32, memory),
+        SYS_READ => read::<H, M>(fd, address as u32, len as u32, memory),
+        SYS_CLOSE => H::close(fd).map(|_| 0),
+        SYS_LSEEK => H::lseek(fd, args[1], args[2]),
+        SYS_GETTIMEOFDAY => gettimeofday::<H, M>(args[0] as u32, memory),
+        SYS_EXIT => {
+            H::exit(fd);
+            Ok(fd)
+        }
+        _ => return None,
+    };
+
+    Some(result.map_err(|error| error.code()))
+}
+
+/// Write guest memory to a file descriptor, in chunks.
+fn write<H: LibcHost, M: Memory>(
+    fd: i32,
+    address: u32,
+    len: u32,
+    memory: &M,
+) -> Result<i32, Errno> {
+    let mut chunk = [0; LIBC_CHUNK];
+    let mut written = 0u32;
+    while written < len {
+        let size = ((len - written) as usize).min(LIBC_CHUNK);
+        for (i, byte) in chunk[..size].iter_mut().enumerate() {
+            [*byte] = memory
+                .load(address.wrapping_add(written).wrapping_add(i as u32))
+                .map_err(|_| Errno::InvalidPointer)?;
+        }
+
+        let count = H::write(fd, &chunk[..size])?.min(size);
+        written += count as u32;
+        if count < size {
+            // Short write
+            break;
+        }
+    }
+
+    Ok(written as i32)
+}
+
+/// Read a file descriptor into guest memory (a single chunk).
+fn read<H: LibcHost, M: Memory>(
+    fd: i32,
+    address: u32,
+    len: u32,
+    memory: &mut M,
+) -> Result<i32, Errno> {
+    let mut chunk = [0; LIBC_CHUNK];
+    let size = (len as usize).min(LIBC_CHUNK);
+    // Check the destination before reading, so no data is lost
+    for i in 0..size as u32 {
+        let byte = memory
+            .load::<1>(address.wrapping_add(i))
+            .map_err(|_| Errno::InvalidPointer)?;
+        memory
+            .store(address.wrapping_add(i), byte)
+            .map_err(|_| Errno::InvalidPointer)?;
+    }
+
+    let count = H::read(fd, &mut chunk[..size])?.min(size);
+    for (i, byte) in chunk[..count].iter().enumerate() {
+        memory
+            .store(address.wrapping_add(i as u32), [*byte])
+            .map_err(|_| Errno::InvalidPointer)?;
+    }
+
+    Ok(count as i32)
+}
+
+/// Write the host time to a guest `struct timeval`.
+fn gettimeofday<H: LibcHost, M: Memory>(address: u32, memory: &mut M) -> Result<i32, Errno> {
+    let time = H::time().ok_or(Errno::NotSupported)?;
+    let seconds = (time / 1_000_000).to_le_bytes();
+    let microseconds = ((time % 1_000_000) as u32).to_le_bytes();
+
+    memory
+        .store(address, seconds)
+        .and_then(|_| memory.store(address.wrapping_add(8), microseconds))
+        .map_err(|_| Errno::InvalidPointer)?;
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Config, Engine};
+    use crate::memory::{SliceMemory, RAM_OFFSET};
+    use crate::register::Register;
+    use std::{cell::RefCell, thread_local, vec::Vec};
+
+    thread_local! {
+        static OUTPUT: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+        static CLOSED: RefCell<Vec<i32>> = const { RefCell::new(Vec::new()) };
+    }
+
+    struct TestHost;
+
+    impl LibcHost for TestHost {
+        fn write(fd: i32, data: &[u8]) -> Result<usize, Errno> {
+            if fd != 1 {
+                return Err(Errno::InvalidArgument);
+            }
+
+            // Short writes after 70 bytes
+            OUTPUT.with(|output| {
+                let mut output = output.borrow_mut();
+                let count = data.len().min(70usize.saturating_sub(output.len()));
+                output.extend_from_slice(&data[..count]);
+                Ok(count)
+            })
+        }
+
+        fn read(_fd: i32, buffer: &mut [u8]) -> Result<usize, Errno> {
+            let data = b"input";
+            let count = buffer.len().min(data.len());
+            buffer[..count].copy_from_slice(&data[..count]);
+            Ok(count)
+        }
+
+        fn close(fd: i32) -> Result<(), Errno> {
+            CLOSED.with(|closed| closed.borrow_mut().push(fd));
+            Ok(())
+        }
+
+        fn time() -> Option<u64> {
+            Some(1_700_000_000_250_000)
+        }
+    }
+
+    struct WriteOnly;
+
+    impl LibcHost for WriteOnly {
+        fn write(_fd: i32, data: &[u8]) -> Result<usize, Errno> {
+            Ok(data.len())
+        }
+    }
+
+    /// Guest using the syscalls like picolibc stubs (`sbrk`, `write`, `gettimeofday`, `close` and `exit`).
+    const CODE: &[u8] = &[
+        0x13, 0x05, 0x00, 0x00, // li   a0, 0
+        0x93, 0x08, 0x60, 0x0d, // li   a7, 214 (brk)
+        0x73, 0x00, 0x00, 0x00, // ecall            (Query)
+        0x13, 0x85, 0x05, 0x01, // addi a0, a1, 16
+        0x73, 0x00, 0x00, 0x00, // ecall            (sbrk(16))
+        0x13, 0x84, 0x05, 0x00, // mv   s0, a1
+        0xb7, 0x05, 0x00, 0x80, // lui  a1, 0x80000
+        0x13, 0x05, 0x10, 0x00, // li   a0, 1
+        0x13, 0x06, 0x50, 0x00, // li   a2, 5
+        0x93, 0x08, 0x00, 0x04, // li   a7, 64 (write)
+        0x73, 0x00, 0x00, 0x00, // ecall
+        0x93, 0x84, 0x05, 0x00, // mv   s1, a1
+        0x37, 0x05, 0x00, 0x80, // lui  a0, 0x80000
+        0x13, 0x05, 0x85, 0x00, // addi a0, a0, 8
+        0x93, 0x08, 0x90, 0x0a, // li   a7, 169 (gettimeofday)
+        0x73, 0x00, 0x00, 0x00, // ecall
+        0x13, 0x05, 0x30, 0x00, // li   a0, 3
+        0x93, 0x08, 0x90, 0x03, // li   a7, 57 (close)
+        0x73, 0x00, 0x00, 0x00, // ecall
+        0x13, 0x05, 0x70, 0x00, // li   a0, 7
+        0x93, 0x08, 0xd0, 0x05, // li   a7, 93 (exit)
+        0x73, 0x00, 0x00, 0x00, // ecall
+    ];
+
+    #[test]
+    fn test_libc() {
+        let mut ram = [0; 128];
+        ram[..5].copy_from_slice(b"hello");
+        let mut memory = SliceMemory::new(CODE, &mut ram);
+        let config = Config::default().with_libc::<TestHost>(Heap::new(RAM_OFFSET + 64, 64));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.exit_code(), Some(7));
+        assert_eq!(engine.program_break(), RAM_OFFSET + 80);
+        assert_eq!(
+            engine.registers.get(Register::S0 as usize),
+            Ok((RAM_OFFSET + 80) as i32)
+        );
+        assert_eq!(engine.registers.get(Register::S1 as usize), Ok(5));
+        assert_eq!(OUTPUT.with(|output| output.take()), b"hello");
+        assert_eq!(CLOSED.with(|closed| closed.take()), [3]);
+        assert_eq!(
+            engine.memory.load(RAM_OFFSET + 8),
+            Ok(1_700_000_000u64.to_le_bytes())
+        );
+        assert_eq!(
+            engine.memory.load(RAM_OFFSET + 16),
+            Ok(250_000u32.to_le_bytes())
+        );
+
+        // Out of the heap, the break is unchanged
+        engine.reset();
+        assert_eq!(engine.exit_code(), None);
+        assert_eq!(engine.program_break(), RAM_OFFSET + 64);
+        engine.program_counter = 8;
+        engine.registers.inner[Register::A0 as usize] = (RAM_OFFSET + 129) as i32;
+        engine.registers.inner[Register::A7 as usize] = SYS_BRK;
+        assert_eq!(engine.step(), Ok(true));
+        assert_eq!(
+            engine.registers.get(Register::A1 as usize),
+            Ok((RAM_OFFSET + 64) as i32)
+        );
+    }
+
+    #[test]
+    fn test_read_write() {
+        let mut ram = [b'x'; 256];
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut args = [1, RAM_OFFSET as i32, 100, 0, 0, 0, 0];
+
+        // Chunked, until the short write
+        assert_eq!(
+            syscall::<TestHost, _>(SYS_WRITE, &args, &mut memory),
+            Some(Ok(70))
+        );
+        assert_eq!(OUTPUT.with(|output| output.take()), [b'x'; 70]);
+        args[0] = 2;
+        assert_eq!(
+            syscall::<TestHost, _>(SYS_WRITE, &args, &mut memory),
+            Some(Err(Errno::InvalidArgument.code()))
+        );
+
+        args[2] = 3;
+        assert_eq!(
+            syscall::<TestHost, _>(SYS_READ, &args, &mut memory),
+            Some(Ok(3))
+        );
+        assert_eq!(memory.load(RAM_OFFSET), Ok(*b"inpx"));
+        args[1] = 0;
+        assert_eq!(
+            syscall::<TestHost, _>(SYS_READ, &args, &mut memory),
+            Some(Err(Errno::InvalidPointer.code()))
+        );
+
+        // Defaults and other syscall numbers
+        args[1] = RAM_OFFSET as i32;
+        assert_eq!(
+            syscall::<WriteOnly, _>(SYS_READ, &args, &mut memory),
+            Some(Err(Errno::NotSupported.code()))
+        );
+        assert_eq!(
+            syscall::<WriteOnly, _>(SYS_LSEEK, &args, &mut memory),
+            Some(Err(Errno::NotSupported.code()))
+        );
+        assert_eq!(
+            syscall::<WriteOnly, _>(SYS_GETTIMEOFDAY, &args, &mut memory),
+            Some(Err(Errno::NotSupported.code()))
+        );
+        assert_eq!(
+            syscall::<WriteOnly, _>(SYS_CLOSE, &args, &mut memory),
+            Some(Ok(0))
+        );
+        assert_eq!(syscall::<WriteOnly, _>(1, &args, &mut memory), None);
+    }
+}
@@ -0,0 +1,578 @@
+//! Lint Module
+//!
+//! Static scan of guest code, reporting which instruction set extensions it requires
+//! and which of them aren't enabled in this build of Embive.
+//!
+//! The scan is a heuristic: read-only data placed in the code section is also decoded,
+//! so findings should be checked against the guest disassembly (the reported address helps with that).
+//!
+//! ```
+//! use embive::lint::{lint, IsaExtension};
+//!
+//! let code = &[
+//!     0x13, 0x05, 0x10, 0x00, // li  a0, 1
+//!     0x33, 0x05, 0xa5, 0x02, // mul a0, a0, a0
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//! ];
+//!
+//! let report = lint(code);
+//! assert_eq!(report.first_use(IsaExtension::M), Some(0x4));
+//!
+//! for missing in report.missing() {
+//!     // ex.: "guest uses M extension at 0x00000004 but feature `m_extension` is disabled"
+//!     println!("{}", missing);
+//! }
+//! ```
+//!
+//! ## Illegal Instruction Statistics
+//! At runtime, the engine can classify the illegal instructions it hits (with the same heuristic) and count them
+//! per extension ([`IllegalStats`], enabled by [`crate::engine::Config::illegal_instruction_stats`]),
+//! so fleets can learn which extensions to prioritize enabling or compiling for.
+//!
+//! ## Extension Probing
+//! To learn what a guest needs before choosing the engine build, run it once with its optional extensions
+//! probed ([`crate::engine::Config::probe_extensions`]): their illegal instructions are skipped instead of
+//! faulting, and the attempted extensions are reported in [`crate::engine::Engine::probed_extensions`].
+//! The guest results are meaningless while instructions are skipped, only the report is.
+//!
+//! ```
+//! use embive::{engine::{Config, Engine}, lint::IsaExtension, memory::SliceMemory};
+//!
+//! let code = &[
+//!     0x53, 0x75, 0xb5, 0x00, // fadd.s fa0, fa0, fa1
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//! ];
+//! let mut memory = SliceMemory::new(code, &mut []);
+//! let probed = IsaExtension::F.bit() | IsaExtension::D.bit();
+//! let config = Config::default().with_probe_extensions(probed);
+//! let mut engine = Engine::new(&mut memory, config).unwrap();
+//!
+//! assert_eq!(engine.run(), Ok(false));
+//! assert_eq!(engine.probed_extensions, IsaExtension::F.bit());
+//! ```
+
+use core::fmt::Display;
+
+/// Number of instruction set extensions tracked by the linter.
+const EXTENSION_COUNT: usize = 15;
+
+/// Instruction set extension (as required by the guest code).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(u8)]
+pub enum IsaExtension {
+    /// Base integer instruction set (RV32I).
+    I,
+    /// Integer multiplication and division.
+    M,
+    /// Atomic instructions.
+    A,
+    /// Compressed instructions.
+    C,
+    /// Single-precision floating point.
+    F,
+    /// Double-precision floating point.
+    D,
+    /// Quad-precision floating point.
+    Q,
+    /// Vector instructions.
+    V,
+    /// Control and status register instructions.
+    Zicsr,
+    /// Instruction-fetch fence.
+    Zifencei,
+    /// Cache block management.
+    Zicbom,
+    /// Cache block zero.
+    Zicboz,
+    /// Atomic compare-and-swap.
+    Zacas,
+    /// Custom opcodes (custom-0 to custom-3).
+    Custom,
+    /// Reserved or unsupported opcode.
+    Unknown,
+}
+
+impl IsaExtension {
+    /// All tracked extensions.
+    pub const ALL: [IsaExtension; EXTENSION_COUNT] = [
+        IsaExtension::I,
+        IsaExtension::M,
+        IsaExtension::A,
+        IsaExtension::C,
+        IsaExtension::F,
+        IsaExtension::D,
+        IsaExtension::Q,
+        IsaExtension::V,
+        IsaExtension::Zicsr,
+        IsaExtension::Zifencei,
+        IsaExtension::Zicbom,
+        IsaExtension::Zicboz,
+        IsaExtension::Zacas,
+        IsaExtension::Custom,
+        IsaExtension::Unknown,
+    ];
+
+    /// Extension name.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            IsaExtension::I => "I",
+            IsaExtension::M => "M",
+            IsaExtension::A => "A",
+            IsaExtension::C => "C",
+            IsaExtension::F => "F",
+            IsaExtension::D => "D",
+            IsaExtension::Q => "Q",
+            IsaExtension::V => "V",
+            IsaExtension::Zicsr => "Zicsr",
+            IsaExtension::Zifencei => "Zifencei",
+            IsaExtension::Zicbom => "Zicbom",
+            IsaExtension::Zicboz => "Zicboz",
+            IsaExtension::Zacas => "Zacas",
+            IsaExtension::Custom => "custom",
+            IsaExtension::Unknown => "unknown",
+        }
+    }
+
+    /// Extension bit (ex.: for [`crate::engine::Config::disabled_extensions`]).
+    pub const fn bit(&self) -> u32 {
+        1 << *self as u32
+    }
+
+    /// Crate feature that enables the extension.
+    ///
+    /// Returns:
+    /// - `Some(&str)`: Feature name.
+    /// - `None`: Always enabled, not supported by Embive or provided by the host
+    ///   ([`IsaExtension::Custom`], check [`crate::extension`]).
+    pub const fn feature(&self) -> Option<&'static str> {
+        match self {
+            IsaExtension::M => Some("m_extension"),
+            IsaExtension::A => Some("a_extension"),
+            IsaExtension::C => Some("c_extension"),
+            IsaExtension::V => Some("v_extension"),
+            IsaExtension::Zacas => Some("zacas"),
+            _ => None,
+        }
+    }
+
+    /// Check if the extension is executed by this build of Embive.
+    /// [`IsaExtension::Custom`] and [`IsaExtension::Zicsr`] are reported as disabled, as they depend on the engine
+    /// configuration (check [`crate::extension`] and [`crate::csr`]).
+    pub const fn enabled(&self) -> bool {
+        match self {
+            IsaExtension::M => cfg!(feature = "m_extension"),
+            IsaExtension::A => cfg!(feature = "a_extension"),
+            IsaExtension::C => cfg!(feature = "c_extension"),
+            IsaExtension::V => cfg!(feature = "v_extension"),
+            IsaExtension::Zacas => cfg!(feature = "zacas"),
+            extension => matches!(
+                extension,
+                IsaExtension::I
+                    | IsaExtension::Zifencei
+                    | IsaExtension::Zicbom
+                    | IsaExtension::Zicboz
+            ),
+        }
+    }
+
+    /// Classify an instruction.
+    ///
+    /// Arguments:
+    /// - `data`: `u32` value representing the instruction
+    ///   (only the lower 16 bits are used for compressed instructions).
+    ///
+    /// Returns:
+    /// - `IsaExtension`: Extension required by the instruction.
+    pub const fn of(data: u32) -> Self {
+        if data & 0b11 != 0b11 {
+            return IsaExtension::C;
+        }
+
+        let funct3 = (data >> 12) & 0b111;
+        match data & 0x7F {
+            // LUI, AUIPC, JAL, JALR, BRANCH, LOAD, STORE, OP-IMM
+            0b011_0111 | 0b001_0111 | 0b110_1111 | 0b110_0111 | 0b110_0011 | 0b000_0011
+            | 0b010_0011 | 0b001_0011 => IsaExtension::I,
+            // OP
+            0b011_0011 => match data >> 25 {
+                0b000_0001 => IsaExtension::M,
+                _ => IsaExtension::I,
+            },
+            // MISC-MEM
+            0b000_1111 => match funct3 {
+                0b000 => IsaExtension::I,
+                0b001 => IsaExtension::Zifencei,
+                0b010 if data >> 20 == 0b100 => IsaExtension::Zicboz,
+                0b010 => IsaExtension::Zicbom,
+                _ => IsaExtension::Unknown,
+            },
+            // SYSTEM
+            0b111_0011 => match funct3 {
+                0b000 => IsaExtension::I,
+                0b100 => IsaExtension::Unknown,
+                _ => IsaExtension::Zicsr,
+            },
+            // AMO
+            0b010_1111 => match data >> 27 {
+                0b00101 => IsaExtension::Zacas,
+                _ => IsaExtension::A,
+            },
+            // OP-V
+            0b101_0111 => IsaExtension::V,
+            // LOAD-FP, STORE-FP (vector accesses share the opcodes)
+            0b000_0111 | 0b010_0111 => match funct3 {
+                0b000 | 0b101 | 0b110 | 0b111 => IsaExtension::V,
+                0b010 => IsaExtension::F,
+                0b011 => IsaExtension::D,
+                0b100 => IsaExtension::Q,
+                _ => IsaExtension::Unknown,
+            },
+            // OP-FP, MADD, MSUB, NMSUB, NMADD (format in bits 26:25)
+            0b101_0011 | 0b100_0011 | 0b100_0111 | 0b100_1011 | 0b100_1111 => {
+                match (data >> 25) & 0b11 {
+                    0b00 => IsaExtension::F,
+                    0b01 => IsaExtension::D,
+                    0b11 => IsaExtension::Q,
+                    _ => IsaExtension::Unknown,
+                }
+            }
+            // custom-0 to custom-3
+            0b000_1011 | 0b010_1011 | 0b101_1011 | 0b111_1011 => IsaExtension::Custom,
+            _ => IsaExtension::Unknown,
+        }
+    }
+}
+
+impl Display for IsaExtension {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Extension required by the guest, but not enabled (check [`LintReport::missing`]).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Missing {
+    /// Required extension.
+    pub extension: IsaExtension,
+    /// Address of the first instruction requiring it.
+    pub address: u32,
+}
+
+impl Display for Missing {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self.extension {
+            IsaExtension::Custom => write!(
+                f,
+                "guest uses custom instructions at {:#010x}, an extension must be registered (Config::with_extension)",
+                self.address
+            ),
+            IsaExtension::Unknown => write!(
+                f,
+                "guest uses an unknown instruction at {:#010x}",
+                self.address
+            ),
+            extension => match extension.feature() {
+                Some(feature) => write!(
+                    f,
+                    "guest uses {} extension at {:#010x} but feature `{}` is disabled",
+                    extension, self.address, feature
+                ),
+                None => write!(
+                    f,
+                    "guest uses {} extension at {:#010x} but it isn't supported by Embive",
+                    extension, self.address
+                ),
+            },
+        }
+    }
+}
+
+/// Lint report: extensions required by the guest code.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LintReport {
+    /// Address of the first instruction requiring each extension (indexed by [`IsaExtension`]).
+    first_use: [Option<u32>; EXTENSION_COUNT],
+}
+
+impl LintReport {
+    /// Check if the guest requires an extension.
+    pub fn requires(&self, extension: IsaExtension) -> bool {
+        self.first_use[extension as usize].is_some()
+    }
+
+    /// Address of the first instruction requiring an extension.
+    ///
+    /// Returns:
+    /// - `Some(u32)`: Instruction address.
+    /// - `None`: Extension is not required.
+    pub fn first_use(&self, extension: IsaExtension) -> Option<u32> {
+        self.first_use[extension as usize]
+    }
+
+    /// Extensions required by the guest (in [`IsaExtension::ALL`] order).
+    pub fn required(&self) -> impl Iterator<Item = IsaExtension> + '_ {
+        IsaExtension::ALL
+            .into_iter()
+            .filter(|extension| self.requires(*extension))
+    }
+
+    /// Extensions required by the guest but not enabled in this build.
+    pub fn missing(&self) -> impl Iterator<Item = Missing> + '_ {
+        IsaExtension::ALL.into_iter().filter_map(|extension| {
+            match (self.first_use(extension), extension.enabled()) {
+                (Some(address), false) => Some(Missing { extension, address }),
+                _ => None,
+            }
+        })
+    }
+
+    /// Check if all extensions required by the guest are enabled.
+    pub fn is_supported(&self) -> bool {
+        self.missing().next().is_none()
+    }
+}
+
+/// Scan guest code (loaded at address `0x00000000`), reporting the required extensions.
+///
+/// Arguments:
+/// - `code`: Guest code.
+///
+/// Returns:
+/// - `LintReport`: Extensions required by the guest.
+pub fn lint(code: &[u8]) -> LintReport {
+    let mut report = LintReport {
+        first_use: [None; EXTENSION_COUNT],
+    };
+
+    let mut address = 0;
+    while let Some(half) = code.get(address..address + 2) {
+        let low = u16::from_le_bytes([half[0], half[1]]) as u32;
+
+        let (data, size) = if low & 0b11 != 0b11 {
+            // Compressed (16 bits)
+            (low, 2)
+        } else {
+            match code.get(address + 2..address + 4) {
+                Some(high) => (
+                    low | ((u16::from_le_bytes([high[0], high[1]]) as u32) << 16),
+                    4,
+                ),
+                None => break, // Truncated instruction
+            }
+        };
+
+        // All-zero and all-one words are defined illegal instructions (usually padding)
+        if data != 0 && data != u32::MAX {
+            let extension = IsaExtension::of(data);
+            let first_use = &mut report.first_use[extension as usize];
+            if first_use.is_none() {
+                *first_use = Some(address as u32);
+            }
+        }
+
+        address += size;
+    }
+
+    report
+}
+
+/// Illegal instructions executed by the guest, classified by extension (check the [module documentation](self)).
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct IllegalStats {
+    /// Occurrences of each extension (indexed by [`IsaExtension`]).
+    counts: [u64; EXTENSION_COUNT],
+    /// Last illegal instruction (extension, address, instruction).
+    last: Option<(IsaExtension, u32, u32)>,
+    /// Host clock when the last illegal instruction was hit.
+    last_timestamp: Option<u64>,
+}
+
+impl IllegalStats {
+    /// Record an illegal instruction.
+    ///
+    /// Arguments:
+    /// - `data`: `u32` value representing the instruction.
+    /// - `address`: Instruction address.
+    /// - `timestamp`: Host clock (None = No tick function).
+    #[cold]
+    pub(crate) fn record(&mut self, data: u32, address: u32, timestamp: Option<u64>) {
+        let extension = IsaExtension::of(data);
+        let count = &mut self.counts[extension as usize];
+        *count = count.saturating_add(1);
+        self.last = Some((extension, address, data));
+        self.last_timestamp = timestamp;
+    }
+
+    /// Illegal instructions classified as an extension.
+    pub fn count(&self, extension: IsaExtension) -> u64 {
+        self.counts[extension as usize]
+    }
+
+    /// Total illegal instructions.
+    pub fn total(&self) -> u64 {
+        self.counts
+            .iter()
+            .fold(0u64, |total, count| total.saturating_add(*count))
+    }
+
+    /// Extensions with occurrences and their counts (in [`IsaExtension::ALL`] order).
+    pub fn entries(&self) -> impl Iterator<Item = (IsaExtension, u64)> + '_ {
+        IsaExtension::ALL
+            .into_iter()
+            .map(|extension| (extension, self.count(extension)))
+            .filter(|(_, count)| *count > 0)
+    }
+
+    /// Last illegal instruction.
+    ///
+    /// Returns:
+    /// - `Some((IsaExtension, u32, u32))`: Extension, address and instruction.
+    /// - `None`: No illegal instruction recorded.
+    pub fn last(&self) -> Option<(IsaExtension, u32, u32)> {
+        self.last
+    }
+
+    /// Host clock when the last illegal instruction was hit
+    /// (None = None recorded or no tick function, check [`crate::engine::Config::tick_fn`]).
+    pub fn last_timestamp(&self) -> Option<u64> {
+        self.last_timestamp
+    }
+
+    /// Clear the statistics.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl Display for IllegalStats {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        writeln!(f, "extension count")?;
+        for (extension, count) in self.entries() {
+            writeln!(f, "{} {}", extension, count)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(IsaExtension::of(0x0010_0513), IsaExtension::I); // li a0, 1
+        assert_eq!(IsaExtension::of(0x02a5_0533), IsaExtension::M); // mul a0, a0, a0
+        assert_eq!(IsaExtension::of(0x1005_252f), IsaExtension::A); // lr.w a0, (a0)
+        assert_eq!(IsaExtension::of(0x28c7_352f), IsaExtension::Zacas); // amocas.d a0, a2, (a4)
+        assert_eq!(IsaExtension::of(0x4505), IsaExtension::C); // c.li a0, 1
+        assert_eq!(IsaExtension::of(0x0005_2507), IsaExtension::F); // flw fa0, 0(a0)
+        assert_eq!(IsaExtension::of(0x0205_7057), IsaExtension::V); // vsetvli x0, a0, e8, m1
+        assert_eq!(IsaExtension::of(0x0205_6007), IsaExtension::V); // vle32.v v0, (a0)
+        assert_eq!(IsaExtension::of(0x3000_2573), IsaExtension::Zicsr); // csrr a0, mstatus
+        assert_eq!(IsaExtension::of(0x0000_100f), IsaExtension::Zifencei); // fence.i
+        assert_eq!(IsaExtension::of(0x0025_200f), IsaExtension::Zicbom); // cbo.flush (a0)
+        assert_eq!(IsaExtension::of(0x0045_200f), IsaExtension::Zicboz); // cbo.zero (a0)
+        assert_eq!(IsaExtension::of(0x0000_000b), IsaExtension::Custom);
+        assert_eq!(IsaExtension::of(0x0000_007f), IsaExtension::Unknown);
+    }
+
+    #[test]
+    fn test_lint() {
+        let code = &[
+            0x05, 0x45, // c.li a0, 1
+            0x13, 0x05, 0x10, 0x00, // li a0, 1
+            0x33, 0x05, 0xa5, 0x02, // mul a0, a0, a0
+            0x73, 0x25, 0x00, 0x30, // csrr a0, mstatus
+            0x00, 0x00, 0x00, 0x00, // padding
+            0x73, 0x00, 0x10, 0x00, // ebreak
+            0x13, 0x05, // truncated
+        ];
+
+        let report = lint(code);
+        assert_eq!(report.first_use(IsaExtension::C), Some(0x0));
+        assert_eq!(report.first_use(IsaExtension::I), Some(0x2));
+        assert_eq!(report.first_use(IsaExtension::M), Some(0x6));
+        assert_eq!(report.first_use(IsaExtension::Zicsr), Some(0xA));
+        assert!(!report.requires(IsaExtension::Unknown));
+        assert_eq!(report.required().count(), 4);
+
+        assert_eq!(
+            report.missing().next(),
+            if cfg!(feature = "m_extension") && cfg!(feature = "c_extension") {
+                Some(Missing {
+                    extension: IsaExtension::Zicsr,
+                    address: 0xA,
+                })
+            } else if cfg!(feature = "m_extension") {
+                Some(Missing {
+                    extension: IsaExtension::C,
+                    address: 0x0,
+                })
+            } else {
+                Some(Missing {
+                    extension: IsaExtension::M,
+                    address: 0x6,
+                })
+            }
+        );
+        assert!(!report.is_supported());
+    }
+
+    #[test]
+    fn test_lint_supported() {
+        let code = &[
+            0x13, 0x05, 0x10, 0x00, // li a0, 1
+            0x73, 0x00, 0x10, 0x00, // ebreak
+        ];
+
+        let report = lint(code);
+        assert!(report.is_supported());
+        assert_eq!(
+            report.required().collect::<std::vec::Vec<_>>(),
+            [IsaExtension::I]
+        );
+    }
+
+    #[test]
+    fn test_missing_message() {
+        let missing = Missing {
+            extension: IsaExtension::M,
+            address: 0x1234,
+        };
+        assert_eq!(
+            format!("{}", missing),
+            "guest uses M extension at 0x00001234 but feature `m_extension` is disabled"
+        );
+
+        let missing = Missing {
+            extension: IsaExtension::F,
+            address: 0x10,
+        };
+        assert_eq!(
+            format!("{}", missing),
+            "guest uses F extension at 0x00000010 but it isn't supported by Embive"
+        );
+    }
+
+    #[test]
+    fn test_illegal_stats() {
+        let mut stats = IllegalStats::default();
+        stats.record(0x0005_2507, 0x10, None); // flw fa0, 0(a0)
+        stats.record(0x0005_2507, 0x14, None);
+        stats.record(0x3000_2573, 0x18, Some(7)); // csrr a0, mstatus
+
+        assert_eq!(stats.count(IsaExtension::F), 2);
+        assert_eq!(stats.count(IsaExtension::Zicsr), 1);
+        assert_eq!(stats.count(IsaExtension::I), 0);
+        assert_eq!(stats.total(), 3);
+        assert_eq!(stats.last(), Some((IsaExtension::Zicsr, 0x18, 0x3000_2573)));
+        assert_eq!(stats.last_timestamp(), Some(7));
+        assert_eq!(format!("{}", stats), "extension count\nF 2\nZicsr 1\n");
+
+        stats.clear();
+        assert_eq!(stats.total(), 0);
+        assert_eq!(stats.last(), None);
+    }
+}
@@ -0,0 +1,959 @@
+//! Loader Module
+//!
+//! Minimal ELF loader for guest programs (32-bit, little endian, RISC-V executables).
+//!
+//! Loadable segments are placed at their physical (load) address, same as a flat binary
+//! generated by `objcopy -O binary`: code at `0x00000000` and RAM at [`RAM_OFFSET`].
+//! The [`LoadReport`] can be checked against the device capabilities before any memory is written.
+//!
+//! Guests using C++ exceptions or unwinding panics (Rust `panic=unwind`, Zig) locate their unwind tables
+//! through the `.eh_frame_hdr` section (`PT_GNU_EH_FRAME`, linked with `--eh-frame-hdr`). It is reported in
+//! [`LoadReport::eh_frame_hdr`] (check [`UnwindTable`]), and it must be part of a loadable segment, same as the
+//! `.eh_frame` section it points to (so the unwind tables are loaded).
+//!
+//! ```
+//! use embive::{
+//!     engine::{Config, Engine},
+//!     error::LoaderError,
+//!     loader::Elf,
+//!     memory::SliceMemory,
+//! };
+//!
+//! fn run_guest(elf: &[u8]) -> Result<(), LoaderError> {
+//!     let elf = Elf::parse(elf)?;
+//!     let report = elf.report()?;
+//!     println!("Code: {} bytes, RAM: {} bytes", report.code_size, report.required_ram());
+//!
+//!     let mut code = [0; 1024];
+//!     let mut ram = [0; 1024];
+//!     elf.load(&mut code, &mut ram)?;
+//!
+//!     let mut memory = SliceMemory::new(&code, &mut ram);
+//!     let mut engine = Engine::new(&mut memory, report.apply(Config::default())).unwrap();
+//!     engine.run().unwrap();
+//!     Ok(())
+//! }
+//! ```
+
+pub mod slots;
+pub mod upload;
+
+use crate::engine::Config;
+use crate::error::LoaderError;
+use crate::memory::{Memory, RAM_OFFSET};
+
+/// ELF header size (32-bit).
+const EHDR_SIZE: usize = 52;
+/// Program header size (32-bit).
+const PHDR_SIZE: usize = 32;
+/// Section header size (32-bit).
+const SHDR_SIZE: usize = 40;
+
+/// ELF machine: RISC-V.
+const EM_RISCV: u16 = 0xF3;
+/// ELF type: Executable.
+const ET_EXEC: u16 = 2;
+/// Program header type: Loadable segment.
+const PT_LOAD: u32 = 1;
+/// Program header type: Unwind table (`.eh_frame_hdr` section).
+const PT_GNU_EH_FRAME: u32 = 0x6474_E550;
+/// Program header type: Stack (size requested with `-z stack-size=<size>`).
+const PT_GNU_STACK: u32 = 0x6474_E551;
+
+/// Exception header version (`.eh_frame_hdr`).
+const EH_FRAME_HDR_VERSION: u8 = 1;
+/// Exception header pointer encoding: Omitted (`DW_EH_PE_omit`).
+const DW_EH_PE_OMIT: u8 = 0xFF;
+
+/// Segment flag: Executable.
+pub const PF_X: u32 = 0x1;
+/// Segment flag: Writable.
+pub const PF_W: u32 = 0x2;
+/// Segment flag: Readable.
+pub const PF_R: u32 = 0x4;
+
+/// Read a little endian `u16` from a slice.
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, LoaderError> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or(LoaderError::InvalidHeader)
+}
+
+/// Read a little endian `u32` from a slice.
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, LoaderError> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or(LoaderError::InvalidHeader)
+}
+
+/// Read a pointer from an exception header (`DW_EH_PE_*` encoding, 16 and 32-bit formats).
+///
+/// Arguments:
+/// - `header`: Exception header data.
+/// - `offset`: Offset of the pointer in the header.
+/// - `encoding`: Pointer encoding.
+/// - `address`: Run address of the header.
+///
+/// Returns:
+/// - `Ok((u32, usize))`: Decoded pointer and offset after it.
+/// - `Err(LoaderError)`: The pointer is outside of the header, or the encoding is not supported.
+fn read_encoded(
+    header: &[u8],
+    offset: usize,
+    encoding: u8,
+    address: u32,
+) -> Result<(u32, usize), LoaderError> {
+    let truncated = |_| LoaderError::InvalidSegment;
+    let (value, size) = match encoding & 0x0F {
+        // Absolute pointer, unsigned and signed 32-bit
+        0x00 | 0x03 | 0x0B => (read_u32(header, offset).map_err(truncated)?, 4),
+        // Unsigned 16-bit
+        0x02 => (read_u16(header, offset).map_err(truncated)? as u32, 2),
+        // Signed 16-bit
+        0x0A => (
+            read_u16(header, offset).map_err(truncated)? as i16 as u32,
+            2,
+        ),
+        _ => return Err(LoaderError::Unsupported),
+    };
+
+    let base = match encoding & 0xF0 {
+        // Absolute
+        0x00 => 0,
+        // Relative to the pointer
+        0x10 => address.wrapping_add(offset as u32),
+        // Relative to the header
+        0x30 => address,
+        _ => return Err(LoaderError::Unsupported),
+    };
+
+    Ok((base.wrapping_add(value), offset + size))
+}
+
+/// Loadable segment (ELF program header).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Segment {
+    /// Offset of the segment data in the file.
+    pub offset: u32,
+    /// Virtual (run) address.
+    pub virtual_address: u32,
+    /// Physical (load) address.
+    pub physical_address: u32,
+    /// Size of the segment data in the file.
+    pub file_size: u32,
+    /// Size of the segment in memory (bigger than the file size for zero-initialized data).
+    pub memory_size: u32,
+    /// Segment flags ([`PF_R`], [`PF_W`], [`PF_X`]).
+    pub flags: u32,
+}
+
+impl Segment {
+    /// Parse a program header.
+    fn parse(data: &[u8]) -> Result<(u32, Self), LoaderError> {
+        Ok((
+            read_u32(data, 0)?,
+            Segment {
+                offset: read_u32(data, 4)?,
+                virtual_address: read_u32(data, 8)?,
+                physical_address: read_u32(data, 12)?,
+                file_size: read_u32(data, 16)?,
+                memory_size: read_u32(data, 20)?,
+                flags: read_u32(data, 24)?,
+            },
+        ))
+    }
+}
+
+/// ELF section (section header), used to locate conventions not described by the segments (ex.: `.tohost`).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Section {
+    /// Offset of the section data in the file.
+    pub offset: u32,
+    /// Run address.
+    pub address: u32,
+    /// Size of the section in bytes.
+    pub size: u32,
+}
+
+/// Unwind table of a guest (`.eh_frame_hdr` section, check the [module documentation](self)).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct UnwindTable {
+    /// Address of the `.eh_frame_hdr` section.
+    pub address: u32,
+    /// Address of the `.eh_frame` section.
+    pub eh_frame: u32,
+    /// Number of FDEs (functions) in the binary search table (0 = No table, the unwinder scans `.eh_frame`).
+    pub fde_count: u32,
+}
+
+/// Guest binary size and layout report (check [`Elf::report`]).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LoadReport {
+    /// Entry point.
+    pub entry_point: u32,
+    /// Number of loadable segments.
+    pub segments: u32,
+    /// Code size in bytes (end of the last segment loaded to the code region).
+    pub code_size: u32,
+    /// Static RAM size in bytes (end of the last segment running from RAM, including zero-initialized data).
+    pub ram_size: u32,
+    /// Data size in bytes loaded directly to RAM (part of `ram_size`).
+    pub ram_load_size: u32,
+    /// Stack size requested by the guest (`PT_GNU_STACK`, `-z stack-size=<size>`), if any.
+    pub stack_size: Option<u32>,
+    /// ELF flags (RISC-V ABI: RVC, float ABI, RVE, TSO).
+    pub flags: u32,
+    /// Unwind table (`PT_GNU_EH_FRAME`, `.eh_frame_hdr` section), if any.
+    pub eh_frame_hdr: Option<UnwindTable>,
+}
+
+impl LoadReport {
+    /// RAM required by the guest (static RAM + requested stack).
+    pub fn required_ram(&self) -> u32 {
+        self.ram_size.saturating_add(self.stack_size.unwrap_or(0))
+    }
+
+    /// Check the report against the available memory.
+    ///
+    /// Arguments:
+    /// - `code_size`: Available code region size in bytes.
+    /// - `ram_size`: Available RAM size in bytes.
+    ///
+    /// Returns:
+    /// - `Ok(())`: The guest fits.
+    /// - `Err(LoaderError)`: The guest doesn't fit ([`LoaderError::CodeTooLarge`] or [`LoaderError::RamTooLarge`]).
+    pub fn check(&self, code_size: usize, ram_size: usize) -> Result<(), LoaderError> {
+        if self.code_size as usize > code_size {
+            return Err(LoaderError::CodeTooLarge);
+        }
+
+        if self.required_ram() as usize > ram_size {
+            return Err(LoaderError::RamTooLarge);
+        }
+
+        Ok(())
+    }
+
+    /// Apply the report to an engine configuration (entry point and requested stack size).
+    ///
+    /// Arguments:
+    /// - `config`: Engine configuration.
+    ///
+    /// Returns:
+    /// - `Config`: The updated configuration.
+    pub fn apply<M: Memory>(&self, config: Config<M>) -> Config<M> {
+        let config = config.with_entry_point(Some(self.entry_point));
+        match self.stack_size {
+            Some(stack_size) => config.with_stack_size(self.ram_size.saturating_add(stack_size)),
+            None => config,
+        }
+    }
+}
+
+/// Parsed ELF file (32-bit, little endian, RISC-V executable).
+#[derive(Debug, Clone, Copy)]
+pub struct Elf<'a> {
+    /// ELF file.
+    data: &'a [u8],
+    /// Entry point.
+    entry_point: u32,
+    /// Program header table offset.
+    phoff: usize,
+    /// Program header entry size.
+    phentsize: usize,
+    /// Number of program headers.
+    phnum: usize,
+    /// ELF flags.
+    flags: u32,
+}
+
+impl<'a> Elf<'a> {
+    /// Parse an ELF file header.
+    ///
+    /// Arguments:
+    /// - `data`: ELF file.
+    ///
+    /// Returns:
+    /// - `Ok(Elf)`: The parsed ELF file.
+    /// - `Err(LoaderError)`: Not a valid or supported ELF file.
+    pub fn parse(data: &'a [u8]) -> Result<Self, LoaderError> {
+        let ident = data.get(..16).ok_or(LoaderError::InvalidHeader)?;
+        if ident[..4] != [0x7F, b'E', b'L', b'F'] || data.len() < EHDR_SIZE {
+            return Err(LoaderError::InvalidHeader);
+        }
+
+        // 32-bit, little endian, version 1, RISC-V executable
+        if ident[4] != 1 || ident[5] != 1 || ident[6] != 1 {
+            return Err(LoaderError::Unsupported);
+        }
+        if read_u16(data, 16)? != ET_EXEC || read_u16(data, 18)? != EM_RISCV {
+            return Err(LoaderError::Unsupported);
+        }
+
+        let elf = Elf {
+            data,
+            entry_point: read_u32(data, 24)?,
+            phoff: read_u32(data, 28)? as usize,
+            flags: read_u32(data, 36)?,
+            phentsize: read_u16(data, 42)? as usize,
+            phnum: read_u16(data, 44)? as usize,
+        };
+
+        // Program header table must be inside the file
+        let table_size = elf
+            .phentsize
+            .checked_mul(elf.phnum)
+            .ok_or(LoaderError::InvalidHeader)?;
+        let table_end = elf
+            .phoff
+            .checked_add(table_size)
+            .ok_or(LoaderError::InvalidHeader)?;
+        if elf.phentsize < PHDR_SIZE || table_end > data.len() {
+            return Err(LoaderError::InvalidHeader);
+        }
+
+        Ok(elf)
+    }
+
+    /// Entry point.
+    pub fn entry_point(&self) -> u32 {
+        self.entry_point
+    }
+
+    /// Iterate over the program headers (type and segment).
+    fn headers(&self) -> impl Iterator<Item = Result<(u32, Segment), LoaderError>> + 'a {
+        let data = self.data;
+        let (phoff, phentsize) = (self.phoff, self.phentsize);
+        (0..self.phnum).map(move |i| {
+            let offset = phoff + i * phentsize;
+            Segment::parse(&data[offset..offset + PHDR_SIZE])
+        })
+    }
+
+    /// Iterate over the loadable segments.
+    pub fn segments(&self) -> impl Iterator<Item = Result<Segment, LoaderError>> + 'a {
+        self.headers().filter_map(|header| match header {
+            Ok((PT_LOAD, segment)) => Some(Ok(segment)),
+            Ok(_) => None,
+            Err(error) => Some(Err(error)),
+        })
+    }
+
+    /// Find a section by name.
+    ///
+    /// Arguments:
+    /// - `name`: Section name (ex.: `.tohost`).
+    ///
+    /// Returns:
+    /// - `Ok(Some(Section))`: The section.
+    /// - `Ok(None)`: No section with this name (or no section headers, ex.: stripped file).
+    /// - `Err(LoaderError)`: The section headers are invalid ([`LoaderError::InvalidSection`]).
+    pub fn section(&self, name: &str) -> Result<Option<Section>, LoaderError> {
+        let data = self.data;
+        let shoff = read_u32(data, 32)? as usize;
+        let shentsize = read_u16(data, 46)? as usize;
+        let shnum = read_u16(data, 48)? as usize;
+        let shstrndx = read_u16(data, 50)? as usize;
+        if shoff == 0 || shnum == 0 {
+            return Ok(None);
+        }
+
+        // Section header table must be inside the file
+        let table_end = shentsize
+            .checked_mul(shnum)
+            .and_then(|size| size.checked_add(shoff));
+        if shentsize < SHDR_SIZE
+            || shstrndx >= shnum
+            || !matches!(table_end, Some(end) if end <= data.len())
+        {
+            return Err(LoaderError::InvalidSection);
+        }
+
+        // Section names (string table)
+        let header = |index: usize| &data[shoff + index * shentsize..][..SHDR_SIZE];
+        let names_offset = read_u32(header(shstrndx), 16)? as usize;
+        let names_size = read_u32(header(shstrndx), 20)? as usize;
+        let names = data
+            .get(names_offset..)
+            .and_then(|names| names.get(..names_size))
+            .ok_or(LoaderError::InvalidSection)?;
+
+        for index in 0..shnum {
+            let header = header(index);
+            let found = names
+                .get(read_u32(header, 0)? as usize..)
+                .ok_or(LoaderError::InvalidSection)?
+                .split(|&byte| byte == 0)
+                .next()
+                .is_some_and(|found| found == name.as_bytes());
+            if found {
+                return Ok(Some(Section {
+                    address: read_u32(header, 12)?,
+                    offset: read_u32(header, 16)?,
+                    size: read_u32(header, 20)?,
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Compute the size and layout report, without loading anything.
+    ///
+    /// Returns:
+    /// - `Ok(LoadReport)`: The report.
+    /// - `Err(LoaderError)`: A segment is invalid or outside of the memory regions.
+    pub fn report(&self) -> Result<LoadReport, LoaderError> {
+        let mut report = LoadReport {
+            entry_point: self.entry_point,
+            segments: 0,
+            code_size: 0,
+            ram_size: 0,
+            ram_load_size: 0,
+            stack_size: None,
+            flags: self.flags,
+            eh_frame_hdr: None,
+        };
+        let mut eh_frame_hdr = None;
+
+        for header in self.headers() {
+            let (kind, segment) = header?;
+            match kind {
+                PT_LOAD => {
+                    let (region, offset) = placement(&segment)?;
+                    report.segments += 1;
+
+                    // Loaded data
+                    let load_end = offset
+                        .checked_add(segment.file_size)
+                        .ok_or(LoaderError::InvalidSegment)?;
+                    match region {
+                        Region::Code => report.code_size = report.code_size.max(load_end),
+                        Region::Ram => {
+                            report.ram_size = report.ram_size.max(load_end);
+                            report.ram_load_size = report
+                                .ram_load_size
+                                .checked_add(segment.file_size)
+                                .ok_or(LoaderError::InvalidSegment)?;
+                        }
+                    }
+
+                    // Running from RAM (including zero-initialized data)
+                    if segment.virtual_address >= RAM_OFFSET {
+                        let run_end = (segment.virtual_address - RAM_OFFSET)
+                            .checked_add(segment.memory_size)
+                            .ok_or(LoaderError::InvalidSegment)?;
+                        report.ram_size = report.ram_size.max(run_end);
+                    }
+                }
+                PT_GNU_STACK if segment.memory_size > 0 => {
+                    report.stack_size = Some(segment.memory_size);
+                }
+                PT_GNU_EH_FRAME => eh_frame_hdr = Some(segment),
+                _ => {}
+            }
+        }
+
+        if let Some(table) = eh_frame_hdr {
+            report.eh_frame_hdr = Some(self.unwind_table(&table)?);
+        }
+
+        Ok(report)
+    }
+
+    /// Parse the unwind table (`.eh_frame_hdr` section).
+    ///
+    /// Arguments:
+    /// - `table`: Unwind table segment (`PT_GNU_EH_FRAME`).
+    ///
+    /// Returns:
+    /// - `Ok(UnwindTable)`: The unwind table.
+    /// - `Err(LoaderError)`: The table is invalid, not loaded, or its encoding is not supported.
+    fn unwind_table(&self, table: &Segment) -> Result<UnwindTable, LoaderError> {
+        // Must be loaded (inside the file data of a loadable segment)
+        let loaded = |start: u32, size: u32| -> Result<(), LoaderError> {
+            let end = start.checked_add(size).ok_or(LoaderError::InvalidSegment)?;
+            let mut segments = self.segments().filter_map(Result::ok);
+            segments
+                .any(|segment| {
+                    start >= segment.virtual_address
+                        && end - segment.virtual_address <= segment.file_size
+                })
+                .then_some(())
+                .ok_or(LoaderError::InvalidSegment)
+        };
+        loaded(table.virtual_address, table.memory_size)?;
+
+        let header = self
+            .data
+            .get(table.offset as usize..)
+            .and_then(|data| data.get(..table.file_size as usize))
+            .ok_or(LoaderError::InvalidSegment)?;
+        let [version, eh_frame_encoding, count_encoding, table_encoding, ..] = *header else {
+            return Err(LoaderError::InvalidSegment);
+        };
+        if version != EH_FRAME_HDR_VERSION {
+            return Err(LoaderError::Unsupported);
+        }
+
+        let address = table.virtual_address;
+        let (eh_frame, offset) = read_encoded(header, 4, eh_frame_encoding, address)?;
+        loaded(eh_frame, 4)?;
+
+        // Binary search table (optional), pairs of initial location and FDE address
+        let mut fde_count = 0;
+        if count_encoding != DW_EH_PE_OMIT && table_encoding != DW_EH_PE_OMIT {
+            let (count, offset) = read_encoded(header, offset, count_encoding, address)?;
+            let (_, entry_size) = read_encoded(&[0; 4], 0, table_encoding, 0)?;
+            if offset as u64 + count as u64 * entry_size as u64 * 2 > header.len() as u64 {
+                return Err(LoaderError::InvalidSegment);
+            }
+            fde_count = count;
+        }
+
+        Ok(UnwindTable {
+            address,
+            eh_frame,
+            fde_count,
+        })
+    }
+
+    /// Load the guest into the code and RAM buffers.
+    /// The report is checked against the buffer sizes before anything is written.
+    ///
+    /// Arguments:
+    /// - `code`: Code buffer (address `0x00000000`).
+    /// - `ram`: RAM buffer (address [`RAM_OFFSET`]).
+    ///
+    /// Returns:
+    /// - `Ok(LoadReport)`: The guest was loaded.
+    /// - `Err(LoaderError)`: The guest is invalid or doesn't fit.
+    pub fn load(&self, code: &mut [u8], ram: &mut [u8]) -> Result<LoadReport, LoaderError> {
+        let report = self.report()?;
+        report.check(code.len(), ram.len())?;
+
+        for segment in self.segments() {
+            let segment = segment?;
+            let (region, offset) = placement(&segment)?;
+            let offset = offset as usize;
+            let file_size = segment.file_size as usize;
+
+            let source = self
+                .data
+                .get(segment.offset as usize..)
+                .and_then(|data| data.get(..file_size))
+                .ok_or(LoaderError::InvalidSegment)?;
+            let destination = match region {
+                Region::Code => &mut code[offset..offset + file_size],
+                Region::Ram => &mut ram[offset..offset + file_size],
+            };
+            destination.copy_from_slice(source);
+
+            // Zero-initialized data running from where it's loaded
+            if region == Region::Ram && segment.virtual_address == segment.physical_address {
+                let end = offset + segment.memory_size as usize;
+                ram[offset + file_size..end].fill(0);
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Memory region of a segment.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Region {
+    /// Code region (`0x00000000`).
+    Code,
+    /// RAM region ([`RAM_OFFSET`]).
+    Ram,
+}
+
+/// Get the region and offset where a segment is loaded (physical address).
+fn placement(segment: &Segment) -> Result<(Region, u32), LoaderError> {
+    if segment.memory_size < segment.file_size {
+        return Err(LoaderError::InvalidSegment);
+    }
+
+    // Running address range must fit in the address space
+    segment
+        .virtual_address
+        .checked_add(segment.memory_size)
+        .ok_or(LoaderError::InvalidSegment)?;
+
+    let address = segment.physical_address;
+    let end = address
+        .checked_add(segment.file_size)
+        .ok_or(LoaderError::InvalidSegment)?;
+
+    if address >= RAM_OFFSET {
+        Ok((Region::Ram, address - RAM_OFFSET))
+    } else if end <= RAM_OFFSET {
+        Ok((Region::Code, address))
+    } else {
+        // Crosses from code to RAM
+        Err(LoaderError::InvalidSegment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write a program header to a test ELF.
+    fn phdr(elf: &mut [u8], index: usize, fields: [u32; 7]) {
+        let offset = EHDR_SIZE + index * PHDR_SIZE;
+        for (i, field) in fields.iter().enumerate() {
+            elf[offset + i * 4..offset + i * 4 + 4].copy_from_slice(&field.to_le_bytes());
+        }
+    }
+
+    /// Build a test ELF with a code segment (`li a0, 1; ebreak`), a stack request (4 KiB)
+    /// and a RAM segment (`.data` + `.bss`, loaded at `data_lma`).
+    fn test_elf(data_lma: u32) -> [u8; 160] {
+        let mut elf = [0; 160];
+        // Header: 32-bit, little endian, RISC-V executable, 3 program headers
+        elf[..7].copy_from_slice(&[0x7F, b'E', b'L', b'F', 1, 1, 1]);
+        elf[16..20].copy_from_slice(&[2, 0, 0xF3, 0]);
+        elf[20..24].copy_from_slice(&1u32.to_le_bytes());
+        elf[24..28].copy_from_slice(&4u32.to_le_bytes());
+        elf[28..32].copy_from_slice(&(EHDR_SIZE as u32).to_le_bytes());
+        elf[42..44].copy_from_slice(&(PHDR_SIZE as u16).to_le_bytes());
+        elf[44..46].copy_from_slice(&3u16.to_le_bytes());
+
+        // Type, offset, virtual address, physical address, file size, memory size, flags
+        phdr(&mut elf, 0, [PT_LOAD, 148, 0, 0, 8, 8, PF_R | PF_X]);
+        phdr(&mut elf, 1, [PT_GNU_STACK, 0, 0, 0, 0, 4096, PF_R | PF_W]);
+        phdr(
+            &mut elf,
+            2,
+            [PT_LOAD, 156, RAM_OFFSET, data_lma, 4, 16, PF_R | PF_W],
+        );
+
+        elf[148..156].copy_from_slice(&[0x13, 0x05, 0x10, 0x00, 0x73, 0x00, 0x10, 0x00]);
+        elf[156..160].copy_from_slice(&[0xAA, 0xBB, 0xCC, 0xDD]);
+        elf
+    }
+
+    #[test]
+    fn test_report() {
+        let elf = test_elf(RAM_OFFSET);
+        let report = Elf::parse(&elf).unwrap().report().unwrap();
+
+        assert_eq!(
+            report,
+            LoadReport {
+                entry_point: 4,
+                segments: 2,
+                code_size: 8,
+                ram_size: 16,
+                ram_load_size: 4,
+                stack_size: Some(4096),
+                flags: 0,
+                eh_frame_hdr: None,
+            }
+        );
+        assert_eq!(report.required_ram(), 16 + 4096);
+        assert_eq!(report.check(8, 4112), Ok(()));
+        assert_eq!(report.check(7, 4112), Err(LoaderError::CodeTooLarge));
+        assert_eq!(report.check(8, 4111), Err(LoaderError::RamTooLarge));
+    }
+
+    /// Unwind table fixture, two functions with FDEs (check `tests/unwind/unwind.s`).
+    const UNWIND_ELF: &[u8; 232] = include_bytes!("../tests/unwind/unwind.elf");
+    /// Address of the `.eh_frame_hdr` section in the unwind table fixture.
+    const UNWIND_EH_FRAME_HDR: u32 = 0x8C;
+
+    #[test]
+    fn test_eh_frame_hdr() {
+        let elf = Elf::parse(UNWIND_ELF).unwrap();
+        let report = elf.report().unwrap();
+        assert_eq!(
+            report.eh_frame_hdr,
+            Some(UnwindTable {
+                address: UNWIND_EH_FRAME_HDR,
+                eh_frame: 0xA8,
+                fde_count: 2,
+            })
+        );
+
+        // Unwind tables are loaded with the code
+        let mut code = [0; 232];
+        elf.load(&mut code, &mut []).unwrap();
+        assert_eq!(&code, UNWIND_ELF);
+
+        let mut memory = crate::memory::SliceMemory::new(&code, &mut []);
+        let config = report.apply(Config::default());
+        let mut engine = crate::engine::Engine::new(&mut memory, config).unwrap();
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(10), Ok(2));
+    }
+
+    /// Throw/catch guest, Rust `panic=unwind` with the `unwinding` crate (check `tests/unwind/throw`).
+    #[cfg(feature = "m_extension")]
+    const THROW_ELF: &[u8] = include_bytes!("../tests/unwind/throw.elf");
+
+    #[cfg(feature = "m_extension")]
+    #[test]
+    fn test_unwind_guest() {
+        let elf = Elf::parse(THROW_ELF).unwrap();
+        let report = elf.report().unwrap();
+        assert!(report.eh_frame_hdr.is_some());
+
+        let mut code = std::vec![0; report.code_size as usize];
+        let mut ram = std::vec![0; report.required_ram() as usize];
+        elf.load(&mut code, &mut ram).unwrap();
+
+        let mut memory = crate::memory::SliceMemory::new(&code, &mut ram);
+        let config = report.apply(Config::default());
+        let mut engine = crate::engine::Engine::new(&mut memory, config).unwrap();
+        assert_eq!(engine.run(), Ok(false));
+
+        // 2 panics caught (5 guards dropped while unwinding, 10 in total), sum of (0, 1, 2) * 10 + 1
+        assert_eq!(engine.registers.get(10), Ok(10 * 1000 + 2 * 100 + 33));
+    }
+
+    #[test]
+    fn test_eh_frame_hdr_invalid() {
+        let header = UNWIND_EH_FRAME_HDR as usize;
+
+        // No binary search table (the unwinder scans `.eh_frame`)
+        let mut elf = *UNWIND_ELF;
+        elf[header + 2] = DW_EH_PE_OMIT;
+        let report = Elf::parse(&elf).unwrap().report().unwrap();
+        assert_eq!(report.eh_frame_hdr.map(|table| table.fde_count), Some(0));
+
+        // Unknown version or encoding
+        let mut elf = *UNWIND_ELF;
+        elf[header] = 2;
+        let result = Elf::parse(&elf).unwrap().report();
+        assert_eq!(result, Err(LoaderError::Unsupported));
+
+        let mut elf = *UNWIND_ELF;
+        elf[header + 1] = 0x1C; // pcrel, sdata8
+        let result = Elf::parse(&elf).unwrap().report();
+        assert_eq!(result, Err(LoaderError::Unsupported));
+
+        // Binary search table past the end of the header
+        let mut elf = *UNWIND_ELF;
+        elf[header + 8] = 3;
+        let result = Elf::parse(&elf).unwrap().report();
+        assert_eq!(result, Err(LoaderError::InvalidSegment));
+
+        // `.eh_frame` not loaded
+        let mut elf = *UNWIND_ELF;
+        elf[header + 7] = 0x10;
+        let result = Elf::parse(&elf).unwrap().report();
+        assert_eq!(result, Err(LoaderError::InvalidSegment));
+
+        // Not loaded (outside of the code segment, or in zero-initialized data)
+        let mut elf = test_elf(RAM_OFFSET);
+        phdr(&mut elf, 1, [PT_GNU_EH_FRAME, 152, 6, 6, 4, 4, PF_R]);
+        let result = Elf::parse(&elf).unwrap().report();
+        assert_eq!(result, Err(LoaderError::InvalidSegment));
+
+        phdr(
+            &mut elf,
+            1,
+            [
+                PT_GNU_EH_FRAME,
+                0,
+                RAM_OFFSET + 4,
+                RAM_OFFSET + 4,
+                0,
+                4,
+                PF_R,
+            ],
+        );
+        let result = Elf::parse(&elf).unwrap().report();
+        assert_eq!(result, Err(LoaderError::InvalidSegment));
+    }
+
+    #[test]
+    fn test_load() {
+        let elf = test_elf(RAM_OFFSET);
+        let mut code = [0xFF; 8];
+        let mut ram = [0xFF; 4112];
+
+        let report = Elf::parse(&elf).unwrap().load(&mut code, &mut ram).unwrap();
+        assert_eq!(report.segments, 2);
+        assert_eq!(code, [0x13, 0x05, 0x10, 0x00, 0x73, 0x00, 0x10, 0x00]);
+        assert_eq!(ram[..4], [0xAA, 0xBB, 0xCC, 0xDD]);
+        assert_eq!(ram[4..16], [0; 12]); // bss
+        assert_eq!(ram[16], 0xFF);
+    }
+
+    #[test]
+    fn test_load_data_in_code() {
+        // .data loaded after the code (copied to RAM by the guest startup code)
+        let elf = test_elf(8);
+        let mut code = [0; 12];
+        let mut ram = [0xFF; 4112];
+
+        let report = Elf::parse(&elf).unwrap().load(&mut code, &mut ram).unwrap();
+        assert_eq!(report.code_size, 12);
+        assert_eq!(report.ram_size, 16);
+        assert_eq!(report.ram_load_size, 0);
+        assert_eq!(code[8..], [0xAA, 0xBB, 0xCC, 0xDD]);
+        assert_eq!(ram[0], 0xFF); // Untouched
+    }
+
+    #[test]
+    fn test_load_too_large() {
+        let elf = test_elf(RAM_OFFSET);
+        let mut code = [0; 8];
+        let mut ram = [0xFF; 16];
+
+        let result = Elf::parse(&elf).unwrap().load(&mut code, &mut ram);
+        assert_eq!(result, Err(LoaderError::RamTooLarge));
+        assert_eq!(ram, [0xFF; 16]); // Nothing was written
+        assert_eq!(code, [0; 8]);
+    }
+
+    #[test]
+    fn test_apply() {
+        let elf = test_elf(RAM_OFFSET);
+        let report = Elf::parse(&elf).unwrap().report().unwrap();
+        let config = report.apply(Config::<crate::memory::SliceMemory>::default());
+
+        assert_eq!(config.entry_point, Some(4));
+        assert_eq!(config.stack_size, 16 + 4096);
+    }
+
+    #[test]
+    fn test_run() {
+        let elf = test_elf(RAM_OFFSET);
+        let elf = Elf::parse(&elf).unwrap();
+        let mut code = [0; 8];
+        let mut ram = [0; 4112];
+        let report = elf.load(&mut code, &mut ram).unwrap();
+
+        let mut memory = crate::memory::SliceMemory::new(&code, &mut ram);
+        let config = report.apply(Config::default()).with_entry_point(Some(0));
+        let mut engine = crate::engine::Engine::new(&mut memory, config).unwrap();
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(10), Ok(1));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(
+            Elf::parse(&[0; 64]).map(|_| ()),
+            Err(LoaderError::InvalidHeader)
+        );
+
+        let mut elf = test_elf(RAM_OFFSET);
+        elf[4] = 2; // 64-bit
+        assert_eq!(Elf::parse(&elf).map(|_| ()), Err(LoaderError::Unsupported));
+
+        let mut elf = test_elf(RAM_OFFSET);
+        elf[44..46].copy_from_slice(&100u16.to_le_bytes()); // Program headers out of bounds
+        assert_eq!(
+            Elf::parse(&elf).map(|_| ()),
+            Err(LoaderError::InvalidHeader)
+        );
+
+        let mut elf = test_elf(RAM_OFFSET);
+        phdr(&mut elf, 0, [PT_LOAD, 148, 0, RAM_OFFSET - 4, 8, 8, PF_R]); // Crosses regions
+        let result = Elf::parse(&elf).unwrap().report();
+        assert_eq!(result, Err(LoaderError::InvalidSegment));
+
+        let mut elf = test_elf(RAM_OFFSET);
+        phdr(&mut elf, 0, [PT_LOAD, 156, 0, 0, 8, 8, PF_R]); // Data out of bounds
+        let result = Elf::parse(&elf).unwrap().load(&mut [0; 8], &mut [0; 4112]);
+        assert_eq!(result, Err(LoaderError::InvalidSegment));
+    }
+
+    #[test]
+    fn test_overflow() {
+        // RAM segments with a total size overflowing 32 bits
+        let mut elf = test_elf(RAM_OFFSET);
+        for index in 0..3 {
+            let size = 0x7000_0000;
+            phdr(
+                &mut elf,
+                index,
+                [PT_LOAD, 148, RAM_OFFSET, RAM_OFFSET, size, size, PF_R],
+            );
+        }
+        let result = Elf::parse(&elf).unwrap().report();
+        assert_eq!(result, Err(LoaderError::InvalidSegment));
+
+        // Segment ending past the end of the address space
+        let mut elf = test_elf(RAM_OFFSET);
+        phdr(
+            &mut elf,
+            2,
+            [PT_LOAD, 156, RAM_OFFSET, u32::MAX - 2, 4, 4, PF_R],
+        );
+        let result = Elf::parse(&elf).unwrap().report();
+        assert_eq!(result, Err(LoaderError::InvalidSegment));
+
+        let mut elf = test_elf(RAM_OFFSET);
+        phdr(
+            &mut elf,
+            2,
+            [PT_LOAD, 156, u32::MAX - 2, RAM_OFFSET, 4, 4, PF_R],
+        );
+        let result = Elf::parse(&elf).unwrap().report();
+        assert_eq!(result, Err(LoaderError::InvalidSegment));
+    }
+
+    /// Build a test ELF with section headers (null, `.tohost` and `.shstrtab`, names at `names_offset`).
+    fn section_elf(names_offset: u32) -> [u8; 300] {
+        let mut elf = [0; 300];
+        elf[..160].copy_from_slice(&test_elf(RAM_OFFSET));
+        elf[160..179].copy_from_slice(b"\0.tohost\0.shstrtab\0");
+        elf[32..36].copy_from_slice(&180u32.to_le_bytes());
+        elf[46..48].copy_from_slice(&(SHDR_SIZE as u16).to_le_bytes());
+        elf[48..50].copy_from_slice(&3u16.to_le_bytes());
+        elf[50..52].copy_from_slice(&2u16.to_le_bytes());
+
+        // Name, type, flags, address, offset, size
+        let sections = [
+            [1, 1, 3, RAM_OFFSET + 0x1000, 0, 8],
+            [9, 3, 0, 0, names_offset, 19],
+        ];
+        for (index, fields) in sections.iter().enumerate() {
+            let offset = 180 + (index + 1) * SHDR_SIZE;
+            for (i, field) in fields.iter().enumerate() {
+                elf[offset + i * 4..offset + i * 4 + 4].copy_from_slice(&field.to_le_bytes());
+            }
+        }
+        elf
+    }
+
+    #[test]
+    fn test_section() {
+        let elf = section_elf(160);
+        let parsed = Elf::parse(&elf).unwrap();
+        assert_eq!(
+            parsed.section(".tohost"),
+            Ok(Some(Section {
+                offset: 0,
+                address: RAM_OFFSET + 0x1000,
+                size: 8,
+            }))
+        );
+        assert_eq!(parsed.section(".tohos"), Ok(None));
+        assert_eq!(parsed.section(".text"), Ok(None));
+
+        // No section headers
+        let elf = test_elf(RAM_OFFSET);
+        assert_eq!(Elf::parse(&elf).unwrap().section(".tohost"), Ok(None));
+
+        // Names out of bounds
+        let mut elf = section_elf(290);
+        assert_eq!(
+            Elf::parse(&elf).unwrap().section(".tohost"),
+            Err(LoaderError::InvalidSection)
+        );
+
+        // Table out of bounds
+        elf[48..50].copy_from_slice(&4u16.to_le_bytes());
+        assert_eq!(
+            Elf::parse(&elf).unwrap().section(".tohost"),
+            Err(LoaderError::InvalidSection)
+        );
+    }
+}
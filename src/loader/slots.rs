@@ -0,0 +1,314 @@
+//! Image Slots Module
+//!
+//! A/B guest image slots for safe over-the-air updates:
+//! - The active slot runs, the standby slot receives the update (ex.: [`super::upload::Upload`]).
+//! - After a swap, the new image runs in trial mode: a fault during the trial rolls back
+//!   to the previous image, and after enough successful runs the health check confirms it.
+//!
+//! The code of the active slot is borrowed by the engine memory, so recreate the memory and the engine
+//! after a swap or rollback. Persist [`SlotState`] (ex.: in flash) to survive device resets.
+//!
+//! ```
+//! use embive::{
+//!     engine::{Config, Engine},
+//!     loader::slots::{Slot, SlotEvent, SlotState, Slots},
+//!     memory::SliceMemory,
+//! };
+//!
+//! let mut a = [0; 16];
+//! let mut b = [0; 16];
+//! let mut slots = Slots::new(&mut a, &mut b, SlotState::new(Slot::A)).with_trial_runs(1);
+//!
+//! // Write the update to the standby slot and swap
+//! slots.standby_mut().unwrap()[..4].copy_from_slice(&[0x73, 0x00, 0x10, 0x00]); // ebreak
+//! slots.swap().unwrap();
+//!
+//! let mut ram = [0; 16];
+//! let mut memory = SliceMemory::new(slots.code(), &mut ram);
+//! let result = Engine::new(&mut memory, Config::default()).unwrap().run();
+//!
+//! assert_eq!(slots.on_run(&result), SlotEvent::Confirmed);
+//! assert_eq!(slots.state().active, Slot::B);
+//! ```
+
+use crate::error::{EmbiveError, LoaderError};
+
+/// Image slot.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Slot {
+    /// Slot A.
+    A,
+    /// Slot B.
+    B,
+}
+
+impl Slot {
+    /// The other slot.
+    pub const fn other(&self) -> Self {
+        match self {
+            Slot::A => Slot::B,
+            Slot::B => Slot::A,
+        }
+    }
+}
+
+/// Slot state, to be persisted by the host.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SlotState {
+    /// Active slot.
+    pub active: Slot,
+    /// Remaining successful runs before the active slot is confirmed (None = confirmed).
+    pub trial: Option<u32>,
+}
+
+impl SlotState {
+    /// Create a new state, with a confirmed active slot.
+    ///
+    /// Arguments:
+    /// - `active`: Active slot.
+    pub const fn new(active: Slot) -> Self {
+        SlotState {
+            active,
+            trial: None,
+        }
+    }
+}
+
+/// Event reported after a run (check [`Slots::on_run`]).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SlotEvent {
+    /// Nothing changed.
+    None,
+    /// The active slot passed the trial and the health check, it's now confirmed.
+    Confirmed,
+    /// The active slot faulted (or failed the health check), the previous slot is active again.
+    RolledBack,
+}
+
+/// Health check function signature
+///
+/// Called when the trial of a slot ends without faults.
+///
+/// Arguments:
+/// - `slot`: Slot under trial.
+///
+/// Returns:
+/// - `bool`: The slot is healthy (`false` rolls back to the previous slot).
+pub type HealthFn = fn(slot: Slot) -> bool;
+
+/// A/B image slots.
+pub struct Slots<'a> {
+    /// Code regions (A, B).
+    code: [&'a mut [u8]; 2],
+    /// Current state.
+    state: SlotState,
+    /// Successful runs required to confirm a new slot.
+    trial_runs: u32,
+    /// Health check function.
+    health_fn: Option<HealthFn>,
+}
+
+impl<'a> Slots<'a> {
+    /// Create the image slots.
+    ///
+    /// Arguments:
+    /// - `a`: Slot A code region.
+    /// - `b`: Slot B code region.
+    /// - `state`: Current (persisted) state.
+    pub fn new(a: &'a mut [u8], b: &'a mut [u8], state: SlotState) -> Self {
+        Slots {
+            code: [a, b],
+            state,
+            trial_runs: 1,
+            health_fn: None,
+        }
+    }
+
+    /// Set the successful runs required to confirm a new slot (at least 1) and return the slots.
+    ///
+    /// Arguments:
+    /// - `trial_runs`: Successful runs (calls to [`Engine::run`](crate::engine::Engine::run)).
+    pub fn with_trial_runs(mut self, trial_runs: u32) -> Self {
+        self.trial_runs = trial_runs.max(1);
+        self
+    }
+
+    /// Set the health check function and return the slots.
+    ///
+    /// Arguments:
+    /// - `health_fn`: Optional health check function (None = Always healthy).
+    pub fn with_health_fn(mut self, health_fn: Option<HealthFn>) -> Self {
+        self.health_fn = health_fn;
+        self
+    }
+
+    /// Current state (persist it to survive device resets).
+    pub fn state(&self) -> SlotState {
+        self.state
+    }
+
+    /// Code of the active slot.
+    pub fn code(&self) -> &[u8] {
+        self.code[self.state.active as usize]
+    }
+
+    /// Code region of the standby slot, to write an update.
+    ///
+    /// Returns:
+    /// - `Ok(&mut [u8])`: Standby code region.
+    /// - `Err(LoaderError)`: The active slot is under trial, the standby slot holds the rollback image
+    ///   ([`LoaderError::SlotInTrial`]).
+    pub fn standby_mut(&mut self) -> Result<&mut [u8], LoaderError> {
+        if self.state.trial.is_some() {
+            return Err(LoaderError::SlotInTrial);
+        }
+
+        Ok(self.code[self.state.active.other() as usize])
+    }
+
+    /// Activate the standby slot, under trial.
+    ///
+    /// Returns:
+    /// - `Ok(())`: The standby slot is active.
+    /// - `Err(LoaderError)`: The active slot is already under trial ([`LoaderError::SlotInTrial`]).
+    pub fn swap(&mut self) -> Result<(), LoaderError> {
+        if self.state.trial.is_some() {
+            return Err(LoaderError::SlotInTrial);
+        }
+
+        self.state = SlotState {
+            active: self.state.active.other(),
+            trial: Some(self.trial_runs),
+        };
+        Ok(())
+    }
+
+    /// Confirm the active slot, ending the trial.
+    pub fn confirm(&mut self) {
+        self.state.trial = None;
+    }
+
+    /// Roll back to the previous slot, if the active slot is under trial.
+    ///
+    /// Returns:
+    /// - `bool`: Rolled back.
+    pub fn rollback(&mut self) -> bool {
+        if self.state.trial.is_none() {
+            return false;
+        }
+
+        self.state = SlotState::new(self.state.active.other());
+        true
+    }
+
+    /// Report the result of a run of the active slot.
+    ///
+    /// Arguments:
+    /// - `result`: Result of [`Engine::run`](crate::engine::Engine::run).
+    ///
+    /// Returns:
+    /// - `SlotEvent`: What changed (recreate the memory and engine on [`SlotEvent::RolledBack`]).
+    pub fn on_run(&mut self, result: &Result<bool, EmbiveError>) -> SlotEvent {
+        let Some(remaining) = self.state.trial else {
+            return SlotEvent::None;
+        };
+
+        if result.is_err() {
+            // Early fault
+            self.rollback();
+            return SlotEvent::RolledBack;
+        }
+
+        if remaining > 1 {
+            self.state.trial = Some(remaining - 1);
+            return SlotEvent::None;
+        }
+
+        // Trial ended, check the health
+        if self
+            .health_fn
+            .map_or(true, |health_fn| health_fn(self.state.active))
+        {
+            self.confirm();
+            SlotEvent::Confirmed
+        } else {
+            self.rollback();
+            SlotEvent::RolledBack
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swap_confirm() {
+        let mut a = [1; 4];
+        let mut b = [0; 4];
+        let mut slots = Slots::new(&mut a, &mut b, SlotState::new(Slot::A)).with_trial_runs(2);
+
+        slots.standby_mut().unwrap().fill(2);
+        slots.swap().unwrap();
+        assert_eq!(slots.code(), [2; 4]);
+        assert_eq!(slots.state().trial, Some(2));
+        assert_eq!(slots.standby_mut(), Err(LoaderError::SlotInTrial));
+        assert_eq!(slots.swap(), Err(LoaderError::SlotInTrial));
+
+        assert_eq!(slots.on_run(&Ok(true)), SlotEvent::None);
+        assert_eq!(slots.on_run(&Ok(false)), SlotEvent::Confirmed);
+        assert_eq!(slots.state(), SlotState::new(Slot::B));
+        assert_eq!(
+            slots.on_run(&Err(EmbiveError::InvalidInstruction)),
+            SlotEvent::None
+        );
+    }
+
+    #[test]
+    fn test_rollback_on_fault() {
+        let mut a = [1; 4];
+        let mut b = [2; 4];
+        let mut slots = Slots::new(&mut a, &mut b, SlotState::new(Slot::B)).with_trial_runs(3);
+
+        slots.swap().unwrap();
+        assert_eq!(slots.on_run(&Ok(true)), SlotEvent::None);
+        assert_eq!(
+            slots.on_run(&Err(EmbiveError::InvalidInstruction)),
+            SlotEvent::RolledBack
+        );
+        assert_eq!(slots.state(), SlotState::new(Slot::B));
+        assert_eq!(slots.code(), [2; 4]);
+    }
+
+    #[test]
+    fn test_health_check() {
+        fn unhealthy(slot: Slot) -> bool {
+            slot != Slot::B
+        }
+
+        let mut a = [1; 4];
+        let mut b = [2; 4];
+        let mut slots =
+            Slots::new(&mut a, &mut b, SlotState::new(Slot::A)).with_health_fn(Some(unhealthy));
+
+        slots.swap().unwrap();
+        assert_eq!(slots.on_run(&Ok(true)), SlotEvent::RolledBack);
+        assert_eq!(slots.state(), SlotState::new(Slot::A));
+    }
+
+    #[test]
+    fn test_restore_state() {
+        let mut a = [1; 4];
+        let mut b = [2; 4];
+        let state = SlotState {
+            active: Slot::B,
+            trial: Some(1),
+        };
+        let mut slots = Slots::new(&mut a, &mut b, state);
+
+        assert_eq!(slots.code(), [2; 4]);
+        assert!(slots.rollback());
+        assert!(!slots.rollback());
+        assert_eq!(slots.code(), [1; 4]);
+    }
+}
@@ -0,0 +1,246 @@
+//! Streaming Upload Module
+//!
+//! Receive guest code in chunks (ex.: over a serial link) directly into the code region,
+//! verifying it incrementally, without a second full-size staging buffer.
+//!
+//! ```
+//! use embive::loader::upload::{Crc32, Digest, Upload};
+//!
+//! let image = [0x13, 0x05, 0x10, 0x00, 0x73, 0x00, 0x10, 0x00]; // li a0, 1; ebreak
+//! let mut expected = Crc32::new();
+//! expected.update(&image);
+//!
+//! let mut code = [0; 1024];
+//! let mut upload = Upload::new(&mut code, Crc32::new());
+//! for (i, chunk) in image.chunks(3).enumerate() {
+//!     upload.write(i * 3, chunk).unwrap();
+//! }
+//!
+//! // Validate and get the code, ready to be used by the engine
+//! let code = upload.commit(image.len(), expected.finalize()).unwrap();
+//! assert_eq!(code[..8], image);
+//! ```
+
+use crate::error::LoaderError;
+
+/// Incremental digest (hash or checksum) used to verify uploads.
+/// Implement it to use a stronger hash (ex.: SHA-256 with a hardware accelerator).
+pub trait Digest {
+    /// Digest value.
+    type Output: PartialEq;
+
+    /// Add data to the digest.
+    ///
+    /// Arguments:
+    /// - `data`: Data chunk.
+    fn update(&mut self, data: &[u8]);
+
+    /// Get the digest value of all data added so far.
+    fn finalize(&self) -> Self::Output;
+}
+
+/// CRC-32 (IEEE 802.3) digest.
+/// Detects transmission errors, but isn't a cryptographic hash (it doesn't authenticate the code).
+#[derive(Debug, Clone, Copy)]
+pub struct Crc32 {
+    state: u32,
+}
+
+impl Crc32 {
+    /// Create a new CRC-32 digest.
+    pub const fn new() -> Self {
+        Crc32 { state: u32::MAX }
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Digest for Crc32 {
+    type Output = u32;
+
+    fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.state ^= *byte as u32;
+            for _ in 0..8 {
+                let mask = (self.state & 1).wrapping_neg();
+                self.state = (self.state >> 1) ^ (0xEDB8_8320 & mask);
+            }
+        }
+    }
+
+    fn finalize(&self) -> u32 {
+        !self.state
+    }
+}
+
+/// Streaming code upload.
+///
+/// Chunks must be written in order (retransmitted chunks are rejected, check [`Upload::written`]).
+/// The code is only usable after [`Upload::commit`] validates its size and digest.
+pub struct Upload<'a, D: Digest> {
+    /// Code region.
+    code: &'a mut [u8],
+    /// Bytes written so far.
+    written: usize,
+    /// Digest of the written bytes.
+    digest: D,
+}
+
+impl<'a, D: Digest> Upload<'a, D> {
+    /// Start an upload.
+    ///
+    /// Arguments:
+    /// - `code`: Code region (destination).
+    /// - `digest`: Digest used to verify the upload.
+    pub fn new(code: &'a mut [u8], digest: D) -> Self {
+        Upload {
+            code,
+            written: 0,
+            digest,
+        }
+    }
+
+    /// Bytes written so far (offset of the next chunk).
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Write a chunk.
+    ///
+    /// Arguments:
+    /// - `offset`: Chunk offset in the image (must be equal to [`Upload::written`]).
+    /// - `chunk`: Chunk data.
+    ///
+    /// Returns:
+    /// - `Ok(())`: Chunk written.
+    /// - `Err(LoaderError)`: Chunk is out of order ([`LoaderError::OutOfOrder`])
+    ///   or doesn't fit in the code region ([`LoaderError::CodeTooLarge`]).
+    pub fn write(&mut self, offset: usize, chunk: &[u8]) -> Result<(), LoaderError> {
+        if offset != self.written {
+            return Err(LoaderError::OutOfOrder);
+        }
+
+        let destination = self
+            .code
+            .get_mut(offset..offset + chunk.len())
+            .ok_or(LoaderError::CodeTooLarge)?;
+        destination.copy_from_slice(chunk);
+
+        self.digest.update(chunk);
+        self.written += chunk.len();
+        Ok(())
+    }
+
+    /// Validate the upload and finish it.
+    /// The rest of the code region is zeroed. If the validation fails, the written code is also zeroed,
+    /// so partial or corrupted code can't be executed.
+    ///
+    /// Arguments:
+    /// - `size`: Expected image size in bytes.
+    /// - `digest`: Expected image digest.
+    ///
+    /// Returns:
+    /// - `Ok(&[u8])`: The code region, ready to be used by the engine (ex.: [`crate::memory::SliceMemory`]).
+    /// - `Err(LoaderError)`: Upload is incomplete ([`LoaderError::SizeMismatch`])
+    ///   or corrupted ([`LoaderError::DigestMismatch`]).
+    pub fn commit(self, size: usize, digest: D::Output) -> Result<&'a [u8], LoaderError> {
+        let result = if self.written != size {
+            Err(LoaderError::SizeMismatch)
+        } else if self.digest.finalize() != digest {
+            Err(LoaderError::DigestMismatch)
+        } else {
+            Ok(())
+        };
+
+        match result {
+            Ok(()) => {
+                self.code[self.written..].fill(0);
+                Ok(self.code)
+            }
+            Err(error) => {
+                self.code.fill(0);
+                Err(error)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IMAGE: [u8; 8] = [0x13, 0x05, 0x10, 0x00, 0x73, 0x00, 0x10, 0x00];
+
+    fn crc(data: &[u8]) -> u32 {
+        let mut digest = Crc32::new();
+        digest.update(data);
+        digest.finalize()
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc(b""), 0);
+    }
+
+    #[test]
+    fn test_upload() {
+        let mut code = [0xFF; 12];
+        let mut upload = Upload::new(&mut code, Crc32::new());
+
+        upload.write(0, &IMAGE[..5]).unwrap();
+        assert_eq!(upload.written(), 5);
+        upload.write(5, &IMAGE[5..]).unwrap();
+
+        let code = upload.commit(IMAGE.len(), crc(&IMAGE)).unwrap();
+        assert_eq!(code[..8], IMAGE);
+        assert_eq!(code[8..], [0; 4]);
+    }
+
+    #[test]
+    fn test_upload_out_of_order() {
+        let mut code = [0; 12];
+        let mut upload = Upload::new(&mut code, Crc32::new());
+
+        upload.write(0, &IMAGE[..4]).unwrap();
+        assert_eq!(upload.write(0, &IMAGE[..4]), Err(LoaderError::OutOfOrder));
+        assert_eq!(upload.write(6, &IMAGE[6..]), Err(LoaderError::OutOfOrder));
+        assert_eq!(upload.written(), 4);
+    }
+
+    #[test]
+    fn test_upload_too_large() {
+        let mut code = [0; 4];
+        let mut upload = Upload::new(&mut code, Crc32::new());
+
+        assert_eq!(upload.write(0, &IMAGE), Err(LoaderError::CodeTooLarge));
+        assert_eq!(upload.written(), 0);
+    }
+
+    #[test]
+    fn test_upload_corrupted() {
+        let mut code = [0; 8];
+        let mut upload = Upload::new(&mut code, Crc32::new());
+        upload.write(0, &IMAGE[..4]).unwrap();
+        upload.write(4, &[0; 4]).unwrap();
+
+        let result = upload.commit(IMAGE.len(), crc(&IMAGE));
+        assert_eq!(result, Err(LoaderError::DigestMismatch));
+        assert_eq!(code, [0; 8]);
+    }
+
+    #[test]
+    fn test_upload_incomplete() {
+        let mut code = [0; 8];
+        let mut upload = Upload::new(&mut code, Crc32::new());
+        upload.write(0, &IMAGE[..4]).unwrap();
+
+        let result = upload.commit(IMAGE.len(), crc(&IMAGE));
+        assert_eq!(result, Err(LoaderError::SizeMismatch));
+        assert_eq!(code, [0; 8]);
+    }
+}
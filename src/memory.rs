@@ -3,6 +3,33 @@
 use crate::error::EmbiveError;
 use core::fmt::Debug;
 
+pub mod framebuffer;
+pub mod helpers;
+pub mod input;
+
+mod hashed;
+mod mmio;
+mod paged;
+mod protected;
+mod sanitized;
+mod scratch;
+mod unified;
+#[cfg(feature = "alloc")]
+mod vec;
+mod window;
+pub use framebuffer::FramebufferMemory;
+pub use hashed::HashedMemory;
+pub use input::InputMemory;
+pub use mmio::{MmioDevice, MmioMemory};
+pub use paged::PagedMemory;
+pub use protected::{ProtectedMemory, Protection};
+pub use sanitized::SanitizedMemory;
+pub use scratch::ScratchMemory;
+pub use unified::UnifiedSliceMemory;
+#[cfg(feature = "alloc")]
+pub use vec::VecMemory;
+pub use window::WindowMemory;
+
 /// RAM address offset
 pub const RAM_OFFSET: u32 = 0x80000000;
 
@@ -23,6 +50,21 @@ pub trait Memory {
     /// - `Err(EmbiveError)`: An error occurred. Ex.: Memory address is out of bounds.
     fn load<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError>;
 
+    /// Load `N` bytes of instructions from memory address (instruction fetch).
+    /// By default, the same as [`Memory::load`], override it to tell instruction fetches from data loads
+    /// (ex.: execute permissions, check [`ProtectedMemory`]).
+    ///
+    /// Arguments:
+    /// - `address`: Memory address to get (code or RAM).
+    ///
+    /// Returns:
+    /// - `Ok([u8; N])`: Bytes at the memory address.
+    /// - `Err(EmbiveError)`: An error occurred. Ex.: Memory address is out of bounds.
+    #[inline(always)]
+    fn fetch<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+        self.load(address)
+    }
+
     /// Store `N` bytes to memory address.
     /// Memory address can only be from RAM ([`RAM_OFFSET`]) region.
     /// RISC-V is little-endian, always use `to_le_bytes()` and `from_le_bytes()`.
@@ -35,10 +77,50 @@ pub trait Memory {
     /// - `Ok(())`: Bytes were stored successfully.
     /// - `Err(EmbiveError)`: An error occurred. Ex.: Memory address is out of bounds.
     fn store<const N: usize>(&mut self, address: u32, data: [u8; N]) -> Result<(), EmbiveError>;
+
+    /// Load bytes from memory address into a buffer (bulk load, ex.: syscalls moving kilobytes of data).
+    /// By default, loads words (bytes where a word isn't accessible), override it for faster copies.
+    ///
+    /// Arguments:
+    /// - `address`: Memory address to get (code or RAM).
+    /// - `buffer`: Buffer, filled completely.
+    ///
+    /// Returns:
+    /// - `Ok(())`: Bytes were loaded.
+    /// - `Err(EmbiveError)`: An error occurred. Ex.: Memory range is out of bounds.
+    fn load_bytes(&self, address: u32, buffer: &mut [u8]) -> Result<(), EmbiveError> {
+        helpers::load_words(self, address, buffer)
+    }
+
+    /// Store bytes to memory address (bulk store, ex.: syscalls moving kilobytes of data).
+    /// By default, stores words (bytes where a word isn't accessible), override it for faster copies.
+    ///
+    /// Arguments:
+    /// - `address`: The memory address to store (only RAM).
+    /// - `data`: Bytes to store.
+    ///
+    /// Returns:
+    /// - `Ok(())`: Bytes were stored successfully.
+    /// - `Err(EmbiveError)`: An error occurred. Ex.: Memory range is out of bounds
+    ///   (bytes may be partially stored, unless the implementation checks the range first).
+    fn store_bytes(&mut self, address: u32, data: &[u8]) -> Result<(), EmbiveError> {
+        helpers::store_words(self, address, data)
+    }
+
+    /// Export the RAM contents, used to snapshot the guest (check [`crate::engine::Engine::snapshot`]).
+    /// Not supported by default.
+    ///
+    /// Returns:
+    /// - `Some(&[u8])`: RAM contents, starting at [`RAM_OFFSET`].
+    /// - `None`: Not supported.
+    fn export_ram(&self) -> Option<&[u8]> {
+        None
+    }
 }
 
 /// A simple memory implementation using slices.
 /// This memory implementation is used to create a memory space from code and RAM slices.
+/// The code is read-only, stores into it fail (check [`UnifiedSliceMemory`] for a writable code region).
 #[derive(Debug)]
 pub struct SliceMemory<'a> {
     /// RISC-V bytecode.
@@ -93,6 +175,200 @@ impl Memory for SliceMemory<'_> {
 
         Ok(())
     }
+
+    fn load_bytes(&self, address: u32, buffer: &mut [u8]) -> Result<(), EmbiveError> {
+        let (region, offset) = if address >= RAM_OFFSET {
+            (&*self.ram, address - RAM_OFFSET)
+        } else {
+            (self.code, address)
+        };
+
+        let source = region
+            .get(offset as usize..)
+            .and_then(|region| region.get(..buffer.len()))
+            .ok_or(EmbiveError::InvalidMemoryAddress)?;
+        buffer.copy_from_slice(source);
+
+        Ok(())
+    }
+
+    fn store_bytes(&mut self, address: u32, data: &[u8]) -> Result<(), EmbiveError> {
+        let offset = address.wrapping_sub(RAM_OFFSET);
+
+        let destination = self
+            .ram
+            .get_mut(offset as usize..)
+            .and_then(|ram| ram.get_mut(..data.len()))
+            .ok_or(EmbiveError::InvalidMemoryAddress)?;
+        destination.copy_from_slice(data);
+
+        Ok(())
+    }
+
+    fn export_ram(&self) -> Option<&[u8]> {
+        Some(self.ram)
+    }
+}
+
+/// RAM layout, computed at compile time by [`ArrayMemory::layout`].
+/// From the top of RAM: stack, guard region and heap (static data is expected below the heap).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RamLayout {
+    /// Stack top address (initial stack pointer, end of RAM).
+    pub stack_top: u32,
+    /// Stack size in bytes.
+    pub stack_size: u32,
+    /// Guard region start address (right below the stack).
+    pub guard_start: u32,
+    /// Guard region size in bytes.
+    pub guard_size: u32,
+    /// Heap start address (right below the guard region).
+    pub heap_start: u32,
+    /// Heap size in bytes.
+    pub heap_size: u32,
+}
+
+/// Guest heap region (ex.: managed with the libc `brk` syscall or the guest runtime allocator).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Heap {
+    /// Heap start address (ex.: the `_end` linker symbol).
+    pub start: u32,
+    /// Heap size in bytes.
+    pub size: u32,
+}
+
+impl Heap {
+    /// Create a new heap.
+    ///
+    /// Arguments:
+    /// - `start`: Heap start address.
+    /// - `size`: Heap size in bytes.
+    pub const fn new(start: u32, size: u32) -> Self {
+        Heap { start, size }
+    }
+
+    /// Check if an address is inside the heap (the end is included, ex.: a program break).
+    pub fn contains(&self, address: u32) -> bool {
+        address >= self.start && address - self.start <= self.size
+    }
+}
+
+impl From<RamLayout> for Heap {
+    fn from(layout: RamLayout) -> Self {
+        Heap::new(layout.heap_start, layout.heap_size)
+    }
+}
+
+/// A memory implementation using fixed-size arrays.
+/// Works like [`SliceMemory`], but the code and RAM sizes are known at compile time,
+/// so the memory map and the RAM layout ([`ArrayMemory::layout`]) are checked by the compiler.
+///
+/// ```
+/// use embive::memory::ArrayMemory;
+///
+/// static CODE: [u8; 1024] = [0; 1024];
+/// let mut ram = [0; 4096];
+/// let memory = ArrayMemory::new(&CODE, &mut ram);
+///
+/// // 2 KiB stack, 64 bytes guard region and 1 KiB heap (fails to compile if it doesn't fit in RAM)
+/// let layout = ArrayMemory::<1024, 4096>::layout::<2048, 64, 1024>();
+/// assert_eq!(layout.stack_top, 0x80001000);
+/// ```
+#[derive(Debug)]
+pub struct ArrayMemory<'a, const CODE: usize, const RAM: usize> {
+    /// Inner memory (slices with known sizes).
+    inner: SliceMemory<'a>,
+}
+
+impl<'a, const CODE: usize, const RAM: usize> ArrayMemory<'a, CODE, RAM> {
+    /// Create a new memory space.
+    /// Fails to compile if the code or RAM don't fit in their address regions.
+    ///
+    /// Arguments:
+    /// - `code`: Code buffer, `u8` array.
+    /// - `ram`: RAM buffer, mutable `u8` array.
+    pub fn new(code: &'a [u8; CODE], ram: &'a mut [u8; RAM]) -> Self {
+        const {
+            assert!(
+                CODE <= RAM_OFFSET as usize,
+                "Code doesn't fit in the code region"
+            );
+            assert!(
+                RAM <= (u32::MAX - RAM_OFFSET) as usize + 1,
+                "RAM doesn't fit in the RAM region"
+            );
+        }
+
+        ArrayMemory {
+            inner: SliceMemory::new(code, ram),
+        }
+    }
+
+    /// Compute the RAM layout (stack at the top of RAM, followed by the guard region and heap).
+    /// Fails to compile if they don't fit in RAM.
+    ///
+    /// Generic Arguments:
+    /// - `STACK`: Stack size in bytes.
+    /// - `GUARD`: Guard region size in bytes (between heap and stack).
+    /// - `HEAP`: Heap size in bytes.
+    ///
+    /// Returns:
+    /// - `RamLayout`: The RAM layout.
+    pub const fn layout<const STACK: usize, const GUARD: usize, const HEAP: usize>() -> RamLayout {
+        const {
+            assert!(
+                STACK + GUARD + HEAP <= RAM,
+                "Stack, guard region and heap don't fit in RAM"
+            );
+            assert!(
+                RAM <= (u32::MAX - RAM_OFFSET) as usize + 1,
+                "RAM doesn't fit in the RAM region"
+            );
+        }
+
+        let stack_top = RAM_OFFSET.wrapping_add(RAM as u32);
+        let guard_start = stack_top.wrapping_sub(STACK as u32 + GUARD as u32);
+        RamLayout {
+            stack_top,
+            stack_size: STACK as u32,
+            guard_start,
+            guard_size: GUARD as u32,
+            heap_start: guard_start.wrapping_sub(HEAP as u32),
+            heap_size: HEAP as u32,
+        }
+    }
+}
+
+impl<const CODE: usize, const RAM: usize> Memory for ArrayMemory<'_, CODE, RAM> {
+    #[inline(always)]
+    fn load<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+        self.inner.load(address)
+    }
+
+    #[inline(always)]
+    fn fetch<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+        self.inner.fetch(address)
+    }
+
+    #[inline(always)]
+    fn store<const N: usize>(&mut self, address: u32, data: [u8; N]) -> Result<(), EmbiveError> {
+        self.inner.store(address, data)
+    }
+
+    #[inline(always)]
+    fn load_bytes(&self, address: u32, buffer: &mut [u8]) -> Result<(), EmbiveError> {
+        self.inner.load_bytes(address, buffer)
+    }
+
+    #[inline(always)]
+    fn store_bytes(&mut self, address: u32, data: &[u8]) -> Result<(), EmbiveError> {
+        self.inner.store_bytes(address, data)
+    }
+
+    #[inline(always)]
+    fn export_ram(&self) -> Option<&[u8]> {
+        self.inner.export_ram()
+    }
 }
 
 #[cfg(test)]
@@ -158,4 +434,104 @@ mod tests {
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), EmbiveError::InvalidMemoryAddress);
     }
+
+    #[test]
+    pub fn array_memory() {
+        let code = [0x1, 0x2, 0x3, 0x4];
+        let mut ram = [0; 8];
+        let mut memory = ArrayMemory::new(&code, &mut ram);
+
+        assert_eq!(memory.load::<4>(0x0), Ok([0x1, 0x2, 0x3, 0x4]));
+        assert_eq!(memory.store(RAM_OFFSET + 4, [0x5; 4]), Ok(()));
+        assert_eq!(memory.load::<4>(RAM_OFFSET + 4), Ok([0x5; 4]));
+        assert_eq!(
+            memory.load::<4>(RAM_OFFSET + 8),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+    }
+
+    #[test]
+    pub fn array_memory_layout() {
+        let layout = ArrayMemory::<0, 1024>::layout::<256, 16, 512>();
+
+        assert_eq!(
+            layout,
+            RamLayout {
+                stack_top: RAM_OFFSET + 1024,
+                stack_size: 256,
+                guard_start: RAM_OFFSET + 1024 - 256 - 16,
+                guard_size: 16,
+                heap_start: RAM_OFFSET + 1024 - 256 - 16 - 512,
+                heap_size: 512,
+            }
+        );
+    }
+
+    #[test]
+    pub fn slice_memory_bytes() {
+        let code = [0x1, 0x2, 0x3, 0x4, 0x5];
+        let mut ram = [0; 6];
+        let mut memory = SliceMemory::new(&code, &mut ram);
+
+        let mut buffer = [0; 3];
+        assert_eq!(memory.load_bytes(0x2, &mut buffer), Ok(()));
+        assert_eq!(buffer, [0x3, 0x4, 0x5]);
+        assert_eq!(
+            memory.load_bytes(0x3, &mut buffer),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+
+        assert_eq!(memory.store_bytes(RAM_OFFSET + 3, &[0x7; 3]), Ok(()));
+        assert_eq!(
+            memory.store_bytes(RAM_OFFSET + 4, &[0x8; 3]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            memory.store_bytes(0x0, &[0x8]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(memory.load_bytes(RAM_OFFSET + 2, &mut buffer), Ok(()));
+        assert_eq!(buffer, [0x0, 0x7, 0x7]);
+    }
+
+    /// Memory with the default bulk accesses.
+    struct DefaultMemory<'a>(SliceMemory<'a>);
+
+    impl Memory for DefaultMemory<'_> {
+        fn load<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+            self.0.load(address)
+        }
+
+        fn store<const N: usize>(
+            &mut self,
+            address: u32,
+            data: [u8; N],
+        ) -> Result<(), EmbiveError> {
+            self.0.store(address, data)
+        }
+    }
+
+    #[test]
+    pub fn default_memory_bytes() {
+        let mut ram = [0; 7];
+        let mut memory = DefaultMemory(SliceMemory::new(&[], &mut ram));
+
+        // Words, then bytes at the end of the RAM
+        assert_eq!(
+            memory.store_bytes(RAM_OFFSET + 1, &[1, 2, 3, 4, 5, 6]),
+            Ok(())
+        );
+        let mut buffer = [0; 6];
+        assert_eq!(memory.load_bytes(RAM_OFFSET + 1, &mut buffer), Ok(()));
+        assert_eq!(buffer, [1, 2, 3, 4, 5, 6]);
+
+        assert_eq!(
+            memory.load_bytes(RAM_OFFSET + 2, &mut buffer),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            memory.load_bytes(u32::MAX, &mut buffer[..2]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+    }
 }
@@ -0,0 +1,637 @@
+//! Memory-Mapped Framebuffer Module
+//!
+//! A linear framebuffer mapped into the guest address space, so UI guests render with plain stores into a
+//! host-displayed surface. Pixel stores are tracked as a dirty rectangle, and writing the flush doorbell
+//! (vsync) calls the host flush function ([`FlushFn`]) with it (ex.: to copy it to a display).
+//!
+//! Register map (offsets from the framebuffer base address, 32-bit accesses only):
+//! - [`WIDTH_OFFSET`]: Width in pixels (read-only).
+//! - [`HEIGHT_OFFSET`]: Height in pixels (read-only).
+//! - [`FORMAT_OFFSET`]: Pixel format (read-only, check [`PixelFormat`]).
+//! - [`STRIDE_OFFSET`]: Bytes per row (read-only).
+//! - [`FLUSH_OFFSET`]: Flush doorbell (write any value) / Frames flushed (read).
+//!
+//! Pixels start at [`PIXELS_OFFSET`], row by row (little-endian, any access width).
+//!
+//! ```
+//! use core::sync::atomic::{AtomicU32, Ordering};
+//! use embive::{
+//!     engine::{Config, Engine},
+//!     error::EmbiveError,
+//!     memory::{
+//!         framebuffer::{Framebuffer, PixelFormat, Rect, FRAMEBUFFER_BASE},
+//!         FramebufferMemory, SliceMemory,
+//!     },
+//! };
+//!
+//! static FLUSHES: AtomicU32 = AtomicU32::new(0);
+//!
+//! fn flush(framebuffer: &Framebuffer, dirty: Rect) -> Result<(), EmbiveError> {
+//!     assert_eq!(dirty, Rect { x: 3, y: 0, width: 1, height: 1 });
+//!     assert_eq!(framebuffer.pixels()[6..8], [0xff, 0xff]); // ex.: copy to the display
+//!     FLUSHES.fetch_add(1, Ordering::Relaxed);
+//!     Ok(())
+//! }
+//!
+//! let code = &[
+//!     0x37, 0x05, 0x00, 0x50, // lui  a0, 0x50000 (registers)
+//!     0xb7, 0x15, 0x00, 0x50, // lui  a1, 0x50001 (pixels)
+//!     0x93, 0x02, 0xf0, 0xff, // li   t0, -1
+//!     0x23, 0x93, 0x55, 0x00, // sh   t0, 6(a1)   (pixel 3, 0)
+//!     0x23, 0x28, 0x05, 0x00, // sw   zero, 16(a0) (flush)
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//! ];
+//! let mut pixels = [0; 4 * 2 * 2];
+//! let framebuffer = Framebuffer::new(&mut pixels, 4, 2, PixelFormat::Rgb565).unwrap();
+//! let mut memory = FramebufferMemory::new(SliceMemory::new(code, &mut []), FRAMEBUFFER_BASE, framebuffer)
+//!     .unwrap()
+//!     .with_flush_fn(Some(flush));
+//! let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+//!
+//! assert_eq!(engine.run(), Ok(false));
+//! assert_eq!(FLUSHES.load(Ordering::Relaxed), 1);
+//! assert_eq!(engine.memory.frames(), 1);
+//! ```
+
+use super::Memory;
+use crate::error::EmbiveError;
+
+/// Default framebuffer base address (unused code region space).
+pub const FRAMEBUFFER_BASE: u32 = 0x5000_0000;
+
+/// Width register offset.
+pub const WIDTH_OFFSET: u32 = 0x00;
+/// Height register offset.
+pub const HEIGHT_OFFSET: u32 = 0x04;
+/// Pixel format register offset.
+pub const FORMAT_OFFSET: u32 = 0x08;
+/// Stride register offset.
+pub const STRIDE_OFFSET: u32 = 0x0C;
+/// Flush doorbell register offset.
+pub const FLUSH_OFFSET: u32 = 0x10;
+/// Pixels offset.
+pub const PIXELS_OFFSET: u32 = 0x1000;
+
+/// Framebuffer Pixel Format (value of the format register)
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[repr(u32)]
+pub enum PixelFormat {
+    /// 8-bit grayscale.
+    Gray8 = 0,
+    /// 16-bit RGB (5 bits red, 6 bits green, 5 bits blue, red in the upper bits).
+    Rgb565 = 1,
+    /// 24-bit RGB (bytes: blue, green, red).
+    Rgb888 = 2,
+    /// 32-bit RGB (bytes: blue, green, red, unused).
+    Xrgb8888 = 3,
+}
+
+impl PixelFormat {
+    /// Get the size of a pixel in bytes.
+    pub const fn bytes_per_pixel(&self) -> u32 {
+        match self {
+            PixelFormat::Gray8 => 1,
+            PixelFormat::Rgb565 => 2,
+            PixelFormat::Rgb888 => 3,
+            PixelFormat::Xrgb8888 => 4,
+        }
+    }
+}
+
+/// Framebuffer rectangle, in pixels.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Rect {
+    /// Left column.
+    pub x: u32,
+    /// Top row.
+    pub y: u32,
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+}
+
+impl Rect {
+    /// Get the smallest rectangle containing both rectangles.
+    fn union(&self, other: &Rect) -> Rect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Rect {
+            x,
+            y,
+            width: (self.x + self.width).max(other.x + other.width) - x,
+            height: (self.y + self.height).max(other.y + other.height) - y,
+        }
+    }
+}
+
+/// Framebuffer flush function signature
+///
+/// Called when the guest writes the flush doorbell, if any pixel was stored since the last flush.
+///
+/// Arguments:
+/// - `framebuffer`: Framebuffer.
+/// - `dirty`: Rectangle containing every pixel stored since the last flush.
+///
+/// Returns:
+/// - `Ok(())`: Flushed.
+/// - `Err(EmbiveError)`: Failed to flush, returned by the doorbell store (the dirty rectangle is kept).
+pub type FlushFn = fn(framebuffer: &Framebuffer, dirty: Rect) -> Result<(), EmbiveError>;
+
+/// Framebuffer surface (pixels and their layout).
+#[derive(Debug)]
+pub struct Framebuffer<'a> {
+    /// Pixels, row by row (`stride * height` bytes).
+    pixels: &'a mut [u8],
+    /// Width in pixels.
+    width: u32,
+    /// Height in pixels.
+    height: u32,
+    /// Pixel format.
+    format: PixelFormat,
+}
+
+impl<'a> Framebuffer<'a> {
+    /// Create a new framebuffer.
+    ///
+    /// Arguments:
+    /// - `pixels`: Pixel buffer, at least `width * height` pixels (extra bytes aren't mapped).
+    /// - `width`: Width in pixels.
+    /// - `height`: Height in pixels.
+    /// - `format`: Pixel format.
+    ///
+    /// Returns:
+    /// - `Ok(Framebuffer)`: Framebuffer.
+    /// - `Err(EmbiveError)`: Empty framebuffer, or the pixel buffer is too small ([`EmbiveError::InvalidFramebuffer`]).
+    pub fn new(
+        pixels: &'a mut [u8],
+        width: u32,
+        height: u32,
+        format: PixelFormat,
+    ) -> Result<Self, EmbiveError> {
+        let size = width
+            .checked_mul(format.bytes_per_pixel())
+            .and_then(|stride| stride.checked_mul(height))
+            .filter(|size| *size != 0 && *size as usize <= pixels.len())
+            .ok_or(EmbiveError::InvalidFramebuffer)?;
+
+        Ok(Framebuffer {
+            pixels: &mut pixels[..size as usize],
+            width,
+            height,
+            format,
+        })
+    }
+
+    /// Width in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Pixel format.
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    /// Bytes per row.
+    pub fn stride(&self) -> u32 {
+        self.width * self.format.bytes_per_pixel()
+    }
+
+    /// Pixels, row by row.
+    pub fn pixels(&self) -> &[u8] {
+        self.pixels
+    }
+
+    /// Pixels, row by row (mutable, host drawing isn't tracked as dirty).
+    pub fn pixels_mut(&mut self) -> &mut [u8] {
+        self.pixels
+    }
+
+    /// Get the rectangle containing a pixel store.
+    ///
+    /// Arguments:
+    /// - `offset`: Store offset, inside the pixels.
+    /// - `len`: Store width in bytes (not crossing the end of the pixels).
+    fn stored(&self, offset: u32, len: u32) -> Rect {
+        let stride = self.stride();
+        let bytes_per_pixel = self.format.bytes_per_pixel();
+        let last = offset + (len - 1);
+
+        if offset / stride != last / stride {
+            // Crossing rows, full width
+            return Rect {
+                x: 0,
+                y: offset / stride,
+                width: self.width,
+                height: last / stride - offset / stride + 1,
+            };
+        }
+
+        let x = (offset % stride) / bytes_per_pixel;
+        Rect {
+            x,
+            y: offset / stride,
+            width: (last % stride) / bytes_per_pixel - x + 1,
+            height: 1,
+        }
+    }
+}
+
+/// Memory wrapper mapping a [`Framebuffer`] into the guest address space (check the [module documentation](self)).
+/// Accesses outside of the framebuffer registers and pixels are forwarded to the inner memory.
+#[derive(Debug)]
+pub struct FramebufferMemory<'a, M: Memory> {
+    /// Inner memory.
+    pub memory: M,
+    /// Framebuffer.
+    pub framebuffer: Framebuffer<'a>,
+    /// Framebuffer base address.
+    base: u32,
+    /// Host flush function (None = Flushes only count frames).
+    flush_fn: Option<FlushFn>,
+    /// Pixels stored since the last flush (None = Clean).
+    dirty: Option<Rect>,
+    /// Frames flushed.
+    frames: u32,
+}
+
+impl<'a, M: Memory> FramebufferMemory<'a, M> {
+    /// Create a new memory with a framebuffer mapped at `base`.
+    ///
+    /// Arguments:
+    /// - `memory`: Inner memory.
+    /// - `base`: Framebuffer base address (ex.: [`FRAMEBUFFER_BASE`]), pixels start at `base + PIXELS_OFFSET`.
+    /// - `framebuffer`: Framebuffer.
+    ///
+    /// Returns:
+    /// - `Ok(FramebufferMemory)`: Memory.
+    /// - `Err(EmbiveError)`: The pixels wrap around the address space ([`EmbiveError::InvalidFramebuffer`]).
+    pub fn new(memory: M, base: u32, framebuffer: Framebuffer<'a>) -> Result<Self, EmbiveError> {
+        base.checked_add(PIXELS_OFFSET)
+            .and_then(|pixels| pixels.checked_add(framebuffer.pixels.len() as u32 - 1))
+            .ok_or(EmbiveError::InvalidFramebuffer)?;
+
+        Ok(Self {
+            memory,
+            framebuffer,
+            base,
+            flush_fn: None,
+            dirty: None,
+            frames: 0,
+        })
+    }
+
+    /// Set the host flush function.
+    ///
+    /// Arguments:
+    /// - `flush_fn`: Flush function (None = Flushes only count frames).
+    pub fn with_flush_fn(mut self, flush_fn: Option<FlushFn>) -> Self {
+        self.flush_fn = flush_fn;
+        self
+    }
+
+    /// Get the rectangle of the pixels stored since the last flush.
+    ///
+    /// Returns:
+    /// - `Some(Rect)`: Dirty rectangle.
+    /// - `None`: No pixels were stored.
+    pub fn dirty(&self) -> Option<Rect> {
+        self.dirty
+    }
+
+    /// Get the number of frames flushed (doorbell writes).
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    /// Flush the framebuffer, as if the guest wrote the doorbell (ex.: on teardown).
+    ///
+    /// Returns:
+    /// - `Ok(())`: Flushed (the flush function is only called if there are dirty pixels).
+    /// - `Err(EmbiveError)`: The flush function failed.
+    pub fn flush(&mut self) -> Result<(), EmbiveError> {
+        if let (Some(dirty), Some(flush_fn)) = (self.dirty, self.flush_fn) {
+            flush_fn(&self.framebuffer, dirty)?;
+        }
+
+        self.dirty = None;
+        self.frames = self.frames.wrapping_add(1);
+        Ok(())
+    }
+
+    /// Get the framebuffer register offset of an address, if it is inside the register block.
+    #[inline(always)]
+    fn register(&self, address: u32) -> Option<u32> {
+        let offset = address.wrapping_sub(self.base);
+        (offset < PIXELS_OFFSET).then_some(offset)
+    }
+
+    /// Get the pixel offset of an access, if it is inside the pixels.
+    ///
+    /// Returns:
+    /// - `Ok(Some(u32))`: Offset inside the pixels.
+    /// - `Ok(None)`: Outside the pixels.
+    /// - `Err(EmbiveError)`: The access crosses the end of the pixels.
+    #[inline(always)]
+    fn pixel(&self, address: u32, len: usize) -> Result<Option<u32>, EmbiveError> {
+        let offset = address.wrapping_sub(self.base).wrapping_sub(PIXELS_OFFSET);
+        let size = self.framebuffer.pixels.len() as u32;
+        if offset >= size {
+            return Ok(None);
+        }
+
+        if size - offset < len as u32 {
+            return Err(EmbiveError::InvalidMemoryAddress);
+        }
+
+        Ok(Some(offset))
+    }
+
+    /// Read a framebuffer register.
+    fn read(&self, offset: u32) -> Result<u32, EmbiveError> {
+        match offset {
+            WIDTH_OFFSET => Ok(self.framebuffer.width),
+            HEIGHT_OFFSET => Ok(self.framebuffer.height),
+            FORMAT_OFFSET => Ok(self.framebuffer.format as u32),
+            STRIDE_OFFSET => Ok(self.framebuffer.stride()),
+            FLUSH_OFFSET => Ok(self.frames),
+            _ => Err(EmbiveError::InvalidMemoryAddress),
+        }
+    }
+}
+
+impl<M: Memory> Memory for FramebufferMemory<'_, M> {
+    fn load<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+        if let Some(offset) = self.register(address) {
+            if N != 4 {
+                // Only 32-bit accesses
+                return Err(EmbiveError::InvalidMemoryAddress);
+            }
+
+            let value = self.read(offset)?.to_le_bytes();
+            return value
+                .first_chunk::<N>()
+                .copied()
+                .ok_or(EmbiveError::InvalidMemoryAddress);
+        }
+
+        match self.pixel(address, N)? {
+            // Unwrap is safe because the access doesn't cross the end of the pixels.
+            Some(offset) => Ok(*self.framebuffer.pixels[offset as usize..]
+                .first_chunk::<N>()
+                .unwrap()),
+            None => self.memory.load(address),
+        }
+    }
+
+    fn store<const N: usize>(&mut self, address: u32, data: [u8; N]) -> Result<(), EmbiveError> {
+        if let Some(offset) = self.register(address) {
+            if N != 4 || offset != FLUSH_OFFSET {
+                // Only 32-bit accesses, other registers are read-only
+                return Err(EmbiveError::InvalidMemoryAddress);
+            }
+
+            return self.flush();
+        }
+
+        let Some(offset) = self.pixel(address, N)? else {
+            return self.memory.store(address, data);
+        };
+
+        // Unwrap is safe because the access doesn't cross the end of the pixels.
+        *self.framebuffer.pixels[offset as usize..]
+            .first_chunk_mut::<N>()
+            .unwrap() = data;
+
+        let stored = self.framebuffer.stored(offset, N as u32);
+        self.dirty = Some(match self.dirty {
+            Some(dirty) => dirty.union(&stored),
+            None => stored,
+        });
+
+        Ok(())
+    }
+
+    // Framebuffer isn't part of the RAM
+    fn export_ram(&self) -> Option<&[u8]> {
+        self.memory.export_ram()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Config, Engine};
+    use crate::memory::{SliceMemory, RAM_OFFSET};
+    use core::cell::Cell;
+    use std::thread_local;
+
+    const PIXELS: u32 = FRAMEBUFFER_BASE + PIXELS_OFFSET;
+
+    thread_local! {
+        static FLUSHED: Cell<Option<Rect>> = const { Cell::new(None) };
+    }
+
+    fn flush(framebuffer: &Framebuffer, dirty: Rect) -> Result<(), EmbiveError> {
+        assert_eq!(framebuffer.width(), 4);
+        FLUSHED.with(|flushed| flushed.set(Some(dirty)));
+        Ok(())
+    }
+
+    fn rect(x: u32, y: u32, width: u32, height: u32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn test_new() {
+        let mut pixels = [0; 24];
+        assert_eq!(
+            Framebuffer::new(&mut pixels, 4, 3, PixelFormat::Rgb565)
+                .unwrap()
+                .pixels()
+                .len(),
+            24
+        );
+        assert_eq!(
+            Framebuffer::new(&mut pixels, 4, 3, PixelFormat::Rgb888).unwrap_err(),
+            EmbiveError::InvalidFramebuffer
+        );
+        assert_eq!(
+            Framebuffer::new(&mut pixels, 0, 3, PixelFormat::Gray8).unwrap_err(),
+            EmbiveError::InvalidFramebuffer
+        );
+        assert_eq!(
+            Framebuffer::new(&mut pixels, u32::MAX, 2, PixelFormat::Gray8).unwrap_err(),
+            EmbiveError::InvalidFramebuffer
+        );
+
+        // Pixels wrapping around the address space
+        let framebuffer = Framebuffer::new(&mut pixels, 4, 3, PixelFormat::Gray8).unwrap();
+        assert_eq!(
+            FramebufferMemory::new(
+                SliceMemory::new(&[], &mut []),
+                u32::MAX - 0x1000,
+                framebuffer
+            )
+            .unwrap_err(),
+            EmbiveError::InvalidFramebuffer
+        );
+    }
+
+    #[test]
+    fn test_registers() {
+        let mut pixels = [0; 36];
+        let framebuffer = Framebuffer::new(&mut pixels, 4, 3, PixelFormat::Rgb888).unwrap();
+        let mut memory = FramebufferMemory::new(
+            SliceMemory::new(&[], &mut []),
+            FRAMEBUFFER_BASE,
+            framebuffer,
+        )
+        .unwrap();
+
+        let read = |memory: &FramebufferMemory<_>, offset| {
+            memory
+                .load(FRAMEBUFFER_BASE + offset)
+                .map(u32::from_le_bytes)
+        };
+        assert_eq!(read(&memory, WIDTH_OFFSET), Ok(4));
+        assert_eq!(read(&memory, HEIGHT_OFFSET), Ok(3));
+        assert_eq!(read(&memory, FORMAT_OFFSET), Ok(PixelFormat::Rgb888 as u32));
+        assert_eq!(read(&memory, STRIDE_OFFSET), Ok(12));
+        assert_eq!(read(&memory, FLUSH_OFFSET), Ok(0));
+        assert_eq!(read(&memory, 0x14), Err(EmbiveError::InvalidMemoryAddress));
+
+        // Flushing without a flush function
+        assert_eq!(
+            memory.store(FRAMEBUFFER_BASE + FLUSH_OFFSET, [0; 4]),
+            Ok(())
+        );
+        assert_eq!(read(&memory, FLUSH_OFFSET), Ok(1));
+
+        // Read-only registers, 32-bit accesses only
+        assert_eq!(
+            memory.store(FRAMEBUFFER_BASE + WIDTH_OFFSET, [0; 4]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            memory.store(FRAMEBUFFER_BASE + FLUSH_OFFSET, [0; 2]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            memory.load::<1>(FRAMEBUFFER_BASE),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+    }
+
+    #[test]
+    fn test_dirty() {
+        let mut pixels = [0; 36];
+        let mut ram = [0; 4];
+        let framebuffer = Framebuffer::new(&mut pixels, 4, 3, PixelFormat::Rgb888).unwrap();
+        let mut memory = FramebufferMemory::new(
+            SliceMemory::new(&[], &mut ram),
+            FRAMEBUFFER_BASE,
+            framebuffer,
+        )
+        .unwrap();
+
+        // Byte of pixel (1, 0)
+        assert_eq!(memory.store(PIXELS + 4, [1]), Ok(()));
+        assert_eq!(memory.dirty(), Some(rect(1, 0, 1, 1)));
+
+        // Pixels (2, 1) and (3, 1)
+        assert_eq!(memory.store(PIXELS + 12 + 8, [2; 2]), Ok(()));
+        assert_eq!(memory.dirty(), Some(rect(1, 0, 3, 2)));
+
+        // Crossing rows 1 and 2
+        assert_eq!(memory.store(PIXELS + 12 + 10, [3; 4]), Ok(()));
+        assert_eq!(memory.dirty(), Some(rect(0, 0, 4, 3)));
+        assert_eq!(memory.load(PIXELS + 22), Ok([3; 4]));
+
+        // Crossing the end of the pixels
+        assert_eq!(
+            memory.store(PIXELS + 34, [4; 4]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+
+        // Inner memory isn't tracked
+        memory.flush().unwrap();
+        assert_eq!(memory.store(RAM_OFFSET, [5]), Ok(()));
+        assert_eq!(
+            memory.load::<1>(PIXELS + 36),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(memory.dirty(), None);
+        assert_eq!(memory.memory.load(RAM_OFFSET), Ok([5]));
+    }
+
+    #[test]
+    fn test_guest_flush() {
+        let code = &[
+            0x37, 0x05, 0x00, 0x50, // lui  a0, 0x50000  (registers)
+            0xb7, 0x15, 0x00, 0x50, // lui  a1, 0x50001  (pixels)
+            0x23, 0x28, 0x05, 0x00, // sw   zero, 16(a0) (flush, clean)
+            0x93, 0x02, 0xf0, 0xff, // li   t0, -1
+            0x23, 0x93, 0x55, 0x00, // sh   t0, 6(a1)    (pixel 3, 0)
+            0x23, 0x94, 0x55, 0x00, // sh   t0, 8(a1)    (pixel 0, 1)
+            0x23, 0x28, 0x05, 0x00, // sw   zero, 16(a0) (flush)
+            0x03, 0x26, 0x05, 0x01, // lw   a2, 16(a0)   (frames)
+            0x73, 0x00, 0x10, 0x00, // ebreak
+        ];
+        let mut pixels = [0; 16];
+        let framebuffer = Framebuffer::new(&mut pixels, 4, 2, PixelFormat::Rgb565).unwrap();
+        let mut memory = FramebufferMemory::new(
+            SliceMemory::new(code, &mut []),
+            FRAMEBUFFER_BASE,
+            framebuffer,
+        )
+        .unwrap()
+        .with_flush_fn(Some(flush));
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(12), Ok(2));
+        assert_eq!(
+            FLUSHED.with(|flushed| flushed.get()),
+            Some(rect(0, 0, 4, 2))
+        );
+        assert_eq!(engine.memory.dirty(), None);
+        assert_eq!(
+            engine.memory.framebuffer.pixels(),
+            [0, 0, 0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_flush_error() {
+        fn fail(_framebuffer: &Framebuffer, _dirty: Rect) -> Result<(), EmbiveError> {
+            Err(EmbiveError::Custom("display"))
+        }
+
+        let mut pixels = [0; 4];
+        let framebuffer = Framebuffer::new(&mut pixels, 2, 2, PixelFormat::Gray8).unwrap();
+        let mut memory = FramebufferMemory::new(
+            SliceMemory::new(&[], &mut []),
+            FRAMEBUFFER_BASE,
+            framebuffer,
+        )
+        .unwrap()
+        .with_flush_fn(Some(fail));
+
+        memory.store(PIXELS + 3, [1]).unwrap();
+        assert_eq!(memory.flush(), Err(EmbiveError::Custom("display")));
+        assert_eq!(memory.dirty(), Some(rect(1, 1, 1, 1)));
+        assert_eq!(memory.frames(), 0);
+    }
+}
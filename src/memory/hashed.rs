@@ -0,0 +1,196 @@
+//! Hashed Memory Module
+
+use super::Memory;
+use crate::engine::Engine;
+use crate::error::EmbiveError;
+
+/// Mix a 64-bit value (SplitMix64 finalizer).
+#[inline(always)]
+const fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Hash of a memory byte (address and value).
+#[inline(always)]
+const fn byte_hash(address: u32, value: u8) -> u64 {
+    mix(((address as u64) << 8) | value as u64)
+}
+
+/// A memory wrapper maintaining an incremental hash of the memory contents, for lockstep replication.
+///
+/// The hash is the XOR of a per-byte hash over every byte written since the wrapper was created
+/// (or [`HashedMemory::rebase`]), relative to the initial contents. Every store (guest or host, ex.: from
+/// syscalls) updates it in constant time, so replicas that loaded the same image can compare
+/// [`Engine::architectural_hash`] after each slice, without hashing the whole memory.
+///
+/// ```
+/// use embive::{engine::{Config, Engine}, memory::{HashedMemory, SliceMemory}};
+///
+/// let code = &[
+///     0x37, 0x05, 0x00, 0x80, // lui  a0, 0x80000 (RAM)
+///     0x93, 0x05, 0x20, 0x00, // li   a1, 2
+///     0x23, 0x00, 0xb5, 0x00, // sb   a1, 0(a0)
+///     0x73, 0x00, 0x10, 0x00, // ebreak
+/// ];
+/// let (mut ram_a, mut ram_b) = ([0; 4], [0; 4]);
+/// let mut memory_a = HashedMemory::new(SliceMemory::new(code, &mut ram_a));
+/// let mut memory_b = HashedMemory::new(SliceMemory::new(code, &mut ram_b));
+/// let mut replica_a = Engine::new(&mut memory_a, Config::default()).unwrap();
+/// let mut replica_b = Engine::new(&mut memory_b, Config::default()).unwrap();
+///
+/// assert_eq!(replica_a.run(), Ok(false));
+/// assert_eq!(replica_b.run(), Ok(false));
+/// assert_eq!(replica_a.architectural_hash(), replica_b.architectural_hash());
+/// ```
+#[derive(Debug)]
+pub struct HashedMemory<M: Memory> {
+    /// Inner memory (code + RAM).
+    inner: M,
+    /// Memory hash (relative to the initial contents).
+    hash: u64,
+}
+
+impl<M: Memory> HashedMemory<M> {
+    /// Create a new memory wrapper, with the current contents as the initial state.
+    ///
+    /// Arguments:
+    /// - `inner`: Inner memory (code + RAM).
+    pub fn new(inner: M) -> Self {
+        HashedMemory { inner, hash: 0 }
+    }
+
+    /// Memory hash, `0` if the contents match the initial state.
+    pub fn memory_hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Take the current contents as the initial state (ex.: after loading the guest image on every replica).
+    pub fn rebase(&mut self) {
+        self.hash = 0;
+    }
+
+    /// Get a reference to the inner memory.
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Get the inner memory, bypassing the hash (writes to it aren't tracked).
+    pub fn inner_mut(&mut self) -> &mut M {
+        &mut self.inner
+    }
+}
+
+impl<M: Memory> Memory for HashedMemory<M> {
+    #[inline(always)]
+    fn load<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+        self.inner.load(address)
+    }
+
+    #[inline]
+    fn store<const N: usize>(&mut self, address: u32, data: [u8; N]) -> Result<(), EmbiveError> {
+        // A failed load means a failed store (nothing written)
+        let old = self.inner.load::<N>(address)?;
+        self.inner.store(address, data)?;
+
+        for (i, (old, new)) in old.into_iter().zip(data).enumerate() {
+            if old != new {
+                let address = address.wrapping_add(i as u32);
+                self.hash ^= byte_hash(address, old) ^ byte_hash(address, new);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn export_ram(&self) -> Option<&[u8]> {
+        self.inner.export_ram()
+    }
+}
+
+impl<M: Memory> Engine<'_, HashedMemory<M>> {
+    /// Architectural state hash: program counter, registers (and vector registers, if enabled) and memory
+    /// (check [`HashedMemory`]). Equal on replicas executing the same guest from the same initial state.
+    pub fn architectural_hash(&self) -> u64 {
+        let mut hash = mix(self.program_counter as u64);
+        for register in self.registers.inner {
+            hash = mix(hash ^ register as u32 as u64);
+        }
+
+        #[cfg(feature = "v_extension")]
+        {
+            hash = mix(hash ^ ((self.vector.vl as u64) << 32 | self.vector.vtype as u64));
+            for chunk in self.vector.inner.chunks_exact(8) {
+                // Unwrap is safe because the chunk is guaranteed to have 8 elements.
+                hash = mix(hash ^ u64::from_le_bytes(chunk.try_into().unwrap()));
+            }
+        }
+
+        mix(hash ^ self.memory.hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Config;
+    use crate::memory::{SliceMemory, RAM_OFFSET};
+
+    #[test]
+    fn test_memory_hash() {
+        let mut ram = [0; 8];
+        let mut memory = HashedMemory::new(SliceMemory::new(&[], &mut ram));
+        assert_eq!(memory.memory_hash(), 0);
+
+        // Order independent, and back to zero when the contents are restored
+        memory.store(RAM_OFFSET, [1, 2]).unwrap();
+        memory.store(RAM_OFFSET + 4, [3u8; 4]).unwrap();
+        let hash = memory.memory_hash();
+        assert_ne!(hash, 0);
+
+        let mut ram_b = [0; 8];
+        let mut memory_b = HashedMemory::new(SliceMemory::new(&[], &mut ram_b));
+        memory_b.store(RAM_OFFSET + 4, [3u8; 4]).unwrap();
+        memory_b.store(RAM_OFFSET, [0, 2]).unwrap();
+        memory_b.store(RAM_OFFSET, [1]).unwrap();
+        assert_eq!(memory_b.memory_hash(), hash);
+
+        memory.store(RAM_OFFSET, [0u8; 8]).unwrap();
+        assert_eq!(memory.memory_hash(), 0);
+
+        // Failed stores don't change the hash
+        assert_eq!(
+            memory.store(RAM_OFFSET + 6, [1u8; 4]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(memory.memory_hash(), 0);
+
+        memory.store(RAM_OFFSET, [1]).unwrap();
+        memory.rebase();
+        assert_eq!(memory.memory_hash(), 0);
+    }
+
+    #[test]
+    fn test_architectural_hash() {
+        let mut ram = [0; 4];
+        let mut memory = HashedMemory::new(SliceMemory::new(&[], &mut ram));
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+        let initial = engine.architectural_hash();
+
+        engine.registers.inner[5] = 1;
+        let register = engine.architectural_hash();
+        assert_ne!(register, initial);
+
+        engine.memory.store(RAM_OFFSET, [1]).unwrap();
+        assert_ne!(engine.architectural_hash(), register);
+
+        engine.memory.store(RAM_OFFSET, [0]).unwrap();
+        engine.registers.inner[5] = 0;
+        assert_eq!(engine.architectural_hash(), initial);
+
+        engine.program_counter = 4;
+        assert_ne!(engine.architectural_hash(), initial);
+    }
+}
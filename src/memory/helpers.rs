@@ -0,0 +1,372 @@
+//! Memory Helpers Module
+//!
+//! Bounds-checked bulk transfers between host buffers and guest addresses, for syscall functions.
+//! Transfers use the memory bulk accesses ([`Memory::load_bytes`] and [`Memory::store_bytes`])
+//! and fail with [`EmbiveError::InvalidMemoryAddress`] if the range wraps around the address space
+//! or isn't accessible.
+//!
+//! ```
+//! use embive::{
+//!     engine::SYSCALL_ARGS,
+//!     memory::{helpers, SliceMemory, RAM_OFFSET},
+//!     syscall::Errno,
+//! };
+//!
+//! // Uppercase a guest string in place (a0 = address)
+//! fn syscall(_nr: i32, args: &[i32; SYSCALL_ARGS], memory: &mut SliceMemory) -> Result<i32, i32> {
+//!     let mut buffer = [0; 32];
+//!     let string = helpers::read_c_string(memory, args[0] as u32, &mut buffer)
+//!         .map_err(|_| Errno::InvalidPointer)?
+//!         .ok_or(Errno::InvalidArgument)?;
+//!
+//!     let len = string.len();
+//!     buffer[..len].make_ascii_uppercase();
+//!     helpers::write_slice(memory, args[0] as u32, &buffer[..len])
+//!         .map_err(|_| Errno::InvalidPointer)?;
+//!     Ok(len as i32)
+//! }
+//!
+//! let mut ram = *b"embive\0";
+//! let mut memory = SliceMemory::new(&[], &mut ram);
+//! let mut args = [0; SYSCALL_ARGS];
+//! args[0] = RAM_OFFSET as i32;
+//!
+//! assert_eq!(syscall(0, &args, &mut memory), Ok(6));
+//! assert_eq!(&ram, b"EMBIVE\0");
+//! ```
+
+use super::Memory;
+use crate::error::EmbiveError;
+
+/// Bytes per word access.
+const WORD_SIZE: usize = 4;
+
+/// Chunk size of [`copy_between`] (host stack buffer).
+const COPY_CHUNK: usize = 64;
+
+/// Check that a range doesn't wrap around the address space.
+///
+/// Arguments:
+/// - `address`: Range start address.
+/// - `len`: Range length in bytes.
+fn check_range(address: u32, len: usize) -> Result<(), EmbiveError> {
+    if address as u64 + len as u64 > u32::MAX as u64 + 1 {
+        return Err(EmbiveError::InvalidMemoryAddress);
+    }
+
+    Ok(())
+}
+
+/// Check that both ends of a range are writable (stores back the current values).
+///
+/// Arguments:
+/// - `memory`: System memory (code + RAM).
+/// - `address`: Range start address.
+/// - `len`: Range length in bytes.
+fn check_writable<M: Memory + ?Sized>(
+    memory: &mut M,
+    address: u32,
+    len: usize,
+) -> Result<(), EmbiveError> {
+    if len == 0 {
+        return Ok(());
+    }
+
+    for address in [address, address.wrapping_add(len as u32 - 1)] {
+        let byte = memory.load::<1>(address)?;
+        memory.store(address, byte)?;
+    }
+
+    Ok(())
+}
+
+/// Read guest memory into a host buffer ([`Memory::load_bytes`]).
+///
+/// Arguments:
+/// - `memory`: System memory (code + RAM).
+/// - `address`: Guest address.
+/// - `buffer`: Host buffer, filled completely.
+///
+/// Returns:
+/// - `Ok(())`: Bytes were read.
+/// - `Err(EmbiveError)`: Range is outside of the memory ([`EmbiveError::InvalidMemoryAddress`]).
+pub fn read_slice<M: Memory>(
+    memory: &M,
+    address: u32,
+    buffer: &mut [u8],
+) -> Result<(), EmbiveError> {
+    check_range(address, buffer.len())?;
+    memory.load_bytes(address, buffer)
+}
+
+/// Write a host buffer to guest memory ([`Memory::store_bytes`]).
+///
+/// The range is checked before writing (first and last bytes), so on contiguous memories (ex.: [`super::SliceMemory`])
+/// nothing is written on failure. Ranges crossing regions (ex.: memory-mapped I/O) can be partially written.
+///
+/// Arguments:
+/// - `memory`: System memory (code + RAM).
+/// - `address`: Guest address (RAM).
+/// - `data`: Bytes to write.
+///
+/// Returns:
+/// - `Ok(())`: Bytes were written.
+/// - `Err(EmbiveError)`: Range is outside of the RAM ([`EmbiveError::InvalidMemoryAddress`]).
+pub fn write_slice<M: Memory>(
+    memory: &mut M,
+    address: u32,
+    data: &[u8],
+) -> Result<(), EmbiveError> {
+    check_range(address, data.len())?;
+    check_writable(memory, address, data.len())?;
+    memory.store_bytes(address, data)
+}
+
+/// Load bytes with word accesses, falling back to bytes where a word isn't accessible
+/// (default [`Memory::load_bytes`]).
+pub(super) fn load_words<M: Memory + ?Sized>(
+    memory: &M,
+    address: u32,
+    buffer: &mut [u8],
+) -> Result<(), EmbiveError> {
+    check_range(address, buffer.len())?;
+
+    let mut offset = 0;
+    for chunk in buffer.chunks_mut(WORD_SIZE) {
+        let address = address.wrapping_add(offset);
+        match chunk.len() {
+            WORD_SIZE => match memory.load::<WORD_SIZE>(address) {
+                Ok(word) => chunk.copy_from_slice(&word),
+                Err(_) => load_each(memory, address, chunk)?,
+            },
+            _ => load_each(memory, address, chunk)?,
+        }
+        offset += chunk.len() as u32;
+    }
+
+    Ok(())
+}
+
+/// Load bytes one by one.
+fn load_each<M: Memory + ?Sized>(
+    memory: &M,
+    address: u32,
+    buffer: &mut [u8],
+) -> Result<(), EmbiveError> {
+    for (i, byte) in buffer.iter_mut().enumerate() {
+        [*byte] = memory.load(address.wrapping_add(i as u32))?;
+    }
+
+    Ok(())
+}
+
+/// Store bytes with word accesses, falling back to bytes where a word isn't accessible
+/// (default [`Memory::store_bytes`]).
+pub(super) fn store_words<M: Memory + ?Sized>(
+    memory: &mut M,
+    address: u32,
+    data: &[u8],
+) -> Result<(), EmbiveError> {
+    check_range(address, data.len())?;
+
+    let mut offset = 0;
+    for chunk in data.chunks(WORD_SIZE) {
+        let address = address.wrapping_add(offset);
+        match chunk.first_chunk::<WORD_SIZE>() {
+            Some(word) if memory.store(address, *word).is_ok() => {}
+            _ => store_each(memory, address, chunk)?,
+        }
+        offset += chunk.len() as u32;
+    }
+
+    Ok(())
+}
+
+/// Store bytes one by one.
+fn store_each<M: Memory + ?Sized>(
+    memory: &mut M,
+    address: u32,
+    data: &[u8],
+) -> Result<(), EmbiveError> {
+    for (i, byte) in data.iter().enumerate() {
+        memory.store(address.wrapping_add(i as u32), [*byte])?;
+    }
+
+    Ok(())
+}
+
+/// Read a NUL-terminated guest string into a host buffer.
+///
+/// Arguments:
+/// - `memory`: System memory (code + RAM).
+/// - `address`: Guest string address.
+/// - `buffer`: Host buffer (the string and its terminator must fit).
+///
+/// Returns:
+/// - `Ok(Some(&[u8]))`: String bytes, without the terminator.
+/// - `Ok(None)`: No terminator within the buffer size.
+/// - `Err(EmbiveError)`: String is outside of the memory ([`EmbiveError::InvalidMemoryAddress`]).
+pub fn read_c_string<'b, M: Memory>(
+    memory: &M,
+    address: u32,
+    buffer: &'b mut [u8],
+) -> Result<Option<&'b [u8]>, EmbiveError> {
+    for i in 0..buffer.len() {
+        let [byte] = memory.load(
+            address
+                .checked_add(i as u32)
+                .ok_or(EmbiveError::InvalidMemoryAddress)?,
+        )?;
+        if byte == 0 {
+            return Ok(Some(&buffer[..i]));
+        }
+        buffer[i] = byte;
+    }
+
+    Ok(None)
+}
+
+/// Copy bytes between two guest memories (ex.: a mailbox between engines), in chunks.
+/// Both ranges are checked before copying, as in [`write_slice`].
+///
+/// Arguments:
+/// - `source`: Source memory.
+/// - `source_address`: Source address.
+/// - `destination`: Destination memory.
+/// - `destination_address`: Destination address (RAM).
+/// - `len`: Number of bytes to copy.
+///
+/// Returns:
+/// - `Ok(())`: Bytes were copied.
+/// - `Err(EmbiveError)`: A range is outside of its memory ([`EmbiveError::InvalidMemoryAddress`]).
+pub fn copy_between<S: Memory, D: Memory>(
+    source: &S,
+    source_address: u32,
+    destination: &mut D,
+    destination_address: u32,
+    len: u32,
+) -> Result<(), EmbiveError> {
+    check_range(source_address, len as usize)?;
+    check_range(destination_address, len as usize)?;
+    if len > 0 {
+        source.load::<1>(source_address.wrapping_add(len - 1))?;
+    }
+    check_writable(destination, destination_address, len as usize)?;
+
+    let mut chunk = [0; COPY_CHUNK];
+    let mut offset = 0;
+    while offset < len {
+        let size = (len - offset).min(COPY_CHUNK as u32) as usize;
+        read_slice(
+            source,
+            source_address.wrapping_add(offset),
+            &mut chunk[..size],
+        )?;
+        write_slice(
+            destination,
+            destination_address.wrapping_add(offset),
+            &chunk[..size],
+        )?;
+        offset += size as u32;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{SliceMemory, RAM_OFFSET};
+
+    #[test]
+    fn test_read_slice() {
+        let code = [1, 2, 3, 4, 5, 6, 7];
+        let memory = SliceMemory::new(&code, &mut []);
+
+        let mut buffer = [0; 6];
+        assert_eq!(read_slice(&memory, 1, &mut buffer), Ok(()));
+        assert_eq!(buffer, [2, 3, 4, 5, 6, 7]);
+
+        // Past the end
+        assert_eq!(
+            read_slice(&memory, 2, &mut buffer),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(read_slice(&memory, 7, &mut []), Ok(()));
+
+        // Wrapping
+        assert_eq!(
+            read_slice(&memory, u32::MAX, &mut buffer[..2]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+    }
+
+    #[test]
+    fn test_write_slice() {
+        let mut ram = [0; 7];
+        let mut memory = SliceMemory::new(&[], &mut ram);
+
+        assert_eq!(
+            write_slice(&mut memory, RAM_OFFSET + 1, &[1, 2, 3, 4, 5, 6]),
+            Ok(())
+        );
+
+        // Nothing written if the range doesn't fit
+        assert_eq!(
+            write_slice(&mut memory, RAM_OFFSET + 4, &[9; 4]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            write_slice(&mut memory, 0, &[9]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+
+        assert_eq!(ram, [0, 1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_read_c_string() {
+        let code = *b"abc\0def";
+        let memory = SliceMemory::new(&code, &mut []);
+
+        let mut buffer = [0; 4];
+        assert_eq!(
+            read_c_string(&memory, 0, &mut buffer),
+            Ok(Some(&b"abc"[..]))
+        );
+        assert_eq!(read_c_string(&memory, 3, &mut buffer), Ok(Some(&b""[..])));
+
+        // No terminator within the buffer, or in memory
+        assert_eq!(read_c_string(&memory, 0, &mut buffer[..3]), Ok(None));
+        assert_eq!(
+            read_c_string(&memory, 4, &mut buffer),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+    }
+
+    #[test]
+    fn test_copy_between() {
+        let code: [u8; 100] = core::array::from_fn(|i| i as u8);
+        let source = SliceMemory::new(&code, &mut []);
+        let mut ram = [0; 100];
+        let mut destination = SliceMemory::new(&[], &mut ram);
+
+        assert_eq!(
+            copy_between(&source, 10, &mut destination, RAM_OFFSET + 5, 90),
+            Ok(())
+        );
+
+        // Nothing copied if a range doesn't fit
+        assert_eq!(
+            copy_between(&source, 0, &mut destination, RAM_OFFSET + 20, 90),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            copy_between(&source, 20, &mut destination, RAM_OFFSET, 90),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+
+        assert_eq!(ram[..5], [0; 5]);
+        assert_eq!(ram[5..95], code[10..]);
+    }
+}
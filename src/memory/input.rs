@@ -0,0 +1,443 @@
+//! Memory-Mapped Input Module
+//!
+//! An input event device mapped into the guest address space, so interactive guests receive key (buttons)
+//! and touch events from the host through a FIFO, by polling or on an interrupt (with the `interrupt` feature,
+//! check [`update`]). Touch coordinates are in pixels (ex.: of the [`super::framebuffer`]).
+//!
+//! Register map (offsets from the input base address, 32-bit accesses only):
+//! - [`STATUS_OFFSET`]: Queued events (read-only).
+//! - [`EVENT_OFFSET`]: Oldest event, kind and flags (read-only, [`EVENT_NONE`] if the FIFO is empty):
+//!     - Bits 0-7: Kind ([`EVENT_KEY`] or [`EVENT_TOUCH`]).
+//!     - Bit 8: Pressed ([`EVENT_PRESSED`], key down or touch contact).
+//!     - Bits 16-31: Key code (key events).
+//! - [`POSITION_OFFSET`]: Oldest event, touch position (read-only, bits 0-15: `x`, bits 16-31: `y`).
+//! - [`POP_OFFSET`]: Remove the oldest event from the FIFO (write any value).
+//! - [`CONTROL_OFFSET`]: Interrupt enable (bit 0).
+//!
+//! ```
+//! use embive::{
+//!     engine::{Config, Engine},
+//!     memory::{
+//!         input::{InputEvent, INPUT_BASE},
+//!         InputMemory, SliceMemory,
+//!     },
+//! };
+//!
+//! let code = &[
+//!     0x37, 0x05, 0x00, 0x51, // lui  a0, 0x51000 (input)
+//!     0x83, 0x25, 0x45, 0x00, // lw   a1, 4(a0)   (event)
+//!     0x23, 0x26, 0x05, 0x00, // sw   zero, 12(a0) (pop)
+//!     0x03, 0x26, 0x05, 0x00, // lw   a2, 0(a0)   (status)
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//! ];
+//! let mut memory = InputMemory::new(SliceMemory::new(code, &mut []), INPUT_BASE);
+//! assert!(memory.push(InputEvent::Key { code: 0x20, pressed: true }));
+//! let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+//!
+//! assert_eq!(engine.run(), Ok(false));
+//! assert_eq!(engine.registers.get(11), Ok(0x0020_0101)); // Key 0x20, pressed
+//! assert_eq!(engine.registers.get(12), Ok(0)); // FIFO is empty
+//! ```
+
+#[cfg(feature = "interrupt")]
+use crate::engine::Engine;
+use crate::error::EmbiveError;
+use crate::memory::Memory;
+
+/// Default input base address (unused code region space).
+pub const INPUT_BASE: u32 = 0x5100_0000;
+
+/// Size of the input address space.
+pub const INPUT_SIZE: u32 = 0x1000;
+
+/// Maximum number of queued events.
+pub const INPUT_QUEUE: usize = 16;
+
+/// Default engine interrupt line raised by the input device (first platform-defined line).
+#[cfg(feature = "interrupt")]
+pub const INPUT_INTERRUPT_LINE: u32 = 16;
+
+/// Status register offset.
+pub const STATUS_OFFSET: u32 = 0x00;
+/// Event register offset.
+pub const EVENT_OFFSET: u32 = 0x04;
+/// Position register offset.
+pub const POSITION_OFFSET: u32 = 0x08;
+/// Pop register offset.
+pub const POP_OFFSET: u32 = 0x0C;
+/// Control register offset.
+pub const CONTROL_OFFSET: u32 = 0x10;
+
+/// Event kind: no event (empty FIFO).
+pub const EVENT_NONE: u32 = 0;
+/// Event kind: key.
+pub const EVENT_KEY: u32 = 1;
+/// Event kind: touch.
+pub const EVENT_TOUCH: u32 = 2;
+/// Event flag: pressed (key down or touch contact).
+pub const EVENT_PRESSED: u32 = 1 << 8;
+
+/// Control bit: interrupt enable.
+const CONTROL_INTERRUPT: u32 = 0b1;
+
+/// Input Event Enum
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum InputEvent {
+    /// Key (or button) pressed or released.
+    Key {
+        /// Key code (host-defined).
+        code: u16,
+        /// Pressed (false = Released).
+        pressed: bool,
+    },
+    /// Touch contact, move or release.
+    Touch {
+        /// Column, in pixels.
+        x: u16,
+        /// Row, in pixels.
+        y: u16,
+        /// In contact (false = Released).
+        pressed: bool,
+    },
+}
+
+impl InputEvent {
+    /// Encode the event as the guest reads it.
+    ///
+    /// Returns:
+    /// - `(u32, u32)`: Event and position register values.
+    fn encode(&self) -> (u32, u32) {
+        match *self {
+            InputEvent::Key { code, pressed } => (
+                EVENT_KEY | (pressed as u32 * EVENT_PRESSED) | ((code as u32) << 16),
+                0,
+            ),
+            InputEvent::Touch { x, y, pressed } => (
+                EVENT_TOUCH | (pressed as u32 * EVENT_PRESSED),
+                x as u32 | ((y as u32) << 16),
+            ),
+        }
+    }
+}
+
+/// Memory wrapper mapping an input event device into the guest address space (check the [module documentation](self)).
+/// Accesses outside of the input address range are forwarded to the inner memory.
+#[derive(Debug)]
+pub struct InputMemory<M: Memory> {
+    /// Inner memory.
+    pub memory: M,
+    /// Input base address.
+    base: u32,
+    /// Queued events, encoded (event and position registers).
+    events: [(u32, u32); INPUT_QUEUE],
+    /// Index of the oldest event.
+    head: usize,
+    /// Number of queued events.
+    len: usize,
+    /// Control register.
+    control: u32,
+    /// Engine interrupt line.
+    #[cfg(feature = "interrupt")]
+    line: u32,
+}
+
+impl<M: Memory> InputMemory<M> {
+    /// Create a new memory with an input device mapped at `base`.
+    /// The FIFO is empty, with the interrupt disabled.
+    ///
+    /// Arguments:
+    /// - `memory`: Inner memory.
+    /// - `base`: Input base address (ex.: [`INPUT_BASE`]).
+    pub fn new(memory: M, base: u32) -> Self {
+        Self {
+            memory,
+            base,
+            events: [(EVENT_NONE, 0); INPUT_QUEUE],
+            head: 0,
+            len: 0,
+            control: 0,
+            #[cfg(feature = "interrupt")]
+            line: INPUT_INTERRUPT_LINE,
+        }
+    }
+
+    /// Set the engine interrupt line raised by the device (check [`update`]).
+    ///
+    /// Arguments:
+    /// - `line`: Interrupt line (default: [`INPUT_INTERRUPT_LINE`]).
+    #[cfg(feature = "interrupt")]
+    pub fn with_interrupt_line(mut self, line: u32) -> Self {
+        self.line = line;
+        self
+    }
+
+    /// Queue an event for the guest.
+    ///
+    /// Arguments:
+    /// - `event`: Input event.
+    ///
+    /// Returns:
+    /// - `bool`: The event was queued (false = FIFO full, the event was dropped).
+    pub fn push(&mut self, event: InputEvent) -> bool {
+        if self.len == INPUT_QUEUE {
+            return false;
+        }
+
+        self.events[(self.head + self.len) % INPUT_QUEUE] = event.encode();
+        self.len += 1;
+        true
+    }
+
+    /// Get the number of queued events.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the FIFO is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Check if the device is asserting its interrupt (enabled, with queued events).
+    pub fn asserted(&self) -> bool {
+        self.control & CONTROL_INTERRUPT != 0 && self.len != 0
+    }
+
+    /// Get the input register offset of an address, if it is inside the input range.
+    #[inline(always)]
+    fn offset(&self, address: u32) -> Option<u32> {
+        let offset = address.wrapping_sub(self.base);
+        (offset < INPUT_SIZE).then_some(offset)
+    }
+
+    /// Read an input register.
+    fn read(&self, offset: u32) -> Result<u32, EmbiveError> {
+        let (event, position) = if self.len != 0 {
+            self.events[self.head]
+        } else {
+            (EVENT_NONE, 0)
+        };
+
+        match offset {
+            STATUS_OFFSET => Ok(self.len as u32),
+            EVENT_OFFSET => Ok(event),
+            POSITION_OFFSET => Ok(position),
+            CONTROL_OFFSET => Ok(self.control),
+            _ => Err(EmbiveError::InvalidMemoryAddress),
+        }
+    }
+
+    /// Write an input register.
+    fn write(&mut self, offset: u32, value: u32) -> Result<(), EmbiveError> {
+        match offset {
+            POP_OFFSET => {
+                if self.len != 0 {
+                    self.head = (self.head + 1) % INPUT_QUEUE;
+                    self.len -= 1;
+                }
+            }
+            CONTROL_OFFSET => self.control = value & CONTROL_INTERRUPT,
+            _ => return Err(EmbiveError::InvalidMemoryAddress),
+        }
+
+        Ok(())
+    }
+}
+
+impl<M: Memory> Memory for InputMemory<M> {
+    fn load<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+        match self.offset(address) {
+            Some(offset) => {
+                if N != 4 {
+                    // Only 32-bit accesses
+                    return Err(EmbiveError::InvalidMemoryAddress);
+                }
+
+                let value = self.read(offset)?.to_le_bytes();
+                value
+                    .first_chunk::<N>()
+                    .copied()
+                    .ok_or(EmbiveError::InvalidMemoryAddress)
+            }
+            None => self.memory.load(address),
+        }
+    }
+
+    fn store<const N: usize>(&mut self, address: u32, data: [u8; N]) -> Result<(), EmbiveError> {
+        match self.offset(address) {
+            Some(offset) => {
+                let value = data
+                    .first_chunk::<4>()
+                    .filter(|_| N == 4)
+                    .ok_or(EmbiveError::InvalidMemoryAddress)?;
+                self.write(offset, u32::from_le_bytes(*value))
+            }
+            None => self.memory.store(address, data),
+        }
+    }
+
+    // Input state isn't part of the RAM
+    fn export_ram(&self) -> Option<&[u8]> {
+        self.memory.export_ram()
+    }
+}
+
+/// Raise the input interrupt line if the device is asserting it (check [`InputMemory::asserted`]).
+/// Should be called by the host after queuing events (and after the guest handler returns, if events are left).
+///
+/// Arguments:
+/// - `engine`: Engine using an [`InputMemory`].
+///
+/// Returns:
+/// - `Ok(())`: Success.
+/// - `Err(EmbiveError)`: Failed to raise the interrupt.
+#[cfg(feature = "interrupt")]
+pub fn update<M: Memory>(engine: &mut Engine<'_, InputMemory<M>>) -> Result<(), EmbiveError> {
+    if engine.memory.asserted() {
+        engine.raise_interrupt(engine.memory.line)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{SliceMemory, RAM_OFFSET};
+
+    fn read(memory: &InputMemory<SliceMemory>, offset: u32) -> Result<u32, EmbiveError> {
+        memory.load(INPUT_BASE + offset).map(u32::from_le_bytes)
+    }
+
+    fn pop(memory: &mut InputMemory<SliceMemory>) {
+        memory
+            .store(INPUT_BASE + POP_OFFSET, 0u32.to_le_bytes())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_fifo() {
+        let mut memory = InputMemory::new(SliceMemory::new(&[], &mut []), INPUT_BASE);
+        assert_eq!(read(&memory, EVENT_OFFSET), Ok(EVENT_NONE));
+
+        assert!(memory.push(InputEvent::Touch {
+            x: 320,
+            y: 240,
+            pressed: true
+        }));
+        assert!(memory.push(InputEvent::Key {
+            code: 7,
+            pressed: false
+        }));
+        assert_eq!(read(&memory, STATUS_OFFSET), Ok(2));
+
+        // Oldest first
+        assert_eq!(read(&memory, EVENT_OFFSET), Ok(EVENT_TOUCH | EVENT_PRESSED));
+        assert_eq!(read(&memory, POSITION_OFFSET), Ok(320 | (240 << 16)));
+        pop(&mut memory);
+        assert_eq!(read(&memory, EVENT_OFFSET), Ok(EVENT_KEY | (7 << 16)));
+        pop(&mut memory);
+        assert!(memory.is_empty());
+
+        // Popping an empty FIFO
+        pop(&mut memory);
+        assert_eq!(read(&memory, STATUS_OFFSET), Ok(0));
+    }
+
+    #[test]
+    fn test_full() {
+        let mut memory = InputMemory::new(SliceMemory::new(&[], &mut []), INPUT_BASE);
+        for code in 0..INPUT_QUEUE as u16 {
+            assert!(memory.push(InputEvent::Key {
+                code,
+                pressed: true
+            }));
+        }
+
+        // Dropped
+        assert!(!memory.push(InputEvent::Key {
+            code: 99,
+            pressed: true
+        }));
+        assert_eq!(memory.len(), INPUT_QUEUE);
+
+        // Wrapping around
+        pop(&mut memory);
+        assert!(memory.push(InputEvent::Key {
+            code: 99,
+            pressed: true
+        }));
+        for _ in 1..INPUT_QUEUE {
+            pop(&mut memory);
+        }
+        assert_eq!(
+            read(&memory, EVENT_OFFSET),
+            Ok(EVENT_KEY | EVENT_PRESSED | (99 << 16))
+        );
+    }
+
+    #[test]
+    fn test_invalid_access() {
+        let mut ram = [0; 4];
+        let mut memory = InputMemory::new(SliceMemory::new(&[], &mut ram), INPUT_BASE);
+        assert_eq!(
+            memory.load::<2>(INPUT_BASE),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            memory.store(INPUT_BASE + STATUS_OFFSET, 0u32.to_le_bytes()),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            memory.store(INPUT_BASE + CONTROL_OFFSET, [1]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(read(&memory, 0x14), Err(EmbiveError::InvalidMemoryAddress));
+
+        // Inner memory
+        assert_eq!(memory.store(RAM_OFFSET, [1]), Ok(()));
+        assert_eq!(memory.memory.load(RAM_OFFSET), Ok([1]));
+    }
+
+    #[cfg(feature = "interrupt")]
+    #[test]
+    fn test_interrupt() {
+        use crate::engine::Config;
+        use crate::register::Register;
+
+        fn interrupt_fn(
+            cause: u32,
+            engine: &mut Engine<InputMemory<SliceMemory>>,
+        ) -> Result<(), EmbiveError> {
+            engine.registers.inner[Register::A1 as usize] = cause as i32;
+            engine.complete_interrupt();
+            Ok(())
+        }
+
+        let code = &[
+            0x37, 0x05, 0x00, 0x51, // lui  a0, 0x51000 (input)
+            0x93, 0x05, 0x10, 0x00, // li   a1, 1
+            0x23, 0x28, 0xb5, 0x00, // sw   a1, 16(a0)  (enable interrupt)
+            0x73, 0x00, 0x10, 0x00, // ebreak
+            0x73, 0x00, 0x10, 0x00, // ebreak
+        ];
+        let mut memory =
+            InputMemory::new(SliceMemory::new(code, &mut []), INPUT_BASE).with_interrupt_line(3);
+        memory.push(InputEvent::Key {
+            code: 1,
+            pressed: true,
+        });
+
+        let config = Config::default().with_interrupt_fn(Some(interrupt_fn));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        // Disabled
+        update(&mut engine).unwrap();
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(Register::A1 as usize), Ok(1));
+
+        update(&mut engine).unwrap();
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(Register::A1 as usize), Ok(3));
+    }
+}
@@ -0,0 +1,364 @@
+//! Memory-Mapped I/O Module
+
+use super::Memory;
+use crate::error::EmbiveError;
+
+/// Host side of a memory-mapped peripheral (check [`MmioMemory`]).
+///
+/// Accesses are forwarded with their offset inside the mapped region and width (1, 2 or 4 bytes, little-endian),
+/// they never cross the end of the region. Peripheral state lives on the host (ex.: statics or device drivers).
+pub trait MmioDevice {
+    /// Read a device register.
+    ///
+    /// Arguments:
+    /// - `offset`: Offset inside the mapped region.
+    /// - `data`: Buffer to fill with the register value (access width).
+    ///
+    /// Returns:
+    /// - `Ok(())`: Register was read.
+    /// - `Err(EmbiveError)`: Access fault (ex.: [`EmbiveError::InvalidMemoryAddress`] for an unmapped register).
+    fn read(offset: u32, data: &mut [u8]) -> Result<(), EmbiveError>;
+
+    /// Write a device register (read-only device by default).
+    ///
+    /// Arguments:
+    /// - `offset`: Offset inside the mapped region.
+    /// - `data`: Written value (access width).
+    ///
+    /// Returns:
+    /// - `Ok(())`: Register was written.
+    /// - `Err(EmbiveError)`: Access fault (ex.: [`EmbiveError::InvalidMemoryAddress`] for a read-only register).
+    fn write(_offset: u32, _data: &[u8]) -> Result<(), EmbiveError> {
+        Err(EmbiveError::InvalidMemoryAddress)
+    }
+}
+
+/// A mapped peripheral region.
+#[derive(Debug, Clone, Copy)]
+struct MmioRegion {
+    /// Region start address.
+    address: u32,
+    /// Region size in bytes.
+    size: u32,
+    /// Device read function.
+    read: fn(u32, &mut [u8]) -> Result<(), EmbiveError>,
+    /// Device write function.
+    write: fn(u32, &[u8]) -> Result<(), EmbiveError>,
+}
+
+/// A memory wrapper mapping host peripherals ([`MmioDevice`]) at fixed guest addresses, so guests (ex.: firmware)
+/// access device registers with plain loads and stores instead of syscalls.
+///
+/// Up to `R` regions can be mapped, shadowing the inner memory. Outside of them, accesses are forwarded to the
+/// inner memory. Accesses crossing the end of a region are invalid ([`EmbiveError::InvalidMemoryAddress`]).
+///
+/// ```
+/// use embive::{
+///     engine::{Config, Engine},
+///     error::EmbiveError,
+///     memory::{MmioDevice, MmioMemory, SliceMemory},
+/// };
+///
+/// /// Free-running counter register.
+/// struct Timer;
+///
+/// impl MmioDevice for Timer {
+///     fn read(_offset: u32, data: &mut [u8]) -> Result<(), EmbiveError> {
+///         data.copy_from_slice(&1234u32.to_le_bytes()[..data.len()]); // ex.: host clock
+///         Ok(())
+///     }
+/// }
+///
+/// let code = &[
+///     0x37, 0x05, 0x00, 0x40, // lui  a0, 0x40000 (timer address)
+///     0x03, 0x25, 0x05, 0x00, // lw   a0, 0(a0)
+///     0x73, 0x00, 0x10, 0x00, // ebreak
+/// ];
+/// let mut memory: MmioMemory<_, 4> = MmioMemory::new(SliceMemory::new(code, &mut []));
+/// memory.map::<Timer>(0x4000_0000, 4).unwrap();
+/// let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+///
+/// assert_eq!(engine.run(), Ok(false));
+/// assert_eq!(engine.registers.get(10), Ok(1234));
+/// ```
+#[derive(Debug)]
+pub struct MmioMemory<M: Memory, const R: usize> {
+    /// Inner memory (code + RAM).
+    inner: M,
+    /// Mapped regions (None = Free slot).
+    regions: [Option<MmioRegion>; R],
+}
+
+impl<M: Memory, const R: usize> MmioMemory<M, R> {
+    /// Create a new memory wrapper, without mapped regions.
+    ///
+    /// Arguments:
+    /// - `inner`: Inner memory (code + RAM).
+    pub fn new(inner: M) -> Self {
+        MmioMemory {
+            inner,
+            regions: [None; R],
+        }
+    }
+
+    /// Map a peripheral.
+    ///
+    /// Arguments:
+    /// - `address`: Region start address (ex.: unused code region space).
+    /// - `size`: Region size in bytes.
+    ///
+    /// Generic Arguments:
+    /// - `D`: Host peripheral.
+    ///
+    /// Returns:
+    /// - `Ok(())`: The peripheral was mapped.
+    /// - `Err(EmbiveError)`: Empty region, overlapping another one or wrapping around the address space
+    ///   ([`EmbiveError::InvalidMemoryAddress`]), or all `R` regions are in use ([`EmbiveError::TooManyMmioRegions`]).
+    pub fn map<D: MmioDevice>(&mut self, address: u32, size: u32) -> Result<(), EmbiveError> {
+        let end = size
+            .checked_sub(1)
+            .and_then(|last| address.checked_add(last))
+            .ok_or(EmbiveError::InvalidMemoryAddress)?;
+
+        if self
+            .regions
+            .iter()
+            .flatten()
+            .any(|region| address <= region.address + (region.size - 1) && region.address <= end)
+        {
+            return Err(EmbiveError::InvalidMemoryAddress);
+        }
+
+        let slot = self
+            .regions
+            .iter_mut()
+            .find(|region| region.is_none())
+            .ok_or(EmbiveError::TooManyMmioRegions)?;
+
+        *slot = Some(MmioRegion {
+            address,
+            size,
+            read: D::read,
+            write: D::write,
+        });
+
+        Ok(())
+    }
+
+    /// Unmap a peripheral.
+    ///
+    /// Arguments:
+    /// - `address`: Region start address.
+    ///
+    /// Returns:
+    /// - `bool`: A region was mapped at the address (and was unmapped).
+    pub fn unmap(&mut self, address: u32) -> bool {
+        self.regions
+            .iter_mut()
+            .find(|region| region.is_some_and(|region| region.address == address))
+            .map(|region| *region = None)
+            .is_some()
+    }
+
+    /// Inner memory.
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Inner memory (mutable).
+    pub fn inner_mut(&mut self) -> &mut M {
+        &mut self.inner
+    }
+
+    /// Find the region of an access.
+    ///
+    /// Arguments:
+    /// - `address`: Access address.
+    /// - `len`: Access width in bytes.
+    ///
+    /// Returns:
+    /// - `Ok(Some((MmioRegion, u32)))`: Region and offset inside it.
+    /// - `Ok(None)`: Outside every region (inner memory access).
+    /// - `Err(EmbiveError)`: The access crosses the end of a region.
+    #[inline(always)]
+    fn region(&self, address: u32, len: usize) -> Result<Option<(MmioRegion, u32)>, EmbiveError> {
+        for region in self.regions.iter().flatten() {
+            let offset = address.wrapping_sub(region.address);
+            if offset < region.size {
+                if (region.size - offset) < len as u32 {
+                    return Err(EmbiveError::InvalidMemoryAddress);
+                }
+
+                return Ok(Some((*region, offset)));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl<M: Memory, const R: usize> Memory for MmioMemory<M, R> {
+    #[inline]
+    fn load<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+        let Some((region, offset)) = self.region(address, N)? else {
+            return self.inner.load(address);
+        };
+
+        let mut data = [0; N];
+        (region.read)(offset, &mut data)?;
+        Ok(data)
+    }
+
+    #[inline]
+    fn store<const N: usize>(&mut self, address: u32, data: [u8; N]) -> Result<(), EmbiveError> {
+        let Some((region, offset)) = self.region(address, N)? else {
+            return self.inner.store(address, data);
+        };
+
+        (region.write)(offset, &data)
+    }
+
+    // Device state isn't part of the RAM
+    #[inline(always)]
+    fn export_ram(&self) -> Option<&[u8]> {
+        self.inner.export_ram()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Config, Engine};
+    use crate::memory::{SliceMemory, RAM_OFFSET};
+    use core::cell::Cell;
+    use std::thread_local;
+
+    const UART: u32 = 0x4000_0000;
+
+    thread_local! {
+        static LAST_WRITE: Cell<Option<(u32, u32)>> = const { Cell::new(None) };
+    }
+
+    /// Status register at offset 0 (read-only), data register at offset 4.
+    struct Uart;
+
+    impl MmioDevice for Uart {
+        fn read(offset: u32, data: &mut [u8]) -> Result<(), EmbiveError> {
+            match offset {
+                0 => data.fill(0xAA),
+                4 => data.fill(0x55),
+                _ => return Err(EmbiveError::InvalidMemoryAddress),
+            }
+
+            Ok(())
+        }
+
+        fn write(offset: u32, data: &[u8]) -> Result<(), EmbiveError> {
+            if offset != 4 {
+                return Err(EmbiveError::InvalidMemoryAddress);
+            }
+
+            let mut value = [0; 4];
+            value[..data.len()].copy_from_slice(data);
+            LAST_WRITE.with(|last| last.set(Some((offset, u32::from_le_bytes(value)))));
+            Ok(())
+        }
+    }
+
+    struct Rom;
+
+    impl MmioDevice for Rom {
+        fn read(offset: u32, data: &mut [u8]) -> Result<(), EmbiveError> {
+            data.fill(offset as u8);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_map() {
+        let mut memory: MmioMemory<_, 2> = MmioMemory::new(SliceMemory::new(&[], &mut []));
+        assert_eq!(memory.map::<Uart>(UART, 8), Ok(()));
+
+        // Overlapping, empty or wrapping around
+        assert_eq!(
+            memory.map::<Rom>(UART + 7, 4),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            memory.map::<Rom>(UART - 4, 5),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            memory.map::<Rom>(UART + 8, 0),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            memory.map::<Rom>(u32::MAX, 2),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+
+        assert_eq!(memory.map::<Rom>(UART + 8, 4), Ok(()));
+        assert_eq!(
+            memory.map::<Rom>(UART + 12, 4),
+            Err(EmbiveError::TooManyMmioRegions)
+        );
+
+        assert!(memory.unmap(UART + 8));
+        assert!(!memory.unmap(UART + 8));
+        assert_eq!(memory.map::<Rom>(UART + 12, 4), Ok(()));
+    }
+
+    #[test]
+    fn test_access() {
+        let mut ram = [0; 4];
+        let mut memory: MmioMemory<_, 2> = MmioMemory::new(SliceMemory::new(&[9], &mut ram));
+        memory.map::<Uart>(UART, 8).unwrap();
+        memory.map::<Rom>(UART + 8, 4).unwrap();
+
+        assert_eq!(memory.load(UART), Ok([0xAA; 4]));
+        assert_eq!(memory.load(UART + 4), Ok([0x55]));
+        assert_eq!(memory.load(UART + 10), Ok([2, 2]));
+        assert_eq!(
+            memory.load::<2>(UART + 2),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+
+        // Crossing the end of a region
+        assert_eq!(
+            memory.load::<4>(UART + 6),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+
+        // Read-only registers and device
+        assert_eq!(
+            memory.store(UART, [1]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            memory.store(UART + 8, [1]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+
+        // Inner memory
+        assert_eq!(memory.load(0), Ok([9]));
+        assert_eq!(memory.store(RAM_OFFSET, [1, 2]), Ok(()));
+        assert_eq!(memory.inner().load(RAM_OFFSET), Ok([1, 2]));
+    }
+
+    #[test]
+    fn test_guest_access() {
+        let code = &[
+            0x37, 0x05, 0x00, 0x40, // lui  a0, 0x40000 (UART address)
+            0x83, 0x45, 0x05, 0x00, // lbu  a1, 0(a0)   (status)
+            0x23, 0x22, 0xb5, 0x00, // sw   a1, 4(a0)   (data)
+            0x73, 0x00, 0x10, 0x00, // ebreak
+        ];
+        let mut memory: MmioMemory<_, 1> = MmioMemory::new(SliceMemory::new(code, &mut []));
+        memory.map::<Uart>(UART, 8).unwrap();
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(11), Ok(0xAA));
+        assert_eq!(LAST_WRITE.with(|last| last.get()), Some((4, 0xAA)));
+    }
+}
@@ -0,0 +1,293 @@
+//! Paged Memory Module
+
+use super::{Memory, RAM_OFFSET};
+use crate::error::EmbiveError;
+
+/// A memory implementation with the RAM split in fixed-size pages (no allocation needed).
+///
+/// Page `i` is mapped at `RAM_OFFSET + i * PAGE`, from any host buffer (ex.: separate SRAM banks or external PSRAM),
+/// so the RAM can be sparse and larger than a single contiguous slice. Accesses to unmapped pages fail
+/// ([`EmbiveError::InvalidMemoryAddress`]), accesses crossing pages are split between them.
+/// The code is read-only, as in [`super::SliceMemory`].
+///
+/// Generic Arguments:
+/// - `P`: Number of page slots.
+/// - `PAGE`: Page size in bytes (power of two).
+///
+/// ```
+/// use embive::{engine::{Config, Engine}, memory::PagedMemory};
+///
+/// let code = &[
+///     0x37, 0x15, 0x00, 0x80, // lui  a0, 0x80001 (page 1)
+///     0x93, 0x05, 0xa0, 0x02, // li   a1, 42
+///     0x23, 0x2e, 0xb5, 0xfe, // sw   a1, -4(a0)  (end of page 0)
+///     0x03, 0x26, 0x05, 0x00, // lw   a2, 0(a0)   (start of page 1)
+///     0x73, 0x00, 0x10, 0x00, // ebreak
+/// ];
+///
+/// // Pages from two different banks
+/// let mut bank0 = [0; 4096];
+/// let mut bank1 = [0; 4096];
+/// bank1[0] = 7;
+///
+/// let mut memory = PagedMemory::<4, 4096>::new(code);
+/// memory.map(0, &mut bank0).unwrap();
+/// memory.map(1, &mut bank1).unwrap();
+///
+/// let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+/// assert_eq!(engine.run(), Ok(false));
+/// assert_eq!(engine.registers.get(12), Ok(7));
+/// assert_eq!(bank0[4092..], [42, 0, 0, 0]);
+/// ```
+#[derive(Debug)]
+pub struct PagedMemory<'a, const P: usize, const PAGE: usize> {
+    /// RISC-V bytecode.
+    code: &'a [u8],
+    /// RAM page slots (None = Unmapped).
+    pages: [Option<&'a mut [u8; PAGE]>; P],
+}
+
+impl<'a, const P: usize, const PAGE: usize> PagedMemory<'a, P, PAGE> {
+    /// Create a new memory space, without mapped pages.
+    /// Fails to compile if the page size isn't a power of two, or the pages don't fit in the RAM region.
+    ///
+    /// Arguments:
+    /// - `code`: Code buffer, `u8` slice.
+    pub fn new(code: &'a [u8]) -> Self {
+        const {
+            assert!(PAGE.is_power_of_two(), "Page size isn't a power of two");
+            assert!(
+                P as u64 * PAGE as u64 <= (u32::MAX - RAM_OFFSET) as u64 + 1,
+                "Pages don't fit in the RAM region"
+            );
+        }
+
+        PagedMemory {
+            code,
+            pages: [const { None }; P],
+        }
+    }
+
+    /// Map a page.
+    ///
+    /// Arguments:
+    /// - `page`: Page slot (from 0 to `P` - 1).
+    /// - `buffer`: Page buffer.
+    ///
+    /// Returns:
+    /// - `Ok(Option<&mut [u8; PAGE]>)`: Page mapped, returns the buffer previously mapped to the slot (if any).
+    /// - `Err(EmbiveError)`: Page slot is out of bounds ([`EmbiveError::InvalidMemoryAddress`]).
+    pub fn map(
+        &mut self,
+        page: usize,
+        buffer: &'a mut [u8; PAGE],
+    ) -> Result<Option<&'a mut [u8; PAGE]>, EmbiveError> {
+        let slot = self
+            .pages
+            .get_mut(page)
+            .ok_or(EmbiveError::InvalidMemoryAddress)?;
+        Ok(slot.replace(buffer))
+    }
+
+    /// Unmap a page.
+    ///
+    /// Arguments:
+    /// - `page`: Page slot (from 0 to `P` - 1).
+    ///
+    /// Returns:
+    /// - `Some(&mut [u8; PAGE])`: Buffer that was mapped to the slot.
+    /// - `None`: No page mapped (or out of bounds).
+    pub fn unmap(&mut self, page: usize) -> Option<&'a mut [u8; PAGE]> {
+        self.pages.get_mut(page)?.take()
+    }
+
+    /// Get the guest address of a page slot.
+    ///
+    /// Arguments:
+    /// - `page`: Page slot (from 0 to `P` - 1).
+    pub const fn page_address(page: usize) -> u32 {
+        RAM_OFFSET + (page * PAGE) as u32
+    }
+
+    /// Check that a RAM range is fully mapped.
+    ///
+    /// Arguments:
+    /// - `offset`: Offset from [`RAM_OFFSET`].
+    /// - `len`: Range length in bytes.
+    fn check(&self, offset: usize, len: usize) -> Result<(), EmbiveError> {
+        if len == 0 {
+            return Ok(());
+        }
+
+        let last = offset
+            .checked_add(len - 1)
+            .ok_or(EmbiveError::InvalidMemoryAddress)?;
+        for page in offset / PAGE..=last / PAGE {
+            if !matches!(self.pages.get(page), Some(Some(_))) {
+                return Err(EmbiveError::InvalidMemoryAddress);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copy bytes out of the RAM pages.
+    ///
+    /// Arguments:
+    /// - `offset`: Offset from [`RAM_OFFSET`].
+    /// - `buffer`: Buffer, filled completely.
+    #[inline]
+    fn read(&self, mut offset: usize, buffer: &mut [u8]) -> Result<(), EmbiveError> {
+        let mut done = 0;
+        while done < buffer.len() {
+            let page = self
+                .pages
+                .get(offset / PAGE)
+                .and_then(|page| page.as_deref())
+                .ok_or(EmbiveError::InvalidMemoryAddress)?;
+            let start = offset % PAGE;
+            let len = (PAGE - start).min(buffer.len() - done);
+
+            buffer[done..done + len].copy_from_slice(&page[start..start + len]);
+            done += len;
+            offset += len;
+        }
+
+        Ok(())
+    }
+
+    /// Copy bytes into the RAM pages (checked first, nothing is written on failure).
+    ///
+    /// Arguments:
+    /// - `offset`: Offset from [`RAM_OFFSET`].
+    /// - `data`: Bytes to copy.
+    #[inline]
+    fn write(&mut self, mut offset: usize, data: &[u8]) -> Result<(), EmbiveError> {
+        self.check(offset, data.len())?;
+
+        let mut done = 0;
+        while done < data.len() {
+            // Unwrap is safe because the range was checked.
+            let page = self.pages[offset / PAGE].as_deref_mut().unwrap();
+            let start = offset % PAGE;
+            let len = (PAGE - start).min(data.len() - done);
+
+            page[start..start + len].copy_from_slice(&data[done..done + len]);
+            done += len;
+            offset += len;
+        }
+
+        Ok(())
+    }
+}
+
+impl<const P: usize, const PAGE: usize> Memory for PagedMemory<'_, P, PAGE> {
+    fn load<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+        if address < RAM_OFFSET {
+            return self
+                .code
+                .get(address as usize..)
+                .and_then(|code| code.first_chunk::<N>())
+                .copied()
+                .ok_or(EmbiveError::InvalidMemoryAddress);
+        }
+
+        let mut data = [0; N];
+        self.read((address - RAM_OFFSET) as usize, &mut data)?;
+        Ok(data)
+    }
+
+    fn store<const N: usize>(&mut self, address: u32, data: [u8; N]) -> Result<(), EmbiveError> {
+        if address < RAM_OFFSET {
+            return Err(EmbiveError::InvalidMemoryAddress);
+        }
+
+        self.write((address - RAM_OFFSET) as usize, &data)
+    }
+
+    fn load_bytes(&self, address: u32, buffer: &mut [u8]) -> Result<(), EmbiveError> {
+        if address < RAM_OFFSET {
+            let code = self
+                .code
+                .get(address as usize..)
+                .and_then(|code| code.get(..buffer.len()))
+                .ok_or(EmbiveError::InvalidMemoryAddress)?;
+            buffer.copy_from_slice(code);
+            return Ok(());
+        }
+
+        self.read((address - RAM_OFFSET) as usize, buffer)
+    }
+
+    fn store_bytes(&mut self, address: u32, data: &[u8]) -> Result<(), EmbiveError> {
+        if address < RAM_OFFSET {
+            return Err(EmbiveError::InvalidMemoryAddress);
+        }
+
+        self.write((address - RAM_OFFSET) as usize, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pages() {
+        let mut page0 = [0; 16];
+        let mut page2 = [0; 16];
+        let mut extra = [0; 16];
+        let mut memory = PagedMemory::<3, 16>::new(&[0x1, 0x2]);
+
+        assert_eq!(memory.map(0, &mut page0), Ok(None));
+        assert_eq!(memory.map(2, &mut page2), Ok(None));
+        assert!(matches!(
+            memory.map(3, &mut extra),
+            Err(EmbiveError::InvalidMemoryAddress)
+        ));
+        assert_eq!(PagedMemory::<3, 16>::page_address(2), RAM_OFFSET + 32);
+
+        // Code
+        assert_eq!(memory.load::<2>(0x0), Ok([0x1, 0x2]));
+        assert_eq!(
+            memory.store(0x0, [0x3]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+
+        // Unmapped page 1
+        assert_eq!(
+            memory.store(RAM_OFFSET + 14, [0x5; 4]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            memory.load::<1>(RAM_OFFSET + 16),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(memory.store(RAM_OFFSET + 12, [0x5; 4]), Ok(()));
+        assert_eq!(memory.store(RAM_OFFSET + 32, [0x6; 2]), Ok(()));
+        assert_eq!(
+            memory.load::<1>(RAM_OFFSET + 48),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            memory.load::<1>(u32::MAX),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+
+        let page1 = &mut [0; 16];
+        page1[0] = 0x7;
+        memory.map(1, page1).unwrap();
+
+        // Crossing pages
+        assert_eq!(memory.load::<4>(RAM_OFFSET + 14), Ok([0x5, 0x5, 0x7, 0x0]));
+        assert_eq!(memory.store(RAM_OFFSET + 30, [0x8; 4]), Ok(()));
+        let mut buffer = [0; 6];
+        assert_eq!(memory.load_bytes(RAM_OFFSET + 29, &mut buffer), Ok(()));
+        assert_eq!(buffer, [0x0, 0x8, 0x8, 0x8, 0x8, 0x0]);
+
+        assert!(memory.unmap(0).is_some());
+        assert_eq!(memory.unmap(0), None);
+        assert_eq!(page0[12..], [0x5; 4]);
+        assert_eq!(page2[..3], [0x8, 0x8, 0x0]);
+    }
+}
@@ -0,0 +1,317 @@
+//! Protected Memory Module
+
+use super::Memory;
+use crate::error::{AccessKind, EmbiveError};
+
+/// Access protection of a region (check [`ProtectedMemory`]).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Protection {
+    /// Loads and instruction fetches only (ex.: a shared config block).
+    ReadOnly,
+    /// No access at all (ex.: host-owned data inside the guest RAM).
+    NoAccess,
+    /// Instruction fetches only, code can't be read as data.
+    ExecuteOnly,
+}
+
+impl Protection {
+    /// Check if an access is allowed.
+    ///
+    /// Arguments:
+    /// - `kind`: Access kind.
+    #[inline(always)]
+    const fn allows(self, kind: AccessKind) -> bool {
+        matches!(
+            (self, kind),
+            (Protection::ReadOnly, AccessKind::Read | AccessKind::Execute)
+                | (Protection::ExecuteOnly, AccessKind::Execute)
+        )
+    }
+}
+
+/// A protected region.
+#[derive(Debug, Clone, Copy)]
+struct ProtectedRegion {
+    /// Region start address.
+    address: u32,
+    /// Region last address (inclusive).
+    end: u32,
+    /// Region protection.
+    protection: Protection,
+}
+
+/// A memory wrapper restricting guest accesses to address ranges (guest-side MPU), so guest data structures
+/// can be sandboxed from the guest code itself.
+///
+/// Up to `R` regions can be protected, overlapping regions are allowed (every region touched by an access must allow it).
+/// Denied accesses fail with [`EmbiveError::AccessViolation`], outside of them, accesses are forwarded to the inner memory.
+/// Bulk accesses (ex.: syscall buffers) are checked too, the host bypasses the protection through [`ProtectedMemory::inner_mut`].
+///
+/// Instruction fetches are told from data loads through [`Memory::fetch`].
+///
+/// ```
+/// use embive::{
+///     engine::{Config, Engine},
+///     error::{AccessKind, EmbiveError},
+///     memory::{Protection, ProtectedMemory, SliceMemory, RAM_OFFSET},
+/// };
+///
+/// let code = &[
+///     0x37, 0x05, 0x00, 0x80, // lui  a0, 0x80000 (config block)
+///     0x03, 0x26, 0x05, 0x00, // lw   a2, 0(a0)
+///     0x93, 0x05, 0xa0, 0x02, // li   a1, 42
+///     0x23, 0x20, 0xb5, 0x00, // sw   a1, 0(a0)
+///     0x73, 0x00, 0x10, 0x00, // ebreak
+/// ];
+/// let mut ram = [0; 64];
+/// ram[0] = 7;
+///
+/// let mut memory: ProtectedMemory<_, 4> = ProtectedMemory::new(SliceMemory::new(code, &mut ram));
+/// memory.protect(RAM_OFFSET, 16, Protection::ReadOnly).unwrap();
+/// let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+///
+/// assert_eq!(
+///     engine.run(),
+///     Err(EmbiveError::AccessViolation { address: RAM_OFFSET, kind: AccessKind::Write })
+/// );
+/// assert_eq!(engine.registers.get(12), Ok(7));
+/// ```
+#[derive(Debug)]
+pub struct ProtectedMemory<M: Memory, const R: usize> {
+    /// Inner memory (code + RAM).
+    inner: M,
+    /// Protected regions (None = Free slot).
+    regions: [Option<ProtectedRegion>; R],
+}
+
+impl<M: Memory, const R: usize> ProtectedMemory<M, R> {
+    /// Create a new memory wrapper, without protected regions.
+    ///
+    /// Arguments:
+    /// - `inner`: Inner memory (code + RAM).
+    pub fn new(inner: M) -> Self {
+        ProtectedMemory {
+            inner,
+            regions: [None; R],
+        }
+    }
+
+    /// Protect a region.
+    ///
+    /// Arguments:
+    /// - `address`: Region start address (code or RAM).
+    /// - `size`: Region size in bytes.
+    /// - `protection`: Allowed accesses.
+    ///
+    /// Returns:
+    /// - `Ok(())`: The region is protected.
+    /// - `Err(EmbiveError)`: Empty region or wrapping around the address space ([`EmbiveError::InvalidMemoryAddress`]),
+    ///   or all `R` regions are in use ([`EmbiveError::TooManyProtectionRegions`]).
+    pub fn protect(
+        &mut self,
+        address: u32,
+        size: u32,
+        protection: Protection,
+    ) -> Result<(), EmbiveError> {
+        let end = size
+            .checked_sub(1)
+            .and_then(|last| address.checked_add(last))
+            .ok_or(EmbiveError::InvalidMemoryAddress)?;
+
+        let slot = self
+            .regions
+            .iter_mut()
+            .find(|region| region.is_none())
+            .ok_or(EmbiveError::TooManyProtectionRegions)?;
+
+        *slot = Some(ProtectedRegion {
+            address,
+            end,
+            protection,
+        });
+
+        Ok(())
+    }
+
+    /// Remove the protection of a region.
+    ///
+    /// Arguments:
+    /// - `address`: Region start address.
+    ///
+    /// Returns:
+    /// - `bool`: A region was protected at the address (and was removed).
+    pub fn unprotect(&mut self, address: u32) -> bool {
+        self.regions
+            .iter_mut()
+            .find(|region| region.is_some_and(|region| region.address == address))
+            .map(|region| *region = None)
+            .is_some()
+    }
+
+    /// Inner memory.
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Inner memory (mutable, unprotected).
+    pub fn inner_mut(&mut self) -> &mut M {
+        &mut self.inner
+    }
+
+    /// Check an access against the protected regions.
+    ///
+    /// Arguments:
+    /// - `address`: Access address.
+    /// - `len`: Access width in bytes.
+    /// - `kind`: Access kind.
+    ///
+    /// Returns:
+    /// - `Ok(())`: The access is allowed.
+    /// - `Err(EmbiveError)`: A region touched by the access denies it ([`EmbiveError::AccessViolation`]).
+    #[inline(always)]
+    fn check(&self, address: u32, len: usize, kind: AccessKind) -> Result<(), EmbiveError> {
+        if len == 0 {
+            return Ok(());
+        }
+
+        // Accesses wrapping around the address space are clamped (they fail in the inner memory)
+        let end = address.saturating_add((len - 1) as u32);
+        for region in self.regions.iter().flatten() {
+            if address <= region.end && region.address <= end && !region.protection.allows(kind) {
+                return Err(EmbiveError::AccessViolation { address, kind });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<M: Memory, const R: usize> Memory for ProtectedMemory<M, R> {
+    #[inline]
+    fn load<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+        self.check(address, N, AccessKind::Read)?;
+        self.inner.load(address)
+    }
+
+    #[inline]
+    fn fetch<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+        self.check(address, N, AccessKind::Execute)?;
+        self.inner.fetch(address)
+    }
+
+    #[inline]
+    fn store<const N: usize>(&mut self, address: u32, data: [u8; N]) -> Result<(), EmbiveError> {
+        self.check(address, N, AccessKind::Write)?;
+        self.inner.store(address, data)
+    }
+
+    #[inline]
+    fn load_bytes(&self, address: u32, buffer: &mut [u8]) -> Result<(), EmbiveError> {
+        self.check(address, buffer.len(), AccessKind::Read)?;
+        self.inner.load_bytes(address, buffer)
+    }
+
+    #[inline]
+    fn store_bytes(&mut self, address: u32, data: &[u8]) -> Result<(), EmbiveError> {
+        self.check(address, data.len(), AccessKind::Write)?;
+        self.inner.store_bytes(address, data)
+    }
+
+    // Snapshots are taken by the host
+    #[inline(always)]
+    fn export_ram(&self) -> Option<&[u8]> {
+        self.inner.export_ram()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Config, Engine};
+    use crate::memory::{SliceMemory, RAM_OFFSET};
+
+    #[test]
+    fn test_protection() {
+        let code = &[0x1, 0x2, 0x3, 0x4];
+        let mut ram = [0; 16];
+        let mut memory: ProtectedMemory<_, 2> =
+            ProtectedMemory::new(SliceMemory::new(code, &mut ram));
+
+        assert_eq!(
+            memory.protect(RAM_OFFSET, 0, Protection::NoAccess),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            memory.protect(u32::MAX, 2, Protection::NoAccess),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(memory.protect(0x0, 4, Protection::ExecuteOnly), Ok(()));
+        assert_eq!(
+            memory.protect(RAM_OFFSET + 4, 4, Protection::NoAccess),
+            Ok(())
+        );
+        assert_eq!(
+            memory.protect(RAM_OFFSET + 8, 4, Protection::ReadOnly),
+            Err(EmbiveError::TooManyProtectionRegions)
+        );
+
+        // Execute-only code
+        assert_eq!(memory.fetch::<2>(0x2), Ok([0x3, 0x4]));
+        assert_eq!(
+            memory.load::<2>(0x2),
+            Err(EmbiveError::AccessViolation {
+                address: 0x2,
+                kind: AccessKind::Read
+            })
+        );
+
+        // No-access RAM, accesses partially inside the region are denied
+        assert_eq!(memory.store(RAM_OFFSET, [0x5; 4]), Ok(()));
+        assert_eq!(
+            memory.store(RAM_OFFSET + 2, [0x5; 4]),
+            Err(EmbiveError::AccessViolation {
+                address: RAM_OFFSET + 2,
+                kind: AccessKind::Write
+            })
+        );
+        let mut buffer = [0; 8];
+        assert_eq!(
+            memory.load_bytes(RAM_OFFSET, &mut buffer),
+            Err(EmbiveError::AccessViolation {
+                address: RAM_OFFSET,
+                kind: AccessKind::Read
+            })
+        );
+        assert_eq!(memory.store_bytes(RAM_OFFSET + 8, &[0x6; 8]), Ok(()));
+
+        // Host access
+        assert_eq!(memory.inner_mut().store(RAM_OFFSET + 4, [0x7; 4]), Ok(()));
+
+        assert!(memory.unprotect(RAM_OFFSET + 4));
+        assert!(!memory.unprotect(RAM_OFFSET + 4));
+        assert_eq!(memory.load::<4>(RAM_OFFSET + 2), Ok([0x5, 0x5, 0x7, 0x7]));
+    }
+
+    #[test]
+    fn test_execute_only_engine() {
+        let code = &[
+            0x03, 0x25, 0x00, 0x00, // lw   a0, 0(zero)
+            0x73, 0x00, 0x10, 0x00, // ebreak
+        ];
+        let mut memory: ProtectedMemory<_, 1> =
+            ProtectedMemory::new(SliceMemory::new(code, &mut []));
+        memory
+            .protect(0x0, code.len() as u32, Protection::ExecuteOnly)
+            .unwrap();
+
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+        assert_eq!(
+            engine.run(),
+            Err(EmbiveError::AccessViolation {
+                address: 0x0,
+                kind: AccessKind::Read
+            })
+        );
+        assert_eq!(engine.program_counter, 0x0);
+    }
+}
@@ -0,0 +1,368 @@
+//! Sanitized Memory Module
+
+use super::Memory;
+use crate::error::{AccessKind, EmbiveError};
+
+/// A host buffer shared with the guest.
+#[derive(Debug)]
+struct SharedBuffer<'a> {
+    /// Buffer guest address.
+    address: u32,
+    /// Buffer data.
+    data: &'a mut [u8],
+}
+
+/// A memory wrapper sharing host buffers with the guest, separated by inaccessible red zones
+/// (address sanitizer), so off-by-one guest accesses fault at the exact buffer boundary instead of
+/// silently reaching the next shared buffer.
+///
+/// Buffers are placed by the wrapper inside a dedicated region (ex.: unused code region space), each one
+/// with at least `red_zone` bytes before and after it (starts are word-aligned). Every byte of the region
+/// outside of a mapped buffer is a red zone: accesses touching it fail with [`EmbiveError::AccessViolation`],
+/// at the first inaccessible address. Addresses of unmapped buffers aren't reused, so stale guest pointers fault too.
+/// Outside of the region, accesses are forwarded to the inner memory.
+///
+/// Up to `B` buffers can be mapped at the same time.
+///
+/// ```
+/// use embive::{
+///     engine::{Config, Engine},
+///     error::{AccessKind, EmbiveError},
+///     memory::{SanitizedMemory, SliceMemory},
+/// };
+///
+/// let code = &[
+///     0x37, 0x05, 0x00, 0x40, // lui  a0, 0x40000 (shared region)
+///     0x83, 0x25, 0x05, 0x01, // lw   a1, 16(a0)  (first buffer)
+///     0x03, 0x46, 0x45, 0x01, // lbu  a2, 20(a0)  (one past the end)
+///     0x73, 0x00, 0x10, 0x00, // ebreak
+/// ];
+/// let mut request = [0x01, 0x02, 0x03, 0x04];
+/// let mut response = [0; 4];
+///
+/// let mut memory: SanitizedMemory<_, 4> =
+///     SanitizedMemory::new(SliceMemory::new(code, &mut []), 0x4000_0000, 0x1000, 16);
+/// assert_eq!(memory.map(&mut request), Ok(0x4000_0010));
+/// assert_eq!(memory.map(&mut response), Ok(0x4000_0024));
+///
+/// let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+/// assert_eq!(
+///     engine.run(),
+///     Err(EmbiveError::AccessViolation { address: 0x4000_0014, kind: AccessKind::Read })
+/// );
+/// assert_eq!(engine.registers.get(11), Ok(0x04030201));
+/// ```
+#[derive(Debug)]
+pub struct SanitizedMemory<'a, M: Memory, const B: usize> {
+    /// Inner memory (code + RAM).
+    inner: M,
+    /// Shared region start address.
+    start: u32,
+    /// Shared region end address (exclusive).
+    end: u32,
+    /// Minimum red zone size, in bytes.
+    red_zone: u32,
+    /// End of the last mapped buffer (next buffers are placed after it).
+    next: u32,
+    /// Mapped buffers (None = Free slot).
+    buffers: [Option<SharedBuffer<'a>>; B],
+}
+
+impl<'a, M: Memory, const B: usize> SanitizedMemory<'a, M, B> {
+    /// Create a new memory wrapper, without mapped buffers.
+    ///
+    /// Arguments:
+    /// - `inner`: Inner memory (code + RAM).
+    /// - `address`: Shared region start address (outside of the inner memory).
+    /// - `size`: Shared region size in bytes (clamped to the address space).
+    /// - `red_zone`: Minimum red zone size around each buffer, in bytes.
+    pub fn new(inner: M, address: u32, size: u32, red_zone: u32) -> Self {
+        SanitizedMemory {
+            inner,
+            start: address,
+            end: address.saturating_add(size),
+            red_zone,
+            next: address,
+            buffers: [const { None }; B],
+        }
+    }
+
+    /// Map a host buffer, after the previously mapped ones.
+    ///
+    /// Arguments:
+    /// - `buffer`: Host buffer, shared with the guest.
+    ///
+    /// Returns:
+    /// - `Ok(u32)`: Buffer guest address.
+    /// - `Err(EmbiveError)`: The buffer and its red zones don't fit in the rest of the region
+    ///   ([`EmbiveError::InvalidMemoryAddress`]), or all `B` buffers are mapped ([`EmbiveError::TooManySharedBuffers`]).
+    pub fn map(&mut self, buffer: &'a mut [u8]) -> Result<u32, EmbiveError> {
+        let address = self
+            .next
+            .checked_add(self.red_zone)
+            .and_then(|address| address.checked_next_multiple_of(4))
+            .ok_or(EmbiveError::InvalidMemoryAddress)?;
+        let end = u32::try_from(buffer.len())
+            .ok()
+            .and_then(|len| address.checked_add(len))
+            .filter(|end| {
+                end.checked_add(self.red_zone)
+                    .is_some_and(|last| last <= self.end)
+            })
+            .ok_or(EmbiveError::InvalidMemoryAddress)?;
+
+        let slot = self
+            .buffers
+            .iter_mut()
+            .find(|buffer| buffer.is_none())
+            .ok_or(EmbiveError::TooManySharedBuffers)?;
+
+        *slot = Some(SharedBuffer {
+            address,
+            data: buffer,
+        });
+        self.next = end;
+
+        Ok(address)
+    }
+
+    /// Unmap a host buffer (its addresses become a red zone).
+    ///
+    /// Arguments:
+    /// - `address`: Buffer guest address.
+    ///
+    /// Returns:
+    /// - `Some(&mut [u8])`: Host buffer that was mapped at the address.
+    /// - `None`: No buffer mapped at the address.
+    pub fn unmap(&mut self, address: u32) -> Option<&'a mut [u8]> {
+        self.buffers
+            .iter_mut()
+            .find(|buffer| {
+                buffer
+                    .as_ref()
+                    .is_some_and(|buffer| buffer.address == address)
+            })?
+            .take()
+            .map(|buffer| buffer.data)
+    }
+
+    /// Inner memory.
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Inner memory (mutable).
+    pub fn inner_mut(&mut self) -> &mut M {
+        &mut self.inner
+    }
+
+    /// Find the buffer bytes of an access.
+    ///
+    /// Arguments:
+    /// - `address`: Access address.
+    /// - `len`: Access width in bytes.
+    /// - `kind`: Access kind.
+    ///
+    /// Returns:
+    /// - `Ok(Some((usize, usize)))`: Buffer slot and offset inside it.
+    /// - `Ok(None)`: Outside of the shared region (inner memory access).
+    /// - `Err(EmbiveError)`: The access touches a red zone ([`EmbiveError::AccessViolation`]).
+    #[inline(always)]
+    fn locate(
+        &self,
+        address: u32,
+        len: usize,
+        kind: AccessKind,
+    ) -> Result<Option<(usize, usize)>, EmbiveError> {
+        if address < self.start || address >= self.end {
+            return Ok(None);
+        }
+
+        for (slot, buffer) in self.buffers.iter().enumerate() {
+            let Some(buffer) = buffer else {
+                continue;
+            };
+
+            let offset = address.wrapping_sub(buffer.address) as usize;
+            if offset < buffer.data.len() {
+                if buffer.data.len() - offset < len {
+                    // Crosses the end of the buffer, faults at the first red zone byte
+                    let address = buffer.address.wrapping_add(buffer.data.len() as u32);
+                    return Err(EmbiveError::AccessViolation { address, kind });
+                }
+
+                return Ok(Some((slot, offset)));
+            }
+        }
+
+        Err(EmbiveError::AccessViolation { address, kind })
+    }
+
+    /// Get the buffer bytes of an access.
+    ///
+    /// Arguments:
+    /// - `slot`: Buffer slot (located).
+    /// - `offset`: Offset inside the buffer.
+    /// - `len`: Access width in bytes.
+    #[inline(always)]
+    fn bytes(&self, slot: usize, offset: usize, len: usize) -> &[u8] {
+        // Unwrap is safe because the slot was located.
+        &self.buffers[slot].as_ref().unwrap().data[offset..offset + len]
+    }
+
+    /// Get the buffer bytes of an access (mutable).
+    ///
+    /// Arguments:
+    /// - `slot`: Buffer slot (located).
+    /// - `offset`: Offset inside the buffer.
+    /// - `len`: Access width in bytes.
+    #[inline(always)]
+    fn bytes_mut(&mut self, slot: usize, offset: usize, len: usize) -> &mut [u8] {
+        // Unwrap is safe because the slot was located.
+        &mut self.buffers[slot].as_mut().unwrap().data[offset..offset + len]
+    }
+}
+
+impl<M: Memory, const B: usize> Memory for SanitizedMemory<'_, M, B> {
+    #[inline]
+    fn load<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+        let Some((slot, offset)) = self.locate(address, N, AccessKind::Read)? else {
+            return self.inner.load(address);
+        };
+
+        let mut data = [0; N];
+        data.copy_from_slice(self.bytes(slot, offset, N));
+        Ok(data)
+    }
+
+    #[inline]
+    fn fetch<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+        let Some((slot, offset)) = self.locate(address, N, AccessKind::Execute)? else {
+            return self.inner.fetch(address);
+        };
+
+        let mut data = [0; N];
+        data.copy_from_slice(self.bytes(slot, offset, N));
+        Ok(data)
+    }
+
+    #[inline]
+    fn store<const N: usize>(&mut self, address: u32, data: [u8; N]) -> Result<(), EmbiveError> {
+        let Some((slot, offset)) = self.locate(address, N, AccessKind::Write)? else {
+            return self.inner.store(address, data);
+        };
+
+        self.bytes_mut(slot, offset, N).copy_from_slice(&data);
+        Ok(())
+    }
+
+    #[inline]
+    fn load_bytes(&self, address: u32, buffer: &mut [u8]) -> Result<(), EmbiveError> {
+        let Some((slot, offset)) = self.locate(address, buffer.len(), AccessKind::Read)? else {
+            return self.inner.load_bytes(address, buffer);
+        };
+
+        buffer.copy_from_slice(self.bytes(slot, offset, buffer.len()));
+        Ok(())
+    }
+
+    #[inline]
+    fn store_bytes(&mut self, address: u32, data: &[u8]) -> Result<(), EmbiveError> {
+        let Some((slot, offset)) = self.locate(address, data.len(), AccessKind::Write)? else {
+            return self.inner.store_bytes(address, data);
+        };
+
+        self.bytes_mut(slot, offset, data.len())
+            .copy_from_slice(data);
+        Ok(())
+    }
+
+    // Shared buffers aren't part of the RAM
+    #[inline(always)]
+    fn export_ram(&self) -> Option<&[u8]> {
+        self.inner.export_ram()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{SliceMemory, RAM_OFFSET};
+
+    const REGION: u32 = 0x4000_0000;
+
+    #[test]
+    fn test_red_zones() {
+        let mut first = [0x1; 6];
+        let mut second = [0x2; 4];
+        let mut third = [0x3; 4];
+        let mut ram = [0; 4];
+        let mut memory: SanitizedMemory<_, 2> =
+            SanitizedMemory::new(SliceMemory::new(&[], &mut ram), REGION, 64, 8);
+
+        // Word-aligned, at least 8 bytes apart
+        assert_eq!(memory.map(&mut first), Ok(REGION + 8));
+        assert_eq!(memory.map(&mut second), Ok(REGION + 24));
+        assert!(matches!(
+            memory.map(&mut third),
+            Err(EmbiveError::TooManySharedBuffers)
+        ));
+
+        // Inside the buffers
+        assert_eq!(memory.load::<2>(REGION + 12), Ok([0x1; 2]));
+        assert_eq!(memory.store(REGION + 24, [0x5; 4]), Ok(()));
+
+        // Red zones (before, between and crossing the end)
+        assert_eq!(
+            memory.load::<1>(REGION + 7),
+            Err(EmbiveError::AccessViolation {
+                address: REGION + 7,
+                kind: AccessKind::Read
+            })
+        );
+        assert_eq!(
+            memory.store(REGION + 12, [0x6; 4]),
+            Err(EmbiveError::AccessViolation {
+                address: REGION + 14,
+                kind: AccessKind::Write
+            })
+        );
+        let mut buffer = [0; 5];
+        assert_eq!(
+            memory.load_bytes(REGION + 24, &mut buffer),
+            Err(EmbiveError::AccessViolation {
+                address: REGION + 28,
+                kind: AccessKind::Read
+            })
+        );
+        assert_eq!(
+            memory.fetch::<4>(REGION + 16),
+            Err(EmbiveError::AccessViolation {
+                address: REGION + 16,
+                kind: AccessKind::Execute
+            })
+        );
+
+        // Inner memory
+        assert_eq!(memory.store(RAM_OFFSET, [0x7; 4]), Ok(()));
+        assert_eq!(
+            memory.load::<4>(REGION + 64),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+
+        // Unmapped buffers become red zones, their addresses aren't reused
+        let first = memory.unmap(REGION + 8).unwrap();
+        assert_eq!(first, [0x1; 6]);
+        assert_eq!(memory.unmap(REGION + 8), None);
+        assert!(memory.load::<1>(REGION + 8).is_err());
+        assert_eq!(memory.map(first), Ok(REGION + 36));
+        assert_eq!(memory.load::<4>(REGION + 36), Ok([0x1; 4]));
+        assert_eq!(
+            memory.map(&mut [0; 16]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+
+        assert_eq!(second, [0x5; 4]);
+        assert_eq!(ram, [0x7; 4]);
+    }
+}
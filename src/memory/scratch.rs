@@ -0,0 +1,82 @@
+//! Scratch Memory Module
+
+use crate::error::EmbiveError;
+
+/// Host-supplied scratch memory (ex.: a static pool), for engine subsystems that need buffers
+/// without a global allocator.
+///
+/// A bump arena: buffers are carved from the start of the remaining memory and live as long as it,
+/// so one pool can be shared by multiple engines. Give it to an engine with [`crate::engine::Engine::set_scratch`].
+///
+/// ```
+/// use embive::{engine::{Config, Engine}, memory::{ScratchMemory, SliceMemory}};
+///
+/// let mut pool = [0; 256];
+/// let mut scratch = ScratchMemory::new(&mut pool);
+///
+/// let mut memory = SliceMemory::new(&[], &mut []);
+/// let config = Config::default().with_syscall_history(4);
+/// let mut engine = Engine::new(&mut memory, config).unwrap();
+/// engine.set_scratch(&mut scratch).unwrap();
+///
+/// assert_eq!(scratch.remaining(), 256 - 4 * 16);
+/// ```
+#[derive(Debug, Default)]
+pub struct ScratchMemory<'a> {
+    /// Remaining (unallocated) memory.
+    buffer: &'a mut [u8],
+}
+
+impl<'a> ScratchMemory<'a> {
+    /// Create a new scratch memory.
+    ///
+    /// Arguments:
+    /// - `buffer`: Host memory, owned by the scratch memory.
+    pub fn new(buffer: &'a mut [u8]) -> Self {
+        ScratchMemory { buffer }
+    }
+
+    /// Remaining (unallocated) size in bytes.
+    pub fn remaining(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Allocate a zeroed buffer.
+    ///
+    /// Arguments:
+    /// - `len`: Buffer size in bytes.
+    ///
+    /// Returns:
+    /// - `Ok(&mut [u8])`: Allocated buffer.
+    /// - `Err(EmbiveError)`: Not enough scratch memory left ([`EmbiveError::ScratchTooSmall`]).
+    pub fn alloc(&mut self, len: usize) -> Result<&'a mut [u8], EmbiveError> {
+        if len > self.buffer.len() {
+            return Err(EmbiveError::ScratchTooSmall);
+        }
+
+        let (buffer, remaining) = core::mem::take(&mut self.buffer).split_at_mut(len);
+        self.buffer = remaining;
+        buffer.fill(0);
+        Ok(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alloc() {
+        let mut pool = [0xFF; 8];
+        let mut scratch = ScratchMemory::new(&mut pool);
+
+        let a = scratch.alloc(3).unwrap();
+        assert_eq!(a, [0; 3]);
+        a[0] = 1;
+        assert_eq!(scratch.remaining(), 5);
+        assert_eq!(scratch.alloc(6), Err(EmbiveError::ScratchTooSmall));
+        assert_eq!(scratch.alloc(5).unwrap().len(), 5);
+        assert_eq!(scratch.alloc(0).unwrap().len(), 0);
+        assert_eq!(scratch.remaining(), 0);
+    }
+}
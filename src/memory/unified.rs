@@ -0,0 +1,149 @@
+//! Unified Memory Module
+
+use super::{Memory, RAM_OFFSET};
+use crate::error::EmbiveError;
+
+/// A memory implementation using slices, with a writable code region (unified memory, instead of the
+/// Harvard-style [`super::SliceMemory`], where stores into the code region fail).
+///
+/// For self-modifying or JIT-style guests, which write instructions and then execute them.
+/// Instructions are fetched from memory every time (no decode cache), so stores take effect
+/// immediately, `fence.i` is not required (but recommended for portable guests).
+///
+/// ```
+/// use embive::{engine::{Config, Engine}, memory::UnifiedSliceMemory};
+///
+/// let mut code = [
+///     0x37, 0x05, 0xa0, 0x02, // lui     a0, 0x2a00
+///     0x13, 0x05, 0x35, 0x59, // addi    a0, a0, 0x593 (a0 = "li a1, 42")
+///     0x23, 0x28, 0xa0, 0x00, // sw      a0, 16(zero)
+///     0x0f, 0x10, 0x00, 0x00, // fence.i
+///     0x13, 0x00, 0x00, 0x00, // nop     (overwritten)
+///     0x73, 0x00, 0x10, 0x00, // ebreak
+/// ];
+/// let mut memory = UnifiedSliceMemory::new(&mut code, &mut []);
+/// let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+///
+/// assert_eq!(engine.run(), Ok(false));
+/// assert_eq!(engine.registers.get(11), Ok(42));
+/// ```
+#[derive(Debug)]
+pub struct UnifiedSliceMemory<'a> {
+    /// RISC-V bytecode (writable).
+    code: &'a mut [u8],
+    /// RAM buffer.
+    ram: &'a mut [u8],
+}
+
+impl UnifiedSliceMemory<'_> {
+    /// Create a new memory space.
+    ///
+    /// Arguments:
+    /// - `code`: Code buffer, mutable `u8` slice.
+    /// - `ram`: RAM buffer, mutable `u8` slice.
+    pub fn new<'a>(code: &'a mut [u8], ram: &'a mut [u8]) -> UnifiedSliceMemory<'a> {
+        UnifiedSliceMemory { code, ram }
+    }
+
+    /// Get the region of an address.
+    ///
+    /// Returns:
+    /// - `(&[u8], usize)`: Region (code or RAM) and offset inside it.
+    #[inline(always)]
+    fn region(&self, address: u32) -> (&[u8], usize) {
+        if address >= RAM_OFFSET {
+            (&*self.ram, (address - RAM_OFFSET) as usize)
+        } else {
+            (&*self.code, address as usize)
+        }
+    }
+
+    /// Get the region of an address (mutable).
+    ///
+    /// Returns:
+    /// - `(&mut [u8], usize)`: Region (code or RAM) and offset inside it.
+    #[inline(always)]
+    fn region_mut(&mut self, address: u32) -> (&mut [u8], usize) {
+        if address >= RAM_OFFSET {
+            (&mut *self.ram, (address - RAM_OFFSET) as usize)
+        } else {
+            (&mut *self.code, address as usize)
+        }
+    }
+}
+
+impl Memory for UnifiedSliceMemory<'_> {
+    fn load<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+        let (region, offset) = self.region(address);
+        region
+            .get(offset..)
+            .and_then(|region| region.first_chunk::<N>())
+            .copied()
+            .ok_or(EmbiveError::InvalidMemoryAddress)
+    }
+
+    fn store<const N: usize>(&mut self, address: u32, data: [u8; N]) -> Result<(), EmbiveError> {
+        let (region, offset) = self.region_mut(address);
+        *region
+            .get_mut(offset..)
+            .and_then(|region| region.first_chunk_mut::<N>())
+            .ok_or(EmbiveError::InvalidMemoryAddress)? = data;
+
+        Ok(())
+    }
+
+    fn load_bytes(&self, address: u32, buffer: &mut [u8]) -> Result<(), EmbiveError> {
+        let (region, offset) = self.region(address);
+        let source = region
+            .get(offset..)
+            .and_then(|region| region.get(..buffer.len()))
+            .ok_or(EmbiveError::InvalidMemoryAddress)?;
+        buffer.copy_from_slice(source);
+
+        Ok(())
+    }
+
+    fn store_bytes(&mut self, address: u32, data: &[u8]) -> Result<(), EmbiveError> {
+        let (region, offset) = self.region_mut(address);
+        let destination = region
+            .get_mut(offset..)
+            .and_then(|region| region.get_mut(..data.len()))
+            .ok_or(EmbiveError::InvalidMemoryAddress)?;
+        destination.copy_from_slice(data);
+
+        Ok(())
+    }
+
+    fn export_ram(&self) -> Option<&[u8]> {
+        Some(self.ram)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_code() {
+        let mut code = [0; 4];
+        let mut ram = [0; 4];
+        let mut memory = UnifiedSliceMemory::new(&mut code, &mut ram);
+
+        assert_eq!(memory.store(0x0, [0x1, 0x2]), Ok(()));
+        assert_eq!(memory.store(RAM_OFFSET + 2, [0x3, 0x4]), Ok(()));
+        assert_eq!(memory.load::<4>(0x0), Ok([0x1, 0x2, 0x0, 0x0]));
+        assert_eq!(memory.load::<4>(RAM_OFFSET), Ok([0x0, 0x0, 0x3, 0x4]));
+
+        // Out of bounds
+        assert_eq!(
+            memory.store(0x2, [0x5; 4]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            memory.store_bytes(RAM_OFFSET + 1, &[0x5; 4]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(memory.store_bytes(0x1, &[0x5; 3]), Ok(()));
+        assert_eq!(code, [0x1, 0x5, 0x5, 0x5]);
+    }
+}
@@ -0,0 +1,255 @@
+//! Vec Memory Module
+
+use alloc::{vec, vec::Vec};
+
+use super::{Memory, RAM_OFFSET};
+use crate::error::EmbiveError;
+
+/// Minimum RAM growth, in bytes (avoids reallocating on every store past the end).
+const MIN_GROWTH: usize = 4096;
+
+/// A memory implementation owning its code and RAM (`alloc` hosts), no borrowed buffers.
+///
+/// The RAM region is `max_ram` bytes, allocated on demand: bytes past the allocated RAM read as zero,
+/// and stores past it grow the RAM (zeroed, up to `max_ram`). The code is read-only, as in
+/// [`super::SliceMemory`].
+///
+/// ```
+/// use embive::{engine::{Config, Engine}, memory::{Memory, VecMemory, RAM_OFFSET}};
+///
+/// let code = vec![
+///     0x37, 0x15, 0x00, 0x80, // lui  a0, 0x80001
+///     0x93, 0x05, 0xa0, 0x02, // li   a1, 42
+///     0x23, 0x20, 0xb5, 0x00, // sw   a1, 0(a0)
+///     0x73, 0x00, 0x10, 0x00, // ebreak
+/// ];
+///
+/// // No RAM allocated yet, up to 64 KiB
+/// let mut memory = VecMemory::new(code, 0, 64 * 1024);
+/// let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+/// assert_eq!(engine.run(), Ok(false));
+///
+/// assert!(memory.ram().len() > 4096);
+/// assert_eq!(memory.load(RAM_OFFSET + 4096), Ok(42u32.to_le_bytes()));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct VecMemory {
+    /// RISC-V bytecode.
+    code: Vec<u8>,
+    /// RAM buffer (allocated part).
+    ram: Vec<u8>,
+    /// RAM size limit, in bytes.
+    max_ram: usize,
+}
+
+impl VecMemory {
+    /// Create a new memory space.
+    ///
+    /// Arguments:
+    /// - `code`: Code buffer.
+    /// - `ram_size`: Initial RAM size in bytes, allocated and zeroed.
+    /// - `max_ram`: RAM size limit in bytes (at least `ram_size`, at most the RAM region size).
+    pub fn new(code: Vec<u8>, ram_size: usize, max_ram: usize) -> Self {
+        let region = (u32::MAX - RAM_OFFSET) as usize + 1;
+        let max_ram = max_ram.max(ram_size).min(region);
+
+        VecMemory {
+            code,
+            ram: vec![0; ram_size.min(max_ram)],
+            max_ram,
+        }
+    }
+
+    /// Get the code.
+    pub fn code(&self) -> &[u8] {
+        &self.code
+    }
+
+    /// Get the allocated RAM.
+    pub fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    /// Get the allocated RAM (mutable).
+    pub fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
+    /// Get the RAM size limit, in bytes.
+    pub fn max_ram(&self) -> usize {
+        self.max_ram
+    }
+
+    /// Consume the memory, returning the code and the allocated RAM.
+    pub fn into_parts(self) -> (Vec<u8>, Vec<u8>) {
+        (self.code, self.ram)
+    }
+
+    /// Grow the allocated RAM (zeroed), ex.: before copying a guest image into it.
+    ///
+    /// Arguments:
+    /// - `size`: Minimum RAM size, in bytes (never shrinks).
+    ///
+    /// Returns:
+    /// - `Ok(())`: RAM has at least `size` bytes.
+    /// - `Err(EmbiveError)`: Larger than the limit, or the allocation failed ([`EmbiveError::InvalidMemoryAddress`]).
+    pub fn grow_ram(&mut self, size: usize) -> Result<(), EmbiveError> {
+        if size <= self.ram.len() {
+            return Ok(());
+        }
+
+        if size > self.max_ram {
+            return Err(EmbiveError::InvalidMemoryAddress);
+        }
+
+        // Grow at least by half the RAM (or MIN_GROWTH), within the limit
+        let size = size
+            .max(self.ram.len() + self.ram.len() / 2)
+            .max(MIN_GROWTH)
+            .min(self.max_ram);
+        self.ram
+            .try_reserve_exact(size - self.ram.len())
+            .map_err(|_| EmbiveError::InvalidMemoryAddress)?;
+        self.ram.resize(size, 0);
+
+        Ok(())
+    }
+
+    /// Get the RAM offset of a range, checking it against the RAM size limit.
+    ///
+    /// Arguments:
+    /// - `address`: Range start address (RAM).
+    /// - `len`: Range length in bytes.
+    ///
+    /// Returns:
+    /// - `Ok(usize)`: Offset from [`RAM_OFFSET`].
+    /// - `Err(EmbiveError)`: Not a RAM address, or out of bounds ([`EmbiveError::InvalidMemoryAddress`]).
+    #[inline(always)]
+    fn ram_offset(&self, address: u32, len: usize) -> Result<usize, EmbiveError> {
+        let offset = address
+            .checked_sub(RAM_OFFSET)
+            .ok_or(EmbiveError::InvalidMemoryAddress)? as usize;
+        match offset.checked_add(len) {
+            Some(end) if end <= self.max_ram => Ok(offset),
+            _ => Err(EmbiveError::InvalidMemoryAddress),
+        }
+    }
+
+    /// Copy RAM bytes into a buffer (zero past the allocated RAM).
+    ///
+    /// Arguments:
+    /// - `offset`: Offset from [`RAM_OFFSET`], range checked against the limit.
+    /// - `buffer`: Buffer, filled completely.
+    #[inline(always)]
+    fn read_ram(&self, offset: usize, buffer: &mut [u8]) {
+        let allocated = self.ram.get(offset..).unwrap_or_default();
+        let len = allocated.len().min(buffer.len());
+
+        buffer[..len].copy_from_slice(&allocated[..len]);
+        buffer[len..].fill(0);
+    }
+}
+
+impl Memory for VecMemory {
+    fn load<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+        if address < RAM_OFFSET {
+            return self
+                .code
+                .get(address as usize..)
+                .and_then(|code| code.first_chunk::<N>())
+                .copied()
+                .ok_or(EmbiveError::InvalidMemoryAddress);
+        }
+
+        // Fast path, allocated RAM
+        let offset = (address - RAM_OFFSET) as usize;
+        if let Some(data) = self
+            .ram
+            .get(offset..)
+            .and_then(|ram| ram.first_chunk::<N>())
+        {
+            return Ok(*data);
+        }
+
+        let mut data = [0; N];
+        self.read_ram(self.ram_offset(address, N)?, &mut data);
+        Ok(data)
+    }
+
+    fn store<const N: usize>(&mut self, address: u32, data: [u8; N]) -> Result<(), EmbiveError> {
+        self.store_bytes(address, &data)
+    }
+
+    fn load_bytes(&self, address: u32, buffer: &mut [u8]) -> Result<(), EmbiveError> {
+        if address < RAM_OFFSET {
+            let code = self
+                .code
+                .get(address as usize..)
+                .and_then(|code| code.get(..buffer.len()))
+                .ok_or(EmbiveError::InvalidMemoryAddress)?;
+            buffer.copy_from_slice(code);
+            return Ok(());
+        }
+
+        self.read_ram(self.ram_offset(address, buffer.len())?, buffer);
+        Ok(())
+    }
+
+    fn store_bytes(&mut self, address: u32, data: &[u8]) -> Result<(), EmbiveError> {
+        let offset = self.ram_offset(address, data.len())?;
+        let end = offset + data.len();
+        self.grow_ram(end)?;
+
+        self.ram[offset..end].copy_from_slice(data);
+        Ok(())
+    }
+
+    fn export_ram(&self) -> Option<&[u8]> {
+        Some(&self.ram)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vec_memory() {
+        let mut memory = VecMemory::new(vec![0x1, 0x2, 0x3], 4, 8192);
+        assert_eq!(memory.ram().len(), 4);
+        assert_eq!(memory.load::<2>(0x1), Ok([0x2, 0x3]));
+        assert_eq!(
+            memory.store(0x0, [0x4]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+
+        // Unallocated RAM reads as zero
+        assert_eq!(memory.load::<4>(RAM_OFFSET + 2), Ok([0x0; 4]));
+        assert_eq!(memory.ram().len(), 4);
+
+        // Stores grow the RAM
+        assert_eq!(memory.store(RAM_OFFSET + 2, [0x5; 4]), Ok(()));
+        assert_eq!(memory.ram().len(), MIN_GROWTH);
+        assert_eq!(memory.load::<4>(RAM_OFFSET), Ok([0x0, 0x0, 0x5, 0x5]));
+
+        // Up to the limit
+        assert_eq!(memory.store(RAM_OFFSET + 8188, [0x6; 4]), Ok(()));
+        assert_eq!(memory.ram().len(), 8192);
+        assert_eq!(
+            memory.store(RAM_OFFSET + 8190, [0x7; 4]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            memory.load::<4>(RAM_OFFSET + 8190),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            memory.grow_ram(8193),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+
+        let (code, ram) = memory.into_parts();
+        assert_eq!(code, [0x1, 0x2, 0x3]);
+        assert_eq!(ram[8186..], [0x0, 0x0, 0x6, 0x6, 0x6, 0x6]);
+    }
+}
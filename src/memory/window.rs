@@ -0,0 +1,234 @@
+//! Host Memory Window Module
+
+use super::Memory;
+use crate::engine::Engine;
+use crate::error::EmbiveError;
+
+/// A memory wrapper exposing a read-only window of host memory (ex.: a sensor DMA buffer) to the guest.
+///
+/// The window is only exposed during [`Engine::run_with_window`] and revoked when it returns
+/// (yield, halt or error), so the guest reads live host data without it being copied,
+/// and can never write to it. Outside the window, accesses are forwarded to the inner memory.
+///
+/// The window buffer is held by the memory (ex.: an array, or a `&'static mut [u8]` DMA buffer),
+/// the host refreshes it between runs through [`WindowMemory::window_mut`].
+///
+/// ```
+/// use embive::{engine::{Config, Engine}, memory::{SliceMemory, WindowMemory}};
+///
+/// let code = &[
+///     0x37, 0x05, 0x00, 0x40, // lui  a0, 0x40000 (window address)
+///     0x03, 0x45, 0x15, 0x00, // lbu  a0, 1(a0)
+///     0x73, 0x00, 0x10, 0x00, // ebreak
+/// ];
+/// let mut memory = WindowMemory::new(SliceMemory::new(code, &mut []), 0x4000_0000, [0u8; 2]);
+/// let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+///
+/// // New sensor data
+/// engine.memory.window_mut().copy_from_slice(&[0x12, 0x34]);
+/// assert_eq!(engine.run_with_window(), Ok(false));
+/// assert_eq!(engine.registers.get(10), Ok(0x34));
+///
+/// engine.reset();
+/// engine.memory.window_mut().copy_from_slice(&[0x56, 0x78]);
+/// assert_eq!(engine.run_with_window(), Ok(false));
+/// assert_eq!(engine.registers.get(10), Ok(0x78));
+/// ```
+#[derive(Debug)]
+pub struct WindowMemory<M: Memory, W: AsRef<[u8]>> {
+    /// Inner memory (code + RAM).
+    inner: M,
+    /// Window guest address.
+    address: u32,
+    /// Window buffer.
+    window: W,
+    /// The window is exposed (only during [`Engine::run_with_window`]).
+    exposed: bool,
+}
+
+impl<M: Memory, W: AsRef<[u8]>> WindowMemory<M, W> {
+    /// Create a new memory wrapper, without an exposed window.
+    ///
+    /// Arguments:
+    /// - `inner`: Inner memory (code + RAM).
+    /// - `address`: Window guest address, shadowing the inner memory (ex.: unused code region space).
+    /// - `window`: Window buffer, exposed at `address` while running with the window.
+    pub fn new(inner: M, address: u32, window: W) -> Self {
+        WindowMemory {
+            inner,
+            address,
+            window,
+            exposed: false,
+        }
+    }
+
+    /// Window guest address.
+    pub fn address(&self) -> u32 {
+        self.address
+    }
+
+    /// Window buffer.
+    pub fn window(&self) -> &W {
+        &self.window
+    }
+
+    /// Window buffer (mutable), ex.: to refresh it between runs.
+    pub fn window_mut(&mut self) -> &mut W {
+        &mut self.window
+    }
+
+    /// Inner memory.
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Inner memory (mutable).
+    pub fn inner_mut(&mut self) -> &mut M {
+        &mut self.inner
+    }
+
+    /// Check if an access is inside the exposed window.
+    ///
+    /// Arguments:
+    /// - `address`: Access address.
+    ///
+    /// Returns:
+    /// - `Some((&[u8], usize))`: Window and offset inside it (the access may still go past its end).
+    /// - `None`: Outside the window, or the window isn't exposed.
+    #[inline(always)]
+    fn offset(&self, address: u32) -> Option<(&[u8], usize)> {
+        if !self.exposed {
+            return None;
+        }
+
+        let window = self.window.as_ref();
+        let offset = address.wrapping_sub(self.address) as usize;
+        (offset < window.len()).then_some((window, offset))
+    }
+}
+
+impl<M: Memory, W: AsRef<[u8]>> Memory for WindowMemory<M, W> {
+    #[inline]
+    fn load<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+        let Some((window, offset)) = self.offset(address) else {
+            return self.inner.load(address);
+        };
+
+        window[offset..]
+            .first_chunk::<N>()
+            .copied()
+            .ok_or(EmbiveError::InvalidMemoryAddress)
+    }
+
+    #[inline]
+    fn store<const N: usize>(&mut self, address: u32, data: [u8; N]) -> Result<(), EmbiveError> {
+        if self.offset(address).is_some() {
+            // Read-only window
+            return Err(EmbiveError::InvalidMemoryAddress);
+        }
+
+        self.inner.store(address, data)
+    }
+
+    #[inline(always)]
+    fn export_ram(&self) -> Option<&[u8]> {
+        self.inner.export_ram()
+    }
+}
+
+/// Revokes the window when dropped (also when unwinding from a host callback panic).
+struct Revoke<'e, 'a, M: Memory, W: AsRef<[u8]>>(&'e mut Engine<'a, WindowMemory<M, W>>);
+
+impl<M: Memory, W: AsRef<[u8]>> Drop for Revoke<'_, '_, M, W> {
+    fn drop(&mut self) {
+        self.0.memory.exposed = false;
+    }
+}
+
+impl<M: Memory, W: AsRef<[u8]>> Engine<'_, WindowMemory<M, W>> {
+    /// Run the engine with the read-only window of host memory exposed to the guest (check [`Engine::run`]).
+    /// The window is revoked when this function returns.
+    ///
+    /// Returns:
+    /// - `Ok(bool)`: Success, returns if should continue (check [`Engine::run`]).
+    /// - `Err(EmbiveError)`: Failed to run.
+    pub fn run_with_window(&mut self) -> Result<bool, EmbiveError> {
+        let guard = Revoke(self);
+        guard.0.memory.exposed = true;
+
+        guard.0.run()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Config;
+    use crate::memory::{SliceMemory, RAM_OFFSET};
+
+    const WINDOW: u32 = 0x4000_0000;
+
+    #[test]
+    fn test_window_load() {
+        let mut memory = WindowMemory::new(SliceMemory::new(&[], &mut []), WINDOW, [1, 2, 3, 4, 5]);
+        assert_eq!(
+            memory.load::<1>(WINDOW),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+
+        memory.exposed = true;
+        assert_eq!(memory.load(WINDOW), Ok([1, 2, 3, 4]));
+        assert_eq!(memory.load(WINDOW + 3), Ok([4, 5]));
+        assert_eq!(
+            memory.load::<4>(WINDOW + 3),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            memory.load::<1>(WINDOW + 5),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(
+            memory.store(WINDOW + 1, [0]),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+        assert_eq!(memory.window(), &[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_window_inner() {
+        let mut ram = [0; 4];
+        let mut memory = WindowMemory::new(SliceMemory::new(&[9], &mut ram), WINDOW, [0; 4]);
+        assert_eq!(memory.load(0), Ok([9]));
+        assert_eq!(memory.store(RAM_OFFSET, [1, 2]), Ok(()));
+        assert_eq!(memory.inner().load(RAM_OFFSET), Ok([1, 2]));
+    }
+
+    #[test]
+    fn test_run_with_window() {
+        let code = &[
+            0x37, 0x05, 0x00, 0x40, // lui  a0, 0x40000 (window address)
+            0x83, 0x25, 0x05, 0x00, // lw   a1, 0(a0)
+            0x23, 0x00, 0xb5, 0x00, // sb   a1, 0(a0)   (read-only)
+        ];
+        let window = 0x1234_5678u32.to_le_bytes();
+        let mut memory = WindowMemory::new(SliceMemory::new(code, &mut []), WINDOW, window);
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+
+        assert_eq!(
+            engine.run_with_window(),
+            Err(EmbiveError::StoreFault {
+                pc: 8,
+                address: WINDOW
+            })
+        );
+        assert_eq!(engine.registers.get(11), Ok(0x1234_5678));
+        assert_eq!(engine.program_counter, 8);
+
+        // Revoked
+        assert!(!engine.memory.exposed);
+        assert_eq!(
+            engine.memory.load::<4>(WINDOW),
+            Err(EmbiveError::InvalidMemoryAddress)
+        );
+    }
+}
@@ -5,6 +5,18 @@ use crate::error::EmbiveError;
 /// Number of registers available
 pub const REGISTER_COUNT: usize = 32;
 
+/// Vector register length in bits (`VLEN`).
+#[cfg(feature = "v_extension")]
+pub const VLEN: usize = 128;
+
+/// Vector register length in bytes (`VLENB`).
+#[cfg(feature = "v_extension")]
+pub const VLENB: usize = VLEN / 8;
+
+/// Vector type illegal bit (`vtype.vill`).
+#[cfg(feature = "v_extension")]
+pub const VILL: u32 = 1 << 31;
+
 /// CPU Register Enum
 #[repr(usize)]
 #[derive(Debug)]
@@ -77,6 +89,7 @@ pub enum Register {
 
 /// CPU Registers
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Registers {
     pub(crate) inner: [i32; REGISTER_COUNT],
 }
@@ -136,6 +149,323 @@ impl Registers {
 
         Ok(&mut self.inner[index])
     }
+
+    /// Get a general purpose register from a decoded instruction.
+    /// Decoded register indexes are always in bounds (5 bits), with the `performance_unchecked`
+    /// feature enabled this invariant is only checked by debug assertions.
+    ///
+    /// Arguments:
+    /// - `index`: The index of the register, decoded from an instruction.
+    ///
+    /// Returns:
+    /// - `Ok(i32)`: The value of the register.
+    /// - `Err(EmbiveError)`: The register index is out of bounds (never with `performance_unchecked`).
+    #[inline(always)]
+    pub(crate) fn get_decoded(&self, index: usize) -> Result<i32, EmbiveError> {
+        #[cfg(feature = "performance_unchecked")]
+        {
+            debug_assert!(index < REGISTER_COUNT);
+            Ok(self.inner[index & (REGISTER_COUNT - 1)])
+        }
+
+        #[cfg(not(feature = "performance_unchecked"))]
+        self.get(index)
+    }
+
+    /// Get a mutable reference to a general purpose register from a decoded instruction.
+    /// Decoded register indexes are always in bounds (5 bits), with the `performance_unchecked`
+    /// feature enabled this invariant is only checked by debug assertions.
+    ///
+    /// Arguments:
+    /// - `index`: The index of the register, decoded from an instruction.
+    ///
+    /// Returns:
+    /// - `Ok(&mut i32)`: Mutable reference to the register.
+    /// - `Err(EmbiveError)`: The register index is out of bounds (never with `performance_unchecked`).
+    #[inline(always)]
+    pub(crate) fn get_decoded_mut(&mut self, index: usize) -> Result<&mut i32, EmbiveError> {
+        #[cfg(feature = "performance_unchecked")]
+        {
+            debug_assert!(index < REGISTER_COUNT);
+            Ok(&mut self.inner[index & (REGISTER_COUNT - 1)])
+        }
+
+        #[cfg(not(feature = "performance_unchecked"))]
+        self.get_mut(index)
+    }
+}
+
+/// Decoded vector type (`vtype`).
+#[cfg(feature = "v_extension")]
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub(crate) struct VType {
+    /// Selected element width in bytes (1, 2 or 4).
+    pub sew: usize,
+    /// Register group multiplier, log2 (from -3 to 3).
+    pub lmul_log2: i32,
+    /// Maximum vector length (elements).
+    pub vlmax: u32,
+}
+
+#[cfg(feature = "v_extension")]
+impl VType {
+    /// Decode a vector type.
+    ///
+    /// Arguments:
+    /// - `vtype`: Raw vector type (`vtype` CSR / `vset{i}vl{i}` immediate).
+    ///
+    /// Returns:
+    /// - `Some(VType)`: The decoded vector type.
+    /// - `None`: Vector type is not supported (reserved, `vill` or element width above 32 bits).
+    pub fn decode(vtype: u32) -> Option<Self> {
+        // Only vlmul, vsew, vta and vma are defined
+        if vtype >> 8 != 0 {
+            return None;
+        }
+
+        let sew = match (vtype >> 3) & 0b111 {
+            0b000 => 1,
+            0b001 => 2,
+            0b010 => 4,
+            _ => return None,
+        };
+
+        let lmul_log2 = match vtype & 0b111 {
+            0b100 => return None,
+            vlmul @ 0b000..=0b011 => vlmul as i32,
+            vlmul => vlmul as i32 - 8,
+        };
+
+        // Fractional LMUL must hold at least one element of SEW (ELEN = 32)
+        if lmul_log2 < 0 && (sew << -lmul_log2) > 4 {
+            return None;
+        }
+
+        let vlmax = if lmul_log2 < 0 {
+            (VLENB / sew) >> -lmul_log2
+        } else {
+            (VLENB / sew) << lmul_log2
+        };
+
+        Some(VType {
+            sew,
+            lmul_log2,
+            vlmax: vlmax as u32,
+        })
+    }
+
+    /// Number of registers in a group with the given element width.
+    ///
+    /// Arguments:
+    /// - `eew`: Effective element width in bytes.
+    ///
+    /// Returns:
+    /// - `Some(usize)`: Number of registers in a group (EMUL, at least 1).
+    /// - `None`: The effective group multiplier is not supported.
+    pub fn group(&self, eew: usize) -> Option<usize> {
+        let emul_log2 =
+            self.lmul_log2 + eew.trailing_zeros() as i32 - self.sew.trailing_zeros() as i32;
+        if !(-3..=3).contains(&emul_log2) {
+            return None;
+        }
+
+        Some(1 << emul_log2.max(0))
+    }
+}
+
+/// Vector Registers (`V` extension, `VLEN` = 128 bits)
+#[cfg(feature = "v_extension")]
+#[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VectorRegisters {
+    #[cfg_attr(feature = "serde", serde(with = "byte_array"))]
+    pub(crate) inner: [u8; VLENB * REGISTER_COUNT],
+    pub(crate) vl: u32,
+    pub(crate) vtype: u32,
+}
+
+#[cfg(feature = "v_extension")]
+impl Default for VectorRegisters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "v_extension")]
+impl VectorRegisters {
+    /// Create a new set of vector registers.
+    /// All registers are set to 0, vector type is illegal (`vill`) until configured by the guest.
+    pub(crate) fn new() -> Self {
+        Self {
+            inner: [0; VLENB * REGISTER_COUNT],
+            vl: 0,
+            vtype: VILL,
+        }
+    }
+
+    /// Reset the vector registers to their initial state.
+    /// All registers are set to 0, vector type is illegal (`vill`) until configured by the guest.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Get a vector register.
+    ///
+    /// Arguments:
+    /// - `index`: The index of the register (from `0` to `31`).
+    ///
+    /// Returns:
+    /// - `Ok(&[u8; VLENB])`: The value of the register (little endian elements).
+    /// - `Err(EmbiveError)`: The register index is out of bounds.
+    pub fn get(&self, index: usize) -> Result<&[u8; VLENB], EmbiveError> {
+        self.inner
+            .chunks_exact(VLENB)
+            .nth(index)
+            .and_then(|register| register.try_into().ok())
+            .ok_or(EmbiveError::InvalidRegister)
+    }
+
+    /// Get a mutable reference to a vector register.
+    ///
+    /// Arguments:
+    /// - `index`: The index of the register (from `0` to `31`).
+    ///
+    /// Returns:
+    /// - `Ok(&mut [u8; VLENB])`: Mutable reference to the register (little endian elements).
+    /// - `Err(EmbiveError)`: The register index is out of bounds.
+    pub fn get_mut(&mut self, index: usize) -> Result<&mut [u8; VLENB], EmbiveError> {
+        self.inner
+            .chunks_exact_mut(VLENB)
+            .nth(index)
+            .and_then(|register| register.try_into().ok())
+            .ok_or(EmbiveError::InvalidRegister)
+    }
+
+    /// Current vector length (`vl`).
+    pub fn vl(&self) -> u32 {
+        self.vl
+    }
+
+    /// Current vector type (`vtype`).
+    pub fn vtype(&self) -> u32 {
+        self.vtype
+    }
+
+    /// Get an element from a register group (zero-extended).
+    ///
+    /// Arguments:
+    /// - `register`: First register of the group.
+    /// - `index`: Element index.
+    /// - `eew`: Element width in bytes (1, 2 or 4).
+    ///
+    /// Returns:
+    /// - `Ok(u32)`: The element value.
+    /// - `Err(EmbiveError)`: The element is out of the register file.
+    pub(crate) fn element(
+        &self,
+        register: usize,
+        index: usize,
+        eew: usize,
+    ) -> Result<u32, EmbiveError> {
+        let offset = register * VLENB + index * eew;
+        let bytes = self
+            .inner
+            .get(offset..offset + eew)
+            .ok_or(EmbiveError::InvalidRegister)?;
+
+        let mut value = [0; 4];
+        value[..eew].copy_from_slice(bytes);
+        Ok(u32::from_le_bytes(value))
+    }
+
+    /// Set an element in a register group (truncated to the element width).
+    ///
+    /// Arguments:
+    /// - `register`: First register of the group.
+    /// - `index`: Element index.
+    /// - `eew`: Element width in bytes (1, 2 or 4).
+    /// - `value`: Element value.
+    ///
+    /// Returns:
+    /// - `Ok(())`: The element was set.
+    /// - `Err(EmbiveError)`: The element is out of the register file.
+    pub(crate) fn set_element(
+        &mut self,
+        register: usize,
+        index: usize,
+        eew: usize,
+        value: u32,
+    ) -> Result<(), EmbiveError> {
+        let offset = register * VLENB + index * eew;
+        self.inner
+            .get_mut(offset..offset + eew)
+            .ok_or(EmbiveError::InvalidRegister)?
+            .copy_from_slice(&value.to_le_bytes()[..eew]);
+        Ok(())
+    }
+
+    /// Check if an element is active.
+    ///
+    /// Arguments:
+    /// - `unmasked`: Instruction is unmasked (`vm` = 1).
+    /// - `index`: Element index.
+    ///
+    /// Returns:
+    /// - `bool`: Element is active (unmasked or mask bit in `v0` set).
+    #[inline]
+    pub(crate) fn active(&self, unmasked: bool, index: usize) -> bool {
+        unmasked || (self.inner[index / 8] >> (index % 8)) & 1 != 0
+    }
+}
+
+/// Serde support for byte arrays of any length (serde derives only support arrays of up to 32 elements).
+#[cfg(all(feature = "v_extension", feature = "serde"))]
+mod byte_array {
+    use core::fmt::{self, Formatter};
+
+    use serde::de::{Error, SeqAccess, Visitor};
+    use serde::ser::SerializeTuple;
+    use serde::{Deserializer, Serializer};
+
+    /// Serialize a byte array as a tuple.
+    pub fn serialize<S: Serializer, const N: usize>(
+        data: &[u8; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(N)?;
+        for byte in data {
+            tuple.serialize_element(byte)?;
+        }
+        tuple.end()
+    }
+
+    /// Deserialize a byte array from a tuple.
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[u8; N], D::Error> {
+        /// Byte array visitor.
+        struct ByteArray<const N: usize>;
+
+        impl<'de, const N: usize> Visitor<'de> for ByteArray<N> {
+            type Value = [u8; N];
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                write!(f, "an array of {} bytes", N)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<[u8; N], A::Error> {
+                let mut data = [0; N];
+                for (index, byte) in data.iter_mut().enumerate() {
+                    *byte = seq
+                        .next_element()?
+                        .ok_or_else(|| Error::invalid_length(index, &self))?;
+                }
+                Ok(data)
+            }
+        }
+
+        deserializer.deserialize_tuple(N, ByteArray)
+    }
 }
 
 #[cfg(test)]
@@ -147,12 +477,9 @@ mod tests {
         let mut registers = Registers::new();
 
         assert_eq!(registers.get(0), Ok(0));
-        assert_eq!(registers.get(REGISTER_COUNT as usize - 1), Ok(0));
+        assert_eq!(registers.get(REGISTER_COUNT - 1), Ok(0));
         assert_eq!(registers.get_mut(0).map(|x| *x), Ok(0));
-        assert_eq!(
-            registers.get_mut(REGISTER_COUNT as usize - 1).map(|x| *x),
-            Ok(0)
-        );
+        assert_eq!(registers.get_mut(REGISTER_COUNT - 1).map(|x| *x), Ok(0));
     }
 
     #[test]
@@ -160,15 +487,24 @@ mod tests {
         let mut registers = Registers::new();
 
         assert_eq!(
-            registers.get(REGISTER_COUNT as usize),
+            registers.get(REGISTER_COUNT),
             Err(EmbiveError::InvalidRegister)
         );
         assert_eq!(
-            registers.get_mut(REGISTER_COUNT as usize).map(|x| *x),
+            registers.get_mut(REGISTER_COUNT).map(|x| *x),
             Err(EmbiveError::InvalidRegister)
         );
     }
 
+    #[test]
+    fn get_decoded_register() {
+        let mut registers = Registers::new();
+        *registers.get_decoded_mut(REGISTER_COUNT - 1).unwrap() = 10;
+
+        assert_eq!(registers.get_decoded(REGISTER_COUNT - 1), Ok(10));
+        assert_eq!(registers.get(REGISTER_COUNT - 1), Ok(10));
+    }
+
     #[test]
     fn reset_registers() {
         let mut registers = Registers::new();
@@ -180,4 +516,50 @@ mod tests {
 
         assert_eq!(registers.inner, [0; REGISTER_COUNT]);
     }
+
+    #[cfg(feature = "v_extension")]
+    #[test]
+    fn decode_vtype() {
+        // e8, m1
+        assert_eq!(
+            VType::decode(0b000_000),
+            Some(VType {
+                sew: 1,
+                lmul_log2: 0,
+                vlmax: 16
+            })
+        );
+        // e32, m8
+        assert_eq!(VType::decode(0b010_011).map(|t| t.vlmax), Some(32));
+        // e16, mf2
+        assert_eq!(VType::decode(0b001_111).map(|t| t.vlmax), Some(4));
+        // e32, mf2 (not enough room for an element with ELEN = 32)
+        assert_eq!(VType::decode(0b010_111), None);
+        // e64
+        assert_eq!(VType::decode(0b011_000), None);
+        // Reserved LMUL
+        assert_eq!(VType::decode(0b000_100), None);
+        // vill
+        assert_eq!(VType::decode(VILL), None);
+    }
+
+    #[cfg(feature = "v_extension")]
+    #[test]
+    fn vector_elements() {
+        let mut vector = VectorRegisters::new();
+
+        vector.set_element(1, 1, 2, 0x12345).unwrap();
+        assert_eq!(vector.element(1, 1, 2), Ok(0x2345));
+        assert_eq!(vector.get(1).unwrap()[2..4], [0x45, 0x23]);
+        assert_eq!(
+            vector.element(31, VLENB, 1),
+            Err(EmbiveError::InvalidRegister)
+        );
+        assert_eq!(vector.get(32), Err(EmbiveError::InvalidRegister));
+
+        vector.get_mut(0).unwrap()[0] = 0b10;
+        assert!(!vector.active(false, 0));
+        assert!(vector.active(false, 1));
+        assert!(vector.active(true, 0));
+    }
 }
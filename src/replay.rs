@@ -0,0 +1,640 @@
+//! Replay Module
+//!
+//! Deterministic replay: the nondeterministic inputs of a guest are recorded into a compact log,
+//! and the guest is re-executed bit-exactly from it (ex.: to debug heisenbugs of field-deployed firmware
+//! on a workstation). Inputs are captured at the execution tracing points (check [`crate::engine::TraceHook`]),
+//! keyed by the number of instructions retired since the recording started:
+//! - Syscall results: registers changed by the syscall (ex.: `a0` and `a1`), and the contents of the writable buffers
+//!   of its contract ([`crate::syscall::Arg::BufferMut`], check [`crate::engine::Config::syscall_contracts`]).
+//! - CSR reads (ex.: `rdtime`, from the host [`crate::csr::CsrHandler`]).
+//! - Interrupts delivered to the guest trap handler (with the `interrupt` feature,
+//!   check [`crate::engine::Config::guest_interrupts`]).
+//!
+//! Everything else the guest does is deterministic, so the replay needs the same code, initial state
+//! and configuration as the recording. While replaying, recorded syscalls aren't executed (their results are applied
+//! instead), and host interrupts are ignored (recorded ones are delivered at the same instruction).
+//! Once the log is consumed, the engine runs live again. Not recorded: other writes to guest memory
+//! (ex.: by the host between runs), and interrupts handled by the host ([`crate::engine::Config::interrupt_fn`]).
+//!
+//! Log format, an entry per input: kind (`u8`), instructions retired since the previous entry (LEB128), then:
+//! - Syscall: number of registers (`u8`), each index (`u8`) and value (`u32`), number of buffers (`u8`),
+//!   each address (`u32`), length (LEB128) and contents.
+//! - CSR read: value (`u32`).
+//! - Interrupt: line (`u8`).
+//!
+//! Words are little-endian.
+//!
+//! ```
+//! use embive::{engine::{Config, Engine}, memory::SliceMemory};
+//!
+//! let code = &[
+//!     0x93, 0x08, 0x10, 0x00, // li   a7, 1 (Syscall nr)
+//!     0x73, 0x00, 0x00, 0x00, // ecall
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//! ];
+//! let mut log = [0; 64];
+//!
+//! // Record (ex.: on the device)
+//! let mut memory = SliceMemory::new(code, &mut []);
+//! let config = Config::default().with_syscall_fn(Some(|_, _, _| Ok(42)));
+//! let mut engine = Engine::new(&mut memory, config).unwrap();
+//! engine.start_recording(&mut log);
+//! assert_eq!(engine.run(), Ok(false));
+//! let len = engine.stop_replay();
+//!
+//! // Replay (ex.: on a workstation, without the device services)
+//! let mut memory = SliceMemory::new(code, &mut []);
+//! let config = Config::default().with_syscall_fn(Some(|_, _, _| unreachable!()));
+//! let mut engine = Engine::new(&mut memory, config).unwrap();
+//! engine.start_replay(&log[..len]);
+//! assert_eq!(engine.run(), Ok(false));
+//! assert_eq!(engine.registers.get(11), Ok(42));
+//! assert!(!engine.replaying());
+//! ```
+
+use crate::engine::{changed, Engine, SYSCALL_ARGS};
+use crate::error::EmbiveError;
+use crate::instruction::{decode_execute, INSTRUCTION_SIZE};
+use crate::memory::Memory;
+use crate::register::Register;
+use crate::syscall;
+#[cfg(feature = "interrupt")]
+use crate::trap;
+
+/// Log entry: syscall result.
+const SYSCALL_ENTRY: u8 = 0;
+/// Log entry: CSR read.
+const CSR_ENTRY: u8 = 1;
+/// Log entry: interrupt delivered to the guest.
+#[cfg(feature = "interrupt")]
+const INTERRUPT_ENTRY: u8 = 2;
+
+/// System instructions (`ecall`, `ebreak`, CSR instructions, ...), the inputs of the guest.
+const SYSTEM_OPCODE: u32 = 0b111_0011;
+/// Environment call instruction.
+const ECALL: u32 = 0x0000_0073;
+/// Maximum writable buffers of a syscall (two registers each).
+const MAX_BUFFERS: usize = SYSCALL_ARGS / 2;
+
+/// Check if an instruction can take an input from the host (system instructions).
+///
+/// Arguments:
+/// - `data`: Instruction (raw).
+#[inline(always)]
+pub(crate) const fn is_input(data: u32) -> bool {
+    data & 0x7F == SYSTEM_OPCODE
+}
+
+/// Replay log mode.
+#[derive(Debug, Default)]
+enum Mode<'a> {
+    /// Not recording nor replaying.
+    #[default]
+    Off,
+    /// Recording, to the log (up to `len` bytes written).
+    Recording { log: &'a mut [u8], len: usize },
+    /// Replaying, from the log (up to `position` bytes consumed).
+    Replaying { log: &'a [u8], position: usize },
+}
+
+/// Replay state of an engine (check the [module documentation](self)).
+#[derive(Debug, Default)]
+pub(crate) struct Replay<'a> {
+    /// Log mode.
+    mode: Mode<'a>,
+    /// Instructions retired since the recording (or replay) started.
+    instret: u64,
+    /// Instructions retired at the last log entry.
+    last: u64,
+    /// A syscall completed during the current instruction.
+    pub(crate) syscall: bool,
+}
+
+impl<'a> Replay<'a> {
+    /// Check if recording or replaying.
+    #[inline(always)]
+    pub(crate) fn active(&self) -> bool {
+        !matches!(self.mode, Mode::Off)
+    }
+
+    /// Check if replaying (and the log isn't consumed yet).
+    #[inline(always)]
+    pub(crate) fn replaying(&self) -> bool {
+        matches!(self.mode, Mode::Replaying { log, position } if position < log.len())
+    }
+
+    /// Count a retired instruction.
+    #[inline(always)]
+    pub(crate) fn retired(&mut self) {
+        self.instret = self.instret.wrapping_add(1);
+    }
+
+    /// Append an entry to the log (does nothing if not recording).
+    ///
+    /// Arguments:
+    /// - `kind`: Entry kind.
+    /// - `write`: Writes the entry contents (None = Log full).
+    ///
+    /// Returns:
+    /// - `Ok(())`: Entry recorded.
+    /// - `Err(EmbiveError)`: Log full, nothing was recorded ([`EmbiveError::ReplayLogFull`]).
+    fn record(
+        &mut self,
+        kind: u8,
+        write: impl FnOnce(&mut Writer) -> Option<()>,
+    ) -> Result<(), EmbiveError> {
+        let Mode::Recording { log, len } = &mut self.mode else {
+            return Ok(());
+        };
+
+        let mut writer = Writer { log, len: *len };
+        writer
+            .byte(kind)
+            .and_then(|_| writer.varint(self.instret - self.last))
+            .and_then(|_| write(&mut writer))
+            .ok_or(EmbiveError::ReplayLogFull)?;
+
+        *len = writer.len;
+        self.last = self.instret;
+        Ok(())
+    }
+
+    /// Get the next log entry, if due at the current instruction.
+    ///
+    /// Returns:
+    /// - `Ok(Some((u8, Reader)))`: Entry kind, and its contents.
+    /// - `Ok(None)`: Not replaying, or no entry due.
+    /// - `Err(EmbiveError)`: Invalid log ([`EmbiveError::ReplayDiverged`]).
+    fn due(&self) -> Result<Option<(u8, Reader<'a>)>, EmbiveError> {
+        let Mode::Replaying { log, position } = self.mode else {
+            return Ok(None);
+        };
+        if position >= log.len() {
+            return Ok(None);
+        }
+
+        let mut reader = Reader { log, position };
+        let kind = reader.byte()?;
+        let at = self.last.checked_add(reader.varint()?);
+        Ok((at == Some(self.instret)).then_some((kind, reader)))
+    }
+
+    /// Consume a log entry.
+    ///
+    /// Arguments:
+    /// - `reader`: Entry contents, read to its end.
+    fn consume(&mut self, reader: Reader) {
+        if let Mode::Replaying { position, .. } = &mut self.mode {
+            *position = reader.position;
+            self.last = self.instret;
+        }
+    }
+}
+
+/// Log writer.
+struct Writer<'l> {
+    /// Log buffer.
+    log: &'l mut [u8],
+    /// Bytes written.
+    len: usize,
+}
+
+impl Writer<'_> {
+    /// Reserve bytes at the end of the log (None = Log full).
+    fn reserve(&mut self, len: usize) -> Option<&mut [u8]> {
+        let end = self.len.checked_add(len)?;
+        let bytes = self.log.get_mut(self.len..end)?;
+        self.len = end;
+        Some(bytes)
+    }
+
+    /// Write a byte.
+    fn byte(&mut self, value: u8) -> Option<()> {
+        self.reserve(1)?[0] = value;
+        Some(())
+    }
+
+    /// Write a word (little-endian).
+    fn word(&mut self, value: u32) -> Option<()> {
+        self.reserve(4)?.copy_from_slice(&value.to_le_bytes());
+        Some(())
+    }
+
+    /// Write an unsigned integer (LEB128).
+    fn varint(&mut self, mut value: u64) -> Option<()> {
+        while value >= 0x80 {
+            self.byte(value as u8 | 0x80)?;
+            value >>= 7;
+        }
+        self.byte(value as u8)
+    }
+}
+
+/// Log reader, invalid contents are reported as [`EmbiveError::ReplayDiverged`].
+struct Reader<'l> {
+    /// Log buffer.
+    log: &'l [u8],
+    /// Bytes read.
+    position: usize,
+}
+
+impl<'l> Reader<'l> {
+    /// Read bytes.
+    fn take(&mut self, len: usize) -> Result<&'l [u8], EmbiveError> {
+        let end = self
+            .position
+            .checked_add(len)
+            .ok_or(EmbiveError::ReplayDiverged)?;
+        let bytes = self
+            .log
+            .get(self.position..end)
+            .ok_or(EmbiveError::ReplayDiverged)?;
+        self.position = end;
+        Ok(bytes)
+    }
+
+    /// Read a byte.
+    fn byte(&mut self) -> Result<u8, EmbiveError> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// Read a word (little-endian).
+    fn word(&mut self) -> Result<u32, EmbiveError> {
+        // Unwrap is safe because exactly 4 bytes were taken.
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    /// Read an unsigned integer (LEB128).
+    fn varint(&mut self) -> Result<u64, EmbiveError> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(EmbiveError::ReplayDiverged)
+    }
+}
+
+impl<'a, M: Memory> Engine<'a, M> {
+    /// Start recording the guest inputs, replacing any recording or replay in progress
+    /// (check the [module documentation](self)).
+    ///
+    /// Arguments:
+    /// - `log`: Log buffer, entries are appended until it's full ([`EmbiveError::ReplayLogFull`]).
+    pub fn start_recording(&mut self, log: &'a mut [u8]) {
+        self.replay = Replay {
+            mode: Mode::Recording { log, len: 0 },
+            ..Default::default()
+        };
+    }
+
+    /// Start replaying the guest inputs, replacing any recording or replay in progress
+    /// (check the [module documentation](self)). The engine state must match the one at the start of the recording.
+    ///
+    /// Arguments:
+    /// - `log`: Recorded log.
+    pub fn start_replay(&mut self, log: &'a [u8]) {
+        self.replay = Replay {
+            mode: Mode::Replaying { log, position: 0 },
+            ..Default::default()
+        };
+    }
+
+    /// Stop recording or replaying.
+    ///
+    /// Returns:
+    /// - `usize`: Bytes recorded or replayed (0 = Neither).
+    pub fn stop_replay(&mut self) -> usize {
+        match core::mem::take(&mut self.replay).mode {
+            Mode::Off => 0,
+            Mode::Recording { len, .. } => len,
+            Mode::Replaying { position, .. } => position,
+        }
+    }
+
+    /// Check if the engine is replaying (and the log isn't consumed yet).
+    pub fn replaying(&self) -> bool {
+        self.replay.replaying()
+    }
+
+    /// Execute a system instruction while recording or replaying (check [`is_input`]).
+    ///
+    /// Arguments:
+    /// - `address`: Instruction address.
+    /// - `data`: Instruction (raw).
+    ///
+    /// Returns:
+    /// - `Ok(bool)`: Success, returns if should continue (check [`Engine::execute_raw`]).
+    /// - `Err(EmbiveError)`: Failed to execute, or to record or replay the input.
+    #[cold]
+    pub(crate) fn execute_input(&mut self, address: u32, data: u32) -> Result<bool, EmbiveError> {
+        match self.replay.replaying() {
+            true => self.replay_input(address, data),
+            false => self.record_input(data),
+        }
+    }
+
+    /// Execute a system instruction, recording its input.
+    ///
+    /// Arguments:
+    /// - `data`: Instruction (raw).
+    fn record_input(&mut self, data: u32) -> Result<bool, EmbiveError> {
+        let registers = self.registers;
+        let mut buffers = [(0, 0); MAX_BUFFERS];
+        if data == ECALL {
+            // Writable buffers, as checked before calling the syscall function
+            let nr = self.registers.inner[Register::A7 as usize];
+            let args = *self.registers.inner[Register::A0 as usize..]
+                .first_chunk()
+                // Unwrap is safe because the slice is guaranteed to have more than SYSCALL_ARGS elements.
+                .unwrap();
+            if let Some(contract) = syscall::find(self.config.syscall_contracts, nr) {
+                if contract.check(&args, self.memory) {
+                    for (buffer, writable) in buffers.iter_mut().zip(contract.writable(&args)) {
+                        *buffer = writable;
+                    }
+                }
+            }
+        }
+
+        self.replay.syscall = false;
+        let ret = decode_execute(self, data)?;
+
+        let rd = (data >> 7 & 0x1F) as usize;
+        if self.replay.syscall {
+            let memory = &*self.memory;
+            let changes = changed(&registers, &self.registers);
+            let buffers = buffers.iter().filter(|(_, len)| *len > 0);
+            self.replay.record(SYSCALL_ENTRY, |writer| {
+                writer.byte(changes.clone().count() as u8)?;
+                for (register, value) in changes {
+                    writer.byte(register as u8)?;
+                    writer.word(value as u32)?;
+                }
+
+                writer.byte(buffers.clone().count() as u8)?;
+                for &(address, len) in buffers {
+                    writer.word(address)?;
+                    writer.varint(len as u64)?;
+                    memory
+                        .load_bytes(address, writer.reserve(len as usize)?)
+                        .ok()?;
+                }
+                Some(())
+            })?;
+        } else if data >> 12 & 0b111 != 0 && rd != 0 {
+            // CSR read
+            let value = self.registers.inner[rd] as u32;
+            self.replay.record(CSR_ENTRY, |writer| writer.word(value))?;
+        }
+
+        Ok(ret)
+    }
+
+    /// Execute a system instruction, replaying its input.
+    ///
+    /// Arguments:
+    /// - `address`: Instruction address.
+    /// - `data`: Instruction (raw).
+    fn replay_input(&mut self, address: u32, data: u32) -> Result<bool, EmbiveError> {
+        let rd = (data >> 7 & 0x1F) as usize;
+        let csr_read = data >> 12 & 0b111 != 0 && rd != 0;
+
+        match self.replay.due()? {
+            Some((SYSCALL_ENTRY, mut reader)) if data == ECALL => {
+                // Not executed, the recorded result is applied
+                for _ in 0..reader.byte()? {
+                    let register = reader.byte()? as usize;
+                    let value = reader.word()?;
+                    match self.registers.inner.get_mut(register) {
+                        Some(inner) if register != 0 => *inner = value as i32,
+                        _ => return Err(EmbiveError::ReplayDiverged),
+                    }
+                }
+
+                for _ in 0..reader.byte()? {
+                    let address = reader.word()?;
+                    let len = reader.varint()?;
+                    let contents = reader.take(len as usize)?;
+                    self.memory.store_bytes(address, contents)?;
+                }
+
+                // The syscall may change the code
+                self.invalidate_fetch();
+                self.replay.consume(reader);
+                self.program_counter = address.wrapping_add(INSTRUCTION_SIZE);
+                Ok(true)
+            }
+            Some((CSR_ENTRY, mut reader)) if csr_read => {
+                let value = reader.word()?;
+                let ret = decode_execute(self, data)?;
+                self.registers.inner[rd] = value as i32;
+                self.replay.consume(reader);
+                Ok(ret)
+            }
+            None => {
+                self.replay.syscall = false;
+                let ret = decode_execute(self, data)?;
+                if self.replay.syscall || csr_read {
+                    // Input not in the log
+                    return Err(EmbiveError::ReplayDiverged);
+                }
+                Ok(ret)
+            }
+            Some(_) => Err(EmbiveError::ReplayDiverged),
+        }
+    }
+
+    /// Record an interrupt delivered to the guest (does nothing if not recording).
+    ///
+    /// Arguments:
+    /// - `line`: Interrupt line.
+    #[cfg(feature = "interrupt")]
+    pub(crate) fn record_interrupt(&mut self, line: u32) -> Result<(), EmbiveError> {
+        self.replay
+            .record(INTERRUPT_ENTRY, |writer| writer.byte(line as u8))
+    }
+
+    /// Deliver the recorded interrupts due before the next instruction.
+    ///
+    /// Returns:
+    /// - `Ok(())`: Interrupts delivered (if any).
+    /// - `Err(EmbiveError)`: The guest can't take a recorded interrupt ([`EmbiveError::ReplayDiverged`]).
+    #[cfg(feature = "interrupt")]
+    #[cold]
+    pub(crate) fn replay_interrupts(&mut self) -> Result<(), EmbiveError> {
+        while let Some((INTERRUPT_ENTRY, mut reader)) = self.replay.due()? {
+            let line = reader.byte()? as u32;
+            if !self.trap.accepts() {
+                return Err(EmbiveError::ReplayDiverged);
+            }
+
+            // Host interrupts are kept pending (ignored while replaying)
+            let pending = self.interrupt.pending;
+            self.interrupt.pending = 0;
+            self.interrupt.raise(line)?;
+            let claimed = self.interrupt.claim();
+            self.interrupt.pending = pending;
+            if claimed != Some(line) {
+                return Err(EmbiveError::ReplayDiverged);
+            }
+
+            let cause = trap::INTERRUPT_CAUSE | (trap::LOCAL_INTERRUPT_BASE + line);
+            let handler = self
+                .trap
+                .take(cause, self.program_counter, 0)
+                .ok_or(EmbiveError::ReplayDiverged)?;
+            self.invalidate_fetch();
+            self.program_counter = handler;
+            self.replay.consume(reader);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csr::{CsrHandler, TIME};
+    use crate::engine::Config;
+    use crate::memory::SliceMemory;
+    use crate::syscall::{Arg, Errno, SyscallContract};
+
+    static CONTRACTS: [SyscallContract; 1] =
+        [SyscallContract::new(1, "read", &[Arg::BufferMut("buf")])];
+
+    fn read(_nr: i32, args: &[i32; SYSCALL_ARGS], memory: &mut SliceMemory) -> Result<i32, i32> {
+        memory.store_bytes(args[0] as u32, &[1, 2, 3, 4]).unwrap();
+        Ok(4)
+    }
+
+    struct Clock;
+
+    impl CsrHandler for Clock {
+        fn read(csr: u16) -> Option<u32> {
+            (csr == TIME).then_some(1234)
+        }
+    }
+
+    struct Stopped;
+
+    impl CsrHandler for Stopped {
+        fn read(csr: u16) -> Option<u32> {
+            (csr == TIME).then_some(0)
+        }
+    }
+
+    const CODE: &[u8] = &[
+        0x37, 0x05, 0x00, 0x80, // lui   a0, 0x80000 (buf)
+        0x93, 0x05, 0x40, 0x00, // li    a1, 4       (len)
+        0x93, 0x08, 0x10, 0x00, // li    a7, 1       (read)
+        0x73, 0x00, 0x00, 0x00, // ecall
+        0x73, 0x26, 0x10, 0xc0, // rdtime a2
+        0x73, 0x00, 0x10, 0x00, // ebreak
+    ];
+
+    #[test]
+    fn test_record_replay() {
+        let mut log = [0; 64];
+        let mut ram = [0; 4];
+        let (registers, len) = {
+            let mut memory = SliceMemory::new(CODE, &mut ram);
+            let config = Config::default()
+                .with_syscall_fn(Some(read))
+                .with_syscall_contracts(&CONTRACTS, Errno::InvalidPointer.code())
+                .with_csr::<Clock>();
+            let mut engine = Engine::new(&mut memory, config).unwrap();
+            engine.start_recording(&mut log);
+            assert_eq!(engine.run(), Ok(false));
+            (engine.registers, engine.stop_replay())
+        };
+        assert_eq!(ram, [1, 2, 3, 4]);
+
+        // Different host, same guest execution
+        let mut replayed = [0; 4];
+        {
+            let mut memory = SliceMemory::new(CODE, &mut replayed);
+            let config = Config::default()
+                .with_syscall_fn(Some(|_, _, _| Err(-1)))
+                .with_syscall_contracts(&CONTRACTS, Errno::InvalidPointer.code())
+                .with_csr::<Stopped>();
+            let mut engine = Engine::new(&mut memory, config).unwrap();
+            engine.start_replay(&log[..len]);
+            assert!(engine.replaying());
+            assert_eq!(engine.run(), Ok(false));
+            assert_eq!(engine.registers, registers);
+            assert_eq!(engine.registers.get(12), Ok(1234));
+            assert!(!engine.replaying());
+            assert_eq!(engine.stop_replay(), len);
+        }
+        assert_eq!(replayed, ram);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut full = [0; 8];
+        let diverged = [SYSCALL_ENTRY, 5, 0, 0];
+        let invalid = [CSR_ENTRY, 0x80];
+        let mut ram = [0; 4];
+        let mut memory = SliceMemory::new(CODE, &mut ram);
+        let config = Config::default()
+            .with_syscall_fn(Some(|_, _, _| Ok(4)))
+            .with_syscall_contracts(&CONTRACTS, Errno::InvalidPointer.code())
+            .with_csr::<Clock>();
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        // Log full
+        engine.start_recording(&mut full);
+        assert_eq!(engine.run(), Err(EmbiveError::ReplayLogFull));
+        assert_eq!(engine.stop_replay(), 0);
+
+        // The syscall isn't due yet
+        engine.reset();
+        engine.start_replay(&diverged);
+        assert_eq!(engine.run(), Err(EmbiveError::ReplayDiverged));
+
+        // Truncated entry
+        engine.reset();
+        engine.start_replay(&invalid);
+        assert_eq!(engine.run(), Err(EmbiveError::ReplayDiverged));
+    }
+
+    #[cfg(feature = "interrupt")]
+    #[test]
+    fn test_interrupts() {
+        let code = &[
+            0x93, 0x02, 0x00, 0x01, // li   t0, 0x10 (handler)
+            0x73, 0x90, 0x52, 0x30, // csrw mtvec, t0
+            0x13, 0x05, 0x10, 0x00, // li   a0, 1
+            0x73, 0x00, 0x10, 0x00, // ebreak
+            // Handler
+            0xf3, 0x25, 0x20, 0x34, // csrr a1, mcause
+            0x73, 0x00, 0x20, 0x30, // mret
+        ];
+        let mut log = [0; 32];
+        let (registers, len) = {
+            let mut memory = SliceMemory::new(code, &mut []);
+            let config = Config::default().with_guest_interrupts(true);
+            let mut engine = Engine::new(&mut memory, config).unwrap();
+            engine.start_recording(&mut log);
+            engine.raise_interrupt(3).unwrap();
+            assert_eq!(engine.run(), Ok(false));
+            (engine.registers, engine.stop_replay())
+        };
+
+        // Delivered at the same instruction
+        let mut memory = SliceMemory::new(code, &mut []);
+        let config = Config::default().with_guest_interrupts(true);
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+        engine.start_replay(&log[..len]);
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers, registers);
+        assert_eq!(engine.trap.mepc, 0x8);
+        assert!(!engine.replaying());
+    }
+}
@@ -0,0 +1,625 @@
+//! Guest Runtime Module
+//!
+//! Engine-side counterpart of a guest runtime support crate, so Rust guests get `println!`, a `#[panic_handler]`
+//! and a `#[global_allocator]` against any compliant host. Enabled with [`crate::engine::Config::with_runtime`],
+//! backed by a host [`RuntimeHost`] implementation.
+//!
+//! Both sides are versioned together with [`RUNTIME_VERSION`], incremented on incompatible changes.
+//!
+//! Entry convention:
+//! - `a0` holds [`RUNTIME_VERSION`] at the entry point (after [`crate::engine::Engine::new`] and
+//!   [`crate::engine::Engine::reset`]), the guest runtime should halt if it doesn't match.
+//! - The guest sets up its own stack pointer and static data (ex.: from linker symbols).
+//!
+//! Syscall numbers (`a7`) don't overlap the RISC-V Linux ABI ones, results use the Embive convention
+//! (`a0`: [`Errno`] code, `a1`: value):
+//! - [`SYS_PRINT`]: Print `a2` bytes from `a1` to stream `a0` ([`STDOUT`] or [`STDERR`]).
+//!   Returns the bytes printed.
+//! - [`SYS_PANIC`]: Report a panic and halt. Message at `a0` (`a1` bytes), file at `a2` (`a3` bytes),
+//!   line `a4` and column `a5` (check [`PanicReport`], [`crate::engine::Engine::panic_report`]).
+//! - [`SYS_ALLOC`]: Allocate `a0` bytes aligned to `a1` (a power of two, at most [`ALLOC_ALIGN`]) from the
+//!   configured [`Heap`]. Returns the address ([`Errno::QuotaExceeded`] if the heap is exhausted).
+//! - [`SYS_DEALLOC`]: Free the allocation at `a0`.
+//! - [`SYS_EXIT`]: Halt the guest with exit code `a0` ([`crate::engine::Engine::runtime_exit_code`]).
+//! - [`SYS_TICKS`]: Read the host clock ([`crate::engine::Config::tick_fn`]), 64-bit value in `a1` (low)
+//!   and `a2` (high). Returns [`Errno::NotSupported`] if no tick function is configured.
+//!
+//! The allocator keeps its block headers inside the heap (guest memory), so the host doesn't need
+//! dynamic memory. The heap is reinitialized when the engine is reset.
+//!
+//! ```
+//! use embive::{
+//!     engine::{Config, Engine},
+//!     memory::{Heap, SliceMemory, RAM_OFFSET},
+//!     runtime::RuntimeHost,
+//!     syscall::Errno,
+//! };
+//!
+//! struct Console;
+//!
+//! impl RuntimeHost for Console {
+//!     fn print(_stream: i32, data: &[u8]) -> Result<usize, Errno> {
+//!         Ok(data.len()) // ex.: forward to a UART
+//!     }
+//! }
+//!
+//! let code = &[
+//!     0x13, 0x05, 0x00, 0x02, // li   a0, 32
+//!     0x93, 0x05, 0x80, 0x00, // li   a1, 8
+//!     0x93, 0x08, 0xb0, 0x3e, // li   a7, 1003 (alloc)
+//!     0x73, 0x00, 0x00, 0x00, // ecall
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//! ];
+//! let mut ram = [0; 1024];
+//! let mut memory = SliceMemory::new(code, &mut ram);
+//! let config = Config::default().with_runtime::<Console>(Heap::new(RAM_OFFSET + 512, 256));
+//! let mut engine = Engine::new(&mut memory, config).unwrap();
+//!
+//! assert_eq!(engine.run(), Ok(false));
+//! assert_eq!(engine.registers.get(11).unwrap() as u32, RAM_OFFSET + 512 + 8);
+//! ```
+
+use crate::engine::SYSCALL_ARGS;
+use crate::memory::{helpers, Heap, Memory};
+use crate::syscall::Errno;
+
+/// Guest runtime interface version.
+pub const RUNTIME_VERSION: u32 = 1;
+
+/// Syscall number: print to a stream.
+pub const SYS_PRINT: i32 = 1001;
+/// Syscall number: report a panic (doesn't return).
+pub const SYS_PANIC: i32 = 1002;
+/// Syscall number: allocate heap memory.
+pub const SYS_ALLOC: i32 = 1003;
+/// Syscall number: free heap memory.
+pub const SYS_DEALLOC: i32 = 1004;
+/// Syscall number: exit the guest (doesn't return).
+pub const SYS_EXIT: i32 = 1005;
+/// Syscall number: read the host clock.
+pub const SYS_TICKS: i32 = 1006;
+
+/// Standard output stream.
+pub const STDOUT: i32 = 1;
+/// Standard error stream.
+pub const STDERR: i32 = 2;
+
+/// Bytes passed to the host per [`RuntimeHost::print`] call.
+pub const PRINT_CHUNK: usize = 64;
+/// Maximum panic message length, longer messages are truncated.
+pub const PANIC_MESSAGE_MAX: usize = 128;
+/// Maximum panic file name length, longer names are truncated.
+pub const PANIC_FILE_MAX: usize = 64;
+/// Maximum allocation alignment (and allocator granularity) in bytes.
+pub const ALLOC_ALIGN: u32 = 8;
+
+/// Allocator block header size (block size and used flag, magic).
+const HEADER_SIZE: u32 = 8;
+/// Allocator block header magic, detects invalid frees and heap corruption.
+const HEADER_MAGIC: u32 = 0x5642_4D45; // "EMBV"
+/// Allocator block used flag (in the block size word).
+const USED: u32 = 1;
+
+/// Guest panic report (check [`crate::engine::Engine::panic_report`]).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PanicReport {
+    message: [u8; PANIC_MESSAGE_MAX],
+    message_len: usize,
+    file: [u8; PANIC_FILE_MAX],
+    file_len: usize,
+    /// Source line (0 = Unknown).
+    pub line: u32,
+    /// Source column (0 = Unknown).
+    pub column: u32,
+}
+
+impl PanicReport {
+    /// Panic message (truncated to [`PANIC_MESSAGE_MAX`] bytes, at a character boundary).
+    pub fn message(&self) -> &str {
+        utf8_prefix(&self.message[..self.message_len])
+    }
+
+    /// Source file name (truncated to [`PANIC_FILE_MAX`] bytes, at a character boundary).
+    pub fn file(&self) -> &str {
+        utf8_prefix(&self.file[..self.file_len])
+    }
+}
+
+/// Longest valid UTF-8 prefix of a byte slice.
+fn utf8_prefix(bytes: &[u8]) -> &str {
+    match core::str::from_utf8(bytes) {
+        Ok(string) => string,
+        // Unwrap is safe because the prefix is guaranteed to be valid UTF-8.
+        Err(error) => core::str::from_utf8(&bytes[..error.valid_up_to()]).unwrap(),
+    }
+}
+
+/// Host side of the guest runtime services (check the [module documentation](self)).
+pub trait RuntimeHost {
+    /// Print to a stream.
+    ///
+    /// Arguments:
+    /// - `stream`: Stream ([`STDOUT`] or [`STDERR`]).
+    /// - `data`: Data to print (at most [`PRINT_CHUNK`] bytes).
+    ///
+    /// Returns:
+    /// - `Ok(usize)`: Bytes printed (fewer bytes end the guest print early).
+    /// - `Err(Errno)`: Failed to print.
+    fn print(stream: i32, data: &[u8]) -> Result<usize, Errno>;
+
+    /// The guest panicked (the engine halts).
+    ///
+    /// Arguments:
+    /// - `report`: Panic report.
+    fn panic(_report: &PanicReport) {}
+
+    /// The guest exited (the engine halts).
+    ///
+    /// Arguments:
+    /// - `code`: Exit code.
+    fn exit(_code: i32) {}
+}
+
+/// Host functions of a [`RuntimeHost`] implementation (check [`crate::engine::Config::with_runtime`]).
+#[derive(Debug, Clone, Copy)]
+pub struct RuntimeHooks {
+    print: fn(i32, &[u8]) -> Result<usize, Errno>,
+    panic: fn(&PanicReport),
+    exit: fn(i32),
+}
+
+impl RuntimeHooks {
+    /// Get the host functions of a [`RuntimeHost`] implementation.
+    ///
+    /// Generic Arguments:
+    /// - `H`: Host implementation.
+    pub fn of<H: RuntimeHost>() -> Self {
+        RuntimeHooks {
+            print: H::print,
+            panic: H::panic,
+            exit: H::exit,
+        }
+    }
+}
+
+/// Guest runtime state, kept by the engine.
+#[derive(Debug, Default)]
+pub(crate) struct Runtime {
+    /// Heap was initialized (a single free block).
+    heap_ready: bool,
+    /// Guest exit code.
+    pub(crate) exit_code: Option<i32>,
+    /// Guest panic report.
+    pub(crate) panic: Option<PanicReport>,
+}
+
+impl Runtime {
+    /// Reset the runtime state (the heap is reinitialized on the next allocation).
+    pub(crate) fn reset(&mut self) {
+        *self = Runtime::default();
+    }
+}
+
+/// Outcome of a guest runtime syscall.
+pub(crate) enum Outcome {
+    /// Syscall result (value or [`Errno`] code).
+    Return(Result<i32, i32>),
+    /// Guest exited or panicked, halt.
+    Halt,
+}
+
+/// Handle a guest runtime syscall (check the [module documentation](self)).
+///
+/// Arguments:
+/// - `nr`: Syscall number.
+/// - `args`: Syscall arguments.
+/// - `memory`: System memory (code + RAM).
+/// - `hooks`: Host functions.
+/// - `heap`: Guest heap.
+/// - `runtime`: Guest runtime state.
+///
+/// Returns:
+/// - `Some(Outcome)`: Syscall outcome.
+/// - `None`: Not a guest runtime syscall.
+pub(crate) fn syscall<M: Memory>(
+    nr: i32,
+    args: &[i32; SYSCALL_ARGS],
+    memory: &mut M,
+    hooks: &RuntimeHooks,
+    heap: Heap,
+    runtime: &mut Runtime,
+) -> Option<Outcome> {
+    let result = match nr {
+        SYS_PRINT => print(hooks, args[0], args[1] as u32, args[2] as u32, memory),
+        SYS_ALLOC => {
+            let ready = match runtime.heap_ready {
+                true => Ok(()),
+                false => init_heap(heap, memory).inspect(|_| runtime.heap_ready = true),
+            };
+            ready.and_then(|_| alloc(heap, args[0] as u32, args[1] as u32, memory))
+        }
+        SYS_DEALLOC if runtime.heap_ready => dealloc(heap, args[0] as u32, memory).map(|_| 0),
+        SYS_DEALLOC => Err(Errno::InvalidPointer),
+        SYS_PANIC => {
+            let report = panic_report(args, memory);
+            (hooks.panic)(&report);
+            runtime.panic = Some(report);
+            return Some(Outcome::Halt);
+        }
+        SYS_EXIT => {
+            (hooks.exit)(args[0]);
+            runtime.exit_code = Some(args[0]);
+            return Some(Outcome::Halt);
+        }
+        _ => return None,
+    };
+
+    Some(Outcome::Return(result.map_err(|error| error.code())))
+}
+
+/// Print guest memory to a stream, in chunks.
+fn print<M: Memory>(
+    hooks: &RuntimeHooks,
+    stream: i32,
+    address: u32,
+    len: u32,
+    memory: &M,
+) -> Result<i32, Errno> {
+    let mut chunk = [0; PRINT_CHUNK];
+    let mut printed = 0u32;
+    while printed < len {
+        let size = ((len - printed) as usize).min(PRINT_CHUNK);
+        read(address.wrapping_add(printed), &mut chunk[..size], memory)?;
+
+        let count = (hooks.print)(stream, &chunk[..size])?.min(size);
+        printed += count as u32;
+        if count < size {
+            // Short print
+            break;
+        }
+    }
+
+    Ok(printed as i32)
+}
+
+/// Copy the panic details from guest memory (invalid strings are reported empty).
+fn panic_report<M: Memory>(args: &[i32; SYSCALL_ARGS], memory: &M) -> PanicReport {
+    let mut report = PanicReport {
+        message: [0; PANIC_MESSAGE_MAX],
+        message_len: (args[1] as u32 as usize).min(PANIC_MESSAGE_MAX),
+        file: [0; PANIC_FILE_MAX],
+        file_len: (args[3] as u32 as usize).min(PANIC_FILE_MAX),
+        line: args[4] as u32,
+        column: args[5] as u32,
+    };
+
+    if read(
+        args[0] as u32,
+        &mut report.message[..report.message_len],
+        memory,
+    )
+    .is_err()
+    {
+        report.message_len = 0;
+    }
+    if read(args[2] as u32, &mut report.file[..report.file_len], memory).is_err() {
+        report.file_len = 0;
+    }
+
+    report
+}
+
+/// Read guest memory into a buffer.
+fn read<M: Memory>(address: u32, buffer: &mut [u8], memory: &M) -> Result<(), Errno> {
+    helpers::read_slice(memory, address, buffer).map_err(|_| Errno::InvalidPointer)
+}
+
+/// Heap bounds, aligned to [`ALLOC_ALIGN`] (start and end addresses).
+fn bounds(heap: Heap) -> (u32, u32) {
+    let start = heap.start.wrapping_add(ALLOC_ALIGN - 1) & !(ALLOC_ALIGN - 1);
+    let end = heap.start.saturating_add(heap.size) & !(ALLOC_ALIGN - 1);
+    (start, end.max(start))
+}
+
+/// Load a block header (size and used flag).
+fn load_header<M: Memory>(address: u32, memory: &M) -> Result<(u32, bool), Errno> {
+    let size = memory.load(address).map_err(|_| Errno::InvalidPointer)?;
+    let magic = memory
+        .load(address.wrapping_add(4))
+        .map_err(|_| Errno::InvalidPointer)?;
+    if u32::from_le_bytes(magic) != HEADER_MAGIC {
+        return Err(Errno::InvalidPointer);
+    }
+
+    let size = u32::from_le_bytes(size);
+    Ok((size & !USED, size & USED != 0))
+}
+
+/// Store a block header (size and used flag).
+fn store_header<M: Memory>(
+    address: u32,
+    size: u32,
+    used: bool,
+    memory: &mut M,
+) -> Result<(), Errno> {
+    let size = size | if used { USED } else { 0 };
+    memory
+        .store(address, size.to_le_bytes())
+        .and_then(|_| memory.store(address.wrapping_add(4), HEADER_MAGIC.to_le_bytes()))
+        .map_err(|_| Errno::InvalidPointer)
+}
+
+/// Initialize the heap as a single free block.
+fn init_heap<M: Memory>(heap: Heap, memory: &mut M) -> Result<(), Errno> {
+    let (start, end) = bounds(heap);
+    if end - start < 2 * HEADER_SIZE {
+        return Err(Errno::QuotaExceeded);
+    }
+
+    store_header(start, end - start, false, memory)
+}
+
+/// Allocate from the heap (first fit, merging adjacent free blocks on the way).
+fn alloc<M: Memory>(heap: Heap, size: u32, align: u32, memory: &mut M) -> Result<i32, Errno> {
+    if !align.is_power_of_two() {
+        return Err(Errno::InvalidArgument);
+    }
+    if align > ALLOC_ALIGN {
+        return Err(Errno::NotSupported);
+    }
+
+    let needed = size
+        .max(1)
+        .checked_add(HEADER_SIZE + ALLOC_ALIGN - 1)
+        .ok_or(Errno::QuotaExceeded)?
+        & !(ALLOC_ALIGN - 1);
+
+    let (mut address, end) = bounds(heap);
+    while address < end {
+        let (mut block, used) = load_header(address, memory)?;
+        if block < HEADER_SIZE || block > end - address {
+            // Corrupted heap
+            return Err(Errno::InvalidPointer);
+        }
+
+        if !used {
+            // Merge the following free blocks
+            while block < end - address {
+                match load_header(address + block, memory)? {
+                    (next, false) if next <= end - address - block => block += next,
+                    _ => break,
+                }
+            }
+
+            if block >= needed {
+                if block - needed >= 2 * HEADER_SIZE {
+                    // Split, keeping the remainder free
+                    store_header(address + needed, block - needed, false, memory)?;
+                    block = needed;
+                }
+                store_header(address, block, true, memory)?;
+                return Ok((address + HEADER_SIZE) as i32);
+            }
+
+            store_header(address, block, false, memory)?;
+        }
+
+        address += block;
+    }
+
+    Err(Errno::QuotaExceeded)
+}
+
+/// Free an allocation.
+fn dealloc<M: Memory>(heap: Heap, pointer: u32, memory: &mut M) -> Result<(), Errno> {
+    let (start, end) = bounds(heap);
+    let address = pointer.wrapping_sub(HEADER_SIZE);
+    if address < start || address >= end || address % ALLOC_ALIGN != 0 {
+        return Err(Errno::InvalidPointer);
+    }
+
+    match load_header(address, memory)? {
+        (size, true) => store_header(address, size, false, memory),
+        // Double free
+        (_, false) => Err(Errno::InvalidPointer),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Config, Engine};
+    use crate::memory::{SliceMemory, RAM_OFFSET};
+    use crate::register::Register;
+    use std::vec::Vec;
+
+    std::thread_local! {
+        static OUTPUT: core::cell::RefCell<Vec<u8>> = const { core::cell::RefCell::new(Vec::new()) };
+        static PANICKED: core::cell::Cell<Option<u32>> = const { core::cell::Cell::new(None) };
+    }
+
+    struct Host;
+
+    impl RuntimeHost for Host {
+        fn print(stream: i32, data: &[u8]) -> Result<usize, Errno> {
+            if stream != STDOUT {
+                return Err(Errno::InvalidArgument);
+            }
+            OUTPUT.with(|output| output.borrow_mut().extend_from_slice(data));
+            Ok(data.len())
+        }
+
+        fn panic(report: &PanicReport) {
+            PANICKED.with(|panicked| panicked.set(Some(report.line)));
+        }
+    }
+
+    fn call(
+        nr: i32,
+        args: &[i32],
+        memory: &mut SliceMemory,
+        runtime: &mut Runtime,
+    ) -> Option<Result<i32, i32>> {
+        let mut syscall_args = [0; SYSCALL_ARGS];
+        syscall_args[..args.len()].copy_from_slice(args);
+        let heap = Heap::new(RAM_OFFSET + 64, 64);
+        match syscall(
+            nr,
+            &syscall_args,
+            memory,
+            &RuntimeHooks::of::<Host>(),
+            heap,
+            runtime,
+        )? {
+            Outcome::Return(result) => Some(result),
+            Outcome::Halt => None,
+        }
+    }
+
+    #[test]
+    fn test_print() {
+        let mut ram = [0; 128];
+        ram[..100].fill(b'a');
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut runtime = Runtime::default();
+
+        let result = call(
+            SYS_PRINT,
+            &[STDOUT, RAM_OFFSET as i32, 100],
+            &mut memory,
+            &mut runtime,
+        );
+        assert_eq!(result, Some(Ok(100)));
+        assert_eq!(OUTPUT.with(|output| output.borrow().len()), 100);
+
+        let result = call(
+            SYS_PRINT,
+            &[STDERR, RAM_OFFSET as i32, 1],
+            &mut memory,
+            &mut runtime,
+        );
+        assert_eq!(result, Some(Err(Errno::InvalidArgument.code())));
+
+        let result = call(SYS_PRINT, &[STDOUT, 0x1000, 1], &mut memory, &mut runtime);
+        assert_eq!(result, Some(Err(Errno::InvalidPointer.code())));
+    }
+
+    #[test]
+    fn test_alloc() {
+        let mut ram = [0xFF; 128];
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut runtime = Runtime::default();
+        let base = (RAM_OFFSET + 64) as i32;
+
+        // Heap is 64 bytes: 16 + 24 + 24
+        let a = call(SYS_ALLOC, &[5, 4], &mut memory, &mut runtime);
+        assert_eq!(a, Some(Ok(base + 8)));
+        let b = call(SYS_ALLOC, &[16, 8], &mut memory, &mut runtime);
+        assert_eq!(b, Some(Ok(base + 24)));
+        let c = call(SYS_ALLOC, &[16, 8], &mut memory, &mut runtime);
+        assert_eq!(c, Some(Ok(base + 48)));
+        let result = call(SYS_ALLOC, &[1, 1], &mut memory, &mut runtime);
+        assert_eq!(result, Some(Err(Errno::QuotaExceeded.code())));
+
+        // Free and merge the first two blocks
+        assert_eq!(
+            call(SYS_DEALLOC, &[base + 24], &mut memory, &mut runtime),
+            Some(Ok(0))
+        );
+        assert_eq!(
+            call(SYS_DEALLOC, &[base + 8], &mut memory, &mut runtime),
+            Some(Ok(0))
+        );
+        let d = call(SYS_ALLOC, &[32, 8], &mut memory, &mut runtime);
+        assert_eq!(d, Some(Ok(base + 8)));
+
+        // Invalid frees and alignments
+        let result = call(SYS_DEALLOC, &[base + 8 + 16], &mut memory, &mut runtime);
+        assert_eq!(result, Some(Err(Errno::InvalidPointer.code())));
+        let result = call(SYS_DEALLOC, &[base + 48], &mut memory, &mut runtime);
+        assert_eq!(result, Some(Ok(0)));
+        let result = call(SYS_DEALLOC, &[base + 48], &mut memory, &mut runtime);
+        assert_eq!(result, Some(Err(Errno::InvalidPointer.code())));
+        let result = call(SYS_ALLOC, &[1, 3], &mut memory, &mut runtime);
+        assert_eq!(result, Some(Err(Errno::InvalidArgument.code())));
+        let result = call(SYS_ALLOC, &[1, 16], &mut memory, &mut runtime);
+        assert_eq!(result, Some(Err(Errno::NotSupported.code())));
+
+        // Reset reinitializes the heap
+        runtime.reset();
+        let result = call(SYS_DEALLOC, &[base + 8], &mut memory, &mut runtime);
+        assert_eq!(result, Some(Err(Errno::InvalidPointer.code())));
+        let result = call(SYS_ALLOC, &[48, 8], &mut memory, &mut runtime);
+        assert_eq!(result, Some(Ok(base + 8)));
+    }
+
+    #[test]
+    fn test_panic() {
+        let mut ram = [0; 128];
+        ram[..10].copy_from_slice(b"oops\xF0\x9Fmain");
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let mut runtime = Runtime::default();
+        let base = RAM_OFFSET as i32;
+
+        let args = [base, 6, base + 6, 2, 12, 5];
+        assert_eq!(call(SYS_PANIC, &args, &mut memory, &mut runtime), None);
+        assert_eq!(PANICKED.with(|panicked| panicked.get()), Some(12));
+
+        // Truncated at a character boundary
+        let report = runtime.panic.unwrap();
+        assert_eq!(report.message(), "oops");
+        assert_eq!(report.file(), "ma");
+        assert_eq!((report.line, report.column), (12, 5));
+
+        // Invalid strings are reported empty
+        let args = [0x1000, 4, base, 0, 0, 0];
+        assert_eq!(call(SYS_PANIC, &args, &mut memory, &mut runtime), None);
+        assert_eq!(runtime.panic.unwrap().message(), "");
+    }
+
+    #[test]
+    fn test_exit() {
+        let mut memory = SliceMemory::new(&[], &mut []);
+        let mut runtime = Runtime::default();
+
+        assert_eq!(call(SYS_EXIT, &[3], &mut memory, &mut runtime), None);
+        assert_eq!(runtime.exit_code, Some(3));
+
+        // Not a runtime syscall
+        let hooks = RuntimeHooks::of::<Host>();
+        let result = syscall(
+            0,
+            &[0; SYSCALL_ARGS],
+            &mut memory,
+            &hooks,
+            Heap::new(0, 0),
+            &mut runtime,
+        );
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_engine() {
+        let code = &[
+            0x13, 0x05, 0x70, 0x00, // li   a0, 7
+            0x93, 0x08, 0xd0, 0x3e, // li   a7, 1005 (exit)
+            0x73, 0x00, 0x00, 0x00, // ecall
+        ];
+        let mut memory = SliceMemory::new(code, &mut []);
+        let config = Config::default().with_runtime::<Host>(Heap::new(RAM_OFFSET, 0));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        // Entry convention
+        let version = engine.registers.get(Register::A0 as usize);
+        assert_eq!(version, Ok(RUNTIME_VERSION as i32));
+
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.runtime_exit_code(), Some(7));
+        assert_eq!(engine.panic_report(), None);
+
+        engine.reset();
+        assert_eq!(engine.runtime_exit_code(), None);
+        let version = engine.registers.get(Register::A0 as usize);
+        assert_eq!(version, Ok(RUNTIME_VERSION as i32));
+    }
+}
@@ -0,0 +1,564 @@
+//! Syscall Module
+//!
+//! ## Error Codes
+//! Syscalls return an error code in `a0` (0 = Success). [`Errno`] defines the standard codes, used by
+//! every built-in service (ex.: software interrupts, persistent regions, contracts) and recommended
+//! for syscall functions, so guests can handle host errors portably:
+//!
+//! ```
+//! use embive::syscall::Errno;
+//!
+//! fn syscall(nr: i32, args: &[i32; 7], memory: &mut embive::memory::SliceMemory) -> Result<i32, i32> {
+//!     match nr {
+//!         1 => Ok(args[0] + args[1]),
+//!         _ => Err(Errno::NotSupported.into()),
+//!     }
+//! }
+//! # let mut memory = embive::memory::SliceMemory::new(&[], &mut []);
+//! # assert_eq!(syscall(2, &[0; 7], &mut memory), Err(5));
+//! ```
+//!
+//! ## Handlers
+//! Stateful hosts give each engine its own handler ([`crate::engine::Engine::set_syscalls`]),
+//! instead of using globals from a syscall function ([`crate::engine::Config::syscall_fn`]):
+//! - Closures capturing host state.
+//! - Types implementing [`Syscalls`] (ex.: multiple syscall tables).
+//!
+//! ```
+//! use embive::{engine::{Config, Engine, SYSCALL_ARGS}, memory::SliceMemory};
+//!
+//! let code = &[
+//!     0x73, 0x00, 0x00, 0x00, // ecall
+//!     0x73, 0x00, 0x00, 0x00, // ecall
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//! ];
+//! let mut calls = 0;
+//! let mut syscall = |_nr: i32, _args: &[i32; SYSCALL_ARGS], _memory: &mut SliceMemory| {
+//!     calls += 1;
+//!     Ok(calls)
+//! };
+//! let mut memory = SliceMemory::new(code, &mut []);
+//! let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+//! engine.set_syscalls(Some(&mut syscall));
+//!
+//! assert_eq!(engine.run(), Ok(false));
+//! assert_eq!(engine.registers.get(11), Ok(2));
+//! ```
+//!
+//!
+//! ```
+//! use embive::{engine::{Config, Engine, SYSCALL_ARGS}, memory::SliceMemory, syscall::Syscalls};
+//!
+//! struct Counter(i32);
+//!
+//! impl<M: embive::memory::Memory> Syscalls<M> for Counter {
+//!     fn syscall(&mut self, _nr: i32, args: &[i32; SYSCALL_ARGS], _memory: &mut M) -> Result<i32, i32> {
+//!         self.0 += args[0];
+//!         Ok(self.0)
+//!     }
+//! }
+//!
+//! let code = &[
+//!     0x13, 0x05, 0x50, 0x00, // li   a0, 5
+//!     0x73, 0x00, 0x00, 0x00, // ecall
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//! ];
+//! let mut counter = Counter(10);
+//! let mut memory = SliceMemory::new(code, &mut []);
+//! let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+//! engine.set_syscalls(Some(&mut counter));
+//!
+//! assert_eq!(engine.run(), Ok(false));
+//! assert_eq!(engine.registers.get(11), Ok(15));
+//! ```
+//!
+//! ## Blocking Syscalls
+//! With [`crate::engine::Config::suspend_on_would_block`], returning [`Errno::WouldBlock`] suspends the engine
+//! waiting for the I/O handle in `a0` ([`crate::engine::Engine::waiting_for`]), instead of returning the error.
+//! The host wakes it when data arrives ([`crate::engine::Engine::wake`]), and the syscall is retried,
+//! so guests use simple blocking APIs while the host stays event-driven.
+//! Event-loop guests can wait for any of a set of handles with [`crate::engine::Config::poll_nr`].
+//!
+//! ## Deferred Syscalls
+//! With [`crate::engine::Config::defer_syscalls`], there is no syscall function: the engine suspends on each
+//! syscall ([`crate::engine::WaitingFor::Syscall`]), the host takes the request ([`crate::engine::Engine::syscall_request`])
+//! and responds whenever it is done ([`crate::engine::Engine::respond`]), ex.: from an async task or over FFI.
+//!
+//! ## Contracts
+//! Hosts can declare the signature of each syscall ([`SyscallContract`]) in
+//! [`crate::engine::Config::syscall_contracts`]. Before calling the syscall function, the engine checks that
+//! every pointer/length pair is inside the guest memory (writable buffers must be in RAM),
+//! returning the configured error code ([`Errno::InvalidPointer`] by default) to the guest otherwise. Handlers can then access buffers
+//! without repeating these checks. Capability arguments ([`Arg::Capability`]) are resolved to host resource ids,
+//! returning [`Errno::AccessDenied`] if not granted. Contracts also format syscalls for traces ([`SyscallContract::display`]).
+//!
+//! ```
+//! use embive::syscall::{Arg, SyscallContract};
+//!
+//! const WRITE: SyscallContract = SyscallContract::new(
+//!     64,
+//!     "write",
+//!     &[Arg::Value("fd"), Arg::Buffer("buf")], // a0: fd, a1: buf pointer, a2: buf length
+//! );
+//!
+//! let args = [1, 0x8000_0010u32 as i32, 12, 0, 0, 0, 0];
+//! assert_eq!(
+//!     format!("{}", WRITE.display(&args)),
+//!     "write(fd=1, buf=0x80000010[12])"
+//! );
+//! ```
+
+pub mod capability;
+#[cfg(feature = "crypto")]
+pub mod crypto;
+pub mod format;
+#[cfg(feature = "log")]
+pub mod log;
+pub mod trace;
+
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::engine::SYSCALL_ARGS;
+use crate::memory::{Memory, RAM_OFFSET};
+use capability::{Capabilities, Kind, Rights};
+use trace::TraceContext;
+
+/// Syscall handler (check the [module documentation](self)).
+///
+/// Generic Arguments:
+/// - `M`: System memory.
+pub trait Syscalls<M: Memory> {
+    /// Handle a syscall (called by the `ecall` instruction, check [`SyscallFn`]).
+    ///
+    /// Arguments:
+    /// - `nr`: Syscall number (`a7`).
+    /// - `args`: Arguments (`a0` to `a6`).
+    /// - `memory`: System Memory (code + RAM).
+    ///
+    /// Returns:
+    /// - `Result<i32, i32>`: value (`a1`), error (`a0`).
+    fn syscall(&mut self, nr: i32, args: &[i32; SYSCALL_ARGS], memory: &mut M) -> Result<i32, i32>;
+}
+
+/// Syscall functions ([`SyscallFn`]) and closures, capturing host state.
+impl<M: Memory, F> Syscalls<M> for F
+where
+    F: FnMut(i32, &[i32; SYSCALL_ARGS], &mut M) -> Result<i32, i32>,
+{
+    #[inline(always)]
+    fn syscall(&mut self, nr: i32, args: &[i32; SYSCALL_ARGS], memory: &mut M) -> Result<i32, i32> {
+        self(nr, args, memory)
+    }
+}
+
+/// Standard syscall error codes (check the [module documentation](self)).
+/// Codes are stable and positive, `0` is success.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(i32)]
+pub enum Errno {
+    /// Access denied (ex.: not permitted by the host configuration).
+    AccessDenied = 1,
+    /// Operation would block, try again later (ex.: queue full, no data available).
+    WouldBlock = 2,
+    /// Invalid pointer (ex.: buffer outside of the guest memory).
+    InvalidPointer = 3,
+    /// Quota or resource limit exceeded.
+    QuotaExceeded = 4,
+    /// Syscall or operation not supported.
+    NotSupported = 5,
+    /// Invalid argument.
+    InvalidArgument = 6,
+}
+
+impl Errno {
+    /// All error codes.
+    pub const ALL: [Errno; 6] = [
+        Errno::AccessDenied,
+        Errno::WouldBlock,
+        Errno::InvalidPointer,
+        Errno::QuotaExceeded,
+        Errno::NotSupported,
+        Errno::InvalidArgument,
+    ];
+
+    /// Error code (`a0`).
+    pub const fn code(&self) -> i32 {
+        *self as i32
+    }
+
+    /// Get the error of a code.
+    ///
+    /// Arguments:
+    /// - `code`: Error code (`a0`).
+    ///
+    /// Returns:
+    /// - `Some(Errno)`: Standard error.
+    /// - `None`: Success (0) or non-standard code.
+    pub const fn from_code(code: i32) -> Option<Errno> {
+        match code {
+            1 => Some(Errno::AccessDenied),
+            2 => Some(Errno::WouldBlock),
+            3 => Some(Errno::InvalidPointer),
+            4 => Some(Errno::QuotaExceeded),
+            5 => Some(Errno::NotSupported),
+            6 => Some(Errno::InvalidArgument),
+            _ => None,
+        }
+    }
+}
+
+impl From<Errno> for i32 {
+    fn from(errno: Errno) -> Self {
+        errno.code()
+    }
+}
+
+impl Display for Errno {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Syscall argument.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Arg {
+    /// Plain value (one register).
+    Value(&'static str),
+    /// Read-only buffer, pointer and length (two registers).
+    Buffer(&'static str),
+    /// Writable buffer, pointer and length (two registers, must be in RAM).
+    BufferMut(&'static str),
+    /// Capability handle (one register), replaced by the host resource id before calling the syscall function.
+    /// The capability must be of the given kind and include the given rights (check [`capability`]).
+    Capability(&'static str, Kind, Rights),
+}
+
+impl Arg {
+    /// Registers used by the argument.
+    pub const fn registers(&self) -> usize {
+        match self {
+            Arg::Value(_) | Arg::Capability(..) => 1,
+            Arg::Buffer(_) | Arg::BufferMut(_) => 2,
+        }
+    }
+
+    /// Argument name.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Arg::Value(name)
+            | Arg::Buffer(name)
+            | Arg::BufferMut(name)
+            | Arg::Capability(name, ..) => name,
+        }
+    }
+}
+
+/// Syscall signature (check the [module documentation](self)).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SyscallContract {
+    /// Syscall number.
+    pub nr: i32,
+    /// Syscall name.
+    pub name: &'static str,
+    /// Arguments, in register order (from `a0`).
+    pub args: &'static [Arg],
+}
+
+impl SyscallContract {
+    /// Create a new syscall contract.
+    /// Fails to compile (in const context) if the arguments don't fit in the syscall registers.
+    ///
+    /// Arguments:
+    /// - `nr`: Syscall number.
+    /// - `name`: Syscall name.
+    /// - `args`: Arguments, in register order (from `a0`).
+    pub const fn new(nr: i32, name: &'static str, args: &'static [Arg]) -> Self {
+        let contract = SyscallContract { nr, name, args };
+        assert!(
+            contract.registers() <= SYSCALL_ARGS,
+            "Syscall arguments don't fit in the syscall registers"
+        );
+        contract
+    }
+
+    /// Registers used by the arguments.
+    pub const fn registers(&self) -> usize {
+        let mut registers = 0;
+        let mut i = 0;
+        while i < self.args.len() {
+            registers += self.args[i].registers();
+            i += 1;
+        }
+        registers
+    }
+
+    /// Check the syscall arguments against the contract.
+    ///
+    /// Arguments:
+    /// - `args`: Syscall arguments (`a0` to `a6`).
+    /// - `memory`: System memory (code + RAM).
+    ///
+    /// Returns:
+    /// - `bool`: Every buffer is inside the memory (and writable buffers are in RAM).
+    pub fn check<M: Memory>(&self, args: &[i32; SYSCALL_ARGS], memory: &M) -> bool {
+        let mut registers = args.iter().map(|arg| *arg as u32);
+        self.args.iter().all(|arg| match arg {
+            Arg::Value(_) | Arg::Capability(..) => registers.next().is_some(),
+            Arg::Buffer(_) | Arg::BufferMut(_) => {
+                let (Some(address), Some(len)) = (registers.next(), registers.next()) else {
+                    return false;
+                };
+                if len == 0 {
+                    return true;
+                }
+
+                let Some(last) = address.checked_add(len - 1) else {
+                    return false;
+                };
+                if matches!(arg, Arg::BufferMut(_)) && address < RAM_OFFSET {
+                    return false;
+                }
+
+                // Both ends in the same region and inside the memory
+                (address >= RAM_OFFSET) == (last >= RAM_OFFSET)
+                    && memory.load::<1>(address).is_ok()
+                    && memory.load::<1>(last).is_ok()
+            }
+        })
+    }
+
+    /// Resolve the capability arguments, replacing each handle with the host resource id.
+    ///
+    /// Arguments:
+    /// - `args`: Syscall arguments (`a0` to `a6`), modified in place.
+    /// - `capabilities`: Capabilities granted to the guest.
+    ///
+    /// Returns:
+    /// - `Ok(())`: Every capability was resolved.
+    /// - `Err(Errno)`: A capability wasn't granted, or misses rights ([`Errno::AccessDenied`]).
+    pub fn resolve(
+        &self,
+        args: &mut [i32; SYSCALL_ARGS],
+        capabilities: &Capabilities,
+    ) -> Result<(), Errno> {
+        let mut register = 0;
+        for arg in self.args {
+            if let Arg::Capability(_, kind, rights) = arg {
+                let resource = capabilities.resolve(args[register] as u32, *kind, *rights)?;
+                args[register] = resource as i32;
+            }
+            register += arg.registers();
+        }
+
+        Ok(())
+    }
+
+    /// Get the writable buffers of the syscall arguments (unchecked, check [`SyscallContract::check`]).
+    ///
+    /// Arguments:
+    /// - `args`: Syscall arguments (`a0` to `a6`).
+    ///
+    /// Returns:
+    /// - `impl Iterator<Item = (u32, u32)>`: Address and length of each [`Arg::BufferMut`] argument.
+    pub fn writable<'a>(
+        &'a self,
+        args: &'a [i32; SYSCALL_ARGS],
+    ) -> impl Iterator<Item = (u32, u32)> + 'a {
+        self.args
+            .iter()
+            .scan(0, |register, arg| {
+                let first = *register;
+                *register += arg.registers();
+                Some((arg, first))
+            })
+            .filter(|(arg, _)| matches!(arg, Arg::BufferMut(_)))
+            .map(|(_, register)| (args[register] as u32, args[register + 1] as u32))
+    }
+
+    /// Format a syscall for traces, ex.: `write(fd=1, buf=0x80000010[12])`.
+    ///
+    /// Arguments:
+    /// - `args`: Syscall arguments (`a0` to `a6`).
+    pub fn display<'a>(&'a self, args: &'a [i32; SYSCALL_ARGS]) -> SyscallDisplay<'a> {
+        SyscallDisplay {
+            contract: self,
+            args,
+            trace: None,
+            timestamp: None,
+        }
+    }
+}
+
+/// Syscall trace formatting (check [`SyscallContract::display`]).
+pub struct SyscallDisplay<'a> {
+    /// Syscall contract.
+    contract: &'a SyscallContract,
+    /// Syscall arguments.
+    args: &'a [i32; SYSCALL_ARGS],
+    /// Trace context.
+    trace: Option<TraceContext>,
+    /// Host clock.
+    timestamp: Option<u64>,
+}
+
+impl SyscallDisplay<'_> {
+    /// Set the trace context, prefixed to the syscall (ex.: `[<trace>] write(...)`), and return the display.
+    ///
+    /// Arguments:
+    /// - `trace`: Trace context (None = No prefix).
+    pub fn with_trace(mut self, trace: Option<TraceContext>) -> Self {
+        self.trace = trace;
+        self
+    }
+
+    /// Set the host clock, prefixed to the syscall (ex.: `@<ticks> write(...)`, after the trace context),
+    /// and return the display.
+    ///
+    /// Arguments:
+    /// - `timestamp`: Host clock (None = No prefix).
+    pub fn with_timestamp(mut self, timestamp: Option<u64>) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+}
+
+impl Display for SyscallDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        if let Some(trace) = self.trace {
+            write!(f, "[{}] ", trace)?;
+        }
+        if let Some(timestamp) = self.timestamp {
+            write!(f, "@{} ", timestamp)?;
+        }
+        write!(f, "{}(", self.contract.name)?;
+
+        let mut registers = self.args.iter();
+        for (i, arg) in self.contract.args.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+
+            let value = registers.next().copied().unwrap_or_default();
+            match arg {
+                Arg::Value(name) => write!(f, "{}={}", name, value)?,
+                Arg::Capability(name, ..) => write!(f, "{}=#{}", name, value)?,
+                Arg::Buffer(name) | Arg::BufferMut(name) => {
+                    let len = registers.next().copied().unwrap_or_default();
+                    write!(f, "{}={:#010x}[{}]", name, value as u32, len as u32)?
+                }
+            }
+        }
+
+        write!(f, ")")
+    }
+}
+
+/// Find the contract of a syscall number.
+///
+/// Arguments:
+/// - `contracts`: Syscall contracts.
+/// - `nr`: Syscall number.
+///
+/// Returns:
+/// - `Some(&SyscallContract)`: Contract of the syscall.
+/// - `None`: No contract declared.
+pub fn find(contracts: &[SyscallContract], nr: i32) -> Option<&SyscallContract> {
+    contracts.iter().find(|contract| contract.nr == nr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::SliceMemory;
+    use std::format;
+
+    const READ: SyscallContract =
+        SyscallContract::new(63, "read", &[Arg::Value("fd"), Arg::BufferMut("buf")]);
+    const WRITE: SyscallContract =
+        SyscallContract::new(64, "write", &[Arg::Value("fd"), Arg::Buffer("buf")]);
+
+    #[test]
+    fn test_check() {
+        let mut ram = [0; 16];
+        let memory = SliceMemory::new(&[0; 8], &mut ram);
+        let ram = RAM_OFFSET as i32;
+
+        assert!(WRITE.check(&[1, ram, 16, 0, 0, 0, 0], &memory));
+        assert!(WRITE.check(&[1, 4, 4, 0, 0, 0, 0], &memory));
+        assert!(WRITE.check(&[1, -1, 0, 0, 0, 0, 0], &memory));
+        assert!(!WRITE.check(&[1, ram, 17, 0, 0, 0, 0], &memory));
+        assert!(!WRITE.check(&[1, 4, 5, 0, 0, 0, 0], &memory));
+        assert!(!WRITE.check(&[1, -1, 2, 0, 0, 0, 0], &memory));
+
+        assert!(READ.check(&[1, ram + 8, 8, 0, 0, 0, 0], &memory));
+        assert!(!READ.check(&[1, 0, 4, 0, 0, 0, 0], &memory));
+    }
+
+    #[test]
+    fn test_writable() {
+        let args = [1, RAM_OFFSET as i32, 8, 0, 0, 0, 0];
+        assert!(READ.writable(&args).eq([(RAM_OFFSET, 8)]));
+        assert_eq!(WRITE.writable(&args).count(), 0);
+    }
+
+    #[test]
+    fn test_errno() {
+        for errno in Errno::ALL {
+            assert_eq!(Errno::from_code(errno.into()), Some(errno));
+        }
+        assert_eq!(Errno::from_code(0), None);
+        assert_eq!(format!("{}", Errno::WouldBlock), "WouldBlock");
+    }
+
+    #[test]
+    fn test_display() {
+        let args = [3, 0x8000_0000u32 as i32, 4, 0, 0, 0, 0];
+        assert_eq!(
+            format!("{}", READ.display(&args)),
+            "read(fd=3, buf=0x80000000[4])"
+        );
+        assert_eq!(find(&[READ, WRITE], 64), Some(&WRITE));
+        assert_eq!(find(&[READ, WRITE], 1), None);
+        assert_eq!(WRITE.registers(), 3);
+
+        let trace = Some(TraceContext::new(0x1F, 0x2));
+        assert_eq!(
+            format!("{}", WRITE.display(&args).with_trace(trace)),
+            "[0000000000000000000000000000001f-0000000000000002] write(fd=3, buf=0x80000000[4])"
+        );
+        assert_eq!(
+            format!("{}", READ.display(&args).with_timestamp(Some(1500))),
+            "@1500 read(fd=3, buf=0x80000000[4])"
+        );
+    }
+
+    #[test]
+    fn test_resolve() {
+        const SEEK: SyscallContract = SyscallContract::new(
+            62,
+            "seek",
+            &[
+                Arg::Capability("fd", Kind::File, Rights::CONTROL),
+                Arg::Value("offset"),
+            ],
+        );
+
+        let mut caps = Capabilities::default();
+        let handle = caps
+            .grant(Kind::File, 42, Rights::READ | Rights::CONTROL)
+            .unwrap();
+
+        let mut args = [handle as i32, 100, 0, 0, 0, 0, 0];
+        assert_eq!(
+            format!("{}", SEEK.display(&args)),
+            "seek(fd=#1, offset=100)"
+        );
+        assert_eq!(SEEK.resolve(&mut args, &caps), Ok(()));
+        assert_eq!(args[..2], [42, 100]);
+
+        // Not granted
+        let mut args = [2, 100, 0, 0, 0, 0, 0];
+        assert_eq!(SEEK.resolve(&mut args, &caps), Err(Errno::AccessDenied));
+        assert_eq!(args[0], 2);
+    }
+}
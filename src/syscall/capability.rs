@@ -0,0 +1,292 @@
+//! Capability Module
+//!
+//! Per-engine capability table ([`crate::engine::Engine::capabilities`]), scoping which host resources
+//! (files, streams, keys, timers, etc.) a guest may touch. The host grants a resource with a set of rights
+//! and passes the returned handle to the guest. Guests only ever see handles (indexes into their own table),
+//! never host resource ids, so they can't forge access to resources they weren't granted.
+//!
+//! Handles are resolved by the engine:
+//! - Syscall contract arguments ([`super::Arg::Capability`]) are replaced by the host resource id before calling
+//!   the syscall function, or [`Errno::AccessDenied`] is returned to the guest.
+//! - Crypto key handles (with the `crypto` feature) are resolved as [`Kind::Key`] capabilities.
+//!
+//! Capabilities are revoked automatically on [`crate::engine::Engine::reset`] (and warm restarts),
+//! so a restarted or newly loaded guest starts with nothing granted.
+//!
+//! ```
+//! use embive::{
+//!     engine::{Config, Engine},
+//!     memory::SliceMemory,
+//!     syscall::capability::{Kind, Rights},
+//! };
+//!
+//! let mut memory = SliceMemory::new(&[], &mut []);
+//! let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+//!
+//! // Host file descriptor 3, read-only
+//! let handle = engine.capabilities.grant(Kind::File, 3, Rights::READ).unwrap();
+//! assert_eq!(engine.capabilities.resolve(handle, Kind::File, Rights::READ), Ok(3));
+//! assert!(engine.capabilities.resolve(handle, Kind::File, Rights::WRITE).is_err());
+//!
+//! engine.reset();
+//! assert!(engine.capabilities.get(handle).is_none());
+//! ```
+
+use core::ops::BitOr;
+
+use super::Errno;
+use crate::error::EmbiveError;
+
+/// Maximum number of capabilities per engine.
+pub const CAPABILITIES: usize = 16;
+
+/// Resource kind.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Kind {
+    /// File (ex.: a host file descriptor).
+    File,
+    /// Stream (ex.: a UART or a socket).
+    Stream,
+    /// Crypto key (check [`super::crypto`]).
+    Key,
+    /// Host timer.
+    Timer,
+    /// Host-defined resource kind.
+    Custom(u8),
+}
+
+/// Rights over a resource (combine with `|`).
+/// The meaning of each bit depends on the resource kind, generic rights are provided for convenience.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Rights(u8);
+
+impl Rights {
+    /// No rights.
+    pub const NONE: Rights = Rights(0);
+    /// Read from the resource.
+    pub const READ: Rights = Rights(0b001);
+    /// Write to the resource.
+    pub const WRITE: Rights = Rights(0b010);
+    /// Control the resource (ex.: configure, seek, arm).
+    pub const CONTROL: Rights = Rights(0b100);
+    /// Every right.
+    pub const ALL: Rights = Rights(u8::MAX);
+
+    /// Create rights from raw bits (kind-specific).
+    pub const fn from_bits(bits: u8) -> Self {
+        Rights(bits)
+    }
+
+    /// Raw bits.
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Combine two sets of rights.
+    pub const fn union(self, other: Rights) -> Self {
+        Rights(self.0 | other.0)
+    }
+
+    /// Check if every right in `other` is included.
+    pub const fn contains(self, other: Rights) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for Rights {
+    type Output = Rights;
+
+    fn bitor(self, rhs: Rights) -> Rights {
+        self.union(rhs)
+    }
+}
+
+/// Granted capability.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Capability {
+    /// Resource kind.
+    pub kind: Kind,
+    /// Host resource id (never visible to the guest).
+    pub resource: u32,
+    /// Rights over the resource.
+    pub rights: Rights,
+}
+
+/// Per-engine capability table (check the [module documentation](self)).
+/// Handles are `1` to [`CAPABILITIES`], `0` is never a valid handle.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Capabilities {
+    /// Granted capabilities, indexed by handle - 1.
+    entries: [Option<Capability>; CAPABILITIES],
+}
+
+impl Capabilities {
+    /// Grant a resource to the guest.
+    ///
+    /// Arguments:
+    /// - `kind`: Resource kind.
+    /// - `resource`: Host resource id.
+    /// - `rights`: Rights over the resource.
+    ///
+    /// Returns:
+    /// - `Ok(u32)`: Capability handle, to be passed to the guest.
+    /// - `Err(EmbiveError)`: Capability table is full ([`EmbiveError::TooManyCapabilities`]).
+    pub fn grant(&mut self, kind: Kind, resource: u32, rights: Rights) -> Result<u32, EmbiveError> {
+        let (index, entry) = self
+            .entries
+            .iter_mut()
+            .enumerate()
+            .find(|(_, entry)| entry.is_none())
+            .ok_or(EmbiveError::TooManyCapabilities)?;
+
+        *entry = Some(Capability {
+            kind,
+            resource,
+            rights,
+        });
+        Ok(index as u32 + 1)
+    }
+
+    /// Revoke a capability.
+    ///
+    /// Arguments:
+    /// - `handle`: Capability handle.
+    ///
+    /// Returns:
+    /// - `bool`: The handle was granted.
+    pub fn revoke(&mut self, handle: u32) -> bool {
+        self.entry(handle).and_then(Option::take).is_some()
+    }
+
+    /// Revoke every capability over a resource (ex.: when the host closes it).
+    ///
+    /// Arguments:
+    /// - `kind`: Resource kind.
+    /// - `resource`: Host resource id.
+    ///
+    /// Returns:
+    /// - `usize`: Number of revoked capabilities.
+    pub fn revoke_resource(&mut self, kind: Kind, resource: u32) -> usize {
+        let mut revoked = 0;
+        for entry in self.entries.iter_mut() {
+            if entry.is_some_and(|cap| cap.kind == kind && cap.resource == resource) {
+                *entry = None;
+                revoked += 1;
+            }
+        }
+
+        revoked
+    }
+
+    /// Revoke every capability.
+    pub fn clear(&mut self) {
+        self.entries = [None; CAPABILITIES];
+    }
+
+    /// Get a granted capability.
+    ///
+    /// Arguments:
+    /// - `handle`: Capability handle.
+    pub fn get(&self, handle: u32) -> Option<&Capability> {
+        self.entries.get(handle.checked_sub(1)? as usize)?.as_ref()
+    }
+
+    /// Resolve a capability handle (ex.: from a guest syscall).
+    ///
+    /// Arguments:
+    /// - `handle`: Capability handle.
+    /// - `kind`: Expected resource kind.
+    /// - `rights`: Required rights.
+    ///
+    /// Returns:
+    /// - `Ok(u32)`: Host resource id.
+    /// - `Err(Errno)`: Handle not granted, other kind or missing rights ([`Errno::AccessDenied`]).
+    pub fn resolve(&self, handle: u32, kind: Kind, rights: Rights) -> Result<u32, Errno> {
+        match self.get(handle) {
+            Some(cap) if cap.kind == kind && cap.rights.contains(rights) => Ok(cap.resource),
+            _ => Err(Errno::AccessDenied),
+        }
+    }
+
+    /// Capability slot of a handle (if in range).
+    fn entry(&mut self, handle: u32) -> Option<&mut Option<Capability>> {
+        self.entries.get_mut(handle.checked_sub(1)? as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grant_resolve() {
+        let mut caps = Capabilities::default();
+        let file = caps.grant(Kind::File, 10, Rights::READ).unwrap();
+        let uart = caps
+            .grant(Kind::Stream, 10, Rights::READ | Rights::WRITE)
+            .unwrap();
+        assert_eq!((file, uart), (1, 2));
+
+        assert_eq!(caps.resolve(file, Kind::File, Rights::READ), Ok(10));
+        assert_eq!(caps.resolve(uart, Kind::Stream, Rights::WRITE), Ok(10));
+        assert_eq!(caps.resolve(uart, Kind::Stream, Rights::NONE), Ok(10));
+        assert_eq!(
+            caps.resolve(file, Kind::File, Rights::WRITE),
+            Err(Errno::AccessDenied)
+        );
+        assert_eq!(
+            caps.resolve(file, Kind::Stream, Rights::READ),
+            Err(Errno::AccessDenied)
+        );
+        assert_eq!(
+            caps.resolve(0, Kind::File, Rights::READ),
+            Err(Errno::AccessDenied)
+        );
+        assert_eq!(
+            caps.resolve(u32::MAX, Kind::File, Rights::READ),
+            Err(Errno::AccessDenied)
+        );
+    }
+
+    #[test]
+    fn test_revoke() {
+        let mut caps = Capabilities::default();
+        let handle = caps.grant(Kind::Timer, 1, Rights::ALL).unwrap();
+        assert!(caps.revoke(handle));
+        assert!(!caps.revoke(handle));
+        assert!(!caps.revoke(0));
+        assert!(caps.get(handle).is_none());
+
+        // Slot is reused
+        assert_eq!(caps.grant(Kind::Custom(1), 5, Rights::READ), Ok(handle));
+        caps.grant(Kind::Custom(1), 5, Rights::WRITE).unwrap();
+        caps.grant(Kind::Custom(2), 5, Rights::WRITE).unwrap();
+        assert_eq!(caps.revoke_resource(Kind::Custom(1), 5), 2);
+        assert_eq!(
+            caps.get(3),
+            Some(&Capability {
+                kind: Kind::Custom(2),
+                resource: 5,
+                rights: Rights::WRITE
+            })
+        );
+
+        caps.clear();
+        assert!(caps.get(3).is_none());
+    }
+
+    #[test]
+    fn test_full() {
+        let mut caps = Capabilities::default();
+        for resource in 0..CAPABILITIES as u32 {
+            assert_eq!(
+                caps.grant(Kind::File, resource, Rights::READ),
+                Ok(resource + 1)
+            );
+        }
+        assert_eq!(
+            caps.grant(Kind::File, 0, Rights::READ),
+            Err(EmbiveError::TooManyCapabilities)
+        );
+    }
+}
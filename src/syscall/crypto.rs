@@ -0,0 +1,522 @@
+//! Crypto Service Module
+//!
+//! Hashing, HMAC and AEAD syscalls backed by a host implementation of the [`Crypto`] trait
+//! (ex.: hardware crypto accelerators), registered with [`crate::engine::Config::with_crypto`].
+//! Guests don't ship slow software crypto, and keys stay in the host: guests refer to them by key handle,
+//! a [`Kind::Key`](super::capability::Kind::Key) capability ([`super::capability`]) resolved by the engine before calling the implementation.
+//!
+//! Syscall arguments:
+//! - `a0`: Operation ([`CRYPTO_HASH`], [`CRYPTO_HMAC`], [`CRYPTO_SEAL`] or [`CRYPTO_OPEN`]) | algorithm `<< 8`.
+//! - `a1`: Key handle (HMAC and AEAD, [`Errno::AccessDenied`] if not granted with the `KEY_*` right of the operation).
+//! - `a2`, `a3`: Input buffer (pointer, length): data, plaintext (seal) or ciphertext + tag (open).
+//! - `a4`, `a5`: Output buffer (pointer, length): digest, MAC, ciphertext + tag (seal) or plaintext (open).
+//! - `a6`: AEAD parameters pointer, four words: nonce (pointer, length) and associated data (pointer, length).
+//!
+//! Returns the number of bytes written to the output buffer (`a1`), or a [`Errno`] error code (`a0`).
+//!
+//! ```
+//! use embive::{
+//!     engine::Config,
+//!     memory::{Memory, SliceMemory},
+//!     syscall::{crypto::{Crypto, GuestBuffer, HASH_SHA256}, Errno},
+//! };
+//!
+//! /// Host crypto, only hashing is supported (other operations return `Errno::NotSupported`).
+//! struct HostCrypto;
+//!
+//! impl<M: Memory> Crypto<M> for HostCrypto {
+//!     fn hash(algorithm: u8, data: GuestBuffer, digest: GuestBuffer, memory: &mut M) -> Result<u32, Errno> {
+//!         if algorithm != HASH_SHA256 {
+//!             return Err(Errno::NotSupported);
+//!         }
+//!
+//!         // let mut hasher = Sha256::new();
+//!         data.chunks(memory, |_chunk| { /* hasher.update(chunk) */ })?;
+//!         let output = [0u8; 32]; // hasher.finalize()
+//!         digest.write(memory, 0, &output)?;
+//!         Ok(32)
+//!     }
+//! }
+//!
+//! let config: Config<SliceMemory> = Config::default().with_crypto::<HostCrypto>(Some(16));
+//! ```
+
+use super::capability::Rights;
+use super::{Errno, SYSCALL_ARGS};
+use crate::memory::{helpers, Memory};
+
+/// Crypto operation: hash (`a2..a3` data, `a4..a5` digest).
+pub const CRYPTO_HASH: u8 = 0;
+/// Crypto operation: HMAC (`a1` key, `a2..a3` data, `a4..a5` MAC).
+pub const CRYPTO_HMAC: u8 = 1;
+/// Crypto operation: AEAD encrypt (`a1` key, `a2..a3` plaintext, `a4..a5` ciphertext + tag, `a6` parameters).
+pub const CRYPTO_SEAL: u8 = 2;
+/// Crypto operation: AEAD decrypt (`a1` key, `a2..a3` ciphertext + tag, `a4..a5` plaintext, `a6` parameters).
+pub const CRYPTO_OPEN: u8 = 3;
+
+/// Hash algorithm: SHA-256.
+pub const HASH_SHA256: u8 = 0;
+/// Hash algorithm: SHA-512.
+pub const HASH_SHA512: u8 = 1;
+/// AEAD algorithm: AES-128-GCM.
+pub const AEAD_AES_128_GCM: u8 = 0;
+/// AEAD algorithm: AES-256-GCM.
+pub const AEAD_AES_256_GCM: u8 = 1;
+/// AEAD algorithm: ChaCha20-Poly1305.
+pub const AEAD_CHACHA20_POLY1305: u8 = 2;
+
+/// Key right: compute MACs ([`CRYPTO_HMAC`]).
+pub const KEY_HMAC: Rights = Rights::from_bits(0b001);
+/// Key right: encrypt ([`CRYPTO_SEAL`]).
+pub const KEY_SEAL: Rights = Rights::from_bits(0b010);
+/// Key right: decrypt ([`CRYPTO_OPEN`]).
+pub const KEY_OPEN: Rights = Rights::from_bits(0b100);
+
+/// Key right required by a crypto operation (`a0`, None = No key).
+pub(crate) fn key_rights(op: i32) -> Option<Rights> {
+    match op as u8 {
+        CRYPTO_HMAC => Some(KEY_HMAC),
+        CRYPTO_SEAL => Some(KEY_SEAL),
+        CRYPTO_OPEN => Some(KEY_OPEN),
+        _ => None,
+    }
+}
+
+/// Chunk size used by [`GuestBuffer::chunks`].
+pub const CHUNK_SIZE: usize = 64;
+
+/// Crypto function signature (check [`crate::engine::Config::with_crypto`]).
+///
+/// Arguments:
+/// - `args`: Syscall arguments (`a0` to `a6`).
+/// - `memory`: System Memory (code + RAM).
+///
+/// Returns:
+/// - `Result<i32, i32>`: value (`a1`), error (`a0`).
+pub type CryptoFn<M> = fn(args: &[i32; SYSCALL_ARGS], memory: &mut M) -> Result<i32, i32>;
+
+/// Guest memory buffer, accesses are bounds-checked.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct GuestBuffer {
+    /// Buffer address.
+    pub address: u32,
+    /// Buffer length in bytes.
+    pub len: u32,
+}
+
+impl GuestBuffer {
+    /// Create a new guest buffer.
+    ///
+    /// Arguments:
+    /// - `address`: Buffer address.
+    /// - `len`: Buffer length in bytes.
+    pub const fn new(address: u32, len: u32) -> Self {
+        GuestBuffer { address, len }
+    }
+
+    /// Address of a range inside the buffer.
+    ///
+    /// Arguments:
+    /// - `offset`: Range offset.
+    /// - `len`: Range length.
+    ///
+    /// Returns:
+    /// - `Ok(u32)`: Range address.
+    /// - `Err(Errno)`: Range outside of the buffer ([`Errno::InvalidPointer`]).
+    fn range(&self, offset: u32, len: usize) -> Result<u32, Errno> {
+        let end = (offset as u64).saturating_add(len as u64);
+        if end > self.len as u64 {
+            return Err(Errno::InvalidPointer);
+        }
+
+        Ok(self.address.wrapping_add(offset))
+    }
+
+    /// Read bytes from the buffer.
+    ///
+    /// Arguments:
+    /// - `memory`: System memory (code + RAM).
+    /// - `offset`: Offset inside the buffer.
+    /// - `data`: Destination, filled completely.
+    ///
+    /// Returns:
+    /// - `Ok(())`: Bytes were read.
+    /// - `Err(Errno)`: Outside of the buffer or the memory ([`Errno::InvalidPointer`]).
+    pub fn read<M: Memory>(&self, memory: &M, offset: u32, data: &mut [u8]) -> Result<(), Errno> {
+        let address = self.range(offset, data.len())?;
+        helpers::read_slice(memory, address, data).map_err(|_| Errno::InvalidPointer)
+    }
+
+    /// Write bytes to the buffer.
+    ///
+    /// Arguments:
+    /// - `memory`: System memory (code + RAM).
+    /// - `offset`: Offset inside the buffer.
+    /// - `data`: Bytes to write.
+    ///
+    /// Returns:
+    /// - `Ok(())`: Bytes were written.
+    /// - `Err(Errno)`: Outside of the buffer or the RAM ([`Errno::InvalidPointer`]).
+    pub fn write<M: Memory>(&self, memory: &mut M, offset: u32, data: &[u8]) -> Result<(), Errno> {
+        let address = self.range(offset, data.len())?;
+        helpers::write_slice(memory, address, data).map_err(|_| Errno::InvalidPointer)
+    }
+
+    /// Read the whole buffer in chunks (up to [`CHUNK_SIZE`] bytes), ex.: to feed a hasher.
+    ///
+    /// Arguments:
+    /// - `memory`: System memory (code + RAM).
+    /// - `f`: Called with each chunk, in order.
+    ///
+    /// Returns:
+    /// - `Ok(())`: Whole buffer was read.
+    /// - `Err(Errno)`: Outside of the memory ([`Errno::InvalidPointer`]).
+    pub fn chunks<M: Memory>(&self, memory: &M, mut f: impl FnMut(&[u8])) -> Result<(), Errno> {
+        let mut chunk = [0; CHUNK_SIZE];
+        let mut offset = 0;
+        while offset < self.len {
+            let len = (self.len - offset).min(CHUNK_SIZE as u32) as usize;
+            self.read(memory, offset, &mut chunk[..len])?;
+            f(&chunk[..len]);
+            offset += len as u32;
+        }
+
+        Ok(())
+    }
+}
+
+/// AEAD parameters.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct AeadParams {
+    /// Nonce.
+    pub nonce: GuestBuffer,
+    /// Associated data (authenticated, not encrypted).
+    pub aad: GuestBuffer,
+}
+
+/// Host crypto implementation (check the [module documentation](self)).
+///
+/// Every operation returns the number of bytes written to the output buffer,
+/// unimplemented operations return [`Errno::NotSupported`].
+pub trait Crypto<M: Memory> {
+    /// Hash data.
+    ///
+    /// Arguments:
+    /// - `algorithm`: Hash algorithm (ex.: [`HASH_SHA256`]).
+    /// - `data`: Data to hash.
+    /// - `digest`: Digest output.
+    /// - `memory`: System memory (code + RAM).
+    fn hash(
+        _algorithm: u8,
+        _data: GuestBuffer,
+        _digest: GuestBuffer,
+        _memory: &mut M,
+    ) -> Result<u32, Errno> {
+        Err(Errno::NotSupported)
+    }
+
+    /// Compute the HMAC of data with a host key.
+    ///
+    /// Arguments:
+    /// - `algorithm`: Hash algorithm (ex.: [`HASH_SHA256`]).
+    /// - `key`: Host key id (resolved by the engine from the guest key handle).
+    /// - `data`: Data to authenticate.
+    /// - `mac`: MAC output.
+    /// - `memory`: System memory (code + RAM).
+    fn hmac(
+        _algorithm: u8,
+        _key: u32,
+        _data: GuestBuffer,
+        _mac: GuestBuffer,
+        _memory: &mut M,
+    ) -> Result<u32, Errno> {
+        Err(Errno::NotSupported)
+    }
+
+    /// Encrypt and authenticate data with a host key.
+    ///
+    /// Arguments:
+    /// - `algorithm`: AEAD algorithm (ex.: [`AEAD_AES_128_GCM`]).
+    /// - `key`: Host key id (resolved by the engine from the guest key handle).
+    /// - `params`: Nonce and associated data.
+    /// - `plaintext`: Data to encrypt.
+    /// - `ciphertext`: Ciphertext and tag output.
+    /// - `memory`: System memory (code + RAM).
+    fn seal(
+        _algorithm: u8,
+        _key: u32,
+        _params: AeadParams,
+        _plaintext: GuestBuffer,
+        _ciphertext: GuestBuffer,
+        _memory: &mut M,
+    ) -> Result<u32, Errno> {
+        Err(Errno::NotSupported)
+    }
+
+    /// Authenticate and decrypt data with a host key.
+    ///
+    /// Arguments:
+    /// - `algorithm`: AEAD algorithm (ex.: [`AEAD_AES_128_GCM`]).
+    /// - `key`: Host key id (resolved by the engine from the guest key handle).
+    /// - `params`: Nonce and associated data.
+    /// - `ciphertext`: Ciphertext and tag.
+    /// - `plaintext`: Plaintext output (not written if authentication fails).
+    /// - `memory`: System memory (code + RAM).
+    fn open(
+        _algorithm: u8,
+        _key: u32,
+        _params: AeadParams,
+        _ciphertext: GuestBuffer,
+        _plaintext: GuestBuffer,
+        _memory: &mut M,
+    ) -> Result<u32, Errno> {
+        Err(Errno::NotSupported)
+    }
+
+    /// Decode a crypto syscall and call the operation (check the [module documentation](self)).
+    ///
+    /// Arguments:
+    /// - `args`: Syscall arguments (`a0` to `a6`).
+    /// - `memory`: System memory (code + RAM).
+    ///
+    /// Returns:
+    /// - `Result<i32, i32>`: Bytes written (`a1`), error (`a0`).
+    fn syscall(args: &[i32; SYSCALL_ARGS], memory: &mut M) -> Result<i32, i32> {
+        let [op, key, input, input_len, output, output_len, params] = args.map(|arg| arg as u32);
+        let algorithm = (op >> 8) as u8;
+        let input = GuestBuffer::new(input, input_len);
+        let output = GuestBuffer::new(output, output_len);
+
+        let written = match op as u8 {
+            CRYPTO_HASH => Self::hash(algorithm, input, output, memory),
+            CRYPTO_HMAC => Self::hmac(algorithm, key, input, output, memory),
+            CRYPTO_SEAL | CRYPTO_OPEN => {
+                let mut words = [0; 16];
+                GuestBuffer::new(params, 16).read(memory, 0, &mut words)?;
+                let word = |i: usize| u32::from_le_bytes(words[i * 4..][..4].try_into().unwrap());
+                let params = AeadParams {
+                    nonce: GuestBuffer::new(word(0), word(1)),
+                    aad: GuestBuffer::new(word(2), word(3)),
+                };
+
+                if op as u8 == CRYPTO_SEAL {
+                    Self::seal(algorithm, key, params, input, output, memory)
+                } else {
+                    Self::open(algorithm, key, params, input, output, memory)
+                }
+            }
+            _ => Err(Errno::NotSupported),
+        }?;
+
+        Ok(written as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Config, Engine};
+    use crate::memory::{SliceMemory, RAM_OFFSET};
+    use crate::register::Register;
+    use crate::syscall::capability::Kind;
+
+    const NR: i32 = 16;
+    const KEY: u32 = 7;
+
+    const CODE: &[u8] = &[
+        0x73, 0x00, 0x00, 0x00, // ecall
+        0x73, 0x00, 0x10, 0x00, // ebreak
+    ];
+
+    /// Toy crypto: XOR "hash", XOR "cipher" with a 1-byte tag (nonce length), one key.
+    struct ToyCrypto;
+
+    impl<M: Memory> Crypto<M> for ToyCrypto {
+        fn hash(
+            algorithm: u8,
+            data: GuestBuffer,
+            digest: GuestBuffer,
+            memory: &mut M,
+        ) -> Result<u32, Errno> {
+            if algorithm != HASH_SHA256 {
+                return Err(Errno::NotSupported);
+            }
+
+            let mut output = 0;
+            data.chunks(memory, |chunk| {
+                output = chunk.iter().fold(output, |acc, byte| acc ^ byte)
+            })?;
+            digest.write(memory, 0, &[output])?;
+            Ok(1)
+        }
+
+        fn seal(
+            _algorithm: u8,
+            key: u32,
+            params: AeadParams,
+            plaintext: GuestBuffer,
+            ciphertext: GuestBuffer,
+            memory: &mut M,
+        ) -> Result<u32, Errno> {
+            if key != KEY {
+                return Err(Errno::AccessDenied);
+            }
+
+            let mut byte = [0];
+            for i in 0..plaintext.len {
+                plaintext.read(memory, i, &mut byte)?;
+                ciphertext.write(memory, i, &[byte[0] ^ KEY as u8])?;
+            }
+            ciphertext.write(memory, plaintext.len, &[params.nonce.len as u8])?;
+            Ok(plaintext.len + 1)
+        }
+    }
+
+    fn call(engine: &mut Engine<SliceMemory>, args: [i32; 7]) -> (i32, i32) {
+        // Restart without resetting (would revoke the key handles)
+        engine.program_counter = 0;
+        for (i, arg) in args.into_iter().enumerate() {
+            engine.registers.inner[Register::A0 as usize + i] = arg;
+        }
+        engine.registers.inner[Register::A7 as usize] = NR;
+
+        assert_eq!(engine.run(), Ok(false));
+        (
+            engine.registers.inner[Register::A0 as usize],
+            engine.registers.inner[Register::A1 as usize],
+        )
+    }
+
+    #[test]
+    fn test_guest_buffer() {
+        let mut ram = [1, 2, 3, 4];
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let buffer = GuestBuffer::new(RAM_OFFSET + 1, 2);
+
+        let mut data = [0; 2];
+        assert_eq!(buffer.read(&memory, 0, &mut data), Ok(()));
+        assert_eq!(data, [2, 3]);
+        assert_eq!(buffer.write(&mut memory, 1, &[9]), Ok(()));
+        assert_eq!(
+            buffer.write(&mut memory, 1, &[9, 9]),
+            Err(Errno::InvalidPointer)
+        );
+        assert_eq!(
+            GuestBuffer::new(RAM_OFFSET + 3, 2).read(&memory, 0, &mut data),
+            Err(Errno::InvalidPointer)
+        );
+        assert_eq!(ram, [1, 2, 9, 4]);
+    }
+
+    #[test]
+    fn test_hash() {
+        let mut ram = [0; 80];
+        ram[..70]
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, b)| *b = i as u8);
+        let mut memory = SliceMemory::new(CODE, &mut ram);
+        let config = Config::default().with_crypto::<ToyCrypto>(Some(NR));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        let data = RAM_OFFSET as i32;
+        let digest = data + 75;
+        assert_eq!(
+            call(&mut engine, [CRYPTO_HASH as i32, 0, data, 70, digest, 4, 0]),
+            (0, 1)
+        );
+        assert_eq!(
+            engine.memory.load::<1>(digest as u32),
+            Ok([(0..70u8).fold(0, |acc, byte| acc ^ byte)])
+        );
+
+        // Unsupported algorithm, invalid output
+        let op = CRYPTO_HASH as i32 | (HASH_SHA512 as i32) << 8;
+        assert_eq!(
+            call(&mut engine, [op, 0, data, 70, digest, 4, 0]),
+            (Errno::NotSupported.code(), 0)
+        );
+        assert_eq!(
+            call(&mut engine, [CRYPTO_HASH as i32, 0, data, 70, 0, 4, 0]),
+            (Errno::InvalidPointer.code(), 0)
+        );
+    }
+
+    #[test]
+    fn test_aead() {
+        let mut ram = [0; 64];
+        ram[0..4].copy_from_slice(&(RAM_OFFSET + 32).to_le_bytes()); // Nonce
+        ram[4..8].copy_from_slice(&12u32.to_le_bytes());
+        ram[16..19].copy_from_slice(b"abc"); // Plaintext
+        let mut memory = SliceMemory::new(CODE, &mut ram);
+        let config = Config::default().with_crypto::<ToyCrypto>(Some(NR));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        let seal = engine.capabilities.grant(Kind::Key, KEY, KEY_SEAL).unwrap() as i32;
+        let both = engine
+            .capabilities
+            .grant(Kind::Key, KEY, KEY_SEAL | KEY_OPEN)
+            .unwrap() as i32;
+
+        let params = RAM_OFFSET as i32;
+        let plaintext = params + 16;
+        let ciphertext = params + 24;
+        let op = CRYPTO_SEAL as i32 | (AEAD_CHACHA20_POLY1305 as i32) << 8;
+        assert_eq!(
+            call(&mut engine, [op, seal, plaintext, 3, ciphertext, 4, params]),
+            (0, 4)
+        );
+        assert_eq!(
+            engine.memory.load::<4>(ciphertext as u32),
+            Ok([b'a' ^ 7, b'b' ^ 7, b'c' ^ 7, 12])
+        );
+
+        // Parameters outside of memory
+        assert_eq!(
+            call(&mut engine, [op, seal, plaintext, 3, ciphertext, 4, 0]),
+            (Errno::InvalidPointer.code(), 0)
+        );
+
+        // Host key ids and handles not granted for the operation are rejected by the engine
+        let open = CRYPTO_OPEN as i32;
+        assert_eq!(
+            call(
+                &mut engine,
+                [op, KEY as i32, plaintext, 3, ciphertext, 4, params]
+            ),
+            (Errno::AccessDenied.code(), 0)
+        );
+        assert_eq!(
+            call(
+                &mut engine,
+                [open, seal, ciphertext, 4, plaintext, 3, params]
+            ),
+            (Errno::AccessDenied.code(), 0)
+        );
+        assert_eq!(
+            call(
+                &mut engine,
+                [open, both, ciphertext, 4, plaintext, 3, params]
+            ),
+            (Errno::NotSupported.code(), 0)
+        );
+
+        // Revoked handle
+        assert!(engine.capabilities.revoke(seal as u32));
+        assert_eq!(
+            call(&mut engine, [op, seal, plaintext, 3, ciphertext, 4, params]),
+            (Errno::AccessDenied.code(), 0)
+        );
+    }
+
+    #[test]
+    fn test_not_permitted() {
+        let mut memory = SliceMemory::new(CODE, &mut []);
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+        engine.registers.inner[Register::A7 as usize] = NR;
+        assert_eq!(
+            engine.run(),
+            Err(crate::error::EmbiveError::NoSyscallFunction)
+        );
+    }
+}
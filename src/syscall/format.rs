@@ -0,0 +1,373 @@
+//! Formatting Service Module
+//!
+//! String formatting syscall ([`crate::engine::Config::format_nr`]), so tiny guests don't need to link a
+//! `printf` implementation. The engine enforces the bounds and the specifiers:
+//! - `a0`, `a1`: Output buffer (pointer, length), in RAM.
+//! - `a2`, `a3`: Format string (pointer, length), at most [`FORMAT_MAX`] bytes.
+//! - `a4`, `a5`: Arguments (pointer, count), one word each (two for `%s`), at most [`FORMAT_ARGS`] words.
+//!
+//! Supported specifiers are `%[-][0][width]` followed by `d`/`i` (signed), `u` (unsigned), `x`/`X` (hexadecimal),
+//! `c` (character), `s` (string, pointer and length arguments, up to [`FORMAT_STRING_MAX`] bytes) and `%%`,
+//! with a width up to [`FORMAT_WIDTH_MAX`].
+//! The whole format is validated before writing, so invalid calls leave the buffer untouched.
+//!
+//! Returns the formatted length in `a1`, possibly larger than the buffer (the output is truncated, like
+//! `snprintf`, without a terminating NUL). Errors are returned in `a0`: [`Errno::InvalidPointer`] for
+//! inaccessible buffers, and [`Errno::InvalidArgument`] for unsupported specifiers or missing arguments.
+//!
+//! ```
+//! use embive::{engine::{Config, Engine}, memory::{Memory, SliceMemory, RAM_OFFSET}};
+//!
+//! let code = &[
+//!     0x37, 0x05, 0x00, 0x80, // lui  a0, 0x80000 (RAM, output)
+//!     0x93, 0x05, 0x00, 0x01, // li   a1, 16
+//!     0x13, 0x06, 0x05, 0x01, // addi a2, a0, 16  (Format)
+//!     0x93, 0x06, 0x60, 0x00, // li   a3, 6
+//!     0x13, 0x07, 0x85, 0x01, // addi a4, a0, 24  (Arguments)
+//!     0x93, 0x07, 0x10, 0x00, // li   a5, 1
+//!     0x93, 0x08, 0x10, 0x03, // li   a7, 49      (Syscall nr)
+//!     0x73, 0x00, 0x00, 0x00, // ecall
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//! ];
+//! let mut ram = [0; 28];
+//! ram[16..22].copy_from_slice(b"v=%04x");
+//! ram[24..28].copy_from_slice(&0xbeu32.to_le_bytes());
+//! let mut memory = SliceMemory::new(code, &mut ram);
+//! let config = Config::default().with_format_nr(Some(49));
+//! let mut engine = Engine::new(&mut memory, config).unwrap();
+//!
+//! assert_eq!(engine.run(), Ok(false));
+//! assert_eq!(engine.registers.get(11), Ok(6)); // Formatted length
+//! assert_eq!(engine.memory.load(RAM_OFFSET), Ok(*b"v=00be"));
+//! ```
+
+use super::{Arg, Errno, SyscallContract};
+use crate::memory::{helpers, Memory};
+
+/// Maximum format string length in bytes.
+pub const FORMAT_MAX: usize = 128;
+/// Maximum number of argument words.
+pub const FORMAT_ARGS: usize = 16;
+/// Maximum specifier width.
+pub const FORMAT_WIDTH_MAX: usize = 32;
+/// Maximum `%s` string length in bytes.
+pub const FORMAT_STRING_MAX: u32 = 256;
+
+/// Output buffer of the format syscall (`a0`, `a1`).
+const OUTPUT: SyscallContract = SyscallContract::new(0, "format", &[Arg::BufferMut("buf")]);
+
+/// Bounded output, writing up to its capacity and counting the full length.
+struct Output {
+    /// Buffer address.
+    address: u32,
+    /// Buffer length (0 = Validation only).
+    capacity: u32,
+    /// Formatted length.
+    len: u32,
+}
+
+impl Output {
+    /// Create a new output.
+    ///
+    /// Arguments:
+    /// - `address`: Buffer address.
+    /// - `capacity`: Buffer length (0 = Validation only).
+    fn new(address: u32, capacity: u32) -> Self {
+        Output {
+            address,
+            capacity,
+            len: 0,
+        }
+    }
+
+    /// Append a byte (dropped past the capacity).
+    fn push<M: Memory>(&mut self, memory: &mut M, byte: u8) -> Result<(), Errno> {
+        if self.len < self.capacity {
+            memory
+                .store(self.address.wrapping_add(self.len), [byte])
+                .map_err(|_| Errno::InvalidPointer)?;
+        }
+
+        self.len = self.len.saturating_add(1);
+        Ok(())
+    }
+
+    /// Append `count` bytes, padded to the specifier width.
+    ///
+    /// Arguments:
+    /// - `memory`: System memory (code + RAM).
+    /// - `spec`: Specifier flags and width.
+    /// - `count`: Bytes to append.
+    /// - `byte`: Get the byte at an index.
+    fn pad<M: Memory>(
+        &mut self,
+        memory: &mut M,
+        spec: &Spec,
+        count: u32,
+        mut byte: impl FnMut(&mut M, u32) -> Result<u8, Errno>,
+    ) -> Result<(), Errno> {
+        let padding = (spec.width as u32).saturating_sub(count);
+        if !spec.left {
+            let fill = if spec.zero { b'0' } else { b' ' };
+            (0..padding).try_for_each(|_| self.push(memory, fill))?;
+        }
+        for i in 0..count {
+            let byte = byte(memory, i)?;
+            self.push(memory, byte)?;
+        }
+        if spec.left {
+            (0..padding).try_for_each(|_| self.push(memory, b' '))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parsed specifier flags and width.
+#[derive(Default)]
+struct Spec {
+    /// Left-justify (`-`).
+    left: bool,
+    /// Pad with zeros (`0`, ignored if left-justified).
+    zero: bool,
+    /// Minimum width.
+    width: usize,
+}
+
+/// Read guest bytes into a buffer.
+///
+/// Returns:
+/// - `Ok(&[u8])`: Bytes read.
+/// - `Err(Errno)`: Inaccessible memory.
+fn read<'b, M: Memory>(memory: &M, address: u32, buffer: &'b mut [u8]) -> Result<&'b [u8], Errno> {
+    helpers::read_slice(memory, address, buffer).map_err(|_| Errno::InvalidPointer)?;
+    Ok(buffer)
+}
+
+/// Render a format string.
+///
+/// Arguments:
+/// - `format`: Format string.
+/// - `args`: Argument words.
+/// - `memory`: System memory (code + RAM).
+/// - `output`: Output.
+fn render<M: Memory>(
+    format: &[u8],
+    args: &[u32],
+    memory: &mut M,
+    output: &mut Output,
+) -> Result<(), Errno> {
+    let mut args = args.iter().copied();
+    let mut next = || args.next().ok_or(Errno::InvalidArgument);
+    let mut bytes = format.iter().copied();
+
+    while let Some(byte) = bytes.next() {
+        if byte != b'%' {
+            output.push(memory, byte)?;
+            continue;
+        }
+
+        let mut spec = Spec::default();
+        let mut conversion = bytes.next().ok_or(Errno::InvalidArgument)?;
+        while matches!(conversion, b'-' | b'0') {
+            spec.left |= conversion == b'-';
+            spec.zero |= conversion == b'0';
+            conversion = bytes.next().ok_or(Errno::InvalidArgument)?;
+        }
+        while conversion.is_ascii_digit() {
+            spec.width = spec.width * 10 + (conversion - b'0') as usize;
+            if spec.width > FORMAT_WIDTH_MAX {
+                return Err(Errno::InvalidArgument);
+            }
+            conversion = bytes.next().ok_or(Errno::InvalidArgument)?;
+        }
+        spec.zero &= !spec.left;
+
+        match conversion {
+            b'%' => output.push(memory, b'%')?,
+            b'd' | b'i' | b'u' | b'x' | b'X' => {
+                let value = next()?;
+                let negative = matches!(conversion, b'd' | b'i') && (value as i32) < 0;
+                let (mut value, radix) = match conversion {
+                    b'x' | b'X' => (value, 16),
+                    _ if negative => ((value as i32).unsigned_abs(), 10),
+                    _ => (value, 10),
+                };
+
+                let mut digits = [0; 11];
+                let mut start = digits.len();
+                loop {
+                    let digit = (value % radix) as u8;
+                    start -= 1;
+                    digits[start] = match digit {
+                        0..=9 => b'0' + digit,
+                        _ if conversion == b'X' => b'A' + digit - 10,
+                        _ => b'a' + digit - 10,
+                    };
+                    value /= radix;
+                    if value == 0 {
+                        break;
+                    }
+                }
+
+                if negative {
+                    if spec.zero {
+                        // Sign goes before the zero padding
+                        output.push(memory, b'-')?;
+                        spec.width = spec.width.saturating_sub(1);
+                    } else {
+                        start -= 1;
+                        digits[start] = b'-';
+                    }
+                }
+                let digits = &digits[start..];
+                output.pad(memory, &spec, digits.len() as u32, |_, i| {
+                    Ok(digits[i as usize])
+                })?;
+            }
+            b'c' => {
+                let character = next()? as u8;
+                output.pad(memory, &spec, 1, |_, _| Ok(character))?;
+            }
+            b's' => {
+                let (address, len) = (next()?, next()?);
+                if len > FORMAT_STRING_MAX {
+                    return Err(Errno::InvalidArgument);
+                }
+                spec.zero = false;
+                output.pad(memory, &spec, len, |memory, i| {
+                    let [byte] = memory
+                        .load(address.wrapping_add(i))
+                        .map_err(|_| Errno::InvalidPointer)?;
+                    Ok(byte)
+                })?;
+            }
+            _ => return Err(Errno::InvalidArgument),
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle a format syscall (check the [module documentation](self)).
+///
+/// Arguments:
+/// - `args`: Syscall arguments (`a0` to `a5`).
+/// - `memory`: System memory (code + RAM).
+///
+/// Returns:
+/// - `Ok(i32)`: Formatted length.
+/// - `Err(i32)`: Invalid buffers, format or arguments ([`Errno`] code).
+pub(crate) fn syscall<M: Memory>(args: &[i32], memory: &mut M) -> Result<i32, i32> {
+    let [address, capacity, format, format_len, arguments, count] =
+        [args[0], args[1], args[2], args[3], args[4], args[5]].map(|arg| arg as u32);
+    if !OUTPUT.check(&[address as i32, capacity as i32, 0, 0, 0, 0, 0], memory) {
+        return Err(Errno::InvalidPointer.code());
+    }
+    if format_len as usize > FORMAT_MAX || count as usize > FORMAT_ARGS {
+        return Err(Errno::InvalidArgument.code());
+    }
+
+    let mut format_buffer = [0; FORMAT_MAX];
+    let format = read(memory, format, &mut format_buffer[..format_len as usize])?;
+    let mut words = [0; FORMAT_ARGS * 4];
+    let words = read(memory, arguments, &mut words[..count as usize * 4])?;
+    let mut args = [0; FORMAT_ARGS];
+    for (arg, word) in args.iter_mut().zip(words.chunks_exact(4)) {
+        // Unwrap is safe because the chunk is guaranteed to have 4 elements.
+        *arg = u32::from_le_bytes(word.try_into().unwrap());
+    }
+    let args = &args[..count as usize];
+
+    // Validate everything before writing (invalid calls leave the buffer untouched)
+    render(format, args, memory, &mut Output::new(address, 0))?;
+
+    let mut output = Output::new(address, capacity);
+    render(format, args, memory, &mut output)?;
+    Ok(output.len as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{SliceMemory, RAM_OFFSET};
+    use std::vec::Vec;
+
+    /// RAM: output at 0 (32 bytes), format at 64, arguments at 192, "embive" at 240.
+    fn format(format: &[u8], args: &[u32], capacity: u32) -> (Result<i32, i32>, Vec<u8>) {
+        let mut ram = [0xAA; 256];
+        ram[64..][..format.len()].copy_from_slice(format);
+        for (i, arg) in args.iter().enumerate() {
+            ram[192 + i * 4..][..4].copy_from_slice(&arg.to_le_bytes());
+        }
+        ram[240..246].copy_from_slice(b"embive");
+
+        let mut memory = SliceMemory::new(&[], &mut ram);
+        let args = [
+            RAM_OFFSET,
+            capacity,
+            RAM_OFFSET + 64,
+            format.len() as u32,
+            RAM_OFFSET + 192,
+            args.len() as u32,
+        ]
+        .map(|arg| arg as i32);
+        let result = syscall(&args, &mut memory);
+
+        let output = ram[..32]
+            .iter()
+            .copied()
+            .take_while(|byte| *byte != 0xAA)
+            .collect();
+        (result, output)
+    }
+
+    #[test]
+    fn test_format() {
+        let string = RAM_OFFSET + 240;
+        let (result, output) = format(b"%d|%5i|%-4u|", &[-42i32 as u32, 7, 3], 32);
+        assert_eq!(result, Ok(15));
+        assert_eq!(output, b"-42|    7|3   |");
+
+        let (result, output) = format(b"%05d %x %X %%", &[-12i32 as u32, 0xbeef, 0xbeef], 32);
+        assert_eq!(result, Ok(17));
+        assert_eq!(output, b"-0012 beef BEEF %");
+
+        let (result, output) = format(b"%c:%8s:%-3s", &[b'!' as u32, string, 6, string, 2], 32);
+        assert_eq!(result, Ok(14));
+        assert_eq!(output, b"!:  embive:em ");
+
+        let (result, output) = format(b"%d", &[i32::MIN as u32], 32);
+        assert_eq!(result, Ok(11));
+        assert_eq!(output, b"-2147483648");
+    }
+
+    #[test]
+    fn test_format_truncated() {
+        let (result, output) = format(b"value=%u", &[12345], 8);
+        assert_eq!(result, Ok(11));
+        assert_eq!(output, b"value=12");
+
+        let (result, output) = format(b"value=%u", &[12345], 0);
+        assert_eq!(result, Ok(11));
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_format_invalid() {
+        let invalid = Err(Errno::InvalidArgument.code());
+
+        // Unsupported specifiers, missing arguments and limits, nothing written
+        assert_eq!(format(b"ok %f", &[1], 32), (invalid, Vec::new()));
+        assert_eq!(format(b"ok %n", &[0], 32), (invalid, Vec::new()));
+        assert_eq!(format(b"%d %d", &[1], 32), (invalid, Vec::new()));
+        assert_eq!(format(b"trailing %", &[], 32), (invalid, Vec::new()));
+        assert_eq!(format(b"%33d", &[1], 32), (invalid, Vec::new()));
+        assert_eq!(format(b"%s", &[RAM_OFFSET, 257], 32), (invalid, Vec::new()));
+
+        // Inaccessible memory
+        let invalid = Err(Errno::InvalidPointer.code());
+        assert_eq!(format(b"%s", &[0x4000_0000, 4], 32).0, invalid);
+        assert_eq!(format(b"x", &[], 512).0, invalid);
+    }
+}
@@ -0,0 +1,473 @@
+//! Logging Service Module
+//!
+//! Structured log syscall, delivered to a host [`LogSink`] registered with
+//! [`crate::engine::Config::with_log`]. Guest logging is uniform and bounded:
+//! - Messages are truncated to [`LOG_MESSAGE_MAX`] bytes, and at most [`LOG_FIELDS`] key-value pairs are kept
+//!   (keys and values truncated to [`LOG_FIELD_MAX`] bytes). Truncated records are flagged.
+//! - The engine enforces a record budget ([`crate::engine::Config::log_burst`]), refilled by the host
+//!   ([`crate::engine::Engine::refill_log_budget`], ex.: every second) and on reset. Records over the budget are
+//!   dropped (the guest gets [`Errno::QuotaExceeded`]), and counted in the next delivered record.
+//!
+//! Syscall arguments:
+//! - `a0`: Level ([`Level`]).
+//! - `a1`, `a2`: Message (pointer, length).
+//! - `a3`, `a4`: Fields (pointer, count). Each field is four words: key (pointer, length) and value (pointer, length).
+//!
+//! Returns `0` in `a0` on success, or a [`Errno`] error code.
+//!
+//! ```
+//! use embive::{
+//!     engine::Config,
+//!     memory::SliceMemory,
+//!     syscall::log::{LogSink, Record},
+//! };
+//!
+//! struct Console;
+//!
+//! impl LogSink for Console {
+//!     fn log(record: &Record) {
+//!         // println!("[{}] {}", record.level, core::str::from_utf8(record.message).unwrap_or("?"));
+//!     }
+//! }
+//!
+//! let config: Config<SliceMemory> = Config::default()
+//!     .with_log::<Console>(Some(17))
+//!     .with_log_burst(10);
+//! ```
+
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+use super::trace::TraceContext;
+use super::Errno;
+use crate::memory::{helpers, Memory};
+
+/// Maximum message length in bytes (longer messages are truncated).
+pub const LOG_MESSAGE_MAX: usize = 128;
+/// Maximum number of fields per record (extra fields are dropped).
+pub const LOG_FIELDS: usize = 4;
+/// Maximum field key/value length in bytes (longer ones are truncated).
+pub const LOG_FIELD_MAX: usize = 32;
+
+/// Log sink function signature (check [`crate::engine::Config::with_log`]).
+///
+/// Arguments:
+/// - `record`: Log record.
+pub type LogFn = fn(record: &Record);
+
+/// Log level.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[repr(u8)]
+pub enum Level {
+    /// Error.
+    Error = 0,
+    /// Warning.
+    Warn = 1,
+    /// Information.
+    Info = 2,
+    /// Debug.
+    Debug = 3,
+    /// Trace.
+    Trace = 4,
+}
+
+impl Level {
+    /// Get the level of a code.
+    ///
+    /// Arguments:
+    /// - `code`: Level code (`a0`).
+    ///
+    /// Returns:
+    /// - `Some(Level)`: Level of the code.
+    /// - `None`: Unknown level.
+    pub const fn from_code(code: i32) -> Option<Self> {
+        match code {
+            0 => Some(Level::Error),
+            1 => Some(Level::Warn),
+            2 => Some(Level::Info),
+            3 => Some(Level::Debug),
+            4 => Some(Level::Trace),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let name = match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Log record field (key-value pair).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Field<'r> {
+    /// Key (possibly truncated).
+    pub key: &'r [u8],
+    /// Value (possibly truncated).
+    pub value: &'r [u8],
+}
+
+/// Log record, as delivered to the [`LogSink`].
+/// Contents are copied from the guest memory, and not validated as UTF-8.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Record<'r> {
+    /// Level.
+    pub level: Level,
+    /// Message (possibly truncated).
+    pub message: &'r [u8],
+    /// Fields.
+    pub fields: &'r [Field<'r>],
+    /// Message, fields, keys or values were truncated.
+    pub truncated: bool,
+    /// Records dropped (over the budget) since the previous delivered record.
+    pub dropped: u64,
+    /// Trace context of the run (check [`crate::engine::Engine::set_trace_context`]).
+    pub trace: Option<TraceContext>,
+    /// Host clock when the record was emitted (None = No tick function, check [`crate::engine::Config::tick_fn`]).
+    pub timestamp: Option<u64>,
+}
+
+/// Host log sink (check the [module documentation](self)).
+pub trait LogSink {
+    /// Deliver a log record.
+    ///
+    /// Arguments:
+    /// - `record`: Log record.
+    fn log(record: &Record);
+}
+
+/// Record budget state.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct LogBudget {
+    /// Records delivered (or attempted) since the last refill.
+    used: u32,
+    /// Records dropped since the last delivered record.
+    dropped: u64,
+}
+
+impl LogBudget {
+    /// Refill the budget.
+    pub(crate) fn refill(&mut self) {
+        self.used = 0;
+    }
+
+    /// Take a record from the budget.
+    ///
+    /// Arguments:
+    /// - `burst`: Budget size (0 = Unlimited).
+    ///
+    /// Returns:
+    /// - `bool`: The record is within the budget (otherwise, it's counted as dropped).
+    fn acquire(&mut self, burst: u32) -> bool {
+        if burst != 0 && self.used >= burst {
+            self.dropped = self.dropped.saturating_add(1);
+            return false;
+        }
+
+        self.used += 1;
+        true
+    }
+}
+
+/// Copy bytes from the guest memory, truncated to the buffer size.
+///
+/// Returns:
+/// - `Ok((usize, bool))`: Bytes copied, bytes were truncated.
+/// - `Err(Errno)`: Outside of the memory ([`Errno::InvalidPointer`]).
+fn copy<M: Memory>(
+    memory: &M,
+    address: u32,
+    len: u32,
+    buffer: &mut [u8],
+) -> Result<(usize, bool), Errno> {
+    let copied = (len as usize).min(buffer.len());
+    helpers::read_slice(memory, address, &mut buffer[..copied])
+        .map_err(|_| Errno::InvalidPointer)?;
+
+    Ok((copied, copied < len as usize))
+}
+
+/// Read a log syscall (check the [module documentation](self)) and deliver it to the sink.
+///
+/// Arguments:
+/// - `args`: Syscall arguments (`a0` to `a4`).
+/// - `memory`: System memory (code + RAM).
+/// - `budget`: Record budget.
+/// - `burst`: Budget size (0 = Unlimited).
+/// - `log_fn`: Log sink.
+/// - `trace`: Trace context of the run.
+/// - `timestamp`: Host clock (None = No tick function).
+///
+/// Returns:
+/// - `Ok(i32)`: Record delivered (0).
+/// - `Err(i32)`: Invalid arguments, or over the budget ([`Errno`] code).
+pub(crate) fn syscall<M: Memory>(
+    args: &[i32],
+    memory: &M,
+    budget: &mut LogBudget,
+    burst: u32,
+    log_fn: LogFn,
+    trace: Option<TraceContext>,
+    timestamp: Option<u64>,
+) -> Result<i32, i32> {
+    let [level, message, message_len, fields, field_count] =
+        [args[0], args[1], args[2], args[3], args[4]].map(|arg| arg as u32);
+    let level = Level::from_code(level as i32).ok_or(Errno::InvalidArgument)?;
+
+    if !budget.acquire(burst) {
+        return Err(Errno::QuotaExceeded.code());
+    }
+
+    let mut message_buffer = [0; LOG_MESSAGE_MAX];
+    let (message_len, mut truncated) = copy(memory, message, message_len, &mut message_buffer)?;
+
+    let mut buffers = [[[0; LOG_FIELD_MAX]; 2]; LOG_FIELDS];
+    let mut lens = [[0; 2]; LOG_FIELDS];
+    let count = (field_count as usize).min(LOG_FIELDS);
+    truncated |= count < field_count as usize;
+    for (i, (buffer, len)) in buffers
+        .iter_mut()
+        .zip(lens.iter_mut())
+        .take(count)
+        .enumerate()
+    {
+        let mut words = [0; 16];
+        copy(memory, fields.wrapping_add(i as u32 * 16), 16, &mut words)?;
+        let word = |i: usize| u32::from_le_bytes(words[i * 4..][..4].try_into().unwrap());
+
+        for (j, (part, part_len)) in buffer.iter_mut().zip(len.iter_mut()).enumerate() {
+            let (copied, cut) = copy(memory, word(j * 2), word(j * 2 + 1), part)?;
+            *part_len = copied;
+            truncated |= cut;
+        }
+    }
+
+    let fields: [Field; LOG_FIELDS] = core::array::from_fn(|i| Field {
+        key: &buffers[i][0][..lens[i][0]],
+        value: &buffers[i][1][..lens[i][1]],
+    });
+    log_fn(&Record {
+        level,
+        message: &message_buffer[..message_len],
+        fields: &fields[..count],
+        truncated,
+        dropped: core::mem::take(&mut budget.dropped),
+        trace,
+        timestamp,
+    });
+
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Config, Engine};
+    use crate::memory::{SliceMemory, RAM_OFFSET};
+    use crate::register::Register;
+    use std::{cell::RefCell, thread_local, vec::Vec};
+
+    /// Delivered record (level, message, fields, truncated, dropped).
+    type Logged = (Level, Vec<u8>, Vec<(Vec<u8>, Vec<u8>)>, bool, u64);
+
+    thread_local! {
+        static LOGGED: RefCell<Vec<Logged>> = const { RefCell::new(Vec::new()) };
+        static TRACE: core::cell::Cell<Option<TraceContext>> = const { core::cell::Cell::new(None) };
+        static TIMESTAMP: core::cell::Cell<Option<u64>> = const { core::cell::Cell::new(None) };
+    }
+
+    struct TestSink;
+
+    impl LogSink for TestSink {
+        fn log(record: &Record) {
+            let fields = record
+                .fields
+                .iter()
+                .map(|field| (field.key.to_vec(), field.value.to_vec()))
+                .collect();
+            TRACE.with(|trace| trace.set(record.trace));
+            TIMESTAMP.with(|timestamp| timestamp.set(record.timestamp));
+            LOGGED.with(|logged| {
+                logged.borrow_mut().push((
+                    record.level,
+                    record.message.to_vec(),
+                    fields,
+                    record.truncated,
+                    record.dropped,
+                ))
+            });
+        }
+    }
+
+    fn take_logged() -> Vec<Logged> {
+        LOGGED.with(|logged| logged.take())
+    }
+
+    /// RAM: message at 0, field table at 64 (5 fields), "key" at 192, "value" at 200.
+    fn ram() -> [u8; 256] {
+        let mut ram = [0; 256];
+        ram[..64].copy_from_slice(&[b'x'; 64]);
+        ram[..5].copy_from_slice(b"hello");
+        for i in 0..5 {
+            let words = [RAM_OFFSET + 192, 3, RAM_OFFSET + 200, 5];
+            for (j, word) in words.iter().enumerate() {
+                ram[64 + i * 16 + j * 4..][..4].copy_from_slice(&word.to_le_bytes());
+            }
+        }
+        ram[192..195].copy_from_slice(b"key");
+        ram[200..205].copy_from_slice(b"value");
+        ram
+    }
+
+    #[test]
+    fn test_log() {
+        let mut ram = ram();
+        let memory = SliceMemory::new(&[], &mut ram);
+        let mut budget = LogBudget::default();
+        let message = RAM_OFFSET as i32;
+        let fields = message + 64;
+
+        let log = |args: &[i32], budget: &mut LogBudget| {
+            syscall(args, &memory, budget, 0, TestSink::log, None, None)
+        };
+        assert_eq!(log(&[2, message, 5, fields, 1], &mut budget), Ok(0));
+        assert_eq!(log(&[0, message, 5, 0, 0], &mut budget), Ok(0));
+        assert_eq!(
+            take_logged(),
+            [
+                (
+                    Level::Info,
+                    b"hello".to_vec(),
+                    [(b"key".to_vec(), b"value".to_vec())].to_vec(),
+                    false,
+                    0
+                ),
+                (Level::Error, b"hello".to_vec(), [].to_vec(), false, 0)
+            ]
+        );
+
+        // Invalid level and pointers
+        assert_eq!(
+            log(&[5, message, 5, 0, 0], &mut budget),
+            Err(Errno::InvalidArgument.code())
+        );
+        assert_eq!(
+            log(&[0, 0, 5, 0, 0], &mut budget),
+            Err(Errno::InvalidPointer.code())
+        );
+        assert_eq!(
+            log(&[0, message, 5, 0, 1], &mut budget),
+            Err(Errno::InvalidPointer.code())
+        );
+        assert!(take_logged().is_empty());
+    }
+
+    #[test]
+    fn test_truncation() {
+        let mut ram = ram();
+        let memory = SliceMemory::new(&[], &mut ram);
+        let mut budget = LogBudget::default();
+        let message = RAM_OFFSET as i32;
+
+        // Message is longer than the maximum (not read past it), too many fields
+        let args = [4, message, 1000, message + 64, 5];
+        assert_eq!(
+            syscall(&args, &memory, &mut budget, 0, TestSink::log, None, None),
+            Ok(0)
+        );
+        let logged = take_logged();
+        let (level, text, fields, truncated, _) = &logged[0];
+        assert_eq!(*level, Level::Trace);
+        assert_eq!(text.len(), LOG_MESSAGE_MAX);
+        assert_eq!(fields.len(), LOG_FIELDS);
+        assert!(truncated);
+    }
+
+    #[test]
+    fn test_budget() {
+        let code = &[
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x73, 0x00, 0x10, 0x00, // ebreak
+        ];
+        let mut ram = ram();
+        let mut memory = SliceMemory::new(code, &mut ram);
+        let config = Config::default()
+            .with_log::<TestSink>(Some(17))
+            .with_log_burst(2);
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        let log = |engine: &mut Engine<SliceMemory>| {
+            engine.program_counter = 0;
+            let args = [2, RAM_OFFSET as i32, 5, 0, 0, 0, 0, 17];
+            engine.registers.inner[Register::A0 as usize..][..8].copy_from_slice(&args);
+            assert_eq!(engine.run(), Ok(false));
+            engine.registers.inner[Register::A0 as usize]
+        };
+
+        assert_eq!(log(&mut engine), 0);
+        assert_eq!(log(&mut engine), 0);
+        assert_eq!(log(&mut engine), Errno::QuotaExceeded.code());
+        assert_eq!(log(&mut engine), Errno::QuotaExceeded.code());
+        assert_eq!(take_logged().len(), 2);
+
+        // Dropped records are reported in the next delivered one
+        engine.refill_log_budget();
+        assert_eq!(log(&mut engine), 0);
+        assert_eq!(take_logged()[0].4, 2);
+
+        engine.reset();
+        assert_eq!(log(&mut engine), 0);
+        assert_eq!(log(&mut engine), 0);
+        assert_eq!(take_logged()[1].4, 0);
+        assert_eq!(TRACE.with(|trace| trace.get()), None);
+    }
+
+    #[test]
+    fn test_trace_context() {
+        let code = &[
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x73, 0x00, 0x10, 0x00, // ebreak
+        ];
+        let mut ram = ram();
+        let mut memory = SliceMemory::new(code, &mut ram);
+        let config = Config::default().with_log::<TestSink>(Some(17));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        let context = TraceContext::new(0xABCD, 1);
+        engine.set_trace_context(Some(context));
+        engine.reset();
+        assert_eq!(engine.trace_context(), Some(context));
+
+        let args = [1, RAM_OFFSET as i32, 5, 0, 0, 0, 0, 17];
+        engine.registers.inner[Register::A0 as usize..][..8].copy_from_slice(&args);
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(take_logged().len(), 1);
+        assert_eq!(TRACE.with(|trace| trace.get()), Some(context));
+    }
+
+    #[test]
+    fn test_timestamp() {
+        let code = &[
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x73, 0x00, 0x10, 0x00, // ebreak
+        ];
+        let mut ram = ram();
+        let mut memory = SliceMemory::new(code, &mut ram);
+        let config = Config::default()
+            .with_log::<TestSink>(Some(17))
+            .with_tick_fn(Some(|| 1234));
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        let args = [1, RAM_OFFSET as i32, 5, 0, 0, 0, 0, 17];
+        engine.registers.inner[Register::A0 as usize..][..8].copy_from_slice(&args);
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(take_logged().len(), 1);
+        assert_eq!(TIMESTAMP.with(|timestamp| timestamp.get()), Some(1234));
+    }
+}
@@ -0,0 +1,47 @@
+//! Trace Context Module
+//!
+//! Opaque trace/correlation context attached by the host to a guest run
+//! ([`crate::engine::Engine::set_trace_context`]), so guest activity can be stitched into distributed traces
+//! of the surrounding service. Once set, it's automatically included in:
+//! - Log records (`Record::trace`, if the `log` feature is enabled, check `crate::syscall::log`).
+//! - Syscall traces ([`crate::engine::Engine::display_syscall`] and [`super::SyscallDisplay::with_trace`]).
+//!
+//! ```
+//! use embive::syscall::trace::TraceContext;
+//!
+//! // W3C Trace Context IDs (ex.: from the `traceparent` header of the request being served)
+//! let context = TraceContext::new(0x4bf92f3577b34da6a3ce929d0e0e4736, 0x00f067aa0ba902b7);
+//! assert_eq!(
+//!     format!("{}", context),
+//!     "4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7"
+//! );
+//! ```
+
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+/// Trace context (check the [module documentation](self)).
+/// Opaque to the engine, formatted as `trace_id-span_id` in hexadecimal.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct TraceContext {
+    /// Trace/correlation ID.
+    pub trace_id: u128,
+    /// Span ID (0 = None).
+    pub span_id: u64,
+}
+
+impl TraceContext {
+    /// Create a new trace context.
+    ///
+    /// Arguments:
+    /// - `trace_id`: Trace/correlation ID.
+    /// - `span_id`: Span ID (0 = None).
+    pub const fn new(trace_id: u128, span_id: u64) -> Self {
+        TraceContext { trace_id, span_id }
+    }
+}
+
+impl Display for TraceContext {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{:032x}-{:016x}", self.trace_id, self.span_id)
+    }
+}
@@ -0,0 +1,395 @@
+//! Testkit Module
+//!
+//! Instruction-level conformance test vectors (RV32I and RV32M), with programmatic access to each instruction word,
+//! the state before executing it and the expected state after. Users adding custom extensions
+//! ([`crate::extension`]) or modifying the engine can re-verify the base semantics in their own test suites.
+//!
+//! Each vector is executed as a single [`Engine::step`] on a fresh [`VectorMemory`]
+//! (code at `0x00000000`, RAM at [`RAM_OFFSET`]), with the instruction placed at the initial program counter.
+//! Registers not listed in the pre-state are zero, registers not listed in the post-state must be unchanged.
+//!
+//! For stress testing, [`random`] generates pseudo-random RV32IM programs, and compares the engine against
+//! a simple [`reference`] interpreter.
+//!
+//! ```
+//! use embive::{engine::Config, testkit};
+//!
+//! // Every vector supported by this build, with the configuration under test
+//! let checked = testkit::check_all(Config::default).unwrap();
+//! assert!(checked > 0);
+//!
+//! // Or a single one
+//! let vector = &testkit::RV32I[0];
+//! assert_eq!(testkit::run(vector, Config::default()), Ok(()));
+//! ```
+
+pub mod random;
+pub mod reference;
+mod vectors;
+
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::engine::{Config, Engine};
+use crate::error::EmbiveError;
+use crate::lint::IsaExtension;
+use crate::memory::{Memory, RAM_OFFSET};
+use crate::register::REGISTER_COUNT;
+
+pub use vectors::{RV32I, RV32M};
+
+/// Code size of a [`VectorMemory`] in bytes.
+pub const CODE_SIZE: usize = 256;
+/// RAM size of a [`VectorMemory`] in bytes.
+pub const RAM_SIZE: usize = 256;
+
+/// Machine state (program counter, registers and memory).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct State {
+    /// Program counter.
+    pub program_counter: u32,
+    /// Registers (index, value), others are zero (pre-state) or unchanged (post-state).
+    pub registers: &'static [(u8, i32)],
+    /// Memory contents (address, bytes), in code or RAM.
+    pub memory: &'static [(u32, &'static [u8])],
+}
+
+impl State {
+    /// Create a new machine state.
+    ///
+    /// Arguments:
+    /// - `program_counter`: Program counter.
+    /// - `registers`: Registers (index, value).
+    /// - `memory`: Memory contents (address, bytes).
+    pub const fn new(
+        program_counter: u32,
+        registers: &'static [(u8, i32)],
+        memory: &'static [(u32, &'static [u8])],
+    ) -> Self {
+        State {
+            program_counter,
+            registers,
+            memory,
+        }
+    }
+}
+
+/// Test vector (check the [module documentation](self)).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Vector {
+    /// Vector name (instruction disassembly and operands).
+    pub name: &'static str,
+    /// Extension of the instruction.
+    pub extension: IsaExtension,
+    /// Instruction word.
+    pub instruction: u32,
+    /// State before executing the instruction.
+    pub pre: State,
+    /// Expected state after executing the instruction.
+    pub post: State,
+    /// Expected result of [`Engine::step`].
+    pub result: Result<bool, EmbiveError>,
+}
+
+impl Display for Vector {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{} ({:#010x})", self.name, self.instruction)
+    }
+}
+
+/// Difference between the expected and the actual state.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Mismatch {
+    /// Invalid vector or configuration (ex.: the program counter is outside of the code).
+    Setup(EmbiveError),
+    /// Result of [`Engine::step`].
+    Result {
+        /// Expected result.
+        expected: Result<bool, EmbiveError>,
+        /// Actual result.
+        actual: Result<bool, EmbiveError>,
+    },
+    /// Program counter.
+    ProgramCounter {
+        /// Expected value.
+        expected: u32,
+        /// Actual value.
+        actual: u32,
+    },
+    /// Register.
+    Register {
+        /// Register index.
+        index: u8,
+        /// Expected value.
+        expected: i32,
+        /// Actual value.
+        actual: i32,
+    },
+    /// Memory byte.
+    Memory {
+        /// Byte address.
+        address: u32,
+        /// Expected value.
+        expected: u8,
+        /// Actual value (None = Not accessible).
+        actual: Option<u8>,
+    },
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Mismatch::Setup(error) => write!(f, "setup failed: {}", error),
+            Mismatch::Result { expected, actual } => {
+                write!(f, "result: expected {:?}, got {:?}", expected, actual)
+            }
+            Mismatch::ProgramCounter { expected, actual } => {
+                write!(f, "pc: expected {:#010x}, got {:#010x}", expected, actual)
+            }
+            Mismatch::Register {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "x{}: expected {:#010x}, got {:#010x}",
+                index, expected, actual
+            ),
+            Mismatch::Memory {
+                address,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "memory at {:#010x}: expected {:#04x}, got {:?}",
+                address, expected, actual
+            ),
+        }
+    }
+}
+
+/// Memory used to run test vectors: [`CODE_SIZE`] bytes of code and [`RAM_SIZE`] bytes of RAM.
+#[derive(Debug, Clone)]
+pub struct VectorMemory {
+    /// Code buffer (read-only for the guest).
+    code: [u8; CODE_SIZE],
+    /// RAM buffer.
+    ram: [u8; RAM_SIZE],
+}
+
+impl VectorMemory {
+    /// Create a new (zeroed) memory.
+    pub const fn new() -> Self {
+        VectorMemory {
+            code: [0; CODE_SIZE],
+            ram: [0; RAM_SIZE],
+        }
+    }
+
+    /// Buffer and offset of an address range (code or RAM).
+    fn range(&mut self, address: u32, len: usize) -> Result<&mut [u8], EmbiveError> {
+        let (buffer, offset) = match address.checked_sub(RAM_OFFSET) {
+            Some(offset) => (&mut self.ram[..], offset),
+            None => (&mut self.code[..], address),
+        };
+
+        buffer
+            .get_mut(offset as usize..)
+            .and_then(|buffer| buffer.get_mut(..len))
+            .ok_or(EmbiveError::InvalidMemoryAddress)
+    }
+}
+
+impl Default for VectorMemory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Memory for VectorMemory {
+    fn load<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+        let (buffer, offset) = match address.checked_sub(RAM_OFFSET) {
+            Some(offset) => (&self.ram[..], offset),
+            None => (&self.code[..], address),
+        };
+
+        buffer
+            .get(offset as usize..)
+            .and_then(|buffer| buffer.first_chunk::<N>())
+            .copied()
+            .ok_or(EmbiveError::InvalidMemoryAddress)
+    }
+
+    fn store<const N: usize>(&mut self, address: u32, data: [u8; N]) -> Result<(), EmbiveError> {
+        if address < RAM_OFFSET {
+            // Code is read-only
+            return Err(EmbiveError::InvalidMemoryAddress);
+        }
+
+        self.range(address, N)?.copy_from_slice(&data);
+        Ok(())
+    }
+
+    fn export_ram(&self) -> Option<&[u8]> {
+        Some(&self.ram)
+    }
+}
+
+/// Test vectors supported by this build (extension enabled, ex.: RV32M requires the `m_extension` feature).
+pub fn vectors() -> impl Iterator<Item = &'static Vector> {
+    RV32I
+        .iter()
+        .chain(RV32M.iter())
+        .filter(|vector| vector.extension.enabled())
+}
+
+/// Run a test vector.
+///
+/// Arguments:
+/// - `vector`: Test vector.
+/// - `config`: Engine configuration under test (ex.: with a custom extension).
+///
+/// Returns:
+/// - `Ok(())`: The engine matches the expected state.
+/// - `Err(Mismatch)`: First difference found.
+pub fn run(vector: &Vector, config: Config<VectorMemory>) -> Result<(), Mismatch> {
+    let mut memory = VectorMemory::new();
+    memory
+        .range(vector.pre.program_counter, 4)
+        .map_err(Mismatch::Setup)?
+        .copy_from_slice(&vector.instruction.to_le_bytes());
+    for (address, data) in vector.pre.memory {
+        memory
+            .range(*address, data.len())
+            .map_err(Mismatch::Setup)?
+            .copy_from_slice(data);
+    }
+
+    let mut engine = Engine::new(&mut memory, config).map_err(Mismatch::Setup)?;
+    engine.program_counter = vector.pre.program_counter;
+    let mut expected = [0; REGISTER_COUNT];
+    for (index, value) in vector.pre.registers {
+        *engine
+            .registers
+            .get_mut(*index as usize)
+            .map_err(Mismatch::Setup)? = *value;
+        expected[*index as usize] = *value;
+    }
+
+    let actual = engine.step();
+    if actual != vector.result {
+        return Err(Mismatch::Result {
+            expected: vector.result,
+            actual,
+        });
+    }
+
+    if engine.program_counter != vector.post.program_counter {
+        return Err(Mismatch::ProgramCounter {
+            expected: vector.post.program_counter,
+            actual: engine.program_counter,
+        });
+    }
+
+    for (index, value) in vector.post.registers {
+        expected[*index as usize] = *value;
+    }
+    for (index, expected) in expected.into_iter().enumerate() {
+        let actual = engine.registers.get(index).map_err(Mismatch::Setup)?;
+        if actual != expected {
+            return Err(Mismatch::Register {
+                index: index as u8,
+                expected,
+                actual,
+            });
+        }
+    }
+
+    for (address, data) in vector.post.memory {
+        for (i, expected) in data.iter().enumerate() {
+            let address = address.wrapping_add(i as u32);
+            let actual = engine.memory.load::<1>(address).ok().map(|[byte]| byte);
+            if actual != Some(*expected) {
+                return Err(Mismatch::Memory {
+                    address,
+                    expected: *expected,
+                    actual,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Run every test vector supported by this build ([`vectors`]).
+///
+/// Arguments:
+/// - `config`: Creates the engine configuration under test (called for each vector).
+///
+/// Returns:
+/// - `Ok(usize)`: Number of vectors checked, all matched.
+/// - `Err((&Vector, Mismatch))`: First failing vector and its difference.
+pub fn check_all(
+    config: impl Fn() -> Config<VectorMemory>,
+) -> Result<usize, (&'static Vector, Mismatch)> {
+    let mut checked = 0;
+    for vector in vectors() {
+        run(vector, config()).map_err(|mismatch| (vector, mismatch))?;
+        checked += 1;
+    }
+
+    Ok(checked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn test_vectors() {
+        for vector in vectors() {
+            if let Err(mismatch) = run(vector, Config::default()) {
+                panic!("{}: {}", vector, mismatch);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mismatch() {
+        let mut vector = RV32I[0];
+        vector.post.registers = &[(10, 7)];
+        assert_eq!(
+            run(&vector, Config::default()),
+            Err(Mismatch::Register {
+                index: 10,
+                expected: 7,
+                actual: 8
+            })
+        );
+        assert_eq!(
+            format!("{}", run(&vector, Config::default()).unwrap_err()),
+            "x10: expected 0x00000007, got 0x00000008"
+        );
+
+        vector.pre.program_counter = CODE_SIZE as u32;
+        assert_eq!(
+            run(&vector, Config::default()),
+            Err(Mismatch::Setup(EmbiveError::InvalidMemoryAddress))
+        );
+    }
+
+    #[test]
+    fn test_check_all() {
+        let checked = check_all(Config::default).unwrap();
+        assert_eq!(
+            checked,
+            RV32I.len()
+                + if cfg!(feature = "m_extension") {
+                    RV32M.len()
+                } else {
+                    0
+                }
+        );
+    }
+}
@@ -0,0 +1,299 @@
+//! Deterministic pseudo-random RV32IM program generator, for stress and differential testing.
+//!
+//! Programs are random but valid: they start by pointing `s0` to RAM (never written afterwards),
+//! memory accesses are aligned and inside the first [`RAM_WINDOW`] bytes of RAM, and control flow only
+//! moves forward (branches and jumps never skip past the final `ebreak`), so every program halts.
+//! The same seed always produces the same program.
+//!
+//! ```
+//! use embive::{engine::Config, testkit::random::{differential, Generator}};
+//!
+//! let mut code = [0; 64];
+//! let len = Generator::new(42).fill(&mut code);
+//! assert_eq!(len, 64);
+//!
+//! // Compare the engine against the reference interpreter
+//! for seed in 0..16 {
+//!     differential(seed, Config::default()).unwrap();
+//! }
+//! ```
+
+use super::reference::Reference;
+use super::{Mismatch, VectorMemory, CODE_SIZE};
+use crate::engine::{Config, Engine};
+use crate::memory::{Memory, RAM_OFFSET};
+use crate::register::REGISTER_COUNT;
+
+/// RAM bytes accessed by generated loads and stores (from [`RAM_OFFSET`]).
+pub const RAM_WINDOW: u32 = 128;
+
+/// Base register of memory accesses (`s0`).
+const BASE: u32 = 8;
+
+/// Generated `ebreak` instruction.
+const EBREAK: u32 = 0x0010_0073;
+
+/// Pseudo-random program generator (check the [module documentation](self)).
+#[derive(Debug, Clone)]
+pub struct Generator {
+    /// PRNG state (SplitMix64).
+    state: u64,
+    /// Generate M extension instructions.
+    m_extension: bool,
+}
+
+impl Generator {
+    /// Create a new generator.
+    /// Generates M extension instructions if the `m_extension` feature is enabled.
+    ///
+    /// Arguments:
+    /// - `seed`: PRNG seed.
+    pub const fn new(seed: u64) -> Self {
+        Generator {
+            state: seed,
+            m_extension: cfg!(feature = "m_extension"),
+        }
+    }
+
+    /// Set if M extension instructions are generated and return the generator.
+    ///
+    /// Arguments:
+    /// - `enabled`: Generate M extension instructions.
+    pub const fn with_m_extension(mut self, enabled: bool) -> Self {
+        self.m_extension = enabled;
+        self
+    }
+
+    /// Next pseudo-random value.
+    pub fn next_u32(&mut self) -> u32 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        (z ^ (z >> 31)) as u32
+    }
+
+    /// Pseudo-random value below `n`.
+    fn below(&mut self, n: u32) -> u32 {
+        self.next_u32() % n
+    }
+
+    /// Destination register (any but the base register).
+    fn destination(&mut self) -> u32 {
+        let rd = self.below(REGISTER_COUNT as u32 - 1);
+        if rd >= BASE {
+            rd + 1
+        } else {
+            rd
+        }
+    }
+
+    /// Generate an instruction.
+    ///
+    /// Arguments:
+    /// - `remaining`: Instructions after this one, up to and including the final `ebreak`
+    ///   (forward jumps are bounded by it, `0` means no jumps).
+    ///
+    /// Returns:
+    /// - `u32`: Instruction word.
+    pub fn instruction(&mut self, remaining: u32) -> u32 {
+        let rd = self.destination() << 7;
+        let rs1 = self.below(REGISTER_COUNT as u32) << 15;
+        let rs2 = self.below(REGISTER_COUNT as u32) << 20;
+        let funct3 = self.below(8);
+
+        match self.below(10) {
+            // LUI, AUIPC
+            0 => (self.next_u32() & 0xFFFF_F000) | rd | 0x37,
+            1 => (self.next_u32() & 0xFFFF_F000) | rd | 0x17,
+            // OP-IMM
+            2 => {
+                let imm = match funct3 {
+                    1 => self.below(32),
+                    5 => self.below(32) | (self.below(2) << 10),
+                    _ => self.next_u32() & 0xFFF,
+                };
+                (imm << 20) | rs1 | (funct3 << 12) | rd | 0x13
+            }
+            // OP (M extension)
+            3 if self.m_extension => (0x01 << 25) | rs2 | rs1 | (funct3 << 12) | rd | 0x33,
+            // OP
+            3 | 4 => {
+                let funct7 = match funct3 {
+                    0 | 5 => self.below(2) * 0x20,
+                    _ => 0,
+                };
+                (funct7 << 25) | rs2 | rs1 | (funct3 << 12) | rd | 0x33
+            }
+            // LOAD
+            5 => {
+                let (funct3, size) =
+                    [(0, 1), (1, 2), (2, 4), (4, 1), (5, 2)][self.below(5) as usize];
+                let offset = self.below(RAM_WINDOW / size) * size;
+                (offset << 20) | (BASE << 15) | (funct3 << 12) | rd | 0x03
+            }
+            // STORE
+            6 => {
+                let funct3 = self.below(3);
+                let size = 1 << funct3;
+                let offset = self.below(RAM_WINDOW / size) * size;
+                ((offset >> 5) << 25)
+                    | rs2
+                    | (BASE << 15)
+                    | (funct3 << 12)
+                    | ((offset & 0x1F) << 7)
+                    | 0x23
+            }
+            // BRANCH (forward)
+            7 if remaining > 0 => {
+                let funct3 = [0, 1, 4, 5, 6, 7][self.below(6) as usize];
+                let offset = (1 + self.below(remaining.min(8))) * 4;
+                (((offset >> 5) & 0x3F) << 25)
+                    | rs2
+                    | rs1
+                    | (funct3 << 12)
+                    | (((offset >> 1) & 0xF) << 8)
+                    | (((offset >> 11) & 1) << 7)
+                    | 0x63
+            }
+            // JAL (forward)
+            8 if remaining > 0 => {
+                let offset = (1 + self.below(remaining.min(8))) * 4;
+                (((offset >> 1) & 0x3FF) << 21) | rd | 0x6F
+            }
+            // FENCE
+            _ => 0x0FF0_000F,
+        }
+    }
+
+    /// Fill a code buffer with a program: `s0` setup, random instructions and a final `ebreak`.
+    ///
+    /// Arguments:
+    /// - `code`: Code buffer (at least 8 bytes, only whole instructions are written).
+    ///
+    /// Returns:
+    /// - `usize`: Bytes written (0 if the buffer is too small).
+    pub fn fill(&mut self, code: &mut [u8]) -> usize {
+        let count = code.len() / 4;
+        if count < 2 {
+            return 0;
+        }
+
+        for (i, chunk) in code.chunks_exact_mut(4).take(count).enumerate() {
+            let instruction = match i {
+                // lui s0, 0x80000 (RAM)
+                0 => RAM_OFFSET | (BASE << 7) | 0x37,
+                _ if i == count - 1 => EBREAK,
+                _ => self.instruction((count - 1 - i) as u32),
+            };
+            chunk.copy_from_slice(&instruction.to_le_bytes());
+        }
+
+        count * 4
+    }
+}
+
+/// Run a generated program (filling the whole [`super::VectorMemory`] code) on the engine and on the
+/// reference interpreter, comparing the final states.
+///
+/// Arguments:
+/// - `seed`: Generator seed.
+/// - `config`: Engine configuration under test.
+///
+/// Returns:
+/// - `Ok(())`: Engine and reference states match.
+/// - `Err(Mismatch)`: First difference found.
+pub fn differential(seed: u64, config: Config<VectorMemory>) -> Result<(), Mismatch> {
+    let mut memory = VectorMemory::new();
+    Generator::new(seed)
+        .with_m_extension(cfg!(feature = "m_extension"))
+        .fill(&mut memory.code);
+
+    let mut reference = Reference::new(memory.clone());
+    let expected = reference.run(CODE_SIZE / 4);
+
+    let mut engine = Engine::new(&mut memory, config).map_err(Mismatch::Setup)?;
+    let mut actual = Ok(true);
+    for _ in 0..CODE_SIZE / 4 {
+        actual = engine.step();
+        if actual != Ok(true) {
+            break;
+        }
+    }
+
+    if actual != expected {
+        return Err(Mismatch::Result { expected, actual });
+    }
+
+    if engine.program_counter != reference.program_counter {
+        return Err(Mismatch::ProgramCounter {
+            expected: reference.program_counter,
+            actual: engine.program_counter,
+        });
+    }
+
+    for (index, expected) in reference.registers.into_iter().enumerate() {
+        let actual = engine.registers.get(index).map_err(Mismatch::Setup)?;
+        if actual != expected as i32 {
+            return Err(Mismatch::Register {
+                index: index as u8,
+                expected: expected as i32,
+                actual,
+            });
+        }
+    }
+
+    for offset in 0..RAM_WINDOW {
+        let address = RAM_OFFSET + offset;
+        let [expected] = reference.memory.load(address).map_err(Mismatch::Setup)?;
+        let actual = engine.memory.load::<1>(address).ok().map(|[byte]| byte);
+        if actual != Some(expected) {
+            return Err(Mismatch::Memory {
+                address,
+                expected,
+                actual,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::{lint, IsaExtension};
+
+    #[test]
+    fn test_deterministic() {
+        let (mut a, mut b) = ([0; 128], [0; 128]);
+        assert_eq!(Generator::new(7).fill(&mut a), 128);
+        assert_eq!(Generator::new(7).fill(&mut b), 128);
+        assert_eq!(a, b);
+
+        Generator::new(8).fill(&mut b);
+        assert_ne!(a, b);
+        assert_eq!(Generator::new(0).fill(&mut [0; 7]), 0);
+    }
+
+    #[test]
+    fn test_valid() {
+        let mut code = [0; CODE_SIZE];
+        Generator::new(1).with_m_extension(false).fill(&mut code);
+        let report = lint(&code);
+        assert!(report.requires(IsaExtension::I));
+        assert_eq!(report.required().count(), 1);
+
+        Generator::new(1).with_m_extension(true).fill(&mut code);
+        assert!(lint(&code).requires(IsaExtension::M));
+    }
+
+    #[test]
+    fn test_differential() {
+        for seed in 0..500 {
+            if let Err(mismatch) = differential(seed, Config::default()) {
+                panic!("seed {}: {}", seed, mismatch);
+            }
+        }
+    }
+}
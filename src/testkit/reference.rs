@@ -0,0 +1,199 @@
+//! Reference RV32IM interpreter, used for differential testing ([`super::random::differential`]).
+//!
+//! Written for clarity, independently of the engine instruction modules.
+
+use super::VectorMemory;
+use crate::error::EmbiveError;
+use crate::memory::Memory;
+use crate::register::REGISTER_COUNT;
+
+/// Reference machine state.
+#[derive(Debug)]
+pub struct Reference {
+    /// Program counter.
+    pub program_counter: u32,
+    /// Registers.
+    pub registers: [u32; REGISTER_COUNT],
+    /// Memory (code + RAM).
+    pub memory: VectorMemory,
+}
+
+impl Reference {
+    /// Create a new reference machine (registers and program counter zeroed).
+    ///
+    /// Arguments:
+    /// - `memory`: Initial memory (code + RAM).
+    pub fn new(memory: VectorMemory) -> Self {
+        Reference {
+            program_counter: 0,
+            registers: [0; REGISTER_COUNT],
+            memory,
+        }
+    }
+
+    /// Execute one instruction.
+    ///
+    /// Returns:
+    /// - `Ok(bool)`: Should continue (false on `ebreak`).
+    /// - `Err(EmbiveError)`: Invalid instruction or memory access (with the same context as the engine).
+    pub fn step(&mut self) -> Result<bool, EmbiveError> {
+        let pc = self.program_counter;
+        let inst = u32::from_le_bytes(
+            self.memory
+                .load(pc)
+                .or(Err(EmbiveError::FetchFault { pc }))?,
+        );
+        let illegal = EmbiveError::IllegalInstruction { pc, raw: inst };
+        let rd = ((inst >> 7) & 0x1F) as usize;
+        let rs1 = self.registers[((inst >> 15) & 0x1F) as usize];
+        let rs2 = self.registers[((inst >> 20) & 0x1F) as usize];
+        let funct3 = (inst >> 12) & 0x7;
+        let funct7 = inst >> 25;
+        let imm_i = (inst as i32 >> 20) as u32;
+        let imm_s = (((inst as i32 >> 25) << 5) as u32) | ((inst >> 7) & 0x1F);
+        let imm_b = (((inst as i32 >> 31) << 12) as u32)
+            | ((inst & 0x80) << 4)
+            | ((inst >> 20) & 0x7E0)
+            | ((inst >> 7) & 0x1E);
+        let imm_j = (((inst as i32 >> 31) << 20) as u32)
+            | (inst & 0xF_F000)
+            | ((inst >> 9) & 0x800)
+            | ((inst >> 20) & 0x7FE);
+
+        let mut next = self.program_counter.wrapping_add(4);
+        let value = match inst & 0x7F {
+            // LUI, AUIPC
+            0x37 => Some(inst & 0xFFFF_F000),
+            0x17 => Some(self.program_counter.wrapping_add(inst & 0xFFFF_F000)),
+            // JAL, JALR
+            0x6F => {
+                next = self.program_counter.wrapping_add(imm_j);
+                Some(self.program_counter.wrapping_add(4))
+            }
+            0x67 => {
+                next = rs1.wrapping_add(imm_i);
+                Some(self.program_counter.wrapping_add(4))
+            }
+            // BRANCH
+            0x63 => {
+                let taken = match funct3 {
+                    0 => rs1 == rs2,
+                    1 => rs1 != rs2,
+                    4 => (rs1 as i32) < (rs2 as i32),
+                    5 => (rs1 as i32) >= (rs2 as i32),
+                    6 => rs1 < rs2,
+                    7 => rs1 >= rs2,
+                    _ => return Err(illegal),
+                };
+                if taken {
+                    next = self.program_counter.wrapping_add(imm_b);
+                }
+                None
+            }
+            // LOAD
+            0x03 => {
+                let address = rs1.wrapping_add(imm_i);
+                let fault = EmbiveError::LoadFault { pc, address };
+                Some(match funct3 {
+                    0 => self.memory.load::<1>(address).or(Err(fault))?[0] as i8 as u32,
+                    1 => i16::from_le_bytes(self.memory.load(address).or(Err(fault))?) as u32,
+                    2 => u32::from_le_bytes(self.memory.load(address).or(Err(fault))?),
+                    4 => self.memory.load::<1>(address).or(Err(fault))?[0] as u32,
+                    5 => u16::from_le_bytes(self.memory.load(address).or(Err(fault))?) as u32,
+                    _ => return Err(illegal),
+                })
+            }
+            // STORE
+            0x23 => {
+                let address = rs1.wrapping_add(imm_s);
+                let result = match funct3 {
+                    0 => self.memory.store(address, [rs2 as u8]),
+                    1 => self.memory.store(address, (rs2 as u16).to_le_bytes()),
+                    2 => self.memory.store(address, rs2.to_le_bytes()),
+                    _ => return Err(illegal),
+                };
+                result.or(Err(EmbiveError::StoreFault { pc, address }))?;
+                None
+            }
+            // OP-IMM
+            0x13 => Some(match funct3 {
+                0 => rs1.wrapping_add(imm_i),
+                1 => rs1 << (imm_i & 0x1F),
+                2 => ((rs1 as i32) < (imm_i as i32)) as u32,
+                3 => (rs1 < imm_i) as u32,
+                4 => rs1 ^ imm_i,
+                5 if funct7 == 0x20 => ((rs1 as i32) >> (imm_i & 0x1F)) as u32,
+                5 => rs1 >> (imm_i & 0x1F),
+                6 => rs1 | imm_i,
+                _ => rs1 & imm_i,
+            }),
+            // OP
+            0x33 if funct7 == 0x01 => Some(Self::muldiv(funct3, rs1, rs2)),
+            0x33 => Some(match (funct3, funct7) {
+                (0, 0x00) => rs1.wrapping_add(rs2),
+                (0, 0x20) => rs1.wrapping_sub(rs2),
+                (1, 0x00) => rs1 << (rs2 & 0x1F),
+                (2, 0x00) => ((rs1 as i32) < (rs2 as i32)) as u32,
+                (3, 0x00) => (rs1 < rs2) as u32,
+                (4, 0x00) => rs1 ^ rs2,
+                (5, 0x00) => rs1 >> (rs2 & 0x1F),
+                (5, 0x20) => ((rs1 as i32) >> (rs2 & 0x1F)) as u32,
+                (6, 0x00) => rs1 | rs2,
+                (7, 0x00) => rs1 & rs2,
+                _ => return Err(illegal),
+            }),
+            // FENCE
+            0x0F if funct3 == 0 => None,
+            // EBREAK (halts after the instruction)
+            0x73 if inst == 0x0010_0073 => {
+                self.program_counter = next;
+                return Ok(false);
+            }
+            _ => return Err(illegal),
+        };
+
+        if let Some(value) = value {
+            if rd != 0 {
+                self.registers[rd] = value;
+            }
+        }
+        self.program_counter = next;
+        Ok(true)
+    }
+
+    /// M extension operation.
+    fn muldiv(funct3: u32, rs1: u32, rs2: u32) -> u32 {
+        let (signed1, signed2) = (rs1 as i32 as i64, rs2 as i32 as i64);
+        match funct3 {
+            0 => rs1.wrapping_mul(rs2),
+            1 => ((signed1 * signed2) >> 32) as u32,
+            2 => ((signed1 * rs2 as i64) >> 32) as u32,
+            3 => ((rs1 as u64 * rs2 as u64) >> 32) as u32,
+            4 if rs2 == 0 => u32::MAX,
+            4 => (rs1 as i32).wrapping_div(rs2 as i32) as u32,
+            5 if rs2 == 0 => u32::MAX,
+            5 => rs1 / rs2,
+            6 if rs2 == 0 => rs1,
+            6 => (rs1 as i32).wrapping_rem(rs2 as i32) as u32,
+            _ if rs2 == 0 => rs1,
+            _ => rs1 % rs2,
+        }
+    }
+
+    /// Run until `ebreak`, an error or the step limit.
+    ///
+    /// Arguments:
+    /// - `max_steps`: Step limit.
+    ///
+    /// Returns:
+    /// - `Result<bool, EmbiveError>`: Result of the last step (`Ok(true)` if the limit was reached).
+    pub fn run(&mut self, max_steps: usize) -> Result<bool, EmbiveError> {
+        for _ in 0..max_steps {
+            if !self.step()? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
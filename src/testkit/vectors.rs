@@ -0,0 +1,1540 @@
+//! RV32I and RV32M test vectors.
+
+use super::{State, Vector};
+use crate::error::EmbiveError;
+use crate::lint::IsaExtension;
+
+/// RV32I test vectors.
+pub const RV32I: &[Vector] = &[
+    Vector {
+        name: "add a0, a1, a2 [5, 3]",
+        extension: IsaExtension::I,
+        instruction: 0x00c58533,
+        pre: State::new(0x0, &[(11, 5), (12, 3)], &[]),
+        post: State::new(0x4, &[(10, 8)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "add a0, a1, a2 [-1, 1]",
+        extension: IsaExtension::I,
+        instruction: 0x00c58533,
+        pre: State::new(0x0, &[(11, -1), (12, 1)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "add a0, a1, a2 [2147483647, 1]",
+        extension: IsaExtension::I,
+        instruction: 0x00c58533,
+        pre: State::new(0x0, &[(11, 0x7fffffff), (12, 1)], &[]),
+        post: State::new(0x4, &[(10, -0x80000000)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "add a0, a1, a2 [-2147483648, -1]",
+        extension: IsaExtension::I,
+        instruction: 0x00c58533,
+        pre: State::new(0x0, &[(11, -0x80000000), (12, -1)], &[]),
+        post: State::new(0x4, &[(10, 0x7fffffff)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sub a0, a1, a2 [5, 3]",
+        extension: IsaExtension::I,
+        instruction: 0x40c58533,
+        pre: State::new(0x0, &[(11, 5), (12, 3)], &[]),
+        post: State::new(0x4, &[(10, 2)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sub a0, a1, a2 [-1, 1]",
+        extension: IsaExtension::I,
+        instruction: 0x40c58533,
+        pre: State::new(0x0, &[(11, -1), (12, 1)], &[]),
+        post: State::new(0x4, &[(10, -2)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sub a0, a1, a2 [2147483647, 1]",
+        extension: IsaExtension::I,
+        instruction: 0x40c58533,
+        pre: State::new(0x0, &[(11, 0x7fffffff), (12, 1)], &[]),
+        post: State::new(0x4, &[(10, 0x7ffffffe)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sub a0, a1, a2 [-2147483648, -1]",
+        extension: IsaExtension::I,
+        instruction: 0x40c58533,
+        pre: State::new(0x0, &[(11, -0x80000000), (12, -1)], &[]),
+        post: State::new(0x4, &[(10, -0x7fffffff)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sll a0, a1, a2 [252641535, 16715535]",
+        extension: IsaExtension::I,
+        instruction: 0x00c59533,
+        pre: State::new(0x0, &[(11, 0xf0f00ff), (12, 0xff0f0f)], &[]),
+        post: State::new(0x4, &[(10, -0x7f808000)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sll a0, a1, a2 [-8, 2]",
+        extension: IsaExtension::I,
+        instruction: 0x00c59533,
+        pre: State::new(0x0, &[(11, -8), (12, 2)], &[]),
+        post: State::new(0x4, &[(10, -32)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sll a0, a1, a2 [305419896, 33]",
+        extension: IsaExtension::I,
+        instruction: 0x00c59533,
+        pre: State::new(0x0, &[(11, 0x12345678), (12, 33)], &[]),
+        post: State::new(0x4, &[(10, 0x2468acf0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "slt a0, a1, a2 [5, 3]",
+        extension: IsaExtension::I,
+        instruction: 0x00c5a533,
+        pre: State::new(0x0, &[(11, 5), (12, 3)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "slt a0, a1, a2 [-1, 1]",
+        extension: IsaExtension::I,
+        instruction: 0x00c5a533,
+        pre: State::new(0x0, &[(11, -1), (12, 1)], &[]),
+        post: State::new(0x4, &[(10, 1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "slt a0, a1, a2 [2147483647, 1]",
+        extension: IsaExtension::I,
+        instruction: 0x00c5a533,
+        pre: State::new(0x0, &[(11, 0x7fffffff), (12, 1)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "slt a0, a1, a2 [-2147483648, -1]",
+        extension: IsaExtension::I,
+        instruction: 0x00c5a533,
+        pre: State::new(0x0, &[(11, -0x80000000), (12, -1)], &[]),
+        post: State::new(0x4, &[(10, 1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sltu a0, a1, a2 [5, 3]",
+        extension: IsaExtension::I,
+        instruction: 0x00c5b533,
+        pre: State::new(0x0, &[(11, 5), (12, 3)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sltu a0, a1, a2 [-1, 1]",
+        extension: IsaExtension::I,
+        instruction: 0x00c5b533,
+        pre: State::new(0x0, &[(11, -1), (12, 1)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sltu a0, a1, a2 [2147483647, 1]",
+        extension: IsaExtension::I,
+        instruction: 0x00c5b533,
+        pre: State::new(0x0, &[(11, 0x7fffffff), (12, 1)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sltu a0, a1, a2 [-2147483648, -1]",
+        extension: IsaExtension::I,
+        instruction: 0x00c5b533,
+        pre: State::new(0x0, &[(11, -0x80000000), (12, -1)], &[]),
+        post: State::new(0x4, &[(10, 1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "xor a0, a1, a2 [252641535, 16715535]",
+        extension: IsaExtension::I,
+        instruction: 0x00c5c533,
+        pre: State::new(0x0, &[(11, 0xf0f00ff), (12, 0xff0f0f)], &[]),
+        post: State::new(0x4, &[(10, 0xff00ff0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "xor a0, a1, a2 [-8, 2]",
+        extension: IsaExtension::I,
+        instruction: 0x00c5c533,
+        pre: State::new(0x0, &[(11, -8), (12, 2)], &[]),
+        post: State::new(0x4, &[(10, -6)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "xor a0, a1, a2 [305419896, 33]",
+        extension: IsaExtension::I,
+        instruction: 0x00c5c533,
+        pre: State::new(0x0, &[(11, 0x12345678), (12, 33)], &[]),
+        post: State::new(0x4, &[(10, 0x12345659)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "srl a0, a1, a2 [252641535, 16715535]",
+        extension: IsaExtension::I,
+        instruction: 0x00c5d533,
+        pre: State::new(0x0, &[(11, 0xf0f00ff), (12, 0xff0f0f)], &[]),
+        post: State::new(0x4, &[(10, 0x1e1e)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "srl a0, a1, a2 [-8, 2]",
+        extension: IsaExtension::I,
+        instruction: 0x00c5d533,
+        pre: State::new(0x0, &[(11, -8), (12, 2)], &[]),
+        post: State::new(0x4, &[(10, 0x3ffffffe)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "srl a0, a1, a2 [305419896, 33]",
+        extension: IsaExtension::I,
+        instruction: 0x00c5d533,
+        pre: State::new(0x0, &[(11, 0x12345678), (12, 33)], &[]),
+        post: State::new(0x4, &[(10, 0x91a2b3c)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sra a0, a1, a2 [252641535, 16715535]",
+        extension: IsaExtension::I,
+        instruction: 0x40c5d533,
+        pre: State::new(0x0, &[(11, 0xf0f00ff), (12, 0xff0f0f)], &[]),
+        post: State::new(0x4, &[(10, 0x1e1e)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sra a0, a1, a2 [-8, 2]",
+        extension: IsaExtension::I,
+        instruction: 0x40c5d533,
+        pre: State::new(0x0, &[(11, -8), (12, 2)], &[]),
+        post: State::new(0x4, &[(10, -2)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sra a0, a1, a2 [305419896, 33]",
+        extension: IsaExtension::I,
+        instruction: 0x40c5d533,
+        pre: State::new(0x0, &[(11, 0x12345678), (12, 33)], &[]),
+        post: State::new(0x4, &[(10, 0x91a2b3c)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "or a0, a1, a2 [252641535, 16715535]",
+        extension: IsaExtension::I,
+        instruction: 0x00c5e533,
+        pre: State::new(0x0, &[(11, 0xf0f00ff), (12, 0xff0f0f)], &[]),
+        post: State::new(0x4, &[(10, 0xfff0fff)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "or a0, a1, a2 [-8, 2]",
+        extension: IsaExtension::I,
+        instruction: 0x00c5e533,
+        pre: State::new(0x0, &[(11, -8), (12, 2)], &[]),
+        post: State::new(0x4, &[(10, -6)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "or a0, a1, a2 [305419896, 33]",
+        extension: IsaExtension::I,
+        instruction: 0x00c5e533,
+        pre: State::new(0x0, &[(11, 0x12345678), (12, 33)], &[]),
+        post: State::new(0x4, &[(10, 0x12345679)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "and a0, a1, a2 [252641535, 16715535]",
+        extension: IsaExtension::I,
+        instruction: 0x00c5f533,
+        pre: State::new(0x0, &[(11, 0xf0f00ff), (12, 0xff0f0f)], &[]),
+        post: State::new(0x4, &[(10, 0xf000f)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "and a0, a1, a2 [-8, 2]",
+        extension: IsaExtension::I,
+        instruction: 0x00c5f533,
+        pre: State::new(0x0, &[(11, -8), (12, 2)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "and a0, a1, a2 [305419896, 33]",
+        extension: IsaExtension::I,
+        instruction: 0x00c5f533,
+        pre: State::new(0x0, &[(11, 0x12345678), (12, 33)], &[]),
+        post: State::new(0x4, &[(10, 32)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "add zero, a1, a2",
+        extension: IsaExtension::I,
+        instruction: 0x00c58033,
+        pre: State::new(0x0, &[(11, 1), (12, 2)], &[]),
+        post: State::new(0x4, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "addi a0, a1, -1 [5]",
+        extension: IsaExtension::I,
+        instruction: 0xfff58513,
+        pre: State::new(0x0, &[(11, 5)], &[]),
+        post: State::new(0x4, &[(10, 4)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "addi a0, a1, 2047 [-2147483648]",
+        extension: IsaExtension::I,
+        instruction: 0x7ff58513,
+        pre: State::new(0x0, &[(11, -0x80000000)], &[]),
+        post: State::new(0x4, &[(10, -0x7ffff801)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "addi a0, a1, -2048 [2147483647]",
+        extension: IsaExtension::I,
+        instruction: 0x80058513,
+        pre: State::new(0x0, &[(11, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, 0x7ffff7ff)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "addi a0, a1, 0 [0]",
+        extension: IsaExtension::I,
+        instruction: 0x00058513,
+        pre: State::new(0x0, &[(11, 0)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "slti a0, a1, -1 [5]",
+        extension: IsaExtension::I,
+        instruction: 0xfff5a513,
+        pre: State::new(0x0, &[(11, 5)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "slti a0, a1, 2047 [-2147483648]",
+        extension: IsaExtension::I,
+        instruction: 0x7ff5a513,
+        pre: State::new(0x0, &[(11, -0x80000000)], &[]),
+        post: State::new(0x4, &[(10, 1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "slti a0, a1, -2048 [2147483647]",
+        extension: IsaExtension::I,
+        instruction: 0x8005a513,
+        pre: State::new(0x0, &[(11, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "slti a0, a1, 0 [0]",
+        extension: IsaExtension::I,
+        instruction: 0x0005a513,
+        pre: State::new(0x0, &[(11, 0)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sltiu a0, a1, -1 [5]",
+        extension: IsaExtension::I,
+        instruction: 0xfff5b513,
+        pre: State::new(0x0, &[(11, 5)], &[]),
+        post: State::new(0x4, &[(10, 1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sltiu a0, a1, 2047 [-2147483648]",
+        extension: IsaExtension::I,
+        instruction: 0x7ff5b513,
+        pre: State::new(0x0, &[(11, -0x80000000)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sltiu a0, a1, -2048 [2147483647]",
+        extension: IsaExtension::I,
+        instruction: 0x8005b513,
+        pre: State::new(0x0, &[(11, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, 1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sltiu a0, a1, 0 [0]",
+        extension: IsaExtension::I,
+        instruction: 0x0005b513,
+        pre: State::new(0x0, &[(11, 0)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "xori a0, a1, -1 [5]",
+        extension: IsaExtension::I,
+        instruction: 0xfff5c513,
+        pre: State::new(0x0, &[(11, 5)], &[]),
+        post: State::new(0x4, &[(10, -6)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "xori a0, a1, 2047 [-2147483648]",
+        extension: IsaExtension::I,
+        instruction: 0x7ff5c513,
+        pre: State::new(0x0, &[(11, -0x80000000)], &[]),
+        post: State::new(0x4, &[(10, -0x7ffff801)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "xori a0, a1, -2048 [2147483647]",
+        extension: IsaExtension::I,
+        instruction: 0x8005c513,
+        pre: State::new(0x0, &[(11, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, -0x7ffff801)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "xori a0, a1, 0 [0]",
+        extension: IsaExtension::I,
+        instruction: 0x0005c513,
+        pre: State::new(0x0, &[(11, 0)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "ori a0, a1, -1 [5]",
+        extension: IsaExtension::I,
+        instruction: 0xfff5e513,
+        pre: State::new(0x0, &[(11, 5)], &[]),
+        post: State::new(0x4, &[(10, -1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "ori a0, a1, 2047 [-2147483648]",
+        extension: IsaExtension::I,
+        instruction: 0x7ff5e513,
+        pre: State::new(0x0, &[(11, -0x80000000)], &[]),
+        post: State::new(0x4, &[(10, -0x7ffff801)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "ori a0, a1, -2048 [2147483647]",
+        extension: IsaExtension::I,
+        instruction: 0x8005e513,
+        pre: State::new(0x0, &[(11, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, -1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "ori a0, a1, 0 [0]",
+        extension: IsaExtension::I,
+        instruction: 0x0005e513,
+        pre: State::new(0x0, &[(11, 0)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "andi a0, a1, -1 [5]",
+        extension: IsaExtension::I,
+        instruction: 0xfff5f513,
+        pre: State::new(0x0, &[(11, 5)], &[]),
+        post: State::new(0x4, &[(10, 5)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "andi a0, a1, 2047 [-2147483648]",
+        extension: IsaExtension::I,
+        instruction: 0x7ff5f513,
+        pre: State::new(0x0, &[(11, -0x80000000)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "andi a0, a1, -2048 [2147483647]",
+        extension: IsaExtension::I,
+        instruction: 0x8005f513,
+        pre: State::new(0x0, &[(11, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, 0x7ffff800)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "andi a0, a1, 0 [0]",
+        extension: IsaExtension::I,
+        instruction: 0x0005f513,
+        pre: State::new(0x0, &[(11, 0)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "slli a0, a1, 4 [-2147483632]",
+        extension: IsaExtension::I,
+        instruction: 0x00459513,
+        pre: State::new(0x0, &[(11, -0x7ffffff0)], &[]),
+        post: State::new(0x4, &[(10, 256)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "slli a0, a1, 31 [2147483647]",
+        extension: IsaExtension::I,
+        instruction: 0x01f59513,
+        pre: State::new(0x0, &[(11, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, -0x80000000)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "slli a0, a1, 0 [-1]",
+        extension: IsaExtension::I,
+        instruction: 0x00059513,
+        pre: State::new(0x0, &[(11, -1)], &[]),
+        post: State::new(0x4, &[(10, -1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "srli a0, a1, 4 [-2147483632]",
+        extension: IsaExtension::I,
+        instruction: 0x0045d513,
+        pre: State::new(0x0, &[(11, -0x7ffffff0)], &[]),
+        post: State::new(0x4, &[(10, 0x8000001)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "srli a0, a1, 31 [2147483647]",
+        extension: IsaExtension::I,
+        instruction: 0x01f5d513,
+        pre: State::new(0x0, &[(11, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "srli a0, a1, 0 [-1]",
+        extension: IsaExtension::I,
+        instruction: 0x0005d513,
+        pre: State::new(0x0, &[(11, -1)], &[]),
+        post: State::new(0x4, &[(10, -1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "srai a0, a1, 4 [-2147483632]",
+        extension: IsaExtension::I,
+        instruction: 0x4045d513,
+        pre: State::new(0x0, &[(11, -0x7ffffff0)], &[]),
+        post: State::new(0x4, &[(10, -0x7ffffff)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "srai a0, a1, 31 [2147483647]",
+        extension: IsaExtension::I,
+        instruction: 0x41f5d513,
+        pre: State::new(0x0, &[(11, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "srai a0, a1, 0 [-1]",
+        extension: IsaExtension::I,
+        instruction: 0x4005d513,
+        pre: State::new(0x0, &[(11, -1)], &[]),
+        post: State::new(0x4, &[(10, -1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "lui a0, 0x12345",
+        extension: IsaExtension::I,
+        instruction: 0x12345537,
+        pre: State::new(0x0, &[], &[]),
+        post: State::new(0x4, &[(10, 0x12345000)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "lui a0, 0xfffff",
+        extension: IsaExtension::I,
+        instruction: 0xfffff537,
+        pre: State::new(0x0, &[], &[]),
+        post: State::new(0x4, &[(10, -0x1000)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "auipc a0, 0x1",
+        extension: IsaExtension::I,
+        instruction: 0x00001517,
+        pre: State::new(0x10, &[], &[]),
+        post: State::new(0x14, &[(10, 0x1010)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "auipc a0, 0xfffff",
+        extension: IsaExtension::I,
+        instruction: 0xfffff517,
+        pre: State::new(0x20, &[], &[]),
+        post: State::new(0x24, &[(10, -0xfe0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "jal ra, 16",
+        extension: IsaExtension::I,
+        instruction: 0x010000ef,
+        pre: State::new(0x20, &[], &[]),
+        post: State::new(0x30, &[(1, 36)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "jal zero, -16",
+        extension: IsaExtension::I,
+        instruction: 0xff1ff06f,
+        pre: State::new(0x20, &[], &[]),
+        post: State::new(0x10, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "jalr ra, 4(a0)",
+        extension: IsaExtension::I,
+        instruction: 0x004500e7,
+        pre: State::new(0x10, &[(10, 64)], &[]),
+        post: State::new(0x44, &[(1, 20)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "jalr a0, -4(a0)",
+        extension: IsaExtension::I,
+        instruction: 0xffc50567,
+        pre: State::new(0x10, &[(10, 64)], &[]),
+        post: State::new(0x3c, &[(10, 20)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "beq a0, a1, -8 [1, 1]",
+        extension: IsaExtension::I,
+        instruction: 0xfeb50ce3,
+        pre: State::new(0x20, &[(10, 1), (11, 1)], &[]),
+        post: State::new(0x18, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "beq a0, a1, 12 [-1, 1]",
+        extension: IsaExtension::I,
+        instruction: 0x00b50663,
+        pre: State::new(0x20, &[(10, -1), (11, 1)], &[]),
+        post: State::new(0x24, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "beq a0, a1, 12 [1, -1]",
+        extension: IsaExtension::I,
+        instruction: 0x00b50663,
+        pre: State::new(0x20, &[(10, 1), (11, -1)], &[]),
+        post: State::new(0x24, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "bne a0, a1, -8 [1, 1]",
+        extension: IsaExtension::I,
+        instruction: 0xfeb51ce3,
+        pre: State::new(0x20, &[(10, 1), (11, 1)], &[]),
+        post: State::new(0x24, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "bne a0, a1, 12 [-1, 1]",
+        extension: IsaExtension::I,
+        instruction: 0x00b51663,
+        pre: State::new(0x20, &[(10, -1), (11, 1)], &[]),
+        post: State::new(0x2c, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "bne a0, a1, 12 [1, -1]",
+        extension: IsaExtension::I,
+        instruction: 0x00b51663,
+        pre: State::new(0x20, &[(10, 1), (11, -1)], &[]),
+        post: State::new(0x2c, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "blt a0, a1, -8 [1, 1]",
+        extension: IsaExtension::I,
+        instruction: 0xfeb54ce3,
+        pre: State::new(0x20, &[(10, 1), (11, 1)], &[]),
+        post: State::new(0x24, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "blt a0, a1, 12 [-1, 1]",
+        extension: IsaExtension::I,
+        instruction: 0x00b54663,
+        pre: State::new(0x20, &[(10, -1), (11, 1)], &[]),
+        post: State::new(0x2c, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "blt a0, a1, 12 [1, -1]",
+        extension: IsaExtension::I,
+        instruction: 0x00b54663,
+        pre: State::new(0x20, &[(10, 1), (11, -1)], &[]),
+        post: State::new(0x24, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "bge a0, a1, -8 [1, 1]",
+        extension: IsaExtension::I,
+        instruction: 0xfeb55ce3,
+        pre: State::new(0x20, &[(10, 1), (11, 1)], &[]),
+        post: State::new(0x18, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "bge a0, a1, 12 [-1, 1]",
+        extension: IsaExtension::I,
+        instruction: 0x00b55663,
+        pre: State::new(0x20, &[(10, -1), (11, 1)], &[]),
+        post: State::new(0x24, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "bge a0, a1, 12 [1, -1]",
+        extension: IsaExtension::I,
+        instruction: 0x00b55663,
+        pre: State::new(0x20, &[(10, 1), (11, -1)], &[]),
+        post: State::new(0x2c, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "bltu a0, a1, -8 [1, 1]",
+        extension: IsaExtension::I,
+        instruction: 0xfeb56ce3,
+        pre: State::new(0x20, &[(10, 1), (11, 1)], &[]),
+        post: State::new(0x24, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "bltu a0, a1, 12 [-1, 1]",
+        extension: IsaExtension::I,
+        instruction: 0x00b56663,
+        pre: State::new(0x20, &[(10, -1), (11, 1)], &[]),
+        post: State::new(0x24, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "bltu a0, a1, 12 [1, -1]",
+        extension: IsaExtension::I,
+        instruction: 0x00b56663,
+        pre: State::new(0x20, &[(10, 1), (11, -1)], &[]),
+        post: State::new(0x2c, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "bgeu a0, a1, -8 [1, 1]",
+        extension: IsaExtension::I,
+        instruction: 0xfeb57ce3,
+        pre: State::new(0x20, &[(10, 1), (11, 1)], &[]),
+        post: State::new(0x18, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "bgeu a0, a1, 12 [-1, 1]",
+        extension: IsaExtension::I,
+        instruction: 0x00b57663,
+        pre: State::new(0x20, &[(10, -1), (11, 1)], &[]),
+        post: State::new(0x2c, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "bgeu a0, a1, 12 [1, -1]",
+        extension: IsaExtension::I,
+        instruction: 0x00b57663,
+        pre: State::new(0x20, &[(10, 1), (11, -1)], &[]),
+        post: State::new(0x24, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "lb a0, 0(a1)",
+        extension: IsaExtension::I,
+        instruction: 0x00058503,
+        pre: State::new(
+            0x0,
+            &[(11, -0x7ffffff0)],
+            &[(
+                0x80000010,
+                &[0x80, 0xff, 0x34, 0x12, 0x78, 0x56, 0x34, 0x92],
+            )],
+        ),
+        post: State::new(0x4, &[(10, -128)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "lb a0, 2(a1)",
+        extension: IsaExtension::I,
+        instruction: 0x00258503,
+        pre: State::new(
+            0x0,
+            &[(11, -0x7ffffff0)],
+            &[(
+                0x80000010,
+                &[0x80, 0xff, 0x34, 0x12, 0x78, 0x56, 0x34, 0x92],
+            )],
+        ),
+        post: State::new(0x4, &[(10, 52)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "lbu a0, 0(a1)",
+        extension: IsaExtension::I,
+        instruction: 0x0005c503,
+        pre: State::new(
+            0x0,
+            &[(11, -0x7ffffff0)],
+            &[(
+                0x80000010,
+                &[0x80, 0xff, 0x34, 0x12, 0x78, 0x56, 0x34, 0x92],
+            )],
+        ),
+        post: State::new(0x4, &[(10, 128)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "lh a0, 0(a1)",
+        extension: IsaExtension::I,
+        instruction: 0x00059503,
+        pre: State::new(
+            0x0,
+            &[(11, -0x7ffffff0)],
+            &[(
+                0x80000010,
+                &[0x80, 0xff, 0x34, 0x12, 0x78, 0x56, 0x34, 0x92],
+            )],
+        ),
+        post: State::new(0x4, &[(10, -128)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "lh a0, 2(a1)",
+        extension: IsaExtension::I,
+        instruction: 0x00259503,
+        pre: State::new(
+            0x0,
+            &[(11, -0x7ffffff0)],
+            &[(
+                0x80000010,
+                &[0x80, 0xff, 0x34, 0x12, 0x78, 0x56, 0x34, 0x92],
+            )],
+        ),
+        post: State::new(0x4, &[(10, 0x1234)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "lhu a0, 0(a1)",
+        extension: IsaExtension::I,
+        instruction: 0x0005d503,
+        pre: State::new(
+            0x0,
+            &[(11, -0x7ffffff0)],
+            &[(
+                0x80000010,
+                &[0x80, 0xff, 0x34, 0x12, 0x78, 0x56, 0x34, 0x92],
+            )],
+        ),
+        post: State::new(0x4, &[(10, 0xff80)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "lw a0, 0(a1)",
+        extension: IsaExtension::I,
+        instruction: 0x0005a503,
+        pre: State::new(
+            0x0,
+            &[(11, -0x7ffffff0)],
+            &[(
+                0x80000010,
+                &[0x80, 0xff, 0x34, 0x12, 0x78, 0x56, 0x34, 0x92],
+            )],
+        ),
+        post: State::new(0x4, &[(10, 0x1234ff80)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "lw a0, 4(a1)",
+        extension: IsaExtension::I,
+        instruction: 0x0045a503,
+        pre: State::new(
+            0x0,
+            &[(11, -0x7ffffff0)],
+            &[(
+                0x80000010,
+                &[0x80, 0xff, 0x34, 0x12, 0x78, 0x56, 0x34, 0x92],
+            )],
+        ),
+        post: State::new(0x4, &[(10, -0x6dcba988)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "lhu zero, 0(a1)",
+        extension: IsaExtension::I,
+        instruction: 0x0005d003,
+        pre: State::new(0x0, &[(11, -0x7ffffff0)], &[(0x80000010, &[0x34, 0x12])]),
+        post: State::new(0x4, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "lw a0, -4(a1)",
+        extension: IsaExtension::I,
+        instruction: 0xffc5a503,
+        pre: State::new(
+            0x0,
+            &[(11, -0x7fffffe8)],
+            &[(
+                0x80000010,
+                &[0x80, 0xff, 0x34, 0x12, 0x78, 0x56, 0x34, 0x92],
+            )],
+        ),
+        post: State::new(0x4, &[(10, -0x6dcba988)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "lw a0, 0(a1) [code]",
+        extension: IsaExtension::I,
+        instruction: 0x0005a503,
+        pre: State::new(0x0, &[(11, 0)], &[]),
+        post: State::new(0x4, &[(10, 0x5a503)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "lw a0, 0(a1) [out of bounds]",
+        extension: IsaExtension::I,
+        instruction: 0x0005a503,
+        pre: State::new(0x0, &[(11, -0x7ffff000)], &[]),
+        post: State::new(0x0, &[], &[]),
+        result: Err(EmbiveError::LoadFault {
+            pc: 0x0,
+            address: 0x80001000,
+        }),
+    },
+    Vector {
+        name: "sb a0, 4(a1)",
+        extension: IsaExtension::I,
+        instruction: 0x00a58223,
+        pre: State::new(
+            0x0,
+            &[(10, -0x76543211), (11, -0x7ffffff0)],
+            &[(
+                0x80000010,
+                &[0x80, 0xff, 0x34, 0x12, 0x78, 0x56, 0x34, 0x92],
+            )],
+        ),
+        post: State::new(
+            0x4,
+            &[],
+            &[(
+                0x80000010,
+                &[0x80, 0xff, 0x34, 0x12, 0xef, 0x56, 0x34, 0x92],
+            )],
+        ),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sh a0, 4(a1)",
+        extension: IsaExtension::I,
+        instruction: 0x00a59223,
+        pre: State::new(
+            0x0,
+            &[(10, -0x76543211), (11, -0x7ffffff0)],
+            &[(
+                0x80000010,
+                &[0x80, 0xff, 0x34, 0x12, 0x78, 0x56, 0x34, 0x92],
+            )],
+        ),
+        post: State::new(
+            0x4,
+            &[],
+            &[(
+                0x80000010,
+                &[0x80, 0xff, 0x34, 0x12, 0xef, 0xcd, 0x34, 0x92],
+            )],
+        ),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sw a0, 4(a1)",
+        extension: IsaExtension::I,
+        instruction: 0x00a5a223,
+        pre: State::new(
+            0x0,
+            &[(10, -0x76543211), (11, -0x7ffffff0)],
+            &[(
+                0x80000010,
+                &[0x80, 0xff, 0x34, 0x12, 0x78, 0x56, 0x34, 0x92],
+            )],
+        ),
+        post: State::new(
+            0x4,
+            &[],
+            &[(
+                0x80000010,
+                &[0x80, 0xff, 0x34, 0x12, 0xef, 0xcd, 0xab, 0x89],
+            )],
+        ),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sw a0, -4(a1)",
+        extension: IsaExtension::I,
+        instruction: 0xfea5ae23,
+        pre: State::new(0x0, &[(10, 0x11223344), (11, -0x7fffffec)], &[]),
+        post: State::new(0x4, &[], &[(0x80000010, &[0x44, 0x33, 0x22, 0x11])]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "sw a0, 0(a1) [code]",
+        extension: IsaExtension::I,
+        instruction: 0x00a5a023,
+        pre: State::new(0x0, &[(10, 1), (11, 0)], &[]),
+        post: State::new(0x0, &[], &[]),
+        result: Err(EmbiveError::StoreFault {
+            pc: 0x0,
+            address: 0x0,
+        }),
+    },
+    Vector {
+        name: "fence",
+        extension: IsaExtension::I,
+        instruction: 0x0ff0000f,
+        pre: State::new(0x0, &[], &[]),
+        post: State::new(0x4, &[], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "ebreak",
+        extension: IsaExtension::I,
+        instruction: 0x00100073,
+        pre: State::new(0x0, &[], &[]),
+        post: State::new(0x4, &[], &[]),
+        result: Ok(false),
+    },
+];
+
+/// RV32M test vectors.
+pub const RV32M: &[Vector] = &[
+    Vector {
+        name: "mul a0, a1, a2 [7, 3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c58533,
+        pre: State::new(0x0, &[(11, 7), (12, 3)], &[]),
+        post: State::new(0x4, &[(10, 21)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mul a0, a1, a2 [-7, 3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c58533,
+        pre: State::new(0x0, &[(11, -7), (12, 3)], &[]),
+        post: State::new(0x4, &[(10, -21)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mul a0, a1, a2 [7, -3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c58533,
+        pre: State::new(0x0, &[(11, 7), (12, -3)], &[]),
+        post: State::new(0x4, &[(10, -21)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mul a0, a1, a2 [-2147483648, -1]",
+        extension: IsaExtension::M,
+        instruction: 0x02c58533,
+        pre: State::new(0x0, &[(11, -0x80000000), (12, -1)], &[]),
+        post: State::new(0x4, &[(10, -0x80000000)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mul a0, a1, a2 [5, 0]",
+        extension: IsaExtension::M,
+        instruction: 0x02c58533,
+        pre: State::new(0x0, &[(11, 5), (12, 0)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mul a0, a1, a2 [-1, -1]",
+        extension: IsaExtension::M,
+        instruction: 0x02c58533,
+        pre: State::new(0x0, &[(11, -1), (12, -1)], &[]),
+        post: State::new(0x4, &[(10, 1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mul a0, a1, a2 [2147483647, 2147483647]",
+        extension: IsaExtension::M,
+        instruction: 0x02c58533,
+        pre: State::new(0x0, &[(11, 0x7fffffff), (12, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, 1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mul a0, a1, a2 [-2147483648, 2147483647]",
+        extension: IsaExtension::M,
+        instruction: 0x02c58533,
+        pre: State::new(0x0, &[(11, -0x80000000), (12, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, -0x80000000)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulh a0, a1, a2 [7, 3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c59533,
+        pre: State::new(0x0, &[(11, 7), (12, 3)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulh a0, a1, a2 [-7, 3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c59533,
+        pre: State::new(0x0, &[(11, -7), (12, 3)], &[]),
+        post: State::new(0x4, &[(10, -1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulh a0, a1, a2 [7, -3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c59533,
+        pre: State::new(0x0, &[(11, 7), (12, -3)], &[]),
+        post: State::new(0x4, &[(10, -1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulh a0, a1, a2 [-2147483648, -1]",
+        extension: IsaExtension::M,
+        instruction: 0x02c59533,
+        pre: State::new(0x0, &[(11, -0x80000000), (12, -1)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulh a0, a1, a2 [5, 0]",
+        extension: IsaExtension::M,
+        instruction: 0x02c59533,
+        pre: State::new(0x0, &[(11, 5), (12, 0)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulh a0, a1, a2 [-1, -1]",
+        extension: IsaExtension::M,
+        instruction: 0x02c59533,
+        pre: State::new(0x0, &[(11, -1), (12, -1)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulh a0, a1, a2 [2147483647, 2147483647]",
+        extension: IsaExtension::M,
+        instruction: 0x02c59533,
+        pre: State::new(0x0, &[(11, 0x7fffffff), (12, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, 0x3fffffff)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulh a0, a1, a2 [-2147483648, 2147483647]",
+        extension: IsaExtension::M,
+        instruction: 0x02c59533,
+        pre: State::new(0x0, &[(11, -0x80000000), (12, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, -0x40000000)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulhsu a0, a1, a2 [7, 3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5a533,
+        pre: State::new(0x0, &[(11, 7), (12, 3)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulhsu a0, a1, a2 [-7, 3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5a533,
+        pre: State::new(0x0, &[(11, -7), (12, 3)], &[]),
+        post: State::new(0x4, &[(10, -1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulhsu a0, a1, a2 [7, -3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5a533,
+        pre: State::new(0x0, &[(11, 7), (12, -3)], &[]),
+        post: State::new(0x4, &[(10, 6)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulhsu a0, a1, a2 [-2147483648, -1]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5a533,
+        pre: State::new(0x0, &[(11, -0x80000000), (12, -1)], &[]),
+        post: State::new(0x4, &[(10, -0x80000000)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulhsu a0, a1, a2 [5, 0]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5a533,
+        pre: State::new(0x0, &[(11, 5), (12, 0)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulhsu a0, a1, a2 [-1, -1]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5a533,
+        pre: State::new(0x0, &[(11, -1), (12, -1)], &[]),
+        post: State::new(0x4, &[(10, -1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulhsu a0, a1, a2 [2147483647, 2147483647]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5a533,
+        pre: State::new(0x0, &[(11, 0x7fffffff), (12, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, 0x3fffffff)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulhsu a0, a1, a2 [-2147483648, 2147483647]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5a533,
+        pre: State::new(0x0, &[(11, -0x80000000), (12, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, -0x40000000)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulhu a0, a1, a2 [7, 3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5b533,
+        pre: State::new(0x0, &[(11, 7), (12, 3)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulhu a0, a1, a2 [-7, 3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5b533,
+        pre: State::new(0x0, &[(11, -7), (12, 3)], &[]),
+        post: State::new(0x4, &[(10, 2)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulhu a0, a1, a2 [7, -3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5b533,
+        pre: State::new(0x0, &[(11, 7), (12, -3)], &[]),
+        post: State::new(0x4, &[(10, 6)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulhu a0, a1, a2 [-2147483648, -1]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5b533,
+        pre: State::new(0x0, &[(11, -0x80000000), (12, -1)], &[]),
+        post: State::new(0x4, &[(10, 0x7fffffff)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulhu a0, a1, a2 [5, 0]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5b533,
+        pre: State::new(0x0, &[(11, 5), (12, 0)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulhu a0, a1, a2 [-1, -1]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5b533,
+        pre: State::new(0x0, &[(11, -1), (12, -1)], &[]),
+        post: State::new(0x4, &[(10, -2)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulhu a0, a1, a2 [2147483647, 2147483647]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5b533,
+        pre: State::new(0x0, &[(11, 0x7fffffff), (12, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, 0x3fffffff)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "mulhu a0, a1, a2 [-2147483648, 2147483647]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5b533,
+        pre: State::new(0x0, &[(11, -0x80000000), (12, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, 0x3fffffff)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "div a0, a1, a2 [7, 3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5c533,
+        pre: State::new(0x0, &[(11, 7), (12, 3)], &[]),
+        post: State::new(0x4, &[(10, 2)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "div a0, a1, a2 [-7, 3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5c533,
+        pre: State::new(0x0, &[(11, -7), (12, 3)], &[]),
+        post: State::new(0x4, &[(10, -2)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "div a0, a1, a2 [7, -3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5c533,
+        pre: State::new(0x0, &[(11, 7), (12, -3)], &[]),
+        post: State::new(0x4, &[(10, -2)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "div a0, a1, a2 [-2147483648, -1]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5c533,
+        pre: State::new(0x0, &[(11, -0x80000000), (12, -1)], &[]),
+        post: State::new(0x4, &[(10, -0x80000000)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "div a0, a1, a2 [5, 0]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5c533,
+        pre: State::new(0x0, &[(11, 5), (12, 0)], &[]),
+        post: State::new(0x4, &[(10, -1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "div a0, a1, a2 [-1, -1]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5c533,
+        pre: State::new(0x0, &[(11, -1), (12, -1)], &[]),
+        post: State::new(0x4, &[(10, 1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "div a0, a1, a2 [2147483647, 2147483647]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5c533,
+        pre: State::new(0x0, &[(11, 0x7fffffff), (12, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, 1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "div a0, a1, a2 [-2147483648, 2147483647]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5c533,
+        pre: State::new(0x0, &[(11, -0x80000000), (12, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, -1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "divu a0, a1, a2 [7, 3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5d533,
+        pre: State::new(0x0, &[(11, 7), (12, 3)], &[]),
+        post: State::new(0x4, &[(10, 2)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "divu a0, a1, a2 [-7, 3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5d533,
+        pre: State::new(0x0, &[(11, -7), (12, 3)], &[]),
+        post: State::new(0x4, &[(10, 0x55555553)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "divu a0, a1, a2 [7, -3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5d533,
+        pre: State::new(0x0, &[(11, 7), (12, -3)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "divu a0, a1, a2 [-2147483648, -1]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5d533,
+        pre: State::new(0x0, &[(11, -0x80000000), (12, -1)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "divu a0, a1, a2 [5, 0]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5d533,
+        pre: State::new(0x0, &[(11, 5), (12, 0)], &[]),
+        post: State::new(0x4, &[(10, -1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "divu a0, a1, a2 [-1, -1]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5d533,
+        pre: State::new(0x0, &[(11, -1), (12, -1)], &[]),
+        post: State::new(0x4, &[(10, 1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "divu a0, a1, a2 [2147483647, 2147483647]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5d533,
+        pre: State::new(0x0, &[(11, 0x7fffffff), (12, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, 1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "divu a0, a1, a2 [-2147483648, 2147483647]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5d533,
+        pre: State::new(0x0, &[(11, -0x80000000), (12, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, 1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "rem a0, a1, a2 [7, 3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5e533,
+        pre: State::new(0x0, &[(11, 7), (12, 3)], &[]),
+        post: State::new(0x4, &[(10, 1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "rem a0, a1, a2 [-7, 3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5e533,
+        pre: State::new(0x0, &[(11, -7), (12, 3)], &[]),
+        post: State::new(0x4, &[(10, -1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "rem a0, a1, a2 [7, -3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5e533,
+        pre: State::new(0x0, &[(11, 7), (12, -3)], &[]),
+        post: State::new(0x4, &[(10, 1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "rem a0, a1, a2 [-2147483648, -1]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5e533,
+        pre: State::new(0x0, &[(11, -0x80000000), (12, -1)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "rem a0, a1, a2 [5, 0]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5e533,
+        pre: State::new(0x0, &[(11, 5), (12, 0)], &[]),
+        post: State::new(0x4, &[(10, 5)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "rem a0, a1, a2 [-1, -1]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5e533,
+        pre: State::new(0x0, &[(11, -1), (12, -1)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "rem a0, a1, a2 [2147483647, 2147483647]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5e533,
+        pre: State::new(0x0, &[(11, 0x7fffffff), (12, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "rem a0, a1, a2 [-2147483648, 2147483647]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5e533,
+        pre: State::new(0x0, &[(11, -0x80000000), (12, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, -1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "remu a0, a1, a2 [7, 3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5f533,
+        pre: State::new(0x0, &[(11, 7), (12, 3)], &[]),
+        post: State::new(0x4, &[(10, 1)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "remu a0, a1, a2 [-7, 3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5f533,
+        pre: State::new(0x0, &[(11, -7), (12, 3)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "remu a0, a1, a2 [7, -3]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5f533,
+        pre: State::new(0x0, &[(11, 7), (12, -3)], &[]),
+        post: State::new(0x4, &[(10, 7)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "remu a0, a1, a2 [-2147483648, -1]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5f533,
+        pre: State::new(0x0, &[(11, -0x80000000), (12, -1)], &[]),
+        post: State::new(0x4, &[(10, -0x80000000)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "remu a0, a1, a2 [5, 0]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5f533,
+        pre: State::new(0x0, &[(11, 5), (12, 0)], &[]),
+        post: State::new(0x4, &[(10, 5)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "remu a0, a1, a2 [-1, -1]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5f533,
+        pre: State::new(0x0, &[(11, -1), (12, -1)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "remu a0, a1, a2 [2147483647, 2147483647]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5f533,
+        pre: State::new(0x0, &[(11, 0x7fffffff), (12, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, 0)], &[]),
+        result: Ok(true),
+    },
+    Vector {
+        name: "remu a0, a1, a2 [-2147483648, 2147483647]",
+        extension: IsaExtension::M,
+        instruction: 0x02c5f533,
+        pre: State::new(0x0, &[(11, -0x80000000), (12, 0x7fffffff)], &[]),
+        post: State::new(0x4, &[(10, 1)], &[]),
+        result: Ok(true),
+    },
+];
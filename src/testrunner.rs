@@ -0,0 +1,356 @@
+//! Test Runner Module
+//!
+//! Runs the upstream [riscv-tests](https://github.com/riscv-software-src/riscv-tests) ELFs (ex.: `rv32ui-p-add`,
+//! `rv32um-p-mul`) as they are built, so users can re-run conformance locally against their exact feature
+//! combination and engine configuration.
+//!
+//! Layout: the tests are linked at `0x80000000` ([`crate::memory::RAM_OFFSET`]), so they are loaded and run from RAM
+//! ([`Elf::load`], no code region). The `p` environment starts in machine mode: it reads `mhartid`,
+//! initializes `mtvec`, `satp`, `pmp*`, `medeleg`, `mideleg`, `mie` and `mstatus`, and enters the test with `mret`.
+//! The runner emulates these registers ([`MachineCsrs`], reads are zero and writes are ignored)
+//! and vectors `ecall` to the guest trap handler (check [`crate::trap`]).
+//!
+//! Pass/fail conventions:
+//! - `tohost` (upstream): the test result is stored to the `.tohost` section,
+//!   `1` for a pass, `(test << 1) | 1` for a failure.
+//! - Exit syscall (ELFs without a `.tohost` section, ex.: [embive-tests](https://github.com/embive/embive-tests)):
+//!   `ecall` with `a7` = [`EXIT_SYSCALL`], `a0` = `0` for a pass, `(test << 1) | 1` for a failure.
+//!
+//! With the `std` feature, a corpus can be run against every extension combination of the build
+//! (check [`matrix`]).
+//!
+//! ```
+//! use embive::{engine::Config, testrunner::{self, Failure}};
+//!
+//! fn conformance(elf: &[u8]) -> Result<(), Failure> {
+//!     let mut ram = [0; testrunner::RAM_SIZE];
+//!     testrunner::run(elf, &mut ram, Config::default(), testrunner::INSTRUCTION_LIMIT)
+//! }
+//! ```
+
+#[cfg(feature = "std")]
+pub mod matrix;
+
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::csr::CsrHandler;
+use crate::engine::{Config, Engine, SYSCALL_ARGS};
+use crate::error::{EmbiveError, LoaderError};
+use crate::loader::Elf;
+use crate::memory::{Memory, SliceMemory};
+use crate::syscall::Errno;
+use crate::trap::Exception;
+
+/// RAM size in bytes enough for the upstream tests (`.text.init`, `.tohost`, `.text` and `.data`, page aligned).
+pub const RAM_SIZE: usize = 64 * 1024;
+/// Default instruction limit of a test (a test looping forever fails with [`Failure::Timeout`]).
+pub const INSTRUCTION_LIMIT: u64 = 1_000_000;
+/// Exit syscall number (`a7`), used by ELFs without a `.tohost` section.
+pub const EXIT_SYSCALL: i32 = 93;
+/// Section holding the test result (upstream convention).
+pub const TOHOST_SECTION: &str = ".tohost";
+
+/// Why a test failed.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Failure {
+    /// Invalid ELF, or it doesn't fit in the RAM.
+    Load(LoaderError),
+    /// Engine error (ex.: an unsupported instruction for the enabled features).
+    Error(EmbiveError),
+    /// Test case reported as failed (`TESTNUM`).
+    Test(u32),
+    /// Halted, suspended or stopped without reporting a result.
+    Halted,
+    /// Instruction limit reached without reporting a result.
+    Timeout,
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Failure::Load(error) => write!(f, "load failed: {}", error),
+            Failure::Error(error) => write!(f, "engine error: {}", error),
+            Failure::Test(test) => write!(f, "test case {} failed", test),
+            Failure::Halted => write!(f, "halted without a result"),
+            Failure::Timeout => write!(f, "instruction limit reached"),
+        }
+    }
+}
+
+/// Machine-mode registers of the `p` environment, reads are zero (single hart, no paging or protection)
+/// and writes are ignored.
+pub struct MachineCsrs;
+
+impl CsrHandler for MachineCsrs {
+    fn read(_csr: u16) -> Option<u32> {
+        Some(0)
+    }
+
+    fn write(_csr: u16, _value: u32) -> bool {
+        true
+    }
+}
+
+/// Memory used to run a test: RAM only, watching the test result (check the [module documentation](self)).
+#[derive(Debug)]
+pub struct TestMemory<'a> {
+    /// Test RAM.
+    memory: SliceMemory<'a>,
+    /// `tohost` address (None = Exit syscall convention).
+    tohost: Option<u32>,
+    /// Reported result (`1` = Pass, `(test << 1) | 1` = Failure).
+    result: Option<u32>,
+}
+
+impl Memory for TestMemory<'_> {
+    #[inline]
+    fn load<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+        self.memory.load(address)
+    }
+
+    #[inline]
+    fn fetch<const N: usize>(&self, address: u32) -> Result<[u8; N], EmbiveError> {
+        self.memory.fetch(address)
+    }
+
+    #[inline]
+    fn store<const N: usize>(&mut self, address: u32, data: [u8; N]) -> Result<(), EmbiveError> {
+        if Some(address) == self.tohost && N == 4 {
+            let value = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+            if value != 0 {
+                self.result = Some(value);
+            }
+        }
+
+        self.memory.store(address, data)
+    }
+
+    #[inline]
+    fn load_bytes(&self, address: u32, buffer: &mut [u8]) -> Result<(), EmbiveError> {
+        self.memory.load_bytes(address, buffer)
+    }
+
+    #[inline]
+    fn store_bytes(&mut self, address: u32, data: &[u8]) -> Result<(), EmbiveError> {
+        self.memory.store_bytes(address, data)
+    }
+
+    #[inline(always)]
+    fn export_ram(&self) -> Option<&[u8]> {
+        self.memory.export_ram()
+    }
+}
+
+/// Exit syscall (check [`EXIT_SYSCALL`]).
+fn exit(nr: i32, args: &[i32; SYSCALL_ARGS], memory: &mut TestMemory) -> Result<i32, i32> {
+    if nr != EXIT_SYSCALL {
+        return Err(Errno::NotSupported.code());
+    }
+
+    memory.result = Some(match args[0] {
+        0 => 1,
+        code => code as u32,
+    });
+    Ok(0)
+}
+
+/// Run a test ELF (check the [module documentation](self)).
+///
+/// The entry point, CSR handler, syscall function and guest traps of the configuration are set by the runner,
+/// everything else (ex.: instruction granularity, extensions) is used as is.
+///
+/// Arguments:
+/// - `elf`: Test ELF file.
+/// - `ram`: Test RAM (ex.: [`RAM_SIZE`] bytes).
+/// - `config`: Engine configuration under test.
+/// - `limit`: Maximum number of instructions (ex.: [`INSTRUCTION_LIMIT`]).
+///
+/// Returns:
+/// - `Ok(())`: The test passed.
+/// - `Err(Failure)`: The test failed.
+pub fn run<'a>(
+    elf: &[u8],
+    ram: &'a mut [u8],
+    config: Config<TestMemory<'a>>,
+    limit: u64,
+) -> Result<(), Failure> {
+    let elf = Elf::parse(elf).map_err(Failure::Load)?;
+    let report = elf.load(&mut [], ram).map_err(Failure::Load)?;
+    let tohost = elf
+        .section(TOHOST_SECTION)
+        .map_err(Failure::Load)?
+        .map(|section| section.address);
+
+    // With `tohost`, the guest trap handler stores the result
+    let guest_traps = match tohost {
+        Some(_) => Exception::EnvironmentCall.bit(),
+        None => 0,
+    };
+    let config = report
+        .apply(config)
+        .with_csr::<MachineCsrs>()
+        .with_syscall_fn(Some(exit))
+        .with_guest_traps(guest_traps);
+
+    let mut memory = TestMemory {
+        memory: SliceMemory::new(&[], ram),
+        tohost,
+        result: None,
+    };
+    let mut engine = Engine::new(&mut memory, config).map_err(Failure::Error)?;
+
+    for _ in 0..limit {
+        let running = engine.step().map_err(Failure::Error)?;
+        match engine.memory.result {
+            Some(1) => return Ok(()),
+            Some(result) => return Err(Failure::Test(result >> 1)),
+            None if !running => return Err(Failure::Halted),
+            None => {}
+        }
+    }
+
+    Err(Failure::Timeout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::RAM_OFFSET;
+    use std::vec::Vec;
+
+    /// Machine-mode prologue (`mret` to the test at `0x30`), trap handler storing `gp` to `tohost` (`0x80001000`).
+    const PROLOGUE: &[u8] = &[
+        0x73, 0x25, 0x40, 0xf1, // csrr  a0, mhartid
+        0x97, 0x02, 0x00, 0x00, // auipc t0, 0
+        0x93, 0x82, 0x02, 0x02, // addi  t0, t0, 0x20 (trap_vector)
+        0x73, 0x90, 0x52, 0x30, // csrw  mtvec, t0
+        0x73, 0x50, 0x00, 0x18, // csrwi satp, 0
+        0x97, 0x02, 0x00, 0x00, // auipc t0, 0
+        0x93, 0x82, 0xc2, 0x01, // addi  t0, t0, 0x1c (test)
+        0x73, 0x90, 0x12, 0x34, // csrw  mepc, t0
+        0x73, 0x00, 0x20, 0x30, // mret
+        // trap_vector:
+        0x17, 0x1f, 0x00, 0x00, // auipc t5, 1
+        0x23, 0x2e, 0x3f, 0xfc, // sw    gp, -36(t5) (tohost)
+        0x6f, 0xf0, 0x9f, 0xff, // j     trap_vector
+    ];
+
+    /// Report the result in `gp` (ecall, exit syscall).
+    pub(super) const REPORT: &[u8] = &[
+        0x93, 0x08, 0xd0, 0x05, // li    a7, 93
+        0x13, 0x85, 0x01, 0x00, // mv    a0, gp
+        0x73, 0x00, 0x00, 0x00, // ecall
+    ];
+
+    /// Build a test ELF, code at `RAM_OFFSET` (entry point), with a `.tohost` section at `0x80001000` (if `tohost`).
+    pub(super) fn elf(code: &[&[u8]], tohost: bool) -> Vec<u8> {
+        let code = code.concat();
+        let mut elf = std::vec![0; 84];
+        elf[..7].copy_from_slice(&[0x7F, b'E', b'L', b'F', 1, 1, 1]);
+        elf[16..20].copy_from_slice(&[2, 0, 0xF3, 0]);
+        elf[20..24].copy_from_slice(&1u32.to_le_bytes());
+        elf[24..28].copy_from_slice(&RAM_OFFSET.to_le_bytes());
+        elf[28..32].copy_from_slice(&52u32.to_le_bytes());
+        elf[42..44].copy_from_slice(&32u16.to_le_bytes());
+        elf[44..46].copy_from_slice(&1u16.to_le_bytes());
+
+        // Loadable segment: type, offset, virtual address, physical address, file size, memory size, flags
+        let segment = [1, 84, RAM_OFFSET, RAM_OFFSET, code.len() as u32, 0x1008, 7];
+        for (i, field) in segment.iter().enumerate() {
+            elf[52 + i * 4..56 + i * 4].copy_from_slice(&field.to_le_bytes());
+        }
+        elf.extend_from_slice(&code);
+
+        if tohost {
+            // Section names, then headers: null, `.tohost` and `.shstrtab`
+            let names = elf.len() as u32;
+            elf.extend_from_slice(b"\0.tohost\0.shstrtab\0\0");
+            let headers = elf.len() as u32;
+            elf[32..36].copy_from_slice(&headers.to_le_bytes());
+            elf[46..52].copy_from_slice(&[40, 0, 3, 0, 2, 0]);
+            for fields in [
+                [0; 6],
+                [1, 1, 3, RAM_OFFSET + 0x1000, 0, 8],
+                [9, 3, 0, 0, names, 19],
+            ] {
+                elf.extend(fields.iter().flat_map(|field| field.to_le_bytes()));
+                elf.extend_from_slice(&[0; 16]);
+            }
+        }
+        elf
+    }
+
+    fn run_elf(elf: &[u8], limit: u64) -> Result<(), Failure> {
+        let mut ram = [0; 0x2000];
+        run(elf, &mut ram, Config::default(), limit)
+    }
+
+    #[test]
+    fn test_tohost() {
+        let pass = &[0x93, 0x01, 0x10, 0x00]; // li gp, 1
+        assert_eq!(
+            run_elf(&elf(&[PROLOGUE, pass, REPORT], true), INSTRUCTION_LIMIT),
+            Ok(())
+        );
+
+        let fail = &[0x93, 0x01, 0x50, 0x00]; // li gp, (2 << 1) | 1
+        assert_eq!(
+            run_elf(&elf(&[PROLOGUE, fail, REPORT], true), INSTRUCTION_LIMIT),
+            Err(Failure::Test(2))
+        );
+
+        // Not enough instructions to reach the test
+        assert_eq!(
+            run_elf(&elf(&[PROLOGUE, pass, REPORT], true), 8),
+            Err(Failure::Timeout)
+        );
+    }
+
+    #[test]
+    fn test_exit_syscall() {
+        let pass = &[0x93, 0x01, 0x00, 0x00]; // li gp, 0
+        assert_eq!(
+            run_elf(&elf(&[pass, REPORT], false), INSTRUCTION_LIMIT),
+            Ok(())
+        );
+
+        let fail = &[0x93, 0x01, 0x70, 0x00]; // li gp, (3 << 1) | 1
+        assert_eq!(
+            run_elf(&elf(&[fail, REPORT], false), INSTRUCTION_LIMIT),
+            Err(Failure::Test(3))
+        );
+
+        // No trap handler without `tohost`
+        assert_eq!(
+            run_elf(&elf(&[PROLOGUE], false), INSTRUCTION_LIMIT),
+            Err(Failure::Error(EmbiveError::IllegalInstruction {
+                pc: RAM_OFFSET + 0x20,
+                raw: 0x30200073
+            }))
+        );
+    }
+
+    #[test]
+    fn test_failures() {
+        let halt = &[0x73, 0x00, 0x10, 0x00]; // ebreak
+        assert_eq!(
+            run_elf(&elf(&[halt], true), INSTRUCTION_LIMIT),
+            Err(Failure::Halted)
+        );
+
+        let illegal = &[0xff, 0xff, 0xff, 0xff];
+        assert_eq!(
+            run_elf(&elf(&[illegal], true), INSTRUCTION_LIMIT),
+            Err(Failure::Error(EmbiveError::IllegalInstruction {
+                pc: RAM_OFFSET,
+                raw: 0xffffffff
+            }))
+        );
+
+        let mut ram = [0; 0x1000];
+        assert_eq!(
+            run(&elf(&[halt], true), &mut ram, Config::default(), 1),
+            Err(Failure::Load(LoaderError::RamTooLarge))
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! Conformance matrix, a test corpus run against every extension combination of this build.
+//!
+//! Crate features are selected at compile time, so the matrix runs a single build (with every feature used by
+//! the firmware SKUs, ex.: `--all-features`) and restricts each combination at runtime
+//! ([`Config::disabled_extensions`]): instructions of the extensions left out of a combination are illegal,
+//! as in a build without their feature. Only the instructions are disabled, ex.: without `C`, jumps to
+//! halfword-aligned addresses are still accepted.
+//!
+//! ```
+//! use embive::testrunner::matrix;
+//!
+//! fn validate(corpus: &[(&str, &[u8])]) {
+//!     for report in matrix::run(corpus, |config| config, embive::testrunner::INSTRUCTION_LIMIT) {
+//!         println!("{}: {}/{} passed", report.combination, report.passed(), report.results.len());
+//!         for (name, failure) in report.failures() {
+//!             println!("  {}: {}", name, failure);
+//!         }
+//!     }
+//! }
+//! ```
+
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use std::vec;
+use std::vec::Vec;
+
+use super::{Failure, TestMemory, RAM_SIZE};
+use crate::engine::Config;
+use crate::lint::IsaExtension;
+use crate::memory::Memory;
+
+/// Extensions enabled by crate features (part of a combination or not).
+pub const OPTIONAL_EXTENSIONS: [IsaExtension; 5] = [
+    IsaExtension::M,
+    IsaExtension::A,
+    IsaExtension::C,
+    IsaExtension::V,
+    IsaExtension::Zacas,
+];
+
+/// Extension combination, a subset of the optional extensions enabled in this build.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Combination {
+    /// Optional extensions in the combination (bitmask of [`IsaExtension::bit`]).
+    extensions: u32,
+}
+
+impl Combination {
+    /// Check if an extension is part of the combination (always-enabled extensions are part of every combination).
+    ///
+    /// Arguments:
+    /// - `extension`: Instruction set extension.
+    pub fn contains(&self, extension: IsaExtension) -> bool {
+        match OPTIONAL_EXTENSIONS.contains(&extension) {
+            true => self.extensions & extension.bit() != 0,
+            false => extension.enabled(),
+        }
+    }
+
+    /// Optional extensions in the combination.
+    pub fn extensions(&self) -> impl Iterator<Item = IsaExtension> + '_ {
+        OPTIONAL_EXTENSIONS
+            .into_iter()
+            .filter(|extension| self.contains(*extension))
+    }
+
+    /// Extensions left out of the combination (bitmask of [`IsaExtension::bit`]).
+    pub fn disabled(&self) -> u32 {
+        OPTIONAL_EXTENSIONS
+            .into_iter()
+            .filter(|extension| !self.contains(*extension))
+            .fold(0, |disabled, extension| disabled | extension.bit())
+    }
+
+    /// Restrict an engine configuration to the combination.
+    ///
+    /// Arguments:
+    /// - `config`: Engine configuration.
+    ///
+    /// Returns:
+    /// - `Config`: The updated configuration (extensions disabled by the configuration stay disabled).
+    pub fn apply<M: Memory>(&self, config: Config<M>) -> Config<M> {
+        let disabled = config.disabled_extensions | self.disabled();
+        config.with_disabled_extensions(disabled)
+    }
+}
+
+impl Display for Combination {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "RV32I")?;
+        for extension in self.extensions() {
+            match extension.name().len() {
+                1 => write!(f, "{}", extension)?,
+                _ => write!(f, "_{}", extension)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Every extension combination of this build (`Zacas` requires `A`), starting with the base instruction set.
+pub fn combinations() -> Vec<Combination> {
+    let enabled: Vec<IsaExtension> = OPTIONAL_EXTENSIONS
+        .into_iter()
+        .filter(IsaExtension::enabled)
+        .collect();
+
+    (0..1u32 << enabled.len())
+        .map(|subset| Combination {
+            extensions: enabled
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| subset & (1 << index) != 0)
+                .fold(0, |extensions, (_, extension)| extensions | extension.bit()),
+        })
+        .filter(|combination| {
+            !combination.contains(IsaExtension::Zacas) || combination.contains(IsaExtension::A)
+        })
+        .collect()
+}
+
+/// Corpus results of a combination.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Report<'c> {
+    /// Extension combination.
+    pub combination: Combination,
+    /// Test results (name and result, in corpus order).
+    pub results: Vec<(&'c str, Result<(), Failure>)>,
+}
+
+impl Report<'_> {
+    /// Number of tests passed.
+    pub fn passed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|(_, result)| result.is_ok())
+            .count()
+    }
+
+    /// Failed tests (name and failure).
+    pub fn failures(&self) -> impl Iterator<Item = (&str, Failure)> + '_ {
+        self.results
+            .iter()
+            .filter_map(|(name, result)| result.err().map(|failure| (*name, failure)))
+    }
+}
+
+/// Run a test corpus against every extension combination (check [`super::run`]).
+///
+/// Arguments:
+/// - `corpus`: Test ELFs (name and file).
+/// - `configure`: Engine configuration under test, from the default one (restricted to each combination).
+/// - `limit`: Maximum number of instructions per test (ex.: [`super::INSTRUCTION_LIMIT`]).
+///
+/// Returns:
+/// - `Vec<Report>`: Results per combination (in [`combinations`] order).
+pub fn run<'c, F>(corpus: &[(&'c str, &[u8])], configure: F, limit: u64) -> Vec<Report<'c>>
+where
+    F: for<'a> Fn(Config<TestMemory<'a>>) -> Config<TestMemory<'a>>,
+{
+    combinations()
+        .into_iter()
+        .map(|combination| Report {
+            combination,
+            results: corpus
+                .iter()
+                .map(|(name, elf)| {
+                    let mut ram = vec![0; RAM_SIZE];
+                    let config = combination.apply(configure(Config::default()));
+                    (*name, super::run(elf, &mut ram, config, limit))
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{elf, REPORT};
+    use super::*;
+    use crate::error::EmbiveError;
+    use crate::memory::RAM_OFFSET;
+    use std::string::ToString;
+
+    #[test]
+    fn test_combinations() {
+        let combinations = combinations();
+        let enabled = OPTIONAL_EXTENSIONS
+            .iter()
+            .filter(|extension| extension.enabled())
+            .count();
+        assert!(combinations.len() <= 1 << enabled);
+        assert_eq!(combinations[0].to_string(), "RV32I");
+        assert_eq!(combinations[0].disabled() & IsaExtension::I.bit(), 0);
+        assert!(combinations[0].contains(IsaExtension::I));
+        assert!(!combinations[0].contains(IsaExtension::F));
+
+        for combination in &combinations {
+            for extension in combination.extensions() {
+                assert!(extension.enabled());
+                assert_eq!(combination.disabled() & extension.bit(), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        let pass = elf(&[&[0x93, 0x01, 0x00, 0x00], REPORT], false); // li gp, 0
+        let multiply = elf(
+            &[
+                &[0x93, 0x01, 0x00, 0x00], // li  gp, 0
+                &[0xb3, 0x81, 0x31, 0x02], // mul gp, gp, gp
+                REPORT,
+            ],
+            false,
+        );
+        let corpus: [(&str, &[u8]); 2] = [("pass", &pass), ("mul", &multiply)];
+
+        // Disabled by the configuration in every combination
+        let reports = run(
+            &corpus,
+            |config| config.with_disabled_extensions(IsaExtension::M.bit()),
+            1000,
+        );
+        assert_eq!(reports.len(), combinations().len());
+        for report in &reports {
+            assert_eq!(report.passed(), 1);
+            assert_eq!(
+                report.failures().collect::<Vec<_>>(),
+                [(
+                    "mul",
+                    Failure::Error(EmbiveError::IllegalInstruction {
+                        pc: RAM_OFFSET + 4,
+                        raw: 0x0231_81b3
+                    })
+                )]
+            );
+        }
+
+        // Passes with M only
+        for report in run(&corpus, |config| config, 1000) {
+            let m = report.combination.contains(IsaExtension::M);
+            assert_eq!(report.passed(), if m { 2 } else { 1 });
+        }
+    }
+}
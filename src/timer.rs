@@ -0,0 +1,265 @@
+//! Timer Module
+//!
+//! Guest timer service, backed by the host clock: guests create up to [`TIMERS`] one-shot or periodic timers,
+//! delivered as I/O handle readiness (check [`crate::engine::Config::poll_nr`]) or as interrupts.
+//! The host advances the timers with its clock ([`crate::engine::Engine::advance_timers`]), in any tick unit,
+//! and can sleep until the next deadline ([`crate::engine::Engine::next_timer_deadline`]).
+//!
+//! The syscall is configured in [`crate::engine::Config::timer_nr`], the operation is selected by `a0`:
+//! - [`TIMER_CREATE`]: Create a (disarmed) timer, returns its id (`a1`).
+//!     - `a1`: Delivery, [`TIMER_EVENT`] (wake I/O handle) or [`TIMER_INTERRUPT`] (raise interrupt line).
+//!     - `a2`: I/O handle or interrupt line.
+//! - [`TIMER_ARM`]: Arm (or re-arm) a timer.
+//!     - `a1`: Timer id.
+//!     - `a2`: Delay in ticks, from the last time the host advanced the timers.
+//!     - `a3`: Period in ticks (0 = One-shot).
+//! - [`TIMER_CANCEL`]: Disarm a timer (`a1`: timer id).
+//! - [`TIMER_DELETE`]: Delete a timer (`a1`: timer id).
+//!
+//! Returns `0` in `a0` on success, or a [`crate::syscall::Errno`] error code.
+
+use crate::syscall::Errno;
+
+/// Maximum number of timers (per engine).
+pub const TIMERS: usize = 8;
+
+/// Timer operation: create a timer.
+pub const TIMER_CREATE: i32 = 0;
+/// Timer operation: arm a timer.
+pub const TIMER_ARM: i32 = 1;
+/// Timer operation: disarm a timer.
+pub const TIMER_CANCEL: i32 = 2;
+/// Timer operation: delete a timer.
+pub const TIMER_DELETE: i32 = 3;
+
+/// Timer delivery: wake an I/O handle.
+pub const TIMER_EVENT: i32 = 0;
+/// Timer delivery: raise an interrupt line.
+pub const TIMER_INTERRUPT: i32 = 1;
+
+/// How an expired timer is delivered to the guest.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TimerDelivery {
+    /// Wake an I/O handle.
+    Event(u32),
+    /// Raise an interrupt line.
+    Interrupt(u32),
+}
+
+/// Guest timer.
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Timer {
+    /// Delivery.
+    delivery: TimerDelivery,
+    /// Next expiration (None = Disarmed).
+    deadline: Option<u64>,
+    /// Period in ticks (0 = One-shot).
+    period: u64,
+}
+
+/// Guest timers (check the [module documentation](self)).
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct Timers {
+    /// Timers, indexed by id.
+    timers: [Option<Timer>; TIMERS],
+    /// Last host clock value.
+    now: u64,
+}
+
+impl Timers {
+    /// Last host clock value (ticks).
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    /// Next timer deadline.
+    ///
+    /// Returns:
+    /// - `Some(u64)`: Earliest deadline of the armed timers (ticks).
+    /// - `None`: No timer is armed.
+    pub fn next_deadline(&self) -> Option<u64> {
+        self.timers
+            .iter()
+            .flatten()
+            .filter_map(|timer| timer.deadline)
+            .min()
+    }
+
+    /// Delete all timers (the clock is kept).
+    pub(crate) fn clear(&mut self) {
+        self.timers = [None; TIMERS];
+    }
+
+    /// Advance the clock, expiring timers.
+    /// Periodic timers are re-armed (missed periods are skipped, delivered only once).
+    ///
+    /// Arguments:
+    /// - `now`: Host clock (ticks), monotonic.
+    ///
+    /// Returns:
+    /// - `[Option<TimerDelivery>; TIMERS]`: Deliveries of the expired timers (`None` entries are skipped).
+    pub fn advance(&mut self, now: u64) -> [Option<TimerDelivery>; TIMERS] {
+        self.now = self.now.max(now);
+
+        let mut expired = [None; TIMERS];
+        for (timer, expired) in self.timers.iter_mut().flatten().zip(expired.iter_mut()) {
+            let Some(deadline) = timer.deadline.filter(|deadline| *deadline <= self.now) else {
+                continue;
+            };
+
+            *expired = Some(timer.delivery);
+            timer.deadline = match timer.period {
+                0 => None,
+                period => {
+                    let missed = (self.now - deadline) / period;
+                    Some(deadline.saturating_add((missed + 1).saturating_mul(period)))
+                }
+            };
+        }
+
+        expired
+    }
+
+    /// Handle a timer syscall (check the [module documentation](self)).
+    ///
+    /// Arguments:
+    /// - `args`: Syscall arguments (`a0` to `a3`).
+    ///
+    /// Returns:
+    /// - `Ok(i32)`: Success (timer id for [`TIMER_CREATE`], 0 otherwise).
+    /// - `Err(i32)`: Error code ([`Errno`]).
+    pub(crate) fn syscall(&mut self, args: &[i32]) -> Result<i32, i32> {
+        let [op, a1, a2, a3, ..] = *args else {
+            return Err(Errno::InvalidArgument.code());
+        };
+
+        match op {
+            TIMER_CREATE => {
+                let delivery = match a1 {
+                    TIMER_EVENT => TimerDelivery::Event(a2 as u32),
+                    TIMER_INTERRUPT if cfg!(feature = "interrupt") => {
+                        TimerDelivery::Interrupt(a2 as u32)
+                    }
+                    TIMER_INTERRUPT => return Err(Errno::NotSupported.code()),
+                    _ => return Err(Errno::InvalidArgument.code()),
+                };
+
+                let id = self
+                    .timers
+                    .iter()
+                    .position(Option::is_none)
+                    .ok_or(Errno::QuotaExceeded.code())?;
+                self.timers[id] = Some(Timer {
+                    delivery,
+                    deadline: None,
+                    period: 0,
+                });
+                Ok(id as i32)
+            }
+            TIMER_ARM => {
+                let now = self.now;
+                let timer = self.get(a1)?;
+                timer.deadline = Some(now.saturating_add(a2 as u32 as u64));
+                timer.period = a3 as u32 as u64;
+                Ok(0)
+            }
+            TIMER_CANCEL => {
+                self.get(a1)?.deadline = None;
+                Ok(0)
+            }
+            TIMER_DELETE => {
+                self.get(a1)?;
+                self.timers[a1 as usize] = None;
+                Ok(0)
+            }
+            _ => Err(Errno::NotSupported.code()),
+        }
+    }
+
+    /// Get a created timer.
+    ///
+    /// Arguments:
+    /// - `id`: Timer id.
+    ///
+    /// Returns:
+    /// - `Ok(&mut Timer)`: The timer.
+    /// - `Err(i32)`: Invalid timer id ([`Errno::InvalidArgument`]).
+    fn get(&mut self, id: i32) -> Result<&mut Timer, i32> {
+        usize::try_from(id)
+            .ok()
+            .and_then(|id| self.timers.get_mut(id))
+            .and_then(Option::as_mut)
+            .ok_or(Errno::InvalidArgument.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_shot() {
+        let mut timers = Timers::default();
+        timers.advance(100);
+
+        let id = timers.syscall(&[TIMER_CREATE, TIMER_EVENT, 5, 0]).unwrap();
+        assert_eq!(timers.syscall(&[TIMER_ARM, id, 50, 0]), Ok(0));
+        assert_eq!(timers.next_deadline(), Some(150));
+
+        assert_eq!(timers.advance(149), [None; TIMERS]);
+        assert_eq!(timers.advance(150)[0], Some(TimerDelivery::Event(5)));
+        assert_eq!(timers.next_deadline(), None);
+
+        // Cancel
+        timers.syscall(&[TIMER_ARM, id, 10, 0]).unwrap();
+        assert_eq!(timers.syscall(&[TIMER_CANCEL, id, 0, 0]), Ok(0));
+        assert_eq!(timers.advance(1000), [None; TIMERS]);
+    }
+
+    #[test]
+    fn test_periodic() {
+        let mut timers = Timers::default();
+        let id = timers.syscall(&[TIMER_CREATE, TIMER_EVENT, 1, 0]).unwrap();
+        timers.syscall(&[TIMER_ARM, id, 10, 10]).unwrap();
+
+        assert!(timers.advance(10)[0].is_some());
+        assert_eq!(timers.next_deadline(), Some(20));
+
+        // Missed periods are skipped
+        assert!(timers.advance(45)[0].is_some());
+        assert_eq!(timers.next_deadline(), Some(50));
+
+        // Clock doesn't go back
+        timers.advance(0);
+        assert_eq!(timers.now(), 45);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut timers = Timers::default();
+        for id in 0..TIMERS as i32 {
+            assert_eq!(timers.syscall(&[TIMER_CREATE, TIMER_EVENT, 0, 0]), Ok(id));
+        }
+        assert_eq!(
+            timers.syscall(&[TIMER_CREATE, TIMER_EVENT, 0, 0]),
+            Err(Errno::QuotaExceeded.code())
+        );
+        assert_eq!(timers.syscall(&[TIMER_DELETE, 3, 0, 0]), Ok(0));
+        assert_eq!(
+            timers.syscall(&[TIMER_ARM, 3, 0, 0]),
+            Err(Errno::InvalidArgument.code())
+        );
+        assert_eq!(
+            timers.syscall(&[TIMER_CANCEL, -1, 0, 0]),
+            Err(Errno::InvalidArgument.code())
+        );
+        assert_eq!(
+            timers.syscall(&[TIMER_CREATE, 7, 0, 0]),
+            Err(Errno::InvalidArgument.code())
+        );
+        assert_eq!(
+            timers.syscall(&[9, 0, 0, 0]),
+            Err(Errno::NotSupported.code())
+        );
+    }
+}
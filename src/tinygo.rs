@@ -0,0 +1,151 @@
+//! TinyGo Module
+//!
+//! Compatibility mode for TinyGo guests. TinyGo binaries bring their own runtime (scheduler, garbage collector),
+//! which expects a clock, a way to sleep when every goroutine is blocked, a console and a memory layout
+//! matching its linker script. [`crate::engine::Config::tinygo`] configures the engine for it.
+//!
+//! Guest side (TinyGo target for Embive):
+//! - Architecture: `rv32imac` (enable the `m_extension`, `a_extension` and `c_extension` features),
+//!   code linked at `0x00000000` and data at [`crate::memory::RAM_OFFSET`].
+//! - Runtime glue, over the guest runtime syscalls (check [`crate::runtime`]):
+//!     - `runtime.putchar`: [`crate::runtime::SYS_PRINT`] to [`crate::runtime::STDOUT`].
+//!     - `runtime.ticks`: [`crate::runtime::SYS_TICKS`], in microseconds (`ticksToNanoseconds` multiplies by 1000).
+//!     - `runtime.sleepTicks`: [`SYS_SLEEP`] with the duration in microseconds (`a0`, saturated to `i32::MAX`).
+//!     - `runtime.exit`, `runtime.abort` and `runtime._panic`: [`crate::runtime::SYS_EXIT`] and
+//!       [`crate::runtime::SYS_PANIC`].
+//! - Memory: the garbage collector owns the RAM between the static data and the stack (`_heap_start`
+//!   and `_heap_end` linker symbols), check [`layout`] and [`ram_size`].
+//!
+//! Host side:
+//! - [`crate::engine::Config::tick_fn`] must count microseconds.
+//! - [`SYS_SLEEP`] suspends the guest ([`crate::engine::WaitingFor::Resume`] with the duration). The host waits
+//!   (or runs other guests) and resumes it ([`crate::engine::Engine::resume`], the value is ignored).
+//! - The guest runtime allocator is not used (the heap is owned by the garbage collector).
+//!
+//! The convention is tested with a hand-written guest (`tests/tinygo/glue.s`), not with a TinyGo build.
+//!
+//! ```
+//! use embive::{
+//!     engine::{Config, Engine, WaitingFor},
+//!     memory::SliceMemory,
+//!     runtime::RuntimeHost,
+//!     syscall::Errno,
+//!     tinygo,
+//! };
+//!
+//! struct Console;
+//!
+//! impl RuntimeHost for Console {
+//!     fn print(_stream: i32, data: &[u8]) -> Result<usize, Errno> {
+//!         Ok(data.len()) // ex.: forward to a UART
+//!     }
+//! }
+//!
+//! fn micros() -> u64 {
+//!     0 // ex.: host clock
+//! }
+//!
+//! let code = &[
+//!     0x13, 0x05, 0x80, 0x3e, // li   a0, 1000
+//!     0x93, 0x08, 0x20, 0x3f, // li   a7, 1010 (sleep)
+//!     0x73, 0x00, 0x00, 0x00, // ecall
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//! ];
+//! let mut ram = [0; 4096];
+//! let layout = tinygo::layout(ram.len() as u32, 512, tinygo::DEFAULT_STACK_SIZE);
+//! let mut memory = SliceMemory::new(code, &mut ram);
+//! let config = Config::tinygo::<Console>(layout, micros);
+//! let mut engine = Engine::new(&mut memory, config).unwrap();
+//!
+//! // Sleeping for 1000 microseconds
+//! assert_eq!(engine.run(), Ok(true));
+//! assert_eq!(engine.waiting_for(), Some(WaitingFor::Resume(1000)));
+//!
+//! engine.resume(0);
+//! assert_eq!(engine.run(), Ok(false));
+//! ```
+
+use crate::memory::{RamLayout, RAM_OFFSET};
+
+/// Syscall number: sleep (yield to the host, check [`crate::engine::Config::yield_nr`]).
+pub const SYS_SLEEP: i32 = 1010;
+
+/// Recommended stack size in bytes for the main goroutine.
+pub const DEFAULT_STACK_SIZE: u32 = 4096;
+
+/// Alignment of the stack and heap boundaries in bytes (TinyGo linker script).
+pub const ALIGNMENT: u32 = 16;
+
+/// Align a size up to [`ALIGNMENT`].
+const fn align(size: u32) -> u32 {
+    size.saturating_add(ALIGNMENT - 1) & !(ALIGNMENT - 1)
+}
+
+/// Compute the RAM size needed by a TinyGo guest.
+///
+/// Arguments:
+/// - `static_size`: Static data size in bytes (`.data` and `.bss`, ex.: [`crate::loader::LoadReport::required_ram`]).
+/// - `heap_size`: Minimum garbage collector heap size in bytes.
+/// - `stack_size`: Stack size in bytes (ex.: [`DEFAULT_STACK_SIZE`]).
+///
+/// Returns:
+/// - `u32`: RAM size in bytes (saturated to `u32::MAX`).
+pub const fn ram_size(static_size: u32, heap_size: u32, stack_size: u32) -> u32 {
+    align(static_size)
+        .saturating_add(align(heap_size))
+        .saturating_add(align(stack_size))
+}
+
+/// Compute the RAM layout of a TinyGo guest: static data at the start of RAM, stack at the top,
+/// and the garbage collector heap in between (no guard region).
+///
+/// Arguments:
+/// - `ram_size`: RAM size in bytes.
+/// - `static_size`: Static data size in bytes (`.data` and `.bss`).
+/// - `stack_size`: Stack size in bytes.
+///
+/// Returns:
+/// - `RamLayout`: The RAM layout (empty heap if the RAM is too small, check [`ram_size`]).
+pub const fn layout(ram_size: u32, static_size: u32, stack_size: u32) -> RamLayout {
+    let ram_size = ram_size & !(ALIGNMENT - 1);
+    let heap_start = align(static_size);
+    let stack_size = align(stack_size);
+    let heap_size = ram_size
+        .saturating_sub(heap_start)
+        .saturating_sub(stack_size);
+
+    RamLayout {
+        stack_top: RAM_OFFSET.wrapping_add(ram_size),
+        stack_size,
+        guard_start: RAM_OFFSET.wrapping_add(ram_size).wrapping_sub(stack_size),
+        guard_size: 0,
+        heap_start: RAM_OFFSET.wrapping_add(heap_start),
+        heap_size,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ram_size() {
+        assert_eq!(ram_size(100, 1000, DEFAULT_STACK_SIZE), 112 + 1008 + 4096);
+        assert_eq!(ram_size(0, 0, 0), 0);
+        assert_eq!(ram_size(u32::MAX, 1, 1), u32::MAX);
+    }
+
+    #[test]
+    fn test_layout() {
+        let layout = layout(ram_size(100, 1000, 4096), 100, 4096);
+        assert_eq!(layout.heap_start, RAM_OFFSET + 112);
+        assert_eq!(layout.heap_size, 1008);
+        assert_eq!(layout.stack_top, RAM_OFFSET + 112 + 1008 + 4096);
+        assert_eq!(layout.stack_size, 4096);
+        assert_eq!(layout.guard_start, layout.heap_start + layout.heap_size);
+
+        // Too small
+        let layout = super::layout(1024, 512, 1024);
+        assert_eq!(layout.heap_size, 0);
+    }
+}
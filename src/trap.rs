@@ -0,0 +1,305 @@
+//! Trap Module
+//!
+//! Guest trap delegation: the exceptions selected in [`crate::engine::Config::guest_traps`] are vectored to
+//! a guest trap handler, like machine-mode traps on real hardware, instead of stopping the engine with an error.
+//! Guests can implement their own exception handling (ex.: panic handlers, instruction emulation or system calls
+//! handled inside the guest).
+//!
+//! When an exception is trapped:
+//! - `mepc` is set to the address of the instruction that trapped.
+//! - `mcause` is set to the exception code ([`Exception::code`]).
+//! - `mtval` is set to the illegal instruction (raw), the misaligned address, or 0 (`ecall`).
+//! - The program counter jumps to `mtvec` (direct mode, the mode bits are ignored).
+//!
+//! `mret` returns to `mepc` (the handler skips the trapped instruction by adding its size to `mepc`).
+//! Exceptions are reported to the host as usual (errors, syscalls, or misaligned accesses executed normally)
+//! while no handler is installed (`mtvec` = 0), and inside the handler, before `mret` (double fault).
+//!
+//! With the `interrupt` feature, host-raised interrupts can be delivered to the same handler
+//! ([`crate::engine::Config::guest_interrupts`]): `mcause` is set to [`INTERRUPT_CAUSE`] plus the local interrupt
+//! code ([`LOCAL_INTERRUPT_BASE`] + line), `mepc` to the next instruction to execute and `mtval` to 0.
+//! Interrupts are kept pending while no handler is installed or inside the handler, `mret` completes them.
+//!
+//! The trap registers ([`crate::csr::MTVEC`], [`crate::csr::MEPC`], [`crate::csr::MCAUSE`], [`crate::csr::MTVAL`]
+//! and [`crate::csr::MSCRATCH`]) are handled by the engine when any exception
+//! or interrupt is delivered to the guest. They are cleared
+//! by [`crate::engine::Engine::reset`] and not captured by snapshots.
+//!
+//! ```
+//! use embive::{
+//!     engine::{Config, Engine},
+//!     memory::SliceMemory,
+//!     trap::Exception,
+//! };
+//!
+//! let code = &[
+//!     0x93, 0x02, 0x40, 0x01, // li    t0, 0x14 (handler)
+//!     0x73, 0x90, 0x52, 0x30, // csrw  mtvec, t0
+//!     0xff, 0xff, 0xff, 0xff, // (illegal)
+//!     0x93, 0x05, 0x10, 0x00, // li    a1, 1
+//!     0x73, 0x00, 0x10, 0x00, // ebreak
+//!     // Handler
+//!     0x73, 0x25, 0x20, 0x34, // csrr  a0, mcause
+//!     0x73, 0x23, 0x10, 0x34, // csrr  t1, mepc
+//!     0x13, 0x03, 0x43, 0x00, // addi  t1, t1, 4
+//!     0x73, 0x10, 0x13, 0x34, // csrw  mepc, t1
+//!     0x73, 0x00, 0x20, 0x30, // mret
+//! ];
+//! let mut memory = SliceMemory::new(code, &mut []);
+//! let config = Config::default().with_guest_traps(Exception::IllegalInstruction.bit());
+//! let mut engine = Engine::new(&mut memory, config).unwrap();
+//!
+//! assert_eq!(engine.run(), Ok(false));
+//! assert_eq!(engine.registers.get(10), Ok(Exception::IllegalInstruction.code() as i32));
+//! assert_eq!(engine.registers.get(11), Ok(1));
+//! assert_eq!(engine.trap.mtval, 0xffffffff);
+//! ```
+
+use crate::csr::{MCAUSE, MEPC, MSCRATCH, MTVAL, MTVEC};
+
+/// Exceptions that can be vectored to the guest trap handler (machine-mode exception codes).
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[repr(u32)]
+pub enum Exception {
+    /// Illegal or not implemented instruction (`mtval`: instruction, raw).
+    IllegalInstruction = 2,
+    /// Load address not aligned to the access width (`mtval`: load address).
+    /// Without delegation, misaligned loads are executed.
+    LoadMisaligned = 4,
+    /// Store or atomic address not aligned to the access width (`mtval`: store address).
+    /// Without delegation, misaligned stores are executed.
+    StoreMisaligned = 6,
+    /// Environment call, `ecall` (`mtval`: 0). The host syscalls aren't called while delegated.
+    EnvironmentCall = 11,
+}
+
+impl Exception {
+    /// Get the exception code (`mcause`).
+    pub const fn code(self) -> u32 {
+        self as u32
+    }
+
+    /// Get the exception bit, for [`crate::engine::Config::guest_traps`] (bit `n` = exception code `n`).
+    pub const fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+/// Interrupt bit of `mcause` (the rest of the register is the interrupt code).
+pub const INTERRUPT_CAUSE: u32 = 1 << 31;
+
+/// Interrupt code of interrupt line 0 (local interrupts, platform-defined codes).
+pub const LOCAL_INTERRUPT_BASE: u32 = 16;
+
+/// Misaligned access exceptions (check [`Exception::LoadMisaligned`] and [`Exception::StoreMisaligned`]).
+pub(crate) const MISALIGNED: u32 =
+    Exception::LoadMisaligned.bit() | Exception::StoreMisaligned.bit();
+
+/// Guest trap registers (check the [module documentation](self)).
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct TrapState {
+    /// Trap handler address (0 = No handler installed).
+    pub mtvec: u32,
+    /// Address of the instruction that trapped.
+    pub mepc: u32,
+    /// Exception code of the last trap.
+    pub mcause: u32,
+    /// Exception value of the last trap.
+    pub mtval: u32,
+    /// Scratch register, for the trap handler.
+    pub mscratch: u32,
+    /// Inside the trap handler (trap taken, `mret` not executed yet).
+    pub(crate) handling: bool,
+}
+
+impl TrapState {
+    /// Check if the guest is inside the trap handler (trap taken, `mret` not executed yet).
+    pub fn in_handler(&self) -> bool {
+        self.handling
+    }
+
+    /// Check if a register is a trap register.
+    ///
+    /// Arguments:
+    /// - `csr`: Register address (12 bits).
+    pub(crate) const fn is_trap_csr(csr: u16) -> bool {
+        matches!(csr, MTVEC | MEPC | MCAUSE | MTVAL | MSCRATCH)
+    }
+
+    /// Read a trap register.
+    ///
+    /// Arguments:
+    /// - `csr`: Register address (12 bits).
+    ///
+    /// Returns:
+    /// - `Some(u32)`: Register value.
+    /// - `None`: Not a trap register.
+    pub(crate) fn read_csr(&self, csr: u16) -> Option<u32> {
+        match csr {
+            MTVEC => Some(self.mtvec),
+            MEPC => Some(self.mepc),
+            MCAUSE => Some(self.mcause),
+            MTVAL => Some(self.mtval),
+            MSCRATCH => Some(self.mscratch),
+            _ => None,
+        }
+    }
+
+    /// Write a trap register (`mtvec` mode bits and `mepc` bit 0 are hardwired to zero).
+    ///
+    /// Arguments:
+    /// - `csr`: Register address (12 bits).
+    /// - `value`: New value.
+    ///
+    /// Returns:
+    /// - `true`: Written.
+    /// - `false`: Not a trap register.
+    pub(crate) fn write_csr(&mut self, csr: u16, value: u32) -> bool {
+        match csr {
+            MTVEC => self.mtvec = value & !0b11,
+            MEPC => self.mepc = value & !0b1,
+            MCAUSE => self.mcause = value,
+            MTVAL => self.mtval = value,
+            MSCRATCH => self.mscratch = value,
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Check if a trap can be taken: a handler is installed and the guest isn't inside it.
+    pub(crate) fn accepts(&self) -> bool {
+        self.mtvec != 0 && !self.handling
+    }
+
+    /// Take a trap, if a handler is installed and the guest isn't inside it.
+    ///
+    /// Arguments:
+    /// - `cause`: Trap cause (exception code, or interrupt).
+    /// - `epc`: Address of the instruction that trapped (or the next one, for interrupts).
+    /// - `tval`: Exception value.
+    ///
+    /// Returns:
+    /// - `Some(u32)`: Trap taken, handler address.
+    /// - `None`: Not taken (report the exception to the host).
+    pub(crate) fn take(&mut self, cause: u32, epc: u32, tval: u32) -> Option<u32> {
+        if !self.accepts() {
+            return None;
+        }
+
+        self.mepc = epc;
+        self.mcause = cause;
+        self.mtval = tval;
+        self.handling = true;
+        Some(self.mtvec)
+    }
+
+    /// Return from the trap handler (`mret`).
+    ///
+    /// Returns:
+    /// - `u32`: Return address (`mepc`).
+    pub(crate) fn mret(&mut self) -> u32 {
+        self.handling = false;
+        self.mepc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Config, Engine};
+    use crate::error::EmbiveError;
+    use crate::memory::{SliceMemory, RAM_OFFSET};
+
+    #[test]
+    fn test_registers() {
+        let mut trap = TrapState::default();
+        assert!(TrapState::is_trap_csr(MSCRATCH));
+        assert!(!TrapState::is_trap_csr(0x300));
+
+        assert!(trap.write_csr(MTVEC, 0x103));
+        assert!(trap.write_csr(MEPC, 0x21));
+        assert!(!trap.write_csr(0x300, 1));
+        assert_eq!(trap.read_csr(MTVEC), Some(0x100));
+        assert_eq!(trap.read_csr(MEPC), Some(0x20));
+        assert_eq!(trap.read_csr(0x300), None);
+
+        // Double fault
+        assert_eq!(
+            trap.take(Exception::EnvironmentCall.code(), 0x8, 0),
+            Some(0x100)
+        );
+        assert!(trap.in_handler());
+        assert_eq!(
+            trap.take(Exception::IllegalInstruction.code(), 0x100, 0),
+            None
+        );
+        assert_eq!(trap.mcause, 11);
+        assert_eq!(trap.mret(), 0x8);
+        assert!(!trap.in_handler());
+
+        // No handler
+        trap.mtvec = 0;
+        assert_eq!(trap.take(Exception::EnvironmentCall.code(), 0x8, 0), None);
+    }
+
+    #[test]
+    fn test_ecall_and_misaligned() {
+        let code = &[
+            0x93, 0x02, 0x00, 0x02, // li    t0, 0x20 (handler)
+            0x73, 0x90, 0x52, 0x30, // csrw  mtvec, t0
+            0x37, 0x05, 0x00, 0x80, // lui   a0, 0x80000
+            0x73, 0x00, 0x00, 0x00, // ecall
+            0x83, 0x25, 0x25, 0x00, // lw    a1, 2(a0)
+            0x03, 0x16, 0x25, 0x00, // lh    a2, 2(a0)
+            0x73, 0x00, 0x10, 0x00, // ebreak
+            0x00, 0x00, 0x00, 0x00, // (padding)
+            // Handler, counts traps in a3
+            0x93, 0x86, 0x16, 0x00, // addi  a3, a3, 1
+            0x73, 0x23, 0x10, 0x34, // csrr  t1, mepc
+            0x13, 0x03, 0x43, 0x00, // addi  t1, t1, 4
+            0x73, 0x10, 0x13, 0x34, // csrw  mepc, t1
+            0x73, 0x00, 0x20, 0x30, // mret
+        ];
+        let mut ram = [0x1; 8];
+        let mut memory = SliceMemory::new(code, &mut ram);
+        let config = Config::default()
+            .with_guest_traps(Exception::EnvironmentCall.bit() | Exception::LoadMisaligned.bit());
+        let mut engine = Engine::new(&mut memory, config).unwrap();
+
+        assert_eq!(engine.run(), Ok(false));
+        assert_eq!(engine.registers.get(13), Ok(2));
+        assert_eq!(engine.registers.get(11), Ok(0));
+        assert_eq!(engine.registers.get(12), Ok(0x0101));
+        assert_eq!(engine.trap.mcause, Exception::LoadMisaligned.code());
+        assert_eq!(engine.trap.mtval, RAM_OFFSET + 2);
+        assert_eq!(engine.trap.mepc, 0x14);
+    }
+
+    #[test]
+    fn test_not_delegated() {
+        let code = &[
+            0x73, 0x25, 0x50, 0x30, // csrr  a0, mtvec
+            0x73, 0x00, 0x20, 0x30, // mret
+        ];
+        let mut memory = SliceMemory::new(code, &mut []);
+        let mut engine = Engine::new(&mut memory, Config::default()).unwrap();
+
+        // Trap registers and `mret` are illegal
+        assert_eq!(
+            engine.run(),
+            Err(EmbiveError::IllegalInstruction {
+                pc: 0x0,
+                raw: 0x30502573
+            })
+        );
+
+        // No handler installed
+        engine.config = Config::default().with_guest_traps(Exception::IllegalInstruction.bit());
+        engine.reset();
+        assert_eq!(engine.step(), Ok(true));
+        assert_eq!(engine.registers.get(10), Ok(0));
+        assert_eq!(engine.step(), Ok(true));
+        assert_eq!(engine.program_counter, 0x0);
+    }
+}
@@ -0,0 +1,64 @@
+# Runtime glue convention of `embive::tinygo` (rv32imac), hand-written: putchar, ticks, sleepTicks and exit.
+# Not generated by TinyGo, it checks the documented convention, not TinyGo compatibility.
+# Assemble: llvm-mc -triple=riscv32 -mattr=+m,+a,+c,-relax -filetype=obj glue.s -o glue.o
+#           llvm-objcopy -O binary --only-section=.text glue.o glue.bin
+    .text
+    .globl _start
+_start:
+    # Entry convention: runtime version in a0
+    li      t0, 1
+    bne     a0, t0, abort
+
+    # Stack at the top of RAM (4 KiB, `_stack_top` linker symbol)
+    lui     sp, 0x80001
+
+    # Scheduler state (.bss): goroutine count, updated atomically
+    lui     s0, 0x80000
+    li      t1, 1
+    amoadd.w zero, t1, (s0)
+
+    # println("hello"): runtime.putchar for each byte
+    la      s1, message
+    li      s2, 6
+putchar:
+    li      a0, 1           # STDOUT
+    mv      a1, s1
+    li      a2, 1
+    li      a7, 1001        # SYS_PRINT
+    ecall
+    bnez    a0, abort
+    addi    s1, s1, 1
+    addi    s2, s2, -1
+    bnez    s2, putchar
+
+    # runtime.ticks()
+    li      a7, 1006        # SYS_TICKS
+    ecall
+    mv      s3, a1
+
+    # runtime.sleepTicks(1000): yield to the host
+    li      a0, 1000
+    li      a7, 1010        # SYS_SLEEP
+    ecall
+
+    # runtime.ticks(), ticksToNanoseconds(elapsed)
+    li      a7, 1006        # SYS_TICKS
+    ecall
+    sub     a1, a1, s3
+    li      t0, 1000
+    mul     a0, a1, t0
+
+    # Exit with the elapsed nanoseconds
+    li      a7, 1005        # SYS_EXIT
+    ecall
+
+abort:
+    # runtime._panic: no message, line 1
+    li      a1, 0
+    li      a3, 0
+    li      a4, 1
+    li      a7, 1002        # SYS_PANIC
+    ecall
+
+message:
+    .ascii  "hello\n"
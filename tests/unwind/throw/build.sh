@@ -0,0 +1,13 @@
+#!/bin/sh
+# Build the throw/catch guest into `../throw.elf` (stripped).
+# Requires a nightly toolchain with `rust-src` (core and alloc are rebuilt with `panic=unwind`).
+# The `unwinding` crate is taken from the `rust-src` vendor directory, so no network is needed.
+set -e
+cd "$(dirname "$0")"
+
+VENDOR="$(rustc +nightly --print sysroot)/lib/rustlib/src/rust/library/vendor"
+cargo +nightly build --release \
+    --config "source.crates-io.replace-with = 'rust-src'" \
+    --config "source.rust-src.directory = '$VENDOR'"
+
+${OBJCOPY:-llvm-objcopy} --strip-all target/riscv32im-unknown-none-elf/release/throw ../throw.elf
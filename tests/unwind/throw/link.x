@@ -0,0 +1,31 @@
+/* Embive layout: code at 0x00000000, RAM at 0x80000000 (loaded in place), stack at the end of .bss */
+ENTRY(_start)
+
+MEMORY
+{
+    CODE (rx) : ORIGIN = 0x00000000, LENGTH = 128K
+    RAM (rw)  : ORIGIN = 0x80000000, LENGTH = 128K
+}
+
+SECTIONS
+{
+    .text : {
+        __executable_start = .;
+        KEEP(*(.text._start));
+        *(.text .text.*);
+        __etext = .;
+    } > CODE
+
+    .rodata : { *(.rodata .rodata.* .srodata .srodata.*) } > CODE
+    .eh_frame_hdr : { __GNU_EH_FRAME_HDR = .; *(.eh_frame_hdr) } > CODE
+    .eh_frame : { KEEP(*(.eh_frame)) } > CODE
+    .gcc_except_table : { *(.gcc_except_table .gcc_except_table.*) } > CODE
+
+    .data : { *(.data .data.* .sdata .sdata.*) } > RAM
+    .bss : {
+        *(.bss .bss.* .sbss .sbss.* COMMON);
+        . = ALIGN(16);
+        . += 64K;
+        __stack_top = .;
+    } > RAM
+}
@@ -0,0 +1,92 @@
+//! Throw/catch guest: panics unwind through frames with destructors and are caught
+//! (`panic=unwind`, `unwinding` crate). Halts with `a0` = drops * 1000 + caught * 100 + sum.
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::UnsafeCell;
+use core::hint::black_box;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+core::arch::global_asm!(
+    ".section .text._start",
+    ".global _start",
+    "_start:",
+    "    la sp, __stack_top",
+    "    call main",
+    "    ebreak",
+);
+
+/// Bump allocator (the guest never frees much).
+struct Bump(UnsafeCell<([u8; 4096], usize)>);
+
+unsafe impl Sync for Bump {}
+
+unsafe impl GlobalAlloc for Bump {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let (heap, next) = unsafe { &mut *self.0.get() };
+        let start = (*next + layout.align() - 1) & !(layout.align() - 1);
+        if start + layout.size() > heap.len() {
+            return core::ptr::null_mut();
+        }
+
+        *next = start + layout.size();
+        heap[start..].as_mut_ptr()
+    }
+
+    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {}
+}
+
+#[global_allocator]
+static HEAP: Bump = Bump(UnsafeCell::new(([0; 4096], 0)));
+
+/// Destructors run while unwinding.
+static DROPS: AtomicU32 = AtomicU32::new(0);
+
+struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        DROPS.store(DROPS.load(Ordering::Relaxed) + 1, Ordering::Relaxed);
+    }
+}
+
+#[panic_handler]
+fn panic(_info: &core::panic::PanicInfo) -> ! {
+    unwinding::panic::begin_panic(Box::new(()));
+    // Not caught
+    loop {}
+}
+
+#[inline(never)]
+fn may_throw(n: u32) -> u32 {
+    let _guard = Guard;
+    if n > 2 {
+        panic!("too big");
+    }
+
+    n * 10
+}
+
+#[inline(never)]
+fn nested(n: u32) -> u32 {
+    let _guard = Guard;
+    may_throw(black_box(n)) + 1
+}
+
+#[no_mangle]
+extern "C" fn main() -> u32 {
+    let mut sum = 0;
+    let mut caught = 0;
+    for n in 0..5 {
+        match unwinding::panic::catch_unwind(|| nested(n)) {
+            Ok(value) => sum += value,
+            Err(_) => caught += 1,
+        }
+    }
+
+    DROPS.load(Ordering::Relaxed) * 1000 + caught * 100 + sum
+}
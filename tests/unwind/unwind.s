@@ -0,0 +1,99 @@
+# Unwind table fixture: an executable ELF file written by hand, with two functions described by
+# `.eh_frame` FDEs and a `.eh_frame_hdr` binary search table (same layout as `ld --eh-frame-hdr`).
+# Loaded at 0x00000000 in a single segment (headers included, same as a flat `objcopy -O binary` image).
+# Data directives only, instructions are hand-encoded (the RISC-V assembler keeps label differences as
+# relocations, so assemble for the host, where they are resolved):
+# Assemble: llvm-mc -filetype=obj unwind.s -o unwind.o
+#           llvm-objcopy -O binary --only-section=.data unwind.o unwind.elf
+    .data
+elf:
+    # ELF header: 32-bit, little endian, RISC-V executable
+    .byte   0x7f, 'E', 'L', 'F', 1, 1, 1, 0
+    .zero   8
+    .short  2                       # e_type: executable
+    .short  0xf3                    # e_machine: RISC-V
+    .long   1                       # e_version
+    .long   _start - elf            # e_entry
+    .long   phdrs - elf             # e_phoff
+    .long   0                       # e_shoff
+    .long   0                       # e_flags
+    .short  52                      # e_ehsize
+    .short  32                      # e_phentsize
+    .short  2                       # e_phnum
+    .short  40                      # e_shentsize
+    .short  0                       # e_shnum
+    .short  0                       # e_shstrndx
+
+phdrs:
+    # PT_LOAD: whole file (R + X)
+    .long   1, 0, 0, 0, end - elf, end - elf, 5, 4
+    # PT_GNU_EH_FRAME: .eh_frame_hdr (R)
+    .long   0x6474e550, eh_frame_hdr - elf, eh_frame_hdr - elf, eh_frame_hdr - elf
+    .long   eh_frame_hdr_end - eh_frame_hdr, eh_frame_hdr_end - eh_frame_hdr, 4, 4
+
+# Code: a0 = f(0) + 1
+_start:
+    .byte   0x13, 0x05, 0x00, 0x00  # li   a0, 0
+    .byte   0xef, 0x00, 0xc0, 0x00  # jal  ra, f
+    .byte   0x13, 0x05, 0x15, 0x00  # addi a0, a0, 1
+    .byte   0x73, 0x00, 0x10, 0x00  # ebreak
+_start_end:
+
+f:
+    .byte   0x13, 0x05, 0x15, 0x00  # addi a0, a0, 1
+    .byte   0x67, 0x80, 0x00, 0x00  # ret
+f_end:
+
+# .eh_frame_hdr
+    .p2align 2
+eh_frame_hdr:
+    .byte   1                       # Version
+    .byte   0x1b                    # eh_frame_ptr encoding: pcrel, sdata4
+    .byte   0x03                    # fde_count encoding: udata4
+    .byte   0x3b                    # Table encoding: datarel, sdata4
+    .long   eh_frame - .            # eh_frame_ptr
+    .long   2                       # fde_count
+    # Binary search table (initial location, FDE address), sorted by initial location
+    .long   _start - eh_frame_hdr, fde_start - eh_frame_hdr
+    .long   f - eh_frame_hdr, fde_f - eh_frame_hdr
+eh_frame_hdr_end:
+
+# .eh_frame
+eh_frame:
+cie:
+    .long   cie_end - cie_id        # Length
+cie_id:
+    .long   0                       # CIE id
+    .byte   1                       # Version
+    .asciz  "zR"                    # Augmentation
+    .uleb128 1                      # Code alignment
+    .sleb128 -4                     # Data alignment
+    .uleb128 1                      # Return address register (ra)
+    .uleb128 1                      # Augmentation data length
+    .byte   0x1b                    # FDE pointer encoding: pcrel, sdata4
+    .byte   0x0c, 2, 0              # DW_CFA_def_cfa: sp + 0
+    .p2align 2, 0                   # DW_CFA_nop
+cie_end:
+
+fde_start:
+    .long   fde_start_end - fde_start_cie
+fde_start_cie:
+    .long   fde_start_cie - cie     # CIE pointer
+    .long   _start - .              # Initial location
+    .long   _start_end - _start     # Address range
+    .uleb128 0                      # Augmentation data length
+    .p2align 2, 0
+fde_start_end:
+
+fde_f:
+    .long   fde_f_end - fde_f_cie
+fde_f_cie:
+    .long   fde_f_cie - cie         # CIE pointer
+    .long   f - .                   # Initial location
+    .long   f_end - f               # Address range
+    .uleb128 0                      # Augmentation data length
+    .p2align 2, 0
+fde_f_end:
+
+    .long   0                       # Terminator
+end: